@@ -1,17 +1,21 @@
 //! BOM Upload Handler
 //! 
-//! Handles file uploads for Bill of Materials processing.
+//! Handles file uploads for Bill of Materials processing. Column mappings
+//! are saved per client, under the tenant the request authenticated as.
 
 use axum::{
-    extract::{Multipart, State},
+    extract::{Extension, Multipart, Query, State},
     http::StatusCode,
     response::Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::AppState;
-use elementa_utils::bom::{BomParser, SupplierExtractor, BomValidator};
+use crate::{middleware::AuthenticatedClient, AppState};
+use elementa_database::ColumnMappingRepository;
+use elementa_models::{ColumnMapping, SavedColumnMapping};
+use elementa_utils::bom::dedup::normalize_supplier_name;
+use elementa_utils::bom::{BomParser, DetectedColumn, SheetSelection, SupplierExtractor, SupplierMatchReview, BomValidator};
 
 /// BOM upload response
 #[derive(Debug, Serialize)]
@@ -20,7 +24,11 @@ pub struct BomUploadResponse {
     pub filename: String,
     pub format: String,
     pub total_rows: usize,
+    /// Column each field was read from, with the detection confidence
+    pub columns: Vec<DetectedColumn>,
     pub suppliers: BomSupplierSummary,
+    /// Supplier names close to another but not merged
+    pub supplier_review: Vec<SupplierMatchReview>,
     pub validation: BomValidationSummary,
     pub warnings: Vec<String>,
 }
@@ -32,6 +40,8 @@ pub struct BomSupplierSummary {
     pub complete: usize,
     pub incomplete: usize,
     pub duplicates_merged: usize,
+    /// Suppliers matched to ones already on record
+    pub existing: usize,
 }
 
 /// Validation summary
@@ -42,11 +52,27 @@ pub struct BomValidationSummary {
     pub warnings: usize,
 }
 
+/// Which sheets of an Excel BOM to read
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    /// Comma-separated sheet names; every sheet when absent
+    pub sheets: Option<String>,
+    /// Sheet whose rows are the BOM's components
+    pub primary_sheet: Option<String>,
+    /// Saved column mapping to read fields with
+    pub mapping: Option<String>,
+    /// Match extracted suppliers against the suppliers on record
+    #[serde(default)]
+    pub match_existing: bool,
+}
+
 /// Upload and process BOM file
 /// 
-/// POST /api/v1/bom/upload
+/// POST /api/v1/bom/upload?sheets=BOM,Suppliers&primary_sheet=BOM&mapping=acme
 pub async fn upload_bom(
     State(state): State<AppState>,
+    Extension(client): Extension<AuthenticatedClient>,
+    Query(query): Query<UploadQuery>,
     mut multipart: Multipart,
 ) -> Result<Json<BomUploadResponse>, (StatusCode, String)> {
     // Get file from multipart
@@ -62,8 +88,27 @@ pub async fn upload_bom(
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Failed to read file data: {}", e)))?;
     
     // Parse BOM
-    let parser = BomParser::new();
-    let parsed_bom = parser.parse_bytes(&filename, &data, None)
+    let mapping = match &query.mapping {
+        Some(name) => Some(
+            ColumnMappingRepository::new(state.platform()?.postgres_pools.primary().clone())
+                .get(&client.tenant, name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or((StatusCode::NOT_FOUND, format!("Column mapping '{}' not found", name)))?
+                .mapping,
+        ),
+        None => None,
+    };
+    
+    let mut sheets = SheetSelection::sheets(
+        query.sheets.iter()
+            .flat_map(|s| s.split(','))
+            .map(str::trim)
+            .filter(|s| !s.is_empty()),
+    );
+    sheets.primary = query.primary_sheet;
+    let parser = BomParser::new().with_sheets(sheets);
+    let parsed_bom = parser.parse_bytes(&filename, &data, None, mapping.as_ref())
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Failed to parse BOM: {}", e)))?;
     
     // Validate
@@ -71,7 +116,22 @@ pub async fn upload_bom(
     let validation = validator.validate(&parsed_bom);
     
     // Extract suppliers
-    let extractor = SupplierExtractor::new();
+    let mut extractor = SupplierExtractor::new();
+    if query.match_existing {
+        // Only suppliers on record with names like the BOM's can match
+        let mut names: Vec<String> = parsed_bom.rows.iter()
+            .filter_map(|row| row.supplier_name.as_deref())
+            .map(normalize_supplier_name)
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+        names.dedup();
+        let known = state.stores.suppliers
+            .find_similar_names(&names)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        extractor = extractor.with_known_suppliers(&known);
+    }
     let extraction = extractor.extract(&parsed_bom);
     
     // Combine warnings
@@ -82,6 +142,7 @@ pub async fn upload_bom(
         elementa_utils::bom::BomFormat::Csv => "CSV",
         elementa_utils::bom::BomFormat::Excel => "Excel",
         elementa_utils::bom::BomFormat::Xml => "XML",
+        elementa_utils::bom::BomFormat::Ipc2581 => "IPC-2581",
     };
     
     Ok(Json(BomUploadResponse {
@@ -89,12 +150,15 @@ pub async fn upload_bom(
         filename,
         format: format.to_string(),
         total_rows: parsed_bom.total_rows,
+        columns: parsed_bom.column_mapping.clone(),
         suppliers: BomSupplierSummary {
             total: extraction.suppliers.len(),
             complete: extraction.complete_count,
             incomplete: extraction.incomplete_count,
             duplicates_merged: extraction.duplicate_count,
+            existing: extraction.suppliers.iter().filter(|s| s.existing_supplier.is_some()).count(),
         },
+        supplier_review: extraction.review.clone(),
         validation: BomValidationSummary {
             is_valid: validation.is_valid,
             errors: validation.error_count,
@@ -104,24 +168,54 @@ pub async fn upload_bom(
     }))
 }
 
-/// Get extracted suppliers from a previous upload
+/// List saved column mappings
 /// 
-/// GET /api/v1/bom/{upload_id}/suppliers
-#[derive(Debug, Serialize)]
-pub struct ExtractedSupplierResponse {
-    pub id: Uuid,
-    pub name: String,
-    pub email: Option<String>,
-    pub contact_person: Option<String>,
-    pub component_count: usize,
-    pub is_complete: bool,
-    pub missing_fields: Vec<String>,
+/// GET /api/v1/bom/mappings
+pub async fn list_column_mappings(
+    State(state): State<AppState>,
+    Extension(client): Extension<AuthenticatedClient>,
+) -> Result<Json<Vec<SavedColumnMapping>>, (StatusCode, String)> {
+    ColumnMappingRepository::new(state.platform()?.postgres_pools.primary().clone())
+        .list(&client.tenant)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
-pub async fn get_bom_suppliers(
-    State(_state): State<AppState>,
-    axum::extract::Path(upload_id): axum::extract::Path<Uuid>,
-) -> Result<Json<Vec<ExtractedSupplierResponse>>, (StatusCode, String)> {
-    // TODO: Retrieve from storage (for now, return not found)
-    Err((StatusCode::NOT_FOUND, format!("BOM upload {} not found", upload_id)))
+/// Save a column mapping for reuse, replacing one with the same name
+/// 
+/// PUT /api/v1/bom/mappings/{name}
+pub async fn save_column_mapping(
+    State(state): State<AppState>,
+    Extension(client): Extension<AuthenticatedClient>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    Json(mapping): Json<ColumnMapping>,
+) -> Result<Json<SavedColumnMapping>, (StatusCode, String)> {
+    if mapping.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Column mapping maps no fields".to_string()));
+    }
+    ColumnMappingRepository::new(state.platform()?.postgres_pools.primary().clone())
+        .save(&client.tenant, &name, &mapping)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Delete a saved column mapping
+/// 
+/// DELETE /api/v1/bom/mappings/{name}
+pub async fn delete_column_mapping(
+    State(state): State<AppState>,
+    Extension(client): Extension<AuthenticatedClient>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let deleted = ColumnMappingRepository::new(state.platform()?.postgres_pools.primary().clone())
+        .delete(&client.tenant, &name)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, format!("Column mapping '{}' not found", name)))
+    }
 }
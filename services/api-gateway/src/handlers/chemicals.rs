@@ -0,0 +1,24 @@
+//! Chemical Handlers
+//!
+//! Chemical substance lookups by CAS number, served through the Redis cache.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+
+use crate::AppState;
+use elementa_models::ChemicalSubstance;
+
+/// Get a chemical substance by CAS number
+pub async fn get_chemical(
+    State(state): State<AppState>,
+    Path(cas_number): Path<String>,
+) -> Result<Json<ChemicalSubstance>, (StatusCode, String)> {
+    let chemical = state.platform()?.chemicals.get(cas_number.trim()).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Chemical not found".to_string()))?;
+
+    Ok(Json(chemical))
+}
@@ -1,5 +1,4 @@
 use axum::{extract::State, response::Json};
-use elementa_database::{postgres_health_check, mongo_health_check, redis_health_check};
 use serde_json::{json, Value};
 
 use crate::AppState;
@@ -13,27 +12,30 @@ pub async fn detailed_health_check(State(state): State<AppState>) -> Json<Value>
         "checks": {}
     });
 
-    // Check PostgreSQL
-    let postgres_status = match postgres_health_check(&state.postgres_pool).await {
-        Ok(_) => json!({"status": "healthy", "message": "Connected"}),
-        Err(e) => json!({"status": "unhealthy", "message": e.to_string()}),
+    // Check every pool now rather than reporting the monitor's last result,
+    // which may be up to an interval old. The PostgreSQL read replica is
+    // included when configured; reads fall back to the primary while it is down.
+    // The SQLite backend has no pools to monitor.
+    let pools = match &state.platform {
+        Some(platform) => platform.health.check_all().await,
+        None => Vec::new(),
     };
-    health_status["checks"]["postgres"] = postgres_status;
-
-    // Check MongoDB
-    let mongo_status = match mongo_health_check(&state.mongo_client).await {
-        Ok(_) => json!({"status": "healthy", "message": "Connected"}),
-        Err(e) => json!({"status": "unhealthy", "message": e.to_string()}),
-    };
-    health_status["checks"]["mongodb"] = mongo_status;
-
-    // Check Redis
-    let mut redis_pool = state.redis_pool.clone();
-    let redis_status = match redis_health_check(&mut redis_pool).await {
-        Ok(_) => json!({"status": "healthy", "message": "Connected"}),
-        Err(e) => json!({"status": "unhealthy", "message": e.to_string()}),
-    };
-    health_status["checks"]["redis"] = redis_status;
+    for pool in pools {
+        health_status["checks"][pool.pool.as_str()] = json!({
+            "status": pool.status(),
+            "message": pool.message,
+            "latency_ms": pool.latency_ms,
+            "last_healthy_at": pool.last_healthy_at.map(|at| at.to_rfc3339()),
+            "consecutive_failures": pool.consecutive_failures,
+            "connections": pool.connections.map(|c| json!({
+                "open": c.open,
+                "idle": c.idle,
+                "in_use": c.in_use,
+                "max": c.max,
+                "saturation": c.saturation(),
+            })),
+        });
+    }
 
     // Determine overall status
     let all_healthy = health_status["checks"]
@@ -47,4 +49,4 @@ pub async fn detailed_health_check(State(state): State<AppState>) -> Json<Value>
     }
 
     Json(health_status)
-}
\ No newline at end of file
+}
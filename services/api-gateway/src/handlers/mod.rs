@@ -1,3 +1,9 @@
+pub mod bom;
+pub mod chemicals;
 pub mod health;
+pub mod suppliers;
 
-pub use health::*;
\ No newline at end of file
+pub use bom::*;
+pub use chemicals::*;
+pub use health::*;
+pub use suppliers::*;
@@ -1,2 +1,56 @@
-// Placeholder for supplier handlers
-// Will be implemented in future tasks
\ No newline at end of file
+//! Supplier Handlers
+//!
+//! Supplier reads served through the Redis cache, and the lifecycle changes
+//! that invalidate it. On the SQLite backend reads go to the supplier store
+//! and lifecycle changes are not available.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use uuid::Uuid;
+
+use crate::AppState;
+use elementa_models::{SupplierId, SupplierRecord};
+
+/// Get a supplier by ID
+pub async fn get_supplier(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SupplierRecord>, (StatusCode, String)> {
+    let id = SupplierId::from(id);
+    let supplier = match &state.platform {
+        Some(platform) => platform.suppliers.get(&id).await,
+        None => state.stores.suppliers.find_by_id(id).await,
+    };
+    let supplier = supplier
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Supplier not found".to_string()))?;
+
+    Ok(Json(supplier))
+}
+
+/// Archive a supplier that is no longer in use
+pub async fn archive_supplier(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SupplierRecord>, (StatusCode, String)> {
+    let supplier = state.platform()?.suppliers.archive(SupplierId::from(id), None).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Supplier not found".to_string()))?;
+
+    Ok(Json(supplier))
+}
+
+/// Bring an archived supplier back into use
+pub async fn restore_supplier(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SupplierRecord>, (StatusCode, String)> {
+    let supplier = state.platform()?.suppliers.restore(SupplierId::from(id), None).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Supplier not found".to_string()))?;
+
+    Ok(Json(supplier))
+}
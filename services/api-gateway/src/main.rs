@@ -1,15 +1,21 @@
 use anyhow::Result;
 use axum::{
     extract::DefaultBodyLimit,
-    http::{header, Method},
+    http::{header, Method, StatusCode},
     response::Json,
     routing::{get},
     serve, Router,
 };
-use elementa_database::initialize_databases;
+use elementa_database::{
+    initialize_databases, initialize_stores, AuditPartitionJob, Cached, ChemicalRepository, DatabaseBackend, DatabaseConfig,
+    HealthMonitor, PgSslMode, PostgresTls, SecretSource, Stores, SupplierRepository, TenantAccess,
+};
 use elementa_utils::{init_logging, AppConfig};
 use serde_json::json;
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::{
@@ -39,18 +45,47 @@ async fn main() -> Result<()> {
     info!("Starting Elementa API Gateway");
 
     // Initialize databases
-    let db_config = elementa_database::DatabaseConfig {
-        postgres_url: config.database.postgres_url.clone(),
-        mongodb_url: config.database.mongodb_url.clone(),
-        redis_url: config.database.redis_url.clone(),
-        max_connections: config.database.max_connections,
-        connection_timeout: std::time::Duration::from_secs(config.database.connection_timeout_seconds),
+    let db_config = database_config(&config.database)?;
+    let state = match db_config.backend {
+        DatabaseBackend::Postgres => {
+            let (postgres_pools, mongo_client, redis_pool) = initialize_databases(&db_config).await?;
+            info!("Database connections established");
+
+            // Pick up PostgreSQL password rotations without a restart
+            if let Some(rotation) = db_config.credential_rotation(&postgres_pools) {
+                tokio::spawn(rotation.run());
+            }
+
+            // Watch the pools so detailed health and metrics reflect outages as they happen
+            let health = Arc::new(HealthMonitor::new(postgres_pools.clone(), mongo_client.clone(), redis_pool.clone()));
+            health.clone().spawn();
+
+            // Create audit partitions ahead of the months they cover
+            tokio::spawn(AuditPartitionJob::new(postgres_pools.primary().clone()).run());
+
+            AppState {
+                stores: Stores::postgres(postgres_pools.clone()),
+                platform: Some(Platform {
+                    suppliers: Arc::new(Cached::new(SupplierRepository::with_pools(postgres_pools.clone()), redis_pool.clone())),
+                    chemicals: Arc::new(Cached::new(ChemicalRepository::new(postgres_pools.primary().clone()), redis_pool.clone())),
+                    postgres_pools,
+                    mongo_client,
+                    redis_pool,
+                    health,
+                }),
+                config: config.clone(),
+            }
+        }
+        // Single-node deployments: the core stores only, without MongoDB or Redis
+        DatabaseBackend::Sqlite => {
+            let stores = initialize_stores(&db_config).await?;
+            info!("Core stores opened on SQLite at {}", db_config.sqlite_url);
+            AppState { stores, platform: None, config: config.clone() }
+        }
     };
-    let (postgres_pool, mongo_client, redis_pool) = initialize_databases(&db_config).await?;
-    info!("Database connections established");
 
     // Build application router
-    let app = create_app(postgres_pool, mongo_client, redis_pool, &config).await?;
+    let app = create_app(state, &config).await?;
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
@@ -62,19 +97,47 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn create_app(
-    postgres_pool: elementa_database::PostgresPool,
-    mongo_client: elementa_database::MongoClient,
-    redis_pool: elementa_database::RedisPool,
-    config: &AppConfig,
-) -> Result<Router> {
+fn database_config(config: &elementa_utils::config::DatabaseConfig) -> Result<DatabaseConfig> {
+    let secret = |value: &Option<String>| value.as_deref().map(str::parse::<SecretSource>).transpose();
+    let path = |value: &Option<String>| value.as_ref().map(PathBuf::from);
+
+    Ok(DatabaseConfig {
+        backend: config.backend.parse()?,
+        sqlite_url: config.sqlite_url.clone(),
+        postgres_url: config.postgres_url.clone(),
+        postgres_read_url: config.postgres_read_url.clone(),
+        mongodb_url: config.mongodb_url.clone(),
+        redis_url: config.redis_url.clone(),
+        max_connections: config.max_connections,
+        connection_timeout: Duration::from_secs(config.connection_timeout_seconds),
+        postgres_tls: PostgresTls {
+            mode: config.postgres_ssl_mode.as_deref().map(str::parse::<PgSslMode>).transpose()?,
+            root_cert: path(&config.postgres_ssl_root_cert),
+            client_cert: path(&config.postgres_ssl_client_cert),
+            client_key: path(&config.postgres_ssl_client_key),
+        },
+        postgres_password: secret(&config.postgres_password)?,
+        mongodb_password: secret(&config.mongodb_password)?,
+        redis_password: secret(&config.redis_password)?,
+        credential_refresh_interval: Duration::from_secs(config.credential_refresh_seconds),
+        slow_query_threshold: Duration::from_millis(config.slow_query_threshold_ms),
+        // Requests are scoped to their client's tenant by `auth_middleware`
+        tenant_access: TenantAccess::Scoped,
+    })
+}
+
+async fn create_app(state: AppState, config: &AppConfig) -> Result<Router> {
     let app = Router::new()
         // Health check endpoint
         .route("/health", get(health_check))
         .route("/metrics", get(metrics_handler))
         
-        // API routes
-        .nest("/api/v1", routes::create_api_routes())
+        // API routes, each scoped to the authenticated client's tenant
+        .nest(
+            "/api/v1",
+            routes::create_api_routes()
+                .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth_middleware)),
+        )
         
         // Middleware stack
         .layer(
@@ -93,22 +156,42 @@ async fn create_app(
         )
         
         // Application state
-        .with_state(AppState {
-            postgres_pool,
-            mongo_client,
-            redis_pool,
-            config: config.clone(),
-        });
+        .with_state(state);
 
     Ok(app)
 }
 
 #[derive(Clone)]
 pub struct AppState {
-    pub postgres_pool: elementa_database::PostgresPool,
+    /// Core stores on the configured backend
+    pub stores: Stores,
+    /// The PostgreSQL, MongoDB and Redis deployment; `None` on the SQLite
+    /// backend, where only the core stores are served
+    pub platform: Option<Platform>,
+    pub config: AppConfig,
+}
+
+impl AppState {
+    /// The full deployment, or 501 for endpoints the SQLite backend lacks
+    pub fn platform(&self) -> Result<&Platform, (StatusCode, String)> {
+        self.platform.as_ref().ok_or((
+            StatusCode::NOT_IMPLEMENTED,
+            "Not available on the SQLite backend; run with the PostgreSQL backend".to_string(),
+        ))
+    }
+}
+
+/// Services of the full deployment, beyond the core stores
+#[derive(Clone)]
+pub struct Platform {
+    pub postgres_pools: elementa_database::PostgresPools,
     pub mongo_client: elementa_database::MongoClient,
     pub redis_pool: elementa_database::RedisPool,
-    pub config: AppConfig,
+    /// Supplier reads cached in Redis; writes through it invalidate the cache
+    pub suppliers: Arc<Cached<SupplierRepository>>,
+    /// Chemical lookups by CAS number cached in Redis
+    pub chemicals: Arc<Cached<ChemicalRepository>>,
+    pub health: Arc<HealthMonitor>,
 }
 
 async fn health_check() -> Json<serde_json::Value> {
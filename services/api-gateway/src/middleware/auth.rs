@@ -1,53 +1,47 @@
 use axum::{
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, StatusCode},
     middleware::Next,
     response::Response,
 };
-use elementa_utils::ElementaError;
+use elementa_database::{tenant, TenantId};
 
 use crate::AppState;
 
+/// The client a request was authenticated as
+#[derive(Debug, Clone)]
+pub struct AuthenticatedClient {
+    pub tenant: TenantId,
+}
+
+/// Authenticate the request's bearer token against the configured API keys
+/// and run the rest of the request scoped to the key's tenant, so its
+/// queries only reach that tenant's rows
 pub async fn auth_middleware(
-    State(_state): State<AppState>,
-    headers: HeaderMap,
-    request: Request,
+    State(state): State<AppState>,
+    mut request: Request,
     next: Next,
-) -> Result<Response, ElementaError> {
-    // Extract authorization header
-    let auth_header = headers
-        .get("authorization")
+) -> Result<Response, (StatusCode, String)> {
+    let auth_header = request
+        .headers()
+        .get(header::AUTHORIZATION)
         .and_then(|header| header.to_str().ok());
 
-    // For now, we'll implement a simple token-based auth
-    // In production, this would validate JWT tokens or API keys
-    match auth_header {
-        Some(header) if header.starts_with("Bearer ") => {
-            let token = &header[7..]; // Remove "Bearer " prefix
-            
-            // TODO: Implement proper token validation
-            if token == "development-token" || validate_token(token).await {
-                Ok(next.run(request).await)
-            } else {
-                Err(ElementaError::Authentication {
-                    message: "Invalid token".to_string(),
-                })
-            }
-        }
-        Some(_) => Err(ElementaError::Authentication {
-            message: "Invalid authorization header format".to_string(),
-        }),
-        None => Err(ElementaError::Authentication {
-            message: "Missing authorization header".to_string(),
-        }),
-    }
-}
+    let token = match auth_header {
+        Some(header) => header
+            .strip_prefix("Bearer ")
+            .ok_or((StatusCode::UNAUTHORIZED, "Invalid authorization header format".to_string()))?,
+        None => return Err((StatusCode::UNAUTHORIZED, "Missing authorization header".to_string())),
+    };
 
-async fn validate_token(_token: &str) -> bool {
-    // TODO: Implement proper token validation
-    // This could involve:
-    // - JWT token verification
-    // - Database lookup for API keys
-    // - Integration with external auth providers
-    true
-}
\ No newline at end of file
+    let tenant_id = state.config.server.api_keys
+        .get(token)
+        .ok_or((StatusCode::UNAUTHORIZED, "Invalid token".to_string()))?;
+    let tenant = TenantId::new(tenant_id.as_str()).map_err(|e| {
+        tracing::error!("API key configured with an invalid tenant: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Invalid API key configuration".to_string())
+    })?;
+
+    request.extensions_mut().insert(AuthenticatedClient { tenant: tenant.clone() });
+    Ok(tenant::scope(tenant, next.run(request)).await)
+}
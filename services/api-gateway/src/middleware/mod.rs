@@ -1,5 +1,7 @@
+pub mod auth;
 pub mod error_handling;
 pub mod request_id;
 
+pub use auth::*;
 pub use error_handling::*;
 pub use request_id::*;
\ No newline at end of file
@@ -1,12 +1,18 @@
-use axum::{routing::get, Router};
+use axum::{routing::{get, post, put}, Router};
 
 use crate::{handlers::*, AppState};
 
 pub fn create_api_routes() -> Router<AppState> {
     Router::new()
         .route("/health/detailed", get(detailed_health_check))
+        .route("/suppliers/:id", get(get_supplier))
+        .route("/suppliers/:id/archive", post(archive_supplier))
+        .route("/suppliers/:id/restore", post(restore_supplier))
+        .route("/chemicals/:cas_number", get(get_chemical))
+        .route("/bom/upload", post(upload_bom))
+        .route("/bom/mappings", get(list_column_mappings))
+        .route("/bom/mappings/:name", put(save_column_mapping).delete(delete_column_mapping))
         // TODO: Add other API routes as services are implemented
-        // .nest("/suppliers", supplier_routes())
         // .nest("/components", component_routes())
         // .nest("/compliance", compliance_routes())
         // .nest("/workflows", workflow_routes())
@@ -0,0 +1,249 @@
+//! Archive Expander
+//!
+//! Expands ZIP response packages into individual documents.
+//! Entry sizes, counts and compression ratios are bounded to guard against zip bombs.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Entries smaller than this are exempt from the compression ratio check
+const RATIO_CHECK_MIN_SIZE: u64 = 1024 * 1024;
+
+/// Limits applied while expanding an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveLimits {
+    pub max_entries: usize,
+    pub max_entry_size: u64,
+    pub max_total_size: u64,
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 500,
+            max_entry_size: 50 * 1024 * 1024,
+            max_total_size: 500 * 1024 * 1024,
+            max_compression_ratio: 100,
+        }
+    }
+}
+
+/// Document extracted from an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub filename: String,
+    pub file_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Archive entry that was not turned into a document
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedEntry {
+    pub filename: String,
+    pub reason: String,
+}
+
+/// Result of expanding an archive
+#[derive(Debug, Clone, Default)]
+pub struct ExpandedArchive {
+    pub entries: Vec<ArchiveEntry>,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// ZIP archive expander
+pub struct ArchiveExpander {
+    limits: ArchiveLimits,
+}
+
+impl ArchiveExpander {
+    pub fn new() -> Self {
+        Self::with_limits(ArchiveLimits::default())
+    }
+
+    pub fn with_limits(limits: ArchiveLimits) -> Self {
+        Self { limits }
+    }
+
+    /// Expand archive bytes into typed entries.
+    /// Fails outright if the archive looks like a zip bomb.
+    pub fn expand(&self, data: &[u8]) -> Result<ExpandedArchive> {
+        let mut archive = ZipArchive::new(Cursor::new(data))
+            .context("Invalid ZIP archive")?;
+
+        if archive.len() > self.limits.max_entries {
+            bail!("Archive has {} entries, limit is {}", archive.len(), self.limits.max_entries);
+        }
+
+        let mut expanded = ExpandedArchive::default();
+        let mut total_size: u64 = 0;
+
+        for index in 0..archive.len() {
+            let entry = archive.by_index(index)
+                .context("Failed to read archive entry")?;
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            let filename = match entry.enclosed_name() {
+                Some(path) => path.to_string_lossy().to_string(),
+                None => {
+                    expanded.skipped.push(SkippedEntry {
+                        filename: entry.name().to_string(),
+                        reason: "Unsafe entry path".to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            // OS metadata, not supplier content
+            let basename = filename.rsplit('/').next().unwrap_or(&filename);
+            if filename.starts_with("__MACOSX/") || basename.starts_with("._") {
+                continue;
+            }
+
+            if entry.size() > self.limits.max_entry_size {
+                expanded.skipped.push(SkippedEntry {
+                    filename,
+                    reason: format!("Entry exceeds {} byte limit", self.limits.max_entry_size),
+                });
+                continue;
+            }
+
+            // Declared sizes can lie, so bound the actual read as well
+            let compressed_size = entry.compressed_size().max(1);
+            let mut buffer = Vec::new();
+            entry.take(self.limits.max_entry_size + 1).read_to_end(&mut buffer)
+                .with_context(|| format!("Failed to decompress {}", filename))?;
+            let size = buffer.len() as u64;
+
+            if size > self.limits.max_entry_size {
+                expanded.skipped.push(SkippedEntry {
+                    filename,
+                    reason: format!("Entry exceeds {} byte limit", self.limits.max_entry_size),
+                });
+                continue;
+            }
+
+            if size > RATIO_CHECK_MIN_SIZE && size / compressed_size > self.limits.max_compression_ratio {
+                bail!("Suspicious compression ratio for {}", filename);
+            }
+
+            total_size += size;
+            if total_size > self.limits.max_total_size {
+                bail!("Archive exceeds {} byte uncompressed limit", self.limits.max_total_size);
+            }
+
+            match detect_file_type(&filename, &buffer) {
+                Some("application/zip") => expanded.skipped.push(SkippedEntry {
+                    filename,
+                    reason: "Nested archives are not expanded".to_string(),
+                }),
+                Some(file_type) => expanded.entries.push(ArchiveEntry {
+                    filename,
+                    file_type: file_type.to_string(),
+                    data: buffer,
+                }),
+                None => expanded.skipped.push(SkippedEntry {
+                    filename,
+                    reason: "Unsupported file type".to_string(),
+                }),
+            }
+        }
+
+        Ok(expanded)
+    }
+}
+
+impl Default for ArchiveExpander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check whether an upload is a ZIP archive
+pub fn is_zip(filename: &str, content_type: &str, data: &[u8]) -> bool {
+    content_type.contains("zip")
+        || (filename.to_lowercase().ends_with(".zip") && data.starts_with(b"PK\x03\x04"))
+}
+
+/// Detect MIME type from magic bytes, falling back to the file extension
+pub fn detect_file_type(filename: &str, data: &[u8]) -> Option<&'static str> {
+    let extension = filename.rsplit('.').next().unwrap_or("").to_lowercase();
+
+    if data.starts_with(b"%PDF") {
+        return Some("application/pdf");
+    }
+    if data.starts_with(b"\x89PNG") {
+        return Some("image/png");
+    }
+    if data.starts_with(b"\xFF\xD8\xFF") {
+        return Some("image/jpeg");
+    }
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some("image/tiff");
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        // Office formats are ZIP containers
+        return match extension.as_str() {
+            "xlsx" => Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            "docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            _ => Some("application/zip"),
+        };
+    }
+
+    match extension.as_str() {
+        "csv" => Some("text/csv"),
+        "txt" => Some("text/plain"),
+        "xml" => Some("application/xml"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn build_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_expand_detects_types_and_skips_unsupported() {
+        let inner = build_zip(&[("a.pdf", b"%PDF-1.4")]);
+        let data = build_zip(&[
+            ("sds/part-1.pdf", b"%PDF-1.4 test"),
+            ("readme.exe", b"MZ"),
+            ("nested.zip", &inner),
+            ("__MACOSX/._part-1.pdf", b"meta"),
+        ]);
+
+        let expanded = ArchiveExpander::new().expand(&data).unwrap();
+
+        assert_eq!(expanded.entries.len(), 1);
+        assert_eq!(expanded.entries[0].filename, "sds/part-1.pdf");
+        assert_eq!(expanded.entries[0].file_type, "application/pdf");
+        assert_eq!(expanded.skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_expand_rejects_zip_bombs() {
+        let zeros = vec![0u8; 4 * 1024 * 1024];
+        let data = build_zip(&[("bomb.txt", &zeros)]);
+        assert!(ArchiveExpander::new().expand(&data).is_err());
+
+        let limits = ArchiveLimits { max_entries: 1, ..ArchiveLimits::default() };
+        let data = build_zip(&[("a.pdf", b"%PDF"), ("b.pdf", b"%PDF")]);
+        assert!(ArchiveExpander::with_limits(limits).expand(&data).is_err());
+    }
+}
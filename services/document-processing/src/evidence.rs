@@ -0,0 +1,154 @@
+//! Evidence Locator
+//!
+//! Finds where extracted values appear on PDF pages and captures snippet images,
+//! so reviewers and audit exports can point at the exact source location.
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use pdfium_render::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::thumbnail::encode_png;
+
+/// Width pages are rendered at before cropping snippets
+const SNIPPET_RENDER_WIDTH: Pixels = 1200;
+/// Padding around snippets, as a fraction of page size
+const SNIPPET_PADDING: f32 = 0.03;
+
+/// Region of a page, as fractions of page width/height with a top-left origin
+#[derive(Debug, Clone, Serialize)]
+pub struct BoundingBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Location of an extracted value within a document
+#[derive(Debug, Clone)]
+pub struct Evidence {
+    pub id: Uuid,
+    pub page: usize,
+    pub bounding_box: BoundingBox,
+    pub snippet: Option<Vec<u8>>,
+}
+
+/// Locates extracted values on PDF pages
+pub struct EvidenceLocator {
+    pdfium: Option<Arc<Pdfium>>,
+}
+
+impl EvidenceLocator {
+    pub fn new(pdfium: Option<Arc<Pdfium>>) -> Self {
+        Self { pdfium }
+    }
+
+    /// Find every occurrence of each term, keyed by term.
+    /// Returns nothing when Pdfium is unavailable or the PDF cannot be read.
+    pub fn locate(&self, data: &[u8], terms: &[&str]) -> HashMap<String, Vec<Evidence>> {
+        let Some(pdfium) = &self.pdfium else {
+            return HashMap::new();
+        };
+
+        match self.locate_with(pdfium, data, terms) {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("Evidence location failed: {:#}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    fn locate_with(&self, pdfium: &Pdfium, data: &[u8], terms: &[&str]) -> Result<HashMap<String, Vec<Evidence>>> {
+        let document = pdfium.load_pdf_from_byte_slice(data, None)
+            .context("Failed to load PDF")?;
+        let options = PdfSearchOptions::new().match_whole_word(true);
+        let mut found: HashMap<String, Vec<Evidence>> = HashMap::new();
+
+        for (index, page) in document.pages().iter().enumerate() {
+            let page_width = page.width().value;
+            let page_height = page.height().value;
+            let text = page.text().context("Failed to read page text")?;
+
+            // Rendered lazily, only for pages with matches
+            let mut rendered: Option<DynamicImage> = None;
+
+            for term in terms.iter().filter(|t| !t.is_empty()) {
+                let search = text.search(term, &options)
+                    .context("Failed to search page text")?;
+
+                while let Some(segments) = search.find_next() {
+                    let Some(bounds) = segments.iter()
+                        .map(|segment| segment.bounds())
+                        .map(|r| (r.left().value, r.top().value, r.right().value, r.bottom().value))
+                        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1), a.2.max(b.2), a.3.min(b.3)))
+                    else {
+                        continue;
+                    };
+
+                    let (left, top, right, bottom) = bounds;
+                    let bounding_box = BoundingBox {
+                        x: left / page_width,
+                        y: (page_height - top) / page_height,
+                        width: (right - left) / page_width,
+                        height: (top - bottom) / page_height,
+                    };
+
+                    if rendered.is_none() {
+                        let config = PdfRenderConfig::new().set_target_width(SNIPPET_RENDER_WIDTH);
+                        rendered = page.render_with_config(&config).ok().map(|bitmap| bitmap.as_image());
+                    }
+                    let snippet = rendered.as_ref()
+                        .and_then(|image| crop_snippet(image, &bounding_box).ok());
+
+                    found.entry(term.to_string()).or_default().push(Evidence {
+                        id: Uuid::new_v4(),
+                        page: index + 1,
+                        bounding_box,
+                        snippet,
+                    });
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+/// Crop padded bounding box out of a rendered page
+fn crop_snippet(page: &DynamicImage, bbox: &BoundingBox) -> Result<Vec<u8>> {
+    let (width, height) = (page.width() as f32, page.height() as f32);
+
+    let x0 = ((bbox.x - SNIPPET_PADDING).max(0.0) * width).round() as u32;
+    let y0 = ((bbox.y - SNIPPET_PADDING).max(0.0) * height).round() as u32;
+    let x1 = ((bbox.x + bbox.width + SNIPPET_PADDING).min(1.0) * width).round() as u32;
+    let y1 = ((bbox.y + bbox.height + SNIPPET_PADDING).min(1.0) * height).round() as u32;
+
+    if x1 <= x0 || y1 <= y0 {
+        anyhow::bail!("Empty snippet region");
+    }
+
+    encode_png(&page.crop_imm(x0, y0, x1 - x0, y1 - y0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    #[test]
+    fn test_crop_snippet_pads_and_clamps() {
+        let page = DynamicImage::ImageRgb8(RgbImage::new(1000, 1000));
+        let bbox = BoundingBox { x: 0.0, y: 0.5, width: 0.1, height: 0.02 };
+
+        let snippet = crop_snippet(&page, &bbox).unwrap();
+        let decoded = image::load_from_memory(&snippet).unwrap();
+
+        assert_eq!(decoded.width(), 130);
+        assert_eq!(decoded.height(), 80);
+    }
+}
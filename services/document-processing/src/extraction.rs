@@ -2,13 +2,23 @@
 //! 
 //! Orchestrates document processing and VLM extraction.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use axum::body::Body;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+use tokio_util::io::ReaderStream;
+use tracing::warn;
 use uuid::Uuid;
 
-use crate::pdf_processor::{PdfProcessor, CasMatch};
+use elementa_database::{DocumentMetadata, DocumentRepository};
+
+use crate::archive::{ArchiveExpander, SkippedEntry};
+use crate::evidence::{BoundingBox, Evidence, EvidenceLocator};
+use crate::pdf_processor::{bind_pdfium, PdfProcessor, CasMatch};
+use crate::thumbnail::ThumbnailGenerator;
+use crate::worker_pool::{QueueFull, WorkerPermit, WorkerPool};
 
 
 /// Stored document
@@ -19,8 +29,44 @@ pub struct StoredDocument {
     pub file_type: String,
     pub upload_date: String,
     pub status: String,
+    /// Content, unless it is kept in the document store
     pub data: Vec<u8>,
+    pub thumbnail: Option<Vec<u8>>,
+    pub package_id: Option<Uuid>,
+    pub source: Option<DocumentSource>,
     pub extraction: Option<ExtractionResult>,
+    pub snippets: HashMap<Uuid, Vec<u8>>,
+}
+
+/// Origin of a document received through supplier correspondence
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct DocumentSource {
+    pub supplier_id: Option<Uuid>,
+    pub email_id: Option<Uuid>,
+    pub thread_id: Option<String>,
+    /// Campaign of a document uploaded through a supplier upload link
+    pub campaign_id: Option<Uuid>,
+}
+
+impl DocumentSource {
+    /// Source with no fields set carries no information
+    pub fn into_option(self) -> Option<Self> {
+        if self.supplier_id.is_none() && self.email_id.is_none() && self.thread_id.is_none() && self.campaign_id.is_none() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// Stored response package (ZIP upload)
+#[derive(Debug, Clone)]
+pub struct StoredPackage {
+    pub id: Uuid,
+    pub filename: String,
+    pub upload_date: String,
+    pub document_ids: Vec<Uuid>,
+    pub skipped: Vec<SkippedEntry>,
 }
 
 /// Extraction result
@@ -38,7 +84,10 @@ pub struct CasExtractionResponse {
     pub cas_number: String,
     pub confidence: f64,
     pub context: String,
+    pub contexts: Vec<String>,
+    pub occurrences: usize,
     pub page: Option<usize>,
+    pub evidence: Vec<EvidenceResponse>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -47,6 +96,27 @@ pub struct TestResultResponse {
     pub result: String,
     pub unit: Option<String>,
     pub confidence: f64,
+    pub evidence: Vec<EvidenceResponse>,
+}
+
+/// Where an extracted value was found
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EvidenceResponse {
+    pub evidence_id: Uuid,
+    pub page: usize,
+    pub bounding_box: BoundingBox,
+    pub has_snippet: bool,
+}
+
+impl From<&Evidence> for EvidenceResponse {
+    fn from(evidence: &Evidence) -> Self {
+        Self {
+            evidence_id: evidence.id,
+            page: evidence.page,
+            bounding_box: evidence.bounding_box.clone(),
+            has_snippet: evidence.snippet.is_some(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -67,28 +137,132 @@ pub struct UncertaintyResponse {
 #[derive(Clone)]
 pub struct DocumentExtractor {
     documents: Arc<RwLock<HashMap<Uuid, StoredDocument>>>,
+    packages: Arc<RwLock<HashMap<Uuid, StoredPackage>>>,
     pdf_processor: Arc<PdfProcessor>,
+    thumbnails: Arc<ThumbnailGenerator>,
+    evidence_locator: Arc<EvidenceLocator>,
+    archive_expander: Arc<ArchiveExpander>,
+    workers: WorkerPool,
+    /// Keeps document content and metadata in MongoDB rather than in memory
+    store: Option<Arc<DocumentRepository>>,
+}
+
+/// Original content of a document, ready to send
+pub struct DocumentContent {
+    pub filename: String,
+    pub file_type: String,
+    pub body: Body,
 }
 
 impl DocumentExtractor {
     pub fn new() -> Self {
+        let pdfium = bind_pdfium();
         Self {
             documents: Arc::new(RwLock::new(HashMap::new())),
+            packages: Arc::new(RwLock::new(HashMap::new())),
             pdf_processor: Arc::new(PdfProcessor::new()),
+            thumbnails: Arc::new(ThumbnailGenerator::new(pdfium.clone())),
+            evidence_locator: Arc::new(EvidenceLocator::new(pdfium)),
+            archive_expander: Arc::new(ArchiveExpander::new()),
+            workers: WorkerPool::default(),
+            store: None,
         }
     }
     
+    /// Store document content in GridFS and metadata in MongoDB
+    pub fn with_document_store(mut self, store: DocumentRepository) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+    
     /// Store uploaded document
-    pub async fn store_document(&self, filename: &str, file_type: &str, data: &[u8]) -> Result<Uuid> {
+    pub async fn store_document(
+        &self,
+        filename: &str,
+        file_type: &str,
+        data: &[u8],
+        source: Option<DocumentSource>,
+    ) -> Result<Uuid> {
+        self.insert_document(filename, file_type, data, None, source).await
+    }
+    
+    /// Expand a ZIP package and store each supported entry as a document
+    pub async fn store_package(
+        &self,
+        filename: &str,
+        data: &[u8],
+        source: Option<DocumentSource>,
+    ) -> Result<StoredPackage> {
+        let expanded = self.archive_expander.expand(data)?;
         let id = Uuid::new_v4();
+        
+        let mut document_ids = Vec::with_capacity(expanded.entries.len());
+        for entry in &expanded.entries {
+            let doc_id = self.insert_document(
+                &entry.filename, &entry.file_type, &entry.data, Some(id), source.clone(),
+            ).await?;
+            document_ids.push(doc_id);
+        }
+        
+        let package = StoredPackage {
+            id,
+            filename: filename.to_string(),
+            upload_date: chrono::Utc::now().to_rfc3339(),
+            document_ids,
+            skipped: expanded.skipped,
+        };
+        
+        let mut packages = self.packages.write().await;
+        packages.insert(id, package.clone());
+        
+        Ok(package)
+    }
+    
+    /// Get package by ID
+    pub async fn get_package(&self, id: Uuid) -> Result<Option<StoredPackage>> {
+        let packages = self.packages.read().await;
+        Ok(packages.get(&id).cloned())
+    }
+    
+    async fn insert_document(
+        &self,
+        filename: &str,
+        file_type: &str,
+        data: &[u8],
+        package_id: Option<Uuid>,
+        source: Option<DocumentSource>,
+    ) -> Result<Uuid> {
+        let mut metadata = DocumentMetadata::new(filename, file_type);
+        let id = metadata.id;
+        let thumbnail = self.thumbnails.generate(file_type, data);
+        
+        let data = match &self.store {
+            Some(store) => {
+                metadata.package_id = package_id;
+                if let Some(source) = &source {
+                    metadata.supplier_id = source.supplier_id;
+                    metadata.email_id = source.email_id;
+                    metadata.thread_id = source.thread_id.clone();
+                    metadata.campaign_id = source.campaign_id;
+                }
+                store.upload(metadata, data).await?;
+                Vec::new()
+            }
+            None => data.to_vec(),
+        };
+        
         let doc = StoredDocument {
             id,
             filename: filename.to_string(),
             file_type: file_type.to_string(),
             upload_date: chrono::Utc::now().to_rfc3339(),
             status: "uploaded".to_string(),
-            data: data.to_vec(),
+            data,
+            thumbnail,
+            package_id,
+            source,
             extraction: None,
+            snippets: HashMap::new(),
         };
         
         let mut docs = self.documents.write().await;
@@ -97,55 +271,173 @@ impl DocumentExtractor {
         Ok(id)
     }
     
-    /// Get document by ID
+    /// Get document by ID; documents stored before a restart come back
+    /// from the document store without thumbnail or extraction
     pub async fn get_document(&self, id: Uuid) -> Result<Option<StoredDocument>> {
+        if let Some(doc) = self.documents.read().await.get(&id) {
+            return Ok(Some(doc.clone()));
+        }
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        
+        let document = store.find_by_id(id).await?.map(|metadata| StoredDocument {
+            id: metadata.id,
+            filename: metadata.filename,
+            file_type: metadata.content_type,
+            upload_date: metadata.uploaded_at.to_rfc3339(),
+            status: metadata.status,
+            data: Vec::new(),
+            thumbnail: None,
+            package_id: metadata.package_id,
+            source: DocumentSource {
+                supplier_id: metadata.supplier_id,
+                email_id: metadata.email_id,
+                thread_id: metadata.thread_id,
+                campaign_id: metadata.campaign_id,
+            }.into_option(),
+            extraction: None,
+            snippets: HashMap::new(),
+        });
+        Ok(document)
+    }
+    
+    /// Open a document's original content, streamed from the document store when it is there
+    pub async fn open_content(&self, id: Uuid) -> Result<Option<DocumentContent>> {
+        if let Some(store) = &self.store {
+            return Ok(store.open_download(id).await?.map(|(metadata, stream)| DocumentContent {
+                filename: metadata.filename,
+                file_type: metadata.content_type,
+                body: Body::from_stream(ReaderStream::new(stream.compat())),
+            }));
+        }
+        
         let docs = self.documents.read().await;
-        Ok(docs.get(&id).cloned())
+        Ok(docs.get(&id).map(|doc| DocumentContent {
+            filename: doc.filename.clone(),
+            file_type: doc.file_type.clone(),
+            body: Body::from(doc.data.clone()),
+        }))
+    }
+    
+    /// Content of a document for extraction
+    async fn load_content(&self, id: Uuid, data: Vec<u8>) -> Result<Vec<u8>> {
+        match &self.store {
+            Some(store) => store.download(id).await?.context("Document content not found"),
+            None => Ok(data),
+        }
+    }
+    
+    /// Mirror a status change into the document store
+    async fn record_status(&self, id: Uuid, status: &str) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.update_status(id, status).await {
+                warn!("Failed to record status of document {}: {:#}", id, e);
+            }
+        }
+    }
+    
+    /// Wait for an extraction worker slot
+    pub async fn acquire_worker(&self, tenant: &str) -> std::result::Result<WorkerPermit, QueueFull> {
+        self.workers.acquire(tenant).await
     }
     
     /// Extract data from document
     pub async fn extract(&self, id: Uuid) -> Result<ExtractionResult> {
-        let mut docs = self.documents.write().await;
-        let doc = docs.get_mut(&id)
-            .ok_or_else(|| anyhow::anyhow!("Document not found"))?;
+        // Uploaded before a restart or through another instance: only the document store has it
+        if !self.documents.read().await.contains_key(&id) {
+            if let Some(document) = self.get_document(id).await? {
+                self.documents.write().await.entry(id).or_insert(document);
+            }
+        }
         
-        // Update status
-        doc.status = "processing".to_string();
+        // Release the store lock while processing so extractions can run concurrently
+        let (file_type, data) = {
+            let mut docs = self.documents.write().await;
+            let doc = docs.get_mut(&id)
+                .ok_or_else(|| anyhow::anyhow!("Document not found"))?;
+            doc.status = "processing".to_string();
+            (doc.file_type.clone(), doc.data.clone())
+        };
+        self.record_status(id, "processing").await;
+        let data = self.load_content(id, data).await?;
         
         // Determine extraction method based on file type
-        let extraction = if doc.file_type.contains("pdf") {
-            self.extract_from_pdf(&doc.data).await?
+        let processed = if file_type.contains("pdf") {
+            self.extract_from_pdf(&data).await
         } else {
             // For images, use VLM directly
             // For now, return empty result
-            self.create_empty_result()
+            Ok((self.create_empty_result(), HashMap::new()))
+        };
+        
+        let mut docs = self.documents.write().await;
+        let doc = docs.get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Document not found"))?;
+        
+        let (extraction, snippets) = match processed {
+            Ok(processed) => processed,
+            Err(e) => {
+                doc.status = "failed".to_string();
+                drop(docs);
+                self.record_status(id, "failed").await;
+                return Err(e);
+            }
         };
         
         doc.extraction = Some(extraction.clone());
+        doc.snippets = snippets;
         doc.status = "extracted".to_string();
+        drop(docs);
+        self.record_status(id, "extracted").await;
         
         Ok(extraction)
     }
     
-    /// Extract from PDF
-    async fn extract_from_pdf(&self, data: &[u8]) -> Result<ExtractionResult> {
+    /// Extract from PDF, returning evidence snippets keyed by evidence ID
+    async fn extract_from_pdf(&self, data: &[u8]) -> Result<(ExtractionResult, HashMap<Uuid, Vec<u8>>)> {
         // First, extract text and CAS numbers using regex
         let pdf_content = self.pdf_processor.extract(data)?;
         let cas_matches = self.pdf_processor.extract_cas_numbers(&pdf_content.text);
         
+        // Locate each distinct CAS number on the rendered pages
+        let mut terms: Vec<&str> = cas_matches.iter().map(|m| m.cas_number.as_str()).collect();
+        terms.sort_unstable();
+        terms.dedup();
+        let mut located = self.evidence_locator.locate(data, &terms);
+        
+        let mut snippets = HashMap::new();
+        for evidence in located.values_mut().flatten() {
+            if let Some(snippet) = evidence.snippet.take() {
+                snippets.insert(evidence.id, snippet);
+            }
+        }
+        
         // Convert to response format
         let cas_numbers: Vec<CasExtractionResponse> = cas_matches.into_iter()
             .map(|m| {
                 let cas_number = m.cas_number.clone();
                 let confidence = self.validate_cas_confidence(&m);
+                let evidence: Vec<EvidenceResponse> = located.get(&cas_number)
+                    .map(|found| found.iter()
+                        .map(|e| EvidenceResponse {
+                            has_snippet: snippets.contains_key(&e.id),
+                            ..EvidenceResponse::from(e)
+                        })
+                        .collect())
+                    .unwrap_or_default();
                 CasExtractionResponse {
                     cas_number,
                     confidence,
-                    context: m.context,
-                    page: Some(1),
+                    context: m.context.clone(),
+                    contexts: vec![m.context],
+                    occurrences: 1,
+                    page: evidence.first().map(|e| e.page).or(Some(1)),
+                    evidence,
                 }
             })
             .collect();
+        let cas_numbers = consolidate_cas_numbers(cas_numbers);
         
         // Calculate overall confidence
         let overall_confidence = if cas_numbers.is_empty() {
@@ -166,13 +458,15 @@ impl DocumentExtractor {
             }
         }
         
-        Ok(ExtractionResult {
+        let extraction = ExtractionResult {
             cas_numbers,
             test_results: Vec::new(), // Would need VLM for structured test results
             certifications: Vec::new(),
             overall_confidence,
             uncertainties,
-        })
+        };
+        
+        Ok((extraction, snippets))
     }
     
     /// Validate CAS and calculate confidence
@@ -217,8 +511,74 @@ impl DocumentExtractor {
     }
 }
 
+/// Merge repeated mentions of the same CAS number into one record,
+/// keeping first-seen order, distinct contexts and the highest confidence
+fn consolidate_cas_numbers(cas_numbers: Vec<CasExtractionResponse>) -> Vec<CasExtractionResponse> {
+    let mut consolidated: Vec<CasExtractionResponse> = Vec::new();
+    let mut index_by_cas: HashMap<String, usize> = HashMap::new();
+    
+    for cas in cas_numbers {
+        match index_by_cas.get(&cas.cas_number) {
+            Some(&index) => {
+                let existing = &mut consolidated[index];
+                existing.occurrences += cas.occurrences;
+                if cas.confidence > existing.confidence {
+                    existing.confidence = cas.confidence;
+                    existing.context = cas.context.clone();
+                }
+                for context in cas.contexts {
+                    if !existing.contexts.contains(&context) {
+                        existing.contexts.push(context);
+                    }
+                }
+            }
+            None => {
+                index_by_cas.insert(cas.cas_number.clone(), consolidated.len());
+                consolidated.push(cas);
+            }
+        }
+    }
+    
+    consolidated
+}
+
 impl Default for DocumentExtractor {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn mention(cas_number: &str, confidence: f64, context: &str) -> CasExtractionResponse {
+        CasExtractionResponse {
+            cas_number: cas_number.to_string(),
+            confidence,
+            context: context.to_string(),
+            contexts: vec![context.to_string()],
+            occurrences: 1,
+            page: Some(1),
+            evidence: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_consolidate_duplicate_cas_numbers() {
+        let consolidated = consolidate_cas_numbers(vec![
+            mention("7732-18-5", 0.5, "water, table 1"),
+            mention("7647-14-5", 0.95, "sodium chloride"),
+            mention("7732-18-5", 0.95, "water, section 3"),
+            mention("7732-18-5", 0.95, "water, table 1"),
+        ]);
+        
+        assert_eq!(consolidated.len(), 2);
+        assert_eq!(consolidated[0].cas_number, "7732-18-5");
+        assert_eq!(consolidated[0].occurrences, 3);
+        assert_eq!(consolidated[0].confidence, 0.95);
+        assert_eq!(consolidated[0].context, "water, section 3");
+        assert_eq!(consolidated[0].contexts, vec!["water, table 1", "water, section 3"]);
+        assert_eq!(consolidated[1].occurrences, 1);
+    }
+}
@@ -5,9 +5,9 @@
 
 use anyhow::Result;
 use axum::{
-    extract::{Multipart, Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -18,12 +18,18 @@ use tower_http::trace::TraceLayer;
 use tracing::info;
 use uuid::Uuid;
 
+mod archive;
+mod evidence;
 mod vlm_client;
 mod pdf_processor;
 mod extraction;
+mod thumbnail;
+mod worker_pool;
 
+use archive::SkippedEntry;
+use elementa_database::DocumentRepository;
 use extraction::{
-    DocumentExtractor, CasExtractionResponse, TestResultResponse, 
+    DocumentExtractor, DocumentSource, StoredPackage, CasExtractionResponse, TestResultResponse, 
     CertificationResponse, UncertaintyResponse
 };
 
@@ -32,14 +38,28 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     info!("Starting Elementa Document Processing Service");
     
-    let extractor = DocumentExtractor::new();
+    let mut extractor = DocumentExtractor::new();
+    
+    if let Ok(mongodb_url) = std::env::var("MONGODB_URL") {
+        let client = elementa_database::create_mongo_client(&mongodb_url).await?;
+        let database = client.default_database()
+            .unwrap_or_else(|| elementa_database::get_database(&client, "elementa"));
+        let store = DocumentRepository::new(&database);
+        store.ensure_indexes().await?;
+        extractor = extractor.with_document_store(store);
+    }
     
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/api/v1/documents/upload", post(upload_document))
         .route("/api/v1/documents/:id", get(get_document))
+        .route("/api/v1/documents/:id/content", get(get_content))
         .route("/api/v1/documents/:id/extract", post(extract_data))
         .route("/api/v1/documents/:id/cas-numbers", get(get_cas_numbers))
+        .route("/api/v1/documents/:id/thumbnail", get(get_thumbnail))
+        .route("/api/v1/documents/:id/evidence/:evidence_id", get(get_evidence_snippet))
+        .route("/api/v1/packages/upload", post(upload_package))
+        .route("/api/v1/packages/:id", get(get_package))
         .layer(TraceLayer::new_for_http())
         .with_state(extractor);
     
@@ -73,6 +93,7 @@ pub struct DocumentUploadResponse {
 /// Upload compliance document
 async fn upload_document(
     State(extractor): State<DocumentExtractor>,
+    Query(source): Query<DocumentSource>,
     mut multipart: Multipart,
 ) -> Result<Json<DocumentUploadResponse>, (StatusCode, String)> {
     let field = multipart.next_field().await
@@ -90,7 +111,11 @@ async fn upload_document(
     let data = field.bytes().await
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Read error: {}", e)))?;
     
-    let doc_id = extractor.store_document(&filename, &content_type, &data).await
+    if archive::is_zip(&filename, &content_type, &data) {
+        return Err((StatusCode::BAD_REQUEST, "ZIP archives must be uploaded to /api/v1/packages/upload".to_string()));
+    }
+    
+    let doc_id = extractor.store_document(&filename, &content_type, &data, source.into_option()).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
     Ok(Json(DocumentUploadResponse {
@@ -110,6 +135,8 @@ pub struct DocumentResponse {
     pub file_type: String,
     pub upload_date: String,
     pub processing_status: String,
+    pub package_id: Option<Uuid>,
+    pub source: Option<DocumentSource>,
     pub extraction_result: Option<ExtractionResultResponse>,
 }
 
@@ -127,6 +154,8 @@ async fn get_document(
         file_type: doc.file_type,
         upload_date: doc.upload_date,
         processing_status: doc.status,
+        package_id: doc.package_id,
+        source: doc.source,
         extraction_result: doc.extraction.map(|e| ExtractionResultResponse {
             cas_numbers: e.cas_numbers,
             test_results: e.test_results,
@@ -137,6 +166,79 @@ async fn get_document(
     }))
 }
 
+/// Download the original document content
+async fn get_content(
+    State(extractor): State<DocumentExtractor>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let content = extractor.open_content(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Document not found".to_string()))?;
+    
+    let disposition = format!("attachment; filename=\"{}\"", content.filename.replace('"', ""));
+    Ok((
+        [(header::CONTENT_TYPE, content.file_type), (header::CONTENT_DISPOSITION, disposition)],
+        content.body,
+    ))
+}
+
+/// Package response
+#[derive(Debug, Serialize)]
+pub struct PackageResponse {
+    pub package_id: Uuid,
+    pub filename: String,
+    pub upload_date: String,
+    pub document_ids: Vec<Uuid>,
+    pub skipped_entries: Vec<SkippedEntry>,
+}
+
+impl From<StoredPackage> for PackageResponse {
+    fn from(package: StoredPackage) -> Self {
+        Self {
+            package_id: package.id,
+            filename: package.filename,
+            upload_date: package.upload_date,
+            document_ids: package.document_ids,
+            skipped_entries: package.skipped,
+        }
+    }
+}
+
+/// Upload ZIP response package and expand it into documents
+async fn upload_package(
+    State(extractor): State<DocumentExtractor>,
+    Query(source): Query<DocumentSource>,
+    mut multipart: Multipart,
+) -> Result<Json<PackageResponse>, (StatusCode, String)> {
+    let field = multipart.next_field().await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Upload error: {}", e)))?
+        .ok_or((StatusCode::BAD_REQUEST, "No file provided".to_string()))?;
+    
+    let filename = field.file_name()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "package.zip".to_string());
+    
+    let data = field.bytes().await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Read error: {}", e)))?;
+    
+    let package = extractor.store_package(&filename, &data, source.into_option()).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid package: {:#}", e)))?;
+    
+    Ok(Json(package.into()))
+}
+
+/// Get package and its expanded documents
+async fn get_package(
+    State(extractor): State<DocumentExtractor>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PackageResponse>, (StatusCode, String)> {
+    let package = extractor.get_package(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Package not found".to_string()))?;
+    
+    Ok(Json(package.into()))
+}
+
 /// Extraction result response
 #[derive(Debug, Serialize)]
 pub struct ExtractionResultResponse {
@@ -158,12 +260,32 @@ pub struct ExtractResponse {
     pub needs_review: bool,
 }
 
+/// Header identifying the tenant for extraction fairness
+const TENANT_HEADER: &str = "x-tenant-id";
+
 async fn extract_data(
     State(extractor): State<DocumentExtractor>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ExtractResponse>, (StatusCode, String)> {
+    headers: HeaderMap,
+) -> Result<Json<ExtractResponse>, Response> {
+    let tenant = headers.get(TENANT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default");
+    
+    // Unknown documents shouldn't take up a worker slot
+    extractor.get_document(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Document not found".to_string()).into_response())?;
+    
+    let _permit = extractor.acquire_worker(tenant).await
+        .map_err(|e| (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, e.retry_after_secs.to_string())],
+            "Extraction queue is full".to_string(),
+        ).into_response())?;
+    
     let result = extractor.extract(id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
     
     Ok(Json(ExtractResponse {
         document_id: id,
@@ -189,4 +311,34 @@ async fn get_cas_numbers(
         .unwrap_or_default();
     
     Ok(Json(cas_numbers))
-}
\ No newline at end of file
+}
+
+/// Get first-page PNG thumbnail of document
+async fn get_thumbnail(
+    State(extractor): State<DocumentExtractor>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let doc = extractor.get_document(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Document not found".to_string()))?;
+    
+    let thumbnail = doc.thumbnail
+        .ok_or((StatusCode::NOT_FOUND, "Thumbnail not available".to_string()))?;
+    
+    Ok(([(header::CONTENT_TYPE, "image/png")], thumbnail))
+}
+
+/// Get PNG snippet showing where an extracted value was found
+async fn get_evidence_snippet(
+    State(extractor): State<DocumentExtractor>,
+    Path((id, evidence_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut doc = extractor.get_document(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Document not found".to_string()))?;
+    
+    let snippet = doc.snippets.remove(&evidence_id)
+        .ok_or((StatusCode::NOT_FOUND, "Evidence snippet not found".to_string()))?;
+    
+    Ok(([(header::CONTENT_TYPE, "image/png")], snippet))
+}
@@ -3,6 +3,9 @@
 //! Extracts text and images from PDF documents.
 
 use anyhow::{Context, Result};
+use pdfium_render::prelude::Pdfium;
+use std::sync::Arc;
+use tracing::warn;
 
 
 /// PDF processing result
@@ -44,6 +47,25 @@ pub struct PdfMetadata {
     pub page_count: usize,
 }
 
+/// Bind to the Pdfium library used for page rendering and text positions.
+///
+/// `PDFIUM_LIBRARY_PATH` may point at a directory containing the library;
+/// otherwise the system library path is searched.
+pub fn bind_pdfium() -> Option<Arc<Pdfium>> {
+    let bindings = match std::env::var("PDFIUM_LIBRARY_PATH") {
+        Ok(path) => Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&path)),
+        Err(_) => Pdfium::bind_to_system_library(),
+    };
+    
+    match bindings {
+        Ok(bindings) => Some(Arc::new(Pdfium::new(bindings))),
+        Err(e) => {
+            warn!("Pdfium not available, PDF rendering disabled: {}", e);
+            None
+        }
+    }
+}
+
 /// PDF processor
 pub struct PdfProcessor;
 
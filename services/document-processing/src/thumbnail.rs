@@ -0,0 +1,102 @@
+//! Thumbnail Generator
+//!
+//! Renders first-page PNG previews of uploaded documents for the review UI.
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, ImageFormat};
+use pdfium_render::prelude::*;
+use std::io::Cursor;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Maximum thumbnail width in pixels
+const THUMBNAIL_WIDTH: u32 = 256;
+/// Maximum thumbnail height in pixels
+const THUMBNAIL_HEIGHT: u32 = 362;
+
+/// Thumbnail generator
+pub struct ThumbnailGenerator {
+    pdfium: Option<Arc<Pdfium>>,
+}
+
+impl ThumbnailGenerator {
+    /// Create generator. Without Pdfium, PDF thumbnails are skipped
+    /// but image thumbnails still work.
+    pub fn new(pdfium: Option<Arc<Pdfium>>) -> Self {
+        Self { pdfium }
+    }
+
+    /// Generate a PNG thumbnail of the first page, if the file type is supported
+    pub fn generate(&self, file_type: &str, data: &[u8]) -> Option<Vec<u8>> {
+        let result = if file_type.contains("pdf") {
+            match &self.pdfium {
+                Some(pdfium) => self.render_pdf(pdfium, data),
+                None => return None,
+            }
+        } else if file_type.starts_with("image/") {
+            self.render_image(data)
+        } else {
+            return None;
+        };
+
+        match result {
+            Ok(png) => Some(png),
+            Err(e) => {
+                warn!("Thumbnail generation failed: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Render first PDF page
+    fn render_pdf(&self, pdfium: &Pdfium, data: &[u8]) -> Result<Vec<u8>> {
+        let document = pdfium.load_pdf_from_byte_slice(data, None)
+            .context("Failed to load PDF")?;
+        let page = document.pages().first()
+            .context("PDF has no pages")?;
+
+        let config = PdfRenderConfig::new()
+            .set_target_width(THUMBNAIL_WIDTH as Pixels)
+            .set_maximum_height(THUMBNAIL_HEIGHT as Pixels);
+        let bitmap = page.render_with_config(&config)
+            .context("Failed to render PDF page")?;
+
+        encode_png(&bitmap.as_image())
+    }
+
+    /// Downscale an uploaded image
+    fn render_image(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let image = image::load_from_memory(data)
+            .context("Failed to decode image")?;
+
+        encode_png(&image.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT))
+    }
+}
+
+/// Encode image as PNG bytes
+pub fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)
+        .context("Failed to encode thumbnail")?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    #[test]
+    fn test_image_thumbnail_is_downscaled_png() {
+        let source = DynamicImage::ImageRgb8(RgbImage::new(1024, 512));
+        let data = encode_png(&source).unwrap();
+
+        let generator = ThumbnailGenerator { pdfium: None };
+        let thumbnail = generator.generate("image/png", &data).unwrap();
+
+        let decoded = image::load_from_memory_with_format(&thumbnail, ImageFormat::Png).unwrap();
+        assert!(decoded.width() <= THUMBNAIL_WIDTH);
+        assert!(decoded.height() <= THUMBNAIL_HEIGHT);
+        assert!(generator.generate("text/plain", &data).is_none());
+    }
+}
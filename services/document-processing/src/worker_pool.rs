@@ -0,0 +1,290 @@
+//! Extraction Worker Pool
+//!
+//! Bounds concurrent extractions and queues the overflow per tenant.
+//! Queued work is dispatched round-robin across tenants so one large
+//! upload batch cannot starve everyone else. A finished extraction hands
+//! its permit straight to the next waiter, so a waiter that gives up before
+//! picking it up drops the permit and the slot is freed again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Worker pool configuration
+#[derive(Debug, Clone)]
+pub struct WorkerPoolConfig {
+    pub max_concurrency: usize,
+    pub max_queue_depth: usize,
+    pub retry_after_secs: u64,
+}
+
+impl Default for WorkerPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: std::env::var("EXTRACTION_MAX_CONCURRENCY")
+                .ok().and_then(|v| v.parse().ok()).unwrap_or(4),
+            max_queue_depth: std::env::var("EXTRACTION_MAX_QUEUE_DEPTH")
+                .ok().and_then(|v| v.parse().ok()).unwrap_or(32),
+            retry_after_secs: std::env::var("EXTRACTION_RETRY_AFTER_SECS")
+                .ok().and_then(|v| v.parse().ok()).unwrap_or(10),
+        }
+    }
+}
+
+/// Rejection when the queue is at capacity
+#[derive(Debug, Clone)]
+pub struct QueueFull {
+    pub retry_after_secs: u64,
+}
+
+struct Waiter {
+    id: u64,
+    sender: oneshot::Sender<WorkerPermit>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    running: usize,
+    queued: usize,
+    next_waiter: u64,
+    waiters: HashMap<String, VecDeque<Waiter>>,
+    /// Tenants with waiting work, in dispatch order
+    rotation: VecDeque<String>,
+}
+
+/// Bounded extraction worker pool
+#[derive(Clone)]
+pub struct WorkerPool {
+    config: WorkerPoolConfig,
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl WorkerPool {
+    pub fn new(config: WorkerPoolConfig) -> Self {
+        Self {
+            config: WorkerPoolConfig {
+                max_concurrency: config.max_concurrency.max(1),
+                ..config
+            },
+            state: Arc::new(Mutex::new(PoolState::default())),
+        }
+    }
+
+    /// Wait for a worker slot, or fail immediately if the queue is full
+    pub async fn acquire(&self, tenant: &str) -> Result<WorkerPermit, QueueFull> {
+        let (receiver, id) = {
+            let mut state = self.state.lock().expect("worker pool lock poisoned");
+
+            if state.running < self.config.max_concurrency && state.queued == 0 {
+                state.running += 1;
+                return Ok(WorkerPermit { pool: Some(self.clone()) });
+            }
+
+            if state.queued >= self.config.max_queue_depth {
+                return Err(QueueFull { retry_after_secs: self.config.retry_after_secs });
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let id = state.next_waiter;
+            state.next_waiter += 1;
+            let queue = state.waiters.entry(tenant.to_string()).or_default();
+            queue.push_back(Waiter { id, sender });
+            if queue.len() == 1 {
+                state.rotation.push_back(tenant.to_string());
+            }
+            state.queued += 1;
+            (receiver, id)
+        };
+
+        // Leaves the queue if this request is cancelled while waiting
+        let mut waiting = Waiting { pool: self, tenant, id, done: false };
+
+        // The releasing permit itself is handed over, so `running` is already counted
+        let permit = receiver.await;
+        waiting.done = true;
+        permit.map_err(|_| QueueFull { retry_after_secs: self.config.retry_after_secs })
+    }
+
+    /// Remove a cancelled waiter still in the queue
+    fn cancel(&self, tenant: &str, id: u64) {
+        let mut state = self.state.lock().expect("worker pool lock poisoned");
+
+        let Some(queue) = state.waiters.get_mut(tenant) else {
+            return;
+        };
+        let Some(position) = queue.iter().position(|w| w.id == id) else {
+            return;
+        };
+        queue.remove(position);
+        if queue.is_empty() {
+            state.waiters.remove(tenant);
+            state.rotation.retain(|t| t != tenant);
+        }
+        state.queued -= 1;
+    }
+
+    /// Hand a finished slot to the next tenant in rotation
+    fn release(&self) {
+        let mut state = self.state.lock().expect("worker pool lock poisoned");
+        let mut permit = WorkerPermit { pool: Some(self.clone()) };
+
+        while let Some(tenant) = state.rotation.pop_front() {
+            let Some(queue) = state.waiters.get_mut(&tenant) else {
+                continue;
+            };
+            let sender = queue.pop_front();
+            if queue.is_empty() {
+                state.waiters.remove(&tenant);
+            } else {
+                state.rotation.push_back(tenant);
+            }
+
+            if let Some(waiter) = sender {
+                state.queued -= 1;
+                // Waiter may have gone away (client disconnected); try the next one
+                match waiter.sender.send(permit) {
+                    Ok(()) => return,
+                    Err(returned) => permit = returned,
+                }
+            }
+        }
+
+        // Nobody to hand the slot to; free it without releasing again
+        permit.pool = None;
+        state.running -= 1;
+    }
+}
+
+impl Default for WorkerPool {
+    fn default() -> Self {
+        Self::new(WorkerPoolConfig::default())
+    }
+}
+
+/// Held while an extraction runs; frees the slot on drop
+pub struct WorkerPermit {
+    pool: Option<WorkerPool>,
+}
+
+impl Drop for WorkerPermit {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.release();
+        }
+    }
+}
+
+/// A queued `acquire` call, removed from the queue if it is dropped unfinished
+struct Waiting<'a> {
+    pool: &'a WorkerPool,
+    tenant: &'a str,
+    id: u64,
+    done: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.pool.cancel(self.tenant, self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(max_queue_depth: usize) -> WorkerPool {
+        WorkerPool::new(WorkerPoolConfig {
+            max_concurrency: 1,
+            max_queue_depth,
+            retry_after_secs: 5,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_queue_full() {
+        let pool = pool(1);
+        let running = pool.acquire("a").await.unwrap();
+
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire("a").await.map(|_| ()) }
+        });
+        tokio::task::yield_now().await;
+
+        let rejected = pool.acquire("b").await.err().unwrap();
+        assert_eq!(rejected.retry_after_secs, 5);
+
+        drop(running);
+        assert!(queued.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dispatches_round_robin_across_tenants() {
+        let pool = pool(10);
+        let running = pool.acquire("a").await.unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut handles = Vec::new();
+        for tenant in ["a", "a", "a", "b"] {
+            let (pool, order) = (pool.clone(), order.clone());
+            handles.push(tokio::spawn(async move {
+                let _permit = pool.acquire(tenant).await.unwrap();
+                order.lock().unwrap().push(tenant);
+            }));
+            tokio::task::yield_now().await;
+        }
+
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec!["a", "b", "a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiters_leave_the_queue() {
+        let pool = pool(1);
+        let running = pool.acquire("a").await.unwrap();
+
+        let waiter = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire("b").await.map(|_| ()) }
+        });
+        tokio::task::yield_now().await;
+        waiter.abort();
+        assert!(waiter.await.is_err());
+
+        // The cancelled waiter no longer takes up the queue
+        assert_eq!(pool.state.lock().unwrap().queued, 0);
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire("c").await.map(|_| ()) }
+        });
+        tokio::task::yield_now().await;
+        drop(running);
+        assert!(queued.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_slot_handed_to_cancelled_waiter_is_freed() {
+        let pool = pool(1);
+        let running = pool.acquire("a").await.unwrap();
+
+        let waiter = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire("b").await.map(|_| ()) }
+        });
+        tokio::task::yield_now().await;
+
+        // Hand the slot over, then cancel the waiter before it picks it up
+        drop(running);
+        waiter.abort();
+        assert!(waiter.await.is_err());
+
+        let state = pool.state.lock().unwrap();
+        assert_eq!((state.running, state.queued), (0, 0));
+    }
+}
@@ -0,0 +1,161 @@
+//! Template A/B Tests
+//!
+//! A campaign can split its suppliers between the template each email names
+//! (variant A) and an alternative template (variant B). Suppliers are ranked
+//! by a hash of campaign and supplier and the first share goes to B, so the
+//! split is exact and a supplier keeps its variant if the campaign is
+//! rescheduled. Response rates are then compared per variant.
+
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use elementa_models::{EmailCommunication, EmailDirection, ReplyClassification};
+
+pub const VARIANT_A: &str = "A";
+pub const VARIANT_B: &str = "B";
+
+/// Suppliers that get variant B, `percent_b` percent of the distinct suppliers (rounded)
+pub fn variant_b_suppliers(campaign_id: Uuid, suppliers: &[Uuid], percent_b: u8) -> HashSet<Uuid> {
+    let mut ranked: Vec<([u8; 32], Uuid)> = suppliers.iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|supplier_id| {
+            let rank = Sha256::new()
+                .chain_update(campaign_id.as_bytes())
+                .chain_update(supplier_id.as_bytes())
+                .finalize()
+                .into();
+            (rank, *supplier_id)
+        })
+        .collect();
+    ranked.sort();
+
+    let count = (ranked.len() * percent_b.min(100) as usize + 50) / 100;
+    ranked.into_iter().take(count).map(|(_, supplier_id)| supplier_id).collect()
+}
+
+/// Outcome of one variant
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct VariantStats {
+    pub variant: String,
+    pub template_id: Option<String>,
+    /// Suppliers the variant was sent to
+    pub suppliers: usize,
+    /// Suppliers who replied in person; out-of-office replies do not count
+    pub responded: usize,
+    /// Suppliers who sent documents or a complete or partial response
+    pub data_received: usize,
+    pub response_rate: f64,
+    pub data_rate: f64,
+}
+
+/// Per-variant results from all emails in a campaign's threads. Threads are
+/// attributed to the variant of their first tagged outbound email.
+pub fn variant_stats(emails: &[EmailCommunication]) -> Vec<VariantStats> {
+    let mut outbound: Vec<&EmailCommunication> = emails.iter()
+        .filter(|e| matches!(e.direction, EmailDirection::Outbound) && e.template_variant.is_some())
+        .collect();
+    outbound.sort_by_key(|e| e.created_at);
+
+    let mut thread_variants: HashMap<&str, &str> = HashMap::new();
+    let mut templates: HashMap<&str, &str> = HashMap::new();
+    let mut sent: HashMap<&str, HashSet<Uuid>> = HashMap::new();
+    for email in outbound {
+        let variant = email.template_variant.as_deref().unwrap_or(VARIANT_A);
+        thread_variants.entry(email.thread_id.as_str()).or_insert(variant);
+        if let Some(template_id) = &email.template_id {
+            templates.entry(variant).or_insert(template_id);
+        }
+        sent.entry(variant).or_default().insert(email.supplier_id.into());
+    }
+
+    let mut responded: HashMap<&str, HashSet<Uuid>> = HashMap::new();
+    let mut data_received: HashMap<&str, HashSet<Uuid>> = HashMap::new();
+    for email in emails.iter().filter(|e| matches!(e.direction, EmailDirection::Inbound)) {
+        let Some(&variant) = thread_variants.get(email.thread_id.as_str()) else {
+            continue;
+        };
+        if matches!(email.classification, Some(ReplyClassification::OutOfOffice)) {
+            continue;
+        }
+        responded.entry(variant).or_default().insert(email.supplier_id.into());
+        if !email.attachments.is_empty()
+            || matches!(email.classification, Some(ReplyClassification::CompleteResponse | ReplyClassification::PartialResponse))
+        {
+            data_received.entry(variant).or_default().insert(email.supplier_id.into());
+        }
+    }
+
+    let mut stats: Vec<VariantStats> = sent.into_iter()
+        .map(|(variant, suppliers)| {
+            let count = |by: &HashMap<&str, HashSet<Uuid>>| by.get(variant).map_or(0, |s| s.intersection(&suppliers).count());
+            let rate = |n: usize| n as f64 / suppliers.len() as f64;
+            let (responded, data_received) = (count(&responded), count(&data_received));
+            VariantStats {
+                variant: variant.to_string(),
+                template_id: templates.get(variant).map(|t| t.to_string()),
+                suppliers: suppliers.len(),
+                responded,
+                data_received,
+                response_rate: rate(responded),
+                data_rate: rate(data_received),
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| a.variant.cmp(&b.variant));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(direction: EmailDirection, thread: &str, supplier_id: Uuid, variant: Option<&str>) -> EmailCommunication {
+        EmailCommunication {
+            thread_id: thread.to_string(),
+            supplier_id: supplier_id.into(),
+            direction,
+            template_id: variant.map(|v| format!("pfas_request_{}", v.to_lowercase())),
+            template_variant: variant.map(str::to_string),
+            ..EmailCommunication::default()
+        }
+    }
+
+    #[test]
+    fn test_split_and_response_rates() {
+        let campaign_id = Uuid::new_v4();
+        let suppliers: Vec<Uuid> = (0..10).map(|_| Uuid::new_v4()).collect();
+        let variant_b = variant_b_suppliers(campaign_id, &suppliers, 30);
+        assert_eq!(variant_b.len(), 3);
+        assert_eq!(variant_b_suppliers(campaign_id, &suppliers, 30), variant_b);
+        assert_eq!(variant_b_suppliers(campaign_id, &[suppliers[0], suppliers[0], suppliers[1]], 50).len(), 1);
+
+        let (a1, a2, b1) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut documents = email(EmailDirection::Inbound, "t-b1", b1, None);
+        documents.classification = Some(ReplyClassification::CompleteResponse);
+        let mut away = email(EmailDirection::Inbound, "t-a1", a1, None);
+        away.classification = Some(ReplyClassification::OutOfOffice);
+        let mut question = email(EmailDirection::Inbound, "t-a2", a2, None);
+        question.classification = Some(ReplyClassification::Question);
+
+        let stats = variant_stats(&[
+            email(EmailDirection::Outbound, "t-a1", a1, Some("A")),
+            email(EmailDirection::Outbound, "t-a2", a2, Some("A")),
+            email(EmailDirection::Outbound, "t-b1", b1, Some("B")),
+            // Follow-up in the same thread without a variant of its own
+            email(EmailDirection::Outbound, "t-a1", a1, None),
+            away,
+            question,
+            documents,
+            email(EmailDirection::Inbound, "unrelated", a1, None),
+        ]);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].variant.as_str(), stats[0].suppliers, stats[0].responded, stats[0].data_received), ("A", 2, 1, 0));
+        assert_eq!(stats[0].response_rate, 0.5);
+        assert_eq!(stats[0].template_id.as_deref(), Some("pfas_request_a"));
+        assert_eq!((stats[1].variant.as_str(), stats[1].responded, stats[1].data_received), ("B", 1, 1));
+        assert_eq!(stats[1].data_rate, 1.0);
+    }
+}
@@ -0,0 +1,226 @@
+//! Address Validation
+//!
+//! Checks supplier addresses before anything is sent to them: syntax,
+//! disposable and placeholder domains (BOM exports are full of
+//! `n/a@example.com`), and, when DNS lookups are enabled, whether the
+//! domain accepts mail at all. Only definite failures block a send; a DNS
+//! lookup that times out leaves the address unverified.
+
+use chrono::{DateTime, Duration, Utc};
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+/// Throwaway-inbox providers
+const DISPOSABLE_DOMAINS: &[&str] = &[
+    "mailinator.com", "guerrillamail.com", "guerrillamail.net", "sharklasers.com", "10minutemail.com",
+    "tempmail.com", "temp-mail.org", "throwawaymail.com", "yopmail.com", "trashmail.com",
+    "getnada.com", "maildrop.cc", "dispostable.com", "fakeinbox.com", "mailnesia.com",
+];
+
+/// Filler domains that end up in BOM contact columns
+const PLACEHOLDER_DOMAINS: &[&str] = &[
+    "example.com", "example.org", "example.net", "test.com", "domain.com", "email.com",
+    "company.com", "noemail.com", "none.com", "na.com", "unknown.com", "localhost",
+];
+
+/// How long a domain's mail server lookup is reused
+const MX_CACHE_TTL_HOURS: i64 = 6;
+
+/// Send blocked because the recipient address cannot receive email
+#[derive(Debug)]
+pub struct UndeliverableAddress {
+    pub email_address: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for UndeliverableAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} cannot receive email: {}", self.email_address, self.reason)
+    }
+}
+
+impl std::error::Error for UndeliverableAddress {}
+
+/// Validation result for one address
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddressCheck {
+    pub email_address: String,
+    /// `valid`, `unverified`, `invalid_syntax`, `disposable_domain`, `blocked_domain` or `no_mail_server`
+    pub status: String,
+    pub detail: Option<String>,
+}
+
+impl AddressCheck {
+    fn new(email_address: &str, status: &str, detail: Option<String>) -> Self {
+        Self { email_address: email_address.to_string(), status: status.to_string(), detail }
+    }
+
+    /// Whether sending to the address can succeed; unverified addresses get the benefit of the doubt
+    pub fn deliverable(&self) -> bool {
+        matches!(self.status.as_str(), "valid" | "unverified")
+    }
+
+    pub fn into_error(self) -> UndeliverableAddress {
+        UndeliverableAddress {
+            reason: self.detail.unwrap_or_else(|| self.status.replace('_', " ")),
+            email_address: self.email_address,
+        }
+    }
+}
+
+/// Whether a domain accepts mail
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MailDomain {
+    Accepts,
+    DoesNotExist,
+    /// RFC 7505 null MX, or no MX and no address records
+    NoMailServer,
+}
+
+pub struct AddressValidator {
+    blocked_domains: HashSet<String>,
+    resolver: Option<TokioAsyncResolver>,
+    mx_cache: RwLock<HashMap<String, (MailDomain, DateTime<Utc>)>>,
+}
+
+impl AddressValidator {
+    /// Syntax and domain-list checks only
+    pub fn new(blocked_domains: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            blocked_domains: blocked_domains.into_iter().map(|d| d.trim().to_lowercase()).collect(),
+            resolver: None,
+            mx_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// `BLOCKED_EMAIL_DOMAINS` (comma-separated) on top of the built-in lists
+    pub fn from_env() -> Self {
+        let blocked = std::env::var("BLOCKED_EMAIL_DOMAINS").unwrap_or_default();
+        Self::new(blocked.split(',').filter(|d| !d.trim().is_empty()).map(str::to_string).collect::<Vec<_>>())
+    }
+
+    /// Also require the domain to publish an MX record, or an address record as implicit MX
+    pub fn with_mx_lookups(mut self) -> Self {
+        self.resolver = Some(TokioAsyncResolver::tokio_from_system_conf()
+            .unwrap_or_else(|_| TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())));
+        self
+    }
+
+    pub async fn check(&self, email_address: &str) -> AddressCheck {
+        let address = email_address.trim().to_lowercase();
+        if let Err(problem) = check_syntax(&address) {
+            return AddressCheck::new(&address, "invalid_syntax", Some(problem.to_string()));
+        }
+
+        let domain = address.rsplit_once('@').map(|(_, domain)| domain).unwrap_or_default();
+        if DISPOSABLE_DOMAINS.contains(&domain) {
+            return AddressCheck::new(&address, "disposable_domain", Some(format!("{} is a disposable email provider", domain)));
+        }
+        if PLACEHOLDER_DOMAINS.contains(&domain) || self.is_blocked(domain) {
+            return AddressCheck::new(&address, "blocked_domain", Some(format!("{} is not a real supplier domain", domain)));
+        }
+
+        match self.mail_domain(domain).await {
+            None => AddressCheck::new(&address, if self.resolver.is_some() { "unverified" } else { "valid" }, None),
+            Some(MailDomain::Accepts) => AddressCheck::new(&address, "valid", None),
+            Some(MailDomain::DoesNotExist) => {
+                AddressCheck::new(&address, "no_mail_server", Some(format!("{} does not exist", domain)))
+            }
+            Some(MailDomain::NoMailServer) => {
+                AddressCheck::new(&address, "no_mail_server", Some(format!("{} does not accept email", domain)))
+            }
+        }
+    }
+
+    /// Blocked domains cover their subdomains too
+    fn is_blocked(&self, domain: &str) -> bool {
+        self.blocked_domains.iter().any(|blocked| {
+            domain == blocked || domain.strip_suffix(blocked.as_str()).is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+
+    /// `None` when lookups are disabled or inconclusive
+    async fn mail_domain(&self, domain: &str) -> Option<MailDomain> {
+        let resolver = self.resolver.as_ref()?;
+        let now = Utc::now();
+        if let Some((found, at)) = self.mx_cache.read().await.get(domain) {
+            if now - *at < Duration::hours(MX_CACHE_TTL_HOURS) {
+                return Some(*found);
+            }
+        }
+
+        let found = match resolver.mx_lookup(domain).await {
+            // A single MX of "." is the RFC 7505 "no mail here" record
+            Ok(lookup) if lookup.iter().all(|mx| mx.exchange().is_root()) => MailDomain::NoMailServer,
+            Ok(_) => MailDomain::Accepts,
+            Err(e) => match e.kind() {
+                ResolveErrorKind::NoRecordsFound { response_code: ResponseCode::NXDomain, .. } => MailDomain::DoesNotExist,
+                ResolveErrorKind::NoRecordsFound { .. } => match resolver.lookup_ip(domain).await {
+                    Ok(_) => MailDomain::Accepts,
+                    Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => MailDomain::NoMailServer,
+                    Err(_) => return None,
+                },
+                _ => return None,
+            },
+        };
+        self.mx_cache.write().await.insert(domain.to_string(), (found, now));
+        Some(found)
+    }
+}
+
+impl Default for AddressValidator {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// RFC 5321 limits plus the shared format check
+fn check_syntax(address: &str) -> Result<(), &'static str> {
+    let (local, domain) = address.rsplit_once('@').ok_or("missing @")?;
+    if local.is_empty() || local.len() > 64 {
+        return Err("local part must be 1 to 64 characters");
+    }
+    if address.len() > 254 {
+        return Err("address is longer than 254 characters");
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err("local part has a misplaced dot");
+    }
+    if elementa_utils::validate_email_address(address).is_err() {
+        return Err("not a valid email address");
+    }
+    match domain.rsplit_once('.') {
+        Some((_, tld)) if tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()) => Ok(()),
+        _ => Err("domain has no top-level domain"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_syntax_and_domain_lists() {
+        let validator = AddressValidator::new(["Acme-Internal.example".to_string()]);
+        let status = |check: AddressCheck| check.status;
+
+        assert_eq!(status(validator.check(" QA@Supplier.example ").await), "valid");
+        assert_eq!(validator.check(" QA@Supplier.example ").await.email_address, "qa@supplier.example");
+        assert_eq!(status(validator.check("qa.@supplier.example").await), "invalid_syntax");
+        assert_eq!(status(validator.check("qa@supplier").await), "invalid_syntax");
+        assert_eq!(status(validator.check("n/a").await), "invalid_syntax");
+        assert_eq!(status(validator.check("buyer@yopmail.com").await), "disposable_domain");
+        assert_eq!(status(validator.check("contact@example.com").await), "blocked_domain");
+        assert_eq!(status(validator.check("qa@eu.acme-internal.example").await), "blocked_domain");
+        assert_eq!(status(validator.check("qa@notacme-internal.example").await), "valid");
+
+        let blocked = validator.check("sales@mailinator.com").await;
+        assert!(!blocked.deliverable());
+        assert_eq!(blocked.into_error().to_string(), "sales@mailinator.com cannot receive email: mailinator.com is a disposable email provider");
+    }
+}
@@ -0,0 +1,116 @@
+//! Tenant Branding
+//!
+//! Per-tenant header, logo and footer injected into rendered emails, so
+//! outreach carries the client's branding instead of the generic Elementa
+//! footer. Branding reaches the template engine as the `branding` variable.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Header colour when a tenant sets a logo or header text but no colour
+const DEFAULT_PRIMARY_COLOR: &str = "#2563eb";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenantBranding {
+    /// HTTPS URL of the logo shown in the header
+    pub logo_url: Option<String>,
+    pub header_text: Option<String>,
+    /// Replaces the Elementa footer; may span several lines
+    pub footer_text: Option<String>,
+    /// Header background as `#rgb` or `#rrggbb`
+    pub primary_color: Option<String>,
+}
+
+impl TenantBranding {
+    /// Reject values that would break out of the injected markup
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = &self.logo_url {
+            if !url.starts_with("https://") || url.contains(['"', '<', '>', ' ']) {
+                bail!("logo_url must be an https URL");
+            }
+        }
+        if let Some(color) = &self.primary_color {
+            let hex = color.strip_prefix('#').unwrap_or_default();
+            if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                bail!("primary_color must be a hex colour such as #1f2937");
+            }
+        }
+        Ok(())
+    }
+
+    /// Header block placed right after `<body>`, if there is anything to show
+    pub fn header_html(&self) -> Option<String> {
+        if self.logo_url.is_none() && self.header_text.is_none() {
+            return None;
+        }
+
+        let mut header = format!(
+            "<div class=\"brand-header\" style=\"background:{};color:white;padding:20px;\">",
+            self.primary_color.as_deref().unwrap_or(DEFAULT_PRIMARY_COLOR)
+        );
+        if let Some(url) = &self.logo_url {
+            header.push_str(&format!("<img src=\"{}\" alt=\"\" style=\"max-height:48px;\">", url));
+        }
+        if let Some(text) = &self.header_text {
+            header.push_str(&format!("<h2 style=\"margin:0;\">{}</h2>", handlebars::html_escape(text)));
+        }
+        header.push_str("</div>\n");
+        Some(header)
+    }
+
+    /// Footer block placed before `</body>`
+    pub fn footer_html(&self) -> Option<String> {
+        let footer = self.footer_text.as_ref()?;
+        let lines: Vec<String> = footer.lines().map(handlebars::html_escape).collect();
+        Some(format!(
+            "<div class=\"brand-footer\" style=\"background:#f3f4f6;padding:20px;font-size:12px;\">{}</div>\n",
+            lines.join("<br>")
+        ))
+    }
+}
+
+/// Branding keyed by tenant; tenants without one get the Elementa defaults
+#[derive(Default)]
+pub struct BrandingRegistry {
+    tenants: RwLock<HashMap<String, TenantBranding>>,
+}
+
+impl BrandingRegistry {
+    pub async fn set(&self, tenant: &str, branding: TenantBranding) {
+        self.tenants.write().await.insert(tenant.to_string(), branding);
+    }
+
+    pub async fn get(&self, tenant: &str) -> Option<TenantBranding> {
+        self.tenants.read().await.get(tenant).cloned()
+    }
+
+    pub async fn remove(&self, tenant: &str) -> bool {
+        self.tenants.write().await.remove(tenant).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branding_rejects_markup_injection() {
+        let branding = TenantBranding {
+            logo_url: Some("https://cdn.acme.example/logo.png".to_string()),
+            header_text: Some("Acme <Compliance>".to_string()),
+            footer_text: Some("Acme Corp\n1 Main St".to_string()),
+            primary_color: Some("#1F2937".to_string()),
+        };
+        assert!(branding.validate().is_ok());
+        let header = branding.header_html().unwrap();
+        assert!(header.contains("background:#1F2937") && header.contains("Acme &lt;Compliance&gt;"));
+        assert_eq!(branding.footer_html().unwrap().matches("<br>").count(), 1);
+
+        let bad_logo = TenantBranding { logo_url: Some("javascript:alert(1)".to_string()), ..branding.clone() };
+        assert!(bad_logo.validate().is_err());
+        let bad_color = TenantBranding { primary_color: Some("red;display:none".to_string()), ..branding };
+        assert!(bad_color.validate().is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Deadline Calendar Events
+//!
+//! RFC 5545 `.ics` attachments that put a compliance response deadline in
+//! the supplier contact's own calendar. The event UID is derived from the
+//! thread, so a follow-up with a moved deadline updates the original event
+//! instead of adding a second one.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::email_provider::EmailAttachment;
+
+/// Days before the deadline the calendar reminder fires
+const REMINDER_DAYS: i64 = 3;
+
+/// Response deadline, either a whole day or an exact time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deadline {
+    Date(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+impl Deadline {
+    /// Accepts RFC 3339 timestamps and `YYYY-MM-DD` dates
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Self::At(at.with_timezone(&Utc)));
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Self::Date)
+            .with_context(|| format!("Invalid deadline {:?}; expected RFC 3339 or YYYY-MM-DD", value))
+    }
+
+    /// Date shown to the supplier in the email body
+    pub fn display_date(&self) -> String {
+        match self {
+            Self::Date(date) => date.format("%Y-%m-%d").to_string(),
+            Self::At(at) => at.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Everything the event needs besides the deadline itself
+pub struct DeadlineEvent<'a> {
+    /// Stable per thread, e.g. `thread_…@acme.example`
+    pub uid: &'a str,
+    pub summary: &'a str,
+    pub description: &'a str,
+    pub organizer_email: &'a str,
+    pub organizer_name: &'a str,
+}
+
+/// `.ics` attachment for a deadline event
+pub fn deadline_attachment(deadline: Deadline, event: &DeadlineEvent, now: DateTime<Utc>) -> EmailAttachment {
+    EmailAttachment {
+        filename: "deadline.ics".to_string(),
+        content_type: "text/calendar; charset=utf-8; method=PUBLISH".to_string(),
+        data: deadline_ics(deadline, event, now).into_bytes(),
+    }
+}
+
+/// Calendar with a single event on the deadline and a reminder a few days ahead
+pub fn deadline_ics(deadline: Deadline, event: &DeadlineEvent, now: DateTime<Utc>) -> String {
+    let timestamp = |at: DateTime<Utc>| at.format("%Y%m%dT%H%M%SZ").to_string();
+    let (start, end) = match deadline {
+        Deadline::Date(date) => (
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", (date + Duration::days(1)).format("%Y%m%d")),
+        ),
+        Deadline::At(at) => (
+            format!("DTSTART:{}", timestamp(at)),
+            format!("DTEND:{}", timestamp(at + Duration::minutes(30))),
+        ),
+    };
+
+    let lines = [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Elementa//Compliance Deadlines//EN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", timestamp(now)),
+        start,
+        end,
+        format!("SUMMARY:{}", escape_text(event.summary)),
+        format!("DESCRIPTION:{}", escape_text(event.description)),
+        format!("ORGANIZER;CN={}:mailto:{}", quote_param(event.organizer_name), event.organizer_email),
+        "TRANSP:TRANSPARENT".to_string(),
+        "BEGIN:VALARM".to_string(),
+        "ACTION:DISPLAY".to_string(),
+        format!("TRIGGER:-P{}D", REMINDER_DAYS),
+        format!("DESCRIPTION:{}", escape_text(event.summary)),
+        "END:VALARM".to_string(),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ];
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11)
+fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Parameter value, quoted and stripped of characters it cannot contain
+fn quote_param(value: &str) -> String {
+    format!("\"{}\"", value.replace(['"', '\r', '\n'], ""))
+}
+
+/// Fold a content line at 75 octets without splitting a UTF-8 character,
+/// terminating it with CRLF
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_event_is_valid_ics() {
+        let now = Utc::now();
+        let event = DeadlineEvent {
+            uid: "thread_1@acme.example",
+            summary: "PFAS data due: Acme, Inc.",
+            description: "Reference REF-1; upload at https://mail.elementa.io/api/v1/uploads/abc",
+            organizer_email: "compliance@acme.example",
+            organizer_name: "Acme \"Compliance\"",
+        };
+
+        let ics = deadline_ics(Deadline::parse("2026-12-01").unwrap(), &event, now);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261201\r\nDTEND;VALUE=DATE:20261202\r\n"));
+        assert!(ics.contains("SUMMARY:PFAS data due: Acme\\, Inc.\r\n"));
+        assert!(ics.contains("ORGANIZER;CN=\"Acme Compliance\":mailto:compliance@acme.example"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+        assert!(ics.lines().any(|line| line.starts_with(' ')));
+
+        let timed = Deadline::parse("2026-12-01T17:00:00+01:00").unwrap();
+        assert_eq!(timed.display_date(), "2026-12-01");
+        assert!(deadline_ics(timed, &event, now).contains("DTSTART:20261201T160000Z\r\n"));
+        assert!(Deadline::parse("next week").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Reply Classifier
+//!
+//! Categorizes inbound supplier replies with keyword rules, deferring
+//! to an optional LLM when the rules are not confident.
+
+use anyhow::{Context, Result};
+use elementa_models::{EventClassification, ReplyClassification};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+
+/// Rule confidence below which the LLM is consulted
+const LLM_THRESHOLD: f64 = 0.7;
+
+const OUT_OF_OFFICE_SUBJECTS: &[&str] = &[
+    "out of office", "out of the office", "automatic reply", "auto-reply", "autoreply", "away from",
+];
+const OUT_OF_OFFICE_PHRASES: &[&str] = &[
+    "out of the office", "out of office", "on vacation", "on holiday", "on leave",
+    "limited access to email", "will return on", "back in the office",
+];
+const WRONG_CONTACT_PHRASES: &[&str] = &[
+    "no longer with", "no longer work", "left the company", "not the right person",
+    "wrong person", "not responsible for", "please contact", "please reach out to", "forwarded your request",
+];
+const REFUSAL_PHRASES: &[&str] = &[
+    "unable to provide", "cannot provide", "can't provide", "will not provide", "won't provide",
+    "not able to share", "unable to share", "cannot share", "must decline", "we decline",
+    "proprietary", "trade secret", "confidential business information",
+];
+const PARTIAL_PHRASES: &[&str] = &[
+    "remaining", "rest of the", "will follow", "to follow", "still working", "still waiting",
+    "partial", "some of the", "outstanding", "missing", "next week",
+];
+const QUESTION_PHRASES: &[&str] = &[
+    "could you clarify", "can you clarify", "what do you need", "which parts", "which components",
+    "what format", "is there a template", "do you need",
+];
+
+/// Classification result
+#[derive(Debug, Clone, Serialize)]
+pub struct Classification {
+    pub category: ReplyClassification,
+    pub confidence: f64,
+    /// `rules`, `llm` or `headers`
+    pub method: String,
+}
+
+impl Classification {
+    fn rules(category: ReplyClassification, confidence: f64) -> Self {
+        Self { category, confidence, method: "rules".to_string() }
+    }
+
+    /// Reply whose headers mark it as automatically generated
+    pub fn auto_reply() -> Self {
+        Self { category: ReplyClassification::OutOfOffice, confidence: 1.0, method: "headers".to_string() }
+    }
+}
+
+impl From<Classification> for EventClassification {
+    fn from(classification: Classification) -> Self {
+        Self {
+            category: classification.category,
+            confidence: classification.confidence,
+            method: classification.method,
+        }
+    }
+}
+
+/// LLM configuration for ambiguous replies
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub api_url: String,
+    pub api_key: String,
+    pub model: String,
+}
+
+impl LlmConfig {
+    /// Load from environment; `None` when no API key is configured
+    pub fn from_env() -> Option<Self> {
+        let api_key = std::env::var("CLASSIFIER_LLM_API_KEY")
+            .or_else(|_| std::env::var("OPENAI_API_KEY"))
+            .ok()
+            .filter(|key| !key.is_empty())?;
+
+        Some(Self {
+            api_url: std::env::var("CLASSIFIER_LLM_URL")
+                .unwrap_or_else(|_| "https://api.openai.com/v1/chat/completions".to_string()),
+            api_key,
+            model: std::env::var("CLASSIFIER_LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+        })
+    }
+}
+
+/// Classifies supplier replies
+pub struct ReplyClassifier {
+    client: Client,
+    llm: Option<LlmConfig>,
+}
+
+impl ReplyClassifier {
+    pub fn new(llm: Option<LlmConfig>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, llm }
+    }
+
+    /// Classify a reply. LLM failures fall back to the rule result.
+    pub async fn classify(&self, subject: &str, body: &str, attachment_count: usize) -> Classification {
+        let rules = classify_with_rules(subject, body, attachment_count);
+        if rules.confidence >= LLM_THRESHOLD {
+            return rules;
+        }
+
+        let Some(llm) = &self.llm else {
+            return rules;
+        };
+
+        match self.classify_with_llm(llm, subject, body, attachment_count).await {
+            Ok(classification) => classification,
+            Err(e) => {
+                warn!("LLM reply classification failed, using rules: {:#}", e);
+                rules
+            }
+        }
+    }
+
+    async fn classify_with_llm(
+        &self,
+        llm: &LlmConfig,
+        subject: &str,
+        body: &str,
+        attachment_count: usize,
+    ) -> Result<Classification> {
+        let request = serde_json::json!({
+            "model": llm.model,
+            "temperature": 0.0,
+            "response_format": { "type": "json_object" },
+            "messages": [
+                { "role": "system", "content": CLASSIFICATION_PROMPT },
+                {
+                    "role": "user",
+                    "content": format!(
+                        "Subject: {}\nAttachments: {}\n\n{}",
+                        subject, attachment_count, strip_quoted(body)
+                    ),
+                },
+            ],
+        });
+
+        let response: ChatResponse = self.client
+            .post(&llm.api_url)
+            .bearer_auth(&llm.api_key)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to reach LLM API")?
+            .error_for_status()
+            .context("LLM API error")?
+            .json()
+            .await
+            .context("Invalid LLM response")?;
+
+        let content = response.choices.first()
+            .map(|c| c.message.content.as_str())
+            .context("No response content")?;
+        let answer: LlmAnswer = serde_json::from_str(content)
+            .context("Failed to parse classification JSON")?;
+
+        Ok(Classification {
+            category: answer.category,
+            confidence: answer.confidence.clamp(0.0, 1.0),
+            method: "llm".to_string(),
+        })
+    }
+}
+
+impl Default for ReplyClassifier {
+    fn default() -> Self {
+        Self::new(LlmConfig::from_env())
+    }
+}
+
+/// Keyword classification of the reply's own text (quoted history is ignored)
+pub fn classify_with_rules(subject: &str, body: &str, attachment_count: usize) -> Classification {
+    let subject = subject.to_lowercase();
+    let text = strip_quoted(body).to_lowercase();
+    let mentions = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+
+    if OUT_OF_OFFICE_SUBJECTS.iter().any(|p| subject.contains(p)) {
+        return Classification::rules(ReplyClassification::OutOfOffice, 0.95);
+    }
+    if attachment_count == 0 && mentions(OUT_OF_OFFICE_PHRASES) {
+        return Classification::rules(ReplyClassification::OutOfOffice, 0.8);
+    }
+    if attachment_count == 0 && mentions(WRONG_CONTACT_PHRASES) {
+        return Classification::rules(ReplyClassification::WrongContact, 0.8);
+    }
+    if mentions(REFUSAL_PHRASES) {
+        let confidence = if attachment_count == 0 { 0.8 } else { 0.5 };
+        return Classification::rules(ReplyClassification::Refusal, confidence);
+    }
+
+    if attachment_count > 0 {
+        return if mentions(PARTIAL_PHRASES) {
+            Classification::rules(ReplyClassification::PartialResponse, 0.75)
+        } else {
+            Classification::rules(ReplyClassification::CompleteResponse, 0.7)
+        };
+    }
+
+    if mentions(QUESTION_PHRASES) {
+        return Classification::rules(ReplyClassification::Question, 0.8);
+    }
+    if text.contains('?') {
+        return Classification::rules(ReplyClassification::Question, 0.6);
+    }
+
+    // No data and no recognizable intent; left to the LLM, and too unsure
+    // for the workflow to escalate as a question
+    Classification::rules(ReplyClassification::Question, 0.3)
+}
+
+/// Drop quoted history: `>` lines and everything after an "On ... wrote:" marker
+pub fn strip_quoted(body: &str) -> String {
+    let mut lines = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("-----Original Message-----")
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+        {
+            break;
+        }
+        if !trimmed.starts_with('>') {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+const CLASSIFICATION_PROMPT: &str = r#"You classify supplier replies to chemical compliance data requests (PFAS, REACH, RoHS).
+Respond with JSON: {"category": <category>, "confidence": <0.0-1.0>}
+Categories:
+- "CompleteResponse": the supplier provided all requested data or declarations
+- "PartialResponse": some data provided, more promised or missing
+- "Question": the supplier asks for clarification before responding
+- "Refusal": the supplier declines to provide the data
+- "OutOfOffice": automatic absence reply
+- "WrongContact": the recipient is not the right person or has left"#;
+
+/// Chat completions response, shared with the thread summarizer
+#[derive(Debug, Deserialize)]
+pub struct ChatResponse {
+    pub choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatChoice {
+    pub message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatMessage {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmAnswer {
+    category: ReplyClassification,
+    confidence: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_classification() {
+        let category = |subject: &str, body: &str, attachments: usize| {
+            classify_with_rules(subject, body, attachments).category
+        };
+
+        assert_eq!(category("Automatic reply: PFAS request", "", 0), ReplyClassification::OutOfOffice);
+        assert_eq!(
+            category("Re: PFAS request", "John no longer works here, please contact jane@acme.example", 0),
+            ReplyClassification::WrongContact,
+        );
+        assert_eq!(
+            category("Re: PFAS request", "Our formulations are proprietary and we cannot provide CAS numbers.", 0),
+            ReplyClassification::Refusal,
+        );
+        assert_eq!(category("Re: PFAS request", "Please find the SDS attached.", 1), ReplyClassification::CompleteResponse);
+        assert_eq!(
+            category("Re: PFAS request", "Attached are two SDS, the remaining ones will follow next week.", 2),
+            ReplyClassification::PartialResponse,
+        );
+        assert_eq!(
+            category("Re: PFAS request", "Which parts does this cover?\n\nOn Mon, Elementa wrote:\n> Please attach the SDS", 0),
+            ReplyClassification::Question,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Delivery Retry
+//!
+//! Retry policy for handing emails to the provider. Transient failures,
+//! such as SMTP 4xx replies, dropped connections and provider throttling,
+//! are retried with exponential backoff and jitter. Anything else fails
+//! on the first attempt.
+
+use rand::Rng;
+use std::time::Duration;
+
+/// Provider failure that is worth retrying
+#[derive(Debug)]
+pub struct TransientFailure(pub String);
+
+impl std::fmt::Display for TransientFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransientFailure {}
+
+/// Whether a delivery error may clear up on retry
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if cause.is::<TransientFailure>() {
+            return true;
+        }
+        if let Some(smtp) = cause.downcast_ref::<lettre::transport::smtp::Error>() {
+            // Connection-level failures carry no reply code
+            return smtp.is_transient()
+                || smtp.is_timeout()
+                || (smtp.status().is_none() && !smtp.is_client() && !smtp.is_response() && !smtp.is_tls());
+        }
+        if let Some(http) = cause.downcast_ref::<reqwest::Error>() {
+            return http.is_timeout() || http.is_connect();
+        }
+        false
+    })
+}
+
+/// Retry policy configuration
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// `SEND_RETRY_MAX_ATTEMPTS`, `SEND_RETRY_BASE_MS` and `SEND_RETRY_MAX_MS`
+    pub fn from_env() -> Self {
+        let env_u64 = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            max_attempts: env_u64("SEND_RETRY_MAX_ATTEMPTS", 4).max(1) as u32,
+            base_delay: Duration::from_millis(env_u64("SEND_RETRY_BASE_MS", 1_000)),
+            max_delay: Duration::from_millis(env_u64("SEND_RETRY_MAX_MS", 30_000)),
+        }
+    }
+
+    /// Backoff ceiling after the given attempt: base doubled per attempt, capped at `max_delay`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Delay before retrying after the given attempt. Half the backoff is
+    /// fixed and half random, so concurrent failures do not retry in lockstep.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        let half = backoff / 2;
+        half + (backoff - half).mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap_with_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+
+        for _ in 0..20 {
+            let delay = policy.delay(3);
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+
+        let transient: anyhow::Result<()> = Err(TransientFailure("451 try later".to_string()).into());
+        assert!(is_transient(&transient.context("Failed to send email").unwrap_err()));
+        assert!(!is_transient(&anyhow::anyhow!("550 mailbox unavailable")));
+    }
+}
@@ -0,0 +1,347 @@
+//! Delivery Webhooks
+//!
+//! Verifies SendGrid and Amazon SES (via SNS) event webhooks and maps their
+//! delivery, open, bounce and complaint events onto `DeliveryStatus`.
+
+use anyhow::{bail, Context, Result};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::Utc;
+use elementa_models::DeliveryStatus;
+use p256::ecdsa::signature::Verifier as _;
+use p256::pkcs8::DecodePublicKey as _;
+use regex::Regex;
+use reqwest::{Client, Url};
+use rsa::pkcs1v15;
+use rsa::RsaPublicKey;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::warn;
+use x509_cert::der::{DecodePem as _, Encode as _};
+use x509_cert::Certificate;
+
+use crate::email_provider::normalize_message_id;
+
+/// Custom argument carrying our Message-ID through SendGrid events
+pub const SENDGRID_MESSAGE_ID_ARG: &str = "elementa_message_id";
+
+/// Maximum age of a signed SendGrid payload
+const SENDGRID_MAX_SKEW_SECS: i64 = 600;
+
+/// Provider event mapped to a delivery status
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryEvent {
+    /// Message-ID of the outbound email, without angle brackets
+    pub message_id: String,
+    pub status: DeliveryStatus,
+    /// Provider event name, e.g. `delivered` or `Bounce`
+    pub event: String,
+}
+
+/// Whether `new` should replace `current`. Statuses only move forward, so a
+/// late `delivered` never masks a bounce or complaint.
+pub fn supersedes(new: DeliveryStatus, current: DeliveryStatus) -> bool {
+    let rank = |status: DeliveryStatus| match status {
+        DeliveryStatus::Pending => 0,
+        DeliveryStatus::Sent => 1,
+        DeliveryStatus::Delivered => 2,
+        DeliveryStatus::Bounced | DeliveryStatus::Failed | DeliveryStatus::SpamFiltered => 3,
+    };
+    rank(new) > rank(current)
+}
+
+/// Webhook signature verification
+pub struct WebhookVerifier {
+    client: Client,
+    sendgrid_key: Option<p256::ecdsa::VerifyingKey>,
+    /// SNS topics allowed to deliver SES events
+    sns_topics: Vec<String>,
+    sns_certs: RwLock<HashMap<String, RsaPublicKey>>,
+}
+
+impl WebhookVerifier {
+    pub fn new(sendgrid_key: Option<p256::ecdsa::VerifyingKey>, sns_topics: Vec<String>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, sendgrid_key, sns_topics, sns_certs: RwLock::new(HashMap::new()) }
+    }
+
+    /// `SENDGRID_WEBHOOK_PUBLIC_KEY` is the base64 key from SendGrid's Signed
+    /// Event Webhook settings; `SES_SNS_TOPIC_ARNS` is a comma-separated allowlist
+    pub fn from_env() -> Self {
+        let sendgrid_key = std::env::var("SENDGRID_WEBHOOK_PUBLIC_KEY").ok()
+            .filter(|key| !key.is_empty())
+            .and_then(|key| match parse_sendgrid_key(&key) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    warn!("SendGrid webhook disabled: {:#}", e);
+                    None
+                }
+            });
+        let sns_topics = std::env::var("SES_SNS_TOPIC_ARNS").unwrap_or_default()
+            .split(',')
+            .map(|arn| arn.trim().to_string())
+            .filter(|arn| !arn.is_empty())
+            .collect();
+
+        Self::new(sendgrid_key, sns_topics)
+    }
+
+    /// Verify SendGrid's ECDSA signature over timestamp + raw body
+    pub fn verify_sendgrid(&self, signature: &str, timestamp: &str, body: &[u8]) -> Result<()> {
+        let key = self.sendgrid_key.as_ref().context("SendGrid webhook key not configured")?;
+
+        let sent_at: i64 = timestamp.parse().context("Invalid webhook timestamp")?;
+        if (Utc::now().timestamp() - sent_at).abs() > SENDGRID_MAX_SKEW_SECS {
+            bail!("Webhook timestamp outside allowed window");
+        }
+
+        let signature = BASE64.decode(signature).context("Invalid signature encoding")?;
+        let signature = p256::ecdsa::Signature::from_der(&signature).context("Invalid signature")?;
+
+        let mut signed = timestamp.as_bytes().to_vec();
+        signed.extend_from_slice(body);
+        key.verify(&signed, &signature).context("Signature mismatch")
+    }
+
+    /// Verify an SNS message signature against the AWS signing certificate
+    pub async fn verify_sns(&self, message: &SnsMessage) -> Result<()> {
+        if !self.sns_topics.contains(&message.topic_arn) {
+            bail!("SNS topic {} is not allowed", message.topic_arn);
+        }
+
+        let key = self.sns_certificate(&message.signing_cert_url).await?;
+        let signature = BASE64.decode(&message.signature).context("Invalid signature encoding")?;
+        let signature = pkcs1v15::Signature::try_from(signature.as_slice()).context("Invalid signature")?;
+        let signed = message.string_to_sign()?;
+
+        match message.signature_version.as_str() {
+            "1" => pkcs1v15::VerifyingKey::<sha1::Sha1>::new(key).verify(signed.as_bytes(), &signature),
+            "2" => pkcs1v15::VerifyingKey::<sha2::Sha256>::new(key).verify(signed.as_bytes(), &signature),
+            other => bail!("Unsupported SNS signature version {}", other),
+        }
+        .context("Signature mismatch")
+    }
+
+    /// Confirm an SNS subscription so SES events start flowing
+    pub async fn confirm_subscription(&self, message: &SnsMessage) -> Result<()> {
+        let url = message.subscribe_url.as_deref().context("Missing SubscribeURL")?;
+        let url = Url::parse(url).context("Invalid SubscribeURL")?;
+        if !is_sns_host(&url) {
+            bail!("SubscribeURL is not an SNS endpoint");
+        }
+
+        self.client.get(url)
+            .send()
+            .await
+            .context("Failed to reach SNS")?
+            .error_for_status()
+            .context("SNS rejected subscription confirmation")?;
+
+        Ok(())
+    }
+
+    async fn sns_certificate(&self, cert_url: &str) -> Result<RsaPublicKey> {
+        if let Some(key) = self.sns_certs.read().await.get(cert_url) {
+            return Ok(key.clone());
+        }
+
+        let url = Url::parse(cert_url).context("Invalid SigningCertURL")?;
+        if !is_sns_host(&url) || !url.path().ends_with(".pem") {
+            bail!("SigningCertURL is not an SNS certificate");
+        }
+
+        let pem = self.client.get(url)
+            .send()
+            .await
+            .context("Failed to fetch SNS certificate")?
+            .error_for_status()
+            .context("SNS certificate unavailable")?
+            .text()
+            .await?;
+        let certificate = Certificate::from_pem(pem.as_bytes()).context("Invalid SNS certificate")?;
+        let spki = certificate.tbs_certificate.subject_public_key_info.to_der()?;
+        let key = RsaPublicKey::from_public_key_der(&spki).context("SNS certificate key is not RSA")?;
+
+        self.sns_certs.write().await.insert(cert_url.to_string(), key.clone());
+        Ok(key)
+    }
+}
+
+fn parse_sendgrid_key(key: &str) -> Result<p256::ecdsa::VerifyingKey> {
+    let der = BASE64.decode(key.trim()).context("Invalid SENDGRID_WEBHOOK_PUBLIC_KEY encoding")?;
+    p256::ecdsa::VerifyingKey::from_public_key_der(&der).context("Invalid SENDGRID_WEBHOOK_PUBLIC_KEY")
+}
+
+/// https://sns.<region>.amazonaws.com(.cn) only
+fn is_sns_host(url: &Url) -> bool {
+    static SNS_HOST: OnceLock<Regex> = OnceLock::new();
+    let pattern = SNS_HOST.get_or_init(|| Regex::new(r"^sns\.[a-z0-9-]+\.amazonaws\.com(\.cn)?$").expect("valid regex"));
+
+    url.scheme() == "https" && url.host_str().is_some_and(|host| pattern.is_match(host))
+}
+
+/// SNS HTTP(S) delivery envelope
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessage {
+    #[serde(rename = "Type")]
+    pub kind: String,
+    pub message_id: String,
+    pub topic_arn: String,
+    pub subject: Option<String>,
+    pub message: String,
+    pub timestamp: String,
+    pub signature_version: String,
+    pub signature: String,
+    #[serde(rename = "SigningCertURL")]
+    pub signing_cert_url: String,
+    #[serde(rename = "SubscribeURL")]
+    pub subscribe_url: Option<String>,
+    pub token: Option<String>,
+}
+
+impl SnsMessage {
+    /// Canonical string AWS signs, per message type
+    fn string_to_sign(&self) -> Result<String> {
+        let mut fields: Vec<(&str, &str)> = vec![("Message", &self.message), ("MessageId", &self.message_id)];
+        match self.kind.as_str() {
+            "Notification" => {
+                if let Some(subject) = &self.subject {
+                    fields.push(("Subject", subject));
+                }
+            }
+            "SubscriptionConfirmation" | "UnsubscribeConfirmation" => {
+                fields.push(("SubscribeURL", self.subscribe_url.as_deref().context("Missing SubscribeURL")?));
+            }
+            other => bail!("Unknown SNS message type {}", other),
+        }
+        fields.push(("Timestamp", &self.timestamp));
+        if let Some(token) = self.token.as_deref().filter(|_| self.kind != "Notification") {
+            fields.push(("Token", token));
+        }
+        fields.push(("TopicArn", &self.topic_arn));
+        fields.push(("Type", &self.kind));
+
+        Ok(fields.iter().map(|(name, value)| format!("{}\n{}\n", name, value)).collect())
+    }
+}
+
+/// Map a SendGrid event batch. Events for emails we did not tag are skipped.
+pub fn parse_sendgrid_events(body: &[u8]) -> Result<Vec<DeliveryEvent>> {
+    let events: Vec<serde_json::Value> = serde_json::from_slice(body).context("Invalid SendGrid event batch")?;
+
+    Ok(events.iter()
+        .filter_map(|event| {
+            let name = event.get("event")?.as_str()?;
+            let status = match name {
+                "processed" => DeliveryStatus::Sent,
+                "delivered" | "open" | "click" => DeliveryStatus::Delivered,
+                "bounce" => DeliveryStatus::Bounced,
+                "dropped" => DeliveryStatus::Failed,
+                "spamreport" => DeliveryStatus::SpamFiltered,
+                _ => return None,
+            };
+            let message_id = event.get(SENDGRID_MESSAGE_ID_ARG)?.as_str()?;
+            Some(DeliveryEvent {
+                message_id: normalize_message_id(message_id),
+                status,
+                event: name.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Map an SES event (event publishing or feedback notification) from an SNS message body
+pub fn parse_ses_event(message: &str) -> Result<Option<DeliveryEvent>> {
+    let event: serde_json::Value = serde_json::from_str(message).context("Invalid SES event")?;
+
+    let Some(name) = event.get("eventType").or_else(|| event.get("notificationType")).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let status = match name {
+        "Send" => DeliveryStatus::Sent,
+        "Delivery" | "Open" | "Click" => DeliveryStatus::Delivered,
+        // Transient bounces are retried by SES
+        "Bounce" if event["bounce"]["bounceType"] == "Permanent" => DeliveryStatus::Bounced,
+        "Complaint" => DeliveryStatus::SpamFiltered,
+        "Reject" | "Rendering Failure" => DeliveryStatus::Failed,
+        _ => return Ok(None),
+    };
+
+    // SES assigns its own ID; our Message-ID survives in the original headers
+    let mail = &event["mail"];
+    let message_id = mail["headers"].as_array()
+        .and_then(|headers| headers.iter().find(|h| {
+            h["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case("Message-ID"))
+        }))
+        .and_then(|h| h["value"].as_str())
+        .or_else(|| mail["commonHeaders"]["messageId"].as_str());
+
+    Ok(message_id.map(|id| DeliveryEvent {
+        message_id: normalize_message_id(id),
+        status,
+        event: name.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer as _;
+    use p256::pkcs8::EncodePublicKey as _;
+
+    #[test]
+    fn test_sendgrid_signature_and_events() {
+        let signing_key = p256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let public_key = signing_key.verifying_key().to_public_key_der().unwrap();
+        let verifier = WebhookVerifier::new(Some(parse_sendgrid_key(&BASE64.encode(public_key.as_bytes())).unwrap()), Vec::new());
+
+        let body = br#"[
+            {"event":"delivered","email":"a@supplier.example","elementa_message_id":"<abc@elementa.io>"},
+            {"event":"bounce","email":"b@supplier.example","elementa_message_id":"def@elementa.io"},
+            {"event":"deferred","email":"c@supplier.example","elementa_message_id":"ghi@elementa.io"},
+            {"event":"open","email":"d@supplier.example"}
+        ]"#;
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature: p256::ecdsa::Signature = signing_key.sign(&[timestamp.as_bytes(), body].concat());
+        let signature = BASE64.encode(signature.to_der().as_bytes());
+
+        assert!(verifier.verify_sendgrid(&signature, &timestamp, body).is_ok());
+        assert!(verifier.verify_sendgrid(&signature, &timestamp, b"[]").is_err());
+        assert!(verifier.verify_sendgrid(&signature, "1000000000", body).is_err());
+
+        let events = parse_sendgrid_events(body).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message_id, "abc@elementa.io");
+        assert_eq!(events[0].status, DeliveryStatus::Delivered);
+        assert_eq!(events[1].status, DeliveryStatus::Bounced);
+
+        let ses = r#"{"eventType":"Bounce","bounce":{"bounceType":"Permanent"},
+            "mail":{"messageId":"0100-ses","headers":[{"name":"Message-ID","value":"<abc@elementa.io>"}]}}"#;
+        let event = parse_ses_event(ses).unwrap().unwrap();
+        assert_eq!(event.message_id, "abc@elementa.io");
+        assert_eq!(event.status, DeliveryStatus::Bounced);
+
+        assert!(supersedes(DeliveryStatus::Bounced, DeliveryStatus::Delivered));
+        assert!(!supersedes(DeliveryStatus::Delivered, DeliveryStatus::Bounced));
+    }
+
+    #[test]
+    fn test_sns_host() {
+        let sns = |url: &str| is_sns_host(&Url::parse(url).unwrap());
+
+        assert!(sns("https://sns.us-east-1.amazonaws.com/SimpleNotificationService-abc.pem"));
+        assert!(sns("https://sns.cn-north-1.amazonaws.com.cn/?Action=ConfirmSubscription"));
+        assert!(!sns("http://sns.us-east-1.amazonaws.com/cert.pem"));
+        assert!(!sns("https://sns.attacker.example.amazonaws.com/cert.pem"));
+        assert!(!sns("https://sns.evil.com.amazonaws.com/cert.pem"));
+        assert!(!sns("https://sns.us-east-1.amazonaws.com.evil.example/cert.pem"));
+        assert!(!sns("https://notsns.us-east-1.amazonaws.com/cert.pem"));
+    }
+}
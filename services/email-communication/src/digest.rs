@@ -0,0 +1,320 @@
+//! Compliance Digest
+//!
+//! Weekly summary email for internal compliance managers: responses still
+//! outstanding, PFAS found in recent submissions, upcoming campaign
+//! deadlines and open escalations. Rendered with the `compliance_digest`
+//! template and sent on a fixed weekday and hour (UTC).
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use elementa_models::ComplianceRecord;
+
+use crate::service::EmailService;
+use crate::workflow_client::{EscalationSummary, WorkflowSummary};
+
+/// Template the digest is rendered with
+pub const DIGEST_TEMPLATE: &str = "compliance_digest";
+
+/// Workflow states that still have suppliers to chase
+const OPEN_STATES: [&str; 3] = ["pending", "active", "paused"];
+
+#[derive(Debug, Clone)]
+pub struct DigestConfig {
+    /// Internal addresses the digest goes to; no digest is scheduled when empty
+    pub recipients: Vec<String>,
+    pub weekday: Weekday,
+    /// Hour of day (UTC) the digest is sent
+    pub hour: u32,
+    /// How far back PFAS detections are reported
+    pub period: Duration,
+    /// How far ahead campaign deadlines are reported
+    pub lookahead: Duration,
+}
+
+impl DigestConfig {
+    /// `DIGEST_RECIPIENTS` (comma-separated), `DIGEST_WEEKDAY` (default `mon`),
+    /// `DIGEST_HOUR` (default 8) and `DIGEST_LOOKAHEAD_DAYS` (default 14)
+    pub fn from_env() -> Self {
+        let recipients = std::env::var("DIGEST_RECIPIENTS").unwrap_or_default()
+            .split(',')
+            .map(|r| r.trim().to_lowercase())
+            .filter(|r| r.contains('@'))
+            .collect();
+        let weekday = std::env::var("DIGEST_WEEKDAY").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(Weekday::Mon);
+        let hour = std::env::var("DIGEST_HOUR").ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|h| *h < 24)
+            .unwrap_or(8);
+        let lookahead_days = std::env::var("DIGEST_LOOKAHEAD_DAYS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(14);
+
+        Self {
+            recipients,
+            weekday,
+            hour,
+            period: Duration::days(7),
+            lookahead: Duration::days(lookahead_days),
+        }
+    }
+
+    /// First scheduled send strictly after `now`
+    pub fn next_run(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let time = NaiveTime::from_hms_opt(self.hour, 0, 0).expect("hour is below 24");
+        let days_ahead = (7 + self.weekday.num_days_from_monday() as i64
+            - now.weekday().num_days_from_monday() as i64) % 7;
+        let candidate = (now.date_naive() + Duration::days(days_ahead)).and_time(time).and_utc();
+        if candidate > now {
+            candidate
+        } else {
+            candidate + Duration::days(7)
+        }
+    }
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub period_start: String,
+    pub period_end: String,
+    /// Open campaigns with contacted suppliers that have not replied
+    pub pending_responses: Vec<PendingResponses>,
+    pub pfas_detections: Vec<PfasDetection>,
+    pub upcoming_deadlines: Vec<UpcomingDeadline>,
+    pub escalations: Vec<OpenEscalation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingResponses {
+    pub campaign_name: String,
+    pub awaiting: usize,
+    pub contacted: usize,
+    pub deadline: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PfasDetection {
+    pub supplier_id: Uuid,
+    pub component_id: Uuid,
+    pub cas_number: String,
+    pub chemical_name: String,
+    pub submitted_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpcomingDeadline {
+    pub campaign_name: String,
+    pub deadline: String,
+    pub days_remaining: i64,
+    pub percent_complete: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenEscalation {
+    pub campaign_name: String,
+    pub supplier_id: Uuid,
+    pub severity: String,
+    pub reason: String,
+    pub created_at: String,
+}
+
+/// Build the digest for the period ending at `now`
+pub fn compose(
+    now: DateTime<Utc>,
+    config: &DigestConfig,
+    workflows: &[WorkflowSummary],
+    escalations: &[EscalationSummary],
+    records: &[ComplianceRecord],
+) -> Digest {
+    let period_start = now - config.period;
+    let open: Vec<(&WorkflowSummary, DateTime<Utc>)> = workflows.iter()
+        .filter(|w| OPEN_STATES.contains(&w.status.as_str()))
+        .filter_map(|w| match DateTime::parse_from_rfc3339(&w.deadline) {
+            Ok(deadline) => Some((w, deadline.with_timezone(&Utc))),
+            Err(e) => {
+                warn!("Skipping workflow {} with invalid deadline: {}", w.id, e);
+                None
+            }
+        })
+        .collect();
+
+    let mut pending_responses: Vec<PendingResponses> = open.iter()
+        .filter(|(w, _)| w.progress.contacted > w.progress.responded)
+        .map(|(w, deadline)| PendingResponses {
+            campaign_name: w.campaign_name.clone(),
+            awaiting: w.progress.contacted - w.progress.responded,
+            contacted: w.progress.contacted,
+            deadline: deadline.format("%Y-%m-%d").to_string(),
+        })
+        .collect();
+    pending_responses.sort_by_key(|p| std::cmp::Reverse(p.awaiting));
+
+    let mut upcoming: Vec<(&WorkflowSummary, DateTime<Utc>)> = open.iter()
+        .filter(|(_, deadline)| *deadline >= now && *deadline <= now + config.lookahead)
+        .copied()
+        .collect();
+    upcoming.sort_by_key(|(_, deadline)| *deadline);
+    let upcoming_deadlines = upcoming.into_iter()
+        .map(|(w, deadline)| UpcomingDeadline {
+            campaign_name: w.campaign_name.clone(),
+            deadline: deadline.format("%Y-%m-%d").to_string(),
+            days_remaining: (deadline - now).num_days(),
+            percent_complete: w.progress.percent_complete,
+        })
+        .collect();
+
+    let mut pfas_detections: Vec<PfasDetection> = records.iter()
+        .filter(|r| r.submission_date >= period_start)
+        .flat_map(|r| r.cas_records.iter().filter(|c| c.is_pfas).map(move |c| PfasDetection {
+            supplier_id: r.supplier_id.into(),
+            component_id: r.component_id.into(),
+            cas_number: c.cas_number.clone(),
+            chemical_name: c.chemical_name.clone(),
+            submitted_at: r.submission_date.format("%Y-%m-%d").to_string(),
+        }))
+        .collect();
+    pfas_detections.sort_by(|a, b| b.submitted_at.cmp(&a.submitted_at));
+
+    let campaign_names: HashMap<Uuid, &str> = workflows.iter()
+        .map(|w| (w.id, w.campaign_name.as_str()))
+        .collect();
+    let mut open_escalations: Vec<&EscalationSummary> = escalations.iter().filter(|e| !e.resolved).collect();
+    open_escalations.sort_by_key(|e| (severity_rank(&e.severity), e.created_at.clone()));
+    let escalations = open_escalations.into_iter()
+        .map(|e| OpenEscalation {
+            campaign_name: campaign_names.get(&e.workflow_id).unwrap_or(&"Unknown campaign").to_string(),
+            supplier_id: e.supplier_id,
+            severity: e.severity.clone(),
+            reason: e.reason.clone(),
+            created_at: e.created_at.clone(),
+        })
+        .collect();
+
+    Digest {
+        period_start: period_start.format("%Y-%m-%d").to_string(),
+        period_end: now.format("%Y-%m-%d").to_string(),
+        pending_responses,
+        pfas_detections,
+        upcoming_deadlines,
+        escalations,
+    }
+}
+
+/// Most severe first
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "critical" => 0,
+        "high" => 1,
+        "medium" => 2,
+        _ => 3,
+    }
+}
+
+/// Send the digest at each configured weekday and hour
+pub fn spawn_scheduler(service: EmailService) -> Option<JoinHandle<()>> {
+    let config = service.digest_config();
+    if config.recipients.is_empty() {
+        info!("DIGEST_RECIPIENTS not set; compliance digest disabled");
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        loop {
+            let now = Utc::now();
+            let next = config.next_run(now);
+            info!("Next compliance digest at {}", next.to_rfc3339());
+            if let Ok(wait) = (next - now).to_std() {
+                tokio::time::sleep(wait).await;
+            }
+
+            match service.send_digest().await {
+                Ok(sent) => info!("Compliance digest sent to {} recipients", sent.recipients.len()),
+                Err(e) => error!("Compliance digest failed: {:#}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow_client::WorkflowProgress;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn workflow(name: &str, status: &str, deadline: &str, contacted: usize, responded: usize) -> WorkflowSummary {
+        WorkflowSummary {
+            id: Uuid::new_v4(),
+            campaign_name: name.to_string(),
+            status: status.to_string(),
+            deadline: deadline.to_string(),
+            progress: WorkflowProgress { contacted, responded, percent_complete: 40.0 },
+        }
+    }
+
+    #[test]
+    fn test_digest_covers_open_campaigns_only() {
+        let config = DigestConfig {
+            recipients: vec!["compliance@acme.example".to_string()],
+            weekday: Weekday::Mon,
+            hour: 8,
+            period: Duration::days(7),
+            lookahead: Duration::days(14),
+        };
+        let now = utc("2026-03-04T10:00:00Z");
+        assert_eq!(config.next_run(now), utc("2026-03-09T08:00:00Z"));
+        assert_eq!(config.next_run(utc("2026-03-09T07:00:00Z")), utc("2026-03-09T08:00:00Z"));
+
+        let workflows = vec![
+            workflow("Q1 gaskets", "active", "2026-03-10T00:00:00Z", 8, 3),
+            workflow("Resins", "active", "2026-06-01T00:00:00Z", 4, 1),
+            workflow("Done", "completed", "2026-03-06T00:00:00Z", 5, 2),
+        ];
+        let escalations = vec![
+            EscalationSummary {
+                workflow_id: workflows[0].id,
+                supplier_id: Uuid::new_v4(),
+                reason: "No response".to_string(),
+                severity: "medium".to_string(),
+                created_at: "2026-03-01T00:00:00Z".to_string(),
+                resolved: false,
+            },
+            EscalationSummary {
+                workflow_id: workflows[1].id,
+                supplier_id: Uuid::new_v4(),
+                reason: "Opted out".to_string(),
+                severity: "high".to_string(),
+                created_at: "2026-03-02T00:00:00Z".to_string(),
+                resolved: false,
+            },
+        ];
+
+        let digest = compose(now, &config, &workflows, &escalations, &[]);
+        assert_eq!(digest.period_start, "2026-02-25");
+        let pending: Vec<(&str, usize)> = digest.pending_responses.iter().map(|p| (p.campaign_name.as_str(), p.awaiting)).collect();
+        assert_eq!(pending, vec![("Q1 gaskets", 5), ("Resins", 3)]);
+        assert_eq!(digest.upcoming_deadlines.len(), 1);
+        assert_eq!(digest.upcoming_deadlines[0].days_remaining, 5);
+        assert_eq!(digest.escalations[0].campaign_name, "Resins");
+        assert!(digest.pfas_detections.is_empty());
+
+        let variables = serde_json::to_value(&digest).unwrap().as_object().unwrap().clone().into_iter().collect();
+        let rendered = crate::template_engine::TemplateEngine::new().render(DIGEST_TEMPLATE, "en", &variables).unwrap();
+        assert!(rendered.body_text.contains("- Q1 gaskets: 5 of 8 contacted suppliers have not replied"));
+        assert!(rendered.body_text.contains("No PFAS found in submissions this period."));
+    }
+}
@@ -0,0 +1,166 @@
+//! Document Processing Client
+//!
+//! Forwards inbound email attachments to the document-processing service.
+
+use anyhow::{Context, Result};
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Email the attachment arrived on
+#[derive(Debug, Clone)]
+pub struct AttachmentSource {
+    pub supplier_id: Uuid,
+    pub email_id: Uuid,
+    pub thread_id: String,
+    pub campaign_id: Option<Uuid>,
+}
+
+/// Extraction outcome reported by document-processing
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtractionSummary {
+    pub cas_numbers_found: usize,
+    pub needs_review: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentUploadResponse {
+    document_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageUploadResponse {
+    document_ids: Vec<Uuid>,
+}
+
+/// Client for the document-processing service
+pub struct DocumentClient {
+    client: Client,
+    base_url: String,
+}
+
+impl DocumentClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, base_url }
+    }
+
+    /// Upload attachment, returning the created document IDs.
+    /// ZIP attachments are expanded into one document per entry.
+    pub async fn upload(
+        &self,
+        filename: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        source: &AttachmentSource,
+    ) -> Result<Vec<Uuid>> {
+        let is_zip = content_type.contains("zip") || filename.to_lowercase().ends_with(".zip");
+        let path = if is_zip { "packages/upload" } else { "documents/upload" };
+
+        let part = Part::bytes(data)
+            .file_name(filename.to_string())
+            .mime_str(content_type)
+            .context("Invalid attachment content type")?;
+
+        let mut query = vec![
+            ("supplier_id", source.supplier_id.to_string()),
+            ("email_id", source.email_id.to_string()),
+            ("thread_id", source.thread_id.clone()),
+        ];
+        if let Some(campaign_id) = source.campaign_id {
+            query.push(("campaign_id", campaign_id.to_string()));
+        }
+
+        let response = self.client
+            .post(format!("{}/api/v1/{}", self.base_url, path))
+            .query(&query)
+            .multipart(Form::new().part("file", part))
+            .send()
+            .await
+            .context("Failed to reach document service")?
+            .error_for_status()
+            .context("Document upload rejected")?;
+
+        if is_zip {
+            let package: PackageUploadResponse = response.json().await
+                .context("Invalid package upload response")?;
+            Ok(package.document_ids)
+        } else {
+            let document: DocumentUploadResponse = response.json().await
+                .context("Invalid document upload response")?;
+            Ok(vec![document.document_id])
+        }
+    }
+
+    /// Run extraction for an uploaded document
+    pub async fn extract(&self, document_id: Uuid) -> Result<ExtractionSummary> {
+        self.client
+            .post(format!("{}/api/v1/documents/{}/extract", self.base_url, document_id))
+            .send()
+            .await
+            .context("Failed to reach document service")?
+            .error_for_status()
+            .context("Extraction failed")?
+            .json()
+            .await
+            .context("Invalid extraction response")
+    }
+}
+
+impl Default for DocumentClient {
+    fn default() -> Self {
+        Self::new(
+            std::env::var("DOCUMENT_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8083".to_string()),
+        )
+    }
+}
+
+/// Guess attachment MIME type from its filename
+pub fn guess_content_type(filename: &str) -> &'static str {
+    match filename.rsplit('.').next().unwrap_or("").to_lowercase().as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "tif" | "tiff" => "image/tiff",
+        "zip" => "application/zip",
+        "csv" => "text/csv",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "xls" => "application/vnd.ms-excel",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Whether an attachment is a compliance document worth extracting.
+/// Inline images such as signature logos are not forwarded.
+pub fn is_forwardable(filename: &str, content_type: &str) -> bool {
+    let content_type = match content_type {
+        "application/octet-stream" | "" => guess_content_type(filename),
+        other => other,
+    };
+
+    content_type == "application/pdf"
+        || content_type == "text/csv"
+        || content_type.contains("spreadsheetml")
+        || content_type.contains("ms-excel")
+        || content_type.contains("zip")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwards_documents_not_inline_images() {
+        assert!(is_forwardable("sds.pdf", "application/pdf"));
+        assert!(is_forwardable("declaration.xlsx", "application/octet-stream"));
+        assert!(is_forwardable("package.zip", "application/x-zip-compressed"));
+        assert!(!is_forwardable("logo.png", "image/png"));
+        assert!(!is_forwardable("invite.ics", "text/calendar"));
+    }
+}
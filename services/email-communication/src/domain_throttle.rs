@@ -0,0 +1,189 @@
+//! Domain Throttle
+//!
+//! Per-recipient-domain send rate limits. Sends to one domain are spread
+//! evenly across the limit window rather than going out in a burst, since
+//! corporate gateways blacklist senders that flood them.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// Send deferred because the recipient's domain is at its rate limit
+#[derive(Debug)]
+pub struct DomainThrottled {
+    pub domain: String,
+    pub retry_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for DomainThrottled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rate limit reached for {}; retry at {}", self.domain, self.retry_at.to_rfc3339())
+    }
+}
+
+impl std::error::Error for DomainThrottled {}
+
+/// At most `max_sends` emails per `window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DomainLimit {
+    pub max_sends: u32,
+    pub window: Duration,
+}
+
+impl DomainLimit {
+    /// Gap between consecutive sends to the domain
+    fn spacing(&self) -> Duration {
+        self.window / self.max_sends.max(1) as i32
+    }
+}
+
+impl std::str::FromStr for DomainLimit {
+    type Err = anyhow::Error;
+
+    /// `20/60` is 20 sends per 60 seconds
+    fn from_str(s: &str) -> Result<Self> {
+        let (max_sends, window) = s.split_once('/').context("Expected <sends>/<seconds>")?;
+        let max_sends: u32 = max_sends.trim().parse().context("Invalid send count")?;
+        let window: i64 = window.trim().parse().context("Invalid window seconds")?;
+        if max_sends == 0 || window <= 0 {
+            bail!("Domain limits must be positive");
+        }
+        Ok(Self { max_sends, window: Duration::seconds(window) })
+    }
+}
+
+/// Throttle configuration
+#[derive(Debug, Clone)]
+pub struct DomainThrottleConfig {
+    pub default_limit: DomainLimit,
+    /// Limits for specific domains, keyed by lowercase domain
+    pub overrides: HashMap<String, DomainLimit>,
+    /// Longest a send waits in-process for its slot before failing with `DomainThrottled`
+    pub max_wait: Duration,
+}
+
+impl DomainThrottleConfig {
+    /// `DOMAIN_RATE_LIMIT` and `DOMAIN_RATE_WINDOW_SECS` set the default;
+    /// `DOMAIN_RATE_OVERRIDES` takes `bigcorp.com=5/60,other.com=50/60`
+    pub fn from_env() -> Self {
+        let env_i64 = |key: &str, default: i64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        let overrides = std::env::var("DOMAIN_RATE_OVERRIDES").unwrap_or_default()
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .filter_map(|entry| {
+                let parsed = entry.split_once('=')
+                    .context("Expected <domain>=<sends>/<seconds>")
+                    .and_then(|(domain, limit)| Ok((domain.trim().to_lowercase(), limit.parse()?)));
+                match parsed {
+                    Ok(parsed) => Some(parsed),
+                    Err(e) => {
+                        tracing::warn!("Ignoring domain rate override {:?}: {:#}", entry, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self {
+            default_limit: DomainLimit {
+                max_sends: env_i64("DOMAIN_RATE_LIMIT", 20).max(1) as u32,
+                window: Duration::seconds(env_i64("DOMAIN_RATE_WINDOW_SECS", 60).max(1)),
+            },
+            overrides,
+            max_wait: Duration::seconds(env_i64("DOMAIN_THROTTLE_MAX_WAIT_SECS", 5).max(0)),
+        }
+    }
+
+    pub fn limit_for(&self, domain: &str) -> DomainLimit {
+        self.overrides.get(domain).copied().unwrap_or(self.default_limit)
+    }
+}
+
+/// Hands out send slots per recipient domain
+pub struct DomainThrottle {
+    config: DomainThrottleConfig,
+    /// Next free slot per domain
+    next_slot: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl DomainThrottle {
+    pub fn new(config: DomainThrottleConfig) -> Self {
+        Self { config, next_slot: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &DomainThrottleConfig {
+        &self.config
+    }
+
+    /// Reserve the domain's next slot at or after `now`. Fails with
+    /// `DomainThrottled`, reserving nothing, when the slot is further
+    /// away than `max_wait`.
+    pub async fn reserve(&self, domain: &str, now: DateTime<Utc>, max_wait: Duration) -> Result<DateTime<Utc>> {
+        let spacing = self.config.limit_for(domain).spacing();
+        let mut next_slot = self.next_slot.lock().await;
+
+        let slot = next_slot.get(domain).copied().map_or(now, |next| next.max(now));
+        if slot - now > max_wait {
+            return Err(DomainThrottled { domain: domain.to_string(), retry_at: slot }.into());
+        }
+
+        // Domains idle for a while no longer constrain anything
+        if next_slot.len() > 1024 {
+            next_slot.retain(|_, next| *next > now);
+        }
+        next_slot.insert(domain.to_string(), slot + spacing);
+
+        Ok(slot)
+    }
+}
+
+impl Default for DomainThrottle {
+    fn default() -> Self {
+        Self::new(DomainThrottleConfig::from_env())
+    }
+}
+
+/// Lowercase domain of an email address
+pub fn recipient_domain(email_address: &str) -> Option<String> {
+    email_address.rsplit_once('@')
+        .map(|(_, domain)| domain.trim().trim_end_matches('>').to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sends_to_a_domain_are_spread_over_the_window() {
+        let throttle = DomainThrottle::new(DomainThrottleConfig {
+            default_limit: "4/60".parse().unwrap(),
+            overrides: [("bigcorp.com".to_string(), "1/60".parse().unwrap())].into_iter().collect(),
+            max_wait: Duration::zero(),
+        });
+        let now = Utc::now();
+
+        // 4 per minute means one every 15 seconds
+        let mut slots = Vec::new();
+        for _ in 0..3 {
+            slots.push(throttle.reserve("supplier.example", now, Duration::minutes(1)).await.unwrap());
+        }
+        assert_eq!(slots, vec![now, now + Duration::seconds(15), now + Duration::seconds(30)]);
+
+        // Other domains are unaffected; overrides apply per domain
+        assert_eq!(throttle.reserve("bigcorp.com", now, Duration::zero()).await.unwrap(), now);
+        let error = throttle.reserve("bigcorp.com", now, Duration::seconds(30)).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<DomainThrottled>().unwrap().retry_at, now + Duration::seconds(60));
+
+        // A rejected reservation does not take a slot
+        let later = now + Duration::seconds(60);
+        assert_eq!(throttle.reserve("bigcorp.com", later, Duration::zero()).await.unwrap(), later);
+
+        assert_eq!(recipient_domain("QA@Supplier.Example").as_deref(), Some("supplier.example"));
+        assert_eq!(recipient_domain("no-domain"), None);
+    }
+}
@@ -0,0 +1,330 @@
+//! Email Providers
+//!
+//! Delivery abstraction over raw SMTP, SendGrid and AWS SES.
+//! Providers are chosen per deployment via `EMAIL_PROVIDERS`, in failover order.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use lettre::message::dkim::DkimConfig;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use reqwest::{Client, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::delivery_retry::TransientFailure;
+use crate::delivery_webhooks::SENDGRID_MESSAGE_ID_ARG;
+use crate::provider_failover::{FailoverConfig, ProviderPool};
+use crate::smime::SmimeOutbound;
+use crate::smtp_client::{SmtpClient, SmtpConfig, SmtpTls};
+
+/// Sender identity used for outbound mail
+#[derive(Debug, Clone)]
+pub struct SenderConfig {
+    pub from_email: String,
+    pub from_name: String,
+    /// Where supplier replies go when not the From address
+    pub reply_to: Option<String>,
+}
+
+impl Default for SenderConfig {
+    fn default() -> Self {
+        Self {
+            from_email: std::env::var("EMAIL_FROM_ADDRESS")
+                .or_else(|_| std::env::var("SMTP_FROM_EMAIL"))
+                .unwrap_or_else(|_| "compliance@elementa.io".to_string()),
+            from_name: std::env::var("EMAIL_FROM_NAME")
+                .or_else(|_| std::env::var("SMTP_FROM_NAME"))
+                .unwrap_or_else(|_| "Elementa Compliance".to_string()),
+            reply_to: std::env::var("EMAIL_REPLY_TO").ok().filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+/// Email ready for delivery
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub from: SenderConfig,
+    pub to_email: String,
+    pub to_name: String,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: String,
+    pub attachments: Vec<EmailAttachment>,
+    /// RFC 5322 Message-ID, without angle brackets
+    pub message_id: String,
+    /// Message-ID of the email being replied to
+    pub in_reply_to: Option<String>,
+    /// Message-IDs of earlier emails in the thread, oldest first
+    pub references: Vec<String>,
+    /// One-click unsubscribe URL for the `List-Unsubscribe` header
+    pub unsubscribe_url: Option<String>,
+    /// DKIM signing for the sender domain; applied by SMTP-based providers
+    pub dkim: Option<Arc<DkimConfig>>,
+    /// S/MIME signing and encryption; only SMTP-based providers can apply it
+    pub smime: Option<SmimeOutbound>,
+}
+
+/// Decoded email attachment
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Generate a Message-ID in the sender's domain
+pub fn generate_message_id(from_email: &str) -> String {
+    let domain = from_email.rsplit('@').next().unwrap_or("localhost");
+    format!("{}@{}", Uuid::new_v4(), domain)
+}
+
+/// Strip angle brackets and whitespace from a Message-ID header value
+pub fn normalize_message_id(id: &str) -> String {
+    id.trim().trim_start_matches('<').trim_end_matches('>').to_string()
+}
+
+/// Format Message-IDs as a header value
+pub fn message_id_header<'a>(ids: impl IntoIterator<Item = &'a String>) -> String {
+    ids.into_iter().map(|id| format!("<{}>", id)).collect::<Vec<_>>().join(" ")
+}
+
+/// Email delivery provider
+#[async_trait]
+pub trait EmailProvider: Send + Sync {
+    /// Deliver email, returning the provider's ID for the delivery
+    async fn send(&self, email: &OutgoingEmail) -> Result<String>;
+
+    /// Provider name for logging and delivery records
+    fn name(&self) -> &'static str;
+}
+
+/// Build the providers configured for this deployment.
+///
+/// `EMAIL_PROVIDERS` lists `smtp`, `sendgrid`, `ses` or `log` in failover
+/// order, e.g. `smtp,sendgrid`. A single `EMAIL_PROVIDER` is still accepted.
+/// When neither is set, SMTP is used if `SMTP_HOST` is configured. Startup
+/// fails otherwise: only logging emails has to be asked for with `log`.
+pub fn providers_from_env() -> Result<ProviderPool> {
+    let names = match std::env::var("EMAIL_PROVIDERS").or_else(|_| std::env::var("EMAIL_PROVIDER")) {
+        Ok(names) => names,
+        Err(_) if std::env::var("SMTP_HOST").is_ok() => "smtp".to_string(),
+        Err(_) => bail!("No email provider configured; set EMAIL_PROVIDERS, or EMAIL_PROVIDERS=log to only log emails"),
+    };
+
+    let mut providers: Vec<(String, Arc<dyn EmailProvider>)> = Vec::new();
+    for name in names.split(',').map(|n| n.trim().to_lowercase()).filter(|n| !n.is_empty()) {
+        if providers.iter().any(|(existing, _)| *existing == name) {
+            bail!("Email provider {} is listed twice", name);
+        }
+        let provider: Arc<dyn EmailProvider> = match name.as_str() {
+            "smtp" => Arc::new(SmtpClient::new(SmtpConfig::default())?),
+            "sendgrid" => Arc::new(SendGridProvider::new(SendGridConfig::default())?),
+            "ses" => Arc::new(SmtpClient::new(ses_smtp_config())?),
+            "log" => {
+                warn!("Email provider 'log' only logs emails; nothing will be delivered");
+                Arc::new(LogProvider)
+            }
+            other => bail!("Unknown email provider: {}", other),
+        };
+        providers.push((name, provider));
+    }
+    if providers.is_empty() {
+        bail!("EMAIL_PROVIDERS is empty");
+    }
+
+    let pool = ProviderPool::new(providers, FailoverConfig::default());
+    info!("Email providers: {}", pool.names());
+    Ok(pool)
+}
+
+/// AWS SES is reached through its regional SMTP interface
+fn ses_smtp_config() -> SmtpConfig {
+    let region = std::env::var("SES_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+    SmtpConfig {
+        host: format!("email-smtp.{}.amazonaws.com", region),
+        port: 587,
+        tls: SmtpTls::StartTls,
+        username: std::env::var("SES_SMTP_USERNAME").unwrap_or_default(),
+        password: std::env::var("SES_SMTP_PASSWORD").unwrap_or_default(),
+        ..SmtpConfig::default()
+    }
+}
+
+/// SendGrid configuration
+#[derive(Debug, Clone)]
+pub struct SendGridConfig {
+    pub api_key: String,
+    pub api_url: String,
+}
+
+impl Default for SendGridConfig {
+    fn default() -> Self {
+        Self {
+            api_key: std::env::var("SENDGRID_API_KEY").unwrap_or_default(),
+            api_url: std::env::var("SENDGRID_API_URL")
+                .unwrap_or_else(|_| "https://api.sendgrid.com/v3/mail/send".to_string()),
+        }
+    }
+}
+
+/// SendGrid v3 Mail Send API provider
+pub struct SendGridProvider {
+    client: Client,
+    config: SendGridConfig,
+}
+
+impl SendGridProvider {
+    pub fn new(config: SendGridConfig) -> Result<Self> {
+        if config.api_key.is_empty() {
+            bail!("SENDGRID_API_KEY is not set");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl EmailProvider for SendGridProvider {
+    async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+        if email.smime.is_some() {
+            bail!("SendGrid cannot deliver S/MIME email; use the smtp or ses provider");
+        }
+
+        let response = self.client
+            .post(&self.config.api_url)
+            .bearer_auth(&self.config.api_key)
+            .json(&sendgrid_payload(email))
+            .send()
+            .await
+            .context("Failed to reach SendGrid")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let message = format!("SendGrid rejected email ({}): {}", status, body);
+            if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                return Err(TransientFailure(message).into());
+            }
+            bail!(message);
+        }
+
+        Ok(response.headers()
+            .get("x-message-id")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| Uuid::new_v4().to_string()))
+    }
+
+    fn name(&self) -> &'static str {
+        "sendgrid"
+    }
+}
+
+/// Build SendGrid mail send request body
+fn sendgrid_payload(email: &OutgoingEmail) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "personalizations": [{
+            "to": [{ "email": email.to_email, "name": email.to_name }]
+        }],
+        "from": { "email": email.from.from_email, "name": email.from.from_name },
+        "subject": email.subject,
+        "content": [
+            { "type": "text/plain", "value": email.body_text },
+            { "type": "text/html", "value": email.body_html }
+        ]
+    });
+    if let Some(reply_to) = &email.from.reply_to {
+        payload["reply_to"] = serde_json::json!({ "email": reply_to });
+    }
+
+    let mut headers = serde_json::Map::new();
+    headers.insert("Message-ID".to_string(), message_id_header([&email.message_id]).into());
+    if let Some(in_reply_to) = &email.in_reply_to {
+        headers.insert("In-Reply-To".to_string(), message_id_header([in_reply_to]).into());
+    }
+    if !email.references.is_empty() {
+        headers.insert("References".to_string(), message_id_header(&email.references).into());
+    }
+    if let Some(url) = &email.unsubscribe_url {
+        headers.insert("List-Unsubscribe".to_string(), format!("<{}>", url).into());
+        headers.insert("List-Unsubscribe-Post".to_string(), "List-Unsubscribe=One-Click".into());
+    }
+    payload["headers"] = headers.into();
+    // Echoed back on every event so the delivery webhook can find the email
+    payload["custom_args"] = serde_json::json!({ SENDGRID_MESSAGE_ID_ARG: email.message_id });
+
+    if !email.attachments.is_empty() {
+        payload["attachments"] = email.attachments.iter()
+            .map(|a| serde_json::json!({
+                "content": BASE64.encode(&a.data),
+                "type": a.content_type,
+                "filename": a.filename,
+            }))
+            .collect();
+    }
+
+    payload
+}
+
+/// Logs instead of delivering; for local development
+pub struct LogProvider;
+
+#[async_trait]
+impl EmailProvider for LogProvider {
+    async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+        info!("Email not delivered (log provider): to={} subject={:?} id={}", email.to_email, email.subject, email.message_id);
+        Ok(email.message_id.clone())
+    }
+
+    fn name(&self) -> &'static str {
+        "log"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sendgrid_payload_includes_attachments() {
+        let email = OutgoingEmail {
+            from: SenderConfig {
+                from_email: "compliance@elementa.io".to_string(),
+                from_name: "Elementa".to_string(),
+                reply_to: Some("acme-compliance@elementa.io".to_string()),
+            },
+            to_email: "supplier@example.com".to_string(),
+            to_name: "Supplier".to_string(),
+            subject: "PFAS declaration".to_string(),
+            body_html: "<p>Hello</p>".to_string(),
+            body_text: "Hello".to_string(),
+            attachments: vec![EmailAttachment {
+                filename: "bom.csv".to_string(),
+                content_type: "text/csv".to_string(),
+                data: b"part,qty".to_vec(),
+            }],
+            message_id: "abc@elementa.io".to_string(),
+            in_reply_to: Some("reply-1@example.com".to_string()),
+            references: vec!["abc-0@elementa.io".to_string(), "reply-1@example.com".to_string()],
+            unsubscribe_url: None,
+            dkim: None,
+            smime: None,
+        };
+
+        let payload = sendgrid_payload(&email);
+
+        assert_eq!(payload["personalizations"][0]["to"][0]["email"], "supplier@example.com");
+        assert_eq!(payload["from"]["email"], "compliance@elementa.io");
+        assert_eq!(payload["reply_to"]["email"], "acme-compliance@elementa.io");
+        assert_eq!(payload["attachments"][0]["content"], BASE64.encode(b"part,qty"));
+        assert_eq!(payload["headers"]["In-Reply-To"], "<reply-1@example.com>");
+        assert_eq!(payload["headers"]["References"], "<abc-0@elementa.io> <reply-1@example.com>");
+    }
+}
@@ -0,0 +1,197 @@
+//! Email Store
+//!
+//! Email records live in Postgres through `EmailRepository`. Without a
+//! database, e.g. in local development, a process-local map stands in
+//! behind the same interface.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use elementa_database::EmailRepository;
+use elementa_models::{
+    DeliveryAttempt, DeliveryStatus, EmailAttachment, EmailCommunication, EmailDirection, EmailProcessingStatus, ReplyClassification,
+};
+
+pub enum EmailStore {
+    Postgres(EmailRepository),
+    Memory(RwLock<HashMap<Uuid, EmailCommunication>>),
+}
+
+impl EmailStore {
+    pub fn memory() -> Self {
+        Self::Memory(RwLock::new(HashMap::new()))
+    }
+
+    pub async fn create(&self, mut email: EmailCommunication) -> Result<EmailCommunication> {
+        match self {
+            Self::Postgres(repository) => repository.create(email).await,
+            Self::Memory(emails) => {
+                email.created_at = Utc::now();
+                email.updated_at = email.created_at;
+                emails.write().await.insert(email.id, email.clone());
+                Ok(email)
+            }
+        }
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_by_id(id).await,
+            Self::Memory(emails) => Ok(emails.read().await.get(&id).cloned()),
+        }
+    }
+
+    /// Emails in a thread, oldest first
+    pub async fn find_by_thread(&self, thread_id: &str) -> Result<Vec<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_by_thread(thread_id).await,
+            Self::Memory(emails) => {
+                let mut thread = select(&*emails.read().await, |e| e.thread_id == thread_id);
+                thread.reverse();
+                Ok(thread)
+            }
+        }
+    }
+
+    /// Emails for a supplier, newest first
+    pub async fn find_by_supplier(&self, supplier_id: Uuid) -> Result<Vec<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_by_supplier(supplier_id.into()).await,
+            Self::Memory(emails) => Ok(select(&*emails.read().await, |e| e.supplier_id == supplier_id)),
+        }
+    }
+
+    /// Emails in every thread a campaign sent to, oldest first
+    pub async fn find_campaign_threads(&self, campaign_id: Uuid) -> Result<Vec<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_campaign_threads(campaign_id.into()).await,
+            Self::Memory(emails) => {
+                let emails = emails.read().await;
+                let threads: Vec<&str> = emails.values()
+                    .filter(|e| e.campaign_id.is_some_and(|id| id == campaign_id))
+                    .map(|e| e.thread_id.as_str())
+                    .collect();
+                let mut found = select(&emails, |e| threads.contains(&e.thread_id.as_str()));
+                found.reverse();
+                Ok(found)
+            }
+        }
+    }
+
+    /// Most recent email carrying any of the given Message-IDs
+    pub async fn find_by_message_ids(&self, message_ids: &[String]) -> Result<Option<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_by_message_ids(message_ids).await,
+            Self::Memory(emails) => Ok(select(&*emails.read().await, |e| {
+                e.message_id.as_ref().is_some_and(|id| message_ids.contains(id))
+            }).into_iter().next()),
+        }
+    }
+
+    /// Earliest inbound email with the given Message-ID, or from the supplier
+    /// with the same content hash since `since`
+    pub async fn find_inbound_duplicate(
+        &self,
+        supplier_id: Uuid,
+        message_id: Option<&str>,
+        content_hash: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_inbound_duplicate(supplier_id.into(), message_id, content_hash, since).await,
+            Self::Memory(emails) => Ok(select(&*emails.read().await, |e| {
+                matches!(e.direction, EmailDirection::Inbound)
+                    && ((message_id.is_some() && e.message_id.as_deref() == message_id)
+                        || (e.supplier_id == supplier_id
+                            && e.content_hash.as_deref() == Some(content_hash)
+                            && e.created_at >= since))
+            }).pop()),
+        }
+    }
+
+    /// Most recent outbound email to an address
+    pub async fn find_latest_sent_to(&self, recipient: &str) -> Result<Option<EmailCommunication>> {
+        match self {
+            Self::Postgres(repository) => repository.find_latest_sent_to(recipient).await,
+            Self::Memory(emails) => Ok(select(&*emails.read().await, |e| {
+                matches!(e.direction, EmailDirection::Outbound)
+                    && e.recipient.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(recipient))
+            }).into_iter().next()),
+        }
+    }
+
+    pub async fn update_delivery_status(&self, id: Uuid, status: DeliveryStatus) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.update_delivery_status(id, status).await,
+            Self::Memory(_) => Ok(self.update(id, |e| e.delivery_status = status).await),
+        }
+    }
+
+    pub async fn update_delivery_attempts(
+        &self,
+        id: Uuid,
+        attempts: &[DeliveryAttempt],
+        status: DeliveryStatus,
+        sent_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.update_delivery_attempts(id, attempts, status, sent_at).await,
+            Self::Memory(_) => Ok(self.update(id, |e| {
+                e.delivery_attempts = attempts.to_vec();
+                e.delivery_status = status;
+                e.sent_at = sent_at.or(e.sent_at);
+            }).await),
+        }
+    }
+
+    pub async fn update_attachments(&self, id: Uuid, attachments: &[EmailAttachment]) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.update_attachments(id, attachments).await,
+            Self::Memory(_) => Ok(self.update(id, |e| e.attachments = attachments.to_vec()).await),
+        }
+    }
+
+    pub async fn update_processing_status(&self, id: Uuid, status: EmailProcessingStatus) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.update_processing_status(id, status).await,
+            Self::Memory(_) => Ok(self.update(id, |e| e.processing_status = status).await),
+        }
+    }
+
+    pub async fn update_classification(&self, id: Uuid, classification: ReplyClassification, confidence: f64) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.update_classification(id, classification, confidence).await,
+            Self::Memory(_) => Ok(self.update(id, |e| {
+                e.classification = Some(classification);
+                e.classification_confidence = Some(confidence);
+            }).await),
+        }
+    }
+
+    async fn update(&self, id: Uuid, update: impl FnOnce(&mut EmailCommunication)) -> bool {
+        let Self::Memory(emails) = self else {
+            return false;
+        };
+        match emails.write().await.get_mut(&id) {
+            Some(email) => {
+                update(email);
+                email.updated_at = Utc::now();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Matching emails, newest first
+fn select(
+    emails: &HashMap<Uuid, EmailCommunication>,
+    filter: impl Fn(&EmailCommunication) -> bool,
+) -> Vec<EmailCommunication> {
+    let mut selected: Vec<EmailCommunication> = emails.values().filter(|e| filter(e)).cloned().collect();
+    selected.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+    selected
+}
@@ -0,0 +1,277 @@
+//! IMAP Client
+//!
+//! Polls the compliance inbox for supplier replies and parses them
+//! into inbound messages for the email service. Messages are fetched
+//! without marking them seen and only flagged `\Seen` once the service
+//! has processed them, so a failed message is picked up by the next poll.
+
+use anyhow::{Context, Result};
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::email_provider::EmailAttachment;
+use crate::out_of_office::{is_auto_reply, AUTO_REPLY_HEADERS};
+use crate::service::EmailService;
+use crate::smime::is_smime;
+
+/// IMAP connection configuration
+#[derive(Debug, Clone)]
+pub struct ImapConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub mailbox: String,
+    pub poll_interval: Duration,
+}
+
+impl ImapConfig {
+    /// Load from environment; `None` when `IMAP_HOST` is not configured
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("IMAP_HOST").ok()?;
+        Some(Self {
+            host,
+            port: std::env::var("IMAP_PORT").unwrap_or_else(|_| "993".to_string()).parse().unwrap_or(993),
+            username: std::env::var("IMAP_USERNAME").unwrap_or_default(),
+            password: std::env::var("IMAP_PASSWORD").unwrap_or_default(),
+            mailbox: std::env::var("IMAP_MAILBOX").unwrap_or_else(|_| "INBOX".to_string()),
+            poll_interval: Duration::from_secs(
+                std::env::var("IMAP_POLL_INTERVAL_SECS").unwrap_or_else(|_| "60".to_string()).parse().unwrap_or(60),
+            ),
+        })
+    }
+}
+
+/// Parsed inbound email
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    pub from_email: String,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<EmailAttachment>,
+    /// Headers mark the message as an automatic reply
+    pub auto_reply: bool,
+    /// Whole message when it is S/MIME signed or encrypted; the service opens
+    /// it with tenant keys and takes the body and attachments from its content
+    pub smime: Option<Vec<u8>>,
+}
+
+/// Polls an IMAP mailbox for unseen messages
+pub struct ImapPoller {
+    config: ImapConfig,
+}
+
+impl ImapPoller {
+    pub fn new(config: ImapConfig) -> Self {
+        Self { config }
+    }
+
+    /// Poll forever, handing each new message to the email service
+    pub fn spawn(self, service: EmailService) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            info!("Polling IMAP mailbox {} on {} every {:?}", self.config.mailbox, self.config.host, self.config.poll_interval);
+            let mut interval = tokio::time::interval(self.config.poll_interval);
+
+            loop {
+                interval.tick().await;
+
+                let config = self.config.clone();
+                let messages = match tokio::task::spawn_blocking(move || fetch_unseen(&config)).await {
+                    Ok(Ok(messages)) => messages,
+                    Ok(Err(e)) => {
+                        error!("IMAP poll failed: {:#}", e);
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("IMAP poll task panicked: {}", e);
+                        continue;
+                    }
+                };
+
+                let mut processed = Vec::new();
+                for (uid, message) in messages {
+                    let from = message.from_email.clone();
+                    match service.process_inbound_message(message).await {
+                        Ok(Some(email)) => info!("Recorded reply from {} on {}", from, email.thread_id),
+                        Ok(None) => warn!("Ignoring email from unknown sender {}", from),
+                        Err(e) => {
+                            // Left unseen for the next poll
+                            error!("Failed to process email from {}: {:#}", from, e);
+                            continue;
+                        }
+                    }
+                    processed.push(uid);
+                }
+
+                if !processed.is_empty() {
+                    let config = self.config.clone();
+                    match tokio::task::spawn_blocking(move || mark_seen(&config, &processed)).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("Failed to mark processed emails seen: {:#}", e),
+                        Err(e) => error!("IMAP mark seen task panicked: {}", e),
+                    }
+                }
+            }
+        })
+    }
+}
+
+type ImapSession = imap::Session<native_tls::TlsStream<std::net::TcpStream>>;
+
+/// Log in and select the configured mailbox
+fn open_mailbox(config: &ImapConfig) -> Result<ImapSession> {
+    let tls = native_tls::TlsConnector::new().context("Failed to create TLS connector")?;
+    let client = imap::connect((config.host.as_str(), config.port), &config.host, &tls)
+        .context("Failed to connect to IMAP server")?;
+    let mut session = client.login(&config.username, &config.password)
+        .map_err(|(e, _)| e)
+        .context("IMAP login failed")?;
+
+    session.select(&config.mailbox).context("Failed to select mailbox")?;
+    Ok(session)
+}
+
+/// Fetch and parse unseen messages by UID, leaving them unseen
+fn fetch_unseen(config: &ImapConfig) -> Result<Vec<(u32, InboundMessage)>> {
+    let mut session = open_mailbox(config)?;
+
+    let uids = session.uid_search("UNSEEN").context("IMAP search failed")?;
+    let mut messages = Vec::new();
+    let mut unparseable = Vec::new();
+
+    if !uids.is_empty() {
+        let fetches = session.uid_fetch(uid_set(uids.iter()), "(UID BODY.PEEK[])").context("IMAP fetch failed")?;
+
+        for fetch in fetches.iter() {
+            let Some(uid) = fetch.uid else {
+                warn!("Skipping message without a UID");
+                continue;
+            };
+            match fetch.body().and_then(parse_message) {
+                Some(message) => messages.push((uid, message)),
+                None => {
+                    warn!("Skipping unparseable message uid={}", uid);
+                    unparseable.push(uid);
+                }
+            }
+        }
+    }
+
+    // Retrying won't make these parse
+    if !unparseable.is_empty() {
+        session.uid_store(uid_set(&unparseable), "+FLAGS (\\Seen)").context("Failed to mark unparseable messages seen")?;
+    }
+
+    session.logout().ok();
+    Ok(messages)
+}
+
+/// Flag processed messages `\Seen`
+fn mark_seen(config: &ImapConfig, uids: &[u32]) -> Result<()> {
+    let mut session = open_mailbox(config)?;
+    session.uid_store(uid_set(uids), "+FLAGS (\\Seen)").context("IMAP store failed")?;
+    session.logout().ok();
+    Ok(())
+}
+
+fn uid_set<'a>(uids: impl IntoIterator<Item = &'a u32>) -> String {
+    uids.into_iter().map(|uid| uid.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// Parse raw RFC 5322 message
+pub fn parse_message(raw: &[u8]) -> Option<InboundMessage> {
+    let message = MessageParser::default().parse(raw)?;
+
+    let from_email = message.from()
+        .and_then(|from| from.first())
+        .and_then(|addr| addr.address())?
+        .to_lowercase();
+
+    let (body, attachments) = content_of(&message);
+
+    Some(InboundMessage {
+        message_id: message.message_id().map(|id| id.to_string()),
+        in_reply_to: message.in_reply_to().as_text_list()
+            .and_then(|ids| ids.last().map(|id| id.to_string())),
+        references: message.references().as_text_list()
+            .map(|ids| ids.into_iter().map(|id| id.to_string()).collect())
+            .unwrap_or_default(),
+        from_email,
+        subject: message.subject().unwrap_or_default().to_string(),
+        body,
+        attachments,
+        auto_reply: is_auto_reply(AUTO_REPLY_HEADERS.iter().filter_map(|name| Some((*name, message.header_raw(*name)?)))),
+        smime: is_smime(&message).then(|| raw.to_vec()),
+    })
+}
+
+/// Body text and attachments of a MIME entity, such as the content of an S/MIME message
+pub fn parse_content(entity: &[u8]) -> Option<(String, Vec<EmailAttachment>)> {
+    MessageParser::default().parse(entity).map(|message| content_of(&message))
+}
+
+fn content_of(message: &Message) -> (String, Vec<EmailAttachment>) {
+    let attachments = message.attachments()
+        .map(|part| EmailAttachment {
+            filename: part.attachment_name().unwrap_or("attachment").to_string(),
+            content_type: part.content_type()
+                .map(|ct| match ct.subtype() {
+                    Some(subtype) => format!("{}/{}", ct.ctype(), subtype),
+                    None => ct.ctype().to_string(),
+                })
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            data: part.contents().to_vec(),
+        })
+        .collect();
+
+    let body = message.body_text(0).map(|b| b.into_owned()).unwrap_or_default();
+    (body, attachments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reply_with_attachment() {
+        let raw = concat!(
+            "From: Jane Doe <Jane@Supplier.example>\r\n",
+            "To: compliance@elementa.io\r\n",
+            "Subject: Re: PFAS Compliance Data Request\r\n",
+            "Message-ID: <reply-1@supplier.example>\r\n",
+            "In-Reply-To: <out-2@elementa.io>\r\n",
+            "References: <out-1@elementa.io> <out-2@elementa.io>\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b\"\r\n",
+            "\r\n",
+            "--b\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Please find the SDS attached.\r\n",
+            "--b\r\n",
+            "Content-Type: application/pdf\r\n",
+            "Content-Disposition: attachment; filename=\"sds.pdf\"\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "\r\n",
+            "JVBERi0xLjQ=\r\n",
+            "--b--\r\n",
+        );
+
+        let message = parse_message(raw.as_bytes()).unwrap();
+
+        assert_eq!(message.from_email, "jane@supplier.example");
+        assert_eq!(message.message_id.as_deref(), Some("reply-1@supplier.example"));
+        assert_eq!(message.in_reply_to.as_deref(), Some("out-2@elementa.io"));
+        assert_eq!(message.references, vec!["out-1@elementa.io", "out-2@elementa.io"]);
+        assert!(message.body.contains("SDS attached"));
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].filename, "sds.pdf");
+        assert_eq!(message.attachments[0].content_type, "application/pdf");
+        assert_eq!(message.attachments[0].data, b"%PDF-1.4");
+    }
+}
@@ -0,0 +1,90 @@
+//! Inbound Deduplication
+//!
+//! Providers and IMAP polling can deliver the same message more than once.
+//! An inbound email is a duplicate when its Message-ID was already recorded,
+//! or when the supplier sent identical content within the dedup window,
+//! which catches copies whose Message-ID is missing or was rewritten.
+
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::email_provider::EmailAttachment;
+
+pub struct InboundDedup {
+    /// How long identical content from a supplier counts as a redelivery
+    pub window: Duration,
+    /// Held from the duplicate check until the email is recorded, so two
+    /// copies arriving together cannot both pass the check
+    lock: Mutex<()>,
+}
+
+impl InboundDedup {
+    pub fn new(window: Duration) -> Self {
+        Self { window, lock: Mutex::new(()) }
+    }
+
+    /// `INBOUND_DEDUP_WINDOW_HOURS`, default 72
+    pub fn from_env() -> Self {
+        let hours = std::env::var("INBOUND_DEDUP_WINDOW_HOURS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(72);
+
+        Self::new(Duration::hours(hours))
+    }
+
+    pub async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+}
+
+impl Default for InboundDedup {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Hash of subject, body and attachments. Whitespace is collapsed because
+/// webhook and IMAP copies of one message can differ in line endings and wrapping.
+pub fn content_hash(subject: &str, body: &str, attachments: &[EmailAttachment]) -> String {
+    let mut hasher = Sha256::new();
+    for text in [subject, body] {
+        for word in text.split_whitespace() {
+            hasher.update(word.as_bytes());
+            hasher.update(b" ");
+        }
+        hasher.update(b"\0");
+    }
+
+    let mut files: Vec<(&str, [u8; 32])> = attachments.iter()
+        .map(|a| (a.filename.as_str(), Sha256::digest(&a.data).into()))
+        .collect();
+    files.sort();
+    for (filename, digest) in files {
+        hasher.update(filename.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(digest);
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_ignores_whitespace_and_attachment_order() {
+        let sds = EmailAttachment { filename: "sds.pdf".to_string(), content_type: "application/pdf".to_string(), data: b"%PDF-1".to_vec() };
+        let coc = EmailAttachment { filename: "coc.pdf".to_string(), content_type: "application/pdf".to_string(), data: b"%PDF-2".to_vec() };
+
+        let webhook = content_hash("Re: PFAS request", "Please find attached.\r\n\r\nRegards", &[sds.clone(), coc.clone()]);
+        let imap = content_hash("Re: PFAS request", "Please find\nattached.\n\nRegards\n", &[coc.clone(), sds.clone()]);
+        assert_eq!(webhook, imap);
+
+        assert_ne!(webhook, content_hash("Re: PFAS request", "Please find attached. Regards", std::slice::from_ref(&sds)));
+        let mut revised = sds;
+        revised.data = b"%PDF-1 rev2".to_vec();
+        assert_ne!(webhook, content_hash("Re: PFAS request", "Please find attached. Regards", &[revised, coc]));
+    }
+}
@@ -5,12 +5,14 @@
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{Html, Json},
+    routing::{delete, get, post, put},
     Router,
 };
+use elementa_database::{DatabaseConfig, TenantAccess};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
@@ -18,9 +20,35 @@ use tower_http::trace::TraceLayer;
 use tracing::info;
 use uuid::Uuid;
 
+mod ab_testing;
+mod address_validation;
+mod branding;
+mod calendar;
+mod classifier;
+mod delivery_retry;
+mod digest;
+mod delivery_webhooks;
+mod document_client;
+mod domain_throttle;
+mod email_provider;
+mod email_store;
+mod imap_client;
+mod inbound_dedup;
+mod link_signing;
+mod non_response;
+mod out_of_office;
+mod provider_failover;
+mod send_queue;
+mod sender_domains;
+mod sender_identities;
+mod smime;
 mod smtp_client;
+mod suppressions;
 mod template_engine;
+mod thread_summary;
+mod upload_links;
 mod service;
+mod workflow_client;
 
 use service::EmailService;
 
@@ -29,16 +57,69 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     info!("Starting Elementa Email Communication Service");
     
-    let service = EmailService::new();
+    let mut service = EmailService::new(email_provider::providers_from_env()?);
+    
+    if let Some(mut db_config) = DatabaseConfig::postgres_from_env()? {
+        // Jobs and callbacks here work across tenants
+        db_config.tenant_access = TenantAccess::AllTenants;
+        db_config.max_connections = 5;
+        let pools = elementa_database::connect_postgres(&db_config).await?;
+        if let Some(rotation) = db_config.credential_rotation(&pools) {
+            tokio::spawn(rotation.run());
+        }
+        service = service.with_database(pools);
+    }
+    
+    if std::env::var("ADDRESS_VALIDATION_MX").map_or(true, |v| v != "false") {
+        service = service.with_mx_lookups();
+    }
+    
+    if let Some(config) = imap_client::ImapConfig::from_env() {
+        imap_client::ImapPoller::new(config).spawn(service.clone());
+    }
+    
+    send_queue::spawn_worker(service.clone());
+    digest::spawn_scheduler(service.clone());
     
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/api/v1/emails/send", post(send_email))
+        .route("/api/v1/emails/inbound", post(receive_inbound_email))
         .route("/api/v1/emails/:id", get(get_email))
         .route("/api/v1/emails/thread/:thread_id", get(get_thread))
+        .route("/api/v1/emails/thread/:thread_id/summary", get(get_thread_summary))
         .route("/api/v1/emails/supplier/:supplier_id", get(get_supplier_emails))
+        .route("/api/v1/providers", get(get_provider_status))
+        .route("/api/v1/webhooks/sendgrid", post(sendgrid_webhook))
+        .route("/api/v1/webhooks/ses", post(ses_webhook))
+        .route("/api/v1/campaigns/schedule", post(schedule_campaign))
+        .route("/api/v1/campaigns/:campaign_id/queue", get(get_campaign_queue))
+        .route("/api/v1/campaigns/:campaign_id/cancel", post(cancel_campaign))
+        .route("/api/v1/campaigns/:campaign_id/variants", get(get_campaign_variants))
+        .route("/api/v1/sender-domains/:tenant_id", put(set_sender_domain).get(get_sender_domain))
+        .route("/api/v1/sender-domains/:tenant_id/preflight", get(preflight_sender_domain))
+        .route("/api/v1/branding/:tenant_id", put(set_branding).get(get_branding).delete(delete_branding))
+        .route("/api/v1/smime/:tenant_id", put(set_smime_certificate).get(get_smime_certificate).delete(delete_smime_certificate))
+        .route(
+            "/api/v1/smime-recipients/:email_address",
+            put(set_recipient_certificate).get(get_recipient_certificate).delete(delete_recipient_certificate),
+        )
+        .route("/api/v1/sender-identities", post(create_sender_identity).get(list_sender_identities))
+        .route("/api/v1/sender-identities/:id", put(update_sender_identity).delete(delete_sender_identity))
+        .route("/api/v1/campaigns/:campaign_id/sender-identity", put(assign_campaign_sender))
+        .route("/api/v1/addresses/validate", post(validate_addresses))
+        .route("/api/v1/suppressions", post(create_suppression).get(list_suppressions))
+        .route("/api/v1/suppressions/:id", delete(lift_suppression))
+        .route("/api/v1/unsubscribe/:token", get(unsubscribe_page).post(unsubscribe))
+        .route(
+            "/api/v1/uploads/:token",
+            get(upload_page).post(upload_documents).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/api/v1/digests/preview", get(preview_digest))
+        .route("/api/v1/digests/send", post(send_digest))
         .route("/api/v1/templates", get(list_templates))
         .route("/api/v1/templates/:template_id/render", post(render_template))
+        .route("/api/v1/templates/:template_id/preview", post(preview_template))
         .layer(TraceLayer::new_for_http())
         .with_state(service);
     
@@ -60,18 +141,34 @@ async fn health_check() -> Json<serde_json::Value> {
 }
 
 /// Send email request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SendEmailRequest {
     pub supplier_id: Uuid,
     pub template_id: String,
+    /// Selects the tenant's sender domain; the default sender is used when absent
+    pub tenant_id: Option<String>,
+    /// Send as a follow-up in an existing thread
+    pub thread_id: Option<String>,
+    /// Campaign the email belongs to; set for campaign sends and carried into upload links
+    pub campaign_id: Option<Uuid>,
+    /// A/B test variant (`A` or `B`); set when a campaign splits its suppliers
+    pub template_variant: Option<String>,
+    /// Send as this identity instead of the campaign's or tenant's sender
+    pub sender_identity_id: Option<Uuid>,
+    /// Attach an .ics event for this response deadline (RFC 3339 or YYYY-MM-DD);
+    /// also fills the `deadline` variable when it is not given
+    pub calendar_deadline: Option<String>,
     pub subject: Option<String>,
+    /// Template language; defaults to the supplier's preferred language
+    pub language: Option<String>,
     pub variables: std::collections::HashMap<String, String>,
     pub attachments: Option<Vec<AttachmentRequest>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct AttachmentRequest {
     pub filename: String,
+    pub content_type: Option<String>,
     pub content_base64: String,
 }
 
@@ -82,6 +179,7 @@ pub struct SendEmailResponse {
     pub thread_id: String,
     pub recipient: String,
     pub subject: String,
+    pub language: String,
     pub status: String,
     pub sent_at: String,
 }
@@ -91,11 +189,233 @@ async fn send_email(
     Json(request): Json<SendEmailRequest>,
 ) -> Result<Json<SendEmailResponse>, (StatusCode, String)> {
     let result = service.send_compliance_email(request).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| match e.downcast_ref::<suppressions::RecipientSuppressed>() {
+            Some(suppressed) => (StatusCode::CONFLICT, suppressed.to_string()),
+            None => match e.downcast_ref::<address_validation::UndeliverableAddress>() {
+                Some(undeliverable) => (StatusCode::UNPROCESSABLE_ENTITY, undeliverable.to_string()),
+                None => match e.downcast_ref::<domain_throttle::DomainThrottled>() {
+                    Some(throttled) => (StatusCode::TOO_MANY_REQUESTS, throttled.to_string()),
+                    None => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+                },
+            },
+        })?;
     
     Ok(Json(result))
 }
 
+/// Outbound providers in failover order
+async fn get_provider_status(
+    State(service): State<EmailService>,
+) -> Json<Vec<provider_failover::ProviderStatus>> {
+    Json(service.provider_status().await)
+}
+
+/// Delivery webhook result
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub received: usize,
+    pub updated: usize,
+}
+
+/// SendGrid Signed Event Webhook
+async fn sendgrid_webhook(
+    State(service): State<EmailService>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookResponse>, (StatusCode, String)> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    service.webhook_verifier()
+        .verify_sendgrid(
+            header("X-Twilio-Email-Event-Webhook-Signature"),
+            header("X-Twilio-Email-Event-Webhook-Timestamp"),
+            &body,
+        )
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+    
+    let events = delivery_webhooks::parse_sendgrid_events(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let updated = service.apply_delivery_events(&events).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(WebhookResponse { received: events.len(), updated }))
+}
+
+/// SES events delivered through an SNS HTTPS subscription
+async fn ses_webhook(
+    State(service): State<EmailService>,
+    body: String,
+) -> Result<Json<WebhookResponse>, (StatusCode, String)> {
+    let message: delivery_webhooks::SnsMessage = serde_json::from_str(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let verifier = service.webhook_verifier();
+    verifier.verify_sns(&message).await
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+    
+    let events: Vec<_> = match message.kind.as_str() {
+        "SubscriptionConfirmation" => {
+            verifier.confirm_subscription(&message).await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+            info!("Confirmed SNS subscription to {}", message.topic_arn);
+            Vec::new()
+        }
+        "Notification" => delivery_webhooks::parse_ses_event(&message.message)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    };
+    let updated = service.apply_delivery_events(&events).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(WebhookResponse { received: events.len(), updated }))
+}
+
+/// Campaign scheduling request
+#[derive(Debug, Deserialize)]
+pub struct ScheduleCampaignRequest {
+    pub campaign_id: Option<Uuid>,
+    /// RFC 3339; defaults to now
+    pub start_at: Option<String>,
+    pub stagger_seconds: Option<u32>,
+    pub quiet_hours: Option<send_queue::QuietHours>,
+    /// Identity every email in the campaign is sent as
+    pub sender_identity_id: Option<Uuid>,
+    /// Campaign response deadline (RFC 3339 or YYYY-MM-DD); required with `attach_calendar`
+    pub deadline: Option<String>,
+    /// Attach a calendar event for the deadline to every email
+    #[serde(default)]
+    pub attach_calendar: bool,
+    /// Send an alternative template to a share of the suppliers
+    pub ab_test: Option<AbTestRequest>,
+    pub emails: Vec<ScheduledEmailRequest>,
+}
+
+/// Variant B of a campaign; variant A is each email's own template
+#[derive(Debug, Deserialize)]
+pub struct AbTestRequest {
+    pub variant_b_template_id: String,
+    pub variant_b_subject: Option<String>,
+    /// Share of suppliers sent variant B; defaults to 50
+    pub variant_b_percent: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledEmailRequest {
+    #[serde(flatten)]
+    pub email: SendEmailRequest,
+    /// Recipient's IANA timezone, e.g. `Europe/Berlin`; defaults to UTC
+    pub timezone: Option<String>,
+}
+
+/// Campaign send queue status
+#[derive(Debug, Serialize)]
+pub struct CampaignQueueResponse {
+    pub campaign_id: Uuid,
+    pub counts: QueueCounts,
+    pub next_send_at: Option<String>,
+    pub emails: Vec<QueuedEmailResponse>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct QueueCounts {
+    pub queued: usize,
+    pub sending: usize,
+    pub sent: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueuedEmailResponse {
+    pub id: Uuid,
+    pub supplier_id: Uuid,
+    pub recipient: String,
+    pub timezone: String,
+    pub scheduled_for: String,
+    pub status: String,
+    pub template_variant: Option<String>,
+    pub email_id: Option<Uuid>,
+    pub sent_at: Option<String>,
+    pub error: Option<String>,
+}
+
+async fn schedule_campaign(
+    State(service): State<EmailService>,
+    Json(request): Json<ScheduleCampaignRequest>,
+) -> Result<Json<CampaignQueueResponse>, (StatusCode, String)> {
+    let queue = service.schedule_campaign(request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(queue))
+}
+
+async fn get_campaign_queue(
+    State(service): State<EmailService>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<Json<CampaignQueueResponse>, (StatusCode, String)> {
+    let queue = service.campaign_queue(campaign_id).await;
+    if queue.emails.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Campaign not found".to_string()));
+    }
+    
+    Ok(Json(queue))
+}
+
+async fn cancel_campaign(
+    State(service): State<EmailService>,
+    Path(campaign_id): Path<Uuid>,
+) -> Json<CampaignQueueResponse> {
+    Json(service.cancel_campaign(campaign_id).await)
+}
+
+/// A/B test results of a campaign
+#[derive(Debug, Serialize)]
+pub struct CampaignVariantsResponse {
+    pub campaign_id: Uuid,
+    pub variants: Vec<ab_testing::VariantStats>,
+}
+
+async fn get_campaign_variants(
+    State(service): State<EmailService>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<Json<CampaignVariantsResponse>, (StatusCode, String)> {
+    let variants = service.campaign_variants(campaign_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if variants.variants.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Campaign has no A/B test".to_string()));
+    }
+    
+    Ok(Json(variants))
+}
+
+/// Inbound supplier reply
+#[derive(Debug, Deserialize)]
+pub struct InboundEmailRequest {
+    pub supplier_id: Uuid,
+    pub thread_id: Option<String>,
+    pub message_id: Option<String>,
+    /// Used to find the thread when `thread_id` is not given
+    pub in_reply_to: Option<String>,
+    pub references: Option<Vec<String>>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Option<Vec<AttachmentRequest>>,
+    /// Original message headers; only auto-reply markers such as `Auto-Submitted` are used
+    #[serde(default)]
+    pub headers: std::collections::HashMap<String, String>,
+}
+
+/// Receive supplier reply and ingest its attachments
+async fn receive_inbound_email(
+    State(service): State<EmailService>,
+    Json(request): Json<InboundEmailRequest>,
+) -> Result<Json<EmailResponse>, (StatusCode, String)> {
+    let email = service.receive_inbound_email(request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(email))
+}
+
 /// Email response
 #[derive(Debug, Serialize)]
 pub struct EmailResponse {
@@ -109,6 +429,41 @@ pub struct EmailResponse {
     pub received_at: Option<String>,
     pub delivery_status: String,
     pub processing_status: String,
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    /// Set on inbound replies once classified
+    pub classification: Option<elementa_models::ReplyClassification>,
+    pub classification_confidence: Option<f64>,
+    /// Whether the email was S/MIME signed or encrypted, and whether an inbound signature verified
+    pub smime: elementa_models::SmimeStatus,
+    pub attachments: Vec<AttachmentResponse>,
+    /// Provider that delivered an outbound email
+    pub delivered_via: Option<String>,
+    /// Provider hand-off attempts for outbound emails
+    pub delivery_attempts: Vec<DeliveryAttemptResponse>,
+}
+
+/// Attachment ingestion status
+#[derive(Debug, Serialize)]
+pub struct AttachmentResponse {
+    pub filename: String,
+    pub document_ids: Vec<Uuid>,
+    pub status: String,
+    pub cas_numbers_found: usize,
+    pub needs_review: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryAttemptResponse {
+    pub attempt: u32,
+    pub attempted_at: String,
+    pub provider: Option<String>,
+    pub succeeded: bool,
+    pub transient: bool,
+    pub error: Option<String>,
+    pub next_attempt_at: Option<String>,
 }
 
 async fn get_email(
@@ -132,6 +487,42 @@ async fn get_thread(
     Ok(Json(emails))
 }
 
+/// Condensed view of a long supplier thread
+#[derive(Debug, Serialize)]
+pub struct ThreadSummaryResponse {
+    pub thread_id: String,
+    pub message_count: usize,
+    pub summary: String,
+    pub commitments: Vec<String>,
+    pub outstanding_items: Vec<String>,
+    /// Own text of the supplier's latest reply, without quoted history
+    pub last_supplier_statement: Option<SupplierStatementResponse>,
+    /// Model that wrote the summary
+    pub model: String,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplierStatementResponse {
+    pub received_at: String,
+    pub subject: String,
+    pub text: String,
+}
+
+async fn get_thread_summary(
+    State(service): State<EmailService>,
+    Path(thread_id): Path<String>,
+) -> Result<Json<ThreadSummaryResponse>, (StatusCode, String)> {
+    let summary = service.summarize_thread(&thread_id).await
+        .map_err(|e| match e.downcast_ref::<thread_summary::SummariesDisabled>() {
+            Some(disabled) => (StatusCode::SERVICE_UNAVAILABLE, disabled.to_string()),
+            None => (StatusCode::BAD_GATEWAY, format!("{:#}", e)),
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Thread not found".to_string()))?;
+    
+    Ok(Json(summary))
+}
+
 async fn get_supplier_emails(
     State(service): State<EmailService>,
     Path(supplier_id): Path<Uuid>,
@@ -142,6 +533,459 @@ async fn get_supplier_emails(
     Ok(Json(emails))
 }
 
+/// Sender domain configuration request
+#[derive(Debug, Deserialize)]
+pub struct SenderDomainRequest {
+    pub from_email: String,
+    pub from_name: String,
+    pub dkim_selector: Option<String>,
+    /// RSA keys as PKCS#1 PEM, Ed25519 keys as base64
+    pub dkim_private_key: Option<String>,
+    pub dkim_algorithm: Option<String>,
+}
+
+/// Sender domain configuration; private keys are never returned
+#[derive(Debug, Serialize)]
+pub struct SenderDomainResponse {
+    pub tenant_id: String,
+    pub domain: String,
+    pub from_email: String,
+    pub from_name: String,
+    pub dkim_selector: Option<String>,
+    pub dkim_algorithm: Option<String>,
+}
+
+async fn set_sender_domain(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SenderDomainRequest>,
+) -> Result<Json<SenderDomainResponse>, (StatusCode, String)> {
+    let result = service.set_sender_domain(&tenant_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(result))
+}
+
+async fn get_sender_domain(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<SenderDomainResponse>, (StatusCode, String)> {
+    let domain = service.get_sender_domain(&tenant_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(domain))
+}
+
+/// Check SPF, DKIM and DMARC records before sending from a domain
+async fn preflight_sender_domain(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<sender_domains::PreflightReport>, (StatusCode, String)> {
+    let report = service.preflight_sender_domain(&tenant_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(report))
+}
+
+async fn set_branding(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<branding::TenantBranding>,
+) -> Result<Json<branding::TenantBranding>, (StatusCode, String)> {
+    let branding = service.set_branding(&tenant_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(branding))
+}
+
+async fn get_branding(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<branding::TenantBranding>, (StatusCode, String)> {
+    service.get_branding(&tenant_id).await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Tenant has no branding".to_string()))
+}
+
+async fn delete_branding(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> StatusCode {
+    if service.delete_branding(&tenant_id).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Tenant S/MIME certificate, also used to decrypt inbound email
+#[derive(Debug, Deserialize)]
+pub struct SmimeCertificateRequest {
+    pub certificate_pem: String,
+    /// PKCS#8 or PKCS#1 PEM
+    pub private_key_pem: String,
+    /// Intermediate CA certificates sent with each signature
+    pub chain_pem: Option<String>,
+    #[serde(default)]
+    pub sign_outbound: bool,
+    /// Encrypt to recipients whose certificate is known
+    #[serde(default)]
+    pub encrypt_outbound: bool,
+}
+
+/// Tenant S/MIME configuration; the private key is never returned
+#[derive(Debug, Serialize)]
+pub struct SmimeCertificateResponse {
+    pub tenant_id: String,
+    /// Addresses the certificate is issued for
+    pub addresses: Vec<String>,
+    pub not_after: String,
+    pub sign_outbound: bool,
+    pub encrypt_outbound: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecipientCertificateRequest {
+    pub certificate_pem: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecipientCertificateResponse {
+    pub email_address: String,
+    pub not_after: String,
+}
+
+async fn set_smime_certificate(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SmimeCertificateRequest>,
+) -> Result<Json<SmimeCertificateResponse>, (StatusCode, String)> {
+    let result = service.set_smime_certificate(&tenant_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(result))
+}
+
+async fn get_smime_certificate(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<SmimeCertificateResponse>, (StatusCode, String)> {
+    service.get_smime_certificate(&tenant_id).await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Tenant has no S/MIME certificate".to_string()))
+}
+
+async fn delete_smime_certificate(
+    State(service): State<EmailService>,
+    Path(tenant_id): Path<String>,
+) -> StatusCode {
+    if service.delete_smime_certificate(&tenant_id).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Encrypt outbound email to a supplier contact
+async fn set_recipient_certificate(
+    State(service): State<EmailService>,
+    Path(email_address): Path<String>,
+    Json(request): Json<RecipientCertificateRequest>,
+) -> Result<Json<RecipientCertificateResponse>, (StatusCode, String)> {
+    let result = service.set_recipient_certificate(&email_address, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(result))
+}
+
+async fn get_recipient_certificate(
+    State(service): State<EmailService>,
+    Path(email_address): Path<String>,
+) -> Result<Json<RecipientCertificateResponse>, (StatusCode, String)> {
+    service.get_recipient_certificate(&email_address).await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "No certificate for this address".to_string()))
+}
+
+async fn delete_recipient_certificate(
+    State(service): State<EmailService>,
+    Path(email_address): Path<String>,
+) -> StatusCode {
+    if service.delete_recipient_certificate(&email_address).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Named From/Reply-To identity with an optional signature
+#[derive(Debug, Deserialize)]
+pub struct SenderIdentityRequest {
+    /// Tenant whose sender domain and DKIM key the identity sends through
+    pub tenant_id: Option<String>,
+    /// Defaults to the from name
+    pub label: Option<String>,
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    /// Plain text, appended to every email sent as this identity
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SenderIdentityResponse {
+    pub id: Uuid,
+    pub tenant_id: Option<String>,
+    pub label: String,
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    pub signature: Option<String>,
+    pub created_at: String,
+}
+
+/// Identity assignment for a campaign or workflow
+#[derive(Debug, Deserialize)]
+pub struct CampaignSenderRequest {
+    pub sender_identity_id: Uuid,
+}
+
+async fn create_sender_identity(
+    State(service): State<EmailService>,
+    Json(request): Json<SenderIdentityRequest>,
+) -> Result<Json<SenderIdentityResponse>, (StatusCode, String)> {
+    let identity = service.create_sender_identity(request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(identity))
+}
+
+async fn list_sender_identities(
+    State(service): State<EmailService>,
+) -> Json<Vec<SenderIdentityResponse>> {
+    Json(service.list_sender_identities().await)
+}
+
+async fn update_sender_identity(
+    State(service): State<EmailService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SenderIdentityRequest>,
+) -> Result<Json<SenderIdentityResponse>, (StatusCode, String)> {
+    let identity = service.update_sender_identity(id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Sender identity not found".to_string()))?;
+    
+    Ok(Json(identity))
+}
+
+async fn delete_sender_identity(
+    State(service): State<EmailService>,
+    Path(id): Path<Uuid>,
+) -> StatusCode {
+    if service.delete_sender_identity(id).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Send a campaign's emails, including queued ones, as an identity
+async fn assign_campaign_sender(
+    State(service): State<EmailService>,
+    Path(campaign_id): Path<Uuid>,
+    Json(request): Json<CampaignSenderRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    service.assign_campaign_sender(campaign_id, request.sender_identity_id).await
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("{:#}", e)))?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Addresses to check before use
+#[derive(Debug, Deserialize)]
+pub struct ValidateAddressesRequest {
+    pub email_addresses: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateAddressesResponse {
+    pub deliverable: usize,
+    pub undeliverable: usize,
+    pub results: Vec<address_validation::AddressCheck>,
+}
+
+async fn validate_addresses(
+    State(service): State<EmailService>,
+    Json(request): Json<ValidateAddressesRequest>,
+) -> Json<ValidateAddressesResponse> {
+    Json(service.validate_addresses(request).await)
+}
+
+/// Do-not-contact request; give a supplier, an address, or both
+#[derive(Debug, Deserialize)]
+pub struct SuppressionRequest {
+    pub supplier_id: Option<Uuid>,
+    pub email_address: Option<String>,
+    pub reason: Option<String>,
+    /// Client the suppression is recorded for; the supplier's when absent
+    pub tenant_id: Option<String>,
+}
+
+/// Suppression list entry
+#[derive(Debug, Serialize)]
+pub struct SuppressionResponse {
+    pub id: Uuid,
+    pub supplier_id: Option<Uuid>,
+    pub email_address: Option<String>,
+    pub reason: String,
+    pub source: String,
+    pub created_at: String,
+}
+
+async fn create_suppression(
+    State(service): State<EmailService>,
+    Json(request): Json<SuppressionRequest>,
+) -> Result<Json<SuppressionResponse>, (StatusCode, String)> {
+    let suppression = service.suppress(request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(suppression))
+}
+
+async fn list_suppressions(
+    State(service): State<EmailService>,
+) -> Result<Json<Vec<SuppressionResponse>>, (StatusCode, String)> {
+    let suppressions = service.list_suppressions().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(suppressions))
+}
+
+async fn lift_suppression(
+    State(service): State<EmailService>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let lifted = service.lift_suppression(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if lifted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "Suppression not found".to_string()))
+    }
+}
+
+/// Confirmation page for the unsubscribe link in email footers. Link
+/// scanners follow GETs, so only the form's POST unsubscribes.
+async fn unsubscribe_page(Path(token): Path<String>) -> Html<String> {
+    Html(format!(
+        "<!DOCTYPE html><html><body>\
+         <p>Stop receiving compliance requests from Elementa at this address?</p>\
+         <form method=\"post\" action=\"/api/v1/unsubscribe/{}\"><button type=\"submit\">Unsubscribe</button></form>\
+         </body></html>",
+        handlebars::html_escape(&token)
+    ))
+}
+
+/// Unsubscribe from the confirmation form or an RFC 8058 one-click POST
+async fn unsubscribe(
+    State(service): State<EmailService>,
+    Path(token): Path<String>,
+) -> Result<Html<&'static str>, (StatusCode, String)> {
+    service.unsubscribe(&token).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Html("<!DOCTYPE html><html><body><p>You have been unsubscribed.</p></body></html>"))
+}
+
+/// Supplier upload link requests, covering all files in one form
+const MAX_UPLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+/// Upload form behind the link in outreach emails
+async fn upload_page(
+    State(service): State<EmailService>,
+    Path(token): Path<String>,
+) -> Result<Html<String>, (StatusCode, Html<String>)> {
+    service.verify_upload_link(&token)
+        .map_err(|e| (StatusCode::FORBIDDEN, Html(format!(
+            "<!DOCTYPE html><html><body><p>{}</p></body></html>",
+            handlebars::html_escape(&format!("{:#}", e))
+        ))))?;
+    
+    Ok(Html(format!(
+        "<!DOCTYPE html><html><body>\
+         <p>Upload your compliance documents (SDS, test reports, declarations or a ZIP of them).</p>\
+         <form method=\"post\" enctype=\"multipart/form-data\" action=\"/api/v1/uploads/{}\">\
+         <input type=\"file\" name=\"files\" multiple required> <button type=\"submit\">Upload</button></form>\
+         </body></html>",
+        handlebars::html_escape(&token)
+    )))
+}
+
+/// Accept files from a supplier upload link
+async fn upload_documents(
+    State(service): State<EmailService>,
+    Path(token): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<EmailResponse>, (StatusCode, String)> {
+    service.verify_upload_link(&token)
+        .map_err(|e| (StatusCode::FORBIDDEN, format!("{:#}", e)))?;
+    
+    let mut files = Vec::new();
+    while let Some(field) = multipart.next_field().await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Upload error: {}", e)))?
+    {
+        let Some(filename) = field.file_name().filter(|f| !f.is_empty()).map(|f| f.to_string()) else {
+            continue;
+        };
+        let content_type = field.content_type()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| document_client::guess_content_type(&filename).to_string());
+        let data = field.bytes().await
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Read error: {}", e)))?;
+        
+        files.push(email_provider::EmailAttachment { filename, content_type, data: data.to_vec() });
+    }
+    
+    let email = service.receive_upload(&token, files).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(email))
+}
+
+/// Rendered compliance digest with headline counts
+#[derive(Debug, Serialize)]
+pub struct DigestResponse {
+    /// Recipients it was delivered to; empty for previews
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: String,
+    pub pending_responses: usize,
+    pub pfas_detections: usize,
+    pub upcoming_deadlines: usize,
+    pub open_escalations: usize,
+}
+
+async fn preview_digest(
+    State(service): State<EmailService>,
+) -> Result<Json<DigestResponse>, (StatusCode, String)> {
+    let digest = service.preview_digest().await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("{:#}", e)))?;
+    
+    Ok(Json(digest))
+}
+
+/// Send the digest now, outside its weekly schedule
+async fn send_digest(
+    State(service): State<EmailService>,
+) -> Result<Json<DigestResponse>, (StatusCode, String)> {
+    let digest = service.send_digest().await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("{:#}", e)))?;
+    
+    Ok(Json(digest))
+}
+
 /// Template list response
 #[derive(Debug, Serialize)]
 pub struct TemplateListResponse {
@@ -154,6 +998,7 @@ pub struct TemplateInfo {
     pub name: String,
     pub description: String,
     pub variables: Vec<String>,
+    pub languages: Vec<String>,
 }
 
 async fn list_templates(
@@ -166,11 +1011,13 @@ async fn list_templates(
 /// Render template request
 #[derive(Debug, Deserialize)]
 pub struct RenderTemplateRequest {
+    pub language: Option<String>,
     pub variables: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct RenderTemplateResponse {
+    pub language: String,
     pub subject: String,
     pub body: String,
 }
@@ -180,8 +1027,55 @@ async fn render_template(
     Path(template_id): Path<String>,
     Json(request): Json<RenderTemplateRequest>,
 ) -> Result<Json<RenderTemplateResponse>, (StatusCode, String)> {
-    let result = service.render_template(&template_id, &request.variables)
+    let result = service.render_template(&template_id, request.language.as_deref(), &request.variables)
         .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
     
     Ok(Json(result))
-}
\ No newline at end of file
+}
+
+/// Template preview request; values not given are filled with samples
+#[derive(Debug, Default, Deserialize)]
+pub struct PreviewTemplateRequest {
+    pub language: Option<String>,
+    /// Apply this tenant's branding
+    pub tenant_id: Option<String>,
+    /// Lists are allowed, e.g. for `#each` blocks
+    pub variables: Option<std::collections::HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplatePreviewResponse {
+    pub template_id: String,
+    pub language: String,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: String,
+    /// Referenced by the template but not provided; rendered with `sample_value`
+    pub missing_variables: Vec<MissingVariableResponse>,
+    /// Provided but not referenced by the template
+    pub unused_variables: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissingVariableResponse {
+    pub name: String,
+    pub required: bool,
+    pub sample_value: serde_json::Value,
+}
+
+/// Render a template with sample data so authors can check it before a send
+async fn preview_template(
+    State(service): State<EmailService>,
+    Path(template_id): Path<String>,
+    request: Option<Json<PreviewTemplateRequest>>,
+) -> Result<Json<TemplatePreviewResponse>, (StatusCode, String)> {
+    if !service.has_template(&template_id) {
+        return Err((StatusCode::NOT_FOUND, "Template not found".to_string()));
+    }
+    
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let preview = service.preview_template(&template_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    
+    Ok(Json(preview))
+}
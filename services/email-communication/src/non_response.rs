@@ -0,0 +1,96 @@
+//! Non-Response Escalation
+//!
+//! Counts the follow-ups a thread has gone without a reply. When a supplier
+//! has ignored the configured number, the workflow service is told so it
+//! can escalate rather than wait for someone to notice the silence.
+
+use elementa_models::{DeliveryStatus, EmailCommunication, EmailDirection, ReplyClassification};
+
+#[derive(Debug, Clone)]
+pub struct NonResponseConfig {
+    /// Unanswered follow-ups that trigger an escalation; 0 disables it
+    pub escalate_after: usize,
+}
+
+impl NonResponseConfig {
+    /// `ESCALATE_AFTER_FOLLOW_UPS`, default 3
+    pub fn from_env() -> Self {
+        let escalate_after = std::env::var("ESCALATE_AFTER_FOLLOW_UPS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3);
+
+        Self { escalate_after }
+    }
+
+    /// Whether the thread just reached the threshold. Only the send that
+    /// reaches it escalates, so later chasers do not escalate again.
+    pub fn should_escalate(&self, unanswered_follow_ups: usize) -> bool {
+        self.escalate_after > 0 && unanswered_follow_ups == self.escalate_after
+    }
+}
+
+impl Default for NonResponseConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Emails sent since the supplier last replied, not counting the first of
+/// them, which is the request being followed up. Out-of-office replies are
+/// not answers, and emails that never went out are not follow-ups.
+pub fn unanswered_follow_ups(thread: &[EmailCommunication]) -> usize {
+    let mut emails: Vec<&EmailCommunication> = thread.iter().collect();
+    emails.sort_by_key(|e| e.created_at);
+
+    let unanswered = emails.iter().rev()
+        .take_while(|e| match e.direction {
+            EmailDirection::Inbound => matches!(e.classification, Some(ReplyClassification::OutOfOffice)),
+            EmailDirection::Outbound => true,
+        })
+        .filter(|e| {
+            matches!(e.direction, EmailDirection::Outbound)
+                && !matches!(e.delivery_status, DeliveryStatus::Failed | DeliveryStatus::Bounced)
+        })
+        .count();
+    unanswered.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_counts_follow_ups_since_last_reply() {
+        let start = Utc::now();
+        let email = |day: i64, direction: EmailDirection, classification: Option<ReplyClassification>| EmailCommunication {
+            direction,
+            classification,
+            delivery_status: DeliveryStatus::Sent,
+            created_at: start + Duration::days(day),
+            ..EmailCommunication::default()
+        };
+
+        let mut thread = vec![
+            email(0, EmailDirection::Outbound, None),
+            email(7, EmailDirection::Outbound, None),
+        ];
+        assert_eq!(unanswered_follow_ups(&thread), 1);
+
+        // An auto-reply does not reset the count; a bounced chaser does not add to it
+        thread.push(email(8, EmailDirection::Inbound, Some(ReplyClassification::OutOfOffice)));
+        thread.push(EmailCommunication { delivery_status: DeliveryStatus::Bounced, ..email(14, EmailDirection::Outbound, None) });
+        thread.push(email(21, EmailDirection::Outbound, None));
+        assert_eq!(unanswered_follow_ups(&thread), 2);
+
+        // A real reply does
+        thread.push(email(22, EmailDirection::Inbound, Some(ReplyClassification::Question)));
+        thread.push(email(23, EmailDirection::Outbound, None));
+        assert_eq!(unanswered_follow_ups(&thread), 0);
+
+        let config = NonResponseConfig { escalate_after: 2 };
+        assert!(config.should_escalate(2));
+        assert!(!config.should_escalate(3));
+        assert!(!NonResponseConfig { escalate_after: 0 }.should_escalate(0));
+    }
+}
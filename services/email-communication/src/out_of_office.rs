@@ -0,0 +1,150 @@
+//! Out-of-Office Detection
+//!
+//! Recognises automatic replies by their headers (RFC 3834 `Auto-Submitted`
+//! and vendor equivalents) and extracts the day an absent contact is back,
+//! so the workflow can hold follow-ups until then instead of counting the
+//! auto-reply as a supplier response.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Headers that mark a message as automatically generated
+pub const AUTO_REPLY_HEADERS: [&str; 5] = ["Auto-Submitted", "X-Autoreply", "X-Autorespond", "X-Autoresponder", "Precedence"];
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+/// Words that can sit between a marker and its date, as in "back in the office on Monday, 9 March"
+const FILLER: &[&str] = &[
+    "in", "the", "office", "to", "work", "on", "and", "including", "from", "at",
+    "monday", "mon", "tuesday", "tue", "tues", "wednesday", "wed", "thursday", "thu", "thurs",
+    "friday", "fri", "saturday", "sat", "sunday", "sun",
+];
+
+/// Whether the given headers mark the message as an automatic reply
+pub fn is_auto_reply<'a>(headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> bool {
+    headers.into_iter().any(|(name, value)| {
+        let value = value.trim().to_lowercase();
+        match name.to_lowercase().as_str() {
+            "auto-submitted" => !value.is_empty() && value != "no",
+            "x-autoreply" | "x-autorespond" | "x-autoresponder" => !value.is_empty(),
+            "precedence" => value == "auto_reply",
+            _ => false,
+        }
+    })
+}
+
+/// First day the sender of an out-of-office reply is back, if the reply says.
+/// "Back on 9 March" gives 9 March; "away until 6 March" gives 7 March. When
+/// several dates are mentioned the latest wins. Day/month order is only
+/// guessed for dotted dates; ambiguous slashed dates such as 3/5 are ignored.
+pub fn return_date(body: &str, received_at: DateTime<Utc>) -> Option<NaiveDate> {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    let marker = MARKER.get_or_init(|| {
+        Regex::new(r"\b(?:(back|return|returning)|(until|till|through|thru))\b").expect("valid regex")
+    });
+
+    let text = body.to_lowercase();
+    let received = received_at.date_naive();
+    marker.captures_iter(&text)
+        .filter_map(|captures| {
+            let end = captures.get(0)?.end();
+            let date = parse_date(skip_filler(&text[end..]), received)?;
+            match captures.get(2) {
+                Some(_) => date.succ_opt(),
+                None => Some(date),
+            }
+        })
+        // Anything further out is more likely a misread than a sabbatical
+        .filter(|date| *date <= received + Duration::days(365))
+        .max()
+}
+
+fn skip_filler(mut text: &str) -> &str {
+    loop {
+        let trimmed = text.trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == ':');
+        let word_end = trimmed.find(|c: char| !c.is_alphabetic()).unwrap_or(trimmed.len());
+        let word = &trimmed[..word_end];
+        if word.is_empty() || !FILLER.contains(&word) {
+            return trimmed;
+        }
+        text = trimmed[word_end..].strip_prefix('.').unwrap_or(&trimmed[word_end..]);
+    }
+}
+
+/// Date at the very start of `text`
+fn parse_date(text: &str, received: NaiveDate) -> Option<NaiveDate> {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    let date = DATE.get_or_init(|| {
+        Regex::new(concat!(
+            r"^(?:(?P<iso_y>\d{4})-(?P<iso_m>\d{1,2})-(?P<iso_d>\d{1,2})",
+            r"|(?P<num_a>\d{1,2})(?P<sep>[./])(?P<num_b>\d{1,2})(?:[./](?P<num_y>\d{2}(?:\d{2})?))?\b",
+            r"|(?P<dm_d>\d{1,2})(?:st|nd|rd|th)?(?:\s+of)?\s+(?P<dm_m>[a-z]{3,9})\b\.?(?:,?\s+(?P<dm_y>\d{4}))?",
+            r"|(?P<md_m>[a-z]{3,9})\.?\s+(?P<md_d>\d{1,2})(?:st|nd|rd|th)?\b(?:,?\s+(?P<md_y>\d{4}))?)",
+        )).expect("valid regex")
+    });
+
+    let captures = date.captures(text)?;
+    let number = |name: &str| captures.name(name).and_then(|m| m.as_str().parse::<u32>().ok());
+    let year = |name: &str| number(name).map(|y| if y < 100 { 2000 + y as i32 } else { y as i32 });
+
+    let (day, month, year) = if let Some(y) = year("iso_y") {
+        (number("iso_d")?, number("iso_m")?, Some(y))
+    } else if let (Some(a), Some(b)) = (number("num_a"), number("num_b")) {
+        match &captures["sep"] {
+            "." => (a, b, year("num_y")),
+            // Only unambiguous when one side cannot be a month
+            _ if a > 12 && b <= 12 => (a, b, year("num_y")),
+            _ if b > 12 && a <= 12 => (b, a, year("num_y")),
+            _ => return None,
+        }
+    } else if let Some(day) = number("dm_d") {
+        (day, month_number(&captures["dm_m"])?, year("dm_y"))
+    } else {
+        (number("md_d")?, month_number(&captures["md_m"])?, year("md_y"))
+    };
+
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        // A yearless date well in the past means next year, as in a December reply about January
+        None => {
+            let this_year = NaiveDate::from_ymd_opt(received.year(), month, day)?;
+            if this_year < received - Duration::days(30) {
+                NaiveDate::from_ymd_opt(received.year() + 1, month, day)
+            } else {
+                Some(this_year)
+            }
+        }
+    }
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    MONTHS.iter()
+        .position(|month| month.starts_with(word) && word.len() >= 3)
+        .map(|index| index as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_return_date_extraction() {
+        let received = DateTime::parse_from_rfc3339("2026-12-18T10:00:00Z").unwrap().with_timezone(&Utc);
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+
+        assert_eq!(return_date("I am out of the office and will be back in the office on Monday, January 5th.", received), date("2027-01-05"));
+        assert_eq!(return_date("On leave until 23.12.2026, with limited access to email.", received), date("2026-12-24"));
+        assert_eq!(return_date("Away from 20 Dec through Jan 2, returning on 2027-01-04.", received), date("2027-01-04"));
+        assert_eq!(return_date("I will return 12/28.", received), date("2026-12-28"));
+        assert_eq!(return_date("I will return 1/4.", received), None);
+        assert_eq!(return_date("Thank you, I'll get back to you shortly.", received), None);
+
+        assert!(is_auto_reply([("Auto-Submitted", " auto-replied")]));
+        assert!(is_auto_reply([("Precedence", "auto_reply")]));
+        assert!(!is_auto_reply([("Auto-Submitted", "no"), ("Precedence", "bulk")]));
+    }
+}
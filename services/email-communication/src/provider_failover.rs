@@ -0,0 +1,256 @@
+//! Provider Failover
+//!
+//! Outbound email goes to the first healthy provider in the configured
+//! order and fails over to the next one when it errors. A provider that
+//! fails `failure_threshold` sends in a row is skipped until its cooldown
+//! ends. If every provider is cooling down they are still tried, soonest
+//! recovery first, so an outage never refuses a send outright.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::email_provider::{EmailProvider, OutgoingEmail};
+
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// Consecutive failures before a provider is taken out of rotation
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for FailoverConfig {
+    /// `EMAIL_FAILOVER_THRESHOLD` (default 3) and `EMAIL_FAILOVER_COOLDOWN_SECS` (default 300)
+    fn default() -> Self {
+        let env_u32 = |key: &str, default: u32| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            failure_threshold: env_u32("EMAIL_FAILOVER_THRESHOLD", 3).max(1),
+            cooldown: Duration::seconds(env_u32("EMAIL_FAILOVER_COOLDOWN_SECS", 300) as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Health {
+    consecutive_failures: u32,
+    down_until: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    last_success_at: Option<DateTime<Utc>>,
+    last_failure_at: Option<DateTime<Utc>>,
+}
+
+/// Health of one provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    pub provider: String,
+    /// Position in the failover order, 0 for the primary
+    pub priority: usize,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// When a provider out of rotation is tried again
+    pub down_until: Option<String>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<String>,
+    pub last_failure_at: Option<String>,
+}
+
+/// Result of handing an email to the pool
+pub struct Handoff {
+    /// Provider that accepted the email, or the last one tried
+    pub provider: String,
+    pub result: Result<String>,
+}
+
+/// Providers in failover order
+pub struct ProviderPool {
+    providers: Vec<(String, Arc<dyn EmailProvider>)>,
+    health: RwLock<Vec<Health>>,
+    config: FailoverConfig,
+}
+
+impl ProviderPool {
+    /// `providers` pairs each provider with the name it is reported under
+    pub fn new(providers: Vec<(String, Arc<dyn EmailProvider>)>, config: FailoverConfig) -> Self {
+        let health = RwLock::new(vec![Health::default(); providers.len()]);
+        Self { providers, health, config }
+    }
+
+    /// A single provider with nothing to fail over to
+    pub fn single(provider: Arc<dyn EmailProvider>) -> Self {
+        Self::new(vec![(provider.name().to_string(), provider)], FailoverConfig::default())
+    }
+
+    /// Provider names in failover order, for logging
+    pub fn names(&self) -> String {
+        self.providers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(" > ")
+    }
+
+    /// Send through the first provider that accepts the email
+    pub async fn send(&self, email: &OutgoingEmail) -> Handoff {
+        let mut failed: Vec<String> = Vec::new();
+        let mut last_error = None;
+
+        for index in self.order(Utc::now()).await {
+            let (name, provider) = &self.providers[index];
+            match provider.send(email).await {
+                Ok(message_id) => {
+                    self.record(index, None).await;
+                    if !failed.is_empty() {
+                        info!("Email {} failed over to {} after {}", email.message_id, name, failed.join(", "));
+                    }
+                    return Handoff { provider: name.clone(), result: Ok(message_id) };
+                }
+                Err(e) => {
+                    warn!("Provider {} failed for email {}: {:#}", name, email.message_id, e);
+                    self.record(index, Some(format!("{:#}", e))).await;
+                    if let Some((previous, _)) = last_error.replace((name.clone(), e)) {
+                        failed.push(previous);
+                    }
+                }
+            }
+        }
+
+        let (provider, error) = last_error.expect("pool has at least one provider");
+        let result = if failed.is_empty() {
+            Err(error)
+        } else {
+            Err(error).with_context(|| format!("{} failed after failing over from {}", provider, failed.join(", ")))
+        };
+        Handoff { provider, result }
+    }
+
+    /// Healthy providers in configured order, then those cooling down by recovery time
+    async fn order(&self, now: DateTime<Utc>) -> Vec<usize> {
+        let health = self.health.read().await;
+        let (mut healthy, mut down): (Vec<usize>, Vec<usize>) = (0..self.providers.len())
+            .partition(|&i| health[i].down_until.is_none_or(|until| until <= now));
+        down.sort_by_key(|&i| health[i].down_until);
+        healthy.append(&mut down);
+        healthy
+    }
+
+    async fn record(&self, index: usize, error: Option<String>) {
+        let now = Utc::now();
+        let mut health = self.health.write().await;
+        let entry = &mut health[index];
+        match error {
+            None => {
+                entry.consecutive_failures = 0;
+                entry.down_until = None;
+                entry.last_success_at = Some(now);
+            }
+            Some(error) => {
+                entry.consecutive_failures += 1;
+                entry.last_error = Some(error);
+                entry.last_failure_at = Some(now);
+                if entry.consecutive_failures >= self.config.failure_threshold {
+                    if entry.down_until.is_none_or(|until| until <= now) {
+                        warn!("Taking email provider {} out of rotation for {}s", self.providers[index].0, self.config.cooldown.num_seconds());
+                    }
+                    entry.down_until = Some(now + self.config.cooldown);
+                }
+            }
+        }
+    }
+
+    pub async fn status(&self) -> Vec<ProviderStatus> {
+        let now = Utc::now();
+        let health = self.health.read().await;
+        self.providers.iter().zip(health.iter()).enumerate()
+            .map(|(priority, ((name, _), h))| ProviderStatus {
+                provider: name.clone(),
+                priority,
+                healthy: h.down_until.is_none_or(|until| until <= now),
+                consecutive_failures: h.consecutive_failures,
+                down_until: h.down_until.filter(|until| *until > now).map(|t| t.to_rfc3339()),
+                last_error: h.last_error.clone(),
+                last_success_at: h.last_success_at.map(|t| t.to_rfc3339()),
+                last_failure_at: h.last_failure_at.map(|t| t.to_rfc3339()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delivery_retry::{is_transient, TransientFailure};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    /// Fails while `down` is set, counting the sends it sees
+    #[derive(Default)]
+    struct Switchable {
+        down: AtomicBool,
+        sends: AtomicU32,
+    }
+
+    #[async_trait]
+    impl EmailProvider for Switchable {
+        async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                return Err(TransientFailure("connection refused".to_string()).into());
+            }
+            Ok(email.message_id.clone())
+        }
+
+        fn name(&self) -> &'static str {
+            "switchable"
+        }
+    }
+
+    fn email() -> OutgoingEmail {
+        OutgoingEmail {
+            from: crate::email_provider::SenderConfig::default(),
+            to_email: "qa@supplier.example".to_string(),
+            to_name: String::new(),
+            subject: "PFAS declaration".to_string(),
+            body_html: String::new(),
+            body_text: String::new(),
+            attachments: Vec::new(),
+            message_id: "m1@acme.example".to_string(),
+            in_reply_to: None,
+            references: Vec::new(),
+            unsubscribe_url: None,
+            dkim: None,
+            smime: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_skips_unhealthy_primary() {
+        let (primary, secondary) = (Arc::new(Switchable::default()), Arc::new(Switchable::default()));
+        let pool = ProviderPool::new(
+            vec![("smtp".to_string(), primary.clone()), ("sendgrid".to_string(), secondary.clone())],
+            FailoverConfig { failure_threshold: 2, cooldown: Duration::minutes(5) },
+        );
+
+        primary.down.store(true, Ordering::SeqCst);
+        for _ in 0..2 {
+            let handoff = pool.send(&email()).await;
+            assert_eq!(handoff.provider, "sendgrid");
+            assert!(handoff.result.is_ok());
+        }
+        let status = pool.status().await;
+        assert!(!status[0].healthy && status[0].down_until.is_some());
+        assert!(status[1].healthy);
+
+        // Out of rotation: the primary is not tried while cooling down
+        pool.send(&email()).await.result.unwrap();
+        assert_eq!(primary.sends.load(Ordering::SeqCst), 2);
+
+        // Both down: the error stays transient so the send is retried later
+        secondary.down.store(true, Ordering::SeqCst);
+        let handoff = pool.send(&email()).await;
+        let error = handoff.result.unwrap_err();
+        assert!(is_transient(&error));
+        assert!(format!("{:#}", error).contains("failed after failing over from"));
+    }
+}
@@ -0,0 +1,345 @@
+//! Send Queue
+//!
+//! Schedules campaign emails with per-campaign stagger intervals and
+//! recipient-timezone quiet hours, and delivers them as they come due.
+//! Emails held back by a recipient domain's rate limit are requeued for
+//! the domain's next free slot.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::domain_throttle::DomainThrottled;
+use crate::service::EmailService;
+use crate::SendEmailRequest;
+
+/// Queue worker configuration
+#[derive(Debug, Clone)]
+pub struct SendQueueConfig {
+    pub poll_interval: std::time::Duration,
+    /// Maximum emails handed to the provider per poll
+    pub batch_size: usize,
+    pub default_stagger: Duration,
+    pub default_quiet_hours: QuietHours,
+}
+
+impl Default for SendQueueConfig {
+    fn default() -> Self {
+        let env_u32 = |key: &str, default: u32| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            poll_interval: std::time::Duration::from_secs(env_u32("SEND_QUEUE_POLL_SECS", 15) as u64),
+            batch_size: env_u32("SEND_QUEUE_BATCH_SIZE", 10) as usize,
+            default_stagger: Duration::seconds(env_u32("SEND_STAGGER_SECS", 30) as i64),
+            default_quiet_hours: QuietHours {
+                start_hour: env_u32("QUIET_HOURS_START", 20),
+                end_hour: env_u32("QUIET_HOURS_END", 8),
+            },
+        }
+    }
+}
+
+/// Local hours during which no email is sent. The window may wrap midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct QuietHours {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl QuietHours {
+    fn validate(&self) -> Result<()> {
+        if self.start_hour > 23 || self.end_hour > 23 {
+            bail!("Quiet hours must be between 0 and 23");
+        }
+        Ok(())
+    }
+
+    fn contains(&self, hour: u32) -> bool {
+        match self.start_hour.cmp(&self.end_hour) {
+            std::cmp::Ordering::Less => hour >= self.start_hour && hour < self.end_hour,
+            std::cmp::Ordering::Greater => hour >= self.start_hour || hour < self.end_hour,
+            std::cmp::Ordering::Equal => false,
+        }
+    }
+}
+
+/// Earliest instant at or after `at` that falls outside the recipient's quiet hours
+pub fn next_send_time(at: DateTime<Utc>, timezone: Tz, quiet: QuietHours) -> DateTime<Utc> {
+    let local = at.with_timezone(&timezone);
+    if !quiet.contains(local.hour()) {
+        return at;
+    }
+
+    // Quiet hours end today if we're past midnight already, otherwise tomorrow
+    let mut date = local.date_naive();
+    if local.hour() >= quiet.end_hour {
+        date = date.succ_opt().unwrap_or(date);
+    }
+    let end = date.and_time(NaiveTime::from_hms_opt(quiet.end_hour, 0, 0).expect("valid hour"));
+
+    timezone.from_local_datetime(&end)
+        .earliest()
+        // End of quiet hours falls in a DST gap; the hour after always exists
+        .or_else(|| timezone.from_local_datetime(&(end + Duration::hours(1))).earliest())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or(at)
+}
+
+/// Recipient to schedule
+pub struct ScheduleEntry {
+    pub request: SendEmailRequest,
+    pub timezone: Tz,
+}
+
+/// Plan send times: one stagger slot per email, pushed out of quiet hours,
+/// keeping the stagger between recipients who share a timezone
+pub fn plan_send_times(
+    start: DateTime<Utc>,
+    stagger: Duration,
+    quiet: QuietHours,
+    timezones: &[Tz],
+) -> Vec<DateTime<Utc>> {
+    let mut last_by_timezone: HashMap<Tz, DateTime<Utc>> = HashMap::new();
+
+    timezones.iter().enumerate()
+        .map(|(i, &timezone)| {
+            let mut at = next_send_time(start + stagger * i as i32, timezone, quiet);
+            if let Some(&last) = last_by_timezone.get(&timezone) {
+                if at < last + stagger {
+                    at = next_send_time(last + stagger, timezone, quiet);
+                }
+            }
+            last_by_timezone.insert(timezone, at);
+            at
+        })
+        .collect()
+}
+
+/// Queued email
+#[derive(Debug, Clone)]
+pub struct QueuedEmail {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub supplier_id: Uuid,
+    pub recipient: String,
+    pub timezone: Tz,
+    pub quiet_hours: QuietHours,
+    pub scheduled_for: DateTime<Utc>,
+    /// `queued`, `sending`, `sent`, `failed` or `cancelled`
+    pub status: String,
+    pub template_variant: Option<String>,
+    pub email_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    request: SendEmailRequest,
+}
+
+/// In-memory send queue
+pub struct SendQueue {
+    config: SendQueueConfig,
+    entries: RwLock<HashMap<Uuid, QueuedEmail>>,
+}
+
+impl SendQueue {
+    pub fn new(config: SendQueueConfig) -> Self {
+        Self { config, entries: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &SendQueueConfig {
+        &self.config
+    }
+
+    /// Queue a campaign's emails, returning the queued entries in send order
+    pub async fn schedule(
+        &self,
+        campaign_id: Uuid,
+        start: DateTime<Utc>,
+        stagger: Duration,
+        quiet: QuietHours,
+        entries: Vec<ScheduleEntry>,
+    ) -> Result<Vec<QueuedEmail>> {
+        quiet.validate()?;
+        if stagger < Duration::zero() {
+            bail!("Stagger interval cannot be negative");
+        }
+
+        let timezones: Vec<Tz> = entries.iter().map(|e| e.timezone).collect();
+        let times = plan_send_times(start, stagger, quiet, &timezones);
+
+        let mut queued = entries.into_iter().zip(times)
+            .map(|(mut entry, scheduled_for)| {
+                entry.request.campaign_id = Some(campaign_id);
+                let recipient = entry.request.variables.get("contact_email").cloned()
+                    .context("contact_email variable is required")?;
+                Ok(QueuedEmail {
+                    id: Uuid::new_v4(),
+                    campaign_id,
+                    supplier_id: entry.request.supplier_id,
+                    recipient,
+                    timezone: entry.timezone,
+                    quiet_hours: quiet,
+                    scheduled_for,
+                    status: "queued".to_string(),
+                    template_variant: entry.request.template_variant.clone(),
+                    email_id: None,
+                    error: None,
+                    sent_at: None,
+                    request: entry.request,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        queued.sort_by_key(|q| q.scheduled_for);
+
+        let mut store = self.entries.write().await;
+        for entry in &queued {
+            store.insert(entry.id, entry.clone());
+        }
+
+        Ok(queued)
+    }
+
+    /// Claim due emails, oldest first, marking them as sending
+    async fn claim_due(&self, now: DateTime<Utc>, limit: usize) -> Vec<(Uuid, SendEmailRequest)> {
+        let mut store = self.entries.write().await;
+        let mut due: Vec<&mut QueuedEmail> = store.values_mut()
+            .filter(|e| e.status == "queued" && e.scheduled_for <= now)
+            .collect();
+        due.sort_by_key(|e| e.scheduled_for);
+
+        due.into_iter()
+            .take(limit)
+            .map(|e| {
+                e.status = "sending".to_string();
+                (e.id, e.request.clone())
+            })
+            .collect()
+    }
+
+    async fn finish(&self, id: Uuid, result: Result<Uuid, String>) {
+        let mut store = self.entries.write().await;
+        if let Some(entry) = store.get_mut(&id) {
+            match result {
+                Ok(email_id) => {
+                    entry.status = "sent".to_string();
+                    entry.email_id = Some(email_id);
+                    entry.sent_at = Some(Utc::now());
+                }
+                Err(e) => {
+                    entry.status = "failed".to_string();
+                    entry.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Return a claimed email to the queue, to go out no earlier than `not_before`
+    async fn defer(&self, id: Uuid, not_before: DateTime<Utc>) {
+        let mut store = self.entries.write().await;
+        if let Some(entry) = store.get_mut(&id) {
+            entry.status = "queued".to_string();
+            entry.scheduled_for = next_send_time(not_before, entry.timezone, entry.quiet_hours);
+        }
+    }
+
+    /// Cancel a campaign's emails that have not been sent yet
+    pub async fn cancel_campaign(&self, campaign_id: Uuid) -> usize {
+        let mut store = self.entries.write().await;
+        let mut cancelled = 0;
+        for entry in store.values_mut().filter(|e| e.campaign_id == campaign_id && e.status == "queued") {
+            entry.status = "cancelled".to_string();
+            cancelled += 1;
+        }
+        cancelled
+    }
+
+    /// Queue entries for a campaign in send order
+    pub async fn campaign(&self, campaign_id: Uuid) -> Vec<QueuedEmail> {
+        let store = self.entries.read().await;
+        let mut entries: Vec<QueuedEmail> = store.values()
+            .filter(|e| e.campaign_id == campaign_id)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.scheduled_for);
+        entries
+    }
+}
+
+impl Default for SendQueue {
+    fn default() -> Self {
+        Self::new(SendQueueConfig::default())
+    }
+}
+
+/// Deliver queued emails as they come due
+pub fn spawn_worker(service: EmailService) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let queue = service.send_queue();
+        let config = queue.config().clone();
+        info!("Send queue polling every {:?}", config.poll_interval);
+        let mut interval = tokio::time::interval(config.poll_interval);
+
+        loop {
+            interval.tick().await;
+
+            for (id, request) in queue.claim_due(Utc::now(), config.batch_size).await {
+                match service.send_compliance_email(request).await {
+                    Ok(sent) => queue.finish(id, Ok(sent.email_id)).await,
+                    Err(e) => match e.downcast_ref::<DomainThrottled>() {
+                        Some(throttled) => {
+                            debug!("Queued email {} deferred: {}", id, throttled);
+                            queue.defer(id, throttled.retry_at).await;
+                        }
+                        None => {
+                            error!("Queued email {} failed: {:#}", id, e);
+                            queue.finish(id, Err(format!("{:#}", e))).await;
+                        }
+                    },
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIET: QuietHours = QuietHours { start_hour: 20, end_hour: 8 };
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_quiet_hours_defer_to_local_morning() {
+        // 03:00 in Berlin (CEST) waits until 08:00 local
+        let at = next_send_time(utc("2026-06-10T01:00:00Z"), chrono_tz::Europe::Berlin, QUIET);
+        assert_eq!(at, utc("2026-06-10T06:00:00Z"));
+
+        // 21:00 in New York (EDT) waits until 08:00 the next day
+        let at = next_send_time(utc("2026-06-11T01:00:00Z"), chrono_tz::America::New_York, QUIET);
+        assert_eq!(at, utc("2026-06-11T12:00:00Z"));
+
+        // Business hours go out immediately
+        let at = next_send_time(utc("2026-06-10T14:00:00Z"), chrono_tz::UTC, QUIET);
+        assert_eq!(at, utc("2026-06-10T14:00:00Z"));
+    }
+
+    #[test]
+    fn test_stagger_survives_quiet_hours() {
+        let tz = chrono_tz::UTC;
+        let times = plan_send_times(utc("2026-06-10T19:59:00Z"), Duration::minutes(1), QUIET, &[tz, tz, tz]);
+
+        // First goes before quiet hours; the rest resume at 08:00, still a minute apart
+        assert_eq!(times[0], utc("2026-06-10T19:59:00Z"));
+        assert_eq!(times[1], utc("2026-06-11T08:00:00Z"));
+        assert_eq!(times[2], utc("2026-06-11T08:01:00Z"));
+    }
+}
@@ -0,0 +1,379 @@
+//! Sender Domains
+//!
+//! Per-tenant From-domain configuration with DKIM signing keys,
+//! and DNS preflight checks for SPF, DKIM and DMARC alignment.
+//! Tenant domains are saved in Postgres when the service has a database;
+//! parsed keys are kept in memory until the saved row changes.
+
+use anyhow::{bail, Context, Result};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::{DateTime, Utc};
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use lettre::message::dkim::{DkimConfig, DkimSigningAlgorithm, DkimSigningKey};
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPublicKey};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use elementa_database::SenderDomainRepository;
+use elementa_models::TenantSenderDomain;
+
+use crate::email_provider::SenderConfig;
+
+/// DKIM key for a sending domain
+#[derive(Clone)]
+pub struct DkimKey {
+    pub selector: String,
+    pub algorithm: String,
+    private_key: String,
+    /// Encodings of the public key a DNS record may publish
+    public_keys: Vec<Vec<u8>>,
+    config: Arc<DkimConfig>,
+}
+
+impl DkimKey {
+    /// Parse signing key. RSA keys are PKCS#1 PEM, Ed25519 keys are base64.
+    pub fn new(domain: &str, selector: &str, algorithm: &str, private_key: &str) -> Result<Self> {
+        let (algorithm, signing_algorithm) = match algorithm.to_lowercase().as_str() {
+            "rsa" | "" => ("rsa", DkimSigningAlgorithm::Rsa),
+            "ed25519" => ("ed25519", DkimSigningAlgorithm::Ed25519),
+            other => bail!("Unsupported DKIM algorithm: {}", other),
+        };
+        let key = DkimSigningKey::new(private_key, signing_algorithm)
+            .map_err(|e| anyhow::anyhow!("Invalid DKIM private key: {}", e))?;
+
+        Ok(Self {
+            selector: selector.to_string(),
+            algorithm: algorithm.to_string(),
+            private_key: private_key.to_string(),
+            public_keys: public_keys(algorithm, private_key)?,
+            config: Arc::new(DkimConfig::default_config(selector.to_string(), domain.to_string(), key)),
+        })
+    }
+
+    pub fn config(&self) -> Arc<DkimConfig> {
+        self.config.clone()
+    }
+
+    /// Whether `public_key`, as published in DNS, belongs to this signing key
+    pub fn matches(&self, public_key: &[u8]) -> bool {
+        self.public_keys.iter().any(|key| key == public_key)
+    }
+}
+
+/// Public key of a DKIM signing key: SubjectPublicKeyInfo or bare RSAPublicKey
+/// DER for RSA, the raw 32 bytes for Ed25519
+fn public_keys(algorithm: &str, private_key: &str) -> Result<Vec<Vec<u8>>> {
+    if algorithm == "ed25519" {
+        let secret = BASE64.decode(private_key).context("Invalid DKIM private key")?;
+        let key = openssl::pkey::PKey::private_key_from_raw_bytes(&secret, openssl::pkey::Id::ED25519)
+            .context("Invalid DKIM private key")?;
+        return Ok(vec![key.raw_public_key().context("Failed to derive DKIM public key")?]);
+    }
+
+    let public = RsaPublicKey::from(RsaPrivateKey::from_pkcs1_pem(private_key).context("Invalid DKIM private key")?);
+    Ok(vec![
+        public.to_public_key_der().context("Failed to encode DKIM public key")?.into_vec(),
+        public.to_pkcs1_der().context("Failed to encode DKIM public key")?.into_vec(),
+    ])
+}
+
+/// Sending identity for a tenant
+#[derive(Clone)]
+pub struct SenderDomain {
+    pub sender: SenderConfig,
+    pub dkim: Option<DkimKey>,
+}
+
+impl SenderDomain {
+    pub fn domain(&self) -> &str {
+        domain_of(&self.sender.from_email)
+    }
+
+    fn from_saved(saved: &TenantSenderDomain) -> Result<Self> {
+        let dkim = match (&saved.dkim_selector, &saved.dkim_private_key) {
+            (Some(selector), Some(key)) => Some(
+                DkimKey::new(domain_of(&saved.from_email), selector, saved.dkim_algorithm.as_deref().unwrap_or_default(), key)
+                    .with_context(|| format!("Saved DKIM key for tenant {} is invalid", saved.tenant_id))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            sender: SenderConfig {
+                from_email: saved.from_email.clone(),
+                from_name: saved.from_name.clone(),
+                reply_to: None,
+            },
+            dkim,
+        })
+    }
+
+    fn to_saved(&self, tenant: &str) -> TenantSenderDomain {
+        TenantSenderDomain {
+            tenant_id: tenant.to_string(),
+            from_email: self.sender.from_email.clone(),
+            from_name: self.sender.from_name.clone(),
+            dkim_selector: self.dkim.as_ref().map(|key| key.selector.clone()),
+            dkim_algorithm: self.dkim.as_ref().map(|key| key.algorithm.clone()),
+            dkim_private_key: self.dkim.as_ref().map(|key| key.private_key.clone()),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Sender domains keyed by tenant, with a deployment-wide default
+pub struct SenderDomainRegistry {
+    default: SenderDomain,
+    /// `None` keeps tenant domains in memory only
+    repository: Option<SenderDomainRepository>,
+    /// Parsed tenant domains, with when they were saved
+    tenants: RwLock<HashMap<String, (DateTime<Utc>, SenderDomain)>>,
+}
+
+impl SenderDomainRegistry {
+    /// Default sender from environment. `DKIM_SELECTOR` and `DKIM_PRIVATE_KEY_PATH`
+    /// enable signing; `DKIM_ALGORITHM` is `rsa` (default) or `ed25519`.
+    pub fn from_env() -> Self {
+        let sender = SenderConfig::default();
+        let dkim = match (std::env::var("DKIM_SELECTOR"), std::env::var("DKIM_PRIVATE_KEY_PATH")) {
+            (Ok(selector), Ok(path)) => {
+                let algorithm = std::env::var("DKIM_ALGORITHM").unwrap_or_default();
+                std::fs::read_to_string(&path)
+                    .context("Failed to read DKIM private key")
+                    .and_then(|key| DkimKey::new(domain_of(&sender.from_email), &selector, &algorithm, key.trim()))
+                    .map_err(|e| warn!("DKIM signing disabled: {:#}", e))
+                    .ok()
+            }
+            _ => None,
+        };
+
+        Self {
+            default: SenderDomain { sender, dkim },
+            repository: None,
+            tenants: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Save tenant domains through `repository`
+    pub fn with_repository(mut self, repository: SenderDomainRepository) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Set sending identity for a tenant
+    pub async fn register(&self, tenant: &str, domain: SenderDomain) -> Result<()> {
+        let updated_at = match &self.repository {
+            Some(repository) => repository.save(&domain.to_saved(tenant)).await?.updated_at,
+            None => Utc::now(),
+        };
+        self.tenants.write().await.insert(tenant.to_string(), (updated_at, domain));
+        Ok(())
+    }
+
+    /// Sending identity for a tenant, falling back to the default
+    pub async fn sender_for(&self, tenant: Option<&str>) -> Result<SenderDomain> {
+        let Some(tenant) = tenant else {
+            return Ok(self.default.clone());
+        };
+
+        let Some(repository) = &self.repository else {
+            let tenants = self.tenants.read().await;
+            return Ok(tenants.get(tenant).map_or_else(|| self.default.clone(), |(_, domain)| domain.clone()));
+        };
+
+        let Some(saved) = repository.find(tenant).await? else {
+            self.tenants.write().await.remove(tenant);
+            return Ok(self.default.clone());
+        };
+        if let Some((updated_at, domain)) = self.tenants.read().await.get(tenant) {
+            if *updated_at == saved.updated_at {
+                return Ok(domain.clone());
+            }
+        }
+
+        // Saved by another instance since it was last parsed here
+        let domain = SenderDomain::from_saved(&saved)?;
+        self.tenants.write().await.insert(tenant.to_string(), (saved.updated_at, domain.clone()));
+        Ok(domain)
+    }
+}
+
+impl Default for SenderDomainRegistry {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Result of a single DNS check
+#[derive(Debug, Clone, Serialize)]
+pub struct DnsCheck {
+    pub status: String,
+    pub record: Option<String>,
+    pub detail: String,
+}
+
+impl DnsCheck {
+    fn new(status: &str, record: Option<String>, detail: impl Into<String>) -> Self {
+        Self { status: status.to_string(), record, detail: detail.into() }
+    }
+
+    fn passed(&self) -> bool {
+        self.status == "pass"
+    }
+}
+
+/// SPF/DKIM/DMARC readiness for a sending domain
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub domain: String,
+    pub spf: DnsCheck,
+    pub dkim: DnsCheck,
+    pub dmarc: DnsCheck,
+    pub ready: bool,
+}
+
+/// Check DNS records for a sending domain
+pub async fn preflight(domain: &SenderDomain) -> Result<PreflightReport> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .unwrap_or_else(|_| TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()));
+    let name = domain.domain().to_string();
+
+    let spf = evaluate_spf(&txt_records(&resolver, &name).await);
+    let dkim = match &domain.dkim {
+        Some(key) => {
+            let records = txt_records(&resolver, &format!("{}._domainkey.{}", key.selector, name)).await;
+            evaluate_dkim(&records, key)
+        }
+        None => DnsCheck::new("not_configured", None, "No DKIM key configured for this sender"),
+    };
+    let dmarc = evaluate_dmarc(&txt_records(&resolver, &format!("_dmarc.{}", name)).await);
+
+    let ready = spf.passed() && dkim.passed() && dmarc.passed();
+    Ok(PreflightReport { domain: name, spf, dkim, dmarc, ready })
+}
+
+/// TXT records for a name; lookup failures are treated as no records
+async fn txt_records(resolver: &TokioAsyncResolver, name: &str) -> Vec<String> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => lookup.iter()
+            .map(|txt| txt.txt_data().iter().map(|d| String::from_utf8_lossy(d)).collect())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn evaluate_spf(records: &[String]) -> DnsCheck {
+    let spf: Vec<&String> = records.iter()
+        .filter(|r| r.to_lowercase().starts_with("v=spf1"))
+        .collect();
+
+    match spf.as_slice() {
+        [] => DnsCheck::new("missing", None, "No SPF record published"),
+        [record] if record.contains("+all") => {
+            DnsCheck::new("fail", Some(record.to_string()), "SPF record allows any sender (+all)")
+        }
+        [record] => DnsCheck::new("pass", Some(record.to_string()), "SPF record published"),
+        _ => DnsCheck::new("fail", None, "Multiple SPF records published; receivers treat this as an error"),
+    }
+}
+
+fn evaluate_dkim(records: &[String], key: &DkimKey) -> DnsCheck {
+    let Some(record) = records.iter().find(|r| r.contains("p=")) else {
+        return DnsCheck::new("missing", None, "No DKIM public key published for selector");
+    };
+
+    let public_key: String = tag_value(record, "p").unwrap_or_default().split_whitespace().collect();
+    if public_key.is_empty() {
+        return DnsCheck::new("fail", Some(record.clone()), "DKIM key has been revoked (empty p=)");
+    }
+
+    match BASE64.decode(&public_key) {
+        Ok(public_key) if key.matches(&public_key) => {
+            DnsCheck::new("pass", Some(record.clone()), "DKIM public key matches the signing key")
+        }
+        Ok(_) => DnsCheck::new("fail", Some(record.clone()), "Published DKIM public key does not match the configured signing key"),
+        Err(_) => DnsCheck::new("fail", Some(record.clone()), "DKIM public key is not valid base64"),
+    }
+}
+
+fn evaluate_dmarc(records: &[String]) -> DnsCheck {
+    let Some(record) = records.iter().find(|r| r.to_lowercase().starts_with("v=dmarc1")) else {
+        return DnsCheck::new("missing", None, "No DMARC record published");
+    };
+
+    match tag_value(record, "p").map(|p| p.to_lowercase()).as_deref() {
+        Some("quarantine") | Some("reject") => {
+            DnsCheck::new("pass", Some(record.clone()), "DMARC policy enforced")
+        }
+        Some("none") => DnsCheck::new("warn", Some(record.clone()), "DMARC policy is monitor-only (p=none)"),
+        _ => DnsCheck::new("fail", Some(record.clone()), "DMARC record has no valid policy"),
+    }
+}
+
+/// Value of a `tag=value;` pair in a DKIM/DMARC record
+fn tag_value<'a>(record: &'a str, tag: &str) -> Option<&'a str> {
+    record.split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(tag))
+        .map(|(_, value)| value.trim())
+}
+
+pub fn domain_of(email: &str) -> &str {
+    email.rsplit('@').next().unwrap_or(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_record_evaluation() {
+        let spf = evaluate_spf(&["v=spf1 include:amazonses.com ~all".to_string(), "google-site-verification=x".to_string()]);
+        assert_eq!(spf.status, "pass");
+        assert_eq!(evaluate_spf(&["v=spf1 +all".to_string()]).status, "fail");
+        assert_eq!(evaluate_spf(&[]).status, "missing");
+
+
+        assert_eq!(evaluate_dmarc(&["v=DMARC1; p=reject; rua=mailto:d@x.io".to_string()]).status, "pass");
+        assert_eq!(evaluate_dmarc(&["v=DMARC1; p=none".to_string()]).status, "warn");
+        assert_eq!(evaluate_dmarc(&[]).status, "missing");
+    }
+
+    fn dkim_record(public_key: &[u8]) -> Vec<String> {
+        vec![format!("v=DKIM1; k=rsa; p={}", BASE64.encode(public_key))]
+    }
+
+    #[test]
+    fn test_dkim_record_must_match_signing_key() {
+        let rsa = openssl::rsa::Rsa::generate(2048).unwrap();
+        let pem = String::from_utf8(rsa.private_key_to_pem().unwrap()).unwrap();
+        let key = DkimKey::new("acme.example", "s1", "rsa", &pem).unwrap();
+
+        let published = openssl::pkey::PKey::from_rsa(rsa).unwrap().public_key_to_der().unwrap();
+        assert_eq!(evaluate_dkim(&dkim_record(&published), &key).status, "pass");
+
+        let other = openssl::pkey::PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+        let check = evaluate_dkim(&dkim_record(&other.public_key_to_der().unwrap()), &key);
+        assert_eq!(check.status, "fail");
+        assert!(check.detail.contains("does not match"));
+
+        assert_eq!(evaluate_dkim(&["v=DKIM1; k=rsa; p=".to_string()], &key).status, "fail");
+        assert_eq!(evaluate_dkim(&["v=DKIM1; k=rsa; p=not base64!".to_string()], &key).status, "fail");
+        assert_eq!(evaluate_dkim(&[], &key).status, "missing");
+    }
+
+    #[test]
+    fn test_ed25519_dkim_public_key() {
+        let key = DkimKey::new("acme.example", "s1", "ed25519", &BASE64.encode([7u8; 32])).unwrap();
+        let secret = openssl::pkey::PKey::private_key_from_raw_bytes(&[7u8; 32], openssl::pkey::Id::ED25519).unwrap();
+
+        assert!(key.matches(&secret.raw_public_key().unwrap()));
+        assert!(!key.matches(&[7u8; 32]));
+    }
+}
@@ -0,0 +1,81 @@
+//! Sender Identities
+//!
+//! Named From and Reply-To identities with a signature. A campaign is
+//! assigned an identity so each client's outreach goes out under the right
+//! name and replies route to the right mailbox.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct SenderIdentity {
+    pub id: Uuid,
+    /// Tenant whose sender domain, and DKIM key, the identity sends through
+    pub tenant_id: Option<String>,
+    pub label: String,
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    /// Plain-text signature appended to every email sent as this identity
+    pub signature: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Identities, and the campaigns (or workflows) using them
+#[derive(Default)]
+pub struct SenderIdentityRegistry {
+    identities: RwLock<HashMap<Uuid, SenderIdentity>>,
+    campaigns: RwLock<HashMap<Uuid, Uuid>>,
+}
+
+impl SenderIdentityRegistry {
+    pub async fn upsert(&self, identity: SenderIdentity) {
+        self.identities.write().await.insert(identity.id, identity);
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<SenderIdentity> {
+        self.identities.read().await.get(&id).cloned()
+    }
+
+    /// All identities, oldest first
+    pub async fn list(&self) -> Vec<SenderIdentity> {
+        let mut identities: Vec<SenderIdentity> = self.identities.read().await.values().cloned().collect();
+        identities.sort_by_key(|i| i.created_at);
+        identities
+    }
+
+    /// Remove an identity; campaigns using it fall back to the tenant sender
+    pub async fn remove(&self, id: Uuid) -> bool {
+        let removed = self.identities.write().await.remove(&id).is_some();
+        if removed {
+            self.campaigns.write().await.retain(|_, identity_id| *identity_id != id);
+        }
+        removed
+    }
+
+    /// Send a campaign's emails as the given identity
+    pub async fn assign(&self, campaign_id: Uuid, identity_id: Uuid) -> Result<()> {
+        if !self.identities.read().await.contains_key(&identity_id) {
+            bail!("Unknown sender identity {}", identity_id);
+        }
+        self.campaigns.write().await.insert(campaign_id, identity_id);
+        Ok(())
+    }
+
+    pub async fn for_campaign(&self, campaign_id: Uuid) -> Option<SenderIdentity> {
+        let identity_id = *self.campaigns.read().await.get(&campaign_id)?;
+        self.get(identity_id).await
+    }
+}
+
+/// Lowercased address, rejecting anything without a local part and domain
+pub fn normalize_address(field: &str, address: &str) -> Result<String> {
+    let address = address.trim().to_lowercase();
+    address.split_once('@')
+        .filter(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        .with_context(|| format!("{} must be a full email address", field))?;
+    Ok(address)
+}
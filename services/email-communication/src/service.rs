@@ -3,114 +3,1339 @@
 //! Core email orchestration logic.
 
 use anyhow::{Context, Result};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tracing::{debug, error, warn};
 use uuid::Uuid;
 
-use crate::smtp_client::SmtpClient;
-use crate::template_engine::TemplateEngine;
-use crate::{SendEmailRequest, SendEmailResponse, EmailResponse, TemplateInfo, RenderTemplateResponse};
+use elementa_database::{
+    tenant, ComplianceRepository, ComplianceStore, EmailRepository, PostgresPools, SenderDomainRepository, SupplierRepository,
+    SupplierStore, SuppressionRepository, TenantId,
+};
+use elementa_models::{
+    DeliveryAttempt, DeliveryStatus, DocumentExtractedEvent, DomainEvent, EmailBouncedEvent, EmailCommunication,
+    EmailDirection, EmailProcessingStatus, EmailSentEvent, EmailSuppression, ReplyClassification, ReplyClassifiedEvent,
+    SmimeStatus, SupplierSuppressedEvent, SupplierUnresponsiveEvent, SuppressionSource,
+    EmailAttachment as ModelAttachment,
+};
 
-/// Stored email record
-#[derive(Debug, Clone)]
-struct StoredEmail {
-    id: Uuid,
-    thread_id: String,
+use crate::address_validation::AddressValidator;
+use crate::ab_testing::{variant_b_suppliers, variant_stats, VARIANT_A, VARIANT_B};
+use crate::branding::{BrandingRegistry, TenantBranding};
+use crate::calendar::{deadline_attachment, Deadline, DeadlineEvent};
+use crate::classifier::{Classification, ReplyClassifier};
+use crate::delivery_retry::{is_transient, RetryPolicy};
+use crate::digest::{compose, Digest, DigestConfig, DIGEST_TEMPLATE};
+use crate::delivery_webhooks::{supersedes, DeliveryEvent, WebhookVerifier};
+use crate::document_client::{guess_content_type, is_forwardable, AttachmentSource, DocumentClient};
+use crate::imap_client::{parse_content, InboundMessage};
+use crate::inbound_dedup::{content_hash, InboundDedup};
+use crate::out_of_office::{is_auto_reply, return_date};
+use crate::non_response::{unanswered_follow_ups, NonResponseConfig};
+use crate::provider_failover::{Handoff, ProviderPool, ProviderStatus};
+use crate::email_store::EmailStore;
+use crate::email_provider::{
+    generate_message_id, normalize_message_id, EmailAttachment, LogProvider, OutgoingEmail, SenderConfig,
+};
+use crate::domain_throttle::{recipient_domain, DomainThrottle};
+use crate::send_queue::{ScheduleEntry, SendQueue};
+use crate::sender_domains::{domain_of, preflight, DkimKey, PreflightReport, SenderDomain, SenderDomainRegistry};
+use crate::sender_identities::{normalize_address, SenderIdentity, SenderIdentityRegistry};
+use crate::smime::{certificate_addresses, SmimeCredentials, SmimeRegistry, TenantSmime};
+use crate::suppressions::{RecipientSuppressed, SuppressionList, UnsubscribeLinks};
+use crate::thread_summary::{last_supplier_statement, transcript, ChatSummaryModel, SummariesDisabled, SummaryModel, ThreadMessage};
+use crate::template_engine::{
+    TemplateEngine, BRANDING_VARIABLE, DEFAULT_LANGUAGE, SIGNATURE_VARIABLE, UNSUBSCRIBE_URL_VARIABLE, UPLOAD_URL_VARIABLE,
+};
+use crate::upload_links::{UploadLinks, UploadTarget};
+use crate::workflow_client::WorkflowClient;
+use crate::{
+    SendEmailRequest, SendEmailResponse, EmailResponse, TemplateInfo, RenderTemplateResponse,
+    PreviewTemplateRequest, TemplatePreviewResponse, MissingVariableResponse,
+    InboundEmailRequest, AttachmentResponse, AttachmentRequest, DeliveryAttemptResponse,
+    SenderDomainRequest, SenderDomainResponse, SenderIdentityRequest, SenderIdentityResponse,
+    SuppressionRequest, SuppressionResponse, DigestResponse, ValidateAddressesRequest, ValidateAddressesResponse,
+    SmimeCertificateRequest, SmimeCertificateResponse, RecipientCertificateRequest, RecipientCertificateResponse,
+    ScheduleCampaignRequest, CampaignQueueResponse, QueueCounts, QueuedEmailResponse, CampaignVariantsResponse,
+    ThreadSummaryResponse, SupplierStatementResponse,
+};
+
+/// Recorded in place of the body of an S/MIME email no tenant certificate can decrypt
+const UNDECRYPTABLE_BODY: &str = "[S/MIME encrypted email; no tenant certificate can decrypt it]";
+
+/// Inbound email ready to be recorded
+struct InboundRecord {
+    supplier_id: Uuid,
+    thread_id: Option<String>,
+    /// Known for uploads through a campaign's upload link
+    campaign_id: Option<Uuid>,
+    message_id: Option<String>,
+    in_reply_to: Option<String>,
+    references: Vec<String>,
+    subject: String,
+    body: String,
+    attachments: Vec<EmailAttachment>,
+    /// Headers mark the email as an automatic reply
+    auto_reply: bool,
+    smime: SmimeStatus,
+}
+
+/// Inbound reply awaiting classification
+struct ReplyToClassify {
+    email_id: Uuid,
     supplier_id: Uuid,
-    direction: String,
+    thread_id: String,
     subject: String,
     body: String,
-    sent_at: Option<String>,
-    received_at: Option<String>,
-    delivery_status: String,
-    processing_status: String,
+    document_count: usize,
+    auto_reply: bool,
+    received_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Attachment forwarded to document-processing
+#[derive(Debug, Clone)]
+struct StoredAttachment {
+    filename: String,
+    content_type: String,
+    size: usize,
+    document_ids: Vec<Uuid>,
+    status: String,
+    cas_numbers_found: usize,
+    needs_review: bool,
+    error: Option<String>,
 }
 
 /// Email service
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct EmailService {
-    emails: Arc<RwLock<HashMap<Uuid, StoredEmail>>>,
+    emails: Arc<EmailStore>,
     template_engine: Arc<TemplateEngine>,
-    smtp_client: Arc<SmtpClient>,
+    providers: Arc<ProviderPool>,
+    sender_domains: Arc<SenderDomainRegistry>,
+    document_client: Arc<DocumentClient>,
+    classifier: Arc<ReplyClassifier>,
+    /// `None` when thread summaries are disabled
+    summarizer: Option<Arc<dyn SummaryModel>>,
+    workflow_client: Arc<WorkflowClient>,
+    non_response: Arc<NonResponseConfig>,
+    send_queue: Arc<SendQueue>,
+    sender_identities: Arc<SenderIdentityRegistry>,
+    branding: Arc<BrandingRegistry>,
+    smime: Arc<SmimeRegistry>,
+    retry_policy: Arc<RetryPolicy>,
+    domain_throttle: Arc<DomainThrottle>,
+    webhooks: Arc<WebhookVerifier>,
+    suppressions: Arc<SuppressionList>,
+    address_validator: Arc<AddressValidator>,
+    inbound_dedup: Arc<InboundDedup>,
+    unsubscribe_links: Arc<UnsubscribeLinks>,
+    upload_links: Arc<UploadLinks>,
+    digest_config: Arc<DigestConfig>,
+    suppliers: Option<Arc<dyn SupplierStore>>,
+    compliance_records: Option<Arc<dyn ComplianceStore>>,
 }
 
 impl EmailService {
-    pub fn new() -> Self {
+    pub fn new(providers: ProviderPool) -> Self {
         Self {
-            emails: Arc::new(RwLock::new(HashMap::new())),
+            emails: Arc::new(EmailStore::memory()),
             template_engine: Arc::new(TemplateEngine::new()),
-            smtp_client: Arc::new(SmtpClient::default()),
+            providers: Arc::new(providers),
+            sender_domains: Arc::new(SenderDomainRegistry::from_env()),
+            document_client: Arc::new(DocumentClient::default()),
+            classifier: Arc::new(ReplyClassifier::default()),
+            summarizer: ChatSummaryModel::from_env().map(|model| Arc::new(model) as Arc<dyn SummaryModel>),
+            workflow_client: Arc::new(WorkflowClient::default()),
+            non_response: Arc::new(NonResponseConfig::default()),
+            send_queue: Arc::new(SendQueue::default()),
+            sender_identities: Arc::new(SenderIdentityRegistry::default()),
+            branding: Arc::new(BrandingRegistry::default()),
+            smime: Arc::new(SmimeRegistry::default()),
+            retry_policy: Arc::new(RetryPolicy::default()),
+            domain_throttle: Arc::new(DomainThrottle::default()),
+            webhooks: Arc::new(WebhookVerifier::from_env()),
+            suppressions: Arc::new(SuppressionList::memory()),
+            address_validator: Arc::new(AddressValidator::default()),
+            inbound_dedup: Arc::new(InboundDedup::default()),
+            unsubscribe_links: Arc::new(UnsubscribeLinks::from_env()),
+            upload_links: Arc::new(UploadLinks::from_env()),
+            digest_config: Arc::new(DigestConfig::default()),
+            suppliers: None,
+            compliance_records: None,
         }
     }
     
-    /// Send compliance email
+    /// Persist emails and sender domains and match suppliers through Postgres
+    pub fn with_database(mut self, pools: PostgresPools) -> Self {
+        let pool = pools.primary().clone();
+        self.emails = Arc::new(EmailStore::Postgres(EmailRepository::new(pool.clone())));
+        self.sender_domains = Arc::new(
+            SenderDomainRegistry::from_env().with_repository(SenderDomainRepository::new(pool.clone())),
+        );
+        self.suppressions = Arc::new(SuppressionList::Postgres(SuppressionRepository::new(pool)));
+        self.with_stores(
+            Arc::new(SupplierRepository::with_pools(pools.clone())),
+            Arc::new(ComplianceRepository::with_pools(pools)),
+        )
+    }
+    
+    /// Match suppliers and read compliance records through the given stores
+    pub fn with_stores(mut self, suppliers: Arc<dyn SupplierStore>, compliance_records: Arc<dyn ComplianceStore>) -> Self {
+        self.suppliers = Some(suppliers);
+        self.compliance_records = Some(compliance_records);
+        self
+    }
+    
+    /// Check that recipient domains accept mail, on top of syntax and domain lists
+    pub fn with_mx_lookups(mut self) -> Self {
+        self.address_validator = Arc::new(AddressValidator::from_env().with_mx_lookups());
+        self
+    }
+    
+    /// Send compliance email. Suppressed recipients fail with `RecipientSuppressed`, addresses
+    /// that cannot receive email with `UndeliverableAddress`, and sends that would wait too long
+    /// for their domain's rate limit with `DomainThrottled`.
     pub async fn send_compliance_email(&self, request: SendEmailRequest) -> Result<SendEmailResponse> {
+        let to_email = request.variables.get("contact_email").cloned()
+            .context("contact_email variable is required")?;
+        if let Some(suppression) = self.suppressions.find_matching(request.supplier_id, &to_email).await? {
+            return Err(RecipientSuppressed { email_address: to_email, reason: suppression.reason }.into());
+        }
+        let check = self.address_validator.check(&to_email).await;
+        if !check.deliverable() {
+            return Err(check.into_error().into());
+        }
+        
+        let domain = recipient_domain(&to_email).context("contact_email has no domain")?;
+        let now = chrono::Utc::now();
+        let slot = self.domain_throttle.reserve(&domain, now, self.domain_throttle.config().max_wait).await?;
+        if let Ok(wait) = (slot - now).to_std() {
+            tokio::time::sleep(wait).await;
+        }
+        
+        let email_id = Uuid::new_v4();
+        let thread_id = request.thread_id.unwrap_or_else(|| format!("thread_{}", email_id));
+        let unsubscribe_url = self.unsubscribe_links.url(request.supplier_id, &to_email);
+        let upload_url = self.upload_links.url(request.supplier_id, request.campaign_id, &thread_id, chrono::Utc::now());
+        let identity = self.sender_identity_for(request.sender_identity_id, request.campaign_id).await?;
+        let calendar_deadline = request.calendar_deadline.as_deref().map(Deadline::parse).transpose()?;
+        
         // Convert string variables to JSON values
-        let json_vars: HashMap<String, serde_json::Value> = request.variables.iter()
+        let mut json_vars: HashMap<String, serde_json::Value> = request.variables.iter()
             .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
             .collect();
+        json_vars.insert(UNSUBSCRIBE_URL_VARIABLE.to_string(), unsubscribe_url.clone().into());
+        json_vars.insert(UPLOAD_URL_VARIABLE.to_string(), upload_url.clone().into());
+        if let Some(deadline) = &calendar_deadline {
+            json_vars.entry("deadline".to_string()).or_insert_with(|| deadline.display_date().into());
+        }
+        if let Some(identity) = &identity {
+            json_vars.entry("sender_name".to_string()).or_insert_with(|| identity.from_name.clone().into());
+            if let Some(signature) = &identity.signature {
+                json_vars.insert(SIGNATURE_VARIABLE.to_string(), signature.clone().into());
+            }
+        }
+        let tenant = identity.as_ref().and_then(|i| i.tenant_id.as_deref()).or(request.tenant_id.as_deref());
+        if let Some(branding) = self.branding_for(tenant).await {
+            json_vars.insert(BRANDING_VARIABLE.to_string(), serde_json::to_value(branding)?);
+        }
         
-        // Render template
-        let rendered = self.template_engine.render(&request.template_id, &json_vars)
+        // Render template in the requested or supplier's preferred language
+        let language = match &request.language {
+            Some(language) => language.clone(),
+            None => self.preferred_language(request.supplier_id).await,
+        };
+        let rendered = self.template_engine.render(&request.template_id, &language, &json_vars)
             .context("Failed to render template")?;
         
         let subject = request.subject.unwrap_or(rendered.subject.clone());
         
-        // For now, simulate sending (actual SMTP requires configuration)
-        let email_id = Uuid::new_v4();
-        let thread_id = format!("thread_{}", email_id);
-        let sent_at = chrono::Utc::now().to_rfc3339();
+        let mut attachments = request.attachments.unwrap_or_default().into_iter()
+            .map(decode_attachment)
+            .collect::<Result<Vec<_>>>()?;
         
-        // Store email record
-        let email = StoredEmail {
+        let (in_reply_to, references) = self.reply_headers(&thread_id).await?;
+        
+        let sender = self.sender_for(request.tenant_id.as_deref(), identity.as_ref()).await?;
+        let smime = self.smime.outbound(tenant, &to_email).await;
+        if let Some(deadline) = calendar_deadline {
+            let reference = request.variables.get("reference_id").map(String::as_str).unwrap_or(&thread_id);
+            attachments.push(deadline_attachment(deadline, &DeadlineEvent {
+                uid: &format!("{}@{}", thread_id, sender.domain()),
+                summary: &format!("Response due: {}", subject),
+                description: &format!("Reference: {}\nUpload documents: {}", reference, upload_url),
+                organizer_email: &sender.sender.reply_to.clone().unwrap_or_else(|| sender.sender.from_email.clone()),
+                organizer_name: &sender.sender.from_name,
+            }, chrono::Utc::now()));
+        }
+        let message_id = generate_message_id(&sender.sender.from_email);
+        let outgoing = OutgoingEmail {
+            from: sender.sender,
+            to_email: to_email.clone(),
+            to_name: request.variables.get("contact_name").cloned().unwrap_or_default(),
+            subject: subject.clone(),
+            body_html: rendered.body_html.clone(),
+            body_text: rendered.body_text,
+            attachments,
+            message_id: message_id.clone(),
+            in_reply_to: in_reply_to.clone(),
+            references: references.clone(),
+            unsubscribe_url: Some(unsubscribe_url),
+            dkim: sender.dkim.map(|key| key.config()),
+            smime: smime.clone(),
+        };
+        
+        // Record the email before the first attempt so every attempt has somewhere to go
+        let record = EmailCommunication {
             id: email_id,
             thread_id: thread_id.clone(),
-            supplier_id: request.supplier_id,
-            direction: "outbound".to_string(),
+            supplier_id: request.supplier_id.into(),
+            direction: EmailDirection::Outbound,
             subject: subject.clone(),
             body: rendered.body_html,
-            sent_at: Some(sent_at.clone()),
-            received_at: None,
-            delivery_status: "sent".to_string(),
-            processing_status: "complete".to_string(),
+            delivery_status: DeliveryStatus::Pending,
+            processing_status: EmailProcessingStatus::Processed,
+            message_id: Some(message_id),
+            in_reply_to,
+            references,
+            recipient: Some(to_email.clone()),
+            smime: smime.map(|s| s.status()).unwrap_or_default(),
+            campaign_id: request.campaign_id.map(Into::into),
+            template_id: Some(request.template_id.clone()),
+            template_variant: request.template_variant.clone(),
+            ..EmailCommunication::default()
         };
+        in_tenant(tenant, self.emails.create(record)).await
+            .context("Failed to record outbound email")?;
         
-        let mut emails = self.emails.write().await;
-        emails.insert(email_id, email);
+        let sent_at = self.deliver(email_id, &outgoing).await
+            .with_context(|| format!("Failed to deliver email {}", email_id))?;
+        
+        let event = EmailSentEvent {
+            email_id,
+            supplier_id: request.supplier_id,
+            thread_id: thread_id.clone(),
+            campaign_id: request.campaign_id,
+            template_id: Some(request.template_id.clone()),
+            sent_at,
+        };
+        let workflow_client = self.workflow_client.clone();
+        let tenant = request.tenant_id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = workflow_client.publish(DomainEvent::EmailSent(event), tenant).await {
+                warn!("Failed to publish sent email {}: {:#}", email_id, e);
+            }
+        });
+        self.check_non_response(&thread_id, request.supplier_id, request.campaign_id, sent_at).await;
         
         Ok(SendEmailResponse {
             email_id,
             thread_id,
-            recipient: request.variables.get("contact_email").cloned().unwrap_or_default(),
+            recipient: to_email,
             subject,
+            language: rendered.language,
             status: "sent".to_string(),
-            sent_at,
+            sent_at: sent_at.to_rfc3339(),
+        })
+    }
+    
+    /// Tell the workflow service once a thread reaches the unanswered follow-up threshold
+    async fn check_non_response(&self, thread_id: &str, supplier_id: Uuid, campaign_id: Option<Uuid>, sent_at: chrono::DateTime<chrono::Utc>) {
+        let thread = match self.emails.find_by_thread(thread_id).await {
+            Ok(thread) => thread,
+            Err(e) => {
+                warn!("Failed to load thread {} for non-response check: {:#}", thread_id, e);
+                return;
+            }
+        };
+        let unanswered = unanswered_follow_ups(&thread);
+        if !self.non_response.should_escalate(unanswered) {
+            return;
+        }
+        
+        debug!("Supplier {} has not answered {} follow-ups in thread {}", supplier_id, unanswered, thread_id);
+        let event = SupplierUnresponsiveEvent {
+            supplier_id,
+            thread_id: thread_id.to_string(),
+            campaign_id,
+            unanswered_follow_ups: unanswered,
+            last_sent_at: sent_at,
+        };
+        let workflow_client = self.workflow_client.clone();
+        tokio::spawn(async move {
+            if let Err(e) = workflow_client.publish(DomainEvent::SupplierUnresponsive(event), None).await {
+                warn!("Failed to publish non-response of supplier {}: {:#}", supplier_id, e);
+            }
+        });
+    }
+    
+    /// Explicitly requested identity, otherwise the one assigned to the campaign
+    async fn sender_identity_for(&self, identity_id: Option<Uuid>, campaign_id: Option<Uuid>) -> Result<Option<SenderIdentity>> {
+        if let Some(identity_id) = identity_id {
+            let identity = self.sender_identities.get(identity_id).await
+                .with_context(|| format!("Unknown sender identity {}", identity_id))?;
+            return Ok(Some(identity));
+        }
+        
+        Ok(match campaign_id {
+            Some(campaign_id) => self.sender_identities.for_campaign(campaign_id).await,
+            None => None,
+        })
+    }
+    
+    /// Tenant sender domain with the identity's From and Reply-To applied.
+    /// The domain's DKIM key is dropped if the identity sends from another domain.
+    async fn sender_for(&self, tenant: Option<&str>, identity: Option<&SenderIdentity>) -> Result<SenderDomain> {
+        let Some(identity) = identity else {
+            return self.sender_domains.sender_for(tenant).await;
+        };
+        
+        let mut sender = self.sender_domains.sender_for(identity.tenant_id.as_deref().or(tenant)).await?;
+        if sender.domain() != domain_of(&identity.from_email) {
+            sender.dkim = None;
+        }
+        sender.sender = SenderConfig {
+            from_email: identity.from_email.clone(),
+            from_name: identity.from_name.clone(),
+            reply_to: identity.reply_to.clone(),
+        };
+        Ok(sender)
+    }
+    
+    /// Hand an email to the provider, retrying transient failures with
+    /// backoff. Each attempt is recorded on the email; it is only marked
+    /// failed once a permanent error occurs or attempts run out.
+    async fn deliver(&self, email_id: Uuid, outgoing: &OutgoingEmail) -> Result<chrono::DateTime<chrono::Utc>> {
+        let mut attempts: Vec<DeliveryAttempt> = Vec::new();
+        
+        loop {
+            let attempt = attempts.len() as u32 + 1;
+            let attempted_at = chrono::Utc::now();
+            let Handoff { provider, result } = self.providers.send(outgoing).await;
+            
+            let transient = result.as_ref().err().is_some_and(is_transient);
+            let retry_in = (transient && attempt < self.retry_policy.max_attempts)
+                .then(|| self.retry_policy.delay(attempt));
+            let next_attempt_at = retry_in
+                .map(|delay| attempted_at + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero()));
+            
+            attempts.push(DeliveryAttempt {
+                attempt,
+                attempted_at,
+                provider: Some(provider.clone()),
+                succeeded: result.is_ok(),
+                transient,
+                error: result.as_ref().err().map(|e| format!("{:#}", e)),
+                next_attempt_at,
+            });
+            let status = match (&result, retry_in) {
+                (Ok(_), _) => DeliveryStatus::Sent,
+                (Err(_), Some(_)) => DeliveryStatus::Pending,
+                (Err(_), None) => DeliveryStatus::Failed,
+            };
+            let sent_at = result.is_ok().then_some(attempted_at);
+            if let Err(e) = self.emails.update_delivery_attempts(email_id, &attempts, status, sent_at).await {
+                // The provider may already have the email; failing now would invite a duplicate send
+                error!("Failed to record delivery attempt {} for email {}: {:#}", attempt, email_id, e);
+            }
+            
+            match (result, retry_in) {
+                (Ok(_), _) => return Ok(attempted_at),
+                (Err(e), Some(delay)) => {
+                    warn!(
+                        "Attempt {} via {} failed for email {}, retrying in {:?}: {:#}",
+                        attempt, provider, email_id, delay, e,
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                (Err(e), None) => {
+                    error!("Delivery via {} failed for email {} after {} attempts: {:#}", provider, email_id, attempt, e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+    
+    /// Supplier's preferred language, English when unknown
+    async fn preferred_language(&self, supplier_id: Uuid) -> String {
+        let Some(suppliers) = &self.suppliers else {
+            return DEFAULT_LANGUAGE.to_string();
+        };
+        
+        match suppliers.find_by_id(supplier_id.into()).await {
+            Ok(Some(supplier)) => supplier.communication_preferences.preferred_language,
+            Ok(None) => DEFAULT_LANGUAGE.to_string(),
+            Err(e) => {
+                warn!("Failed to look up language for supplier {}: {:#}", supplier_id, e);
+                DEFAULT_LANGUAGE.to_string()
+            }
+        }
+    }
+    
+    pub fn webhook_verifier(&self) -> Arc<WebhookVerifier> {
+        self.webhooks.clone()
+    }
+    
+    /// Apply provider delivery events to outbound emails, returning how many changed
+    pub async fn apply_delivery_events(&self, events: &[DeliveryEvent]) -> Result<usize> {
+        let mut updated = 0;
+        
+        for event in events {
+            let email = self.emails.find_by_message_ids(std::slice::from_ref(&event.message_id)).await?
+                .filter(|e| matches!(e.direction, EmailDirection::Outbound));
+            
+            match email {
+                Some(email) if supersedes(event.status, email.delivery_status) => {
+                    self.emails.update_delivery_status(email.id, event.status).await?;
+                    updated += 1;
+                    if event.status == DeliveryStatus::Bounced {
+                        self.publish_bounce(&email, &event.event).await;
+                    }
+                }
+                _ => debug!("No status change for {} event on {}", event.event, event.message_id),
+            }
+        }
+        
+        Ok(updated)
+    }
+    
+    /// Tell the workflow service an outbound email bounced
+    async fn publish_bounce(&self, email: &EmailCommunication, reason: &str) {
+        let event = EmailBouncedEvent {
+            email_id: Some(email.id),
+            supplier_id: Some(email.supplier_id.into()),
+            email_address: email.recipient.clone(),
+            permanent: true,
+            reason: reason.to_string(),
+            bounced_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.workflow_client.publish(DomainEvent::EmailBounced(event), None).await {
+            warn!("Failed to publish bounce of email {}: {:#}", email.id, e);
+        }
+    }
+    
+    /// Health of each outbound provider, in failover order
+    pub async fn provider_status(&self) -> Vec<ProviderStatus> {
+        self.providers.status().await
+    }
+    
+    pub fn send_queue(&self) -> Arc<SendQueue> {
+        self.send_queue.clone()
+    }
+    
+    /// Queue a campaign for staggered delivery outside recipients' quiet hours
+    pub async fn schedule_campaign(&self, request: ScheduleCampaignRequest) -> Result<CampaignQueueResponse> {
+        let config = self.send_queue.config();
+        let campaign_id = request.campaign_id.unwrap_or_else(Uuid::new_v4);
+        let start = match request.start_at {
+            Some(start_at) => chrono::DateTime::parse_from_rfc3339(&start_at)
+                .context("Invalid start_at format")?
+                .with_timezone(&chrono::Utc),
+            None => chrono::Utc::now(),
+        };
+        let stagger = request.stagger_seconds
+            .map(|secs| chrono::Duration::seconds(secs as i64))
+            .unwrap_or(config.default_stagger);
+        let quiet_hours = request.quiet_hours.unwrap_or(config.default_quiet_hours);
+        
+        let mut entries = request.emails.into_iter()
+            .map(|email| {
+                let timezone = match email.timezone.as_deref() {
+                    Some(name) => name.parse()
+                        .map_err(|_| anyhow::anyhow!("Unknown timezone: {}", name))?,
+                    None => chrono_tz::UTC,
+                };
+                Ok(ScheduleEntry { request: email.email, timezone })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        
+        if request.attach_calendar {
+            let deadline = request.deadline.context("attach_calendar requires a campaign deadline")?;
+            Deadline::parse(&deadline)?;
+            for entry in &mut entries {
+                entry.request.calendar_deadline.get_or_insert_with(|| deadline.clone());
+            }
+        }
+        
+        if let Some(ab_test) = request.ab_test {
+            if self.template_engine.languages(&ab_test.variant_b_template_id).is_empty() {
+                anyhow::bail!("Unknown variant B template: {}", ab_test.variant_b_template_id);
+            }
+            let suppliers: Vec<Uuid> = entries.iter().map(|e| e.request.supplier_id).collect();
+            let variant_b = variant_b_suppliers(campaign_id, &suppliers, ab_test.variant_b_percent.unwrap_or(50));
+            for entry in &mut entries {
+                if variant_b.contains(&entry.request.supplier_id) {
+                    entry.request.template_id = ab_test.variant_b_template_id.clone();
+                    entry.request.subject = ab_test.variant_b_subject.clone();
+                    entry.request.template_variant = Some(VARIANT_B.to_string());
+                } else {
+                    entry.request.template_variant = Some(VARIANT_A.to_string());
+                }
+            }
+        }
+        
+        // Catch bad addresses now rather than as bounces once the campaign is running
+        let mut undeliverable = Vec::new();
+        for recipient in entries.iter().filter_map(|e| e.request.variables.get("contact_email")) {
+            let check = self.address_validator.check(recipient).await;
+            if !check.deliverable() {
+                undeliverable.push(check.into_error().to_string());
+            }
+        }
+        if !undeliverable.is_empty() {
+            anyhow::bail!("Campaign has undeliverable addresses: {}", undeliverable.join("; "));
+        }
+        
+        if let Some(identity_id) = request.sender_identity_id {
+            self.sender_identities.assign(campaign_id, identity_id).await?;
+        }
+        self.send_queue.schedule(campaign_id, start, stagger, quiet_hours, entries).await?;
+        Ok(self.campaign_queue(campaign_id).await)
+    }
+    
+    /// Delivery progress of a scheduled campaign
+    pub async fn campaign_queue(&self, campaign_id: Uuid) -> CampaignQueueResponse {
+        let entries = self.send_queue.campaign(campaign_id).await;
+        
+        let mut counts = QueueCounts::default();
+        for entry in &entries {
+            match entry.status.as_str() {
+                "queued" => counts.queued += 1,
+                "sending" => counts.sending += 1,
+                "sent" => counts.sent += 1,
+                "failed" => counts.failed += 1,
+                _ => counts.cancelled += 1,
+            }
+        }
+        
+        CampaignQueueResponse {
+            campaign_id,
+            counts,
+            next_send_at: entries.iter()
+                .filter(|e| e.status == "queued")
+                .map(|e| e.scheduled_for.to_rfc3339())
+                .next(),
+            emails: entries.into_iter()
+                .map(|e| QueuedEmailResponse {
+                    id: e.id,
+                    supplier_id: e.supplier_id,
+                    recipient: e.recipient,
+                    timezone: e.timezone.name().to_string(),
+                    scheduled_for: e.scheduled_for.to_rfc3339(),
+                    status: e.status,
+                    template_variant: e.template_variant,
+                    email_id: e.email_id,
+                    sent_at: e.sent_at.map(|t| t.to_rfc3339()),
+                    error: e.error,
+                })
+                .collect(),
+        }
+    }
+    
+    /// Response rates per A/B test variant; empty when the campaign had no A/B test
+    pub async fn campaign_variants(&self, campaign_id: Uuid) -> Result<CampaignVariantsResponse> {
+        let emails = self.emails.find_campaign_threads(campaign_id).await?;
+        Ok(CampaignVariantsResponse {
+            campaign_id,
+            variants: variant_stats(&emails),
         })
     }
     
+    /// Cancel a campaign's unsent emails
+    pub async fn cancel_campaign(&self, campaign_id: Uuid) -> CampaignQueueResponse {
+        self.send_queue.cancel_campaign(campaign_id).await;
+        self.campaign_queue(campaign_id).await
+    }
+    
+    /// Check addresses before they are used, e.g. contacts imported from a BOM
+    pub async fn validate_addresses(&self, request: ValidateAddressesRequest) -> ValidateAddressesResponse {
+        let mut results = Vec::with_capacity(request.email_addresses.len());
+        for address in &request.email_addresses {
+            results.push(self.address_validator.check(address).await);
+        }
+        
+        ValidateAddressesResponse {
+            deliverable: results.iter().filter(|r| r.deliverable()).count(),
+            undeliverable: results.iter().filter(|r| !r.deliverable()).count(),
+            results,
+        }
+    }
+    
+    /// Mark a supplier or a single address do-not-contact
+    pub async fn suppress(&self, request: SuppressionRequest) -> Result<SuppressionResponse> {
+        let email_address = request.email_address
+            .map(|a| a.trim().to_lowercase())
+            .filter(|a| !a.is_empty());
+        if request.supplier_id.is_none() && email_address.is_none() {
+            anyhow::bail!("supplier_id or email_address is required");
+        }
+        
+        self.add_suppression(EmailSuppression {
+            id: Uuid::new_v4(),
+            supplier_id: request.supplier_id.map(Into::into),
+            email_address,
+            reason: request.reason.unwrap_or_else(|| "Marked do-not-contact".to_string()),
+            source: SuppressionSource::Manual,
+            created_at: chrono::Utc::now(),
+        }, request.tenant_id.as_deref()).await
+    }
+    
+    /// Suppress the recipient of a signed unsubscribe link. Repeat clicks are no-ops.
+    pub async fn unsubscribe(&self, token: &str) -> Result<SuppressionResponse> {
+        let target = self.unsubscribe_links.verify(token)?;
+        
+        if let Some(existing) = self.suppressions.find_matching(target.supplier_id, &target.email_address).await? {
+            return Ok(suppression_response(existing));
+        }
+        
+        self.add_suppression(EmailSuppression {
+            id: Uuid::new_v4(),
+            supplier_id: Some(target.supplier_id.into()),
+            email_address: Some(target.email_address),
+            reason: "Recipient unsubscribed".to_string(),
+            source: SuppressionSource::UnsubscribeLink,
+            created_at: chrono::Utc::now(),
+        }, None).await
+    }
+    
+    pub async fn list_suppressions(&self) -> Result<Vec<SuppressionResponse>> {
+        Ok(self.suppressions.list().await?.into_iter().map(suppression_response).collect())
+    }
+    
+    /// Lift a suppression so the recipient can be contacted again
+    pub async fn lift_suppression(&self, id: Uuid) -> Result<bool> {
+        self.suppressions.delete(id).await
+    }
+    
+    /// Store a suppression for `tenant` and tell the workflow service to stop
+    /// automated outreach. Without a tenant it is stamped with the supplier's.
+    async fn add_suppression(&self, suppression: EmailSuppression, tenant: Option<&str>) -> Result<SuppressionResponse> {
+        let suppression = in_tenant(tenant, self.suppressions.create(suppression)).await?;
+        
+        let supplier_id = match (suppression.supplier_id, &suppression.email_address) {
+            (Some(supplier_id), _) => Some(supplier_id.into()),
+            (None, Some(address)) => self.match_supplier(address).await?,
+            (None, None) => None,
+        };
+        if let Some(supplier_id) = supplier_id {
+            let event = SupplierSuppressedEvent {
+                supplier_id,
+                email_address: suppression.email_address.clone(),
+                reason: suppression.reason.clone(),
+                source: suppression.source,
+                suppressed_at: suppression.created_at,
+            };
+            let workflow_client = self.workflow_client.clone();
+            tokio::spawn(async move {
+                if let Err(e) = workflow_client.publish(DomainEvent::SupplierSuppressed(event), None).await {
+                    warn!("Failed to publish suppression of supplier {}: {:#}", supplier_id, e);
+                }
+            });
+        }
+        
+        Ok(suppression_response(suppression))
+    }
+    
+    /// Configure the sending identity and DKIM key for a tenant
+    pub async fn set_sender_domain(&self, tenant: &str, request: SenderDomainRequest) -> Result<SenderDomainResponse> {
+        let from_email = request.from_email.trim().to_lowercase();
+        let domain = from_email.split_once('@')
+            .map(|(_, domain)| domain.to_string())
+            .filter(|domain| !domain.is_empty())
+            .context("from_email must be a full email address")?;
+        
+        let dkim = match (request.dkim_selector, request.dkim_private_key) {
+            (Some(selector), Some(key)) => Some(DkimKey::new(
+                &domain,
+                &selector,
+                request.dkim_algorithm.as_deref().unwrap_or("rsa"),
+                key.trim(),
+            )?),
+            (None, None) => None,
+            _ => anyhow::bail!("dkim_selector and dkim_private_key must be provided together"),
+        };
+        
+        let sender_domain = SenderDomain {
+            sender: SenderConfig {
+                from_email,
+                from_name: request.from_name,
+                reply_to: None,
+            },
+            dkim,
+        };
+        let response = sender_domain_response(tenant, &sender_domain);
+        self.sender_domains.register(tenant, sender_domain).await?;
+        
+        Ok(response)
+    }
+    
+    /// Create a named sender identity
+    pub async fn create_sender_identity(&self, request: SenderIdentityRequest) -> Result<SenderIdentityResponse> {
+        let identity = sender_identity(Uuid::new_v4(), chrono::Utc::now(), request)?;
+        let response = sender_identity_response(&identity);
+        self.sender_identities.upsert(identity).await;
+        
+        Ok(response)
+    }
+    
+    /// Replace a sender identity's details
+    pub async fn update_sender_identity(&self, id: Uuid, request: SenderIdentityRequest) -> Result<Option<SenderIdentityResponse>> {
+        let Some(existing) = self.sender_identities.get(id).await else {
+            return Ok(None);
+        };
+        
+        let identity = sender_identity(id, existing.created_at, request)?;
+        let response = sender_identity_response(&identity);
+        self.sender_identities.upsert(identity).await;
+        
+        Ok(Some(response))
+    }
+    
+    pub async fn list_sender_identities(&self) -> Vec<SenderIdentityResponse> {
+        self.sender_identities.list().await.iter().map(sender_identity_response).collect()
+    }
+    
+    pub async fn delete_sender_identity(&self, id: Uuid) -> bool {
+        self.sender_identities.remove(id).await
+    }
+    
+    /// Send a campaign's (or workflow's) emails as the given identity
+    pub async fn assign_campaign_sender(&self, campaign_id: Uuid, identity_id: Uuid) -> Result<()> {
+        self.sender_identities.assign(campaign_id, identity_id).await
+    }
+    
+    /// Sending identity used for a tenant
+    pub async fn get_sender_domain(&self, tenant: &str) -> Result<SenderDomainResponse> {
+        Ok(sender_domain_response(tenant, &self.sender_domains.sender_for(Some(tenant)).await?))
+    }
+    
+    /// Set the header, logo and footer used in a tenant's emails
+    pub async fn set_branding(&self, tenant: &str, branding: TenantBranding) -> Result<TenantBranding> {
+        branding.validate()?;
+        self.branding.set(tenant, branding.clone()).await;
+        Ok(branding)
+    }
+    
+    pub async fn get_branding(&self, tenant: &str) -> Option<TenantBranding> {
+        self.branding.get(tenant).await
+    }
+    
+    /// Return a tenant's emails to the Elementa defaults
+    pub async fn delete_branding(&self, tenant: &str) -> bool {
+        self.branding.remove(tenant).await
+    }
+    
+    async fn branding_for(&self, tenant: Option<&str>) -> Option<TenantBranding> {
+        self.branding.get(tenant?).await
+    }
+    
+    /// Set the certificate a tenant signs outbound and decrypts inbound email with
+    pub async fn set_smime_certificate(&self, tenant: &str, request: SmimeCertificateRequest) -> Result<SmimeCertificateResponse> {
+        let credentials = SmimeCredentials::from_pem(
+            &request.certificate_pem,
+            &request.private_key_pem,
+            request.chain_pem.as_deref(),
+        )?;
+        let smime = TenantSmime {
+            credentials: Arc::new(credentials),
+            sign: request.sign_outbound,
+            encrypt: request.encrypt_outbound,
+        };
+        let response = smime_certificate_response(tenant, &smime);
+        self.smime.set_tenant(tenant, smime).await;
+        
+        Ok(response)
+    }
+    
+    pub async fn get_smime_certificate(&self, tenant: &str) -> Option<SmimeCertificateResponse> {
+        self.smime.tenant(tenant).await
+            .map(|smime| smime_certificate_response(tenant, &smime))
+    }
+    
+    /// Stop signing a tenant's email; its inbound encrypted email can no longer be read
+    pub async fn delete_smime_certificate(&self, tenant: &str) -> bool {
+        self.smime.remove_tenant(tenant).await
+    }
+    
+    /// Encrypt email to a supplier contact with their certificate
+    pub async fn set_recipient_certificate(&self, address: &str, request: RecipientCertificateRequest) -> Result<RecipientCertificateResponse> {
+        let certificate = openssl::x509::X509::from_pem(request.certificate_pem.trim().as_bytes())
+            .context("Invalid certificate")?;
+        self.smime.set_recipient(address, certificate.clone()).await?;
+        Ok(recipient_certificate_response(address, &certificate))
+    }
+    
+    /// Recipient certificate, registered or learned from a verified signature
+    pub async fn get_recipient_certificate(&self, address: &str) -> Option<RecipientCertificateResponse> {
+        self.smime.recipient(address).await
+            .map(|certificate| recipient_certificate_response(address, &certificate))
+    }
+    
+    pub async fn delete_recipient_certificate(&self, address: &str) -> bool {
+        self.smime.remove_recipient(address).await
+    }
+    
+    /// Check SPF/DKIM/DMARC DNS records for a tenant's sending domain
+    pub async fn preflight_sender_domain(&self, tenant: &str) -> Result<PreflightReport> {
+        preflight(&self.sender_domains.sender_for(Some(tenant)).await?).await
+    }
+    
+    /// Record inbound supplier reply and queue its attachments for extraction
+    pub async fn receive_inbound_email(&self, request: InboundEmailRequest) -> Result<EmailResponse> {
+        // Decode up front so malformed payloads are rejected before anything is stored
+        let attachments = request.attachments.unwrap_or_default().into_iter()
+            .map(decode_attachment)
+            .collect::<Result<Vec<_>>>()?;
+        
+        let in_reply_to = request.in_reply_to.as_deref().map(normalize_message_id);
+        let references: Vec<String> = request.references.unwrap_or_default().iter()
+            .map(|id| normalize_message_id(id))
+            .collect();
+        
+        // Explicit thread wins; otherwise thread by headers when they match this supplier
+        let thread_id = match request.thread_id {
+            Some(thread_id) => Some(thread_id),
+            None => self.find_thread_by_headers(in_reply_to.as_ref(), &references).await?
+                .filter(|(supplier_id, _)| *supplier_id == request.supplier_id)
+                .map(|(_, thread_id)| thread_id),
+        };
+        
+        self.record_inbound(InboundRecord {
+            supplier_id: request.supplier_id,
+            thread_id,
+            campaign_id: None,
+            message_id: request.message_id.as_deref().map(normalize_message_id),
+            in_reply_to,
+            references,
+            subject: request.subject,
+            body: request.body,
+            attachments,
+            auto_reply: is_auto_reply(request.headers.iter().map(|(name, value)| (name.as_str(), value.as_str()))),
+            smime: SmimeStatus::default(),
+        }).await
+    }
+    
+    /// Supplier, campaign and thread an unexpired upload link is for
+    pub fn verify_upload_link(&self, token: &str) -> Result<UploadTarget> {
+        self.upload_links.verify(token, chrono::Utc::now())
+    }
+    
+    /// Record documents uploaded through a supplier upload link on the link's thread
+    pub async fn receive_upload(&self, token: &str, files: Vec<EmailAttachment>) -> Result<EmailResponse> {
+        let target = self.verify_upload_link(token)?;
+        if files.is_empty() {
+            anyhow::bail!("No files uploaded");
+        }
+        
+        let filenames: Vec<&str> = files.iter().map(|f| f.filename.as_str()).collect();
+        let body = format!("Documents uploaded through the secure upload link:\n{}", filenames.join("\n"));
+        
+        self.record_inbound(InboundRecord {
+            supplier_id: target.supplier_id,
+            thread_id: Some(target.thread_id),
+            campaign_id: target.campaign_id,
+            message_id: None,
+            in_reply_to: None,
+            references: Vec::new(),
+            subject: "Documents uploaded".to_string(),
+            body,
+            attachments: files,
+            auto_reply: false,
+            smime: SmimeStatus::default(),
+        }).await
+    }
+    
+    /// Match a polled message to its supplier and thread, then record it.
+    /// Threading headers are tried first, then the sender address.
+    /// Returns `None` when neither identifies a supplier.
+    pub async fn process_inbound_message(&self, mut message: InboundMessage) -> Result<Option<EmailResponse>> {
+        let (supplier_id, thread_id) = match self.find_thread_by_headers(message.in_reply_to.as_ref(), &message.references).await? {
+            Some((supplier_id, thread_id)) => (supplier_id, Some(thread_id)),
+            None => {
+                let Some(supplier_id) = self.match_supplier(&message.from_email).await? else {
+                    return Ok(None);
+                };
+                (supplier_id, self.latest_thread(supplier_id).await?)
+            }
+        };
+        
+        let smime = match message.smime.take() {
+            Some(raw) => self.open_smime(&mut message, &raw).await,
+            None => SmimeStatus::default(),
+        };
+        
+        let email = self.record_inbound(InboundRecord {
+            supplier_id,
+            thread_id,
+            campaign_id: None,
+            message_id: message.message_id,
+            in_reply_to: message.in_reply_to,
+            references: message.references,
+            subject: message.subject,
+            body: message.body,
+            attachments: message.attachments,
+            auto_reply: message.auto_reply,
+            smime,
+        }).await?;
+        
+        Ok(Some(email))
+    }
+    
+    /// Replace the body and attachments of an S/MIME message with its decrypted,
+    /// verified content. Messages no tenant key can decrypt are still recorded,
+    /// with a note in place of the body.
+    async fn open_smime(&self, message: &mut InboundMessage, raw: &[u8]) -> SmimeStatus {
+        let opened = match self.smime.open(raw, &message.from_email).await {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!("Could not open S/MIME email from {}: {:#}", message.from_email, e);
+                return SmimeStatus::default();
+            }
+        };
+        
+        match opened.content.as_deref().and_then(parse_content) {
+            Some((body, attachments)) => {
+                message.body = body;
+                message.attachments = attachments;
+            }
+            None => {
+                warn!("S/MIME email from {} could not be decrypted with any tenant certificate", message.from_email);
+                message.body = UNDECRYPTABLE_BODY.to_string();
+                message.attachments.clear();
+            }
+        }
+        if opened.status.signature_valid == Some(false) {
+            warn!("S/MIME signature on email from {} did not verify", message.from_email);
+        }
+        opened.status
+    }
+    
+    /// Supplier and thread of the email referenced by In-Reply-To or References
+    async fn find_thread_by_headers(&self, in_reply_to: Option<&String>, references: &[String]) -> Result<Option<(Uuid, String)>> {
+        // Direct parent first, then the newest referenced message
+        let candidates: Vec<String> = in_reply_to.into_iter()
+            .chain(references.iter().rev())
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        
+        Ok(self.emails.find_by_message_ids(&candidates).await?
+            .map(|email| (email.supplier_id.into(), email.thread_id)))
+    }
+    
+    /// In-Reply-To and References for a new message in a thread.
+    /// References carry the parent's references followed by the parent itself.
+    async fn reply_headers(&self, thread_id: &str) -> Result<(Option<String>, Vec<String>)> {
+        // Threads are ordered oldest first
+        let parent = self.emails.find_by_thread(thread_id).await?
+            .into_iter()
+            .rev()
+            .find(|e| e.message_id.is_some())
+            .map(|e| (e.message_id, e.references));
+        
+        Ok(match parent {
+            Some((Some(message_id), mut references)) => {
+                references.push(message_id.clone());
+                (Some(message_id), references)
+            }
+            _ => (None, Vec::new()),
+        })
+    }
+    
+    /// Resolve sender address to a supplier
+    async fn match_supplier(&self, from_email: &str) -> Result<Option<Uuid>> {
+        if let Some(suppliers) = &self.suppliers {
+            if let Some(supplier) = suppliers.find_by_email(from_email).await? {
+                return Ok(Some(supplier.id.into()));
+            }
+        }
+        
+        // Fall back to suppliers we have written to from this service
+        Ok(self.emails.find_latest_sent_to(from_email).await?.map(|e| e.supplier_id.into()))
+    }
+    
+    /// Most recent outbound thread with a supplier
+    async fn latest_thread(&self, supplier_id: Uuid) -> Result<Option<String>> {
+        // Ordered newest first
+        Ok(self.emails.find_by_supplier(supplier_id).await?
+            .into_iter()
+            .find(|e| matches!(e.direction, EmailDirection::Outbound))
+            .map(|e| e.thread_id))
+    }
+    
+    /// Store inbound email and queue its attachments for extraction
+    async fn record_inbound(&self, inbound: InboundRecord) -> Result<EmailResponse> {
+        // Redelivered copies return the original record without being processed again
+        let content_hash = content_hash(&inbound.subject, &inbound.body, &inbound.attachments);
+        let dedup_guard = self.inbound_dedup.lock().await;
+        let since = chrono::Utc::now() - self.inbound_dedup.window;
+        if let Some(original) = self.emails.find_inbound_duplicate(
+            inbound.supplier_id, inbound.message_id.as_deref(), &content_hash, since,
+        ).await? {
+            debug!("Dropping duplicate of inbound email {} (Message-ID {:?})", original.id, inbound.message_id);
+            return Ok(record_response(original));
+        }
+        
+        let email_id = Uuid::new_v4();
+        let thread_id = inbound.thread_id.unwrap_or_else(|| format!("thread_{}", email_id));
+        let (attachments, skipped): (Vec<_>, Vec<_>) = inbound.attachments.into_iter()
+            .partition(|a| is_forwardable(&a.filename, &a.content_type));
+        let received_at = chrono::Utc::now();
+        
+        // Forwarded attachments come first so ingestion indices line up
+        let stored: Vec<StoredAttachment> = attachments.iter()
+            .map(|a| (a, "pending"))
+            .chain(skipped.iter().map(|a| (a, "skipped")))
+            .map(|(a, status)| StoredAttachment {
+                filename: a.filename.clone(),
+                content_type: a.content_type.clone(),
+                size: a.data.len(),
+                document_ids: Vec::new(),
+                status: status.to_string(),
+                cas_numbers_found: 0,
+                needs_review: false,
+                error: None,
+            })
+            .collect();
+        
+        let record = EmailCommunication {
+            id: email_id,
+            thread_id: thread_id.clone(),
+            supplier_id: inbound.supplier_id.into(),
+            direction: EmailDirection::Inbound,
+            subject: inbound.subject.clone(),
+            body: inbound.body.clone(),
+            attachments: linked_attachments(&stored),
+            received_at: Some(received_at),
+            delivery_status: DeliveryStatus::Delivered,
+            processing_status: if attachments.is_empty() {
+                EmailProcessingStatus::Processed
+            } else {
+                EmailProcessingStatus::Processing
+            },
+            message_id: inbound.message_id,
+            in_reply_to: inbound.in_reply_to,
+            references: inbound.references,
+            content_hash: Some(content_hash),
+            smime: inbound.smime,
+            ..EmailCommunication::default()
+        };
+        let email = self.emails.create(record).await
+            .with_context(|| format!("Failed to record inbound email {}", email_id))?;
+        drop(dedup_guard);
+        let response = record_response(email);
+        
+        {
+            let service = self.clone();
+            let event = ReplyToClassify {
+                email_id,
+                supplier_id: inbound.supplier_id,
+                thread_id: thread_id.clone(),
+                subject: inbound.subject,
+                body: inbound.body,
+                document_count: attachments.len(),
+                auto_reply: inbound.auto_reply,
+                received_at,
+            };
+            tokio::spawn(async move {
+                service.classify_reply(event).await;
+            });
+        }
+        
+        if !attachments.is_empty() {
+            let service = self.clone();
+            let source = AttachmentSource {
+                supplier_id: inbound.supplier_id,
+                email_id,
+                thread_id,
+                campaign_id: inbound.campaign_id,
+            };
+            tokio::spawn(async move {
+                service.ingest_attachments(source, attachments).await;
+            });
+        }
+        
+        Ok(response)
+    }
+    
+    /// Classify a reply, record the result and notify the workflow service
+    async fn classify_reply(&self, reply: ReplyToClassify) {
+        let classification = if reply.auto_reply {
+            Classification::auto_reply()
+        } else {
+            self.classifier.classify(&reply.subject, &reply.body, reply.document_count).await
+        };
+        // Lets the workflow hold follow-ups until the contact is back
+        let return_date = match classification.category {
+            ReplyClassification::OutOfOffice => return_date(&reply.body, reply.received_at),
+            _ => None,
+        };
+        
+        if let Err(e) = self.emails
+            .update_classification(reply.email_id, classification.category, classification.confidence)
+            .await
+        {
+            error!("Failed to store classification for email {}: {:#}", reply.email_id, e);
+        }
+        
+        let event = ReplyClassifiedEvent {
+            email_id: reply.email_id,
+            supplier_id: reply.supplier_id,
+            thread_id: reply.thread_id,
+            classification: classification.into(),
+            document_count: reply.document_count,
+            received_at: reply.received_at,
+            return_date,
+        };
+        if let Err(e) = self.workflow_client.publish(DomainEvent::ReplyClassified(event), None).await {
+            warn!("Failed to publish classification for email {}: {:#}", reply.email_id, e);
+        }
+    }
+    
+    /// Upload and extract each attachment, recording results on the email
+    async fn ingest_attachments(&self, source: AttachmentSource, attachments: Vec<EmailAttachment>) {
+        let mut failed = false;
+        
+        for (index, attachment) in attachments.into_iter().enumerate() {
+            let filename = attachment.filename.clone();
+            if let Err(e) = self.ingest_attachment(&source, index, attachment).await {
+                warn!("Failed to ingest attachment {} on email {}: {:#}", filename, source.email_id, e);
+                failed = true;
+                self.update_attachment(source.email_id, index, |a| {
+                    a.status = "failed".to_string();
+                    a.error = Some(format!("{:#}", e));
+                }).await;
+            }
+        }
+        
+        let status = if failed { EmailProcessingStatus::Failed } else { EmailProcessingStatus::Processed };
+        if let Err(e) = self.emails.update_processing_status(source.email_id, status).await {
+            error!("Failed to update processing status for email {}: {:#}", source.email_id, e);
+        }
+    }
+    
+    async fn ingest_attachment(&self, source: &AttachmentSource, index: usize, attachment: EmailAttachment) -> Result<()> {
+        let document_ids = self.document_client
+            .upload(&attachment.filename, &attachment.content_type, attachment.data, source)
+            .await?;
+        
+        let uploaded = document_ids.clone();
+        self.update_attachment(source.email_id, index, |a| {
+            a.document_ids = uploaded;
+            a.status = "uploaded".to_string();
+        }).await;
+        
+        let mut cas_numbers_found = 0;
+        let mut needs_review = false;
+        for document_id in &document_ids {
+            let summary = self.document_client.extract(*document_id).await?;
+            cas_numbers_found += summary.cas_numbers_found;
+            needs_review |= summary.needs_review;
+        }
+        
+        self.update_attachment(source.email_id, index, |a| {
+            a.status = "extracted".to_string();
+            a.cas_numbers_found = cas_numbers_found;
+            a.needs_review = needs_review;
+        }).await;
+        
+        let event = DocumentExtractedEvent {
+            email_id: source.email_id,
+            supplier_id: source.supplier_id,
+            campaign_id: source.campaign_id,
+            document_ids,
+            cas_numbers_found,
+            needs_review,
+            extracted_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.workflow_client.publish(DomainEvent::DocumentExtracted(event), None).await {
+            warn!("Failed to publish extracted documents from email {}: {:#}", source.email_id, e);
+        }
+        
+        Ok(())
+    }
+    
+    /// Record one attachment's progress on the email
+    async fn update_attachment(&self, email_id: Uuid, index: usize, update: impl FnOnce(&mut StoredAttachment)) {
+        let result = async {
+            let Some(email) = self.emails.find_by_id(email_id).await? else {
+                return Ok(false);
+            };
+            let mut attachments = stored_attachments(email.attachments);
+            let Some(attachment) = attachments.get_mut(index) else {
+                return Ok(false);
+            };
+            update(attachment);
+            self.emails.update_attachments(email_id, &linked_attachments(&attachments)).await
+        }.await;
+        
+        if let Err(e) = result {
+            error!("Failed to update attachment {} on email {}: {:#}", index, email_id, e);
+        }
+    }
+    
     /// Get email by ID
     pub async fn get_email(&self, id: Uuid) -> Result<Option<EmailResponse>> {
-        let emails = self.emails.read().await;
-        Ok(emails.get(&id).map(|e| self.to_response(e)))
+        Ok(self.emails.find_by_id(id).await?.map(record_response))
     }
     
-    /// Get emails in thread
+    /// Get emails in thread, oldest first, including linked documents
     pub async fn get_thread(&self, thread_id: &str) -> Result<Vec<EmailResponse>> {
-        let emails = self.emails.read().await;
-        Ok(emails.values()
-            .filter(|e| e.thread_id == thread_id)
-            .map(|e| self.to_response(e))
-            .collect())
+        let mut thread: Vec<EmailResponse> = self.emails.find_by_thread(thread_id).await?
+            .into_iter()
+            .map(record_response)
+            .collect();
+        
+        thread.sort_by(|a, b| a.sent_at.as_ref().or(a.received_at.as_ref())
+            .cmp(&b.sent_at.as_ref().or(b.received_at.as_ref())));
+        Ok(thread)
+    }
+    
+    /// Summarize a thread's commitments, open items and the supplier's last word;
+    /// `None` when the thread has no emails. Fails with `SummariesDisabled` without a model.
+    pub async fn summarize_thread(&self, thread_id: &str) -> Result<Option<ThreadSummaryResponse>> {
+        let model = self.summarizer.as_ref().ok_or(SummariesDisabled)?;
+        let mut messages: Vec<ThreadMessage> = self.emails.find_by_thread(thread_id).await?
+            .into_iter()
+            // Emails that never went out are not part of the conversation
+            .filter(|e| !matches!(e.delivery_status, DeliveryStatus::Failed | DeliveryStatus::Bounced))
+            .filter_map(|e| {
+                let at = e.sent_at.or(e.received_at)?;
+                let from_supplier = matches!(e.direction, EmailDirection::Inbound);
+                Some(ThreadMessage::new(from_supplier, at, &e.subject, &e.body))
+            })
+            .collect();
+        if messages.is_empty() {
+            return Ok(None);
+        }
+        messages.sort_by_key(|m| m.at);
+        
+        let summary = model.summarize(&transcript(&messages)).await
+            .with_context(|| format!("Failed to summarize thread {}", thread_id))?;
+        Ok(Some(ThreadSummaryResponse {
+            thread_id: thread_id.to_string(),
+            message_count: messages.len(),
+            summary: summary.summary,
+            commitments: summary.commitments,
+            outstanding_items: summary.outstanding_items,
+            last_supplier_statement: last_supplier_statement(&messages).map(|m| SupplierStatementResponse {
+                received_at: m.at.to_rfc3339(),
+                subject: m.subject.clone(),
+                text: m.text.clone(),
+            }),
+            model: model.name().to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+        }))
     }
     
     /// Get emails for supplier
     pub async fn get_supplier_emails(&self, supplier_id: Uuid) -> Result<Vec<EmailResponse>> {
-        let emails = self.emails.read().await;
-        Ok(emails.values()
-            .filter(|e| e.supplier_id == supplier_id)
-            .map(|e| self.to_response(e))
+        Ok(self.emails.find_by_supplier(supplier_id).await?
+            .into_iter()
+            .map(record_response)
             .collect())
     }
     
@@ -122,42 +1347,784 @@ impl EmailService {
                 name: t.name.clone(),
                 description: t.description.clone(),
                 variables: t.variables.iter().map(|v| v.name.clone()).collect(),
+                languages: self.template_engine.languages(&t.id),
             })
             .collect()
     }
     
     /// Render template preview
-    pub fn render_template(&self, template_id: &str, variables: &HashMap<String, String>) -> Result<RenderTemplateResponse> {
+    pub fn render_template(
+        &self,
+        template_id: &str,
+        language: Option<&str>,
+        variables: &HashMap<String, String>,
+    ) -> Result<RenderTemplateResponse> {
         let json_vars: HashMap<String, serde_json::Value> = variables.iter()
             .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
             .collect();
         
-        let rendered = self.template_engine.render(template_id, &json_vars)?;
+        let rendered = self.template_engine.render(template_id, language.unwrap_or(DEFAULT_LANGUAGE), &json_vars)?;
         
         Ok(RenderTemplateResponse {
+            language: rendered.language,
             subject: rendered.subject,
             body: rendered.body_html,
         })
     }
     
-    fn to_response(&self, email: &StoredEmail) -> EmailResponse {
-        EmailResponse {
-            id: email.id,
-            thread_id: email.thread_id.clone(),
-            supplier_id: email.supplier_id,
-            direction: email.direction.clone(),
-            subject: email.subject.clone(),
-            body: email.body.clone(),
-            sent_at: email.sent_at.clone(),
-            received_at: email.received_at.clone(),
-            delivery_status: email.delivery_status.clone(),
-            processing_status: email.processing_status.clone(),
+    pub fn digest_config(&self) -> Arc<DigestConfig> {
+        self.digest_config.clone()
+    }
+    
+    /// Gather the week's campaign progress, escalations and PFAS detections
+    pub async fn compose_digest(&self) -> Result<Digest> {
+        let workflows = self.workflow_client.list_workflows().await?;
+        let escalations = self.workflow_client.list_escalations().await?;
+        let records = match &self.compliance_records {
+            Some(repository) => repository.find_with_pfas().await?,
+            None => Vec::new(),
+        };
+        
+        Ok(compose(chrono::Utc::now(), &self.digest_config, &workflows, &escalations, &records))
+    }
+    
+    /// Render the current digest without sending it
+    pub async fn preview_digest(&self) -> Result<DigestResponse> {
+        let digest = self.compose_digest().await?;
+        self.render_digest(&digest, Vec::new())
+    }
+    
+    /// Send the digest to the configured internal recipients
+    pub async fn send_digest(&self) -> Result<DigestResponse> {
+        if self.digest_config.recipients.is_empty() {
+            anyhow::bail!("No digest recipients configured; set DIGEST_RECIPIENTS");
+        }
+        
+        let digest = self.compose_digest().await?;
+        let response = self.render_digest(&digest, Vec::new())?;
+        let sender = self.sender_domains.sender_for(None).await?;
+        
+        let mut delivered = Vec::new();
+        for recipient in &self.digest_config.recipients {
+            let outgoing = OutgoingEmail {
+                from: sender.sender.clone(),
+                to_email: recipient.clone(),
+                to_name: String::new(),
+                subject: response.subject.clone(),
+                body_html: response.body_html.clone(),
+                body_text: response.body_text.clone(),
+                attachments: Vec::new(),
+                message_id: generate_message_id(&sender.sender.from_email),
+                in_reply_to: None,
+                references: Vec::new(),
+                unsubscribe_url: None,
+                dkim: sender.dkim.as_ref().map(|key| key.config()),
+                smime: None,
+            };
+            match self.providers.send(&outgoing).await.result {
+                Ok(_) => delivered.push(recipient.clone()),
+                Err(e) => error!("Failed to send compliance digest to {}: {:#}", recipient, e),
+            }
         }
+        
+        if delivered.is_empty() {
+            anyhow::bail!("Compliance digest could not be delivered to any recipient");
+        }
+        self.render_digest(&digest, delivered)
+    }
+    
+    fn render_digest(&self, digest: &Digest, recipients: Vec<String>) -> Result<DigestResponse> {
+        let variables = match serde_json::to_value(digest)? {
+            serde_json::Value::Object(map) => map.into_iter().collect(),
+            _ => HashMap::new(),
+        };
+        let rendered = self.template_engine.render(DIGEST_TEMPLATE, DEFAULT_LANGUAGE, &variables)?;
+        
+        Ok(DigestResponse {
+            recipients,
+            subject: rendered.subject,
+            body_html: rendered.body_html,
+            body_text: rendered.body_text,
+            pending_responses: digest.pending_responses.iter().map(|p| p.awaiting).sum(),
+            pfas_detections: digest.pfas_detections.len(),
+            upcoming_deadlines: digest.upcoming_deadlines.len(),
+            open_escalations: digest.escalations.len(),
+        })
+    }
+    
+    pub fn has_template(&self, template_id: &str) -> bool {
+        self.template_engine.get_template(template_id, DEFAULT_LANGUAGE).is_some()
+    }
+    
+    /// Render a template with sample data and report missing and unused variables
+    pub async fn preview_template(&self, template_id: &str, request: PreviewTemplateRequest) -> Result<TemplatePreviewResponse> {
+        let mut variables = request.variables.unwrap_or_default();
+        if let Some(branding) = self.branding_for(request.tenant_id.as_deref()).await {
+            variables.insert(BRANDING_VARIABLE.to_string(), serde_json::to_value(branding)?);
+        }
+        
+        let preview = self.template_engine.preview(
+            template_id,
+            request.language.as_deref().unwrap_or(DEFAULT_LANGUAGE),
+            &variables,
+        )?;
+        
+        Ok(TemplatePreviewResponse {
+            template_id: template_id.to_string(),
+            language: preview.rendered.language,
+            subject: preview.rendered.subject,
+            body_html: preview.rendered.body_html,
+            body_text: preview.rendered.body_text,
+            missing_variables: preview.missing.into_iter()
+                .map(|m| MissingVariableResponse { name: m.name, required: m.required, sample_value: m.sample_value })
+                .collect(),
+            unused_variables: preview.unused,
+        })
     }
 }
 
 impl Default for EmailService {
     fn default() -> Self {
-        Self::new()
+        Self::new(ProviderPool::single(Arc::new(LogProvider)))
+    }
+}
+
+/// Attachment metadata for the email record, one entry per linked document
+/// Run a write in `tenant`'s scope, so the rows it adds belong to that tenant
+async fn in_tenant<T>(tenant: Option<&str>, write: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    match tenant {
+        Some(tenant) => tenant::scope(TenantId::new(tenant)?, write).await,
+        None => write.await,
+    }
+}
+
+fn linked_attachments(attachments: &[StoredAttachment]) -> Vec<ModelAttachment> {
+    attachments.iter()
+        .flat_map(|a| {
+            let document_ids: Vec<Option<Uuid>> = if a.document_ids.is_empty() {
+                vec![None]
+            } else {
+                a.document_ids.iter().copied().map(Some).collect()
+            };
+            document_ids.into_iter().map(|document_id| ModelAttachment {
+                file_name: a.filename.clone(),
+                file_type: a.content_type.clone(),
+                file_size: a.size as i64,
+                document_id,
+                status: a.status.clone(),
+                cas_numbers_found: a.cas_numbers_found,
+                needs_review: a.needs_review,
+                error: a.error.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Inverse of `linked_attachments`: entries for the same file (expanded
+/// archives) are adjacent and grouped back together
+fn stored_attachments(attachments: Vec<ModelAttachment>) -> Vec<StoredAttachment> {
+    let mut grouped: Vec<StoredAttachment> = Vec::new();
+    
+    for attachment in attachments {
+        let same_file = grouped.last_mut().filter(|last| {
+            last.filename == attachment.file_name && !last.document_ids.is_empty() && attachment.document_id.is_some()
+        });
+        if let Some(last) = same_file {
+            last.document_ids.extend(attachment.document_id);
+            continue;
+        }
+        
+        grouped.push(StoredAttachment {
+            // Records from before processing state was stored
+            status: if !attachment.status.is_empty() {
+                attachment.status
+            } else if attachment.document_id.is_some() {
+                "uploaded".to_string()
+            } else {
+                "pending".to_string()
+            },
+            filename: attachment.file_name,
+            content_type: attachment.file_type,
+            size: attachment.file_size.max(0) as usize,
+            document_ids: attachment.document_id.into_iter().collect(),
+            cas_numbers_found: attachment.cas_numbers_found,
+            needs_review: attachment.needs_review,
+            error: attachment.error,
+        });
+    }
+    
+    grouped
+}
+
+/// API response for an email record
+fn record_response(email: EmailCommunication) -> EmailResponse {
+    let attachments = stored_attachments(email.attachments).into_iter()
+        .map(|a| AttachmentResponse {
+            filename: a.filename,
+            document_ids: a.document_ids,
+            status: a.status,
+            cas_numbers_found: a.cas_numbers_found,
+            needs_review: a.needs_review,
+            error: a.error,
+        })
+        .collect();
+    
+    let inbound = matches!(email.direction, EmailDirection::Inbound);
+    EmailResponse {
+        id: email.id,
+        thread_id: email.thread_id,
+        supplier_id: email.supplier_id.into(),
+        direction: if inbound { "inbound" } else { "outbound" }.to_string(),
+        subject: email.subject,
+        body: email.body,
+        sent_at: email.sent_at.map(|t| t.to_rfc3339()),
+        received_at: email.received_at.map(|t| t.to_rfc3339()),
+        delivery_status: match email.delivery_status {
+            DeliveryStatus::Delivered if inbound => "received",
+            status => delivery_status_label(status),
+        }.to_string(),
+        processing_status: match email.processing_status {
+            EmailProcessingStatus::Processing => "processing",
+            EmailProcessingStatus::Failed => "failed",
+            _ => "complete",
+        }.to_string(),
+        message_id: email.message_id,
+        in_reply_to: email.in_reply_to,
+        references: email.references,
+        classification: email.classification,
+        classification_confidence: email.classification_confidence,
+        smime: email.smime,
+        attachments,
+        delivered_via: email.delivery_attempts.iter()
+            .find(|a| a.succeeded)
+            .and_then(|a| a.provider.clone()),
+        delivery_attempts: email.delivery_attempts.into_iter()
+            .map(|a| DeliveryAttemptResponse {
+                attempt: a.attempt,
+                attempted_at: a.attempted_at.to_rfc3339(),
+                provider: a.provider,
+                succeeded: a.succeeded,
+                transient: a.transient,
+                error: a.error,
+                next_attempt_at: a.next_attempt_at.map(|t| t.to_rfc3339()),
+            })
+            .collect(),
+    }
+}
+
+fn suppression_response(suppression: EmailSuppression) -> SuppressionResponse {
+    SuppressionResponse {
+        id: suppression.id,
+        supplier_id: suppression.supplier_id.map(Into::into),
+        email_address: suppression.email_address,
+        reason: suppression.reason,
+        source: match suppression.source {
+            SuppressionSource::Manual => "manual",
+            SuppressionSource::UnsubscribeLink => "unsubscribe_link",
+        }.to_string(),
+        created_at: suppression.created_at.to_rfc3339(),
+    }
+}
+
+/// Validated sender identity from an API request
+fn sender_identity(id: Uuid, created_at: chrono::DateTime<chrono::Utc>, request: SenderIdentityRequest) -> Result<SenderIdentity> {
+    if request.from_name.trim().is_empty() {
+        anyhow::bail!("from_name is required");
+    }
+    
+    Ok(SenderIdentity {
+        id,
+        tenant_id: request.tenant_id,
+        label: request.label.unwrap_or_else(|| request.from_name.clone()),
+        from_name: request.from_name.trim().to_string(),
+        from_email: normalize_address("from_email", &request.from_email)?,
+        reply_to: request.reply_to.as_deref().map(|a| normalize_address("reply_to", a)).transpose()?,
+        signature: request.signature.filter(|s| !s.trim().is_empty()),
+        created_at,
+    })
+}
+
+fn sender_identity_response(identity: &SenderIdentity) -> SenderIdentityResponse {
+    SenderIdentityResponse {
+        id: identity.id,
+        tenant_id: identity.tenant_id.clone(),
+        label: identity.label.clone(),
+        from_name: identity.from_name.clone(),
+        from_email: identity.from_email.clone(),
+        reply_to: identity.reply_to.clone(),
+        signature: identity.signature.clone(),
+        created_at: identity.created_at.to_rfc3339(),
+    }
+}
+
+fn smime_certificate_response(tenant: &str, smime: &TenantSmime) -> SmimeCertificateResponse {
+    let certificate = &smime.credentials.certificate;
+    SmimeCertificateResponse {
+        tenant_id: tenant.to_string(),
+        addresses: certificate_addresses(certificate),
+        not_after: certificate.not_after().to_string(),
+        sign_outbound: smime.sign,
+        encrypt_outbound: smime.encrypt,
+    }
+}
+
+fn recipient_certificate_response(address: &str, certificate: &openssl::x509::X509Ref) -> RecipientCertificateResponse {
+    RecipientCertificateResponse {
+        email_address: address.trim().to_lowercase(),
+        not_after: certificate.not_after().to_string(),
+    }
+}
+
+fn sender_domain_response(tenant: &str, domain: &SenderDomain) -> SenderDomainResponse {
+    SenderDomainResponse {
+        tenant_id: tenant.to_string(),
+        domain: domain.domain().to_string(),
+        from_email: domain.sender.from_email.clone(),
+        from_name: domain.sender.from_name.clone(),
+        dkim_selector: domain.dkim.as_ref().map(|key| key.selector.clone()),
+        dkim_algorithm: domain.dkim.as_ref().map(|key| key.algorithm.clone()),
+    }
+}
+
+/// Decode base64 attachment payload
+fn decode_attachment(attachment: AttachmentRequest) -> Result<EmailAttachment> {
+    let data = BASE64.decode(&attachment.content_base64)
+        .with_context(|| format!("Invalid attachment encoding: {}", attachment.filename))?;
+    let content_type = attachment.content_type
+        .unwrap_or_else(|| guess_content_type(&attachment.filename).to_string());
+    
+    Ok(EmailAttachment { filename: attachment.filename, content_type, data })
+}
+
+fn delivery_status_label(status: DeliveryStatus) -> &'static str {
+    match status {
+        DeliveryStatus::Pending => "pending",
+        DeliveryStatus::Sent => "sent",
+        DeliveryStatus::Delivered => "delivered",
+        DeliveryStatus::Bounced => "bounced",
+        DeliveryStatus::Failed => "failed",
+        DeliveryStatus::SpamFiltered => "spam_filtered",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email_provider::EmailProvider;
+    use elementa_database::{InMemoryComplianceStore, InMemorySupplierStore};
+    use elementa_models::SupplierRecord;
+    
+    #[tokio::test]
+    async fn test_reply_threads_onto_sent_email() {
+        let service = EmailService::default();
+        let supplier_id = Uuid::new_v4();
+        
+        let sent = service.send_compliance_email(SendEmailRequest {
+            supplier_id,
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: None,
+            campaign_id: None,
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: None,
+            subject: None,
+            language: None,
+            variables: [("contact_email", "qa@supplier.example"), ("contact_name", "QA")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            attachments: None,
+        }).await.unwrap();
+        let outbound = service.get_email(sent.email_id).await.unwrap().unwrap();
+        
+        let reply = service.process_inbound_message(InboundMessage {
+            from_email: "someone-else@supplier.example".to_string(),
+            subject: format!("Re: {}", sent.subject),
+            body: "Attached.".to_string(),
+            message_id: Some("reply-1@supplier.example".to_string()),
+            in_reply_to: outbound.message_id.clone(),
+            references: Vec::new(),
+            attachments: Vec::new(),
+            auto_reply: false,
+            smime: None,
+        }).await.unwrap().unwrap();
+        assert_eq!(reply.thread_id, sent.thread_id);
+        assert_eq!(reply.supplier_id, supplier_id);
+        
+        let thread = service.get_thread(&sent.thread_id).await.unwrap();
+        assert_eq!(thread.len(), 2);
+        assert_eq!(thread[0].delivery_status, "sent");
+        assert_eq!(thread[1].delivery_status, "received");
+        
+        let updated = service.apply_delivery_events(&[DeliveryEvent {
+            message_id: outbound.message_id.unwrap(),
+            status: DeliveryStatus::Delivered,
+            event: "delivered".to_string(),
+        }]).await.unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(service.get_email(sent.email_id).await.unwrap().unwrap().delivery_status, "delivered");
+    }
+    
+    #[tokio::test]
+    async fn test_inbound_message_matches_supplier_by_alternate_address() {
+        let mut supplier = SupplierRecord::new("Acme".to_string(), "compliance@acme.example".to_string(), "QA".to_string());
+        supplier.contact_info.alternate_emails = vec!["reach@acme.example".to_string()];
+        let supplier_id: Uuid = supplier.id.into();
+        let service = EmailService::default().with_stores(
+            Arc::new(InMemorySupplierStore::with_suppliers([supplier])),
+            Arc::new(InMemoryComplianceStore::new()),
+        );
+        let message = |from: &str| InboundMessage {
+            from_email: from.to_string(),
+            subject: "PFAS declaration".to_string(),
+            body: "Attached.".to_string(),
+            message_id: None,
+            in_reply_to: None,
+            references: Vec::new(),
+            attachments: Vec::new(),
+            auto_reply: false,
+            smime: None,
+        };
+        
+        let matched = service.process_inbound_message(message("REACH@acme.example")).await.unwrap().unwrap();
+        assert_eq!(matched.supplier_id, supplier_id);
+        assert!(service.process_inbound_message(message("sales@other.example")).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_redelivered_inbound_email_is_recorded_once() {
+        let service = EmailService::default();
+        let supplier_id = Uuid::new_v4();
+        let inbound = |message_id: Option<&str>, body: &str| InboundEmailRequest {
+            supplier_id,
+            thread_id: Some("thread_dedup".to_string()),
+            message_id: message_id.map(|id| id.to_string()),
+            in_reply_to: None,
+            references: None,
+            subject: "Re: PFAS request".to_string(),
+            body: body.to_string(),
+            attachments: None,
+            headers: HashMap::new(),
+        };
+        
+        let original = service.receive_inbound_email(inbound(Some("reply-1@supplier.example"), "No PFAS.\nRegards")).await.unwrap();
+        let redelivered = service.receive_inbound_email(inbound(Some("reply-1@supplier.example"), "No PFAS.\nRegards")).await.unwrap();
+        let imap_copy = service.receive_inbound_email(inbound(None, "No PFAS.\r\nRegards\r\n")).await.unwrap();
+        assert_eq!(redelivered.id, original.id);
+        assert_eq!(imap_copy.id, original.id);
+        
+        let new_reply = service.receive_inbound_email(inbound(Some("reply-2@supplier.example"), "Attached the SDS.")).await.unwrap();
+        assert_ne!(new_reply.id, original.id);
+        assert_eq!(service.get_thread("thread_dedup").await.unwrap().len(), 2);
+    }
+    
+    /// Answers every summary request with the same summary and keeps the transcript
+    #[derive(Default)]
+    struct FixedSummary(std::sync::Mutex<String>);
+    
+    #[async_trait::async_trait]
+    impl SummaryModel for FixedSummary {
+        async fn summarize(&self, transcript: &str) -> Result<crate::thread_summary::ThreadSummary> {
+            *self.0.lock().unwrap() = transcript.to_string();
+            Ok(crate::thread_summary::ThreadSummary {
+                summary: "Supplier will send the SDS by Friday.".to_string(),
+                commitments: vec!["SDS by Friday".to_string()],
+                outstanding_items: vec!["SDS".to_string()],
+            })
+        }
+        
+        fn name(&self) -> &str {
+            "fixed"
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_thread_summary_uses_model_and_latest_reply() {
+        let mut service = EmailService { summarizer: None, ..EmailService::default() };
+        assert!(service.summarize_thread("thread_summary").await.unwrap_err().is::<SummariesDisabled>());
+        
+        let model = Arc::new(FixedSummary::default());
+        service.summarizer = Some(model.clone());
+        assert!(service.summarize_thread("thread_summary").await.unwrap().is_none());
+        
+        let supplier_id = Uuid::new_v4();
+        let sent = service.send_compliance_email(SendEmailRequest {
+            supplier_id,
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: Some("thread_summary".to_string()),
+            campaign_id: None,
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: None,
+            subject: None,
+            language: None,
+            variables: [("contact_email".to_string(), "qa@supplier.example".to_string())].into_iter().collect(),
+            attachments: None,
+        }).await.unwrap();
+        service.receive_inbound_email(InboundEmailRequest {
+            supplier_id,
+            thread_id: Some(sent.thread_id.clone()),
+            message_id: Some("reply-1@supplier.example".to_string()),
+            in_reply_to: None,
+            references: None,
+            subject: format!("Re: {}", sent.subject),
+            body: "We will send the SDS by Friday.\n\nOn Mon, 2 Mar 2026, compliance@acme.example wrote:\n> Please send".to_string(),
+            attachments: None,
+            headers: HashMap::new(),
+        }).await.unwrap();
+        
+        let summary = service.summarize_thread(&sent.thread_id).await.unwrap().unwrap();
+        assert_eq!(summary.message_count, 2);
+        assert_eq!(summary.model, "fixed");
+        assert_eq!(summary.commitments, vec!["SDS by Friday"]);
+        assert_eq!(summary.last_supplier_statement.unwrap().text, "We will send the SDS by Friday.");
+        
+        let transcript = model.0.lock().unwrap().clone();
+        assert!(transcript.find("] Us\n").unwrap() < transcript.find("] Supplier\n").unwrap());
+        assert!(!transcript.contains('<') && !transcript.contains("> Please send"));
+    }
+    
+    /// Fails with a transient error a set number of times, then delivers
+    struct FlakyProvider(std::sync::atomic::AtomicU32);
+    
+    #[async_trait::async_trait]
+    impl EmailProvider for FlakyProvider {
+        async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+            if self.0.load(std::sync::atomic::Ordering::SeqCst) > 0 {
+                self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                return Err(crate::delivery_retry::TransientFailure("421 service not available".to_string()).into());
+            }
+            Ok(email.message_id.clone())
+        }
+        
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+    }
+    
+    /// Keeps the last email handed to it
+    #[derive(Default)]
+    struct RecordingProvider(std::sync::Mutex<Option<OutgoingEmail>>);
+    
+    #[async_trait::async_trait]
+    impl EmailProvider for RecordingProvider {
+        async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+            *self.0.lock().unwrap() = Some(email.clone());
+            Ok(email.message_id.clone())
+        }
+        
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_campaign_sends_as_assigned_identity() {
+        let provider = Arc::new(RecordingProvider::default());
+        let service = EmailService::new(ProviderPool::single(provider.clone()));
+        let identity = service.create_sender_identity(SenderIdentityRequest {
+            tenant_id: None,
+            label: None,
+            from_name: "Acme Compliance".to_string(),
+            from_email: "Compliance@Acme.example".to_string(),
+            reply_to: Some("replies@acme.example".to_string()),
+            signature: Some("Jane Doe\nAcme Compliance".to_string()),
+        }).await.unwrap();
+        assert_eq!(identity.label, "Acme Compliance");
+        
+        let campaign_id = Uuid::new_v4();
+        service.assign_campaign_sender(campaign_id, identity.id).await.unwrap();
+        service.send_compliance_email(SendEmailRequest {
+            supplier_id: Uuid::new_v4(),
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: None,
+            campaign_id: Some(campaign_id),
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: None,
+            subject: None,
+            language: None,
+            variables: [("contact_email".to_string(), "qa@supplier.example".to_string())].into_iter().collect(),
+            attachments: None,
+        }).await.unwrap();
+        
+        let sent = provider.0.lock().unwrap().take().unwrap();
+        assert_eq!(sent.from.from_email, "compliance@acme.example");
+        assert_eq!(sent.from.reply_to.as_deref(), Some("replies@acme.example"));
+        assert!(sent.dkim.is_none());
+        assert!(sent.body_text.contains("\n-- \nJane Doe\nAcme Compliance"));
+        assert!(sent.body_html.contains("Jane Doe<br>Acme Compliance"));
+        
+        // Deleting the identity returns the campaign to the default sender
+        assert!(service.delete_sender_identity(identity.id).await);
+        assert!(service.sender_identity_for(None, Some(campaign_id)).await.unwrap().is_none());
+        assert!(service.sender_identity_for(Some(identity.id), None).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_calendar_deadline_attaches_ics_event() {
+        let provider = Arc::new(RecordingProvider::default());
+        let service = EmailService::new(ProviderPool::single(provider.clone()));
+        let sent = service.send_compliance_email(SendEmailRequest {
+            supplier_id: Uuid::new_v4(),
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: None,
+            campaign_id: None,
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: Some("2026-12-01".to_string()),
+            subject: None,
+            language: None,
+            variables: [("contact_email".to_string(), "qa@supplier.example".to_string())].into_iter().collect(),
+            attachments: None,
+        }).await.unwrap();
+        
+        let email = provider.0.lock().unwrap().take().unwrap();
+        assert!(email.body_text.contains("Deadline: 2026-12-01"));
+        let ics = email.attachments.iter().find(|a| a.filename == "deadline.ics").unwrap();
+        let ics = String::from_utf8(ics.data.clone()).unwrap();
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261201"));
+        assert!(ics.contains(&format!("UID:{}@", sent.thread_id)));
+    }
+    
+    #[tokio::test]
+    async fn test_transient_failures_are_retried_until_exhausted() {
+        let mut service = EmailService::new(ProviderPool::single(Arc::new(FlakyProvider(2.into()))));
+        service.retry_policy = Arc::new(RetryPolicy {
+            max_attempts: 3,
+            base_delay: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+        });
+        let request = SendEmailRequest {
+            supplier_id: Uuid::new_v4(),
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: None,
+            campaign_id: None,
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: None,
+            subject: None,
+            language: None,
+            variables: [("contact_email".to_string(), "qa@supplier.example".to_string())].into_iter().collect(),
+            attachments: None,
+        };
+        
+        let sent = service.send_compliance_email(request.clone()).await.unwrap();
+        let email = service.get_email(sent.email_id).await.unwrap().unwrap();
+        assert_eq!(email.delivery_status, "sent");
+        assert_eq!(email.delivery_attempts.len(), 3);
+        assert!(email.delivery_attempts[0].transient && email.delivery_attempts[0].next_attempt_at.is_some());
+        assert!(email.delivery_attempts[2].succeeded);
+        
+        // Out of attempts: recorded as failed with the full history
+        service.providers = Arc::new(ProviderPool::single(Arc::new(FlakyProvider(3.into()))));
+        let mut request = request;
+        request.variables.insert("contact_email".to_string(), "qa@other-supplier.example".to_string());
+        assert!(service.send_compliance_email(request.clone()).await.is_err());
+        let emails = service.get_supplier_emails(request.supplier_id).await.unwrap();
+        let failed = emails.iter().find(|e| e.delivery_status == "failed").unwrap();
+        assert_eq!(failed.delivery_attempts.len(), 3);
+        assert!(failed.delivery_attempts[2].next_attempt_at.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_upload_link_lands_on_thread() {
+        let service = EmailService::default();
+        let supplier_id = Uuid::new_v4();
+        let campaign_id = Uuid::new_v4();
+        let target = UploadTarget {
+            supplier_id,
+            campaign_id: Some(campaign_id),
+            thread_id: "thread_upload".to_string(),
+            expires_at: chrono::Utc::now() + chrono::Duration::days(1),
+        };
+        let token = service.upload_links.token(&target);
+        assert_eq!(service.verify_upload_link(&token).unwrap(), target);
+        
+        let files = vec![EmailAttachment {
+            filename: "notes.txt".to_string(),
+            content_type: "text/plain".to_string(),
+            data: b"see attached".to_vec(),
+        }];
+        let email = service.receive_upload(&token, files).await.unwrap();
+        assert_eq!(email.supplier_id, supplier_id);
+        assert_eq!(email.thread_id, "thread_upload");
+        assert_eq!(email.attachments[0].status, "skipped");
+        
+        let expired = service.upload_links.token(&UploadTarget { expires_at: chrono::Utc::now(), ..target });
+        assert!(service.receive_upload(&expired, Vec::new()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_unsubscribed_recipient_is_blocked() {
+        let service = EmailService::default();
+        let supplier_id = Uuid::new_v4();
+        let request = SendEmailRequest {
+            supplier_id,
+            template_id: "follow_up".to_string(),
+            tenant_id: None,
+            thread_id: None,
+            campaign_id: None,
+            template_variant: None,
+            sender_identity_id: None,
+            calendar_deadline: None,
+            subject: None,
+            language: None,
+            variables: [("contact_email".to_string(), "QA@supplier.example".to_string())].into_iter().collect(),
+            attachments: None,
+        };
+        service.send_compliance_email(request.clone()).await.unwrap();
+        
+        let token = service.unsubscribe_links.token(supplier_id, "qa@supplier.example");
+        let first = service.unsubscribe(&token).await.unwrap();
+        let again = service.unsubscribe(&token).await.unwrap();
+        assert_eq!(first.id, again.id);
+        
+        let error = service.send_compliance_email(request).await.unwrap_err();
+        assert!(error.downcast_ref::<RecipientSuppressed>().is_some());
+        
+        assert!(service.lift_suppression(first.id).await.unwrap());
+        assert!(service.list_suppressions().await.unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_attachment_round_trip() {
+        let document_ids = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let attachments = vec![
+            StoredAttachment {
+                filename: "sds.zip".to_string(),
+                content_type: "application/zip".to_string(),
+                size: 10,
+                document_ids: document_ids.clone(),
+                status: "extracted".to_string(),
+                cas_numbers_found: 3,
+                needs_review: true,
+                error: None,
+            },
+            StoredAttachment {
+                filename: "logo.png".to_string(),
+                content_type: "image/png".to_string(),
+                size: 5,
+                document_ids: Vec::new(),
+                status: "skipped".to_string(),
+                cas_numbers_found: 0,
+                needs_review: false,
+                error: None,
+            },
+        ];
+        
+        let linked = linked_attachments(&attachments);
+        assert_eq!(linked.len(), 3);
+        
+        let grouped = stored_attachments(linked);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[0].document_ids, document_ids);
+        assert_eq!(grouped[0].cas_numbers_found, 3);
+        assert_eq!(grouped[1].status, "skipped");
     }
 }
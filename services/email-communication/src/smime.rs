@@ -0,0 +1,434 @@
+//! S/MIME
+//!
+//! Signing and encryption of outbound mail, and decryption and signature
+//! verification of inbound mail, for suppliers that require protected
+//! correspondence. Each tenant signs and decrypts with its own certificate;
+//! recipient certificates are registered explicitly or learned from
+//! inbound signatures that verify.
+
+use anyhow::{bail, Context, Result};
+use lettre::message::header::{ContentDisposition, ContentType};
+use lettre::message::{MultiPart, SinglePart};
+use openssl::nid::Nid;
+use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::symm::Cipher;
+use openssl::x509::store::{X509Store, X509StoreBuilder};
+use openssl::x509::{X509Ref, X509};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use elementa_models::SmimeStatus;
+
+/// A tenant's signing and decryption certificate with its private key
+pub struct SmimeCredentials {
+    pub certificate: X509,
+    key: PKey<Private>,
+    /// Intermediate CAs sent along with each signature
+    chain: Stack<X509>,
+}
+
+impl SmimeCredentials {
+    pub fn from_pem(certificate: &str, private_key: &str, chain: Option<&str>) -> Result<Self> {
+        let certificate = X509::from_pem(certificate.trim().as_bytes()).context("Invalid S/MIME certificate")?;
+        let key = PKey::private_key_from_pem(private_key.trim().as_bytes()).context("Invalid S/MIME private key")?;
+        if !certificate.public_key()?.public_eq(&key) {
+            bail!("S/MIME private key does not match the certificate");
+        }
+
+        let mut stack = Stack::new()?;
+        for intermediate in X509::stack_from_pem(chain.unwrap_or_default().as_bytes()).context("Invalid certificate chain")? {
+            stack.push(intermediate)?;
+        }
+
+        Ok(Self { certificate, key, chain: stack })
+    }
+
+    /// Detached signature over `body`, as a `multipart/signed` part
+    fn sign(&self, body: MultiPart) -> Result<MultiPart> {
+        let formatted = body.formatted();
+        // The CRLF before the next boundary belongs to the delimiter, not the signed part
+        let content = formatted.strip_suffix(b"\r\n").unwrap_or(&formatted);
+        let signature = Pkcs7::sign(&self.certificate, &self.key, &self.chain, content, Pkcs7Flags::DETACHED | Pkcs7Flags::BINARY)
+            .and_then(|pkcs7| pkcs7.to_der())
+            .context("S/MIME signing failed")?;
+
+        Ok(MultiPart::signed("application/pkcs7-signature".to_string(), "sha-256".to_string())
+            .multipart(body)
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::parse("application/pkcs7-signature; name=\"smime.p7s\"").expect("valid content type"))
+                    .header(ContentDisposition::attachment("smime.p7s"))
+                    .body(signature)
+            ))
+    }
+}
+
+impl fmt::Debug for SmimeCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmimeCredentials")
+            .field("addresses", &certificate_addresses(&self.certificate))
+            .finish_non_exhaustive()
+    }
+}
+
+/// A tenant's S/MIME setup
+#[derive(Debug, Clone)]
+pub struct TenantSmime {
+    pub credentials: Arc<SmimeCredentials>,
+    /// Sign every outbound email
+    pub sign: bool,
+    /// Encrypt outbound email whenever the recipient's certificate is known
+    pub encrypt: bool,
+}
+
+/// Protection applied to one outbound email by SMTP-based providers
+#[derive(Debug, Clone)]
+pub struct SmimeOutbound {
+    pub signer: Option<Arc<SmimeCredentials>>,
+    /// Recipient certificate the email is encrypted to
+    pub recipient: Option<X509>,
+}
+
+/// Message body after S/MIME protection
+pub enum SmimeBody {
+    Multipart(MultiPart),
+    Enveloped(SinglePart),
+}
+
+impl SmimeOutbound {
+    pub fn status(&self) -> SmimeStatus {
+        SmimeStatus {
+            signed: self.signer.is_some(),
+            encrypted: self.recipient.is_some(),
+            signature_valid: None,
+        }
+    }
+
+    /// Sign, then encrypt, the message body
+    pub fn protect(&self, body: MultiPart) -> Result<SmimeBody> {
+        let body = match &self.signer {
+            Some(signer) => signer.sign(body)?,
+            None => body,
+        };
+        let Some(recipient) = &self.recipient else {
+            return Ok(SmimeBody::Multipart(body));
+        };
+
+        let mut recipients = Stack::new()?;
+        recipients.push(recipient.clone())?;
+        let enveloped = Pkcs7::encrypt(&recipients, &body.formatted(), Cipher::aes_256_cbc(), Pkcs7Flags::BINARY)
+            .and_then(|pkcs7| pkcs7.to_der())
+            .context("S/MIME encryption failed")?;
+
+        Ok(SmimeBody::Enveloped(
+            SinglePart::builder()
+                .header(ContentType::parse("application/pkcs7-mime; smime-type=enveloped-data; name=\"smime.p7m\"").expect("valid content type"))
+                .header(ContentDisposition::attachment("smime.p7m"))
+                .body(enveloped)
+        ))
+    }
+}
+
+/// Inbound message with its S/MIME layers removed
+#[derive(Debug)]
+pub struct OpenedMessage {
+    /// Inner MIME entity; `None` when no tenant certificate can decrypt the message
+    pub content: Option<Vec<u8>>,
+    pub status: SmimeStatus,
+}
+
+/// Tenant credentials, known recipient certificates and the CAs inbound
+/// signatures must chain to
+pub struct SmimeRegistry {
+    tenants: RwLock<HashMap<String, TenantSmime>>,
+    recipients: RwLock<HashMap<String, X509>>,
+    trusted: X509Store,
+}
+
+impl SmimeRegistry {
+    pub fn new(trusted: X509Store) -> Self {
+        Self {
+            tenants: RwLock::new(HashMap::new()),
+            recipients: RwLock::new(HashMap::new()),
+            trusted,
+        }
+    }
+
+    /// Trust the CAs in the PEM bundle at `SMIME_CA_BUNDLE`, otherwise the system store
+    pub fn from_env() -> Self {
+        let trusted = match std::env::var("SMIME_CA_BUNDLE") {
+            Ok(path) => trust_store_from_file(&path).unwrap_or_else(|e| {
+                warn!("Failed to load S/MIME CA bundle {}: {:#}; using the system trust store", path, e);
+                system_trust_store()
+            }),
+            Err(_) => system_trust_store(),
+        };
+
+        Self::new(trusted)
+    }
+
+    pub async fn set_tenant(&self, tenant: &str, smime: TenantSmime) {
+        self.tenants.write().await.insert(tenant.to_string(), smime);
+    }
+
+    pub async fn tenant(&self, tenant: &str) -> Option<TenantSmime> {
+        self.tenants.read().await.get(tenant).cloned()
+    }
+
+    pub async fn remove_tenant(&self, tenant: &str) -> bool {
+        self.tenants.write().await.remove(tenant).is_some()
+    }
+
+    /// Encrypt future email to `address` with this certificate
+    pub async fn set_recipient(&self, address: &str, certificate: X509) -> Result<()> {
+        let address = address.trim().to_lowercase();
+        if !certificate_addresses(&certificate).contains(&address) {
+            bail!("Certificate is not issued for {}", address);
+        }
+        self.recipients.write().await.insert(address, certificate);
+        Ok(())
+    }
+
+    pub async fn recipient(&self, address: &str) -> Option<X509> {
+        self.recipients.read().await.get(&address.trim().to_lowercase()).cloned()
+    }
+
+    pub async fn remove_recipient(&self, address: &str) -> bool {
+        self.recipients.write().await.remove(&address.trim().to_lowercase()).is_some()
+    }
+
+    /// Protection for an email from `tenant` to `to_email`; `None` when the
+    /// tenant neither signs nor can encrypt to the recipient
+    pub async fn outbound(&self, tenant: Option<&str>, to_email: &str) -> Option<SmimeOutbound> {
+        let smime = self.tenant(tenant?).await?;
+        let recipient = match smime.encrypt {
+            true => self.recipient(to_email).await,
+            false => None,
+        };
+        let signer = smime.sign.then_some(smime.credentials);
+
+        (signer.is_some() || recipient.is_some()).then_some(SmimeOutbound { signer, recipient })
+    }
+
+    /// Decrypt and verify an inbound S/MIME message from `from_email`.
+    /// A signature only counts as valid when it chains to a trusted CA and
+    /// the signing certificate is issued for the sender address.
+    pub async fn open(&self, raw: &[u8], from_email: &str) -> Result<OpenedMessage> {
+        let mut status = SmimeStatus::default();
+        let (mut pkcs7, mut detached) = Pkcs7::from_smime(raw).context("Malformed S/MIME message")?;
+
+        if kind(&pkcs7) == Some(Nid::PKCS7_ENVELOPED) {
+            status.encrypted = true;
+            let tenants: Vec<Arc<SmimeCredentials>> = self.tenants.read().await.values()
+                .map(|t| t.credentials.clone())
+                .collect();
+            let Some(content) = tenants.iter()
+                .find_map(|c| pkcs7.decrypt(&c.key, &c.certificate, Pkcs7Flags::empty()).ok())
+            else {
+                return Ok(OpenedMessage { content: None, status });
+            };
+
+            // Signed before being encrypted
+            match Pkcs7::from_smime(&content) {
+                Ok((inner, inner_detached)) if kind(&inner) == Some(Nid::PKCS7_SIGNED) => {
+                    pkcs7 = inner;
+                    detached = inner_detached;
+                }
+                _ => return Ok(OpenedMessage { content: Some(content), status }),
+            }
+        }
+
+        if kind(&pkcs7) != Some(Nid::PKCS7_SIGNED) {
+            bail!("Unsupported S/MIME content");
+        }
+        status.signed = true;
+
+        let no_certs = Stack::new()?;
+        let mut content = Vec::new();
+        let verified = pkcs7.verify(&no_certs, &self.trusted, detached.as_deref(), Some(&mut content), Pkcs7Flags::empty());
+        if verified.is_err() {
+            // Still recover the content of an untrusted signature
+            pkcs7.verify(&no_certs, &self.trusted, detached.as_deref(), Some(&mut content), Pkcs7Flags::NOVERIFY | Pkcs7Flags::NOSIGS)
+                .context("Failed to read signed S/MIME content")?;
+        }
+
+        let from_email = from_email.trim().to_lowercase();
+        let signer = pkcs7.signers(&no_certs, Pkcs7Flags::empty()).ok()
+            .and_then(|signers| signers.into_iter().next());
+        let valid = verified.is_ok()
+            && signer.as_ref().is_some_and(|cert| certificate_addresses(cert).contains(&from_email));
+        status.signature_valid = Some(valid);
+        if let (true, Some(cert)) = (valid, signer) {
+            self.recipients.write().await.insert(from_email, cert);
+        }
+
+        Ok(OpenedMessage { content: Some(content), status })
+    }
+}
+
+impl Default for SmimeRegistry {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Whether a raw message is S/MIME signed or encrypted, judging by its top-level Content-Type
+pub fn is_smime(message: &mail_parser::Message) -> bool {
+    use mail_parser::MimeHeaders;
+
+    let Some(content_type) = message.content_type() else {
+        return false;
+    };
+    let subtype = content_type.subtype().unwrap_or_default().to_lowercase();
+    match content_type.ctype().to_lowercase().as_str() {
+        "application" => subtype == "pkcs7-mime" || subtype == "x-pkcs7-mime",
+        "multipart" => subtype == "signed" && content_type.attribute("protocol")
+            .is_some_and(|protocol| protocol.to_lowercase().contains("pkcs7-signature")),
+        _ => false,
+    }
+}
+
+/// Email addresses a certificate is issued for, lowercased
+pub fn certificate_addresses(certificate: &X509Ref) -> Vec<String> {
+    let mut addresses: Vec<String> = certificate.subject_alt_names().into_iter()
+        .flatten()
+        .filter_map(|name| name.email().map(str::to_lowercase))
+        .collect();
+    addresses.extend(certificate.subject_name().entries_by_nid(Nid::PKCS9_EMAILADDRESS)
+        .filter_map(|entry| entry.data().as_utf8().ok())
+        .map(|address| address.to_lowercase()));
+    addresses
+}
+
+fn kind(pkcs7: &Pkcs7) -> Option<Nid> {
+    pkcs7.type_().map(|object| object.nid())
+}
+
+fn trust_store_from_file(path: &str) -> Result<X509Store> {
+    let pem = std::fs::read(path)?;
+    let mut builder = X509StoreBuilder::new()?;
+    for ca in X509::stack_from_pem(&pem)? {
+        builder.add_cert(ca)?;
+    }
+    Ok(builder.build())
+}
+
+fn system_trust_store() -> X509Store {
+    let mut builder = X509StoreBuilder::new().expect("X509 store allocation");
+    if let Err(e) = builder.set_default_paths() {
+        warn!("Failed to load the system trust store; no S/MIME signature will verify: {}", e);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lettre::Message;
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::hash::MessageDigest;
+    use openssl::rsa::Rsa;
+    use openssl::x509::extension::{BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName};
+    use openssl::x509::X509NameBuilder;
+
+    fn certificate(name: &str, email: Option<&str>, issuer: Option<(&X509, &PKey<Private>)>) -> (X509, PKey<Private>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut subject = X509NameBuilder::new().unwrap();
+        subject.append_entry_by_nid(Nid::COMMONNAME, name).unwrap();
+        let subject = subject.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_serial_number(&BigNum::from_u32(rand::random::<u16>().into()).unwrap().to_asn1_integer().unwrap()).unwrap();
+        builder.set_subject_name(&subject).unwrap();
+        builder.set_issuer_name(issuer.map_or(&subject, |(ca, _)| ca.subject_name())).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&Asn1Time::days_from_now(30).unwrap()).unwrap();
+        match email {
+            Some(email) => {
+                let san = SubjectAlternativeName::new().email(email).build(&builder.x509v3_context(issuer.map(|(ca, _)| ca.as_ref()), None)).unwrap();
+                builder.append_extension(san).unwrap();
+                builder.append_extension(KeyUsage::new().digital_signature().key_encipherment().build().unwrap()).unwrap();
+                builder.append_extension(ExtendedKeyUsage::new().email_protection().build().unwrap()).unwrap();
+            }
+            None => {
+                builder.append_extension(BasicConstraints::new().critical().ca().build().unwrap()).unwrap();
+                builder.append_extension(KeyUsage::new().key_cert_sign().build().unwrap()).unwrap();
+            }
+        }
+        builder.sign(issuer.map_or(&key, |(_, ca_key)| ca_key), MessageDigest::sha256()).unwrap();
+        (builder.build(), key)
+    }
+
+    fn registry_trusting(ca: &X509) -> SmimeRegistry {
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(ca.clone()).unwrap();
+        SmimeRegistry::new(store.build())
+    }
+
+    fn credentials(cert: &X509, key: &PKey<Private>) -> Arc<SmimeCredentials> {
+        let pem = |bytes: Vec<u8>| String::from_utf8(bytes).unwrap();
+        Arc::new(SmimeCredentials::from_pem(&pem(cert.to_pem().unwrap()), &pem(key.private_key_to_pem_pkcs8().unwrap()), None).unwrap())
+    }
+
+    fn raw_email(from: &str, body: SmimeBody) -> Vec<u8> {
+        let builder = Message::builder()
+            .from(from.parse().unwrap())
+            .to("jane@supplier.example".parse().unwrap())
+            .subject("PFAS declaration");
+        match body {
+            SmimeBody::Multipart(part) => builder.multipart(part),
+            SmimeBody::Enveloped(part) => builder.singlepart(part),
+        }.unwrap().formatted()
+    }
+
+    #[tokio::test]
+    async fn test_signed_and_encrypted_round_trip() {
+        let (ca, ca_key) = certificate("Test CA", None, None);
+        let (tenant_cert, tenant_key) = certificate("Acme Compliance", Some("compliance@acme.example"), Some((&ca, &ca_key)));
+        let (supplier_cert, supplier_key) = certificate("Jane Doe", Some("jane@supplier.example"), Some((&ca, &ca_key)));
+
+        let ours = registry_trusting(&ca);
+        ours.set_tenant("acme", TenantSmime { credentials: credentials(&tenant_cert, &tenant_key), sign: true, encrypt: true }).await;
+        let theirs = registry_trusting(&ca);
+        theirs.set_tenant("supplier", TenantSmime { credentials: credentials(&supplier_cert, &supplier_key), sign: true, encrypt: true }).await;
+
+        // Without the supplier's certificate the email can only be signed
+        let body = || MultiPart::alternative_plain_html("Please send the SDS.".to_string(), "<p>Please send the SDS.</p>".to_string());
+        let signed_only = ours.outbound(Some("acme"), "jane@supplier.example").await.unwrap();
+        assert_eq!(signed_only.status(), SmimeStatus { signed: true, encrypted: false, signature_valid: None });
+        let raw = raw_email("compliance@acme.example", signed_only.protect(body()).unwrap());
+        let opened = theirs.open(&raw, "compliance@acme.example").await.unwrap();
+        assert_eq!(opened.status, SmimeStatus { signed: true, encrypted: false, signature_valid: Some(true) });
+        assert!(String::from_utf8_lossy(&opened.content.unwrap()).contains("Please send the SDS."));
+
+        // The same signature does not vouch for a different sender
+        let spoofed = theirs.open(&raw, "ceo@acme.example").await.unwrap();
+        assert_eq!(spoofed.status.signature_valid, Some(false));
+
+        ours.set_recipient("Jane@Supplier.example", supplier_cert.clone()).await.unwrap();
+        assert!(ours.set_recipient("someone@else.example", supplier_cert).await.is_err());
+        let protected = ours.outbound(Some("acme"), "jane@supplier.example").await.unwrap();
+        let raw = raw_email("compliance@acme.example", protected.protect(body()).unwrap());
+        assert!(!String::from_utf8_lossy(&raw).contains("Please send the SDS."));
+        assert!(crate::imap_client::parse_message(&raw).unwrap().smime.is_some());
+
+        let opened = theirs.open(&raw, "compliance@acme.example").await.unwrap();
+        assert_eq!(opened.status, SmimeStatus { signed: true, encrypted: true, signature_valid: Some(true) });
+        let (body, attachments) = crate::imap_client::parse_content(&opened.content.unwrap()).unwrap();
+        assert_eq!(body.trim(), "Please send the SDS.");
+        assert!(attachments.is_empty());
+
+        // Verified senders can be written to encrypted from then on
+        assert!(theirs.recipient("compliance@acme.example").await.is_some());
+        let unreadable = ours.open(&raw, "compliance@acme.example").await.unwrap();
+        assert!(unreadable.content.is_none() && unreadable.status.encrypted);
+    }
+}
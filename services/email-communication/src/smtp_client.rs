@@ -1,23 +1,49 @@
 //! SMTP Client
-//! 
+//!
 //! Handles email sending via SMTP using lettre.
+//! The transport is built once and keeps a pool of authenticated connections.
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use lettre::{
-    transport::smtp::authentication::Credentials,
+    transport::smtp::{authentication::Credentials, PoolConfig},
     AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
-use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::message::header::{ContentType, Header, HeaderName, HeaderValue};
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use crate::email_provider::{message_id_header, EmailProvider, OutgoingEmail};
+use crate::smime::SmimeBody;
+
+/// SMTP transport security
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Upgrade a plaintext connection with STARTTLS (usually port 587)
+    StartTls,
+    /// TLS from the first byte (usually port 465)
+    Implicit,
+    /// No encryption; local relays and testing only
+    None,
+}
+
+impl SmtpTls {
+    fn from_env_value(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "tls" | "implicit" | "smtps" => SmtpTls::Implicit,
+            "none" | "plain" => SmtpTls::None,
+            _ => SmtpTls::StartTls,
+        }
+    }
+}
 
 /// SMTP client configuration
 #[derive(Debug, Clone)]
 pub struct SmtpConfig {
     pub host: String,
     pub port: u16,
+    pub tls: SmtpTls,
     pub username: String,
     pub password: String,
-    pub from_email: String,
-    pub from_name: String,
+    pub pool_size: u32,
 }
 
 impl Default for SmtpConfig {
@@ -25,75 +51,157 @@ impl Default for SmtpConfig {
         Self {
             host: std::env::var("SMTP_HOST").unwrap_or_else(|_| "smtp.example.com".to_string()),
             port: std::env::var("SMTP_PORT").unwrap_or_else(|_| "587".to_string()).parse().unwrap_or(587),
+            tls: SmtpTls::from_env_value(&std::env::var("SMTP_TLS").unwrap_or_default()),
             username: std::env::var("SMTP_USERNAME").unwrap_or_default(),
             password: std::env::var("SMTP_PASSWORD").unwrap_or_default(),
-            from_email: std::env::var("SMTP_FROM_EMAIL").unwrap_or_else(|_| "compliance@elementa.io".to_string()),
-            from_name: std::env::var("SMTP_FROM_NAME").unwrap_or_else(|_| "Elementa Compliance".to_string()),
+            pool_size: std::env::var("SMTP_POOL_SIZE").unwrap_or_else(|_| "4".to_string()).parse().unwrap_or(4),
         }
     }
 }
 
 /// SMTP client for sending emails
-#[allow(dead_code)]
 pub struct SmtpClient {
-    config: SmtpConfig,
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
 }
 
 impl SmtpClient {
-    pub fn new(config: SmtpConfig) -> Self {
-        Self { config }
+    pub fn new(config: SmtpConfig) -> Result<Self> {
+        let builder = match config.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .context("Failed to create SMTP transport")?,
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .context("Failed to create SMTP transport")?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+
+        let mut builder = builder
+            .port(config.port)
+            .pool_config(PoolConfig::new().max_size(config.pool_size.max(1)));
+
+        if !config.username.is_empty() {
+            builder = builder.credentials(Credentials::new(config.username, config.password));
+        }
+
+        Ok(Self { mailer: builder.build() })
     }
-    
-    /// Send email
-    #[allow(dead_code)]
-    pub async fn send(&self, to_email: &str, to_name: &str, subject: &str, body_html: &str, body_text: &str) -> Result<String> {
-        let from_mailbox: Mailbox = format!("{} <{}>", self.config.from_name, self.config.from_email)
+
+    /// Build MIME message with text/HTML alternatives and attachments
+    fn build_message(&self, email: &OutgoingEmail) -> Result<Message> {
+        let from_mailbox: Mailbox = format!("{} <{}>", email.from.from_name, email.from.from_email)
             .parse()
             .context("Invalid from address")?;
-        
-        let to_mailbox: Mailbox = format!("{} <{}>", to_name, to_email)
+
+        let to_mailbox: Mailbox = format!("{} <{}>", email.to_name, email.to_email)
             .parse()
             .context("Invalid to address")?;
+
+        let alternative = MultiPart::alternative()
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(email.body_text.clone())
+            )
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_HTML)
+                    .body(email.body_html.clone())
+            );
+
+        let body = if email.attachments.is_empty() {
+            alternative
+        } else {
+            let mut mixed = MultiPart::mixed().multipart(alternative);
+            for attachment in &email.attachments {
+                let content_type = ContentType::parse(&attachment.content_type)
+                    .unwrap_or(ContentType::parse("application/octet-stream").expect("valid content type"));
+                mixed = mixed.singlepart(
+                    Attachment::new(attachment.filename.clone()).body(attachment.data.clone(), content_type)
+                );
+            }
+            mixed
+        };
+
+        let mut builder = Message::builder()
+            .message_id(Some(message_id_header([&email.message_id])));
+        if let Some(in_reply_to) = &email.in_reply_to {
+            builder = builder.in_reply_to(message_id_header([in_reply_to]));
+        }
+        if !email.references.is_empty() {
+            builder = builder.references(message_id_header(&email.references));
+        }
+        if let Some(reply_to) = &email.from.reply_to {
+            builder = builder.reply_to(reply_to.parse().context("Invalid reply-to address")?);
+        }
+        if let Some(url) = &email.unsubscribe_url {
+            builder = builder
+                .header(ListUnsubscribe(format!("<{}>", url)))
+                .header(ListUnsubscribePost);
+        }
         
-        let email = Message::builder()
+        let builder = builder
             .from(from_mailbox)
             .to(to_mailbox)
-            .subject(subject)
-            .multipart(
-                MultiPart::alternative()
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_PLAIN)
-                            .body(body_text.to_string())
-                    )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(body_html.to_string())
-                    )
-            )
-            .context("Failed to build email")?;
-        
-        let creds = Credentials::new(
-            self.config.username.clone(),
-            self.config.password.clone(),
-        );
-        
-        let mailer: AsyncSmtpTransport<Tokio1Executor> = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.config.host)
-            .context("Failed to create SMTP transport")?
-            .port(self.config.port)
-            .credentials(creds)
-            .build();
-        
-        let response = mailer.send(email).await
-            .context("Failed to send email")?;
-        
-        Ok(response.message().collect::<Vec<_>>().join("\n"))
+            .subject(email.subject.clone());
+        match &email.smime {
+            Some(smime) => match smime.protect(body)? {
+                SmimeBody::Multipart(body) => builder.multipart(body),
+                SmimeBody::Enveloped(body) => builder.singlepart(body),
+            },
+            None => builder.multipart(body),
+        }.context("Failed to build email")
     }
 }
 
-impl Default for SmtpClient {
-    fn default() -> Self {
-        Self::new(SmtpConfig::default())
+/// RFC 2369 `List-Unsubscribe`
+#[derive(Debug, Clone)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(s.to_string()))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), self.0.clone())
+    }
+}
+
+/// RFC 8058 one-click unsubscribe marker
+#[derive(Debug, Clone)]
+struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), "List-Unsubscribe=One-Click".to_string())
+    }
+}
+
+#[async_trait]
+impl EmailProvider for SmtpClient {
+    async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+        let mut message = self.build_message(email)?;
+        if let Some(dkim) = &email.dkim {
+            message.sign(dkim);
+        }
+        self.mailer.send(message).await
+            .context("Failed to send email")?;
+
+        Ok(email.message_id.clone())
+    }
+
+    fn name(&self) -> &'static str {
+        "smtp"
     }
 }
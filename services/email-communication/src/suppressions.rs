@@ -0,0 +1,160 @@
+//! Suppressions
+//!
+//! Do-not-contact list consulted before every send, and the signed
+//! unsubscribe links that let recipients add themselves to it.
+
+use anyhow::{Context, Result};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use elementa_database::SuppressionRepository;
+use elementa_models::EmailSuppression;
+
+use crate::link_signing::LinkSigner;
+
+/// Send blocked because the recipient is on the suppression list
+#[derive(Debug)]
+pub struct RecipientSuppressed {
+    pub email_address: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for RecipientSuppressed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is on the suppression list: {}", self.email_address, self.reason)
+    }
+}
+
+impl std::error::Error for RecipientSuppressed {}
+
+/// Suppression storage: Postgres when configured, otherwise process memory
+pub enum SuppressionList {
+    Postgres(SuppressionRepository),
+    Memory(RwLock<Vec<EmailSuppression>>),
+}
+
+impl SuppressionList {
+    pub fn memory() -> Self {
+        Self::Memory(RwLock::new(Vec::new()))
+    }
+
+    /// Suppression covering the supplier as a whole or this address
+    pub async fn find_matching(&self, supplier_id: Uuid, email_address: &str) -> Result<Option<EmailSuppression>> {
+        match self {
+            Self::Postgres(repository) => repository.find_matching(supplier_id.into(), email_address).await,
+            Self::Memory(entries) => Ok(entries.read().await.iter()
+                .find(|s| match &s.email_address {
+                    Some(address) => address.eq_ignore_ascii_case(email_address),
+                    None => s.supplier_id.is_some_and(|id| id == supplier_id),
+                })
+                .cloned()),
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<EmailSuppression>> {
+        match self {
+            Self::Postgres(repository) => repository.list().await,
+            Self::Memory(entries) => Ok(entries.read().await.iter().rev().cloned().collect()),
+        }
+    }
+
+    pub async fn create(&self, mut suppression: EmailSuppression) -> Result<EmailSuppression> {
+        match self {
+            Self::Postgres(repository) => repository.create(suppression).await,
+            Self::Memory(entries) => {
+                suppression.email_address = suppression.email_address.map(|a| a.to_lowercase());
+                entries.write().await.push(suppression.clone());
+                Ok(suppression)
+            }
+        }
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        match self {
+            Self::Postgres(repository) => repository.delete(id).await,
+            Self::Memory(entries) => {
+                let mut entries = entries.write().await;
+                let before = entries.len();
+                entries.retain(|s| s.id != id);
+                Ok(entries.len() < before)
+            }
+        }
+    }
+}
+
+/// Recipient identified by an unsubscribe token
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsubscribeTarget {
+    pub supplier_id: Uuid,
+    pub email_address: String,
+}
+
+/// Signs and verifies unsubscribe links. Links never expire.
+pub struct UnsubscribeLinks {
+    signer: LinkSigner,
+    base_url: String,
+}
+
+impl UnsubscribeLinks {
+    pub fn new(signer: LinkSigner, base_url: String) -> Self {
+        Self { signer, base_url: base_url.trim_end_matches('/').to_string() }
+    }
+
+    /// `UNSUBSCRIBE_SECRET` signs links; `PUBLIC_BASE_URL` is where this service is reachable
+    pub fn from_env() -> Self {
+        Self::new(LinkSigner::from_env("UNSUBSCRIBE_SECRET"), public_base_url())
+    }
+
+    /// Public unsubscribe URL for a recipient
+    pub fn url(&self, supplier_id: Uuid, email_address: &str) -> String {
+        format!("{}/api/v1/unsubscribe/{}", self.base_url, self.token(supplier_id, email_address))
+    }
+
+    pub fn token(&self, supplier_id: Uuid, email_address: &str) -> String {
+        self.signer.sign(format!("{}:{}", supplier_id, email_address.to_lowercase()).as_bytes())
+    }
+
+    pub fn verify(&self, token: &str) -> Result<UnsubscribeTarget> {
+        let payload = self.signer.verify(token).context("Invalid unsubscribe token")?;
+        let payload = String::from_utf8(payload).context("Malformed unsubscribe token")?;
+        let (supplier_id, email_address) = payload.split_once(':').context("Malformed unsubscribe token")?;
+
+        Ok(UnsubscribeTarget {
+            supplier_id: supplier_id.parse().context("Malformed unsubscribe token")?,
+            email_address: email_address.to_string(),
+        })
+    }
+}
+
+/// Where this service is reachable from recipients' browsers
+pub fn public_base_url() -> String {
+    std::env::var("PUBLIC_BASE_URL")
+        .unwrap_or_else(|_| "http://localhost:8084".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL};
+
+    #[test]
+    fn test_unsubscribe_token_round_trip() {
+        let links = UnsubscribeLinks::new(LinkSigner::new(b"secret".to_vec()), "https://mail.elementa.io/".to_string());
+        let supplier_id = Uuid::new_v4();
+
+        let url = links.url(supplier_id, "QA@Supplier.example");
+        assert!(url.starts_with("https://mail.elementa.io/api/v1/unsubscribe/"));
+
+        let token = links.token(supplier_id, "QA@Supplier.example");
+        let target = links.verify(&token).unwrap();
+        assert_eq!(target, UnsubscribeTarget { supplier_id, email_address: "qa@supplier.example".to_string() });
+
+        // Tampered payload or another deployment's secret
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = format!("{}.{}", BASE64_URL.encode(format!("{}:other@supplier.example", supplier_id)), signature);
+        assert!(links.verify(&forged).is_err());
+        assert!(UnsubscribeLinks::new(LinkSigner::new(b"other".to_vec()), String::new()).verify(&token).is_err());
+    }
+}
@@ -1,16 +1,21 @@
 //! Email Template Engine
 //! 
 //! Handlebars-based template rendering for compliance emails.
+//! Templates have per-language variants; English is the fallback.
 
 use anyhow::{Context, Result};
 use handlebars::Handlebars;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::branding::TenantBranding;
 
 /// Email template definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmailTemplate {
     pub id: String,
+    /// ISO 639-1 language code
+    pub language: String,
     pub name: String,
     pub description: String,
     pub subject_template: String,
@@ -30,16 +35,21 @@ pub struct TemplateVariable {
 /// Template rendering result
 #[derive(Debug, Clone)]
 pub struct RenderedEmail {
+    /// Language of the variant that was rendered
+    pub language: String,
     pub subject: String,
     pub body_html: String,
-    #[allow(dead_code)]
     pub body_text: String,
 }
 
+/// Language used when no variant matches the requested one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
 /// Template engine
 pub struct TemplateEngine {
     handlebars: Handlebars<'static>,
-    templates: HashMap<String, EmailTemplate>,
+    /// Template ID -> language -> variant
+    templates: HashMap<String, HashMap<String, EmailTemplate>>,
 }
 
 impl TemplateEngine {
@@ -51,6 +61,7 @@ impl TemplateEngine {
         
         // Register built-in templates
         engine.register_builtin_templates();
+        engine.register_translations();
         
         engine
     }
@@ -60,6 +71,7 @@ impl TemplateEngine {
         // Initial outreach template
         let initial_outreach = EmailTemplate {
             id: "initial_outreach".to_string(),
+            language: DEFAULT_LANGUAGE.to_string(),
             name: "Initial Compliance Request".to_string(),
             description: "First contact with supplier requesting PFAS compliance data".to_string(),
             subject_template: "PFAS Compliance Data Request - {{company_name}}".to_string(),
@@ -82,10 +94,11 @@ impl TemplateEngine {
 <li>Material Safety Data Sheets (MSDS/SDS) for relevant materials</li>
 </ol>
 <p>Please respond by {{deadline}} to ensure we meet our regulatory reporting deadlines.</p>
+{{#if upload_url}}<p>You can also upload your documents directly through this secure link: <a href="{{upload_url}}">{{upload_url}}</a></p>{{/if}}
 <p>If you have any questions about this request, please don't hesitate to reach out.</p>
 <p>Best regards,<br>{{sender_name}}<br>{{sender_title}}</p>
 </div>
-<div class="footer">This is an automated message from the Elementa Compliance System. Reference: {{reference_id}}</div>
+<div class="footer">{{#unless branding}}This is an automated message from the Elementa Compliance System. {{/unless}}Reference: {{reference_id}}</div>
 </body>
 </html>
 "#.to_string(),
@@ -105,7 +118,9 @@ Specifically, we need:
 3. Material Safety Data Sheets (MSDS/SDS) for relevant materials
 
 Please respond by {{deadline}} to ensure we meet our regulatory reporting deadlines.
-
+{{#if upload_url}}
+You can also upload your documents directly through this secure link: {{upload_url}}
+{{/if}}
 If you have any questions about this request, please don't hesitate to reach out.
 
 Best regards,
@@ -113,8 +128,8 @@ Best regards,
 {{sender_title}}
 
 ---
-This is an automated message from the Elementa Compliance System.
-Reference: {{reference_id}}
+{{#unless branding}}This is an automated message from the Elementa Compliance System.
+{{/unless}}Reference: {{reference_id}}
 "#.to_string(),
             variables: vec![
                 TemplateVariable { name: "contact_name".to_string(), description: "Supplier contact name".to_string(), required: true, default_value: None },
@@ -127,11 +142,12 @@ Reference: {{reference_id}}
             ],
         };
         
-        self.templates.insert(initial_outreach.id.clone(), initial_outreach);
+        self.insert(initial_outreach);
         
         // Follow-up template
         let follow_up = EmailTemplate {
             id: "follow_up".to_string(),
+            language: DEFAULT_LANGUAGE.to_string(),
             name: "Follow-up Request".to_string(),
             description: "Follow-up email for outstanding compliance data".to_string(),
             subject_template: "Reminder: PFAS Compliance Data Request - {{company_name}}".to_string(),
@@ -144,12 +160,13 @@ Reference: {{reference_id}}
 <p>We have not yet received the requested documentation for the following components:</p>
 <ul>{{#each pending_components}}<li>{{this}}</li>{{/each}}</ul>
 <p>The deadline for submission is <strong>{{deadline}}</strong>. Please prioritize this request to avoid any disruption to our business relationship.</p>
+{{#if upload_url}}<p>You can upload the documents directly through this secure link: <a href="{{upload_url}}">{{upload_url}}</a></p>{{/if}}
 <p>If you need assistance or have questions, please contact us.</p>
 <p>Best regards,<br>{{sender_name}}</p>
 </body>
 </html>
 "#.to_string(),
-            body_text_template: "Dear {{contact_name}},\n\nThis is a friendly reminder regarding our PFAS compliance data request (Reference: {{reference_id}}).\n\nDeadline: {{deadline}}\n\nBest regards,\n{{sender_name}}".to_string(),
+            body_text_template: "Dear {{contact_name}},\n\nThis is a friendly reminder regarding our PFAS compliance data request (Reference: {{reference_id}}).\n\nDeadline: {{deadline}}\n\n{{#if upload_url}}Upload documents: {{upload_url}}\n\n{{/if}}Best regards,\n{{sender_name}}".to_string(),
             variables: vec![
                 TemplateVariable { name: "contact_name".to_string(), description: "Supplier contact name".to_string(), required: true, default_value: None },
                 TemplateVariable { name: "pending_components".to_string(), description: "Components still pending".to_string(), required: true, default_value: None },
@@ -157,40 +174,491 @@ Reference: {{reference_id}}
             ],
         };
         
-        self.templates.insert(follow_up.id.clone(), follow_up);
+        self.insert(follow_up);
+        
+        // Sent to suppliers already contacted when a campaign is cancelled
+        let request_withdrawn = EmailTemplate {
+            id: "request_withdrawn".to_string(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            name: "Request Withdrawn".to_string(),
+            description: "Tells a supplier that an earlier compliance data request no longer needs an answer".to_string(),
+            subject_template: "Withdrawn: PFAS Compliance Data Request ({{reference_id}})".to_string(),
+            body_html_template: r#"
+<!DOCTYPE html>
+<html>
+<body style="font-family:Arial,sans-serif;line-height:1.6;color:#333;">
+<p>Dear {{contact_name}},</p>
+<p>We are withdrawing our earlier request for PFAS compliance data (Reference: {{reference_id}}). No further response is needed, and you will not receive reminders about it.</p>
+{{#if reason}}<p>Reason: {{reason}}</p>{{/if}}
+<p>Thank you for your time, and apologies for any inconvenience.</p>
+<p>Best regards,<br>{{sender_name}}</p>
+</body>
+</html>
+"#.to_string(),
+            body_text_template: "Dear {{contact_name}},\n\nWe are withdrawing our earlier request for PFAS compliance data (Reference: {{reference_id}}). No further response is needed, and you will not receive reminders about it.\n\n{{#if reason}}Reason: {{reason}}\n\n{{/if}}Thank you for your time, and apologies for any inconvenience.\n\nBest regards,\n{{sender_name}}".to_string(),
+            variables: vec![
+                TemplateVariable { name: "contact_name".to_string(), description: "Supplier contact name".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "reference_id".to_string(), description: "Reference of the withdrawn request".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "reason".to_string(), description: "Why the request was withdrawn".to_string(), required: false, default_value: None },
+            ],
+        };
+        
+        self.insert(request_withdrawn);
+        
+        // Sent by renewal campaigns when a supplier's certification is about to lapse
+        let certification_renewal = EmailTemplate {
+            id: "certification_renewal".to_string(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            name: "Certification Renewal".to_string(),
+            description: "Asks a supplier for a renewed certificate before the one on file expires".to_string(),
+            subject_template: "Renewed certificate needed: {{reference_id}}".to_string(),
+            body_html_template: r#"
+<!DOCTYPE html>
+<html>
+<body style="font-family:Arial,sans-serif;line-height:1.6;color:#333;">
+<p>Dear {{contact_name}},</p>
+<p>The certificate we hold on file for you ({{reference_id}}) expires on <strong>{{deadline}}</strong>. To keep your compliance record{{#if company_name}} with {{company_name}}{{/if}} current, please send us the renewed certificate by then.</p>
+{{#if upload_url}}<p>You can upload it directly through this secure link: <a href="{{upload_url}}">{{upload_url}}</a></p>{{/if}}
+<p>If the certificate will not be renewed, please let us know so we can discuss alternatives.</p>
+<p>Best regards,<br>{{sender_name}}</p>
+</body>
+</html>
+"#.to_string(),
+            body_text_template: "Dear {{contact_name}},\n\nThe certificate we hold on file for you ({{reference_id}}) expires on {{deadline}}. To keep your compliance record{{#if company_name}} with {{company_name}}{{/if}} current, please send us the renewed certificate by then.\n\n{{#if upload_url}}Upload the certificate: {{upload_url}}\n\n{{/if}}If the certificate will not be renewed, please let us know so we can discuss alternatives.\n\nBest regards,\n{{sender_name}}".to_string(),
+            variables: vec![
+                TemplateVariable { name: "contact_name".to_string(), description: "Supplier contact name".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "reference_id".to_string(), description: "Certificate the renewal is for".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "deadline".to_string(), description: "Expiry date of the current certificate".to_string(), required: true, default_value: None },
+            ],
+        };
+        
+        self.insert(certification_renewal);
+        
+        // Internal weekly digest for compliance managers
+        let digest = EmailTemplate {
+            id: "compliance_digest".to_string(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            name: "Compliance Digest".to_string(),
+            description: "Weekly summary of outstanding responses, PFAS detections, deadlines and escalations for internal recipients".to_string(),
+            subject_template: "Compliance digest {{period_start}} to {{period_end}}".to_string(),
+            body_html_template: r#"
+<!DOCTYPE html>
+<html>
+<body style="font-family:Arial,sans-serif;line-height:1.6;color:#333;">
+<h2>Compliance digest, {{period_start}} to {{period_end}}</h2>
+<h3>Pending responses</h3>
+{{#if pending_responses}}<ul>{{#each pending_responses}}<li>{{campaign_name}}: {{awaiting}} of {{contacted}} contacted suppliers have not replied (deadline {{deadline}})</li>{{/each}}</ul>{{else}}<p>All contacted suppliers have replied.</p>{{/if}}
+<h3>New PFAS detections</h3>
+{{#if pfas_detections}}<ul>{{#each pfas_detections}}<li>{{chemical_name}} (CAS {{cas_number}}) in component {{component_id}} from supplier {{supplier_id}}, submitted {{submitted_at}}</li>{{/each}}</ul>{{else}}<p>No PFAS found in submissions this period.</p>{{/if}}
+<h3>Upcoming deadlines</h3>
+{{#if upcoming_deadlines}}<ul>{{#each upcoming_deadlines}}<li>{{campaign_name}}: {{deadline}} ({{days_remaining}} days, {{percent_complete}}% complete)</li>{{/each}}</ul>{{else}}<p>No campaign deadlines in the coming weeks.</p>{{/if}}
+<h3>Open escalations</h3>
+{{#if escalations}}<ul>{{#each escalations}}<li><strong>{{severity}}</strong> {{campaign_name}}, supplier {{supplier_id}}: {{reason}}</li>{{/each}}</ul>{{else}}<p>No open escalations.</p>{{/if}}
+</body>
+</html>
+"#.to_string(),
+            body_text_template: r#"Compliance digest, {{period_start}} to {{period_end}}
+
+PENDING RESPONSES
+{{#each pending_responses}}- {{campaign_name}}: {{awaiting}} of {{contacted}} contacted suppliers have not replied (deadline {{deadline}})
+{{else}}All contacted suppliers have replied.
+{{/each}}
+NEW PFAS DETECTIONS
+{{#each pfas_detections}}- {{chemical_name}} (CAS {{cas_number}}) in component {{component_id}} from supplier {{supplier_id}}, submitted {{submitted_at}}
+{{else}}No PFAS found in submissions this period.
+{{/each}}
+UPCOMING DEADLINES
+{{#each upcoming_deadlines}}- {{campaign_name}}: {{deadline}} ({{days_remaining}} days, {{percent_complete}}% complete)
+{{else}}No campaign deadlines in the coming weeks.
+{{/each}}
+OPEN ESCALATIONS
+{{#each escalations}}- [{{severity}}] {{campaign_name}}, supplier {{supplier_id}}: {{reason}}
+{{else}}No open escalations.
+{{/each}}"#.to_string(),
+            variables: vec![
+                TemplateVariable { name: "period_start".to_string(), description: "First day covered".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "period_end".to_string(), description: "Last day covered".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "pending_responses".to_string(), description: "Campaigns awaiting supplier replies".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "pfas_detections".to_string(), description: "PFAS found in recent submissions".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "upcoming_deadlines".to_string(), description: "Campaign deadlines coming up".to_string(), required: true, default_value: None },
+                TemplateVariable { name: "escalations".to_string(), description: "Open escalations".to_string(), required: true, default_value: None },
+            ],
+        };
+        
+        self.insert(digest);
     }
     
-    /// Get template by ID
-    #[allow(dead_code)]
-    pub fn get_template(&self, template_id: &str) -> Option<&EmailTemplate> {
-        self.templates.get(template_id)
+    /// Register German, French and Spanish variants of the built-in templates
+    fn register_translations(&mut self) {
+        let translations = [
+            ("initial_outreach", "de",
+                "Anfrage zu PFAS-Konformitätsdaten - {{company_name}}",
+                "Sehr geehrte/r {{contact_name}},",
+                "im Rahmen unserer Compliance-Maßnahmen zu den PFAS-Meldepflichten nach EPA TSCA (gültig ab 2026) bitten wir Sie um Angaben zur chemischen Zusammensetzung der folgenden Komponenten, die Sie an {{company_name}} liefern:",
+                &["Vollständige CAS-Nummern aller in der Herstellung verwendeten Stoffe",
+                  "Vorhandene PFAS-Prüfberichte oder Zertifikate",
+                  "Sicherheitsdatenblätter (SDB) der betreffenden Materialien"][..],
+                "Bitte antworten Sie bis zum {{deadline}}, damit wir unsere gesetzlichen Meldefristen einhalten können.",
+                "Sie können Ihre Unterlagen auch direkt über diesen sicheren Link hochladen:",
+                "Mit freundlichen Grüßen"),
+            ("initial_outreach", "fr",
+                "Demande de données de conformité PFAS - {{company_name}}",
+                "Madame, Monsieur {{contact_name}},",
+                "Dans le cadre de nos obligations de déclaration PFAS au titre de l'EPA TSCA (en vigueur en 2026), nous vous prions de nous communiquer la composition chimique des composants suivants que vous fournissez à {{company_name}} :",
+                &["La liste complète des numéros CAS de toutes les substances utilisées en fabrication",
+                  "Tout rapport d'essai ou certificat PFAS existant",
+                  "Les fiches de données de sécurité (FDS) des matériaux concernés"][..],
+                "Merci de nous répondre avant le {{deadline}} afin que nous respections nos échéances réglementaires.",
+                "Vous pouvez également déposer vos documents directement via ce lien sécurisé :",
+                "Cordialement"),
+            ("initial_outreach", "es",
+                "Solicitud de datos de cumplimiento PFAS - {{company_name}}",
+                "Estimado/a {{contact_name}}:",
+                "Como parte de nuestras obligaciones de notificación de PFAS según la EPA TSCA (vigente desde 2026), le solicitamos la composición química de los siguientes componentes que suministra a {{company_name}}:",
+                &["Listado completo de números CAS de todas las sustancias utilizadas en la fabricación",
+                  "Informes de ensayo o certificados PFAS existentes",
+                  "Fichas de datos de seguridad (FDS) de los materiales correspondientes"][..],
+                "Le rogamos que responda antes del {{deadline}} para cumplir con nuestros plazos regulatorios.",
+                "También puede subir sus documentos directamente mediante este enlace seguro:",
+                "Atentamente"),
+        ];
+        
+        for (id, language, subject, greeting, intro, requested, deadline, upload, closing) in translations {
+            let Some(base) = self.get_template(id, DEFAULT_LANGUAGE).cloned() else { continue };
+            let items_html: String = requested.iter().map(|r| format!("<li>{}</li>", r)).collect();
+            let items_text: String = requested.iter().enumerate().map(|(i, r)| format!("{}. {}\n", i + 1, r)).collect();
+            
+            self.insert(EmailTemplate {
+                language: language.to_string(),
+                subject_template: subject.to_string(),
+                body_html_template: format!(
+                    "<!DOCTYPE html>\n<html>\n<body style=\"font-family:Arial,sans-serif;line-height:1.6;color:#333;\">\n\
+                     <p>{greeting}</p>\n<p>{intro}</p>\n<ul>{{{{#each components}}}}<li>{{{{this}}}}</li>{{{{/each}}}}</ul>\n\
+                     <ol>{items_html}</ol>\n<p>{deadline}</p>\n\
+                     {{{{#if upload_url}}}}<p>{upload} <a href=\"{{{{upload_url}}}}\">{{{{upload_url}}}}</a></p>\n{{{{/if}}}}\
+                     <p>{closing},<br>{{{{sender_name}}}}<br>{{{{sender_title}}}}</p>\n\
+                     <p style=\"font-size:12px;color:#666;\">Ref: {{{{reference_id}}}}</p>\n</body>\n</html>\n"
+                ),
+                body_text_template: format!(
+                    "{greeting}\n\n{intro}\n\n{{{{#each components}}}}- {{{{this}}}}\n{{{{/each}}}}\n{items_text}\n{deadline}\n\n\
+                     {{{{#if upload_url}}}}{upload} {{{{upload_url}}}}\n\n{{{{/if}}}}\
+                     {closing},\n{{{{sender_name}}}}\n{{{{sender_title}}}}\n\n---\nRef: {{{{reference_id}}}}\n"
+                ),
+                ..base
+            });
+        }
+        
+        let follow_ups = [
+            ("de", "Erinnerung: Anfrage zu PFAS-Konformitätsdaten - {{company_name}}",
+                "Sehr geehrte/r {{contact_name}},",
+                "wir möchten Sie freundlich an unsere Anfrage zu PFAS-Konformitätsdaten erinnern (Referenz: {{reference_id}}). Für folgende Komponenten liegen uns noch keine Unterlagen vor:",
+                "Die Frist endet am {{deadline}}.",
+                "Unterlagen hochladen:",
+                "Mit freundlichen Grüßen"),
+            ("fr", "Rappel : demande de données de conformité PFAS - {{company_name}}",
+                "Madame, Monsieur {{contact_name}},",
+                "Nous nous permettons de vous rappeler notre demande de données de conformité PFAS (référence : {{reference_id}}). Nous n'avons pas encore reçu la documentation pour les composants suivants :",
+                "La date limite est le {{deadline}}.",
+                "Déposer les documents :",
+                "Cordialement"),
+            ("es", "Recordatorio: solicitud de datos de cumplimiento PFAS - {{company_name}}",
+                "Estimado/a {{contact_name}}:",
+                "Le recordamos nuestra solicitud de datos de cumplimiento PFAS (referencia: {{reference_id}}). Aún no hemos recibido la documentación de los siguientes componentes:",
+                "La fecha límite es el {{deadline}}.",
+                "Subir documentos:",
+                "Atentamente"),
+        ];
+        
+        for (language, subject, greeting, reminder, deadline, upload, closing) in follow_ups {
+            let Some(base) = self.get_template("follow_up", DEFAULT_LANGUAGE).cloned() else { continue };
+            
+            self.insert(EmailTemplate {
+                language: language.to_string(),
+                subject_template: subject.to_string(),
+                body_html_template: format!(
+                    "<!DOCTYPE html>\n<html>\n<body style=\"font-family:Arial,sans-serif;line-height:1.6;color:#333;\">\n\
+                     <p>{greeting}</p>\n<p>{reminder}</p>\n\
+                     <ul>{{{{#each pending_components}}}}<li>{{{{this}}}}</li>{{{{/each}}}}</ul>\n\
+                     <p><strong>{deadline}</strong></p>\n\
+                     {{{{#if upload_url}}}}<p>{upload} <a href=\"{{{{upload_url}}}}\">{{{{upload_url}}}}</a></p>\n{{{{/if}}}}\
+                     <p>{closing},<br>{{{{sender_name}}}}</p>\n</body>\n</html>\n"
+                ),
+                body_text_template: format!(
+                    "{greeting}\n\n{reminder}\n\n{{{{#each pending_components}}}}- {{{{this}}}}\n{{{{/each}}}}\n\
+                     {deadline}\n\n{{{{#if upload_url}}}}{upload} {{{{upload_url}}}}\n\n{{{{/if}}}}{closing},\n{{{{sender_name}}}}"
+                ),
+                ..base
+            });
+        }
     }
     
-    /// List all templates
+    fn insert(&mut self, template: EmailTemplate) {
+        self.templates.entry(template.id.clone())
+            .or_default()
+            .insert(template.language.clone(), template);
+    }
+    
+    /// Get template variant, falling back to English.
+    /// Regional codes such as `de-AT` match the base language.
+    pub fn get_template(&self, template_id: &str, language: &str) -> Option<&EmailTemplate> {
+        let variants = self.templates.get(template_id)?;
+        let language = language.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        
+        variants.get(&language).or_else(|| variants.get(DEFAULT_LANGUAGE))
+    }
+    
+    /// List all templates (English variants)
     pub fn list_templates(&self) -> Vec<&EmailTemplate> {
-        self.templates.values().collect()
+        self.templates.keys()
+            .filter_map(|id| self.get_template(id, DEFAULT_LANGUAGE))
+            .collect()
     }
     
-    /// Render template with variables
-    pub fn render(&self, template_id: &str, variables: &HashMap<String, serde_json::Value>) -> Result<RenderedEmail> {
-        let template = self.templates.get(template_id)
+    /// Languages available for a template
+    pub fn languages(&self, template_id: &str) -> Vec<String> {
+        let mut languages: Vec<String> = self.templates.get(template_id)
+            .map(|variants| variants.keys().cloned().collect())
+            .unwrap_or_default();
+        languages.sort();
+        languages
+    }
+    
+    /// Render template in the requested language, falling back to English
+    pub fn render(&self, template_id: &str, language: &str, variables: &HashMap<String, serde_json::Value>) -> Result<RenderedEmail> {
+        let template = self.get_template(template_id, language)
             .context("Template not found")?;
         
         let subject = self.handlebars.render_template(&template.subject_template, variables)
             .context("Failed to render subject")?;
         
-        let body_html = self.handlebars.render_template(&template.body_html_template, variables)
+        let mut body_html = self.handlebars.render_template(&template.body_html_template, variables)
             .context("Failed to render HTML body")?;
         
-        let body_text = self.handlebars.render_template(&template.body_text_template, variables)
+        let mut body_text = self.handlebars.render_template(&template.body_text_template, variables)
             .context("Failed to render text body")?;
         
+        let branding = variables.get(BRANDING_VARIABLE)
+            .and_then(|v| serde_json::from_value::<TenantBranding>(v.clone()).ok());
+        if let Some(header) = branding.as_ref().and_then(|b| b.header_html()) {
+            let start = body_html.find("<body")
+                .and_then(|body| body_html[body..].find('>').map(|end| body + end + 1));
+            match start {
+                Some(start) => body_html.insert_str(start, &format!("\n{}", header.trim_end())),
+                None => body_html.insert_str(0, &header),
+            }
+        }
+        if let Some(header_text) = branding.as_ref().and_then(|b| b.header_text.as_ref()) {
+            body_text.insert_str(0, &format!("{}\n\n", header_text));
+        }
+        
+        if let Some(signature) = variables.get(SIGNATURE_VARIABLE).and_then(|v| v.as_str()) {
+            let lines: Vec<String> = signature.lines().map(handlebars::html_escape).collect();
+            let block = format!("<p class=\"signature\">{}</p>\n", lines.join("<br>"));
+            match body_html.rfind("</body>") {
+                Some(end) => body_html.insert_str(end, &block),
+                None => body_html.push_str(&block),
+            }
+            body_text.push_str(&format!("\n\n-- \n{}\n", signature));
+        }
+        
+        if let Some(branding) = &branding {
+            if let (Some(footer), Some(footer_text)) = (branding.footer_html(), &branding.footer_text) {
+                match body_html.rfind("</body>") {
+                    Some(end) => body_html.insert_str(end, &footer),
+                    None => body_html.push_str(&footer),
+                }
+                body_text.push_str(&format!("\n\n---\n{}\n", footer_text));
+            }
+        }
+        
+        if let Some(url) = variables.get(UNSUBSCRIBE_URL_VARIABLE).and_then(|v| v.as_str()) {
+            let (prompt, action) = unsubscribe_text(&template.language);
+            let footer = format!(
+                "<p style=\"font-size:12px;color:#666;\">{} <a href=\"{}\">{}</a></p>\n",
+                prompt, handlebars::html_escape(url), action
+            );
+            match body_html.rfind("</body>") {
+                Some(end) => body_html.insert_str(end, &footer),
+                None => body_html.push_str(&footer),
+            }
+            body_text.push_str(&format!("\n\n{} {}: {}\n", prompt, action, url));
+        }
+        
         Ok(RenderedEmail {
+            language: template.language.clone(),
             subject,
             body_html,
             body_text,
         })
     }
+    
+    /// Render with the given values, filling anything the template references
+    /// but was not given with a sample value, and report the gaps both ways
+    pub fn preview(&self, template_id: &str, language: &str, provided: &HashMap<String, serde_json::Value>) -> Result<TemplatePreview> {
+        let template = self.get_template(template_id, language)
+            .context("Template not found")?;
+        
+        let mut referenced = BTreeMap::new();
+        for source in [&template.subject_template, &template.body_html_template, &template.body_text_template] {
+            collect_references(source, &mut referenced);
+        }
+        
+        let mut variables = provided.clone();
+        variables.entry(UNSUBSCRIBE_URL_VARIABLE.to_string())
+            .or_insert_with(|| "https://example.com/unsubscribe".into());
+        let mut missing = Vec::new();
+        for (name, iterated) in &referenced {
+            if provided.contains_key(name) {
+                continue;
+            }
+            let declared = template.variables.iter().find(|v| &v.name == name);
+            let sample_value = match declared.and_then(|v| v.default_value.clone()) {
+                Some(default) => serde_json::Value::String(default),
+                None => sample_value(name, *iterated),
+            };
+            variables.insert(name.clone(), sample_value.clone());
+            
+            // Link variables are filled in by the send pipeline
+            if !SYSTEM_VARIABLES.contains(&name.as_str()) {
+                missing.push(MissingVariable {
+                    name: name.clone(),
+                    required: declared.is_some_and(|v| v.required),
+                    sample_value,
+                });
+            }
+        }
+        
+        let mut unused: Vec<String> = provided.keys()
+            .filter(|name| !referenced.contains_key(*name) && !SYSTEM_VARIABLES.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        unused.sort();
+        
+        Ok(TemplatePreview {
+            rendered: self.render(template_id, language, &variables)?,
+            missing,
+            unused,
+        })
+    }
+}
+
+/// Template rendered with sample data
+#[derive(Debug, Clone)]
+pub struct TemplatePreview {
+    pub rendered: RenderedEmail,
+    /// Referenced but not provided, in name order
+    pub missing: Vec<MissingVariable>,
+    /// Provided but never referenced
+    pub unused: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MissingVariable {
+    pub name: String,
+    pub required: bool,
+    pub sample_value: serde_json::Value,
+}
+
+/// Variables supplied at send time rather than by the template author.
+/// `contact_email` addresses the email and is never referenced by templates.
+const SYSTEM_VARIABLES: [&str; 5] = [
+    UNSUBSCRIBE_URL_VARIABLE, UPLOAD_URL_VARIABLE, SIGNATURE_VARIABLE, BRANDING_VARIABLE, "contact_email",
+];
+
+/// Top-level variables a Handlebars template reads, and whether each is
+/// iterated with `#each`. Names inside `#each`/`#with` blocks resolve against
+/// the block's item, so they are not counted.
+fn collect_references(source: &str, found: &mut BTreeMap<String, bool>) {
+    let mut scopes: Vec<bool> = Vec::new();
+    let mut rest = source;
+    
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let expression = rest[start + 2..start + end].trim_matches(|c| c == '{' || c == '}' || c == '~').trim();
+        rest = &rest[start + end + 2..];
+        
+        let mut tokens = expression.split_whitespace();
+        let Some(head) = tokens.next() else { continue };
+        let nested = scopes.iter().any(|item_scope| *item_scope);
+        
+        if let Some(helper) = head.strip_prefix('#') {
+            let iterated = helper == "each";
+            if !nested {
+                for param in tokens {
+                    add_reference(param, iterated, found);
+                }
+            }
+            scopes.push(matches!(helper, "each" | "with"));
+        } else if head.starts_with('/') {
+            scopes.pop();
+        } else if head.starts_with('!') || head == "else" || nested {
+            continue;
+        } else {
+            let mut params: Vec<&str> = tokens.collect();
+            if params.is_empty() {
+                params.push(head);
+            }
+            for param in params {
+                add_reference(param, false, found);
+            }
+        }
+    }
+}
+
+fn add_reference(param: &str, iterated: bool, found: &mut BTreeMap<String, bool>) {
+    let literal = param.starts_with(['"', '\'', '@', '.'])
+        || param.parse::<f64>().is_ok()
+        || matches!(param, "this" | "true" | "false" | "null")
+        || param.starts_with("this.")
+        || param.contains('=');
+    if literal {
+        return;
+    }
+    
+    let name = param.split(['.', '[', '/']).next().unwrap_or(param);
+    if !name.is_empty() {
+        *found.entry(name.to_string()).or_insert(false) |= iterated;
+    }
+}
+
+/// Placeholder for a variable the preview was not given
+fn sample_value(name: &str, iterated: bool) -> serde_json::Value {
+    if iterated {
+        return serde_json::json!([format!("Sample {} 1", name), format!("Sample {} 2", name)]);
+    }
+    
+    let sample = if name.contains("deadline") || name.contains("date") {
+        (chrono::Utc::now() + chrono::Duration::days(30)).format("%Y-%m-%d").to_string()
+    } else if name.contains("email") {
+        "supplier@example.com".to_string()
+    } else if name.ends_with("url") {
+        "https://example.com".to_string()
+    } else {
+        format!("[{}]", name)
+    };
+    serde_json::Value::String(sample)
+}
+
+/// Variable the send pipeline fills with the recipient's unsubscribe link
+pub const UNSUBSCRIBE_URL_VARIABLE: &str = "unsubscribe_url";
+
+/// Variable the send pipeline fills with the sender identity's signature
+pub const SIGNATURE_VARIABLE: &str = "signature";
+
+/// Variable the send pipeline fills with the tenant's `TenantBranding`, when set
+pub const BRANDING_VARIABLE: &str = "branding";
+
+/// Variable holding the supplier's document upload link, when one is issued
+pub const UPLOAD_URL_VARIABLE: &str = "upload_url";
+
+/// Unsubscribe footer wording per language
+fn unsubscribe_text(language: &str) -> (&'static str, &'static str) {
+    match language {
+        "de" => ("Sie möchten keine weiteren E-Mails erhalten?", "Abmelden"),
+        "fr" => ("Vous ne souhaitez plus recevoir ces e-mails ?", "Se désabonner"),
+        "es" => ("¿No desea recibir más correos?", "Darse de baja"),
+        _ => ("Don't want to receive these emails?", "Unsubscribe"),
+    }
 }
 
 impl Default for TemplateEngine {
@@ -198,3 +666,88 @@ impl Default for TemplateEngine {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_render_language_variant_with_fallback() {
+        let engine = TemplateEngine::new();
+        let variables: HashMap<String, serde_json::Value> = [
+            ("contact_name", "Anna"),
+            ("company_name", "Acme"),
+            ("deadline", "2026-12-01"),
+        ].into_iter().map(|(k, v)| (k.to_string(), serde_json::Value::String(v.to_string()))).collect();
+        
+        let german = engine.render("initial_outreach", "de-AT", &variables).unwrap();
+        assert_eq!(german.language, "de");
+        assert!(german.subject.starts_with("Anfrage zu PFAS"));
+        assert!(german.body_text.contains("2026-12-01"));
+        
+        let fallback = engine.render("follow_up", "ja", &variables).unwrap();
+        assert_eq!(fallback.language, "en");
+        assert!(fallback.subject.starts_with("Reminder"));
+        
+        assert_eq!(engine.languages("initial_outreach"), vec!["de", "en", "es", "fr"]);
+        
+        let mut variables = variables;
+        variables.insert(UNSUBSCRIBE_URL_VARIABLE.to_string(), "https://mail.elementa.io/u/abc".into());
+        let german = engine.render("follow_up", "de", &variables).unwrap();
+        assert!(german.body_html.contains("<a href=\"https://mail.elementa.io/u/abc\">Abmelden</a></p>\n</body>"));
+        assert!(german.body_text.ends_with("Abmelden: https://mail.elementa.io/u/abc\n"));
+        assert!(!german.body_text.contains("hochladen"));
+        
+        variables.insert(UPLOAD_URL_VARIABLE.to_string(), "https://mail.elementa.io/up/xyz".into());
+        let german = engine.render("initial_outreach", "de", &variables).unwrap();
+        assert!(german.body_html.contains("<a href=\"https://mail.elementa.io/up/xyz\">"));
+        assert!(german.body_text.contains("hochladen: https://mail.elementa.io/up/xyz"));
+    }
+    
+    #[test]
+    fn test_branding_replaces_elementa_footer() {
+        let engine = TemplateEngine::new();
+        let mut variables: HashMap<String, serde_json::Value> = [
+            ("contact_name", "Anna"),
+            ("reference_id", "REF-1"),
+            (UNSUBSCRIBE_URL_VARIABLE, "https://mail.elementa.io/u/abc"),
+        ].into_iter().map(|(k, v)| (k.to_string(), serde_json::Value::String(v.to_string()))).collect();
+        
+        let generic = engine.render("initial_outreach", "en", &variables).unwrap();
+        assert!(generic.body_html.contains("Elementa Compliance System"));
+        
+        variables.insert(BRANDING_VARIABLE.to_string(), serde_json::json!({
+            "logo_url": "https://cdn.acme.example/logo.png",
+            "footer_text": "Acme Corp, 1 Main St",
+        }));
+        let branded = engine.render("initial_outreach", "en", &variables).unwrap();
+        assert!(!branded.body_html.contains("Elementa") && !branded.body_text.contains("Elementa"));
+        assert!(branded.body_html.contains("Reference: REF-1"));
+        assert!(branded.body_html.contains("<body>\n<div class=\"brand-header\""));
+        let footer = branded.body_html.find("Acme Corp, 1 Main St").unwrap();
+        assert!(footer < branded.body_html.find("Unsubscribe").unwrap());
+        assert!(branded.body_text.contains("---\nAcme Corp, 1 Main St\n"));
+    }
+    
+    #[test]
+    fn test_preview_reports_missing_and_unused_variables() {
+        let engine = TemplateEngine::new();
+        let provided: HashMap<String, serde_json::Value> = [
+            ("contact_name", serde_json::json!("Anna")),
+            ("components", serde_json::json!(["Gasket G-12"])),
+            ("contact_email", serde_json::json!("anna@supplier.example")),
+            ("po_number", serde_json::json!("PO-1")),
+        ].into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        
+        let preview = engine.preview("initial_outreach", "en", &provided).unwrap();
+        let missing: Vec<&str> = preview.missing.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(missing, vec!["company_name", "deadline", "reference_id", "sender_name", "sender_title"]);
+        assert!(preview.missing.iter().all(|m| m.required != (m.name == "reference_id")));
+        assert_eq!(preview.missing[2].sample_value, serde_json::json!("AUTO"));
+        assert_eq!(preview.unused, vec!["po_number"]);
+        
+        assert!(preview.rendered.body_html.contains("<li>Gasket G-12</li>"));
+        assert!(preview.rendered.subject.contains("[company_name]"));
+        assert!(preview.rendered.body_html.contains("https://example.com"));
+    }
+}
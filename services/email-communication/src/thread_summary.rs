@@ -0,0 +1,266 @@
+//! Thread Summaries
+//!
+//! Condenses a long back-and-forth with a supplier into the commitments
+//! made, the items still outstanding and the supplier's last statement.
+//! The model sits behind `SummaryModel` so summaries can be disabled or
+//! served by a different backend.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::classifier::{strip_quoted, ChatResponse, LlmConfig};
+
+/// Transcript budget; the oldest messages are left out beyond it
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+
+/// Summaries are switched off
+#[derive(Debug)]
+pub struct SummariesDisabled;
+
+impl std::fmt::Display for SummariesDisabled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Thread summaries are disabled")
+    }
+}
+
+impl std::error::Error for SummariesDisabled {}
+
+/// One email of a thread, as the model sees it
+#[derive(Debug, Clone)]
+pub struct ThreadMessage {
+    pub from_supplier: bool,
+    pub at: DateTime<Utc>,
+    pub subject: String,
+    /// Plain text without quoted history
+    pub text: String,
+}
+
+impl ThreadMessage {
+    /// Reduce a stored body (HTML for outbound emails) to the message's own text
+    pub fn new(from_supplier: bool, at: DateTime<Utc>, subject: &str, body: &str) -> Self {
+        let text = if from_supplier { body.to_string() } else { html_to_text(body) };
+        Self {
+            from_supplier,
+            at,
+            subject: subject.to_string(),
+            text: strip_quoted(&text).trim().to_string(),
+        }
+    }
+}
+
+/// What the model returns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadSummary {
+    pub summary: String,
+    /// Promises made by either side, with dates where given
+    #[serde(default)]
+    pub commitments: Vec<String>,
+    /// Requested data or questions not yet answered
+    #[serde(default)]
+    pub outstanding_items: Vec<String>,
+}
+
+#[async_trait]
+pub trait SummaryModel: Send + Sync {
+    /// Summarize a transcript built by `transcript`
+    async fn summarize(&self, transcript: &str) -> Result<ThreadSummary>;
+
+    /// Reported alongside the summary
+    fn name(&self) -> &str;
+}
+
+/// Any OpenAI-compatible chat completions endpoint
+pub struct ChatSummaryModel {
+    client: Client,
+    llm: LlmConfig,
+}
+
+impl ChatSummaryModel {
+    pub fn new(llm: LlmConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, llm }
+    }
+
+    /// Uses the classifier's LLM settings, with `SUMMARY_LLM_MODEL` overriding
+    /// the model. `None` when no API key is configured or `SUMMARY_LLM_ENABLED=false`.
+    pub fn from_env() -> Option<Self> {
+        if std::env::var("SUMMARY_LLM_ENABLED").is_ok_and(|v| v.eq_ignore_ascii_case("false")) {
+            return None;
+        }
+
+        let mut llm = LlmConfig::from_env()?;
+        if let Ok(model) = std::env::var("SUMMARY_LLM_MODEL") {
+            llm.model = model;
+        }
+        Some(Self::new(llm))
+    }
+}
+
+#[async_trait]
+impl SummaryModel for ChatSummaryModel {
+    async fn summarize(&self, transcript: &str) -> Result<ThreadSummary> {
+        let request = serde_json::json!({
+            "model": self.llm.model,
+            "temperature": 0.0,
+            "response_format": { "type": "json_object" },
+            "messages": [
+                { "role": "system", "content": SUMMARY_PROMPT },
+                { "role": "user", "content": transcript },
+            ],
+        });
+
+        let response: ChatResponse = self.client
+            .post(&self.llm.api_url)
+            .bearer_auth(&self.llm.api_key)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to reach LLM API")?
+            .error_for_status()
+            .context("LLM API error")?
+            .json()
+            .await
+            .context("Invalid LLM response")?;
+
+        let content = response.choices.first()
+            .map(|c| c.message.content.as_str())
+            .context("No response content")?;
+        serde_json::from_str(content).context("Failed to parse summary JSON")
+    }
+
+    fn name(&self) -> &str {
+        &self.llm.model
+    }
+}
+
+/// Thread in chronological order as plain text. When it is over budget the
+/// oldest messages are dropped, and the newest is cut short if it alone is.
+pub fn transcript(messages: &[ThreadMessage]) -> String {
+    let entries: Vec<String> = messages.iter()
+        .map(|m| format!(
+            "[{}] {}\nSubject: {}\n\n{}\n",
+            m.at.format("%Y-%m-%d %H:%M UTC"),
+            if m.from_supplier { "Supplier" } else { "Us" },
+            m.subject,
+            m.text,
+        ))
+        .collect();
+
+    let mut used = 0;
+    let kept = entries.iter().rev()
+        .take_while(|entry| {
+            used += entry.len();
+            used <= MAX_TRANSCRIPT_CHARS
+        })
+        .count();
+    let omitted = entries.len().saturating_sub(kept.max(1));
+
+    let mut transcript = String::new();
+    if omitted > 0 {
+        transcript.push_str(&format!("({} earlier messages omitted)\n\n", omitted));
+    }
+    if kept == 0 {
+        if let Some(last) = entries.last() {
+            let end = (0..=MAX_TRANSCRIPT_CHARS).rev().find(|i| last.is_char_boundary(*i)).unwrap_or(0);
+            transcript.push_str(&last[..end]);
+        }
+        return transcript;
+    }
+    transcript.push_str(&entries[omitted..].join("\n"));
+    transcript
+}
+
+/// The supplier's most recent words, without quoted history
+pub fn last_supplier_statement(messages: &[ThreadMessage]) -> Option<&ThreadMessage> {
+    messages.iter().rev().find(|m| m.from_supplier && !m.text.is_empty())
+}
+
+/// Good enough for our own rendered templates: tags dropped, block tags as line breaks
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/').to_lowercase();
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default();
+        if matches!(name, "br" | "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+const SUMMARY_PROMPT: &str = r#"You summarize email threads between a compliance team ("Us") and a supplier about chemical compliance data requests (PFAS, REACH, RoHS).
+Respond with JSON: {"summary": <2-3 sentences>, "commitments": [<string>], "outstanding_items": [<string>]}
+- "commitments": concrete promises by either side, with any dates given
+- "outstanding_items": requested documents, data or answers not yet provided
+Only use facts stated in the thread. Use empty lists when there are none."#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_transcript_keeps_newest_messages() {
+        let request = ThreadMessage::new(
+            false,
+            at("2026-03-01T09:00:00Z"),
+            "PFAS declaration",
+            "<p>Dear supplier,</p><p>Please send the SDS &amp; a PFAS declaration.<br>Thanks</p>",
+        );
+        assert_eq!(request.text, "Dear supplier,\n\nPlease send the SDS & a PFAS declaration.\nThanks");
+
+        let reply = ThreadMessage::new(
+            true,
+            at("2026-03-02T14:30:00Z"),
+            "Re: PFAS declaration",
+            "We will send the SDS by Friday.\n\nOn Mon, 2 Mar 2026, compliance@acme.example wrote:\n> Please send the SDS",
+        );
+        assert_eq!(reply.text, "We will send the SDS by Friday.");
+        assert_eq!(last_supplier_statement(&[reply.clone(), request.clone()]).unwrap().text, reply.text);
+
+        let full = transcript(&[request.clone(), reply.clone()]);
+        assert!(full.starts_with("[2026-03-01 09:00 UTC] Us\nSubject: PFAS declaration"));
+        assert!(full.contains("[2026-03-02 14:30 UTC] Supplier\nSubject: Re: PFAS declaration\n\nWe will send the SDS by Friday."));
+
+        let mut long = request.clone();
+        long.text = "x".repeat(MAX_TRANSCRIPT_CHARS);
+        let trimmed = transcript(&[long.clone(), reply.clone()]);
+        assert!(trimmed.starts_with("(1 earlier messages omitted)"));
+        assert!(trimmed.contains("by Friday"));
+        assert!(transcript(&[reply, long]).len() < MAX_TRANSCRIPT_CHARS + 100);
+    }
+}
@@ -0,0 +1,94 @@
+//! Upload Links
+//!
+//! Signed, expiring links that let a supplier upload documents without an
+//! account. The token carries the supplier, campaign and thread, so uploads
+//! land on the right correspondence without the supplier identifying itself.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::link_signing::LinkSigner;
+use crate::suppressions::public_base_url;
+
+/// What an upload link grants access to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UploadTarget {
+    pub supplier_id: Uuid,
+    pub campaign_id: Option<Uuid>,
+    pub thread_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct UploadLinks {
+    signer: LinkSigner,
+    base_url: String,
+    ttl: Duration,
+}
+
+impl UploadLinks {
+    pub fn new(signer: LinkSigner, base_url: String, ttl: Duration) -> Self {
+        Self { signer, base_url: base_url.trim_end_matches('/').to_string(), ttl }
+    }
+
+    /// `UPLOAD_LINK_SECRET` signs links, which stay valid for `UPLOAD_LINK_TTL_DAYS` (default 30)
+    pub fn from_env() -> Self {
+        let ttl_days = std::env::var("UPLOAD_LINK_TTL_DAYS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+
+        Self::new(LinkSigner::from_env("UPLOAD_LINK_SECRET"), public_base_url(), Duration::days(ttl_days))
+    }
+
+    /// Upload URL for a thread, valid from `now` for the configured lifetime
+    pub fn url(&self, supplier_id: Uuid, campaign_id: Option<Uuid>, thread_id: &str, now: DateTime<Utc>) -> String {
+        let target = UploadTarget {
+            supplier_id,
+            campaign_id,
+            thread_id: thread_id.to_string(),
+            expires_at: now + self.ttl,
+        };
+        format!("{}/api/v1/uploads/{}", self.base_url, self.token(&target))
+    }
+
+    pub fn token(&self, target: &UploadTarget) -> String {
+        self.signer.sign(&serde_json::to_vec(target).expect("upload target serializes"))
+    }
+
+    /// Target of a token that is genuine and has not expired at `now`
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<UploadTarget> {
+        let payload = self.signer.verify(token).context("Invalid upload link")?;
+        let target: UploadTarget = serde_json::from_slice(&payload).context("Malformed upload link")?;
+        if target.expires_at <= now {
+            bail!("Upload link expired on {}", target.expires_at.to_rfc3339());
+        }
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_link_expires() {
+        let links = UploadLinks::new(LinkSigner::new(b"secret".to_vec()), "https://mail.elementa.io".to_string(), Duration::days(7));
+        let now = Utc::now();
+        let target = UploadTarget {
+            supplier_id: Uuid::new_v4(),
+            campaign_id: Some(Uuid::new_v4()),
+            thread_id: "thread_1".to_string(),
+            expires_at: now + Duration::days(7),
+        };
+
+        let token = links.token(&target);
+        assert_eq!(links.verify(&token, now).unwrap(), target);
+        assert!(links.verify(&token, now + Duration::days(8)).is_err());
+
+        let url = links.url(target.supplier_id, None, "thread_1", now);
+        let token = url.rsplit('/').next().unwrap();
+        assert_eq!(links.verify(token, now).unwrap().campaign_id, None);
+        assert!(UploadLinks::new(LinkSigner::new(b"other".to_vec()), String::new(), Duration::days(7)).verify(token, now).is_err());
+    }
+}
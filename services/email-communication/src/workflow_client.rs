@@ -0,0 +1,109 @@
+//! Workflow Client
+//!
+//! Notifies the workflow-orchestration service about sent and bounced
+//! emails, supplier replies, extracted documents, opt-outs and unanswered
+//! follow-ups so it can track progress and advance, reschedule, escalate or
+//! hand off campaign tasks, and reads campaign progress back for the
+//! compliance digest. Notifications are published as `DomainEvent`s in an
+//! `EventEnvelope`.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+use elementa_models::{DomainEvent, EventEnvelope};
+
+/// Name this service publishes events under
+const EVENT_SOURCE: &str = "email-communication";
+
+/// Campaign as reported by the workflow service
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowSummary {
+    pub id: Uuid,
+    pub campaign_name: String,
+    pub status: String,
+    /// RFC 3339
+    pub deadline: String,
+    pub progress: WorkflowProgress,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowProgress {
+    pub contacted: usize,
+    pub responded: usize,
+    pub percent_complete: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EscalationSummary {
+    pub workflow_id: Uuid,
+    pub supplier_id: Uuid,
+    pub reason: String,
+    pub severity: String,
+    /// RFC 3339
+    pub created_at: String,
+    pub resolved: bool,
+}
+
+/// Client for the workflow-orchestration service
+pub struct WorkflowClient {
+    client: Client,
+    base_url: String,
+}
+
+impl WorkflowClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, base_url }
+    }
+
+    /// Publish an event that starts a new chain, for the tenant when known
+    pub async fn publish(&self, event: DomainEvent, tenant: Option<String>) -> Result<()> {
+        let envelope = EventEnvelope::new(EVENT_SOURCE, event).with_tenant(tenant);
+        self.client
+            .post(format!("{}/api/v1/events", self.base_url))
+            .json(&envelope)
+            .send()
+            .await
+            .context("Failed to reach workflow service")?
+            .error_for_status()
+            .with_context(|| format!("Workflow service rejected {} event", envelope.event_type()))?;
+
+        Ok(())
+    }
+
+    pub async fn list_workflows(&self) -> Result<Vec<WorkflowSummary>> {
+        self.get("workflows").await
+    }
+
+    pub async fn list_escalations(&self) -> Result<Vec<EscalationSummary>> {
+        self.get("escalations").await
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.client
+            .get(format!("{}/api/v1/{}", self.base_url, path))
+            .send()
+            .await
+            .context("Failed to reach workflow service")?
+            .error_for_status()
+            .with_context(|| format!("Workflow service rejected {} request", path))?
+            .json()
+            .await
+            .with_context(|| format!("Invalid {} response", path))
+    }
+}
+
+impl Default for WorkflowClient {
+    fn default() -> Self {
+        Self::new(
+            std::env::var("WORKFLOW_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8085".to_string()),
+        )
+    }
+}
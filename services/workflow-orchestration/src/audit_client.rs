@@ -0,0 +1,59 @@
+//! Audit Client
+//!
+//! Records workflow, task and escalation state changes, and operator
+//! actions such as requeues, in the audit-trail service so every change to
+//! a campaign can be traced.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Subset of the audit service's create request used by workflows
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub agent_id: Option<String>,
+    pub details: serde_json::Value,
+}
+
+/// Client for the audit-trail service
+pub struct AuditClient {
+    client: Client,
+    base_url: String,
+}
+
+impl AuditClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, base_url }
+    }
+
+    pub async fn record(&self, record: &AuditRecord) -> Result<()> {
+        self.client
+            .post(format!("{}/api/v1/audit", self.base_url))
+            .json(record)
+            .send()
+            .await
+            .context("Failed to reach audit service")?
+            .error_for_status()
+            .context("Audit service rejected the entry")?;
+
+        Ok(())
+    }
+}
+
+impl Default for AuditClient {
+    fn default() -> Self {
+        Self::new(
+            std::env::var("AUDIT_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8086".to_string()),
+        )
+    }
+}
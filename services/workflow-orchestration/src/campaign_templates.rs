@@ -0,0 +1,208 @@
+//! Campaign Templates
+//!
+//! Reusable campaign definitions: workflow config (email templates,
+//! follow-up cadence, escalation rules) plus how long suppliers get to
+//! respond, so a recurring campaign is launched with just its suppliers.
+//! A template can also carry a recurrence that launches each cycle on a
+//! cron schedule.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::phases::validate_phases;
+use crate::recurrence::CronSchedule;
+use crate::WorkflowConfig;
+
+/// Built-in quarterly PFAS reporting campaign
+pub const TSCA_PFAS_QUARTERLY: &str = "tsca_pfas_quarterly";
+
+/// Built-in campaign asking a supplier to renew a lapsing certification
+pub const CERTIFICATION_RENEWAL: &str = "certification_renewal";
+
+#[derive(Debug, Clone)]
+pub struct CampaignTemplate {
+    /// Slug, e.g. `tsca_pfas_quarterly`
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub config: WorkflowConfig,
+    /// Days from launch to the response deadline
+    pub response_days: i32,
+    pub built_in: bool,
+    pub updated_at: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+}
+
+/// When, and for whom, a template launches its next cycle
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    pub schedule: CronSchedule,
+    pub client_id: Uuid,
+    pub supplier_ids: Vec<Uuid>,
+    /// Launch cycles as pending campaigns that wait for someone to activate them
+    pub require_confirmation: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_workflow_id: Option<Uuid>,
+}
+
+impl CampaignTemplate {
+    fn validate(&self) -> Result<()> {
+        if self.id.is_empty() || !self.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+            bail!("Template ID must be lowercase letters, digits, '_' or '-'");
+        }
+        if self.name.trim().is_empty() {
+            bail!("Template name is required");
+        }
+        if self.response_days < 1 {
+            bail!("response_days must be at least 1");
+        }
+        validate_config(&self.config)
+    }
+}
+
+/// Cadence and escalation settings that would leave a campaign unable to run
+pub fn validate_config(config: &WorkflowConfig) -> Result<()> {
+    if config.max_follow_ups < 0 {
+        bail!("max_follow_ups cannot be negative");
+    }
+    if config.max_follow_ups > 0 && config.follow_up_interval_days < 1 {
+        bail!("follow_up_interval_days must be at least 1");
+    }
+    if config.escalation_threshold_days < 1 || config.deadline_warning_days < 0 {
+        bail!("Escalation thresholds must be positive");
+    }
+    if config.outreach_template_id.trim().is_empty() || config.follow_up_template_id.trim().is_empty() {
+        bail!("Email template IDs are required");
+    }
+    validate_phases(&config.phases)
+}
+
+pub struct CampaignTemplateRegistry {
+    templates: RwLock<HashMap<String, CampaignTemplate>>,
+}
+
+impl CampaignTemplateRegistry {
+    pub fn new() -> Self {
+        let quarterly = CampaignTemplate {
+            id: TSCA_PFAS_QUARTERLY.to_string(),
+            name: "Quarterly TSCA PFAS outreach".to_string(),
+            description: Some("PFAS composition request for TSCA section 8(a)(7) reporting".to_string()),
+            config: WorkflowConfig {
+                max_follow_ups: 3,
+                follow_up_interval_days: 10,
+                auto_escalate: true,
+                escalation_threshold_days: 30,
+                deadline_warning_days: 10,
+                ..WorkflowConfig::default()
+            },
+            response_days: 45,
+            built_in: true,
+            updated_at: Utc::now(),
+            recurrence: None,
+        };
+
+        let renewal = CampaignTemplate {
+            id: CERTIFICATION_RENEWAL.to_string(),
+            name: "Certification renewal".to_string(),
+            description: Some("Request for a renewed certificate before the current one lapses".to_string()),
+            config: WorkflowConfig {
+                max_follow_ups: 2,
+                follow_up_interval_days: 14,
+                auto_escalate: true,
+                escalation_threshold_days: 30,
+                deadline_warning_days: 14,
+                outreach_template_id: CERTIFICATION_RENEWAL.to_string(),
+                follow_up_template_id: CERTIFICATION_RENEWAL.to_string(),
+                ..WorkflowConfig::default()
+            },
+            response_days: 30,
+            built_in: true,
+            updated_at: Utc::now(),
+            recurrence: None,
+        };
+
+        Self {
+            templates: RwLock::new(HashMap::from([
+                (quarterly.id.clone(), quarterly),
+                (renewal.id.clone(), renewal),
+            ])),
+        }
+    }
+
+    /// Create or replace a template; built-in templates can be overridden.
+    /// A replaced template keeps its recurrence unless the new one has its own.
+    pub async fn upsert(&self, mut template: CampaignTemplate) -> Result<CampaignTemplate> {
+        template.validate()?;
+        template.built_in = false;
+        template.updated_at = Utc::now();
+        let mut templates = self.templates.write().await;
+        if template.recurrence.is_none() {
+            template.recurrence = templates.get(&template.id).and_then(|t| t.recurrence.clone());
+        }
+        templates.insert(template.id.clone(), template.clone());
+        Ok(template)
+    }
+
+    /// Set or clear a template's recurrence; `None` if the template does not exist
+    pub async fn set_recurrence(&self, id: &str, recurrence: Option<Recurrence>) -> Option<CampaignTemplate> {
+        let mut templates = self.templates.write().await;
+        let template = templates.get_mut(id)?;
+        template.recurrence = recurrence;
+        Some(template.clone())
+    }
+
+    /// Templates whose next cycle is due at `now`, as they were when it fell
+    /// due. Each recurrence moves on to its following run before this
+    /// returns, so a cycle is handed out once.
+    pub async fn take_due(&self, now: DateTime<Utc>) -> Vec<CampaignTemplate> {
+        let mut due = Vec::new();
+        for template in self.templates.write().await.values_mut() {
+            let Some(next) = template.recurrence.as_ref()
+                .filter(|r| r.next_run_at <= now)
+                .map(|r| r.schedule.next_after(now))
+            else {
+                continue;
+            };
+            due.push(template.clone());
+            match (next, template.recurrence.as_mut()) {
+                (Some(next_run_at), Some(recurrence)) => recurrence.next_run_at = next_run_at,
+                _ => {
+                    warn!("Recurrence of template {} has no further runs", template.id);
+                    template.recurrence = None;
+                }
+            }
+        }
+        due
+    }
+
+    /// Note the campaign launched for a template's latest cycle
+    pub async fn record_launch(&self, id: &str, workflow_id: Uuid) {
+        if let Some(recurrence) = self.templates.write().await.get_mut(id).and_then(|t| t.recurrence.as_mut()) {
+            recurrence.last_workflow_id = Some(workflow_id);
+        }
+    }
+
+    pub async fn get(&self, id: &str) -> Option<CampaignTemplate> {
+        self.templates.read().await.get(id).cloned()
+    }
+
+    pub async fn list(&self) -> Vec<CampaignTemplate> {
+        let mut templates: Vec<CampaignTemplate> = self.templates.read().await.values().cloned().collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+
+    pub async fn delete(&self, id: &str) -> bool {
+        self.templates.write().await.remove(id).is_some()
+    }
+}
+
+impl Default for CampaignTemplateRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,47 @@
+//! Certification Job
+//!
+//! Periodically looks for supplier certifications that have lapsed or are
+//! about to, and launches renewal outreach for them before the supplier's
+//! compliance evidence runs out.
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::service::WorkflowService;
+
+/// How often expiring certifications are looked for, `CERTIFICATION_CHECK_SECS` (default six hours)
+fn check_interval() -> std::time::Duration {
+    let secs = std::env::var("CERTIFICATION_CHECK_SECS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(6 * 3600);
+    std::time::Duration::from_secs(secs)
+}
+
+/// Days before expiry renewal outreach starts, `CERTIFICATION_RENEWAL_DAYS`
+/// (default `CERTIFICATION_EXPIRY_WARNING_DAYS`)
+pub fn renewal_days() -> i64 {
+    std::env::var("CERTIFICATION_RENEWAL_DAYS").ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(elementa_models::CERTIFICATION_EXPIRY_WARNING_DAYS)
+}
+
+pub fn spawn_certification_job(service: WorkflowService) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = check_interval();
+        let days = renewal_days();
+        info!("Certification expiry checks every {:?}, {} days ahead", interval, days);
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            match service.run_certification_checks(Utc::now(), days).await {
+                Ok(0) => {}
+                Ok(launched) => info!("Launched renewal outreach for {} certifications", launched),
+                Err(e) => error!("Certification expiry check failed: {:#}", e),
+            }
+        }
+    })
+}
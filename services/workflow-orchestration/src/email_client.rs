@@ -0,0 +1,128 @@
+//! Email Client
+//!
+//! Sends outreach and follow-up emails through the email-communication
+//! service on behalf of scheduled tasks, and reads its suppression list so
+//! suppliers that opted out are not scheduled for outreach.
+
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The email service refused the send for a reason retrying will not fix,
+/// e.g. a suppressed or undeliverable recipient
+#[derive(Debug)]
+pub struct SendRejected {
+    pub status: u16,
+    pub message: String,
+}
+
+impl std::fmt::Display for SendRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Email service rejected the send ({}): {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for SendRejected {}
+
+/// Subset of the email service's send request used by tasks
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskEmail {
+    pub supplier_id: Uuid,
+    pub template_id: String,
+    /// Continue an earlier thread so follow-ups arrive as replies
+    pub thread_id: Option<String>,
+    pub campaign_id: Option<Uuid>,
+    pub calendar_deadline: Option<String>,
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SentEmail {
+    pub email_id: Uuid,
+    pub thread_id: String,
+    pub recipient: String,
+}
+
+/// Do-not-contact entry from the email service's suppression list
+#[derive(Debug, Clone, Deserialize)]
+pub struct Suppression {
+    pub supplier_id: Option<Uuid>,
+    /// Lowercased email address
+    pub email_address: Option<String>,
+    pub reason: String,
+}
+
+impl Suppression {
+    /// Whether the entry covers a supplier reached at `email_address`
+    pub fn covers(&self, supplier_id: Uuid, email_address: Option<&str>) -> bool {
+        match (&self.email_address, self.supplier_id) {
+            (Some(suppressed), _) => email_address.is_some_and(|address| address.trim().eq_ignore_ascii_case(suppressed)),
+            (None, Some(suppressed)) => suppressed == supplier_id,
+            (None, None) => false,
+        }
+    }
+}
+
+/// Client for the email-communication service
+pub struct EmailClient {
+    client: Client,
+    base_url: String,
+}
+
+impl EmailClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, base_url }
+    }
+
+    pub async fn send(&self, email: &TaskEmail) -> Result<SentEmail> {
+        let response = self.client
+            .post(format!("{}/api/v1/emails/send", self.base_url))
+            .json(email)
+            .send()
+            .await
+            .context("Failed to reach email service")?;
+
+        let status = response.status();
+        // Throttling is worth waiting out; other client errors are final
+        if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+            let message = response.text().await.unwrap_or_default();
+            return Err(SendRejected { status: status.as_u16(), message }.into());
+        }
+
+        response
+            .error_for_status()
+            .context("Email service failed to send")?
+            .json()
+            .await
+            .context("Invalid send response")
+    }
+
+    pub async fn suppressions(&self) -> Result<Vec<Suppression>> {
+        self.client
+            .get(format!("{}/api/v1/suppressions", self.base_url))
+            .send()
+            .await
+            .context("Failed to reach email service")?
+            .error_for_status()
+            .context("Email service failed to list suppressions")?
+            .json()
+            .await
+            .context("Invalid suppression list")
+    }
+}
+
+impl Default for EmailClient {
+    fn default() -> Self {
+        Self::new(
+            std::env::var("EMAIL_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8084".to_string()),
+        )
+    }
+}
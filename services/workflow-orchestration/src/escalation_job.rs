@@ -0,0 +1,39 @@
+//! Escalation Job
+//!
+//! Periodically escalates suppliers that have gone silent past their
+//! workflow's threshold or are still silent close to the deadline.
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::service::WorkflowService;
+
+/// How often the checks run, `ESCALATION_CHECK_SECS` (default one hour)
+fn check_interval() -> std::time::Duration {
+    let secs = std::env::var("ESCALATION_CHECK_SECS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+    std::time::Duration::from_secs(secs)
+}
+
+pub fn spawn_escalation_job(service: WorkflowService) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = check_interval();
+        info!("Escalation checks every {:?}", interval);
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            match service.run_escalation_checks(Utc::now()).await {
+                Ok(sweep) if sweep.escalations_created + sweep.escalations_raised > 0 => info!(
+                    "Escalation check created {} and raised {} escalations",
+                    sweep.escalations_created, sweep.escalations_raised,
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Escalation check failed: {:#}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,121 @@
+//! Escalation Routing
+//!
+//! Assigns new escalations an owner and a due date. Rules match on
+//! severity, client and supplier relationship; the most specific rule that
+//! matches wins, earlier rules breaking ties. Escalations no rule matches
+//! stay unassigned and are due by their severity's default.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use elementa_models::SupplierRelationship;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct RoutingRule {
+    pub id: Uuid,
+    /// Criteria left empty match anything
+    pub severity: Option<String>,
+    pub client_id: Option<Uuid>,
+    pub relationship: Option<SupplierRelationship>,
+    pub assignee: String,
+    /// Overrides the severity's default due time
+    pub due_hours: Option<i64>,
+}
+
+impl RoutingRule {
+    /// Number of criteria matched, or `None` if any criterion does not match
+    fn specificity(&self, escalation: &RoutingContext) -> Option<usize> {
+        let severity = self.severity.as_ref().map(|s| s.eq_ignore_ascii_case(escalation.severity));
+        let client = self.client_id.map(|id| escalation.client_id == Some(id));
+        let relationship = self.relationship.as_ref().map(|r| escalation.relationship == Some(r));
+
+        let criteria = [severity, client, relationship];
+        if criteria.contains(&Some(false)) {
+            return None;
+        }
+        Some(criteria.iter().flatten().count())
+    }
+}
+
+/// What is known about an escalation when it is raised
+#[derive(Debug, Clone)]
+pub struct RoutingContext<'a> {
+    pub severity: &'a str,
+    pub client_id: Option<Uuid>,
+    /// `None` without a database or for unknown suppliers
+    pub relationship: Option<&'a SupplierRelationship>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub assignee: Option<String>,
+    pub due_at: DateTime<Utc>,
+}
+
+/// Hours an escalation of the given severity may stay unresolved
+pub fn default_due_hours(severity: &str) -> i64 {
+    match severity.to_lowercase().as_str() {
+        "critical" => 4,
+        "high" => 24,
+        "medium" => 72,
+        _ => 168,
+    }
+}
+
+pub struct EscalationRouter {
+    rules: RwLock<Vec<RoutingRule>>,
+}
+
+impl EscalationRouter {
+    pub fn new() -> Self {
+        Self { rules: RwLock::new(Vec::new()) }
+    }
+
+    pub async fn add(&self, rule: RoutingRule) -> Result<RoutingRule> {
+        if rule.assignee.trim().is_empty() {
+            bail!("Routing rule needs an assignee");
+        }
+        if rule.due_hours.is_some_and(|hours| hours < 1) {
+            bail!("due_hours must be at least 1");
+        }
+        self.rules.write().await.push(rule.clone());
+        Ok(rule)
+    }
+
+    pub async fn list(&self) -> Vec<RoutingRule> {
+        self.rules.read().await.clone()
+    }
+
+    pub async fn delete(&self, id: Uuid) -> bool {
+        let mut rules = self.rules.write().await;
+        let before = rules.len();
+        rules.retain(|rule| rule.id != id);
+        rules.len() < before
+    }
+
+    pub async fn route(&self, escalation: &RoutingContext<'_>, now: DateTime<Utc>) -> Route {
+        let rules = self.rules.read().await;
+        let mut best: Option<(usize, &RoutingRule)> = None;
+        for rule in rules.iter() {
+            if let Some(score) = rule.specificity(escalation) {
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, rule));
+                }
+            }
+        }
+
+        let rule = best.map(|(_, rule)| rule);
+        let due_hours = rule.and_then(|r| r.due_hours).unwrap_or_else(|| default_due_hours(escalation.severity));
+        Route {
+            assignee: rule.map(|r| r.assignee.clone()),
+            due_at: now + Duration::hours(due_hours),
+        }
+    }
+}
+
+impl Default for EscalationRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,337 @@
+//! Task Executor
+//!
+//! Runs scheduled tasks as they fall due. Each poll claims a batch from the
+//! workflow service and dispatches it: outreach and follow-ups are sent
+//! through the email service, and escalation tasks raise an escalation.
+//! Document processing and validation, which the email and document
+//! services already do when a reply arrives, are closed. So are closeouts,
+//! unless their phase sends a closing email. Manual tasks are left to people.
+//! Failed runs are retried with backoff until the task's retries are used
+//! up; sends the email service rejects outright are escalated straight away.
+//! Tasks of other campaigns coalesced into an email share its outcome.
+//!
+//! With a database, tasks also go through the agent task queue there, so
+//! executors sharing it never run the same task twice. Only the tasks an
+//! executor claims in the queue are run, each under a lease renewed before
+//! it starts, and claims left behind by executors that died are released on
+//! every poll.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use elementa_database::WorkflowRepository;
+use elementa_models::{AgentTask, AgentTaskType, TaskContext, TaskPriority, TaskStatus};
+use std::collections::{HashMap, HashSet};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::email_client::{EmailClient, SendRejected, TaskEmail};
+use crate::service::{DueTask, WorkflowService};
+use crate::TaskResponse;
+use crate::state_machine::{TaskState, TaskType};
+
+/// Outreach is impossible because the supplier has no known address
+#[derive(Debug)]
+pub struct MissingContact {
+    pub supplier_id: Uuid,
+}
+
+impl std::fmt::Display for MissingContact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No contact email for supplier {}", self.supplier_id)
+    }
+}
+
+impl std::error::Error for MissingContact {}
+
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    pub poll_interval: std::time::Duration,
+    /// Maximum tasks run per poll
+    pub batch_size: usize,
+    /// Delay before the first retry; doubled for each one after
+    pub retry_delay: Duration,
+    /// Requesting company named in outreach emails
+    pub company_name: Option<String>,
+    /// How long a task claimed in the queue stays held without a heartbeat
+    pub lease: Duration,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        let env_u32 = |key: &str, default: u32| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            poll_interval: std::time::Duration::from_secs(env_u32("TASK_EXECUTOR_POLL_SECS", 30) as u64),
+            batch_size: env_u32("TASK_EXECUTOR_BATCH_SIZE", 20) as usize,
+            retry_delay: Duration::seconds(env_u32("TASK_RETRY_DELAY_SECS", 300) as i64),
+            company_name: std::env::var("COMPANY_NAME").ok().filter(|name| !name.trim().is_empty()),
+            lease: Duration::seconds(env_u32("TASK_LEASE_SECS", 300) as i64),
+        }
+    }
+}
+
+impl ExecutorConfig {
+    /// Backoff before retrying after the given attempt
+    pub fn retry_delay(&self, attempt: i32) -> Duration {
+        self.retry_delay * 2i32.pow(attempt.clamp(1, 8) as u32 - 1)
+    }
+}
+
+pub struct TaskExecutor {
+    service: WorkflowService,
+    email: EmailClient,
+    config: ExecutorConfig,
+    /// Holder of this executor's claims in the task queue
+    worker_id: String,
+}
+
+impl TaskExecutor {
+    pub fn new(service: WorkflowService, email: EmailClient, config: ExecutorConfig) -> Self {
+        Self { service, email, config, worker_id: format!("executor-{}", Uuid::new_v4()) }
+    }
+
+    /// Run the tasks due at `now`, returning how many were claimed
+    pub async fn run_due(&self, now: DateTime<Utc>) -> usize {
+        let due = self.service.claim_due_tasks(now, self.config.batch_size).await;
+        let queue = self.service.task_queue();
+        let due = match queue {
+            Some(queue) => self.claim_queued(queue, due, now).await,
+            None => due,
+        };
+        let claimed = due.len();
+
+        for task in due {
+            if let Some(queue) = queue {
+                if !self.renew_claim(queue, &task).await {
+                    continue;
+                }
+            }
+            debug!("Running {} task {} (attempt {})", task.task_type, task.id, task.attempt);
+            let outcome = self.dispatch(&task).await;
+            // Tasks whose email went out with this one share its outcome
+            for coalesced in &task.coalesced {
+                let recorded = match &outcome {
+                    Ok(result) => {
+                        let mut result = result.clone();
+                        result["coalesced_into"] = serde_json::json!(task.id);
+                        self.service.complete_task(coalesced.id, Some(result)).await
+                    }
+                    Err(e) => self.record_failure(&task, coalesced.id, e).await,
+                };
+                if let Err(e) = recorded {
+                    error!("Failed to record outcome of task {}: {:#}", coalesced.id, e);
+                }
+            }
+            let recorded = match outcome {
+                Ok(result) => self.service.complete_task(task.id, Some(result)).await,
+                Err(e) => self.record_failure(&task, task.id, &e).await,
+            };
+            let status = match recorded {
+                Ok(response) if response.status == TaskState::Completed.to_string() => TaskStatus::Completed,
+                Ok(response) if response.status == TaskState::Failed.to_string() => TaskStatus::Queued,
+                Ok(_) => TaskStatus::Failed,
+                Err(e) => {
+                    error!("Failed to record outcome of task {}: {:#}", task.id, e);
+                    TaskStatus::Failed
+                }
+            };
+            if let Some(queue) = queue {
+                if let Err(e) = queue.finish_task(task.id, &self.worker_id, status).await {
+                    error!("Failed to release claim on task {}: {:#}", task.id, e);
+                }
+            }
+        }
+
+        claimed
+    }
+
+    /// Of the tasks the service found due, those this executor claims in the
+    /// queue. The rest are held by other executors, or the queue could not be
+    /// reached, and are put back for a later poll.
+    async fn claim_queued(&self, queue: &WorkflowRepository, due: Vec<DueTask>, now: DateTime<Utc>) -> Vec<DueTask> {
+        match queue.release_stale_claims(now).await {
+            Ok(released) => {
+                for task_id in released.failed {
+                    if let Err(e) = self.service.fail_task(task_id, "Executor lease ran out".to_string(), None).await {
+                        debug!("Task {} released from the queue is not ours: {:#}", task_id, e);
+                    }
+                }
+            }
+            Err(e) => error!("Failed to release stale task claims: {:#}", e),
+        }
+        if due.is_empty() {
+            return due;
+        }
+
+        let mut offered = Vec::with_capacity(due.len());
+        for task in &due {
+            match queue.schedule_task(queued_task(task), now).await {
+                Ok(Some(_)) => offered.push(task.id),
+                Ok(None) => debug!("Task {} is held by another executor", task.id),
+                Err(e) => error!("Failed to queue task {}: {:#}", task.id, e),
+            }
+        }
+        let claimed: HashSet<Uuid> = match queue.claim_due_tasks(&self.worker_id, now, offered.len() as i64, self.config.lease).await {
+            Ok(claimed) => claimed.into_iter().map(|c| c.task.id).collect(),
+            Err(e) => {
+                error!("Failed to claim queued tasks: {:#}", e);
+                HashSet::new()
+            }
+        };
+        // Tasks another executor queued are its to run
+        for task_id in claimed.iter().filter(|id| !due.iter().any(|t| t.id == **id)) {
+            if let Err(e) = queue.finish_task(*task_id, &self.worker_id, TaskStatus::Queued).await {
+                error!("Failed to hand back task {}: {:#}", task_id, e);
+            }
+        }
+
+        let (mine, held): (Vec<DueTask>, Vec<DueTask>) = due.into_iter().partition(|t| claimed.contains(&t.id));
+        self.release(&held).await;
+        mine
+    }
+
+    /// Renew the claim on `task` before running it; `false`, with the task put
+    /// back, if the claim was lost in the meantime
+    async fn renew_claim(&self, queue: &WorkflowRepository, task: &DueTask) -> bool {
+        match queue.heartbeat(task.id, &self.worker_id, Utc::now(), self.config.lease).await {
+            Ok(true) => return true,
+            Ok(false) => warn!("Lost the claim on task {} before running it", task.id),
+            Err(e) => error!("Failed to renew the claim on task {}: {:#}", task.id, e),
+        }
+        self.release(std::slice::from_ref(task)).await;
+        false
+    }
+
+    /// Put claimed tasks, and those coalesced into them, back unrun
+    async fn release(&self, tasks: &[DueTask]) {
+        let task_ids: Vec<Uuid> = tasks.iter()
+            .flat_map(|t| std::iter::once(t.id).chain(t.coalesced.iter().map(|c| c.id)))
+            .collect();
+        self.service.release_tasks(&task_ids).await;
+    }
+
+    /// Fail `task_id` after `task` failed to run, retrying with backoff unless retrying cannot help
+    async fn record_failure(&self, task: &DueTask, task_id: Uuid, e: &anyhow::Error) -> Result<TaskResponse> {
+        let retryable = e.downcast_ref::<SendRejected>().is_none() && e.downcast_ref::<MissingContact>().is_none();
+        let retry_at = retryable.then(|| Utc::now() + self.config.retry_delay(task.attempt));
+        warn!("{} task {} failed on attempt {}: {:#}", task.task_type, task_id, task.attempt, e);
+        self.service.fail_task(task_id, format!("{:#}", e), retry_at).await
+    }
+
+    async fn dispatch(&self, task: &DueTask) -> Result<serde_json::Value> {
+        match task.task_type {
+            TaskType::InitialOutreach => self.send(task, None).await,
+            TaskType::FollowUp => self.send(task, task.thread_id.clone()).await,
+            TaskType::Escalation => {
+                self.service.escalate_supplier(
+                    task.workflow_id,
+                    task.supplier_id,
+                    "Supplier has not provided compliance data".to_string(),
+                ).await?;
+                Ok(serde_json::json!({ "escalated": true }))
+            }
+            TaskType::DocumentProcessing | TaskType::Validation => {
+                Ok(serde_json::json!({ "handled_on_receipt": true }))
+            }
+            TaskType::Closeout => match task.email_template_id {
+                Some(_) => self.send(task, task.thread_id.clone()).await,
+                None => Ok(serde_json::json!({ "closed": true })),
+            },
+            // Never claimed; people complete them through the manual task endpoints
+            TaskType::Manual => anyhow::bail!("Manual tasks are completed by their assignee"),
+        }
+    }
+
+    async fn send(&self, task: &DueTask, thread_id: Option<String>) -> Result<serde_json::Value> {
+        let payload = task.payload.as_ref();
+        let template_id = payload.and_then(|p| p.get("template_id")).and_then(|t| t.as_str()).map(str::to_string)
+            .or_else(|| task.email_template_id.clone())
+            .context("Task has no email template")?;
+        let contact = self.service.supplier_contact(task.supplier_id).await?
+            .filter(|contact| !contact.primary_email.trim().is_empty())
+            .ok_or(MissingContact { supplier_id: task.supplier_id })?;
+
+        let mut variables = HashMap::from([
+            ("contact_email".to_string(), contact.primary_email),
+            ("contact_name".to_string(), contact.contact_person),
+            ("reference_id".to_string(), task.campaign_name.clone()),
+        ]);
+        if let Some(company_name) = &self.config.company_name {
+            variables.insert("company_name".to_string(), company_name.clone());
+        }
+        if !task.coalesced.is_empty() {
+            let campaigns: Vec<&str> = task.coalesced.iter().map(|c| c.campaign_name.as_str()).collect();
+            variables.insert("additional_campaigns".to_string(), campaigns.join(", "));
+        }
+        // Variables given on requeue win over the derived ones
+        if let Some(overrides) = payload.and_then(|p| p.get("variables")).and_then(|v| v.as_object()) {
+            for (name, value) in overrides {
+                let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                variables.insert(name.clone(), value);
+            }
+        }
+
+        let sent = self.email.send(&TaskEmail {
+            supplier_id: task.supplier_id,
+            template_id,
+            thread_id,
+            campaign_id: Some(task.workflow_id),
+            // One email for several campaigns asks for the earliest deadline
+            calendar_deadline: Some(task.coalesced.iter().map(|c| c.deadline).fold(task.deadline, DateTime::min).to_rfc3339()),
+            variables,
+        }).await?;
+
+        Ok(serde_json::json!({
+            "email_id": sent.email_id,
+            "thread_id": sent.thread_id,
+            "recipient": sent.recipient,
+        }))
+    }
+}
+
+/// A claimed task as it goes in the queue
+fn queued_task(task: &DueTask) -> AgentTask {
+    let now = Utc::now();
+    AgentTask {
+        id: task.id,
+        workflow_id: task.workflow_id.into(),
+        task_type: match task.task_type {
+            TaskType::InitialOutreach => AgentTaskType::InitialOutreach,
+            TaskType::DocumentProcessing => AgentTaskType::DocumentProcessing,
+            TaskType::FollowUp => AgentTaskType::FollowUp,
+            TaskType::Validation => AgentTaskType::Validation,
+            TaskType::Escalation | TaskType::Manual => AgentTaskType::Escalation,
+            TaskType::Closeout => AgentTaskType::Closeout,
+        },
+        supplier_id: task.supplier_id.into(),
+        context: TaskContext {
+            components: Vec::new(),
+            deadline: task.deadline,
+            priority: TaskPriority::Medium,
+            custom_instructions: None,
+            previous_attempts: Vec::new(),
+        },
+        status: TaskStatus::Queued,
+        retry_count: (task.attempt - 1).max(0) as u32,
+        max_retries: task.max_retries.max(0) as u32,
+        created_at: now,
+        updated_at: now,
+        completed_at: None,
+    }
+}
+
+/// Run due tasks on every poll
+pub fn spawn_executor(executor: TaskExecutor) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("Task executor polling every {:?}", executor.config.poll_interval);
+        let mut interval = tokio::time::interval(executor.config.poll_interval);
+
+        loop {
+            interval.tick().await;
+            executor.run_due(Utc::now()).await;
+        }
+    })
+}
@@ -0,0 +1,161 @@
+//! Campaign Forecasting
+//!
+//! Projects when a campaign's remaining suppliers are likely to answer,
+//! from how long they took in past campaigns. A supplier with enough
+//! compliance history of its own is judged on it; otherwise on the pooled
+//! history of the campaign's suppliers, including replies already in.
+//! Suppliers still silent are only compared with past answers that took
+//! longer than they have already been waiting.
+
+use chrono::{DateTime, Duration, Utc};
+use elementa_models::{ComplianceHistoryEntry, ComplianceStatus};
+use uuid::Uuid;
+
+/// Past campaigns a supplier needs before its own history is used
+pub const MIN_SUPPLIER_HISTORY: usize = 3;
+
+/// Share of likely answers a supplier needs to count towards the completion date
+const LIKELY: f64 = 0.5;
+
+/// How long requests took to be answered, and how many never were
+#[derive(Debug, Clone, Default)]
+pub struct ResponseHistory {
+    pub answered_after: Vec<Duration>,
+    pub unanswered: usize,
+}
+
+impl ResponseHistory {
+    /// History from a supplier's compliance record, leaving out `campaign_id`
+    /// itself. Campaigns still in progress without an answer say nothing yet.
+    pub fn from_entries(entries: &[ComplianceHistoryEntry], campaign_id: Uuid) -> Self {
+        let mut history = Self::default();
+        for entry in entries.iter().filter(|e| e.campaign_id != campaign_id) {
+            match (entry.response_time_days, &entry.status) {
+                (Some(days), _) => history.answered_after.push(Duration::days(days.max(0) as i64)),
+                (None, ComplianceStatus::NotStarted | ComplianceStatus::NonCompliant) => history.unanswered += 1,
+                (None, _) => {}
+            }
+        }
+        history
+    }
+
+    pub fn len(&self) -> usize {
+        self.answered_after.len() + self.unanswered
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn extend(&mut self, other: &ResponseHistory) {
+        self.answered_after.extend_from_slice(&other.answered_after);
+        self.unanswered += other.unanswered;
+    }
+
+    /// Chance that a supplier silent for `waited` still answers, and the
+    /// median delay of the answers that took that long; `None` without history
+    pub fn outlook(&self, waited: Duration) -> Option<(f64, Option<Duration>)> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut later: Vec<Duration> = self.answered_after.iter().copied().filter(|d| *d > waited).collect();
+        later.sort();
+        // Every past answer came sooner and none went unanswered, so nothing suggests one is coming
+        let chance = match later.len() + self.unanswered {
+            0 => 0.0,
+            cases => later.len() as f64 / cases as f64,
+        };
+        Some((chance, later.get(later.len() / 2).copied()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForecastBasis {
+    SupplierHistory,
+    CampaignHistory,
+    /// Neither the supplier nor the campaign has any history
+    None,
+}
+
+impl std::fmt::Display for ForecastBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SupplierHistory => write!(f, "supplier_history"),
+            Self::CampaignHistory => write!(f, "campaign_history"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Projection for a supplier that has not answered yet
+#[derive(Debug, Clone)]
+pub struct SupplierOutlook {
+    pub supplier_id: Uuid,
+    pub basis: ForecastBasis,
+    pub chance: f64,
+    pub expected_at: Option<DateTime<Utc>>,
+}
+
+impl SupplierOutlook {
+    /// Project a supplier first asked at `asked_at`, from its own history when it has enough
+    pub fn project(
+        supplier_id: Uuid,
+        own: Option<&ResponseHistory>,
+        pooled: &ResponseHistory,
+        asked_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let (basis, history) = match own {
+            Some(own) if own.len() >= MIN_SUPPLIER_HISTORY => (ForecastBasis::SupplierHistory, own),
+            _ if !pooled.is_empty() => (ForecastBasis::CampaignHistory, pooled),
+            _ => (ForecastBasis::None, pooled),
+        };
+        let waited = (now - asked_at).max(Duration::zero());
+        let (chance, delay) = history.outlook(waited).unwrap_or((0.0, None));
+        Self {
+            supplier_id,
+            basis,
+            chance,
+            expected_at: delay.map(|delay| asked_at + delay),
+        }
+    }
+
+    pub fn is_likely(&self) -> bool {
+        self.chance >= LIKELY && self.expected_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supplier_history_is_used_once_long_enough() {
+        let campaign_id = Uuid::new_v4();
+        let entry = |days: Option<i32>, status: ComplianceStatus| ComplianceHistoryEntry {
+            campaign_id: Uuid::new_v4().into(),
+            status,
+            response_time_days: days,
+            completeness_score: 1.0,
+            last_updated: Utc::now(),
+        };
+        let own = ResponseHistory::from_entries(&[
+            entry(Some(2), ComplianceStatus::Complete),
+            entry(Some(10), ComplianceStatus::Complete),
+            entry(None, ComplianceStatus::NonCompliant),
+            entry(None, ComplianceStatus::InProgress),
+        ], campaign_id);
+        assert_eq!((own.len(), own.unanswered), (3, 1));
+
+        // After three days only the ten-day answer and the unanswered campaign are still in play
+        let asked_at = Utc::now();
+        let outlook = SupplierOutlook::project(Uuid::new_v4(), Some(&own), &ResponseHistory::default(), asked_at, asked_at + Duration::days(3));
+        assert_eq!((outlook.basis, outlook.chance), (ForecastBasis::SupplierHistory, 0.5));
+        assert_eq!(outlook.expected_at, Some(asked_at + Duration::days(10)));
+        assert!(outlook.is_likely());
+
+        let short = ResponseHistory { answered_after: vec![Duration::days(1)], unanswered: 0 };
+        let outlook = SupplierOutlook::project(Uuid::new_v4(), Some(&short), &own, asked_at, asked_at);
+        assert_eq!(outlook.basis, ForecastBasis::CampaignHistory);
+    }
+}
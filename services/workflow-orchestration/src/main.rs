@@ -5,23 +5,41 @@
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
+use elementa_database::{DatabaseConfig, TenantAccess};
+use elementa_models::EventEnvelope;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{debug, info};
 use uuid::Uuid;
 
+mod audit_client;
+mod campaign_templates;
+mod certification_job;
+mod email_client;
+mod escalation_job;
+mod escalation_routing;
+mod executor;
+mod forecast;
+mod metrics;
+mod outreach_guard;
+mod phases;
+mod recurrence;
+mod recurrence_job;
 mod state_machine;
 mod scheduler;
 mod service;
+mod sla;
+mod stall_job;
 
+use executor::{ExecutorConfig, TaskExecutor};
 use service::WorkflowService;
 
 #[tokio::main]
@@ -29,7 +47,30 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     info!("Starting Elementa Workflow Orchestration Service");
     
-    let service = WorkflowService::new();
+    let mut service = WorkflowService::new()
+        .with_audit(audit_client::AuditClient::default())
+        .with_email(email_client::EmailClient::default());
+    
+    if let Some(mut db_config) = DatabaseConfig::postgres_from_env()? {
+        // Jobs and callbacks here work across tenants
+        db_config.tenant_access = TenantAccess::AllTenants;
+        db_config.max_connections = 5;
+        let pools = elementa_database::connect_postgres(&db_config).await?;
+        if let Some(rotation) = db_config.credential_rotation(&pools) {
+            tokio::spawn(rotation.run());
+        }
+        service = service.with_database(pools);
+    }
+    
+    executor::spawn_executor(TaskExecutor::new(
+        service.clone(),
+        email_client::EmailClient::default(),
+        ExecutorConfig::default(),
+    ));
+    escalation_job::spawn_escalation_job(service.clone());
+    recurrence_job::spawn_recurrence_job(service.clone());
+    stall_job::spawn_stall_job(service.clone());
+    certification_job::spawn_certification_job(service.clone());
     
     let app = Router::new()
         .route("/health", get(health_check))
@@ -39,14 +80,56 @@ async fn main() -> Result<()> {
         .route("/api/v1/workflows/:id", get(get_workflow))
         .route("/api/v1/workflows/:id/status", put(update_workflow_status))
         .route("/api/v1/workflows/:id/cancel", post(cancel_workflow))
+        .route("/api/v1/workflows/:id/clone", post(clone_workflow))
+        .route("/api/v1/workflows/:id/config", put(update_workflow_config))
+        .route("/api/v1/workflows/:id/config/versions", get(list_config_versions))
+        .route("/api/v1/workflows/:id/suppliers", get(get_supplier_progress))
+        .route("/api/v1/workflows/:id/sla", get(get_sla_report))
+        .route("/api/v1/workflows/:id/timeline", get(get_timeline))
+        .route("/api/v1/workflows/:id/forecast", get(get_forecast))
+        .route("/api/v1/workflows/:id/metrics", get(get_metrics))
+        .route("/api/v1/workflows/:id/phases", get(get_phase_status))
+        .route("/api/v1/attention", get(get_attention_needed))
+        // Campaign templates
+        .route("/api/v1/campaign-templates", get(list_campaign_templates))
+        .route(
+            "/api/v1/campaign-templates/:template_id",
+            put(set_campaign_template).get(get_campaign_template).delete(delete_campaign_template),
+        )
+        .route("/api/v1/campaign-templates/:template_id/launch", post(launch_campaign))
+        .route(
+            "/api/v1/campaign-templates/:template_id/recurrence",
+            put(set_campaign_recurrence).delete(clear_campaign_recurrence),
+        )
         // Task management
         .route("/api/v1/workflows/:id/tasks", get(get_workflow_tasks))
+        .route("/api/v1/workflows/:id/task-graph", get(get_task_graph))
+        .route("/api/v1/tasks/dead-letter", get(list_dead_letters))
         .route("/api/v1/tasks/:task_id", get(get_task))
         .route("/api/v1/tasks/:task_id/complete", post(complete_task))
         .route("/api/v1/tasks/:task_id/retry", post(retry_task))
+        .route("/api/v1/tasks/:task_id/requeue", post(requeue_task))
+        .route("/api/v1/tasks/:task_id/dependencies", post(add_task_dependencies))
+        .route("/api/v1/workflows/:id/manual-tasks", post(create_manual_task))
+        .route("/api/v1/manual-tasks", get(list_manual_tasks))
+        .route("/api/v1/manual-tasks/:task_id/assign", post(assign_manual_task))
+        .route("/api/v1/manual-tasks/:task_id/complete", post(complete_manual_task))
+        // Events from other services
+        .route("/api/v1/events", post(domain_event))
+        .route("/api/v1/events/email-sent", post(email_sent))
+        .route("/api/v1/events/reply-classified", post(reply_classified))
+        .route("/api/v1/events/document-extracted", post(document_extracted))
+        .route("/api/v1/events/supplier-suppressed", post(supplier_suppressed))
+        .route("/api/v1/events/supplier-unresponsive", post(supplier_unresponsive))
+        .route("/api/v1/events/task-updates", post(task_updates))
         // Escalations
         .route("/api/v1/escalations", get(list_escalations))
+        .route("/api/v1/escalations/:id/acknowledge", post(acknowledge_escalation))
+        .route("/api/v1/escalations/:id/assign", post(assign_escalation))
+        .route("/api/v1/escalations/:id/comments", post(comment_on_escalation))
         .route("/api/v1/escalations/:id/resolve", post(resolve_escalation))
+        .route("/api/v1/escalation-rules", get(list_routing_rules).post(add_routing_rule))
+        .route("/api/v1/escalation-rules/:rule_id", delete(delete_routing_rule))
         .layer(TraceLayer::new_for_http())
         .with_state(service);
     
@@ -84,6 +167,30 @@ pub struct WorkflowConfig {
     pub follow_up_interval_days: i32,
     pub auto_escalate: bool,
     pub escalation_threshold_days: i32,
+    /// Suppliers still silent this close to the deadline are escalated
+    #[serde(default = "default_deadline_warning_days")]
+    pub deadline_warning_days: i32,
+    /// Email template for the first request
+    #[serde(default = "default_outreach_template_id")]
+    pub outreach_template_id: String,
+    #[serde(default = "default_follow_up_template_id")]
+    pub follow_up_template_id: String,
+    /// Ordered phases each supplier moves through; when empty, outreach is
+    /// followed by `max_follow_ups` follow-ups
+    #[serde(default)]
+    pub phases: Vec<phases::PhaseConfig>,
+}
+
+fn default_deadline_warning_days() -> i32 {
+    7
+}
+
+fn default_outreach_template_id() -> String {
+    "initial_outreach".to_string()
+}
+
+fn default_follow_up_template_id() -> String {
+    "follow_up".to_string()
 }
 
 impl Default for WorkflowConfig {
@@ -93,6 +200,10 @@ impl Default for WorkflowConfig {
             follow_up_interval_days: 7,
             auto_escalate: true,
             escalation_threshold_days: 21,
+            deadline_warning_days: default_deadline_warning_days(),
+            outreach_template_id: default_outreach_template_id(),
+            follow_up_template_id: default_follow_up_template_id(),
+            phases: Vec::new(),
         }
     }
 }
@@ -108,6 +219,20 @@ pub struct WorkflowResponse {
     pub progress: WorkflowProgress,
     pub supplier_count: usize,
     pub task_count: usize,
+    /// Workflow this campaign was cloned from
+    pub cloned_from: Option<Uuid>,
+    /// Version of the config the workflow runs on, from 1
+    pub config_version: usize,
+}
+
+/// Repeat a campaign; anything left out is copied from the source workflow
+#[derive(Debug, Deserialize)]
+pub struct CloneWorkflowRequest {
+    pub deadline: String,
+    /// Defaults to the source name with " (copy)"
+    pub campaign_name: Option<String>,
+    pub supplier_ids: Option<Vec<Uuid>>,
+    pub config: Option<WorkflowConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,9 +242,30 @@ pub struct WorkflowProgress {
     pub responded: usize,
     pub complete: usize,
     pub escalated: usize,
+    /// Suppliers whose documents have been extracted
+    pub documents_received: usize,
     pub percent_complete: f64,
 }
 
+/// One supplier's status in a workflow, fed by email and document events
+#[derive(Debug, Serialize)]
+pub struct SupplierProgress {
+    pub supplier_id: Uuid,
+    /// not_contacted, contacted, responded, data_complete or escalated
+    pub status: String,
+    /// Reasons of unresolved escalations for the supplier
+    pub open_escalations: Vec<String>,
+    /// Opted out of automated email
+    pub manual: bool,
+    pub emails_sent: usize,
+    pub last_sent_at: Option<String>,
+    pub replies: usize,
+    pub last_reply_at: Option<String>,
+    pub documents_extracted: usize,
+    pub cas_numbers_found: usize,
+    pub needs_review: bool,
+}
+
 async fn create_workflow(
     State(service): State<WorkflowService>,
     Json(request): Json<CreateWorkflowRequest>,
@@ -139,6 +285,256 @@ async fn list_workflows(
     Ok(Json(workflows))
 }
 
+async fn get_supplier_progress(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<SupplierProgress>>, (StatusCode, String)> {
+    let progress = service.supplier_progress(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(progress))
+}
+
+/// Campaign plan for Gantt charts: one row per supplier
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    pub workflow_id: Uuid,
+    pub campaign_name: String,
+    pub start_date: String,
+    pub deadline: String,
+    pub suppliers: Vec<SupplierTimelineResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplierTimelineResponse {
+    pub supplier_id: Uuid,
+    /// Tasks in scheduled order, then projected follow-ups
+    pub entries: Vec<TimelineEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineEntry {
+    /// `None` for projected follow-ups that are not scheduled yet
+    pub task_id: Option<Uuid>,
+    pub task_type: String,
+    pub status: String,
+    /// Bar extent: actual times where known, otherwise the scheduled time
+    pub start: String,
+    pub end: String,
+    pub scheduled_at: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub projected: bool,
+}
+
+async fn get_timeline(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TimelineResponse>, (StatusCode, String)> {
+    let timeline = service.timeline(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(timeline))
+}
+
+/// One SLA check that was missed or is close to being missed
+#[derive(Debug, Serialize)]
+pub struct SlaItemResponse {
+    pub supplier_id: Uuid,
+    /// first_contact or follow_up
+    pub sla: String,
+    pub due_at: String,
+    /// Set when the email went out, late
+    pub completed_at: Option<String>,
+    /// Negative once overdue
+    pub hours_remaining: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlaReportResponse {
+    pub workflow_id: Uuid,
+    pub generated_at: String,
+    pub first_contact_hours: i64,
+    pub follow_up_days: i64,
+    /// Checks met on time
+    pub met: usize,
+    /// Open checks with time to spare
+    pub pending: usize,
+    pub breaches: Vec<SlaItemResponse>,
+    pub at_risk: Vec<SlaItemResponse>,
+}
+
+async fn get_sla_report(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SlaReportResponse>, (StatusCode, String)> {
+    let report = service.sla_report(id, chrono::Utc::now()).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(report))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForecastResponse {
+    pub workflow_id: Uuid,
+    pub campaign_name: String,
+    pub generated_at: String,
+    pub deadline: String,
+    pub total_suppliers: usize,
+    pub responded: usize,
+    /// When the last supplier likely to answer is expected to
+    pub expected_completion_date: String,
+    /// Percent of suppliers expected to have answered in the end
+    pub expected_response_rate: f64,
+    pub expected_response_rate_by_deadline: f64,
+    pub likely_to_miss_deadline: bool,
+    /// Suppliers still to answer
+    pub suppliers: Vec<SupplierForecastResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplierForecastResponse {
+    pub supplier_id: Uuid,
+    /// supplier_history, campaign_history or none
+    pub basis: String,
+    pub response_chance: f64,
+    pub expected_response_at: Option<String>,
+}
+
+async fn get_forecast(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ForecastResponse>, (StatusCode, String)> {
+    let forecast = service.forecast(id, chrono::Utc::now()).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(forecast))
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhaseStatusResponse {
+    pub workflow_id: Uuid,
+    /// Whether the workflow has phases configured; otherwise phases are
+    /// read from its outreach, follow-up and escalation tasks
+    pub phased: bool,
+    pub phases: Vec<phases::PhaseConfig>,
+    pub suppliers: Vec<SupplierPhaseResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplierPhaseResponse {
+    pub supplier_id: Uuid,
+    /// Latest phase that ran
+    pub current_phase: Option<String>,
+    pub entered_at: Option<String>,
+    pub completed_phases: Vec<String>,
+    /// Phases whose entry criteria no longer held
+    pub skipped_phases: Vec<String>,
+    pub next_phase: Option<String>,
+    pub next_at: Option<String>,
+    /// Nothing left to do, or handled by hand
+    pub closed: bool,
+}
+
+async fn get_phase_status(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PhaseStatusResponse>, (StatusCode, String)> {
+    let status = service.phase_status(id).await
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(status))
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsResponse {
+    pub workflow_id: Uuid,
+    pub campaign_name: String,
+    pub status: String,
+    pub generated_at: String,
+    pub funnel: FunnelResponse,
+    pub metrics: MetricsSummaryResponse,
+    /// The client's earlier campaigns; `None` if this is its first
+    pub client_history: Option<ClientHistoryResponse>,
+}
+
+/// Suppliers at each step of the campaign
+#[derive(Debug, Serialize)]
+pub struct FunnelResponse {
+    pub suppliers: usize,
+    pub contacted: usize,
+    pub responded: usize,
+    /// Suppliers that said all their data is in
+    pub complete: usize,
+    pub escalated: usize,
+}
+
+/// Rates are percentages of the campaign's suppliers
+#[derive(Debug, Serialize)]
+pub struct MetricsSummaryResponse {
+    pub response_rate: f64,
+    pub completion_rate: f64,
+    pub escalation_rate: f64,
+    /// From the first request to the first answer
+    pub average_response_hours: Option<f64>,
+    /// Follow-ups sent before the first answer, per supplier that answered
+    pub follow_ups_per_response: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientHistoryResponse {
+    pub campaigns: usize,
+    pub averages: MetricsSummaryResponse,
+    /// This campaign's figures minus the averages
+    pub difference: MetricsSummaryResponse,
+}
+
+async fn get_metrics(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MetricsResponse>, (StatusCode, String)> {
+    let metrics = service.metrics(id, chrono::Utc::now()).await
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(metrics))
+}
+
+/// Campaigns with no change for `days` (default `STALLED_CAMPAIGN_DAYS`) that cannot move on their own
+#[derive(Debug, Deserialize)]
+pub struct AttentionQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StalledWorkflowResponse {
+    pub workflow_id: Uuid,
+    pub client_id: Uuid,
+    pub campaign_name: String,
+    pub deadline: String,
+    pub last_change_at: String,
+    pub idle_days: i64,
+    /// Why the campaign cannot move, e.g. due tasks nobody picked up
+    pub causes: Vec<String>,
+    /// Suppliers whose tasks are stuck or who have nothing scheduled
+    pub supplier_ids: Vec<Uuid>,
+}
+
+async fn get_attention_needed(
+    State(service): State<WorkflowService>,
+    Query(query): Query<AttentionQuery>,
+) -> Result<Json<Vec<StalledWorkflowResponse>>, (StatusCode, String)> {
+    let days = query.days.unwrap_or_else(stall_job::stall_days);
+    if days < 1 {
+        return Err((StatusCode::BAD_REQUEST, "days must be at least 1".to_string()));
+    }
+    
+    Ok(Json(service.stalled_workflows(chrono::Utc::now(), days).await))
+}
+
 async fn get_workflow(
     State(service): State<WorkflowService>,
     Path(id): Path<Uuid>,
@@ -150,6 +546,136 @@ async fn get_workflow(
     Ok(Json(workflow))
 }
 
+// ===== Campaign Template Endpoints =====
+
+#[derive(Debug, Deserialize)]
+pub struct CampaignTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub config: Option<WorkflowConfig>,
+    /// Days from launch to the response deadline
+    pub response_days: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CampaignTemplateResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub config: WorkflowConfig,
+    pub response_days: i32,
+    pub built_in: bool,
+    pub updated_at: String,
+    pub recurrence: Option<RecurrenceResponse>,
+}
+
+/// Relaunch a campaign template on a schedule
+#[derive(Debug, Deserialize)]
+pub struct RecurrenceRequest {
+    /// Five-field cron expression in UTC, e.g. `0 9 1 1,4,7,10 *`
+    pub cron: String,
+    pub client_id: Uuid,
+    pub supplier_ids: Vec<Uuid>,
+    /// Launch each cycle pending, to be activated by hand
+    #[serde(default)]
+    pub require_confirmation: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecurrenceResponse {
+    pub cron: String,
+    pub client_id: Uuid,
+    pub supplier_ids: Vec<Uuid>,
+    pub require_confirmation: bool,
+    pub next_run_at: String,
+    /// Campaign launched for the latest cycle
+    pub last_workflow_id: Option<Uuid>,
+}
+
+/// Launch a workflow from a campaign template
+#[derive(Debug, Deserialize)]
+pub struct LaunchCampaignRequest {
+    pub client_id: Uuid,
+    pub supplier_ids: Vec<Uuid>,
+    /// Defaults to the template name and launch date
+    pub campaign_name: Option<String>,
+    /// Defaults to `response_days` from now
+    pub deadline: Option<String>,
+}
+
+async fn list_campaign_templates(
+    State(service): State<WorkflowService>,
+) -> Json<Vec<CampaignTemplateResponse>> {
+    Json(service.list_campaign_templates().await)
+}
+
+async fn set_campaign_template(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+    Json(request): Json<CampaignTemplateRequest>,
+) -> Result<Json<CampaignTemplateResponse>, (StatusCode, String)> {
+    let template = service.set_campaign_template(&template_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    Ok(Json(template))
+}
+
+async fn get_campaign_template(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+) -> Result<Json<CampaignTemplateResponse>, (StatusCode, String)> {
+    let template = service.get_campaign_template(&template_id).await
+        .ok_or((StatusCode::NOT_FOUND, "Campaign template not found".to_string()))?;
+    
+    Ok(Json(template))
+}
+
+async fn delete_campaign_template(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+) -> StatusCode {
+    if service.delete_campaign_template(&template_id).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+async fn launch_campaign(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+    Json(request): Json<LaunchCampaignRequest>,
+) -> Result<Json<WorkflowResponse>, (StatusCode, String)> {
+    let workflow = service.launch_campaign(&template_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Campaign template not found".to_string()))?;
+    
+    info!("Launched campaign {} from template {}", workflow.id, template_id);
+    Ok(Json(workflow))
+}
+
+async fn set_campaign_recurrence(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+    Json(request): Json<RecurrenceRequest>,
+) -> Result<Json<CampaignTemplateResponse>, (StatusCode, String)> {
+    let template = service.set_campaign_recurrence(&template_id, request, chrono::Utc::now()).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Campaign template not found".to_string()))?;
+    
+    Ok(Json(template))
+}
+
+async fn clear_campaign_recurrence(
+    State(service): State<WorkflowService>,
+    Path(template_id): Path<String>,
+) -> Result<Json<CampaignTemplateResponse>, (StatusCode, String)> {
+    let template = service.clear_campaign_recurrence(&template_id).await
+        .ok_or((StatusCode::NOT_FOUND, "Campaign template not found".to_string()))?;
+    
+    Ok(Json(template))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateStatusRequest {
     pub status: String,
@@ -166,16 +692,94 @@ async fn update_workflow_status(
     Ok(Json(workflow))
 }
 
+/// Optional body of a cancellation
+#[derive(Debug, Default, Deserialize)]
+pub struct CancelWorkflowRequest {
+    pub reason: Option<String>,
+    /// Email suppliers already contacted that the request is withdrawn
+    #[serde(default)]
+    pub notify_suppliers: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancellationResponse {
+    #[serde(flatten)]
+    pub workflow: WorkflowResponse,
+    pub tasks_cancelled: usize,
+    pub escalations_closed: usize,
+    pub withdrawals_sent: usize,
+    /// Suppliers that could not be emailed, e.g. for lack of a contact address
+    pub withdrawals_failed: usize,
+}
+
 async fn cancel_workflow(
     State(service): State<WorkflowService>,
     Path(id): Path<Uuid>,
-) -> Result<Json<WorkflowResponse>, (StatusCode, String)> {
-    let workflow = service.cancel_workflow(id).await
+    request: Option<Json<CancelWorkflowRequest>>,
+) -> Result<Json<CancellationResponse>, (StatusCode, String)> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let cancellation = service.cancel_workflow(id, request).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
+    Ok(Json(cancellation))
+}
+
+async fn clone_workflow(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CloneWorkflowRequest>,
+) -> Result<Json<WorkflowResponse>, (StatusCode, String)> {
+    let workflow = service.clone_workflow(id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    info!("Cloned workflow {} as {}", id, workflow.id);
     Ok(Json(workflow))
 }
 
+/// New config for a running workflow; tasks already scheduled keep their times
+#[derive(Debug, Deserialize)]
+pub struct UpdateWorkflowConfigRequest {
+    pub config: WorkflowConfig,
+    pub changed_by: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigVersionResponse {
+    pub version: usize,
+    pub config: WorkflowConfig,
+    pub changed_by: Option<String>,
+    pub changed_at: String,
+    pub reason: Option<String>,
+    /// Fields that differ from the previous version, as `field: old -> new`
+    pub changes: Vec<String>,
+    pub active: bool,
+}
+
+async fn update_workflow_config(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateWorkflowConfigRequest>,
+) -> Result<Json<ConfigVersionResponse>, (StatusCode, String)> {
+    let version = service.update_config(id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    info!("Workflow {} now runs on config version {}: {}", id, version.version, version.changes.join(", "));
+    Ok(Json(version))
+}
+
+async fn list_config_versions(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ConfigVersionResponse>>, (StatusCode, String)> {
+    let versions = service.config_versions(id).await
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(versions))
+}
+
 // ===== Task Endpoints =====
 
 #[derive(Debug, Serialize)]
@@ -191,6 +795,64 @@ pub struct TaskResponse {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub error: Option<String>,
+    /// Tasks that must complete before this one runs
+    pub depends_on: Vec<Uuid>,
+    /// Higher runs first among due tasks
+    pub priority: i32,
+    /// Assignment of a manual task
+    pub manual: Option<ManualTaskResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManualTaskResponse {
+    pub assignee: Option<String>,
+    pub instructions: String,
+    pub due_at: Option<String>,
+    /// Still open past its due date
+    pub overdue: bool,
+    pub notes: Option<String>,
+    pub completed_by: Option<String>,
+}
+
+/// Exhausted task with its failure context
+#[derive(Debug, Serialize)]
+pub struct DeadLetterResponse {
+    pub task: TaskResponse,
+    pub campaign_name: Option<String>,
+    pub workflow_status: Option<String>,
+    /// Runs made before the task gave up
+    pub attempts: i32,
+    pub exhausted_at: Option<String>,
+    pub last_result: Option<serde_json::Value>,
+    pub payload: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequeueTaskRequest {
+    /// Replaces the task's overrides: `template_id` and extra template `variables`
+    pub payload: Option<serde_json::Value>,
+    /// Who requeued the task, for the audit trail
+    pub requested_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddDependenciesRequest {
+    pub depends_on: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskEdge {
+    pub task_id: Uuid,
+    pub depends_on: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskGraphResponse {
+    pub workflow_id: Uuid,
+    pub tasks: Vec<TaskResponse>,
+    pub edges: Vec<TaskEdge>,
+    /// Open tasks still waiting on a dependency
+    pub blocked: Vec<Uuid>,
 }
 
 async fn get_workflow_tasks(
@@ -203,6 +865,17 @@ async fn get_workflow_tasks(
     Ok(Json(tasks))
 }
 
+async fn get_task_graph(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TaskGraphResponse>, (StatusCode, String)> {
+    let graph = service.task_graph(id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(graph))
+}
+
 async fn get_task(
     State(service): State<WorkflowService>,
     Path(task_id): Path<Uuid>,
@@ -240,6 +913,438 @@ async fn retry_task(
     Ok(Json(task))
 }
 
+async fn list_dead_letters(
+    State(service): State<WorkflowService>,
+) -> Json<Vec<DeadLetterResponse>> {
+    Json(service.dead_letters().await)
+}
+
+async fn requeue_task(
+    State(service): State<WorkflowService>,
+    Path(task_id): Path<Uuid>,
+    Json(request): Json<RequeueTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, String)> {
+    let task = service.requeue_task(task_id, request.payload, request.requested_by).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Task not found".to_string()))?;
+    
+    Ok(Json(task))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateManualTaskRequest {
+    pub supplier_id: Uuid,
+    pub assignee: Option<String>,
+    /// What the person has to do, e.g. "Call the quality manager about the missing SDS"
+    pub instructions: String,
+    pub due_at: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ManualTaskQuery {
+    pub assignee: Option<String>,
+    /// Only open (`true`) or only closed (`false`) tasks
+    pub open: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignManualTaskRequest {
+    pub assignee: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteManualTaskRequest {
+    /// What was done or found, e.g. the outcome of the call
+    pub notes: Option<String>,
+    /// Defaults to the assignee
+    pub completed_by: Option<String>,
+}
+
+async fn create_manual_task(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateManualTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, String)> {
+    let task = service.create_manual_task(id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Workflow not found".to_string()))?;
+    
+    Ok(Json(task))
+}
+
+async fn list_manual_tasks(
+    State(service): State<WorkflowService>,
+    Query(query): Query<ManualTaskQuery>,
+) -> Json<Vec<TaskResponse>> {
+    Json(service.list_manual_tasks(&query).await)
+}
+
+async fn assign_manual_task(
+    State(service): State<WorkflowService>,
+    Path(task_id): Path<Uuid>,
+    Json(request): Json<AssignManualTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, String)> {
+    let task = service.assign_manual_task(task_id, &request.assignee).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Manual task not found".to_string()))?;
+    
+    Ok(Json(task))
+}
+
+async fn complete_manual_task(
+    State(service): State<WorkflowService>,
+    Path(task_id): Path<Uuid>,
+    Json(request): Json<CompleteManualTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, String)> {
+    let task = service.complete_manual_task(task_id, request.notes, request.completed_by).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Manual task not found".to_string()))?;
+    
+    Ok(Json(task))
+}
+
+async fn add_task_dependencies(
+    State(service): State<WorkflowService>,
+    Path(task_id): Path<Uuid>,
+    Json(request): Json<AddDependenciesRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, String)> {
+    let task = service.add_task_dependencies(task_id, &request.depends_on).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Task not found".to_string()))?;
+    
+    Ok(Json(task))
+}
+
+// ===== Event Endpoints =====
+
+#[derive(Debug, Serialize)]
+pub struct EventHandledResponse {
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// False for event types the workflow service has no use for
+    pub handled: bool,
+    /// Response of the handler for the event's type
+    pub outcome: Option<serde_json::Value>,
+}
+
+/// Domain event from another service, in its envelope
+async fn domain_event(
+    State(service): State<WorkflowService>,
+    Json(envelope): Json<EventEnvelope>,
+) -> Result<Json<EventHandledResponse>, (StatusCode, String)> {
+    envelope.check_version()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    debug!(
+        "{} event {} from {} (correlation {})",
+        envelope.event_type(), envelope.id, envelope.source, envelope.correlation_id,
+    );
+    
+    let handled = service.handle_domain_event(envelope).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressUpdatedResponse {
+    pub workflows_updated: usize,
+}
+
+/// Email delivered to a supplier by the email service
+#[derive(Debug, Deserialize)]
+pub struct EmailSentEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    /// Workflow the email was sent for; otherwise every active workflow the supplier is in
+    pub campaign_id: Option<Uuid>,
+    pub template_id: Option<String>,
+    pub sent_at: String,
+}
+
+impl From<elementa_models::EmailSentEvent> for EmailSentEvent {
+    fn from(event: elementa_models::EmailSentEvent) -> Self {
+        Self {
+            email_id: event.email_id,
+            supplier_id: event.supplier_id,
+            thread_id: event.thread_id,
+            campaign_id: event.campaign_id,
+            template_id: event.template_id,
+            sent_at: event.sent_at.to_rfc3339(),
+        }
+    }
+}
+
+async fn email_sent(
+    State(service): State<WorkflowService>,
+    Json(event): Json<EmailSentEvent>,
+) -> Result<Json<ProgressUpdatedResponse>, (StatusCode, String)> {
+    debug!("Email {} sent to supplier {} in thread {}", event.email_id, event.supplier_id, event.thread_id);
+    
+    let handled = service.handle_email_sent(event).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+/// Documents from a supplier reply extracted by document-processing
+#[derive(Debug, Deserialize)]
+pub struct DocumentExtractedEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub campaign_id: Option<Uuid>,
+    pub document_ids: Vec<Uuid>,
+    pub cas_numbers_found: usize,
+    pub needs_review: bool,
+    pub extracted_at: String,
+}
+
+impl From<elementa_models::DocumentExtractedEvent> for DocumentExtractedEvent {
+    fn from(event: elementa_models::DocumentExtractedEvent) -> Self {
+        Self {
+            email_id: event.email_id,
+            supplier_id: event.supplier_id,
+            campaign_id: event.campaign_id,
+            document_ids: event.document_ids,
+            cas_numbers_found: event.cas_numbers_found,
+            needs_review: event.needs_review,
+            extracted_at: event.extracted_at.to_rfc3339(),
+        }
+    }
+}
+
+async fn document_extracted(
+    State(service): State<WorkflowService>,
+    Json(event): Json<DocumentExtractedEvent>,
+) -> Result<Json<ProgressUpdatedResponse>, (StatusCode, String)> {
+    info!(
+        "{} documents from supplier {} extracted ({} CAS numbers)",
+        event.document_ids.len(), event.supplier_id, event.cas_numbers_found,
+    );
+    
+    let handled = service.handle_document_extracted(event).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+/// Supplier reply classified by the email service
+#[derive(Debug, Deserialize)]
+pub struct ReplyClassifiedEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    pub classification: ReplyClassificationPayload,
+    pub document_count: usize,
+    pub received_at: String,
+    /// First day an out-of-office contact is back, when the auto-reply says
+    #[serde(default)]
+    pub return_date: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyClassificationPayload {
+    pub category: elementa_models::ReplyClassification,
+    pub confidence: f64,
+    pub method: String,
+}
+
+impl From<elementa_models::ReplyClassifiedEvent> for ReplyClassifiedEvent {
+    fn from(event: elementa_models::ReplyClassifiedEvent) -> Self {
+        Self {
+            email_id: event.email_id,
+            supplier_id: event.supplier_id,
+            thread_id: event.thread_id,
+            classification: ReplyClassificationPayload {
+                category: event.classification.category,
+                confidence: event.classification.confidence,
+                method: event.classification.method,
+            },
+            document_count: event.document_count,
+            received_at: event.received_at.to_rfc3339(),
+            return_date: event.return_date,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplyHandledResponse {
+    pub workflows_updated: usize,
+    pub tasks_scheduled: usize,
+    pub tasks_skipped: usize,
+    pub tasks_rescheduled: usize,
+    pub escalations_created: usize,
+}
+
+async fn reply_classified(
+    State(service): State<WorkflowService>,
+    Json(event): Json<ReplyClassifiedEvent>,
+) -> Result<Json<ReplyHandledResponse>, (StatusCode, String)> {
+    info!(
+        "Reply {} from supplier {} classified as {:?} ({:.2}, {})",
+        event.email_id, event.supplier_id, event.classification.category,
+        event.classification.confidence, event.classification.method,
+    );
+    
+    let handled = service.handle_reply(event).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+/// Supplier or one of its addresses put on the email suppression list
+#[derive(Debug, Deserialize)]
+pub struct SupplierSuppressedEvent {
+    pub supplier_id: Uuid,
+    pub email_address: Option<String>,
+    pub reason: String,
+    pub source: elementa_models::SuppressionSource,
+    pub suppressed_at: String,
+}
+
+impl From<elementa_models::SupplierSuppressedEvent> for SupplierSuppressedEvent {
+    fn from(event: elementa_models::SupplierSuppressedEvent) -> Self {
+        Self {
+            supplier_id: event.supplier_id,
+            email_address: event.email_address,
+            reason: event.reason,
+            source: event.source,
+            suppressed_at: event.suppressed_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuppressionHandledResponse {
+    pub workflows_updated: usize,
+    pub tasks_skipped: usize,
+    pub escalations_created: usize,
+}
+
+async fn supplier_suppressed(
+    State(service): State<WorkflowService>,
+    Json(event): Json<SupplierSuppressedEvent>,
+) -> Result<Json<SuppressionHandledResponse>, (StatusCode, String)> {
+    info!(
+        "Supplier {} suppressed via {:?}: {}",
+        event.supplier_id, event.source, event.reason,
+    );
+    
+    let handled = service.handle_suppression(event).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+/// Supplier has not answered the email service's follow-ups
+#[derive(Debug, Deserialize)]
+pub struct SupplierUnresponsiveEvent {
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    pub campaign_id: Option<Uuid>,
+    pub unanswered_follow_ups: usize,
+    pub last_sent_at: String,
+}
+
+impl From<elementa_models::SupplierUnresponsiveEvent> for SupplierUnresponsiveEvent {
+    fn from(event: elementa_models::SupplierUnresponsiveEvent) -> Self {
+        Self {
+            supplier_id: event.supplier_id,
+            thread_id: event.thread_id,
+            campaign_id: event.campaign_id,
+            unanswered_follow_ups: event.unanswered_follow_ups,
+            last_sent_at: event.last_sent_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnresponsiveHandledResponse {
+    pub workflows_updated: usize,
+    pub escalations_created: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenewalOutreachResponse {
+    /// Renewal campaign for the certificate
+    pub workflow_id: Uuid,
+    /// False when a renewal campaign for the certificate was already open
+    pub launched: bool,
+}
+
+async fn supplier_unresponsive(
+    State(service): State<WorkflowService>,
+    Json(event): Json<SupplierUnresponsiveEvent>,
+) -> Result<Json<UnresponsiveHandledResponse>, (StatusCode, String)> {
+    info!(
+        "Supplier {} has not answered {} follow-ups in thread {}",
+        event.supplier_id, event.unanswered_follow_ups, event.thread_id,
+    );
+    
+    let handled = service.handle_unresponsive(event).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
+/// Task outcomes reported in bulk by services doing work for the workflow
+#[derive(Debug, Deserialize)]
+pub struct TaskUpdateBatch {
+    pub updates: Vec<TaskStatusUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskStatusUpdate {
+    /// Same key, same update: redelivered updates are acknowledged without being applied again
+    pub idempotency_key: String,
+    pub task_id: Uuid,
+    pub status: TaskUpdateStatus,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// When a failed task may run again; without it the task is exhausted
+    pub retry_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskUpdateStatus {
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskUpdateBatchResponse {
+    pub applied: usize,
+    pub duplicates: usize,
+    pub rejected: usize,
+    /// One per update, in order
+    pub results: Vec<TaskUpdateResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskUpdateResult {
+    pub idempotency_key: String,
+    pub task_id: Uuid,
+    /// `applied`, `duplicate` or `rejected`
+    pub outcome: String,
+    pub error: Option<String>,
+    pub task: Option<TaskResponse>,
+}
+
+async fn task_updates(
+    State(service): State<WorkflowService>,
+    Json(batch): Json<TaskUpdateBatch>,
+) -> Result<Json<TaskUpdateBatchResponse>, (StatusCode, String)> {
+    debug!("Received {} task updates", batch.updates.len());
+    
+    let handled = service.apply_task_updates(batch.updates).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    Ok(Json(handled))
+}
+
 // ===== Escalation Endpoints =====
 
 #[derive(Debug, Serialize)]
@@ -250,20 +1355,94 @@ pub struct EscalationResponse {
     pub reason: String,
     pub severity: String,
     pub created_at: String,
+    /// open, acknowledged or resolved
+    pub state: String,
     pub resolved: bool,
+    pub assignee: Option<String>,
+    pub due_at: String,
+    /// Unresolved past its due date
+    pub overdue: bool,
+    pub acknowledged_at: Option<String>,
     pub resolved_at: Option<String>,
     pub resolution: Option<String>,
+    pub comments: Vec<EscalationCommentResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EscalationCommentResponse {
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EscalationQuery {
+    pub assignee: Option<String>,
+    pub state: Option<String>,
 }
 
 async fn list_escalations(
     State(service): State<WorkflowService>,
+    Query(query): Query<EscalationQuery>,
 ) -> Result<Json<Vec<EscalationResponse>>, (StatusCode, String)> {
-    let escalations = service.list_escalations().await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let escalations = service.list_escalations(&query).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
     
     Ok(Json(escalations))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AcknowledgeEscalationRequest {
+    pub user: String,
+}
+
+async fn acknowledge_escalation(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AcknowledgeEscalationRequest>,
+) -> Result<Json<EscalationResponse>, (StatusCode, String)> {
+    let escalation = service.acknowledge_escalation(id, &request.user).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Escalation not found".to_string()))?;
+    
+    Ok(Json(escalation))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignEscalationRequest {
+    pub assignee: String,
+}
+
+async fn assign_escalation(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignEscalationRequest>,
+) -> Result<Json<EscalationResponse>, (StatusCode, String)> {
+    let escalation = service.assign_escalation(id, &request.assignee).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Escalation not found".to_string()))?;
+    
+    Ok(Json(escalation))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EscalationCommentRequest {
+    pub author: String,
+    pub body: String,
+}
+
+async fn comment_on_escalation(
+    State(service): State<WorkflowService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<EscalationCommentRequest>,
+) -> Result<Json<EscalationResponse>, (StatusCode, String)> {
+    let escalation = service.comment_on_escalation(id, &request.author, &request.body).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Escalation not found".to_string()))?;
+    
+    Ok(Json(escalation))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ResolveEscalationRequest {
     pub resolution: String,
@@ -278,4 +1457,53 @@ async fn resolve_escalation(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
     Ok(Json(escalation))
+}
+
+/// Who new escalations go to; criteria left out match anything
+#[derive(Debug, Deserialize)]
+pub struct RoutingRuleRequest {
+    pub severity: Option<String>,
+    pub client_id: Option<Uuid>,
+    pub relationship: Option<elementa_models::SupplierRelationship>,
+    pub assignee: String,
+    /// Defaults to the severity's due time
+    pub due_hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutingRuleResponse {
+    pub id: Uuid,
+    pub severity: Option<String>,
+    pub client_id: Option<Uuid>,
+    pub relationship: Option<elementa_models::SupplierRelationship>,
+    pub assignee: String,
+    pub due_hours: Option<i64>,
+}
+
+async fn list_routing_rules(
+    State(service): State<WorkflowService>,
+) -> Json<Vec<RoutingRuleResponse>> {
+    Json(service.list_routing_rules().await)
+}
+
+async fn add_routing_rule(
+    State(service): State<WorkflowService>,
+    Json(request): Json<RoutingRuleRequest>,
+) -> Result<Json<RoutingRuleResponse>, (StatusCode, String)> {
+    let rule = service.add_routing_rule(request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    info!("Escalations matching rule {} now go to {}", rule.id, rule.assignee);
+    Ok(Json(rule))
+}
+
+async fn delete_routing_rule(
+    State(service): State<WorkflowService>,
+    Path(rule_id): Path<Uuid>,
+) -> StatusCode {
+    if service.delete_routing_rule(rule_id).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+//! Campaign Metrics
+//!
+//! Funnel and responsiveness figures for a campaign: how many suppliers
+//! were contacted, answered and sent complete data, how quickly they first
+//! answered, how many follow-ups it took and how many had to be escalated.
+//! Rates are percentages of the campaign's suppliers so campaigns of
+//! different sizes compare.
+
+use chrono::Duration;
+
+/// What happened with one supplier in a campaign
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SupplierFunnel {
+    pub contacted: bool,
+    pub responded: bool,
+    pub complete: bool,
+    pub escalated: bool,
+    /// From the first request to the first answer
+    pub response_time: Option<Duration>,
+    /// Follow-ups sent before the first answer, for suppliers that answered
+    pub follow_ups_before_reply: Option<usize>,
+}
+
+/// Suppliers at each step of the campaign
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Funnel {
+    pub suppliers: usize,
+    pub contacted: usize,
+    pub responded: usize,
+    pub complete: usize,
+    pub escalated: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricsSummary {
+    pub response_rate: f64,
+    pub completion_rate: f64,
+    pub escalation_rate: f64,
+    pub average_response_hours: Option<f64>,
+    pub follow_ups_per_response: Option<f64>,
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total > 0 { count as f64 / total as f64 * 100.0 } else { 0.0 }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Funnel and figures of a campaign from its suppliers
+pub fn summarize(suppliers: &[SupplierFunnel]) -> (Funnel, MetricsSummary) {
+    let count = |f: fn(&SupplierFunnel) -> bool| suppliers.iter().filter(|s| f(s)).count();
+    let funnel = Funnel {
+        suppliers: suppliers.len(),
+        contacted: count(|s| s.contacted),
+        responded: count(|s| s.responded),
+        complete: count(|s| s.complete),
+        escalated: count(|s| s.escalated),
+    };
+    let summary = MetricsSummary {
+        response_rate: percent(funnel.responded, funnel.suppliers),
+        completion_rate: percent(funnel.complete, funnel.suppliers),
+        escalation_rate: percent(funnel.escalated, funnel.suppliers),
+        average_response_hours: mean(suppliers.iter()
+            .filter_map(|s| s.response_time)
+            .map(|d| d.num_minutes() as f64 / 60.0)),
+        follow_ups_per_response: mean(suppliers.iter()
+            .filter_map(|s| s.follow_ups_before_reply)
+            .map(|n| n as f64)),
+    };
+    (funnel, summary)
+}
+
+impl MetricsSummary {
+    /// Mean of each figure over campaigns that have it; `None` without campaigns
+    pub fn average(summaries: &[MetricsSummary]) -> Option<Self> {
+        if summaries.is_empty() {
+            return None;
+        }
+        Some(Self {
+            response_rate: mean(summaries.iter().map(|s| s.response_rate))?,
+            completion_rate: mean(summaries.iter().map(|s| s.completion_rate))?,
+            escalation_rate: mean(summaries.iter().map(|s| s.escalation_rate))?,
+            average_response_hours: mean(summaries.iter().filter_map(|s| s.average_response_hours)),
+            follow_ups_per_response: mean(summaries.iter().filter_map(|s| s.follow_ups_per_response)),
+        })
+    }
+
+    /// How far these figures are above `baseline`; figures either lacks are `None`
+    pub fn difference(&self, baseline: &Self) -> Self {
+        let diff = |a: Option<f64>, b: Option<f64>| a.zip(b).map(|(a, b)| a - b);
+        Self {
+            response_rate: self.response_rate - baseline.response_rate,
+            completion_rate: self.completion_rate - baseline.completion_rate,
+            escalation_rate: self.escalation_rate - baseline.escalation_rate,
+            average_response_hours: diff(self.average_response_hours, baseline.average_response_hours),
+            follow_ups_per_response: diff(self.follow_ups_per_response, baseline.follow_ups_per_response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_and_comparison_with_history() {
+        let answered = |hours: i64, follow_ups: usize| SupplierFunnel {
+            contacted: true,
+            responded: true,
+            response_time: Some(Duration::hours(hours)),
+            follow_ups_before_reply: Some(follow_ups),
+            ..SupplierFunnel::default()
+        };
+        let silent = SupplierFunnel { contacted: true, escalated: true, ..SupplierFunnel::default() };
+        let (funnel, summary) = summarize(&[
+            SupplierFunnel { complete: true, ..answered(24, 0) },
+            answered(72, 2),
+            silent,
+            SupplierFunnel::default(),
+        ]);
+        assert_eq!(funnel, Funnel { suppliers: 4, contacted: 3, responded: 2, complete: 1, escalated: 1 });
+        assert_eq!((summary.response_rate, summary.completion_rate, summary.escalation_rate), (50.0, 25.0, 25.0));
+        assert_eq!((summary.average_response_hours, summary.follow_ups_per_response), (Some(48.0), Some(1.0)));
+
+        // A past campaign nobody answered has no response time to average
+        let (_, unanswered) = summarize(&[silent]);
+        let history = MetricsSummary::average(&[summary, unanswered]).unwrap();
+        assert_eq!((history.response_rate, history.average_response_hours), (25.0, Some(48.0)));
+        let difference = summary.difference(&history);
+        assert_eq!((difference.response_rate, difference.average_response_hours), (25.0, Some(0.0)));
+        assert!(unanswered.difference(&history).average_response_hours.is_none());
+        assert!(MetricsSummary::average(&[]).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! Supplier Outreach Guard
+//!
+//! Keeps a supplier that sits in several overlapping campaigns from getting
+//! a burst of emails. Outreach and follow-ups to a supplier emailed within
+//! the minimum gap, by any campaign, are pushed back until the gap has
+//! passed. With the coalesce policy, emails due to the same supplier in the
+//! same run go out as one message instead. Emails falling outside the hours
+//! a supplier accepts email wait for its window to open.
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use elementa_models::ContactWindow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutreachPolicy {
+    /// Space emails to a supplier at least the minimum gap apart
+    Stagger,
+    /// Merge emails due together into one; otherwise stagger
+    Coalesce,
+}
+
+impl OutreachPolicy {
+    /// Parse from string
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "stagger" => Some(Self::Stagger),
+            "coalesce" => Some(Self::Coalesce),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutreachGuard {
+    pub policy: OutreachPolicy,
+    /// Least time between two emails to the same supplier; zero turns the guard off
+    pub min_gap: Duration,
+}
+
+impl Default for OutreachGuard {
+    fn default() -> Self {
+        let policy = std::env::var("SUPPLIER_OUTREACH_POLICY").ok()
+            .and_then(|v| OutreachPolicy::from_str(&v))
+            .unwrap_or(OutreachPolicy::Stagger);
+        let gap_hours = std::env::var("SUPPLIER_OUTREACH_GAP_HOURS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(24i64);
+
+        Self { policy, min_gap: Duration::hours(gap_hours.max(0)) }
+    }
+}
+
+/// What to do with an email task that has fallen due
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutreachDecision {
+    Send,
+    /// Leave it scheduled until then
+    Defer(DateTime<Utc>),
+    /// Send it as part of the given task's email
+    JoinWith(Uuid),
+}
+
+impl OutreachGuard {
+    /// Decide on an email task for a supplier last emailed at `last_sent`
+    /// (by any campaign, in flight included), given the task already claimed
+    /// for the supplier in this run, if any
+    pub fn decide(&self, last_sent: Option<DateTime<Utc>>, claimed: Option<Uuid>, now: DateTime<Utc>) -> OutreachDecision {
+        if self.min_gap <= Duration::zero() {
+            return OutreachDecision::Send;
+        }
+        if let Some(primary) = claimed {
+            return match self.policy {
+                OutreachPolicy::Coalesce => OutreachDecision::JoinWith(primary),
+                OutreachPolicy::Stagger => OutreachDecision::Defer(now + self.min_gap),
+            };
+        }
+        match last_sent {
+            Some(at) if now - at < self.min_gap => OutreachDecision::Defer(at + self.min_gap),
+            _ => OutreachDecision::Send,
+        }
+    }
+}
+
+/// Earliest time from `at` on that falls inside a supplier's contact window;
+/// `at` itself when the window is open
+pub fn next_opening(window: &ContactWindow, at: DateTime<Utc>) -> DateTime<Utc> {
+    let offset = Duration::minutes(window.utc_offset_minutes as i64);
+    // Supplier's local time, carried in a UTC timestamp
+    let local = at + offset;
+    let midnight = local.date_naive().and_time(NaiveTime::MIN).and_utc();
+    let (start, end) = (window.start_hour as i64, window.end_hour as i64);
+    let spans = if start < end { vec![(start, end)] } else { vec![(0, end), (start, 24)] };
+
+    for day in (0..8).map(|d| midnight + Duration::days(d)) {
+        if window.weekdays_only && day.weekday().number_from_monday() > 5 {
+            continue;
+        }
+        for (from, to) in &spans {
+            let (from, to) = (day + Duration::hours(*from), day + Duration::hours(*to));
+            if from < to && local < to {
+                return local.max(from) - offset;
+            }
+        }
+    }
+    at
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_emails_wait_for_the_contact_window() {
+        let at = |d, h, min| Utc.with_ymd_and_hms(2026, 10, d, h, min, 0).unwrap();
+        // 09:00-17:00 in UTC+2 on weekdays; 16 October 2026 is a Friday
+        let office = ContactWindow { start_hour: 9, end_hour: 17, utc_offset_minutes: 120, weekdays_only: true };
+        assert_eq!(next_opening(&office, at(16, 10, 30)), at(16, 10, 30));
+        assert_eq!(next_opening(&office, at(16, 5, 0)), at(16, 7, 0));
+        assert_eq!(next_opening(&office, at(16, 15, 0)), at(19, 7, 0));
+
+        // Past midnight, and all day
+        let night = ContactWindow { start_hour: 22, end_hour: 2, utc_offset_minutes: 0, weekdays_only: false };
+        assert_eq!(next_opening(&night, at(16, 1, 0)), at(16, 1, 0));
+        assert_eq!(next_opening(&night, at(16, 12, 0)), at(16, 22, 0));
+        let always = ContactWindow { start_hour: 0, end_hour: 0, utc_offset_minutes: -300, weekdays_only: false };
+        assert_eq!(next_opening(&always, at(17, 3, 0)), at(17, 3, 0));
+    }
+}
@@ -0,0 +1,185 @@
+//! Campaign Phases
+//!
+//! A campaign can be laid out as ordered phases a supplier moves through:
+//! initial outreach, reminders, escalation, a final notice and closeout.
+//! Each phase has its own email template and starts a number of days after
+//! the previous one ended, if its entry criteria still hold for the
+//! supplier; otherwise it is skipped and the supplier moves on to the next.
+//! Workflows without phases keep the flat outreach and follow-up schedule.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::state_machine::TaskType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignPhase {
+    InitialOutreach,
+    Reminder,
+    /// Raises an escalation for the campaign owner; sends nothing
+    Escalation,
+    FinalNotice,
+    /// Ends the supplier's campaign, with a closing email if it has a template
+    Closeout,
+}
+
+impl std::fmt::Display for CampaignPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InitialOutreach => write!(f, "initial_outreach"),
+            Self::Reminder => write!(f, "reminder"),
+            Self::Escalation => write!(f, "escalation"),
+            Self::FinalNotice => write!(f, "final_notice"),
+            Self::Closeout => write!(f, "closeout"),
+        }
+    }
+}
+
+impl CampaignPhase {
+    /// Task that carries out the phase
+    pub fn task_type(&self) -> TaskType {
+        match self {
+            Self::InitialOutreach => TaskType::InitialOutreach,
+            Self::Reminder | Self::FinalNotice => TaskType::FollowUp,
+            Self::Escalation => TaskType::Escalation,
+            Self::Closeout => TaskType::Closeout,
+        }
+    }
+
+    /// Phase a task of a workflow without phases stands for
+    pub fn of_task(task_type: TaskType) -> Option<Self> {
+        match task_type {
+            TaskType::InitialOutreach => Some(Self::InitialOutreach),
+            TaskType::FollowUp => Some(Self::Reminder),
+            TaskType::Escalation => Some(Self::Escalation),
+            TaskType::Closeout => Some(Self::Closeout),
+            _ => None,
+        }
+    }
+
+    pub fn base_priority(&self) -> i32 {
+        match self {
+            Self::InitialOutreach => 100,
+            Self::Reminder => 80,
+            Self::FinalNotice => 75,
+            Self::Escalation => 70,
+            Self::Closeout => 50,
+        }
+    }
+
+    fn default_entry(&self) -> EntryCriteria {
+        match self {
+            Self::InitialOutreach | Self::Closeout => EntryCriteria::Always,
+            Self::Reminder => EntryCriteria::NoReply,
+            Self::Escalation | Self::FinalNotice => EntryCriteria::DataIncomplete,
+        }
+    }
+}
+
+/// When a phase applies to a supplier, checked as it falls due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryCriteria {
+    Always,
+    /// The supplier has not replied yet
+    NoReply,
+    /// The supplier has not said all its data is in
+    DataIncomplete,
+}
+
+impl EntryCriteria {
+    pub fn is_met(&self, responded: bool, data_complete: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::NoReply => !responded,
+            Self::DataIncomplete => !data_complete,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseConfig {
+    pub phase: CampaignPhase,
+    /// Email template; required for outreach, reminders and the final notice
+    #[serde(default)]
+    pub template_id: Option<String>,
+    /// Days after the previous phase ended
+    #[serde(default)]
+    pub after_days: i32,
+    /// Defaults to no reply for reminders, incomplete data for escalation
+    /// and the final notice, and always otherwise
+    #[serde(default)]
+    pub entry: Option<EntryCriteria>,
+}
+
+impl PhaseConfig {
+    pub fn entry(&self) -> EntryCriteria {
+        self.entry.unwrap_or_else(|| self.phase.default_entry())
+    }
+}
+
+/// Phases must start with initial outreach, keep their order (reminders
+/// may repeat) and name a template wherever an email goes out
+pub fn validate_phases(phases: &[PhaseConfig]) -> Result<()> {
+    let Some(first) = phases.first() else {
+        return Ok(());
+    };
+    if first.phase != CampaignPhase::InitialOutreach {
+        bail!("The first phase must be initial_outreach");
+    }
+    for (previous, next) in phases.iter().zip(phases.iter().skip(1)) {
+        if next.phase < previous.phase || (next.phase == previous.phase && next.phase != CampaignPhase::Reminder) {
+            bail!("Phase {} cannot follow {}", next.phase, previous.phase);
+        }
+    }
+    for phase in phases {
+        if phase.after_days < 0 {
+            bail!("Phase {} cannot start before the previous one", phase.phase);
+        }
+        let template = phase.template_id.as_deref().is_some_and(|t| !t.trim().is_empty());
+        match phase.phase {
+            CampaignPhase::InitialOutreach | CampaignPhase::Reminder | CampaignPhase::FinalNotice if !template => {
+                bail!("Phase {} needs an email template", phase.phase);
+            }
+            CampaignPhase::Escalation if phase.template_id.is_some() => {
+                bail!("The escalation phase sends no email");
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(phase: CampaignPhase, template_id: Option<&str>) -> PhaseConfig {
+        PhaseConfig { phase, template_id: template_id.map(str::to_string), after_days: 7, entry: None }
+    }
+
+    #[test]
+    fn test_phases_keep_their_order_and_templates() {
+        let plan = vec![
+            phase(CampaignPhase::InitialOutreach, Some("initial_outreach")),
+            phase(CampaignPhase::Reminder, Some("follow_up")),
+            phase(CampaignPhase::Reminder, Some("follow_up")),
+            phase(CampaignPhase::Escalation, None),
+            phase(CampaignPhase::FinalNotice, Some("final_notice")),
+            phase(CampaignPhase::Closeout, None),
+        ];
+        assert!(validate_phases(&plan).is_ok());
+        assert!(validate_phases(&[]).is_ok());
+        assert!(validate_phases(&plan[1..]).is_err());
+        assert!(validate_phases(&[plan[0].clone(), plan[4].clone(), plan[1].clone()]).is_err());
+        assert!(validate_phases(&[plan[0].clone(), phase(CampaignPhase::FinalNotice, None)]).is_err());
+        assert!(validate_phases(&[plan[0].clone(), phase(CampaignPhase::Escalation, Some("follow_up"))]).is_err());
+
+        // Reminders stop once the supplier replies; the final notice only once its data is in
+        assert!(!plan[1].entry().is_met(true, false));
+        assert!(plan[4].entry().is_met(true, false));
+        assert!(!plan[4].entry().is_met(true, true));
+        assert!(plan[5].entry().is_met(true, true));
+    }
+}
@@ -0,0 +1,154 @@
+//! Campaign Recurrence
+//!
+//! Cron expressions for campaigns that repeat, e.g. `0 9 1 1,4,7,10 *` for
+//! a quarterly refresh on the first of the quarter at 09:00 UTC. The five
+//! fields are minute, hour, day of month, month and day of week (0 is
+//! Sunday); each takes `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`,
+//! or a comma-separated list of these. As in cron, when both day fields are
+//! restricted a day matching either one runs.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc};
+
+/// How far ahead to look for the next run before giving up on an expression
+const SEARCH_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    /// Whether each day field was anything other than `*`
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+/// Values of one field within `min..=max`
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).context("Invalid step")?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (start.parse()?, end.parse()?),
+                // A bare value with a step runs from the value to the end of the field
+                None if step > 1 => (range.parse()?, max),
+                None => {
+                    let value = range.parse()?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            bail!("{} is outside {}-{}", part, min, max);
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            bail!("Cron expression needs five fields: minute hour day-of-month month day-of-week");
+        };
+        let field = |value: &str, name: &str, min: u32, max: u32| {
+            parse_field(value, min, max).with_context(|| format!("Invalid {} field '{}'", name, value))
+        };
+
+        // Sunday may be written as 7
+        let mut days_of_week: Vec<u32> = field(dow, "day-of-week", 0, 7)?.into_iter().map(|d| d % 7).collect();
+        days_of_week.sort_unstable();
+        days_of_week.dedup();
+
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: field(minute, "minute", 0, 59)?,
+            hours: field(hour, "hour", 0, 23)?,
+            days_of_month: field(dom, "day-of-month", 1, 31)?,
+            months: field(month, "month", 1, 12)?,
+            days_of_week,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    fn runs_on(&self, day: DateTime<Utc>) -> bool {
+        if !self.months.contains(&day.month()) {
+            return false;
+        }
+        let dom = self.days_of_month.contains(&day.day());
+        let dow = self.days_of_week.contains(&day.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// First run strictly after `after`, to the minute; `None` if the
+    /// expression never matches, e.g. the 31st of February
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let midnight = start.date_naive().and_time(NaiveTime::MIN).and_utc();
+
+        for offset in 0..SEARCH_DAYS {
+            let day = midnight + Duration::days(offset);
+            if !self.runs_on(day) {
+                continue;
+            }
+            for hour in &self.hours {
+                for minute in &self.minutes {
+                    let at = day + Duration::hours(*hour as i64) + Duration::minutes(*minute as i64);
+                    if at >= start {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_run_of_cron_expressions() {
+        let at = |y, m, d, h, min| Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+
+        // Quarterly refresh
+        let quarterly = CronSchedule::parse("0 9 1 1,4,7,10 *").unwrap();
+        assert_eq!(quarterly.next_after(at(2026, 2, 14, 12, 0)), Some(at(2026, 4, 1, 9, 0)));
+        assert_eq!(quarterly.next_after(at(2026, 4, 1, 9, 0)), Some(at(2026, 7, 1, 9, 0)));
+
+        // Annual TSCA cycle, and steps and weekday ranges
+        assert_eq!(CronSchedule::parse("30 6 15 3 *").unwrap().next_after(at(2026, 3, 15, 7, 0)), Some(at(2027, 3, 15, 6, 30)));
+        assert_eq!(CronSchedule::parse("*/20 8 * * 1-5").unwrap().next_after(at(2026, 10, 16, 8, 45)), Some(at(2026, 10, 19, 8, 0)));
+
+        // Either day field matches when both are restricted
+        assert_eq!(CronSchedule::parse("0 0 13 * 5").unwrap().next_after(at(2026, 10, 10, 0, 0)), Some(at(2026, 10, 13, 0, 0)));
+
+        assert!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(at(2026, 1, 1, 0, 0)).is_none());
+        assert!(CronSchedule::parse("0 9 1 *").is_err());
+        assert!(CronSchedule::parse("0 25 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Recurrence Job
+//!
+//! Periodically launches the next cycle of campaign templates whose
+//! recurrence has fallen due.
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+use crate::service::WorkflowService;
+
+/// How often due cycles are looked for, `RECURRENCE_CHECK_SECS` (default one minute)
+fn check_interval() -> std::time::Duration {
+    let secs = std::env::var("RECURRENCE_CHECK_SECS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    std::time::Duration::from_secs(secs)
+}
+
+pub fn spawn_recurrence_job(service: WorkflowService) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = check_interval();
+        info!("Recurring campaign checks every {:?}", interval);
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            for workflow in service.launch_due_campaigns(Utc::now()).await {
+                info!("Launched scheduled campaign {} ({}) as {}", workflow.id, workflow.campaign_name, workflow.status);
+            }
+        }
+    })
+}
@@ -3,6 +3,7 @@
 //! Handles task scheduling, follow-up timing, and deadline management.
 
 use chrono::{DateTime, Duration, Utc};
+use elementa_models::SupplierRelationship;
 use uuid::Uuid;
 
 use crate::state_machine::TaskType;
@@ -18,6 +19,16 @@ pub struct ScheduledTask {
     pub task_type: TaskType,
     pub scheduled_at: DateTime<Utc>,
     pub priority: i32,
+    /// Tasks that must complete before this one runs
+    pub depends_on: Vec<Uuid>,
+}
+
+impl ScheduledTask {
+    /// Run only once `task` has completed
+    pub fn after(mut self, task: Uuid) -> Self {
+        self.depends_on.push(task);
+        self
+    }
 }
 
 /// Scheduler for workflow tasks
@@ -47,6 +58,7 @@ impl WorkflowScheduler {
                 task_type: TaskType::InitialOutreach,
                 scheduled_at: now + Duration::minutes(delay_minutes),
                 priority: 100, // High priority for initial outreach
+                depends_on: Vec::new(),
             }
         }).collect()
     }
@@ -67,9 +79,17 @@ impl WorkflowScheduler {
             task_type: TaskType::FollowUp,
             scheduled_at,
             priority: 80 - (follow_up_number * 10), // Lower priority for later follow-ups
+            depends_on: Vec::new(),
         })
     }
     
+    /// When follow-ups would go out for outreach sent at `outreach_at`
+    pub fn project_follow_ups(&self, outreach_at: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        (0..self.config.max_follow_ups.max(0))
+            .map(|n| outreach_at + Duration::days((self.config.follow_up_interval_days * (n + 1)) as i64))
+            .collect()
+    }
+    
     /// Schedule document processing task
     pub fn schedule_document_processing(&self, workflow_id: Uuid, supplier_id: Uuid) -> ScheduledTask {
         ScheduledTask {
@@ -79,6 +99,7 @@ impl WorkflowScheduler {
             task_type: TaskType::DocumentProcessing,
             scheduled_at: Utc::now(), // Immediate processing
             priority: 90,
+            depends_on: Vec::new(),
         }
     }
     
@@ -91,6 +112,7 @@ impl WorkflowScheduler {
             task_type: TaskType::Validation,
             scheduled_at: Utc::now() + Duration::minutes(5), // Small delay after processing
             priority: 85,
+            depends_on: Vec::new(),
         }
     }
     
@@ -107,6 +129,43 @@ impl WorkflowScheduler {
             && follow_up_count >= self.config.max_follow_ups
     }
     
+    /// Escalation severity from the time left: critical once the deadline has
+    /// passed, high in the second half of the warning window, medium in the
+    /// first half and low before it
+    pub fn deadline_severity(&self, deadline: DateTime<Utc>, now: DateTime<Utc>) -> DeadlineRisk {
+        let hours_remaining = (deadline - now).num_hours();
+        let window_hours = self.config.deadline_warning_days as i64 * 24;
+        
+        if hours_remaining <= 0 {
+            DeadlineRisk::Critical
+        } else if hours_remaining * 2 <= window_hours {
+            DeadlineRisk::High
+        } else if hours_remaining <= window_hours {
+            DeadlineRisk::Medium
+        } else {
+            DeadlineRisk::Low
+        }
+    }
+    
+    /// Priority of a task from its type's base priority, raised for key
+    /// suppliers and as the deadline comes closer
+    pub fn task_priority(&self, base: i32, relationship: Option<&SupplierRelationship>, risk: DeadlineRisk) -> i32 {
+        let relationship_boost = match relationship {
+            Some(SupplierRelationship::Strategic) => 30,
+            Some(SupplierRelationship::Preferred) => 15,
+            Some(SupplierRelationship::AtRisk) => 10,
+            Some(SupplierRelationship::NewVendor) => 5,
+            Some(SupplierRelationship::Standard) | None => 0,
+        };
+        let deadline_boost = match risk {
+            DeadlineRisk::Critical => 40,
+            DeadlineRisk::High => 25,
+            DeadlineRisk::Medium => 10,
+            DeadlineRisk::Low => 0,
+        };
+        base + relationship_boost + deadline_boost
+    }
+    
     /// Schedule escalation task
     pub fn schedule_escalation(&self, workflow_id: Uuid, supplier_id: Uuid) -> ScheduledTask {
         ScheduledTask {
@@ -116,6 +175,7 @@ impl WorkflowScheduler {
             task_type: TaskType::Escalation,
             scheduled_at: Utc::now(), // Immediate escalation
             priority: 100, // Highest priority
+            depends_on: Vec::new(),
         }
     }
     
@@ -141,8 +201,8 @@ impl WorkflowScheduler {
     }
 }
 
-/// Deadline risk levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Deadline risk levels, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[allow(dead_code)]
 pub enum DeadlineRisk {
     Low,
@@ -162,6 +222,19 @@ impl std::fmt::Display for DeadlineRisk {
     }
 }
 
+impl DeadlineRisk {
+    /// Parse an escalation severity
+    pub fn from_severity(severity: &str) -> Option<Self> {
+        match severity {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
 impl Default for WorkflowScheduler {
     fn default() -> Self {
         Self::new(WorkflowConfig::default())
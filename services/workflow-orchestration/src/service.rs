@@ -3,19 +3,82 @@
 //! Core workflow orchestration logic.
 
 use anyhow::{Context, Result, bail};
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use elementa_database::{
+    ComplianceRepository, ComplianceStore, PostgresPools, SupplierRepository, SupplierStore, WorkflowRepository,
+};
+use elementa_models::{
+    CertificationExpiringEvent, ComplianceHistoryEntry, ContactInfo, ContactWindow, DomainEvent, EventEnvelope,
+    ReplyClassification, SupplierRecord, SupplierRelationship, WorkflowInstance, WorkflowStatus,
+};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::warn;
 use uuid::Uuid;
 
-use crate::state_machine::{WorkflowState, TaskState, TaskType};
-use crate::scheduler::WorkflowScheduler;
+use crate::audit_client::{AuditClient, AuditRecord};
+use crate::campaign_templates::{validate_config, CampaignTemplate, CampaignTemplateRegistry, Recurrence, CERTIFICATION_RENEWAL};
+use crate::email_client::{EmailClient, TaskEmail};
+use crate::escalation_routing::{EscalationRouter, RoutingContext, RoutingRule};
+use crate::forecast::{ResponseHistory, SupplierOutlook};
+use crate::metrics::{self, MetricsSummary, SupplierFunnel};
+use crate::recurrence::CronSchedule;
+use crate::outreach_guard::{next_opening, OutreachDecision, OutreachGuard};
+use crate::phases::{validate_phases, CampaignPhase, PhaseConfig};
+use crate::state_machine::{
+    creates_cycle, dependency_status, DependencyStatus, EscalationState, SupplierStatus, WorkflowState, TaskState, TaskType,
+};
+use crate::scheduler::{DeadlineRisk, ScheduledTask, WorkflowScheduler};
+use crate::sla::{SlaCheck, SlaPolicy, SlaStatus, SupplierTimeline};
 use crate::{
-    CreateWorkflowRequest, WorkflowConfig, WorkflowResponse, WorkflowProgress,
-    TaskResponse, EscalationResponse,
+    CreateWorkflowRequest, CloneWorkflowRequest, WorkflowConfig, WorkflowResponse, WorkflowProgress,
+    TaskResponse, EscalationResponse, ReplyClassifiedEvent, ReplyHandledResponse,
+    SupplierSuppressedEvent, SuppressionHandledResponse, SupplierUnresponsiveEvent, UnresponsiveHandledResponse,
+    CampaignTemplateRequest, CampaignTemplateResponse, LaunchCampaignRequest, TaskEdge, TaskGraphResponse,
+    EmailSentEvent, DocumentExtractedEvent, EventHandledResponse, ProgressUpdatedResponse, SupplierProgress, SlaItemResponse, SlaReportResponse,
+    EscalationQuery, EscalationCommentResponse, RoutingRuleRequest, RoutingRuleResponse,
+    TimelineResponse, SupplierTimelineResponse, TimelineEntry, DeadLetterResponse,
+    ForecastResponse, SupplierForecastResponse, RecurrenceRequest, RecurrenceResponse,
+    CancelWorkflowRequest, CancellationResponse, CreateManualTaskRequest, ManualTaskQuery, ManualTaskResponse,
+    MetricsResponse, FunnelResponse, MetricsSummaryResponse, ClientHistoryResponse,
+    PhaseStatusResponse, SupplierPhaseResponse, UpdateWorkflowConfigRequest, ConfigVersionResponse,
+    TaskStatusUpdate, TaskUpdateStatus, TaskUpdateBatchResponse, TaskUpdateResult, StalledWorkflowResponse,
+    RenewalOutreachResponse,
 };
 
+/// Reason prefix of escalations raised for unanswered follow-ups
+const NO_RESPONSE_REASON: &str = "No response";
+
+/// Reason prefix of escalations raised for suppliers silent close to the deadline
+const DEADLINE_REASON: &str = "Deadline";
+
+/// Reason prefix of escalations raised for campaigns that stopped moving
+const STALLED_REASON: &str = "Campaign stalled";
+
+/// Campaign name prefix of certification renewal outreach
+const RENEWAL_CAMPAIGN_PREFIX: &str = "Certification renewal";
+
+/// Confidence from which a reply classified as a question is escalated;
+/// below it the classifier only guessed, e.g. a reply with no clear intent
+const QUESTION_ESCALATION_CONFIDENCE: f64 = 0.5;
+
+/// How late a due task may be before the executor counts as having missed it
+const MISSED_TASK_GRACE_HOURS: i64 = 1;
+
+/// Most task updates accepted in one batch
+const MAX_TASK_UPDATES: usize = 500;
+
+/// How long an idempotency key is remembered; redeliveries come within minutes
+const IDEMPOTENCY_RETENTION_DAYS: i64 = 7;
+
+/// Task update applied under an idempotency key
+#[derive(Debug, Clone)]
+struct AppliedUpdate {
+    task_id: Uuid,
+    applied_at: DateTime<Utc>,
+}
+
 /// Stored workflow
 #[derive(Debug, Clone)]
 struct StoredWorkflow {
@@ -24,11 +87,131 @@ struct StoredWorkflow {
     campaign_name: String,
     suppliers: Vec<Uuid>,
     state: WorkflowState,
-    #[allow(dead_code)]
     config: WorkflowConfig,
     start_date: DateTime<Utc>,
     deadline: DateTime<Utc>,
     progress: WorkflowProgress,
+    responded: HashSet<Uuid>,
+    /// Suppliers that opted out of automated email
+    manual: HashSet<Uuid>,
+    /// Email and document events per supplier
+    activity: HashMap<Uuid, SupplierActivity>,
+    /// Workflow this one was cloned from
+    cloned_from: Option<Uuid>,
+    /// Supplier relationships looked up at creation, for task priority
+    relationships: HashMap<Uuid, SupplierRelationship>,
+    /// Hours suppliers accept email, looked up at creation
+    contact_windows: HashMap<Uuid, ContactWindow>,
+    /// Every version of `config`, oldest first; the last is the one in use
+    config_versions: Vec<ConfigVersion>,
+}
+
+/// A workflow's config as set at creation or changed mid-campaign
+#[derive(Debug, Clone)]
+struct ConfigVersion {
+    config: WorkflowConfig,
+    changed_by: Option<String>,
+    changed_at: DateTime<Utc>,
+    reason: Option<String>,
+    changes: Vec<String>,
+}
+
+/// Fields of `after` that differ from `before`, as `field: old -> new`
+fn config_changes(before: &WorkflowConfig, after: &WorkflowConfig) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    after.iter()
+        .filter(|(field, value)| before.get(*field) != Some(*value))
+        .map(|(field, value)| match before.get(field) {
+            Some(old) => format!("{}: {} -> {}", field, old, value),
+            None => format!("{}: {}", field, value),
+        })
+        .collect()
+}
+
+/// What other services have reported about a supplier in one workflow
+#[derive(Debug, Clone, Default)]
+struct SupplierActivity {
+    emails_sent: usize,
+    last_sent_at: Option<DateTime<Utc>>,
+    replies: usize,
+    first_reply_at: Option<DateTime<Utc>>,
+    last_reply_at: Option<DateTime<Utc>>,
+    documents_extracted: usize,
+    cas_numbers_found: usize,
+    needs_review: bool,
+    /// The supplier said its data is all in
+    data_complete: bool,
+}
+
+impl StoredWorkflow {
+    /// Whether an event about a supplier applies: the campaign it names,
+    /// otherwise every active workflow the supplier is in
+    fn receives(&self, supplier_id: Uuid, campaign_id: Option<Uuid>) -> bool {
+        self.state == WorkflowState::Active
+            && self.suppliers.contains(&supplier_id)
+            && campaign_id.is_none_or(|id| id == self.id)
+    }
+}
+
+/// Suppliers reached by outreach or by any email the email service reports
+fn contacted_suppliers(workflow: &StoredWorkflow, reached_by_outreach: HashSet<Uuid>) -> HashSet<Uuid> {
+    let mut contacted = reached_by_outreach;
+    contacted.extend(workflow.activity.iter().filter(|(_, a)| a.emails_sent > 0).map(|(id, _)| *id));
+    contacted
+}
+
+/// What happened with each of a workflow's suppliers, from the workflow's
+/// tasks and the events reported for it
+fn supplier_funnels(workflow: &StoredWorkflow, tasks: &[&StoredTask], escalated: &HashSet<(Uuid, Uuid)>) -> Vec<SupplierFunnel> {
+    let reached = tasks.iter()
+        .filter(|t| t.task_type == TaskType::InitialOutreach && t.state == TaskState::Completed)
+        .map(|t| t.supplier_id)
+        .collect();
+    let contacted = contacted_suppliers(workflow, reached);
+    
+    workflow.suppliers.iter().map(|supplier_id| {
+        let emails = || tasks.iter().filter(|t| {
+            t.supplier_id == *supplier_id
+                && t.state == TaskState::Completed
+                && matches!(t.task_type, TaskType::InitialOutreach | TaskType::FollowUp)
+        });
+        let was_contacted = contacted.contains(supplier_id);
+        // Emails only reported by the email service count from the start of the campaign
+        let first_asked = emails().filter_map(|t| t.completed_at).min()
+            .or(was_contacted.then_some(workflow.start_date));
+        let first_reply = workflow.activity.get(supplier_id).and_then(|a| a.first_reply_at);
+        
+        SupplierFunnel {
+            contacted: was_contacted,
+            responded: workflow.responded.contains(supplier_id),
+            complete: workflow.activity.get(supplier_id).is_some_and(|a| a.data_complete),
+            escalated: escalated.contains(&(workflow.id, *supplier_id)),
+            response_time: first_reply.zip(first_asked).map(|(reply, asked)| (reply - asked).max(Duration::zero())),
+            follow_ups_before_reply: first_reply.map(|reply| {
+                emails().filter(|t| t.task_type == TaskType::FollowUp && t.completed_at.is_some_and(|at| at < reply)).count()
+            }),
+        }
+    }).collect()
+}
+
+fn summary_response(summary: &MetricsSummary) -> MetricsSummaryResponse {
+    MetricsSummaryResponse {
+        response_rate: summary.response_rate,
+        completion_rate: summary.completion_rate,
+        escalation_rate: summary.escalation_rate,
+        average_response_hours: summary.average_response_hours,
+        follow_ups_per_response: summary.follow_ups_per_response,
+    }
+}
+
+fn event_time(timestamp: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(timestamp)
+        .with_context(|| format!("Invalid event timestamp {}", timestamp))?
+        .with_timezone(&Utc))
 }
 
 /// Stored task
@@ -46,6 +229,120 @@ struct StoredTask {
     completed_at: Option<DateTime<Utc>>,
     error: Option<String>,
     result: Option<serde_json::Value>,
+    /// Tasks that must complete first
+    depends_on: Vec<Uuid>,
+    /// Priority of the task type
+    base_priority: i32,
+    /// Base priority raised for the supplier relationship and deadline; refreshed on every claim
+    priority: i32,
+    /// Overrides for the next run, given when a dead-lettered task is requeued
+    payload: Option<serde_json::Value>,
+    /// Assignment of a manual task; `None` for tasks the executor runs
+    manual: Option<ManualWork>,
+    /// Index into the workflow's phases; `None` for workflows without phases
+    phase: Option<usize>,
+}
+
+/// Human work a manual task stands for
+#[derive(Debug, Clone)]
+struct ManualWork {
+    assignee: Option<String>,
+    instructions: String,
+    due_at: Option<DateTime<Utc>>,
+    notes: Option<String>,
+    completed_by: Option<String>,
+}
+
+impl StoredTask {
+    fn scheduled(st: ScheduledTask) -> Self {
+        Self {
+            id: st.id,
+            workflow_id: st.workflow_id,
+            supplier_id: st.supplier_id,
+            task_type: st.task_type,
+            state: TaskState::Scheduled,
+            retry_count: 0,
+            max_retries: 3,
+            scheduled_at: Some(st.scheduled_at),
+            started_at: None,
+            completed_at: None,
+            error: None,
+            result: None,
+            depends_on: st.depends_on,
+            base_priority: st.priority,
+            priority: st.priority,
+            payload: None,
+            manual: None,
+            phase: None,
+        }
+    }
+    
+    /// Task for phase `index` of a workflow's phases, due `after_days` from `from`
+    fn for_phase(workflow_id: Uuid, supplier_id: Uuid, phases: &[PhaseConfig], index: usize, from: DateTime<Utc>) -> Option<Self> {
+        let config = phases.get(index)?;
+        Some(Self {
+            phase: Some(index),
+            ..Self::scheduled(ScheduledTask {
+                id: Uuid::new_v4(),
+                workflow_id,
+                supplier_id,
+                task_type: config.phase.task_type(),
+                scheduled_at: from + Duration::days(config.after_days as i64),
+                priority: config.phase.base_priority(),
+                depends_on: Vec::new(),
+            })
+        })
+    }
+}
+
+/// Task claimed by the executor, with what it needs to run it
+#[derive(Debug, Clone)]
+pub struct DueTask {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    pub supplier_id: Uuid,
+    pub task_type: TaskType,
+    /// 1 on the first run
+    pub attempt: i32,
+    pub max_retries: i32,
+    pub campaign_name: String,
+    pub deadline: DateTime<Utc>,
+    /// Thread of the latest outreach email to the supplier in this workflow
+    pub thread_id: Option<String>,
+    /// Email template for outreach, follow-up and closeout tasks, from the workflow's config or phase
+    pub email_template_id: Option<String>,
+    /// Overrides from a requeue: `template_id` and extra template `variables`
+    pub payload: Option<serde_json::Value>,
+    /// Email tasks of other campaigns for the same supplier sent in this one's email
+    pub coalesced: Vec<CoalescedTask>,
+}
+
+/// Email task folded into another task's email to the same supplier
+#[derive(Debug, Clone)]
+pub struct CoalescedTask {
+    pub id: Uuid,
+    pub campaign_name: String,
+    pub deadline: DateTime<Utc>,
+}
+
+/// What claiming needs from an active workflow
+struct ActiveWorkflow {
+    campaign_name: String,
+    deadline: DateTime<Utc>,
+    config: WorkflowConfig,
+    relationships: HashMap<Uuid, SupplierRelationship>,
+    contact_windows: HashMap<Uuid, ContactWindow>,
+    /// For phase entry criteria
+    responded: HashSet<Uuid>,
+    data_complete: HashSet<Uuid>,
+}
+
+/// Outcome of one escalation check
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EscalationSweep {
+    pub escalations_created: usize,
+    /// Open escalations whose severity went up as the deadline came closer
+    pub escalations_raised: usize,
 }
 
 /// Stored escalation
@@ -57,9 +354,26 @@ struct StoredEscalation {
     reason: String,
     severity: String,
     created_at: DateTime<Utc>,
-    resolved: bool,
+    state: EscalationState,
+    assignee: Option<String>,
+    due_at: DateTime<Utc>,
+    acknowledged_at: Option<DateTime<Utc>>,
     resolved_at: Option<DateTime<Utc>>,
     resolution: Option<String>,
+    comments: Vec<EscalationComment>,
+}
+
+impl StoredEscalation {
+    fn is_open(&self) -> bool {
+        self.state != EscalationState::Resolved
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EscalationComment {
+    author: String,
+    body: String,
+    created_at: DateTime<Utc>,
 }
 
 /// Workflow service
@@ -70,6 +384,22 @@ pub struct WorkflowService {
     escalations: Arc<RwLock<HashMap<Uuid, StoredEscalation>>>,
     #[allow(dead_code)]
     scheduler: Arc<WorkflowScheduler>,
+    campaign_templates: Arc<CampaignTemplateRegistry>,
+    /// Contact details for outreach; `None` without a database
+    suppliers: Option<Arc<dyn SupplierStore>>,
+    /// Certifications to watch for expiry; `None` without a database
+    compliance: Option<Arc<dyn ComplianceStore>>,
+    /// Agent task queue shared by the executors; `None` without a database
+    queue: Option<Arc<WorkflowRepository>>,
+    sla: SlaPolicy,
+    outreach_guard: OutreachGuard,
+    escalation_router: Arc<EscalationRouter>,
+    /// Where state changes are recorded; `None` when not configured
+    audit: Option<Arc<AuditClient>>,
+    /// Reads the suppression list and sends withdrawal notices; `None` when not configured
+    email: Option<Arc<EmailClient>>,
+    /// Task updates by idempotency key
+    applied_updates: Arc<RwLock<HashMap<String, AppliedUpdate>>>,
 }
 
 impl WorkflowService {
@@ -79,22 +409,99 @@ impl WorkflowService {
             tasks: Arc::new(RwLock::new(HashMap::new())),
             escalations: Arc::new(RwLock::new(HashMap::new())),
             scheduler: Arc::new(WorkflowScheduler::default()),
+            campaign_templates: Arc::new(CampaignTemplateRegistry::default()),
+            suppliers: None,
+            compliance: None,
+            queue: None,
+            sla: SlaPolicy::default(),
+            outreach_guard: OutreachGuard::default(),
+            escalation_router: Arc::new(EscalationRouter::default()),
+            audit: None,
+            email: None,
+            applied_updates: Arc::new(RwLock::new(HashMap::new())),
         }
     }
     
+    /// Read supplier contacts and certifications from Postgres, and run
+    /// tasks through the agent task queue there
+    pub fn with_database(self, pools: PostgresPools) -> Self {
+        let mut service = self.with_stores(
+            Arc::new(SupplierRepository::with_pools(pools.clone())),
+            Arc::new(ComplianceRepository::with_pools(pools.clone())),
+        );
+        service.queue = Some(Arc::new(WorkflowRepository::new(pools.primary().clone())));
+        service
+    }
+    
+    /// Agent task queue the executor claims tasks from; `None` without a database
+    pub fn task_queue(&self) -> Option<&WorkflowRepository> {
+        self.queue.as_deref()
+    }
+    
+    /// Read supplier contacts and certifications from the given stores
+    pub fn with_stores(mut self, suppliers: Arc<dyn SupplierStore>, compliance: Arc<dyn ComplianceStore>) -> Self {
+        self.suppliers = Some(suppliers);
+        self.compliance = Some(compliance);
+        self
+    }
+    
+    /// Record state changes in the audit trail
+    pub fn with_audit(mut self, audit: AuditClient) -> Self {
+        self.audit = Some(Arc::new(audit));
+        self
+    }
+    
+    /// Check suppressions and send withdrawal notices through the email service
+    pub fn with_email(mut self, email: EmailClient) -> Self {
+        self.email = Some(Arc::new(email));
+        self
+    }
+    
     /// Create new workflow
     pub async fn create_workflow(&self, request: CreateWorkflowRequest) -> Result<WorkflowResponse> {
+        self.insert_workflow(request, None, WorkflowState::Active).await
+    }
+    
+    /// Start a new campaign with a workflow's suppliers and config and a new
+    /// deadline; `None` if the source workflow does not exist
+    pub async fn clone_workflow(&self, id: Uuid, request: CloneWorkflowRequest) -> Result<Option<WorkflowResponse>> {
+        let Some(source) = self.workflows.read().await.get(&id).cloned() else {
+            return Ok(None);
+        };
+        let supplier_ids = request.supplier_ids.unwrap_or(source.suppliers);
+        if supplier_ids.is_empty() {
+            bail!("At least one supplier is required");
+        }
+        let config = request.config.unwrap_or(source.config);
+        validate_config(&config)?;
+        
+        let workflow = self.insert_workflow(CreateWorkflowRequest {
+            client_id: source.client_id,
+            campaign_name: request.campaign_name.unwrap_or_else(|| format!("{} (copy)", source.campaign_name)),
+            supplier_ids,
+            deadline: request.deadline,
+            config: Some(config),
+        }, Some(id), WorkflowState::Active).await?;
+        
+        Ok(Some(workflow))
+    }
+    
+    async fn insert_workflow(&self, request: CreateWorkflowRequest, cloned_from: Option<Uuid>, state: WorkflowState) -> Result<WorkflowResponse> {
         let config = request.config.unwrap_or_default();
         let deadline = DateTime::parse_from_rfc3339(&request.deadline)
             .context("Invalid deadline format")?
             .with_timezone(&Utc);
+        validate_phases(&config.phases)?;
+        let phased = !config.phases.is_empty();
+        let records = self.supplier_records(&request.supplier_ids).await;
+        let (blocked, contact_windows) = self.outreach_restrictions(&request.supplier_ids, &records).await;
         
         let workflow = StoredWorkflow {
             id: Uuid::new_v4(),
             client_id: request.client_id,
             campaign_name: request.campaign_name,
             suppliers: request.supplier_ids.clone(),
-            state: WorkflowState::Active,
+            state,
             config: config.clone(),
             start_date: Utc::now(),
             deadline,
@@ -104,42 +511,182 @@ impl WorkflowService {
                 responded: 0,
                 complete: 0,
                 escalated: 0,
+                documents_received: 0,
                 percent_complete: 0.0,
             },
+            responded: HashSet::new(),
+            // Suppliers that must not be emailed are handled by hand from the start
+            manual: blocked.keys().copied().collect(),
+            activity: HashMap::new(),
+            cloned_from,
+            relationships: records.iter().map(|(id, supplier)| (*id, supplier.relationship.clone())).collect(),
+            contact_windows,
+            config_versions: vec![ConfigVersion {
+                config: config.clone(),
+                changed_by: None,
+                changed_at: Utc::now(),
+                reason: None,
+                changes: Vec::new(),
+            }],
         };
         
-        // Schedule initial outreach tasks
+        // Queued tasks refer to their workflow's row
+        if let Some(queue) = &self.queue {
+            queue.create(WorkflowInstance {
+                id: workflow.id.into(),
+                client_id: workflow.client_id,
+                campaign_name: workflow.campaign_name.clone(),
+                suppliers: workflow.suppliers.iter().map(|&id| id.into()).collect(),
+                status: WorkflowStatus::InProgress,
+                start_date: workflow.start_date,
+                deadline,
+                ..WorkflowInstance::default()
+            }).await?;
+        }
+        
+        // Schedule initial outreach tasks, each within the supplier's contact window
         let scheduler = WorkflowScheduler::new(config);
-        let scheduled_tasks = scheduler.schedule_initial_outreach(workflow.id, &request.supplier_ids);
+        let reachable: Vec<Uuid> = request.supplier_ids.iter().copied().filter(|id| !blocked.contains_key(id)).collect();
+        let scheduled_tasks = scheduler.schedule_initial_outreach(workflow.id, &reachable);
         
         // Store tasks
         let mut tasks_map = self.tasks.write().await;
-        for st in scheduled_tasks {
-            let task = StoredTask {
-                id: st.id,
-                workflow_id: st.workflow_id,
-                supplier_id: st.supplier_id,
-                task_type: st.task_type,
-                state: TaskState::Scheduled,
-                retry_count: 0,
-                max_retries: 3,
-                scheduled_at: Some(st.scheduled_at),
-                started_at: None,
-                completed_at: None,
-                error: None,
-                result: None,
-            };
+        for mut st in scheduled_tasks {
+            if let Some(window) = workflow.contact_windows.get(&st.supplier_id) {
+                st.scheduled_at = next_opening(window, st.scheduled_at);
+            }
+            // The first phase is always initial outreach
+            let task = StoredTask { phase: phased.then_some(0), ..StoredTask::scheduled(st) };
             tasks_map.insert(task.id, task);
         }
         drop(tasks_map);
         
-        let task_count = request.supplier_ids.len();
+        let task_count = reachable.len();
         
         // Store workflow
         let mut workflows = self.workflows.write().await;
         workflows.insert(workflow.id, workflow.clone());
+        self.audit_workflow(&workflow, None);
+        drop(workflows);
+        
+        if blocked.is_empty() {
+            return Ok(self.to_workflow_response(&workflow, task_count));
+        }
+        let mut blocked: Vec<(Uuid, String)> = blocked.into_iter().collect();
+        blocked.sort();
+        for (supplier_id, reason) in blocked {
+            self.create_escalation(workflow.id, supplier_id, format!("{}; handle manually", reason), "high".to_string()).await?;
+        }
+        
+        let mut workflows = self.workflows.write().await;
+        let workflow = workflows.get_mut(&workflow.id).context("Workflow not found")?;
+        workflow.progress.escalated += workflow.manual.len();
+        Ok(self.to_workflow_response(workflow, task_count))
+    }
+    
+    pub async fn set_campaign_template(&self, id: &str, request: CampaignTemplateRequest) -> Result<CampaignTemplateResponse> {
+        let template = self.campaign_templates.upsert(CampaignTemplate {
+            id: id.to_string(),
+            name: request.name,
+            description: request.description,
+            config: request.config.unwrap_or_default(),
+            response_days: request.response_days,
+            built_in: false,
+            updated_at: Utc::now(),
+            recurrence: None,
+        }).await?;
+        
+        Ok(campaign_template_response(template))
+    }
+    
+    pub async fn get_campaign_template(&self, id: &str) -> Option<CampaignTemplateResponse> {
+        self.campaign_templates.get(id).await.map(campaign_template_response)
+    }
+    
+    pub async fn list_campaign_templates(&self) -> Vec<CampaignTemplateResponse> {
+        self.campaign_templates.list().await.into_iter().map(campaign_template_response).collect()
+    }
+    
+    pub async fn delete_campaign_template(&self, id: &str) -> bool {
+        self.campaign_templates.delete(id).await
+    }
+    
+    /// Create a workflow from a campaign template; `None` if the template does not exist
+    pub async fn launch_campaign(&self, template_id: &str, request: LaunchCampaignRequest) -> Result<Option<WorkflowResponse>> {
+        let Some(template) = self.campaign_templates.get(template_id).await else {
+            return Ok(None);
+        };
+        
+        self.launch_from_template(template, request, WorkflowState::Active).await.map(Some)
+    }
+    
+    async fn launch_from_template(&self, template: CampaignTemplate, request: LaunchCampaignRequest, state: WorkflowState) -> Result<WorkflowResponse> {
+        if request.supplier_ids.is_empty() {
+            bail!("At least one supplier is required");
+        }
         
-        Ok(self.to_workflow_response(&workflow, task_count))
+        let now = Utc::now();
+        self.insert_workflow(CreateWorkflowRequest {
+            client_id: request.client_id,
+            campaign_name: request.campaign_name
+                .unwrap_or_else(|| format!("{} {}", template.name, now.format("%Y-%m-%d"))),
+            supplier_ids: request.supplier_ids,
+            deadline: request.deadline
+                .unwrap_or_else(|| (now + Duration::days(template.response_days as i64)).to_rfc3339()),
+            config: Some(template.config),
+        }, None, state).await
+    }
+    
+    /// Relaunch a template on a cron schedule; `None` if the template does not exist
+    pub async fn set_campaign_recurrence(&self, template_id: &str, request: RecurrenceRequest, now: DateTime<Utc>) -> Result<Option<CampaignTemplateResponse>> {
+        let schedule = CronSchedule::parse(&request.cron)?;
+        if request.supplier_ids.is_empty() {
+            bail!("At least one supplier is required");
+        }
+        let next_run_at = schedule.next_after(now).context("Cron expression never runs")?;
+        
+        let template = self.campaign_templates.set_recurrence(template_id, Some(Recurrence {
+            schedule,
+            client_id: request.client_id,
+            supplier_ids: request.supplier_ids,
+            require_confirmation: request.require_confirmation,
+            next_run_at,
+            last_workflow_id: None,
+        })).await;
+        Ok(template.map(campaign_template_response))
+    }
+    
+    /// Stop a template relaunching; `None` if the template does not exist
+    pub async fn clear_campaign_recurrence(&self, template_id: &str) -> Option<CampaignTemplateResponse> {
+        self.campaign_templates.set_recurrence(template_id, None).await.map(campaign_template_response)
+    }
+    
+    /// Launch the campaign cycles due at `now`. Templates that require
+    /// confirmation launch theirs pending, to be activated through the
+    /// status endpoint or cancelled.
+    pub async fn launch_due_campaigns(&self, now: DateTime<Utc>) -> Vec<WorkflowResponse> {
+        let mut launched = Vec::new();
+        for template in self.campaign_templates.take_due(now).await {
+            let Some(recurrence) = template.recurrence.clone() else {
+                continue;
+            };
+            let state = if recurrence.require_confirmation { WorkflowState::Pending } else { WorkflowState::Active };
+            let template_id = template.id.clone();
+            let request = LaunchCampaignRequest {
+                client_id: recurrence.client_id,
+                supplier_ids: recurrence.supplier_ids,
+                campaign_name: None,
+                deadline: None,
+            };
+            match self.launch_from_template(template, request, state).await {
+                Ok(workflow) => {
+                    self.campaign_templates.record_launch(&template_id, workflow.id).await;
+                    launched.push(workflow);
+                }
+                Err(e) => warn!("Failed to launch scheduled cycle of template {}: {:#}", template_id, e),
+            }
+        }
+        launched
     }
     
     /// List all workflows
@@ -177,7 +724,9 @@ impl WorkflowService {
             bail!("Invalid state transition from {} to {}", workflow.state, new_state);
         }
         
+        let previous = workflow.state;
         workflow.state = new_state;
+        self.audit_workflow(workflow, Some(previous));
         
         let tasks = self.tasks.read().await;
         let task_count = tasks.values().filter(|t| t.workflow_id == id).count();
@@ -185,9 +734,160 @@ impl WorkflowService {
         Ok(self.to_workflow_response(workflow, task_count))
     }
     
-    /// Cancel workflow
-    pub async fn cancel_workflow(&self, id: Uuid) -> Result<WorkflowResponse> {
-        self.update_status(id, "cancelled").await
+    /// Put a workflow on a new version of its config; `None` if the workflow
+    /// does not exist. Only scheduling from now on follows the new version.
+    /// Phases are fixed once the campaign has started, as tasks refer to them.
+    pub async fn update_config(&self, id: Uuid, request: UpdateWorkflowConfigRequest) -> Result<Option<ConfigVersionResponse>> {
+        validate_config(&request.config)?;
+        let mut workflows = self.workflows.write().await;
+        let Some(workflow) = workflows.get_mut(&id) else {
+            return Ok(None);
+        };
+        if workflow.state.is_terminal() {
+            bail!("Workflow is {}; its config can no longer change", workflow.state);
+        }
+        if workflow.state != WorkflowState::Pending && request.config.phases != workflow.config.phases {
+            bail!("Phases cannot change once the campaign has started");
+        }
+        let changes = config_changes(&workflow.config, &request.config);
+        if changes.is_empty() {
+            bail!("Config is unchanged");
+        }
+        
+        let version = ConfigVersion {
+            config: request.config,
+            changed_by: request.changed_by.filter(|c| !c.trim().is_empty()),
+            changed_at: Utc::now(),
+            reason: request.reason.filter(|r| !r.trim().is_empty()),
+            changes,
+        };
+        workflow.config = version.config.clone();
+        workflow.config_versions.push(version.clone());
+        let number = workflow.config_versions.len();
+        self.record_audit("update_config", "workflow", id, version.changed_by.clone(), serde_json::json!({
+            "version": number,
+            "changes": version.changes,
+            "reason": version.reason,
+        }));
+        
+        Ok(Some(config_version_response(number, version, true)))
+    }
+    
+    /// Every config version of a workflow, oldest first; `None` if the workflow does not exist
+    pub async fn config_versions(&self, id: Uuid) -> Option<Vec<ConfigVersionResponse>> {
+        let workflows = self.workflows.read().await;
+        let versions = &workflows.get(&id)?.config_versions;
+        Some(versions.iter().enumerate()
+            .map(|(i, version)| config_version_response(i + 1, version.clone(), i + 1 == versions.len()))
+            .collect())
+    }
+    
+    /// Cancel a workflow: its pending tasks are cancelled, its open
+    /// escalations closed and, if asked, suppliers already emailed are told
+    /// the request is withdrawn. Running tasks are left to finish.
+    pub async fn cancel_workflow(&self, id: Uuid, request: CancelWorkflowRequest) -> Result<CancellationResponse> {
+        let workflow = self.update_status(id, "cancelled").await?;
+        let reason = request.reason.filter(|r| !r.trim().is_empty());
+        let closing_note = match &reason {
+            Some(reason) => format!("Campaign cancelled: {}", reason),
+            None => "Campaign cancelled".to_string(),
+        };
+        
+        let mut tasks_cancelled = 0;
+        // Latest thread per supplier, so a withdrawal arrives as a reply to the request
+        let mut threads: HashMap<Uuid, (DateTime<Utc>, String)> = HashMap::new();
+        {
+            let mut tasks = self.tasks.write().await;
+            for task in tasks.values_mut().filter(|t| t.workflow_id == id) {
+                if task.state.can_transition_to(TaskState::Cancelled) && task.state != TaskState::Running {
+                    let previous = task.state;
+                    task.state = TaskState::Cancelled;
+                    task.completed_at = Some(Utc::now());
+                    task.error = Some(closing_note.clone());
+                    self.audit_task(task, previous);
+                    tasks_cancelled += 1;
+                }
+                let thread_id = task.result.as_ref().and_then(|r| r.get("thread_id")).and_then(|t| t.as_str());
+                if let (Some(thread_id), Some(completed_at)) = (thread_id, task.completed_at) {
+                    if threads.get(&task.supplier_id).is_none_or(|(latest, _)| completed_at > *latest) {
+                        threads.insert(task.supplier_id, (completed_at, thread_id.to_string()));
+                    }
+                }
+            }
+        }
+        
+        let mut escalations_closed = 0;
+        {
+            let mut escalations = self.escalations.write().await;
+            for escalation in escalations.values_mut().filter(|e| e.workflow_id == id && e.is_open()) {
+                let previous = escalation.state;
+                escalation.state = EscalationState::Resolved;
+                escalation.resolved_at = Some(Utc::now());
+                escalation.resolution = Some(closing_note.clone());
+                self.audit_escalation(escalation, Some(previous));
+                escalations_closed += 1;
+            }
+        }
+        
+        let (mut withdrawals_sent, mut withdrawals_failed) = (0, 0);
+        if request.notify_suppliers {
+            let reached = self.reached_by_outreach(id).await;
+            let recipients: Vec<Uuid> = {
+                let workflows = self.workflows.read().await;
+                let stored = workflows.get(&id).context("Workflow not found")?;
+                // Suppliers handled by hand, e.g. after opting out, and those already done are left alone
+                let mut recipients: Vec<Uuid> = contacted_suppliers(stored, reached).into_iter()
+                    .filter(|s| !stored.manual.contains(s))
+                    .filter(|s| !stored.activity.get(s).is_some_and(|a| a.data_complete))
+                    .collect();
+                recipients.sort();
+                recipients
+            };
+            for supplier_id in recipients {
+                let thread_id = threads.remove(&supplier_id).map(|(_, thread_id)| thread_id);
+                match self.send_withdrawal(&workflow, supplier_id, thread_id, reason.as_deref()).await {
+                    Ok(()) => withdrawals_sent += 1,
+                    Err(e) => {
+                        warn!("Failed to tell supplier {} that campaign {} was withdrawn: {:#}", supplier_id, id, e);
+                        withdrawals_failed += 1;
+                    }
+                }
+            }
+        }
+        
+        Ok(CancellationResponse {
+            workflow,
+            tasks_cancelled,
+            escalations_closed,
+            withdrawals_sent,
+            withdrawals_failed,
+        })
+    }
+    
+    async fn send_withdrawal(&self, workflow: &WorkflowResponse, supplier_id: Uuid, thread_id: Option<String>, reason: Option<&str>) -> Result<()> {
+        let email = self.email.as_ref().context("Email service is not configured")?;
+        let contact = self.supplier_contact(supplier_id).await?
+            .filter(|contact| !contact.primary_email.trim().is_empty())
+            .context("Supplier has no contact email")?;
+        
+        let mut variables = HashMap::from([
+            ("contact_email".to_string(), contact.primary_email),
+            ("contact_name".to_string(), contact.contact_person),
+            ("reference_id".to_string(), workflow.campaign_name.clone()),
+        ]);
+        if let Some(reason) = reason {
+            variables.insert("reason".to_string(), reason.to_string());
+        }
+        
+        email.send(&TaskEmail {
+            supplier_id,
+            template_id: "request_withdrawn".to_string(),
+            thread_id,
+            campaign_id: Some(workflow.id),
+            calendar_deadline: None,
+            variables,
+        }).await?;
+        Ok(())
     }
     
     /// Get tasks for workflow
@@ -200,6 +900,68 @@ impl WorkflowService {
             .collect())
     }
     
+    /// Make a task wait on other tasks of the same workflow
+    pub async fn add_task_dependencies(&self, task_id: Uuid, depends_on: &[Uuid]) -> Result<Option<TaskResponse>> {
+        let mut tasks = self.tasks.write().await;
+        let Some(task) = tasks.get(&task_id) else {
+            return Ok(None);
+        };
+        if task.state != TaskState::Scheduled && task.state != TaskState::Failed {
+            bail!("Task {} is {} and can no longer wait on other tasks", task_id, task.state);
+        }
+        
+        let workflow_id = task.workflow_id;
+        for dependency in depends_on {
+            match tasks.get(dependency) {
+                Some(t) if t.workflow_id == workflow_id => {}
+                Some(_) => bail!("Task {} belongs to another workflow", dependency),
+                None => bail!("Task {} not found", dependency),
+            }
+        }
+        let edges: HashMap<Uuid, Vec<Uuid>> = tasks.values()
+            .filter(|t| t.workflow_id == workflow_id)
+            .map(|t| (t.id, t.depends_on.clone()))
+            .collect();
+        if creates_cycle(&edges, task_id, depends_on) {
+            bail!("Dependency would create a cycle");
+        }
+        
+        let task = tasks.get_mut(&task_id).context("Task not found")?;
+        for dependency in depends_on {
+            if !task.depends_on.contains(dependency) {
+                task.depends_on.push(*dependency);
+            }
+        }
+        Ok(Some(self.to_task_response(task)))
+    }
+    
+    /// A workflow's tasks with their dependency edges; `None` if the workflow does not exist
+    pub async fn task_graph(&self, workflow_id: Uuid) -> Result<Option<TaskGraphResponse>> {
+        if !self.workflows.read().await.contains_key(&workflow_id) {
+            return Ok(None);
+        }
+        
+        let tasks = self.tasks.read().await;
+        let mut nodes: Vec<&StoredTask> = tasks.values().filter(|t| t.workflow_id == workflow_id).collect();
+        nodes.sort_by_key(|t| t.scheduled_at);
+        
+        let edges = nodes.iter()
+            .flat_map(|t| t.depends_on.iter().map(|d| TaskEdge { task_id: t.id, depends_on: *d }))
+            .collect();
+        let blocked = nodes.iter()
+            .filter(|t| !t.state.is_terminal())
+            .filter(|t| dependency_status(t.depends_on.iter().map(|id| tasks.get(id).map(|d| d.state))) != DependencyStatus::Ready)
+            .map(|t| t.id)
+            .collect();
+        
+        Ok(Some(TaskGraphResponse {
+            workflow_id,
+            tasks: nodes.iter().map(|t| self.to_task_response(t)).collect(),
+            edges,
+            blocked,
+        }))
+    }
+    
     /// Get task by ID
     pub async fn get_task(&self, task_id: Uuid) -> Result<Option<TaskResponse>> {
         let tasks = self.tasks.read().await;
@@ -212,105 +974,1925 @@ impl WorkflowService {
         let task = tasks.get_mut(&task_id)
             .context("Task not found")?;
         
+        let previous = task.state;
         task.state = TaskState::Completed;
         task.completed_at = Some(Utc::now());
         task.result = result;
+        self.audit_task(task, previous);
+        let (workflow_id, response) = (task.workflow_id, self.to_task_response(task));
+        let outreach_to = (task.task_type == TaskType::InitialOutreach && task.phase.is_none()).then_some(task.supplier_id);
+        let next_phase = task.phase.map(|index| (task.supplier_id, index + 1));
+        // Progress is recounted from the tasks, so the write lock has to go first
+        drop(tasks);
+        
+        if let Some(supplier_id) = outreach_to {
+            self.schedule_follow_ups(workflow_id, supplier_id).await;
+        }
+        if let Some((supplier_id, index)) = next_phase {
+            self.schedule_phase(workflow_id, supplier_id, index, Utc::now()).await;
+        }
         
         // Update workflow progress
-        self.update_workflow_progress(task.workflow_id).await;
+        self.update_workflow_progress(workflow_id).await;
         
-        Ok(self.to_task_response(task))
+        Ok(response)
     }
     
-    /// Retry task
-    pub async fn retry_task(&self, task_id: Uuid) -> Result<TaskResponse> {
-        let mut tasks = self.tasks.write().await;
-        let task = tasks.get_mut(&task_id)
-            .context("Task not found")?;
-        
-        if task.retry_count >= task.max_retries {
-            task.state = TaskState::Exhausted;
-            
-            // Create escalation
-            self.create_escalation(
-                task.workflow_id,
-                task.supplier_id,
-                "Max retries exceeded".to_string(),
-                "high".to_string(),
-            ).await?;
-        } else {
-            task.retry_count += 1;
-            task.state = TaskState::Scheduled;
-            task.scheduled_at = Some(Utc::now());
-            task.error = None;
+    /// Schedule phase `index` for a supplier that has finished the one before
+    async fn schedule_phase(&self, workflow_id: Uuid, supplier_id: Uuid, index: usize, from: DateTime<Utc>) {
+        let Some(phases) = self.workflows.read().await.get(&workflow_id).map(|w| w.config.phases.clone()) else {
+            return;
+        };
+        if let Some(task) = StoredTask::for_phase(workflow_id, supplier_id, &phases, index, from) {
+            self.tasks.write().await.insert(task.id, task);
         }
-        
-        Ok(self.to_task_response(task))
-    }
-    
-    /// List escalations
-    pub async fn list_escalations(&self) -> Result<Vec<EscalationResponse>> {
-        let escalations = self.escalations.read().await;
-        Ok(escalations.values().map(|e| self.to_escalation_response(e)).collect())
     }
     
-    /// Resolve escalation
-    pub async fn resolve_escalation(&self, id: Uuid, resolution: &str) -> Result<EscalationResponse> {
-        let mut escalations = self.escalations.write().await;
-        let escalation = escalations.get_mut(&id)
-            .context("Escalation not found")?;
+    /// Schedule the workflow's follow-ups for a supplier that has just been
+    /// contacted, one per `follow_up_interval_days` up to `max_follow_ups`.
+    /// Nothing is scheduled if the supplier already has follow-ups.
+    async fn schedule_follow_ups(&self, workflow_id: Uuid, supplier_id: Uuid) -> usize {
+        let Some(config) = self.workflows.read().await.get(&workflow_id).map(|w| w.config.clone()) else {
+            return 0;
+        };
+        let scheduler = WorkflowScheduler::new(config);
         
-        escalation.resolved = true;
-        escalation.resolved_at = Some(Utc::now());
-        escalation.resolution = Some(resolution.to_string());
+        let mut tasks = self.tasks.write().await;
+        let supplier_tasks = || tasks.values().filter(|t| t.workflow_id == workflow_id && t.supplier_id == supplier_id);
+        if supplier_tasks().any(|t| t.task_type == TaskType::FollowUp) {
+            return 0;
+        }
         
-        Ok(self.to_escalation_response(escalation))
+        // Each chaser waits on the email before it
+        let mut previous = supplier_tasks().find(|t| t.task_type == TaskType::InitialOutreach).map(|t| t.id);
+        let follow_ups: Vec<StoredTask> = (0..)
+            .map_while(|n| scheduler.schedule_follow_up(workflow_id, supplier_id, n))
+            .map(|st| {
+                let st = match previous {
+                    Some(previous) => st.after(previous),
+                    None => st,
+                };
+                previous = Some(st.id);
+                StoredTask::scheduled(st)
+            })
+            .collect();
+        let scheduled = follow_ups.len();
+        for task in follow_ups {
+            tasks.insert(task.id, task);
+        }
+        scheduled
     }
     
-    /// Create escalation (internal)
-    async fn create_escalation(&self, workflow_id: Uuid, supplier_id: Uuid, reason: String, severity: String) -> Result<()> {
-        let escalation = StoredEscalation {
-            id: Uuid::new_v4(),
-            workflow_id,
-            supplier_id,
-            reason,
-            severity,
-            created_at: Utc::now(),
-            resolved: false,
-            resolved_at: None,
-            resolution: None,
+    /// Claim due tasks of active workflows for the executor: scheduled tasks
+    /// and failed ones whose retry is due, oldest first. They move to running
+    /// under the task map's write lock, so overlapping executor runs never
+    /// claim the same task. Emails to a supplier another campaign has just
+    /// emailed are held back or coalesced by the outreach guard.
+    pub async fn claim_due_tasks(&self, now: DateTime<Utc>, limit: usize) -> Vec<DueTask> {
+        // Latest email to each supplier from any campaign, as reported by the email service
+        let mut last_sent: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+        let active: HashMap<Uuid, ActiveWorkflow> = {
+            let workflows = self.workflows.read().await;
+            for (supplier_id, sent_at) in workflows.values()
+                .flat_map(|w| w.activity.iter())
+                .filter_map(|(supplier_id, activity)| activity.last_sent_at.map(|at| (*supplier_id, at)))
+            {
+                let latest = last_sent.entry(supplier_id).or_insert(sent_at);
+                *latest = (*latest).max(sent_at);
+            }
+            workflows.values()
+                .filter(|w| w.state == WorkflowState::Active)
+                .map(|w| (w.id, ActiveWorkflow {
+                    campaign_name: w.campaign_name.clone(),
+                    deadline: w.deadline,
+                    config: w.config.clone(),
+                    relationships: w.relationships.clone(),
+                    contact_windows: w.contact_windows.clone(),
+                    responded: w.responded.clone(),
+                    data_complete: w.activity.iter().filter(|(_, a)| a.data_complete).map(|(id, _)| *id).collect(),
+                }))
+                .collect()
         };
         
-        let mut escalations = self.escalations.write().await;
-        escalations.insert(escalation.id, escalation);
-        
-        Ok(())
-    }
-    
-    /// Update workflow progress (internal)
-    async fn update_workflow_progress(&self, workflow_id: Uuid) {
-        let tasks = self.tasks.read().await;
-        let workflow_tasks: Vec<_> = tasks.values()
-            .filter(|t| t.workflow_id == workflow_id)
-            .collect();
+        let mut tasks = self.tasks.write().await;
         
-        let total = workflow_tasks.len();
-        let completed = workflow_tasks.iter().filter(|t| t.state == TaskState::Completed).count();
+        // Emails in flight count as sent now; completed ones as of when they went out
+        for task in tasks.values().filter(|t| matches!(t.task_type, TaskType::InitialOutreach | TaskType::FollowUp)) {
+            let sent_at = match task.state {
+                TaskState::Running => Some(now),
+                TaskState::Completed => task.completed_at,
+                _ => None,
+            };
+            if let Some(sent_at) = sent_at {
+                let latest = last_sent.entry(task.supplier_id).or_insert(sent_at);
+                *latest = (*latest).max(sent_at);
+            }
+        }
         
-        drop(tasks);
+        // Latest outreach thread per workflow and supplier, for follow-ups to reply in
+        let mut threads: HashMap<(Uuid, Uuid), (DateTime<Utc>, String)> = HashMap::new();
+        for task in tasks.values().filter(|t| {
+            t.state == TaskState::Completed && matches!(t.task_type, TaskType::InitialOutreach | TaskType::FollowUp)
+        }) {
+            let thread_id = task.result.as_ref().and_then(|r| r.get("thread_id")).and_then(|t| t.as_str());
+            if let (Some(thread_id), Some(completed_at)) = (thread_id, task.completed_at) {
+                let latest = threads.entry((task.workflow_id, task.supplier_id))
+                    .or_insert_with(|| (completed_at, thread_id.to_string()));
+                if completed_at > latest.0 {
+                    *latest = (completed_at, thread_id.to_string());
+                }
+            }
+        }
+        
+        // Tasks whose dependencies failed can never run; the rest wait for theirs to complete
+        let states: HashMap<Uuid, TaskState> = tasks.values().map(|t| (t.id, t.state)).collect();
+        let dependencies = |t: &StoredTask| dependency_status(t.depends_on.iter().map(|id| states.get(id).copied()));
+        for task in tasks.values_mut().filter(|t| !t.state.is_terminal() && t.state != TaskState::Running) {
+            if dependencies(task) == DependencyStatus::Broken {
+                let previous = task.state;
+                task.state = TaskState::Skipped;
+                task.error = Some("A task it depends on did not complete".to_string());
+                self.audit_task(task, previous);
+            }
+        }
+        
+        // Key suppliers and campaigns near their deadline go first
+        for (workflow_id, workflow) in &active {
+            let scheduler = WorkflowScheduler::new(workflow.config.clone());
+            let risk = scheduler.deadline_severity(workflow.deadline, now);
+            for task in tasks.values_mut().filter(|t| t.workflow_id == *workflow_id && !t.state.is_terminal()) {
+                let relationship = workflow.relationships.get(&task.supplier_id);
+                task.priority = scheduler.task_priority(task.base_priority, relationship, risk);
+            }
+        }
+        
+        let mut due: Vec<&mut StoredTask> = tasks.values_mut()
+            .filter(|t| t.state.can_transition_to(TaskState::Running) && t.scheduled_at.is_some_and(|at| at <= now))
+            .filter(|t| t.task_type != TaskType::Manual)
+            .filter(|t| active.contains_key(&t.workflow_id))
+            .filter(|t| dependencies(t) == DependencyStatus::Ready)
+            .collect();
+        due.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.scheduled_at));
+        
+        let mut claimed: Vec<DueTask> = Vec::new();
+        // Email task claimed for each supplier in this run, by index into `claimed`
+        let mut emailing: HashMap<Uuid, usize> = HashMap::new();
+        // Phases skipped in this run, whose next phase is scheduled from now
+        let mut skipped_phases: Vec<(Uuid, Uuid, usize)> = Vec::new();
+        for task in due {
+            let workflow = &active[&task.workflow_id];
+            let phase = task.phase.and_then(|index| workflow.config.phases.get(index));
+            if let Some(phase) = phase {
+                let supplier_id = task.supplier_id;
+                if !phase.entry().is_met(workflow.responded.contains(&supplier_id), workflow.data_complete.contains(&supplier_id)) {
+                    let previous = task.state;
+                    task.state = TaskState::Skipped;
+                    task.error = Some(format!("Entry criteria of the {} phase no longer hold", phase.phase));
+                    self.audit_task(task, previous);
+                    skipped_phases.extend(task.phase.map(|index| (task.workflow_id, supplier_id, index)));
+                    continue;
+                }
+            }
+            let email_template_id = match (phase, task.task_type) {
+                (Some(phase), _) => phase.template_id.clone(),
+                (None, TaskType::InitialOutreach) => Some(workflow.config.outreach_template_id.clone()),
+                (None, TaskType::FollowUp) => Some(workflow.config.follow_up_template_id.clone()),
+                _ => None,
+            };
+            
+            // Outside the supplier's hours the email waits, whatever the guard would say
+            let opening = workflow.contact_windows.get(&task.supplier_id)
+                .filter(|_| email_template_id.is_some())
+                .map(|window| next_opening(window, now));
+            if let Some(opening) = opening.filter(|opening| *opening > now) {
+                task.scheduled_at = Some(opening);
+                continue;
+            }
+            
+            let decision = if email_template_id.is_some() {
+                let primary = emailing.get(&task.supplier_id).map(|&index| claimed[index].id);
+                self.outreach_guard.decide(last_sent.get(&task.supplier_id).copied(), primary, now)
+            } else {
+                OutreachDecision::Send
+            };
+            if decision == OutreachDecision::Send && claimed.len() >= limit {
+                continue;
+            }
+            match decision {
+                OutreachDecision::Defer(until) => {
+                    task.scheduled_at = Some(until);
+                    continue;
+                }
+                OutreachDecision::JoinWith(_) => {
+                    let index = emailing[&task.supplier_id];
+                    claimed[index].coalesced.push(CoalescedTask {
+                        id: task.id,
+                        campaign_name: workflow.campaign_name.clone(),
+                        deadline: workflow.deadline,
+                    });
+                }
+                OutreachDecision::Send => {
+                    if email_template_id.is_some() {
+                        emailing.insert(task.supplier_id, claimed.len());
+                    }
+                    claimed.push(DueTask {
+                        id: task.id,
+                        workflow_id: task.workflow_id,
+                        supplier_id: task.supplier_id,
+                        task_type: task.task_type,
+                        attempt: task.retry_count + 1,
+                        max_retries: task.max_retries,
+                        campaign_name: workflow.campaign_name.clone(),
+                        deadline: workflow.deadline,
+                        thread_id: threads.get(&(task.workflow_id, task.supplier_id)).map(|(_, thread_id)| thread_id.clone()),
+                        email_template_id,
+                        payload: task.payload.clone(),
+                        coalesced: Vec::new(),
+                    });
+                }
+            }
+            
+            let previous = task.state;
+            task.state = TaskState::Running;
+            task.started_at = Some(now);
+            self.audit_task(task, previous);
+        }
+        
+        let mut finished = HashSet::new();
+        for (workflow_id, supplier_id, index) in skipped_phases {
+            match StoredTask::for_phase(workflow_id, supplier_id, &active[&workflow_id].config.phases, index + 1, now) {
+                Some(next) => {
+                    tasks.insert(next.id, next);
+                }
+                None => {
+                    finished.insert(workflow_id);
+                }
+            }
+        }
+        drop(tasks);
+        // A skipped last phase may be all that kept the workflow open
+        for workflow_id in finished {
+            self.update_workflow_progress(workflow_id).await;
+        }
+        
+        claimed
+    }
+    
+    /// Put tasks claimed by `claim_due_tasks` back without running them, as
+    /// when another executor holds them in the task queue
+    pub async fn release_tasks(&self, task_ids: &[Uuid]) {
+        let mut tasks = self.tasks.write().await;
+        for task in tasks.values_mut().filter(|t| t.state == TaskState::Running && task_ids.contains(&t.id)) {
+            let previous = task.state;
+            task.state = if task.retry_count > 0 { TaskState::Failed } else { TaskState::Scheduled };
+            task.started_at = None;
+            self.audit_task(task, previous);
+        }
+    }
+    
+    /// Record a failed run. The task is retried at `retry_at` while it has
+    /// retries left; otherwise it is exhausted and escalated.
+    pub async fn fail_task(&self, task_id: Uuid, error: String, retry_at: Option<DateTime<Utc>>) -> Result<TaskResponse> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id)
+            .context("Task not found")?;
+        
+        let previous = task.state;
+        task.error = Some(error.clone());
+        let exhausted = match retry_at.filter(|_| task.retry_count < task.max_retries) {
+            Some(retry_at) => {
+                task.state = TaskState::Failed;
+                task.retry_count += 1;
+                task.scheduled_at = Some(retry_at);
+                false
+            }
+            None => {
+                task.state = TaskState::Exhausted;
+                task.completed_at = Some(Utc::now());
+                true
+            }
+        };
+        self.audit_task(task, previous);
+        let response = self.to_task_response(task);
+        let (workflow_id, supplier_id, task_type) = (task.workflow_id, task.supplier_id, task.task_type);
+        drop(tasks);
+        
+        if exhausted {
+            self.create_escalation(
+                workflow_id,
+                supplier_id,
+                format!("Task {} failed: {}", task_type, error),
+                "high".to_string(),
+            ).await?;
+            
+            let mut workflows = self.workflows.write().await;
+            if let Some(workflow) = workflows.get_mut(&workflow_id) {
+                workflow.progress.escalated += 1;
+            }
+        }
+        
+        Ok(response)
+    }
+    
+    /// Apply task outcomes reported by other services, in order. An update
+    /// whose idempotency key was already applied is a duplicate and changes
+    /// nothing; one that cannot be applied is rejected and its key left free
+    /// so a corrected update can be sent again.
+    pub async fn apply_task_updates(&self, updates: Vec<TaskStatusUpdate>) -> Result<TaskUpdateBatchResponse> {
+        if updates.len() > MAX_TASK_UPDATES {
+            bail!("At most {} task updates per batch", MAX_TASK_UPDATES);
+        }
+        let now = Utc::now();
+        self.applied_updates.write().await
+            .retain(|_, applied| now - applied.applied_at < Duration::days(IDEMPOTENCY_RETENTION_DAYS));
+        
+        let mut response = TaskUpdateBatchResponse { applied: 0, duplicates: 0, rejected: 0, results: Vec::new() };
+        for update in updates {
+            let (key, task_id) = (update.idempotency_key.clone(), update.task_id);
+            let result = |outcome: &str, error: Option<String>, task: Option<TaskResponse>| TaskUpdateResult {
+                idempotency_key: key.clone(),
+                task_id,
+                outcome: outcome.to_string(),
+                error,
+                task,
+            };
+            if key.trim().is_empty() {
+                response.rejected += 1;
+                response.results.push(result("rejected", Some("Idempotency key is required".to_string()), None));
+                continue;
+            }
+            
+            // The key is taken before the update is applied so a concurrent redelivery sees it
+            let previous = {
+                let mut applied = self.applied_updates.write().await;
+                match applied.get(&key) {
+                    Some(previous) => Some(previous.task_id),
+                    None => {
+                        applied.insert(key.clone(), AppliedUpdate { task_id, applied_at: now });
+                        None
+                    }
+                }
+            };
+            match previous {
+                Some(previous) if previous == task_id => {
+                    let task = self.get_task(task_id).await?;
+                    response.duplicates += 1;
+                    response.results.push(result("duplicate", None, task));
+                }
+                Some(_) => {
+                    response.rejected += 1;
+                    response.results.push(result("rejected", Some("Idempotency key was used for another task".to_string()), None));
+                }
+                None => match self.apply_task_update(update).await {
+                    Ok(task) => {
+                        response.applied += 1;
+                        response.results.push(result("applied", None, Some(task)));
+                    }
+                    Err(e) => {
+                        self.applied_updates.write().await.remove(&key);
+                        response.rejected += 1;
+                        response.results.push(result("rejected", Some(e.to_string()), None));
+                    }
+                },
+            }
+        }
+        
+        Ok(response)
+    }
+    
+    async fn apply_task_update(&self, update: TaskStatusUpdate) -> Result<TaskResponse> {
+        let state = self.tasks.read().await.get(&update.task_id).map(|t| t.state).context("Task not found")?;
+        if state.is_terminal() {
+            bail!("Task is already {}", state);
+        }
+        match update.status {
+            TaskUpdateStatus::Completed => self.complete_task(update.task_id, update.result).await,
+            TaskUpdateStatus::Failed => {
+                let retry_at = update.retry_at.as_deref().map(event_time).transpose()?;
+                let error = update.error.unwrap_or_else(|| "Failed without a reason".to_string());
+                self.fail_task(update.task_id, error, retry_at).await
+            }
+        }
+    }
+    
+    /// Raise an escalation on behalf of a scheduled escalation task
+    pub async fn escalate_supplier(&self, workflow_id: Uuid, supplier_id: Uuid, reason: String) -> Result<()> {
+        self.create_escalation(workflow_id, supplier_id, reason, "medium".to_string()).await?;
+        
+        let mut workflows = self.workflows.write().await;
+        if let Some(workflow) = workflows.get_mut(&workflow_id) {
+            workflow.progress.escalated += 1;
+        }
+        
+        Ok(())
+    }
+    
+    /// Primary contact of a supplier; `None` without a database or when the supplier is unknown
+    pub async fn supplier_contact(&self, supplier_id: Uuid) -> Result<Option<ContactInfo>> {
+        let Some(suppliers) = &self.suppliers else {
+            return Ok(None);
+        };
+        
+        Ok(suppliers.find_by_id(supplier_id.into()).await?.map(|supplier| supplier.contact_info))
+    }
+    
+    /// Records of the suppliers the database knows; a failed lookup only
+    /// costs priority and contact preferences, so it is logged and skipped
+    async fn supplier_records(&self, supplier_ids: &[Uuid]) -> HashMap<Uuid, SupplierRecord> {
+        let Some(suppliers) = &self.suppliers else {
+            return HashMap::new();
+        };
+        
+        let mut records = HashMap::new();
+        for supplier_id in supplier_ids {
+            match suppliers.find_by_id((*supplier_id).into()).await {
+                Ok(Some(record)) => {
+                    records.insert(*supplier_id, record);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to look up supplier {}: {:#}", supplier_id, e),
+            }
+        }
+        records
+    }
+    
+    /// Suppliers that must not be emailed, with why, and the contact windows
+    /// of the others: from their communication preferences and the email
+    /// service's suppression list. If the list cannot be read, outreach goes
+    /// ahead; the email service still refuses suppressed recipients.
+    async fn outreach_restrictions(
+        &self,
+        supplier_ids: &[Uuid],
+        records: &HashMap<Uuid, SupplierRecord>,
+    ) -> (HashMap<Uuid, String>, HashMap<Uuid, ContactWindow>) {
+        let suppressions = match &self.email {
+            Some(email) => email.suppressions().await.unwrap_or_else(|e| {
+                warn!("Failed to read the suppression list before outreach: {:#}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        
+        let (mut blocked, mut windows) = (HashMap::new(), HashMap::new());
+        for supplier_id in supplier_ids {
+            let record = records.get(supplier_id);
+            let email_address = record.map(|r| r.contact_info.primary_email.as_str());
+            let preferences = record.map(|r| &r.communication_preferences);
+            if let Some(suppression) = suppressions.iter().find(|s| s.covers(*supplier_id, email_address)) {
+                blocked.insert(*supplier_id, format!("Supplier is on the suppression list ({})", suppression.reason));
+            } else if preferences.is_some_and(|p| p.do_not_contact) {
+                blocked.insert(*supplier_id, "Supplier asked not to be contacted by email".to_string());
+            } else if let Some(window) = preferences.and_then(|p| p.contact_window.clone()) {
+                windows.insert(*supplier_id, window);
+            }
+        }
+        (blocked, windows)
+    }
+    
+    /// Relationship of a supplier to the client; `None` without a database or when the supplier is unknown
+    async fn supplier_relationship(&self, supplier_id: Uuid) -> Result<Option<SupplierRelationship>> {
+        let Some(suppliers) = &self.suppliers else {
+            return Ok(None);
+        };
+        
+        Ok(suppliers.find_by_id(supplier_id.into()).await?.map(|supplier| supplier.relationship))
+    }
+    
+    /// Retry task
+    pub async fn retry_task(&self, task_id: Uuid) -> Result<TaskResponse> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id)
+            .context("Task not found")?;
+        
+        let previous = task.state;
+        let exhausted = task.retry_count >= task.max_retries;
+        if exhausted {
+            task.state = TaskState::Exhausted;
+        } else {
+            task.retry_count += 1;
+            task.state = TaskState::Scheduled;
+            task.scheduled_at = Some(Utc::now());
+            task.error = None;
+        }
+        self.audit_task(task, previous);
+        let response = self.to_task_response(task);
+        let (workflow_id, supplier_id) = (task.workflow_id, task.supplier_id);
+        // Escalating reads the workflows, which must not wait behind the tasks lock
+        drop(tasks);
+        
+        if exhausted {
+            self.create_escalation(
+                workflow_id,
+                supplier_id,
+                "Max retries exceeded".to_string(),
+                "high".to_string(),
+            ).await?;
+        }
+        
+        Ok(response)
+    }
+    
+    /// Add a step done by a person to a workflow. It stays open until its
+    /// assignee completes it and holds the workflow open meanwhile; the
+    /// executor never runs it. `None` if the workflow does not exist.
+    pub async fn create_manual_task(&self, workflow_id: Uuid, request: CreateManualTaskRequest) -> Result<Option<TaskResponse>> {
+        if request.instructions.trim().is_empty() {
+            bail!("Instructions are required");
+        }
+        let due_at = request.due_at.as_deref()
+            .map(|due| DateTime::parse_from_rfc3339(due).context("Invalid due_at format"))
+            .transpose()?
+            .map(|due| due.with_timezone(&Utc));
+        
+        let workflows = self.workflows.read().await;
+        let Some(workflow) = workflows.get(&workflow_id) else {
+            return Ok(None);
+        };
+        if workflow.state.is_terminal() {
+            bail!("Workflow has already ended");
+        }
+        if !workflow.suppliers.contains(&request.supplier_id) {
+            bail!("Supplier {} is not part of this workflow", request.supplier_id);
+        }
+        
+        let mut tasks = self.tasks.write().await;
+        for dependency in &request.depends_on {
+            match tasks.get(dependency) {
+                Some(t) if t.workflow_id == workflow_id => {}
+                Some(_) => bail!("Task {} belongs to another workflow", dependency),
+                None => bail!("Task {} not found", dependency),
+            }
+        }
+        
+        let task = StoredTask {
+            depends_on: request.depends_on,
+            manual: Some(ManualWork {
+                assignee: request.assignee.filter(|a| !a.trim().is_empty()),
+                instructions: request.instructions,
+                due_at,
+                notes: None,
+                completed_by: None,
+            }),
+            ..StoredTask::scheduled(ScheduledTask {
+                id: Uuid::new_v4(),
+                workflow_id,
+                supplier_id: request.supplier_id,
+                task_type: TaskType::Manual,
+                scheduled_at: Utc::now(),
+                priority: 50,
+                depends_on: Vec::new(),
+            })
+        };
+        self.audit_transition("task", task.id, None, task.state.to_string(), serde_json::json!({
+            "task_type": task.task_type.to_string(),
+            "workflow_id": workflow_id,
+            "supplier_id": task.supplier_id,
+            "assignee": task.manual.as_ref().and_then(|m| m.assignee.clone()),
+        }));
+        let response = self.to_task_response(&task);
+        tasks.insert(task.id, task);
+        Ok(Some(response))
+    }
+    
+    /// Manual tasks across workflows, soonest due first
+    pub async fn list_manual_tasks(&self, query: &ManualTaskQuery) -> Vec<TaskResponse> {
+        let tasks = self.tasks.read().await;
+        let mut matching: Vec<(&StoredTask, &ManualWork)> = tasks.values()
+            .filter_map(|t| t.manual.as_ref().map(|m| (t, m)))
+            .filter(|(_, m)| query.assignee.is_none() || m.assignee == query.assignee)
+            .filter(|(t, _)| query.open.is_none_or(|open| open != t.state.is_terminal()))
+            .collect();
+        // Tasks without a due date come last
+        matching.sort_by_key(|(t, m)| (m.due_at.is_none(), m.due_at, t.scheduled_at));
+        matching.into_iter().map(|(t, _)| self.to_task_response(t)).collect()
+    }
+    
+    /// Hand an open manual task to someone; `None` if there is no such manual task
+    pub async fn assign_manual_task(&self, task_id: Uuid, assignee: &str) -> Result<Option<TaskResponse>> {
+        if assignee.trim().is_empty() {
+            bail!("Assignee is required");
+        }
+        
+        let mut tasks = self.tasks.write().await;
+        let Some(task) = tasks.get_mut(&task_id).filter(|t| t.manual.is_some()) else {
+            return Ok(None);
+        };
+        if task.state.is_terminal() {
+            bail!("Task {} is already {}", task_id, task.state);
+        }
+        
+        if let Some(manual) = task.manual.as_mut() {
+            manual.assignee = Some(assignee.to_string());
+        }
+        Ok(Some(self.to_task_response(task)))
+    }
+    
+    /// Close an open manual task with what was done. Tasks it depends on
+    /// have to be complete first. `None` if there is no such manual task.
+    pub async fn complete_manual_task(&self, task_id: Uuid, notes: Option<String>, completed_by: Option<String>) -> Result<Option<TaskResponse>> {
+        let mut tasks = self.tasks.write().await;
+        let Some(task) = tasks.get(&task_id).filter(|t| t.manual.is_some()) else {
+            return Ok(None);
+        };
+        if task.state != TaskState::Scheduled {
+            bail!("Task {} is already {}", task_id, task.state);
+        }
+        if dependency_status(task.depends_on.iter().map(|id| tasks.get(id).map(|d| d.state))) != DependencyStatus::Ready {
+            bail!("Task {} is still waiting on the tasks it depends on", task_id);
+        }
+        
+        let task = tasks.get_mut(&task_id).context("Task not found")?;
+        let completed_by = completed_by.or_else(|| task.manual.as_ref().and_then(|m| m.assignee.clone()));
+        task.state = TaskState::Completed;
+        task.completed_at = Some(Utc::now());
+        task.result = Some(serde_json::json!({ "notes": notes, "completed_by": completed_by }));
+        if let Some(manual) = task.manual.as_mut() {
+            manual.notes = notes;
+            manual.completed_by = completed_by;
+        }
+        self.audit_task(task, TaskState::Scheduled);
+        let (workflow_id, response) = (task.workflow_id, self.to_task_response(task));
+        drop(tasks);
+        
+        self.update_workflow_progress(workflow_id).await;
+        Ok(Some(response))
+    }
+    
+    /// Exhausted tasks with what is known about their failure, most recent first
+    pub async fn dead_letters(&self) -> Vec<DeadLetterResponse> {
+        let workflows = self.workflows.read().await;
+        let tasks = self.tasks.read().await;
+        
+        let mut dead: Vec<&StoredTask> = tasks.values().filter(|t| t.state == TaskState::Exhausted).collect();
+        dead.sort_by_key(|t| std::cmp::Reverse(t.completed_at));
+        dead.into_iter()
+            .map(|t| {
+                let workflow = workflows.get(&t.workflow_id);
+                DeadLetterResponse {
+                    campaign_name: workflow.map(|w| w.campaign_name.clone()),
+                    workflow_status: workflow.map(|w| w.state.to_string()),
+                    attempts: t.retry_count + 1,
+                    exhausted_at: t.completed_at.map(|d| d.to_rfc3339()),
+                    last_result: t.result.clone(),
+                    payload: t.payload.clone(),
+                    task: self.to_task_response(t),
+                }
+            })
+            .collect()
+    }
+    
+    /// Put an exhausted task back in the queue with its attempts reset,
+    /// optionally replacing its payload. `None` if the task is unknown.
+    pub async fn requeue_task(&self, task_id: Uuid, payload: Option<serde_json::Value>, requested_by: Option<String>) -> Result<Option<TaskResponse>> {
+        let workflows = self.workflows.read().await;
+        let mut tasks = self.tasks.write().await;
+        let Some(task) = tasks.get_mut(&task_id) else {
+            return Ok(None);
+        };
+        
+        if task.state != TaskState::Exhausted {
+            bail!("Only exhausted tasks can be requeued; task is {}", task.state);
+        }
+        if workflows.get(&task.workflow_id).is_some_and(|w| w.state.is_terminal()) {
+            bail!("Workflow has already ended");
+        }
+        if payload.as_ref().is_some_and(|p| !p.is_object()) {
+            bail!("payload must be a JSON object");
+        }
+        
+        let details = serde_json::json!({
+            "operation": "requeue",
+            "from_state": task.state.to_string(),
+            "to_state": TaskState::Scheduled.to_string(),
+            "task_type": task.task_type.to_string(),
+            "workflow_id": task.workflow_id,
+            "previous_attempts": task.retry_count + 1,
+            "previous_error": task.error,
+            "previous_payload": task.payload,
+            "payload": payload,
+        });
+        
+        task.state = TaskState::Scheduled;
+        task.retry_count = 0;
+        task.scheduled_at = Some(Utc::now());
+        task.started_at = None;
+        task.completed_at = None;
+        task.error = None;
+        if payload.is_some() {
+            task.payload = payload;
+        }
+        let response = self.to_task_response(task);
+        drop(tasks);
+        drop(workflows);
+        
+        self.record_audit("update", "task", task_id, requested_by, details);
+        Ok(Some(response))
+    }
+    
+    /// Send an entry to the audit trail in the background; a failed write is logged, not returned
+    fn record_audit(&self, action: &str, entity_type: &str, entity_id: Uuid, agent_id: Option<String>, details: serde_json::Value) {
+        let Some(audit) = self.audit.clone() else {
+            return;
+        };
+        let record = AuditRecord {
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            agent_id,
+            details,
+        };
+        tokio::spawn(async move {
+            if let Err(e) = audit.record(&record).await {
+                warn!("Failed to record {} of {} {} in audit trail: {:#}", record.action, record.entity_type, record.entity_id, e);
+            }
+        });
+    }
+    
+    /// Record a workflow's move into its current state; `from` is `None` when it is created
+    fn audit_workflow(&self, workflow: &StoredWorkflow, from: Option<WorkflowState>) {
+        self.audit_transition("workflow", workflow.id, from.map(|s| s.to_string()), workflow.state.to_string(), serde_json::json!({
+            "campaign_name": workflow.campaign_name,
+            "client_id": workflow.client_id,
+            "supplier_count": workflow.suppliers.len(),
+            "cloned_from": workflow.cloned_from,
+        }));
+    }
+    
+    /// Record a task's move out of `from` into its current state
+    fn audit_task(&self, task: &StoredTask, from: TaskState) {
+        self.audit_transition("task", task.id, Some(from.to_string()), task.state.to_string(), serde_json::json!({
+            "task_type": task.task_type.to_string(),
+            "workflow_id": task.workflow_id,
+            "supplier_id": task.supplier_id,
+            "attempt": task.retry_count + 1,
+            "error": task.error,
+        }));
+    }
+    
+    /// Record an escalation's move into its current state; `from` is `None` when it is raised
+    fn audit_escalation(&self, escalation: &StoredEscalation, from: Option<EscalationState>) {
+        self.audit_transition("escalation", escalation.id, from.map(|s| s.to_string()), escalation.state.to_string(), serde_json::json!({
+            "workflow_id": escalation.workflow_id,
+            "supplier_id": escalation.supplier_id,
+            "severity": escalation.severity,
+            "reason": escalation.reason,
+            "assignee": escalation.assignee,
+            "resolution": escalation.resolution,
+        }));
+    }
+    
+    /// Record a state change with the states before and after alongside `details`.
+    /// Creation is audited as `create`, or `escalate` for escalations.
+    fn audit_transition(&self, entity_type: &str, entity_id: Uuid, from: Option<String>, to: String, details: serde_json::Value) {
+        let action = match (&from, entity_type) {
+            (None, "escalation") => "escalate",
+            (None, _) => "create",
+            (Some(_), _) => "update",
+        };
+        let mut details = match details {
+            serde_json::Value::Object(details) => details,
+            _ => serde_json::Map::new(),
+        };
+        details.insert("from_state".to_string(), serde_json::json!(from));
+        details.insert("to_state".to_string(), serde_json::json!(to));
+        self.record_audit(action, entity_type, entity_id, None, serde_json::Value::Object(details));
+    }
+    
+    /// Advance or reschedule a supplier's tasks in active workflows after a classified reply
+    pub async fn handle_reply(&self, event: ReplyClassifiedEvent) -> Result<ReplyHandledResponse> {
+        let category = event.classification.category;
+        let mut response = ReplyHandledResponse {
+            workflows_updated: 0,
+            tasks_scheduled: 0,
+            tasks_skipped: 0,
+            tasks_rescheduled: 0,
+            escalations_created: 0,
+        };
+        
+        // Supplier actually engaged; auto-replies and misdirected mail do not count
+        let engaged = !matches!(category, ReplyClassification::OutOfOffice | ReplyClassification::WrongContact);
+        let received_at = event_time(&event.received_at)?;
+        
+        let workflows: Vec<(Uuid, WorkflowConfig)> = {
+            let mut workflows = self.workflows.write().await;
+            workflows.values_mut()
+                .filter(|w| w.receives(event.supplier_id, None))
+                .map(|w| {
+                    if engaged {
+                        w.responded.insert(event.supplier_id);
+                        w.progress.responded = w.responded.len();
+                        let activity = w.activity.entry(event.supplier_id).or_default();
+                        activity.replies += 1;
+                        activity.first_reply_at.get_or_insert(received_at);
+                        activity.last_reply_at = Some(received_at);
+                        activity.data_complete |= category == ReplyClassification::CompleteResponse;
+                    }
+                    (w.id, w.config.clone())
+                })
+                .collect()
+        };
+        
+        for (workflow_id, config) in workflows {
+            let scheduler = WorkflowScheduler::new(config.clone());
+            let mut new_tasks = Vec::new();
+            
+            {
+                let mut tasks = self.tasks.write().await;
+                let follow_ups = tasks.values_mut().filter(|t| {
+                    t.workflow_id == workflow_id
+                        && t.supplier_id == event.supplier_id
+                        && t.task_type == TaskType::FollowUp
+                        && t.state == TaskState::Scheduled
+                });
+                
+                match category {
+                    // Chasing is pointless once the data is in, refused, or going to the wrong person
+                    ReplyClassification::CompleteResponse
+                    | ReplyClassification::Refusal
+                    | ReplyClassification::WrongContact => {
+                        // Phases decide for themselves through their entry criteria
+                        for task in follow_ups.filter(|t| t.phase.is_none()) {
+                            task.state = TaskState::Skipped;
+                            self.audit_task(task, TaskState::Scheduled);
+                            response.tasks_skipped += 1;
+                        }
+                    }
+                    // Push chasers past the absence: to the day after the contact is back
+                    // when the auto-reply says, otherwise by one follow-up interval
+                    ReplyClassification::OutOfOffice => {
+                        let mut follow_ups: Vec<&mut StoredTask> = follow_ups.collect();
+                        let next = follow_ups.iter().map(|t| t.scheduled_at.unwrap_or_else(Utc::now)).min();
+                        let delay = match (event.return_date, next) {
+                            (Some(back), Some(next)) => {
+                                let resume = (back + Duration::days(1)).and_time(NaiveTime::MIN).and_utc();
+                                (resume - next).max(Duration::zero())
+                            }
+                            _ => Duration::days(config.follow_up_interval_days as i64),
+                        };
+                        // Later chasers move by the same amount so their spacing is kept
+                        if delay > Duration::zero() {
+                            for task in follow_ups.iter_mut() {
+                                task.scheduled_at = Some(task.scheduled_at.unwrap_or_else(Utc::now) + delay);
+                                response.tasks_rescheduled += 1;
+                            }
+                        }
+                    }
+                    // The supplier is engaged: give them a full interval from this reply before the next chaser
+                    ReplyClassification::PartialResponse | ReplyClassification::Question => {
+                        let mut follow_ups: Vec<&mut StoredTask> = follow_ups.collect();
+                        let resume = Utc::now() + Duration::days(config.follow_up_interval_days as i64);
+                        let next = follow_ups.iter().map(|t| t.scheduled_at.unwrap_or_else(Utc::now)).min();
+                        if let Some(delay) = next.map(|next| resume - next).filter(|delay| *delay > Duration::zero()) {
+                            for task in follow_ups.iter_mut() {
+                                task.scheduled_at = Some(task.scheduled_at.unwrap_or_else(Utc::now) + delay);
+                                response.tasks_rescheduled += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            
+            if event.document_count > 0
+                && matches!(category, ReplyClassification::CompleteResponse | ReplyClassification::PartialResponse)
+            {
+                let processing = scheduler.schedule_document_processing(workflow_id, event.supplier_id);
+                new_tasks.push(scheduler.schedule_validation(workflow_id, event.supplier_id).after(processing.id));
+                new_tasks.push(processing);
+            }
+            
+            let escalation = match category {
+                ReplyClassification::Refusal => Some(("Supplier declined to provide compliance data", "high")),
+                ReplyClassification::WrongContact => Some(("Supplier contact is incorrect and needs updating", "medium")),
+                ReplyClassification::Question if event.classification.confidence >= QUESTION_ESCALATION_CONFIDENCE => {
+                    Some(("Supplier asked a question about the request", "medium"))
+                }
+                _ => None,
+            };
+            if let Some((reason, severity)) = escalation {
+                self.create_escalation(workflow_id, event.supplier_id, reason.to_string(), severity.to_string()).await?;
+                response.escalations_created += 1;
+                
+                let mut workflows = self.workflows.write().await;
+                if let Some(workflow) = workflows.get_mut(&workflow_id) {
+                    workflow.progress.escalated += 1;
+                }
+            }
+            
+            response.tasks_scheduled += new_tasks.len();
+            let mut tasks = self.tasks.write().await;
+            for st in new_tasks {
+                let task = StoredTask::scheduled(st);
+                tasks.insert(task.id, task);
+            }
+            drop(tasks);
+            
+            self.update_workflow_progress(workflow_id).await;
+            response.workflows_updated += 1;
+        }
+        
+        Ok(response)
+    }
+    
+    /// Dispatch an event from another service to the handler for its type;
+    /// types the workflow has no use for are acknowledged without handling
+    pub async fn handle_domain_event(&self, envelope: EventEnvelope) -> Result<EventHandledResponse> {
+        let (event_id, event_type) = (envelope.id, envelope.event_type().to_string());
+        let outcome = match envelope.event {
+            DomainEvent::EmailSent(event) => Some(serde_json::to_value(self.handle_email_sent(event.into()).await?)?),
+            DomainEvent::ReplyClassified(event) => Some(serde_json::to_value(self.handle_reply(event.into()).await?)?),
+            DomainEvent::DocumentExtracted(event) => {
+                Some(serde_json::to_value(self.handle_document_extracted(event.into()).await?)?)
+            }
+            DomainEvent::SupplierSuppressed(event) => {
+                Some(serde_json::to_value(self.handle_suppression(event.into()).await?)?)
+            }
+            DomainEvent::SupplierUnresponsive(event) => {
+                Some(serde_json::to_value(self.handle_unresponsive(event.into()).await?)?)
+            }
+            DomainEvent::CertificationExpiring(event) => {
+                Some(serde_json::to_value(self.handle_certification_expiring(event).await?)?)
+            }
+            DomainEvent::SupplierCreated(_)
+            | DomainEvent::EmailBounced(_)
+            | DomainEvent::PfasDetected(_)
+            | DomainEvent::WorkflowEscalated(_) => None,
+        };
+        Ok(EventHandledResponse { event_id, event_type, handled: outcome.is_some(), outcome })
+    }
+    
+    /// Count an email delivered to a supplier as contact
+    pub async fn handle_email_sent(&self, event: EmailSentEvent) -> Result<ProgressUpdatedResponse> {
+        let sent_at = event_time(&event.sent_at)?;
+        let workflow_ids: Vec<Uuid> = {
+            let mut workflows = self.workflows.write().await;
+            workflows.values_mut()
+                .filter(|w| w.receives(event.supplier_id, event.campaign_id))
+                .map(|w| {
+                    let activity = w.activity.entry(event.supplier_id).or_default();
+                    activity.emails_sent += 1;
+                    activity.last_sent_at = activity.last_sent_at.max(Some(sent_at));
+                    w.id
+                })
+                .collect()
+        };
+        
+        for workflow_id in &workflow_ids {
+            self.update_workflow_progress(*workflow_id).await;
+        }
+        Ok(ProgressUpdatedResponse { workflows_updated: workflow_ids.len() })
+    }
+    
+    /// Record documents extracted from a supplier's reply
+    pub async fn handle_document_extracted(&self, event: DocumentExtractedEvent) -> Result<ProgressUpdatedResponse> {
+        event_time(&event.extracted_at)?;
+        let mut workflows = self.workflows.write().await;
+        let mut updated = 0;
+        for workflow in workflows.values_mut().filter(|w| w.receives(event.supplier_id, event.campaign_id)) {
+            let activity = workflow.activity.entry(event.supplier_id).or_default();
+            activity.documents_extracted += event.document_ids.len();
+            activity.cas_numbers_found += event.cas_numbers_found;
+            activity.needs_review |= event.needs_review;
+            workflow.progress.documents_received = workflow.activity.values().filter(|a| a.documents_extracted > 0).count();
+            updated += 1;
+        }
+        
+        Ok(ProgressUpdatedResponse { workflows_updated: updated })
+    }
+    
+    /// Each supplier's status and activity in a workflow; `None` if the workflow does not exist
+    pub async fn supplier_progress(&self, workflow_id: Uuid) -> Result<Option<Vec<SupplierProgress>>> {
+        let reached = self.reached_by_outreach(workflow_id).await;
+        let mut open_escalations: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for escalation in self.escalations.read().await.values().filter(|e| e.workflow_id == workflow_id && e.is_open()) {
+            open_escalations.entry(escalation.supplier_id).or_default().push(escalation.reason.clone());
+        }
+        
+        let workflows = self.workflows.read().await;
+        let Some(workflow) = workflows.get(&workflow_id) else {
+            return Ok(None);
+        };
+        let contacted = contacted_suppliers(workflow, reached);
+        
+        Ok(Some(workflow.suppliers.iter().map(|supplier_id| {
+            let activity = workflow.activity.get(supplier_id).cloned().unwrap_or_default();
+            let escalations = open_escalations.remove(supplier_id).unwrap_or_default();
+            let status = SupplierStatus::derive(
+                contacted.contains(supplier_id),
+                workflow.responded.contains(supplier_id),
+                activity.data_complete,
+                !escalations.is_empty(),
+            );
+            
+            SupplierProgress {
+                supplier_id: *supplier_id,
+                status: status.to_string(),
+                open_escalations: escalations,
+                manual: workflow.manual.contains(supplier_id),
+                emails_sent: activity.emails_sent,
+                last_sent_at: activity.last_sent_at.map(|d| d.to_rfc3339()),
+                replies: activity.replies,
+                last_reply_at: activity.last_reply_at.map(|d| d.to_rfc3339()),
+                documents_extracted: activity.documents_extracted,
+                cas_numbers_found: activity.cas_numbers_found,
+                needs_review: activity.needs_review,
+            }
+        }).collect()))
+    }
+    
+    /// Planned and actual task times per supplier, with follow-ups projected
+    /// for suppliers whose outreach has not gone out yet; `None` if the
+    /// workflow does not exist
+    pub async fn timeline(&self, workflow_id: Uuid) -> Result<Option<TimelineResponse>> {
+        let mut tasks_by_supplier: HashMap<Uuid, Vec<StoredTask>> = HashMap::new();
+        for task in self.tasks.read().await.values().filter(|t| t.workflow_id == workflow_id) {
+            tasks_by_supplier.entry(task.supplier_id).or_default().push(task.clone());
+        }
+        
+        let workflows = self.workflows.read().await;
+        let Some(workflow) = workflows.get(&workflow_id) else {
+            return Ok(None);
+        };
+        let scheduler = WorkflowScheduler::new(workflow.config.clone());
+        
+        let suppliers = workflow.suppliers.iter().map(|supplier_id| {
+            let mut tasks = tasks_by_supplier.remove(supplier_id).unwrap_or_default();
+            tasks.sort_by_key(|t| t.scheduled_at);
+            let mut entries: Vec<TimelineEntry> = tasks.iter().map(|t| {
+                let start = t.started_at.or(t.scheduled_at).unwrap_or(workflow.start_date);
+                TimelineEntry {
+                    task_id: Some(t.id),
+                    task_type: t.task_type.to_string(),
+                    status: t.state.to_string(),
+                    start: start.to_rfc3339(),
+                    end: t.completed_at.unwrap_or(start).to_rfc3339(),
+                    scheduled_at: t.scheduled_at.map(|d| d.to_rfc3339()),
+                    started_at: t.started_at.map(|d| d.to_rfc3339()),
+                    completed_at: t.completed_at.map(|d| d.to_rfc3339()),
+                    projected: false,
+                }
+            }).collect();
+            
+            // Follow-ups are only scheduled once outreach is sent, so show where they would land
+            let pending_outreach = tasks.iter()
+                .find(|t| t.task_type == TaskType::InitialOutreach && !t.state.is_terminal())
+                .and_then(|t| t.scheduled_at);
+            let chasing = !workflow.responded.contains(supplier_id) && !workflow.manual.contains(supplier_id);
+            if let Some(outreach_at) = pending_outreach.filter(|_| chasing) {
+                entries.extend(scheduler.project_follow_ups(outreach_at).into_iter().map(|at| TimelineEntry {
+                    task_id: None,
+                    task_type: TaskType::FollowUp.to_string(),
+                    status: "projected".to_string(),
+                    start: at.to_rfc3339(),
+                    end: at.to_rfc3339(),
+                    scheduled_at: Some(at.to_rfc3339()),
+                    started_at: None,
+                    completed_at: None,
+                    projected: true,
+                }));
+            }
+            
+            SupplierTimelineResponse { supplier_id: *supplier_id, entries }
+        }).collect();
+        
+        Ok(Some(TimelineResponse {
+            workflow_id,
+            campaign_name: workflow.campaign_name.clone(),
+            start_date: workflow.start_date.to_rfc3339(),
+            deadline: workflow.deadline.to_rfc3339(),
+            suppliers,
+        }))
+    }
+    
+    /// SLA breaches and at-risk checks for a workflow's suppliers; `None` if the workflow does not exist
+    pub async fn sla_report(&self, workflow_id: Uuid, now: DateTime<Utc>) -> Result<Option<SlaReportResponse>> {
+        let mut contacts: HashMap<Uuid, Vec<DateTime<Utc>>> = HashMap::new();
+        for task in self.tasks.read().await.values() {
+            if task.workflow_id == workflow_id
+                && matches!(task.task_type, TaskType::InitialOutreach | TaskType::FollowUp)
+                && task.state == TaskState::Completed
+            {
+                if let Some(completed_at) = task.completed_at {
+                    contacts.entry(task.supplier_id).or_default().push(completed_at);
+                }
+            }
+        }
+        
+        let workflows = self.workflows.read().await;
+        let Some(workflow) = workflows.get(&workflow_id) else {
+            return Ok(None);
+        };
+        let open = !workflow.state.is_terminal();
+        
+        let mut checks = Vec::new();
+        for supplier_id in &workflow.suppliers {
+            let mut contacts = contacts.remove(supplier_id).unwrap_or_default();
+            contacts.sort();
+            let activity = workflow.activity.get(supplier_id).cloned().unwrap_or_default();
+            // Nothing more is owed once the supplier is handled by hand, has sent everything, or has had every chaser
+            let closed = workflow.manual.contains(supplier_id)
+                || activity.data_complete
+                || contacts.len() > workflow.config.max_follow_ups.max(0) as usize;
+            let timeline = SupplierTimeline {
+                supplier_id: *supplier_id,
+                contacts,
+                replied_at: activity.last_reply_at,
+                closed,
+            };
+            checks.extend(self.sla.evaluate(workflow.start_date, &timeline, open, now));
+        }
+        
+        let item = |check: &SlaCheck| SlaItemResponse {
+            supplier_id: check.supplier_id,
+            sla: check.kind.to_string(),
+            due_at: check.due_at.to_rfc3339(),
+            completed_at: check.completed_at.map(|d| d.to_rfc3339()),
+            hours_remaining: (check.due_at - check.completed_at.unwrap_or(now)).num_hours(),
+        };
+        let with_status = |status: SlaStatus| checks.iter().filter(move |c| c.status == status);
+        
+        Ok(Some(SlaReportResponse {
+            workflow_id,
+            generated_at: now.to_rfc3339(),
+            first_contact_hours: self.sla.first_contact.num_hours(),
+            follow_up_days: self.sla.follow_up.num_days(),
+            met: with_status(SlaStatus::Met).count(),
+            pending: with_status(SlaStatus::Pending).count(),
+            breaches: with_status(SlaStatus::Breached).map(item).collect(),
+            at_risk: with_status(SlaStatus::AtRisk).map(item).collect(),
+        }))
+    }
+    
+    /// Where each supplier stands in the workflow's phases; `None` if the
+    /// workflow does not exist. Workflows without phases are read by task type.
+    pub async fn phase_status(&self, workflow_id: Uuid) -> Option<PhaseStatusResponse> {
+        let workflow = self.workflows.read().await.get(&workflow_id)?.clone();
+        let tasks = self.tasks.read().await;
+        let phases = &workflow.config.phases;
+        let mut by_supplier: HashMap<Uuid, Vec<(&StoredTask, CampaignPhase)>> = HashMap::new();
+        for task in tasks.values().filter(|t| t.workflow_id == workflow_id) {
+            let phase = match task.phase {
+                Some(index) => phases.get(index).map(|p| p.phase),
+                None => CampaignPhase::of_task(task.task_type),
+            };
+            if let Some(phase) = phase {
+                by_supplier.entry(task.supplier_id).or_default().push((task, phase));
+            }
+        }
+        
+        let suppliers = workflow.suppliers.iter().map(|supplier_id| {
+            let mut tasks = by_supplier.remove(supplier_id).unwrap_or_default();
+            tasks.sort_by_key(|(t, phase)| (t.phase, *phase, t.scheduled_at));
+            let names = |state: TaskState| tasks.iter().filter(|(t, _)| t.state == state).map(|(_, p)| p.to_string()).collect();
+            let current = tasks.iter().rfind(|(t, _)| t.started_at.is_some() && t.state != TaskState::Skipped);
+            let next = tasks.iter().find(|(t, _)| matches!(t.state, TaskState::Scheduled | TaskState::Failed));
+            
+            SupplierPhaseResponse {
+                supplier_id: *supplier_id,
+                current_phase: current.map(|(_, p)| p.to_string()),
+                entered_at: current.and_then(|(t, _)| t.started_at).map(|d| d.to_rfc3339()),
+                completed_phases: names(TaskState::Completed),
+                skipped_phases: names(TaskState::Skipped),
+                next_phase: next.map(|(_, p)| p.to_string()),
+                next_at: next.and_then(|(t, _)| t.scheduled_at).map(|d| d.to_rfc3339()),
+                closed: workflow.manual.contains(supplier_id)
+                    || (!tasks.is_empty() && tasks.iter().all(|(t, _)| t.state.is_terminal())),
+            }
+        }).collect();
+        
+        Some(PhaseStatusResponse {
+            workflow_id,
+            phased: !phases.is_empty(),
+            phases: phases.clone(),
+            suppliers,
+        })
+    }
+    
+    /// Funnel and response figures of a campaign, compared with the averages
+    /// of the client's earlier campaigns that ran; `None` if the workflow
+    /// does not exist
+    pub async fn metrics(&self, workflow_id: Uuid, now: DateTime<Utc>) -> Option<MetricsResponse> {
+        // A stall is the campaign's doing, not the supplier's
+        let escalated: HashSet<(Uuid, Uuid)> = self.escalations.read().await.values()
+            .filter(|e| !e.reason.starts_with(STALLED_REASON))
+            .map(|e| (e.workflow_id, e.supplier_id))
+            .collect();
+        
+        let workflows = self.workflows.read().await;
+        let workflow = workflows.get(&workflow_id)?;
+        let tasks = self.tasks.read().await;
+        let mut tasks_by_workflow: HashMap<Uuid, Vec<&StoredTask>> = HashMap::new();
+        for task in tasks.values() {
+            tasks_by_workflow.entry(task.workflow_id).or_default().push(task);
+        }
+        let summarize = |w: &StoredWorkflow| {
+            let tasks = tasks_by_workflow.get(&w.id).map(Vec::as_slice).unwrap_or_default();
+            metrics::summarize(&supplier_funnels(w, tasks, &escalated))
+        };
+        
+        let (funnel, summary) = summarize(workflow);
+        let history: Vec<MetricsSummary> = workflows.values()
+            .filter(|w| w.client_id == workflow.client_id && w.id != workflow_id && w.start_date < workflow.start_date)
+            .filter(|w| !matches!(w.state, WorkflowState::Pending | WorkflowState::Cancelled))
+            .map(|w| summarize(w).1)
+            .collect();
+        
+        Some(MetricsResponse {
+            workflow_id,
+            campaign_name: workflow.campaign_name.clone(),
+            status: workflow.state.to_string(),
+            generated_at: now.to_rfc3339(),
+            funnel: FunnelResponse {
+                suppliers: funnel.suppliers,
+                contacted: funnel.contacted,
+                responded: funnel.responded,
+                complete: funnel.complete,
+                escalated: funnel.escalated,
+            },
+            metrics: summary_response(&summary),
+            client_history: MetricsSummary::average(&history).map(|averages| ClientHistoryResponse {
+                campaigns: history.len(),
+                difference: summary_response(&summary.difference(&averages)),
+                averages: summary_response(&averages),
+            }),
+        })
+    }
+    
+    /// Project when an active campaign's silent suppliers will answer and
+    /// how many will, from their compliance history and the replies so far
+    pub async fn forecast(&self, workflow_id: Uuid, now: DateTime<Utc>) -> Result<Option<ForecastResponse>> {
+        // When each supplier was first asked: by its outreach, or when outreach is due
+        let mut asked_at: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+        for task in self.tasks.read().await.values() {
+            if task.workflow_id != workflow_id || task.task_type != TaskType::InitialOutreach {
+                continue;
+            }
+            let at = match task.state {
+                TaskState::Completed => task.completed_at,
+                state if !state.is_terminal() => task.scheduled_at.map(|at| at.max(now)),
+                _ => None,
+            };
+            if let Some(at) = at {
+                let first = asked_at.entry(task.supplier_id).or_insert(at);
+                *first = (*first).min(at);
+            }
+        }
+        
+        let workflow = {
+            let workflows = self.workflows.read().await;
+            let Some(workflow) = workflows.get(&workflow_id) else {
+                return Ok(None);
+            };
+            if workflow.state != WorkflowState::Active {
+                bail!("Only active campaigns can be forecast; campaign is {}", workflow.state);
+            }
+            workflow.clone()
+        };
+        
+        let histories: HashMap<Uuid, ResponseHistory> = self.compliance_histories(&workflow.suppliers).await
+            .into_iter()
+            .map(|(supplier_id, entries)| (supplier_id, ResponseHistory::from_entries(&entries, workflow_id)))
+            .collect();
+        let mut pooled = ResponseHistory::default();
+        for history in histories.values() {
+            pooled.extend(history);
+        }
+        // Replies already in show how this campaign is going
+        for supplier_id in &workflow.responded {
+            let asked = asked_at.get(supplier_id).copied().unwrap_or(workflow.start_date);
+            if let Some(replied_at) = workflow.activity.get(supplier_id).and_then(|a| a.last_reply_at) {
+                pooled.answered_after.push((replied_at - asked).max(Duration::zero()));
+            }
+        }
+        
+        let outlooks: Vec<SupplierOutlook> = workflow.suppliers.iter()
+            .filter(|id| !workflow.responded.contains(id) && !workflow.manual.contains(id))
+            .map(|id| {
+                let asked = asked_at.get(id).copied().unwrap_or(now);
+                SupplierOutlook::project(*id, histories.get(id), &pooled, asked, now)
+            })
+            .collect();
+        
+        let total = workflow.suppliers.len();
+        let responded = workflow.responded.len();
+        let expected: f64 = outlooks.iter().map(|o| o.chance).sum();
+        let by_deadline: f64 = outlooks.iter()
+            .filter(|o| o.expected_at.is_some_and(|at| at <= workflow.deadline))
+            .map(|o| o.chance)
+            .sum();
+        let rate = |answers: f64| if total > 0 { (responded as f64 + answers) / total as f64 * 100.0 } else { 0.0 };
+        // Done once the last likely answer is in; with none left to wait for, it is as good as done now
+        let expected_completion = outlooks.iter()
+            .filter(|o| o.is_likely())
+            .filter_map(|o| o.expected_at)
+            .max()
+            .unwrap_or(now);
+        
+        Ok(Some(ForecastResponse {
+            workflow_id,
+            campaign_name: workflow.campaign_name,
+            generated_at: now.to_rfc3339(),
+            deadline: workflow.deadline.to_rfc3339(),
+            total_suppliers: total,
+            responded,
+            expected_completion_date: expected_completion.to_rfc3339(),
+            expected_response_rate: rate(expected),
+            expected_response_rate_by_deadline: rate(by_deadline),
+            likely_to_miss_deadline: expected_completion > workflow.deadline,
+            suppliers: outlooks.iter()
+                .map(|o| SupplierForecastResponse {
+                    supplier_id: o.supplier_id,
+                    basis: o.basis.to_string(),
+                    response_chance: o.chance,
+                    expected_response_at: o.expected_at.map(|d| d.to_rfc3339()),
+                })
+                .collect(),
+        }))
+    }
+    
+    /// Compliance history of the suppliers the database knows; a failed lookup only narrows the forecast
+    async fn compliance_histories(&self, supplier_ids: &[Uuid]) -> HashMap<Uuid, Vec<ComplianceHistoryEntry>> {
+        let mut histories = HashMap::new();
+        let Some(suppliers) = &self.suppliers else {
+            return histories;
+        };
+        for supplier_id in supplier_ids {
+            match suppliers.find_by_id((*supplier_id).into()).await {
+                Ok(Some(supplier)) => {
+                    histories.insert(*supplier_id, supplier.compliance_history);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to look up supplier {} for forecasting: {:#}", supplier_id, e),
+            }
+        }
+        histories
+    }
+    
+    /// Suppliers whose outreach task has completed
+    async fn reached_by_outreach(&self, workflow_id: Uuid) -> HashSet<Uuid> {
+        let tasks = self.tasks.read().await;
+        tasks.values()
+            .filter(|t| t.workflow_id == workflow_id && t.task_type == TaskType::InitialOutreach && t.state == TaskState::Completed)
+            .map(|t| t.supplier_id)
+            .collect()
+    }
+    
+    /// Stop automated outreach to a suppressed supplier and hand it to a person
+    pub async fn handle_suppression(&self, event: SupplierSuppressedEvent) -> Result<SuppressionHandledResponse> {
+        let mut response = SuppressionHandledResponse {
+            workflows_updated: 0,
+            tasks_skipped: 0,
+            escalations_created: 0,
+        };
+        
+        // Repeat events for a supplier already under manual handling are no-ops
+        let workflow_ids: Vec<Uuid> = {
+            let mut workflows = self.workflows.write().await;
+            workflows.values_mut()
+                .filter(|w| w.state == WorkflowState::Active && w.suppliers.contains(&event.supplier_id))
+                .filter_map(|w| w.manual.insert(event.supplier_id).then_some(w.id))
+                .collect()
+        };
+        
+        for workflow_id in workflow_ids {
+            {
+                let mut tasks = self.tasks.write().await;
+                let outreach = tasks.values_mut().filter(|t| {
+                    t.workflow_id == workflow_id
+                        && t.supplier_id == event.supplier_id
+                        && matches!(t.task_type, TaskType::InitialOutreach | TaskType::FollowUp)
+                        && t.state == TaskState::Scheduled
+                });
+                for task in outreach {
+                    task.state = TaskState::Skipped;
+                    self.audit_task(task, TaskState::Scheduled);
+                    response.tasks_skipped += 1;
+                }
+            }
+            
+            self.create_escalation(
+                workflow_id,
+                event.supplier_id,
+                "Supplier opted out of automated email; handle manually".to_string(),
+                "high".to_string(),
+            ).await?;
+            response.escalations_created += 1;
+            
+            let mut workflows = self.workflows.write().await;
+            if let Some(workflow) = workflows.get_mut(&workflow_id) {
+                workflow.progress.escalated += 1;
+            }
+            drop(workflows);
+            
+            response.workflows_updated += 1;
+        }
+        
+        Ok(response)
+    }
+    
+    /// Escalate silent suppliers in workflows with `auto_escalate` on: those
+    /// first contacted `escalation_threshold_days` ago, and all of them once
+    /// the deadline is within `deadline_warning_days`. Severity follows the
+    /// time left, and open escalations are raised as the deadline nears
+    /// rather than duplicated.
+    pub async fn run_escalation_checks(&self, now: DateTime<Utc>) -> Result<EscalationSweep> {
+        let workflows: Vec<(Uuid, WorkflowConfig, DateTime<Utc>, Vec<Uuid>)> = {
+            let workflows = self.workflows.read().await;
+            workflows.values()
+                .filter(|w| w.state == WorkflowState::Active && w.config.auto_escalate)
+                .map(|w| {
+                    let silent = w.suppliers.iter()
+                        .filter(|s| !w.responded.contains(s) && !w.manual.contains(s))
+                        .copied()
+                        .collect();
+                    (w.id, w.config.clone(), w.deadline, silent)
+                })
+                .collect()
+        };
+        
+        let contacted_at: HashMap<(Uuid, Uuid), DateTime<Utc>> = {
+            let tasks = self.tasks.read().await;
+            tasks.values()
+                .filter(|t| t.task_type == TaskType::InitialOutreach && t.state == TaskState::Completed)
+                .filter_map(|t| t.completed_at.map(|at| ((t.workflow_id, t.supplier_id), at)))
+                .collect()
+        };
+        
+        let mut sweep = EscalationSweep::default();
+        for (workflow_id, config, deadline, silent) in workflows {
+            let scheduler = WorkflowScheduler::new(config.clone());
+            let risk = scheduler.deadline_severity(deadline, now);
+            let days_left = (deadline - now).num_days();
+            
+            for supplier_id in silent {
+                let silent_for = contacted_at.get(&(workflow_id, supplier_id)).map(|at| (now - *at).num_days());
+                if let Some(days) = silent_for.filter(|days| *days >= config.escalation_threshold_days as i64) {
+                    let reason = format!("{} {} days after first contact", NO_RESPONSE_REASON, days);
+                    self.raise_escalation(workflow_id, supplier_id, NO_RESPONSE_REASON, reason, risk.max(DeadlineRisk::Medium), &mut sweep).await?;
+                }
+                
+                if risk >= DeadlineRisk::Medium {
+                    let reason = if deadline > now {
+                        format!("{} in {} days without a response", DEADLINE_REASON, days_left)
+                    } else {
+                        format!("{} passed without a response", DEADLINE_REASON)
+                    };
+                    self.raise_escalation(workflow_id, supplier_id, DEADLINE_REASON, reason, risk, &mut sweep).await?;
+                }
+            }
+        }
+        
+        Ok(sweep)
+    }
+    
+    /// Create an escalation unless one with the same reason prefix exists for
+    /// the supplier; an open one is brought up to `severity` if it is lower
+    async fn raise_escalation(
+        &self,
+        workflow_id: Uuid,
+        supplier_id: Uuid,
+        prefix: &str,
+        reason: String,
+        severity: DeadlineRisk,
+        sweep: &mut EscalationSweep,
+    ) -> Result<()> {
+        {
+            let mut escalations = self.escalations.write().await;
+            let mut existing = escalations.values_mut()
+                .filter(|e| e.workflow_id == workflow_id && e.supplier_id == supplier_id && e.reason.starts_with(prefix))
+                .peekable();
+            if existing.peek().is_some() {
+                for escalation in existing.filter(|e| e.is_open()) {
+                    if DeadlineRisk::from_severity(&escalation.severity).is_some_and(|current| current < severity) {
+                        escalation.severity = severity.to_string();
+                        escalation.reason = reason.clone();
+                        sweep.escalations_raised += 1;
+                    }
+                }
+                return Ok(());
+            }
+        }
+        
+        self.create_escalation(workflow_id, supplier_id, reason, severity.to_string()).await?;
+        sweep.escalations_created += 1;
+        
+        let mut workflows = self.workflows.write().await;
+        if let Some(workflow) = workflows.get_mut(&workflow_id) {
+            workflow.progress.escalated += 1;
+        }
+        
+        Ok(())
+    }
+    
+    /// Active workflows unchanged for `idle_days` that cannot move on their
+    /// own: due tasks nobody picked up, tasks stuck running or blocked for
+    /// good, overdue manual tasks, or nothing left scheduled for suppliers
+    /// whose data is not in. Campaigns merely waiting for a later task are
+    /// not stalled. Closest deadline first.
+    pub async fn stalled_workflows(&self, now: DateTime<Utc>, idle_days: i64) -> Vec<StalledWorkflowResponse> {
+        let workflows: Vec<StoredWorkflow> = self.workflows.read().await.values()
+            .filter(|w| w.state == WorkflowState::Active)
+            .cloned()
+            .collect();
+        let tasks = self.tasks.read().await;
+        let missed_before = now - Duration::hours(MISSED_TASK_GRACE_HOURS);
+        let idle_since = now - Duration::days(idle_days);
+        
+        let mut stalled = Vec::new();
+        for workflow in workflows {
+            let workflow_tasks: Vec<&StoredTask> = tasks.values().filter(|t| t.workflow_id == workflow.id).collect();
+            let last_change_at = workflow_tasks.iter()
+                .flat_map(|t| [t.started_at, t.completed_at])
+                .chain(workflow.activity.values().flat_map(|a| [a.last_sent_at, a.last_reply_at]))
+                .chain(workflow.config_versions.last().map(|v| Some(v.changed_at)))
+                .flatten()
+                .fold(workflow.start_date, DateTime::max);
+            if last_change_at > idle_since {
+                continue;
+            }
+            
+            let open: Vec<&StoredTask> = workflow_tasks.iter().copied().filter(|t| !t.state.is_terminal()).collect();
+            let dependencies = |t: &StoredTask| dependency_status(t.depends_on.iter().map(|id| tasks.get(id).map(|d| d.state)));
+            let overdue: Vec<&StoredTask> = open.iter().copied().filter(|t| {
+                t.manual.is_none()
+                    && matches!(t.state, TaskState::Scheduled | TaskState::Failed)
+                    && t.scheduled_at.is_some_and(|at| at <= missed_before)
+                    && dependencies(t) == DependencyStatus::Ready
+            }).collect();
+            let running = open.iter().copied().filter(|t| t.state == TaskState::Running).collect();
+            let blocked = open.iter().copied().filter(|t| dependencies(t) == DependencyStatus::Broken).collect();
+            let manual_overdue = open.iter().copied()
+                .filter(|t| t.manual.as_ref().and_then(|m| m.due_at).is_some_and(|due| due <= now))
+                .collect();
+            
+            let mut stuck: Vec<Uuid> = Vec::new();
+            let mut causes = Vec::new();
+            for (group, description) in [
+                (overdue, "tasks are overdue; the executor may not be running"),
+                (running, "tasks are stuck running"),
+                (blocked, "tasks can never run because a task they depend on failed"),
+                (manual_overdue, "manual tasks are past their due date"),
+            ] {
+                if !group.is_empty() {
+                    causes.push(format!("{} {}", group.len(), description));
+                    stuck.extend(group.iter().map(|t| t.supplier_id));
+                }
+            }
+            
+            let has_open: HashSet<Uuid> = open.iter().map(|t| t.supplier_id).collect();
+            let unattended: Vec<Uuid> = workflow.suppliers.iter().copied()
+                .filter(|s| !has_open.contains(s) && !workflow.manual.contains(s))
+                .filter(|s| !workflow.activity.get(s).is_some_and(|a| a.data_complete))
+                .collect();
+            // Suppliers handled by hand sit outside the task plan, and everything else finished
+            if !unattended.is_empty() && open.is_empty() {
+                causes.push(format!("Nothing is scheduled for {} suppliers whose data is not in", unattended.len()));
+                stuck.extend(unattended);
+            }
+            if causes.is_empty() {
+                continue;
+            }
+            
+            let mut supplier_ids = Vec::new();
+            for supplier_id in stuck {
+                if !supplier_ids.contains(&supplier_id) {
+                    supplier_ids.push(supplier_id);
+                }
+            }
+            stalled.push((workflow.deadline, StalledWorkflowResponse {
+                workflow_id: workflow.id,
+                client_id: workflow.client_id,
+                campaign_name: workflow.campaign_name.clone(),
+                deadline: workflow.deadline.to_rfc3339(),
+                last_change_at: last_change_at.to_rfc3339(),
+                idle_days: (now - last_change_at).num_days(),
+                causes,
+                supplier_ids,
+            }));
+        }
+        
+        stalled.sort_by_key(|(deadline, _)| *deadline);
+        stalled.into_iter().map(|(_, response)| response).collect()
+    }
+    
+    /// Escalate stalled workflows, once while the escalation is open; returns how many were escalated
+    pub async fn run_stall_checks(&self, now: DateTime<Utc>, idle_days: i64) -> Result<usize> {
+        let mut escalated = 0;
+        for stall in self.stalled_workflows(now, idle_days).await {
+            let already_escalated = self.escalations.read().await.values()
+                .any(|e| e.workflow_id == stall.workflow_id && e.is_open() && e.reason.starts_with(STALLED_REASON));
+            // Escalations belong to a supplier; the first one held up stands for the campaign
+            let Some(supplier_id) = stall.supplier_ids.first().copied().filter(|_| !already_escalated) else {
+                continue;
+            };
+            
+            warn!("Workflow {} ({}) has not moved for {} days: {}", stall.workflow_id, stall.campaign_name, stall.idle_days, stall.causes.join("; "));
+            self.create_escalation(
+                stall.workflow_id,
+                supplier_id,
+                format!("{} for {} days: {}", STALLED_REASON, stall.idle_days, stall.causes.join("; ")),
+                "high".to_string(),
+            ).await?;
+            escalated += 1;
+        }
+        
+        Ok(escalated)
+    }
+    
+    /// Escalate suppliers that have ignored the email service's follow-ups.
+    /// Workflows with `auto_escalate` off, suppliers that have responded or
+    /// are handled manually, and suppliers already escalated for silence
+    /// are left alone.
+    pub async fn handle_unresponsive(&self, event: SupplierUnresponsiveEvent) -> Result<UnresponsiveHandledResponse> {
+        let mut response = UnresponsiveHandledResponse {
+            workflows_updated: 0,
+            escalations_created: 0,
+        };
+        
+        let candidates: Vec<Uuid> = {
+            let workflows = self.workflows.read().await;
+            workflows.values()
+                .filter(|w| w.state == WorkflowState::Active && w.suppliers.contains(&event.supplier_id))
+                .filter(|w| w.config.auto_escalate)
+                .filter(|w| !w.responded.contains(&event.supplier_id) && !w.manual.contains(&event.supplier_id))
+                .map(|w| w.id)
+                .collect()
+        };
+        
+        for workflow_id in candidates {
+            let already_escalated = self.escalations.read().await.values().any(|e| {
+                e.workflow_id == workflow_id
+                    && e.supplier_id == event.supplier_id
+                    && e.is_open()
+                    && e.reason.starts_with(NO_RESPONSE_REASON)
+            });
+            if already_escalated {
+                continue;
+            }
+            
+            self.create_escalation(
+                workflow_id,
+                event.supplier_id,
+                format!("{} after {} follow-ups", NO_RESPONSE_REASON, event.unanswered_follow_ups),
+                "medium".to_string(),
+            ).await?;
+            response.escalations_created += 1;
+            
+            let mut workflows = self.workflows.write().await;
+            if let Some(workflow) = workflows.get_mut(&workflow_id) {
+                workflow.progress.escalated += 1;
+            }
+            drop(workflows);
+            
+            response.workflows_updated += 1;
+        }
+        
+        Ok(response)
+    }
+    
+    /// Launch renewal outreach for a lapsing certification, once while an
+    /// earlier renewal campaign for the same certificate is still open. The
+    /// campaign runs for the event's client, or else for the client of the
+    /// supplier's latest campaign, and is due when the certificate expires.
+    pub async fn handle_certification_expiring(&self, event: CertificationExpiringEvent) -> Result<RenewalOutreachResponse> {
+        let campaign_name = format!(
+            "{} {:?} {}",
+            RENEWAL_CAMPAIGN_PREFIX, event.certification_type, event.certificate_number,
+        );
+        let (open_renewal, latest_client) = {
+            let workflows = self.workflows.read().await;
+            let supplier_workflows = || workflows.values().filter(|w| w.suppliers.contains(&event.supplier_id));
+            (
+                supplier_workflows()
+                    .find(|w| !w.state.is_terminal() && w.campaign_name == campaign_name)
+                    .map(|w| w.id),
+                supplier_workflows().max_by_key(|w| w.start_date).map(|w| w.client_id),
+            )
+        };
+        if let Some(workflow_id) = open_renewal {
+            return Ok(RenewalOutreachResponse { workflow_id, launched: false });
+        }
+        
+        let client_id = event.client_id.or(latest_client)
+            .with_context(|| format!("No client known for supplier {} to renew certificate {}", event.supplier_id, event.certificate_number))?;
+        // A lapsed certificate is asked for within the template's usual response time
+        let deadline = Some(event.expiry_date)
+            .filter(|expiry| *expiry > Utc::now())
+            .map(|expiry| expiry.to_rfc3339());
+        let workflow = self.launch_campaign(CERTIFICATION_RENEWAL, LaunchCampaignRequest {
+            client_id,
+            supplier_ids: vec![event.supplier_id],
+            campaign_name: Some(campaign_name),
+            deadline,
+        }).await?.context("Certification renewal template is missing")?;
+        
+        Ok(RenewalOutreachResponse { workflow_id: workflow.id, launched: true })
+    }
+    
+    /// Launch renewal outreach for every certification expired or expiring
+    /// within `warning_days`; returns how many campaigns were launched
+    pub async fn run_certification_checks(&self, now: DateTime<Utc>, warning_days: i64) -> Result<usize> {
+        let Some(compliance) = &self.compliance else {
+            return Ok(0);
+        };
+        
+        let mut launched = 0;
+        for expiring in compliance.find_expiring_certifications(now, Duration::days(warning_days)).await? {
+            let Some(expiry_date) = expiring.certification.expiry_date else {
+                continue;
+            };
+            let event = CertificationExpiringEvent {
+                supplier_id: expiring.supplier_id.into(),
+                component_id: expiring.component_id.into(),
+                compliance_record_id: expiring.compliance_record_id.into(),
+                client_id: None,
+                certification_type: expiring.certification.certification_type,
+                certificate_number: expiring.certification.certificate_number,
+                expiry_date,
+                status: expiring.status,
+            };
+            match self.handle_certification_expiring(event).await {
+                Ok(outreach) if outreach.launched => launched += 1,
+                Ok(_) => {}
+                Err(e) => warn!("Skipped renewal outreach for supplier {}: {:#}", expiring.supplier_id, e),
+            }
+        }
+        
+        Ok(launched)
+    }
+    
+    /// List escalations, optionally only those of one assignee or in one state
+    pub async fn list_escalations(&self, query: &EscalationQuery) -> Result<Vec<EscalationResponse>> {
+        let state = query.state.as_deref()
+            .map(|state| EscalationState::from_str(state).with_context(|| format!("Unknown escalation state {}", state)))
+            .transpose()?;
+        
+        let escalations = self.escalations.read().await;
+        let mut matching: Vec<&StoredEscalation> = escalations.values()
+            .filter(|e| query.assignee.is_none() || e.assignee == query.assignee)
+            .filter(|e| state.is_none_or(|state| e.state == state))
+            .collect();
+        matching.sort_by_key(|e| e.due_at);
+        Ok(matching.into_iter().map(|e| self.to_escalation_response(e)).collect())
+    }
+    
+    /// Resolve escalation
+    pub async fn resolve_escalation(&self, id: Uuid, resolution: &str) -> Result<EscalationResponse> {
+        let mut escalations = self.escalations.write().await;
+        let escalation = escalations.get_mut(&id)
+            .context("Escalation not found")?;
+        if !escalation.state.can_transition_to(EscalationState::Resolved) {
+            bail!("Escalation {} is already resolved", id);
+        }
+        
+        let previous = escalation.state;
+        escalation.state = EscalationState::Resolved;
+        escalation.resolved_at = Some(Utc::now());
+        escalation.resolution = Some(resolution.to_string());
+        self.audit_escalation(escalation, Some(previous));
+        
+        Ok(self.to_escalation_response(escalation))
+    }
+    
+    /// Mark an open escalation as being worked on; it is assigned to `user` if it has no owner yet
+    pub async fn acknowledge_escalation(&self, id: Uuid, user: &str) -> Result<Option<EscalationResponse>> {
+        let mut escalations = self.escalations.write().await;
+        let Some(escalation) = escalations.get_mut(&id) else {
+            return Ok(None);
+        };
+        if !escalation.state.can_transition_to(EscalationState::Acknowledged) {
+            bail!("Escalation {} is {} and cannot be acknowledged", id, escalation.state);
+        }
+        
+        escalation.state = EscalationState::Acknowledged;
+        escalation.acknowledged_at = Some(Utc::now());
+        escalation.assignee.get_or_insert_with(|| user.to_string());
+        self.audit_escalation(escalation, Some(EscalationState::Open));
+        Ok(Some(self.to_escalation_response(escalation)))
+    }
+    
+    /// Hand an unresolved escalation to someone else
+    pub async fn assign_escalation(&self, id: Uuid, assignee: &str) -> Result<Option<EscalationResponse>> {
+        if assignee.trim().is_empty() {
+            bail!("Assignee is required");
+        }
+        
+        let mut escalations = self.escalations.write().await;
+        let Some(escalation) = escalations.get_mut(&id) else {
+            return Ok(None);
+        };
+        if !escalation.is_open() {
+            bail!("Escalation {} is already resolved", id);
+        }
+        
+        escalation.assignee = Some(assignee.to_string());
+        Ok(Some(self.to_escalation_response(escalation)))
+    }
+    
+    pub async fn comment_on_escalation(&self, id: Uuid, author: &str, body: &str) -> Result<Option<EscalationResponse>> {
+        if body.trim().is_empty() {
+            bail!("Comment is empty");
+        }
+        
+        let mut escalations = self.escalations.write().await;
+        let Some(escalation) = escalations.get_mut(&id) else {
+            return Ok(None);
+        };
+        escalation.comments.push(EscalationComment {
+            author: author.to_string(),
+            body: body.to_string(),
+            created_at: Utc::now(),
+        });
+        Ok(Some(self.to_escalation_response(escalation)))
+    }
+    
+    pub async fn add_routing_rule(&self, request: RoutingRuleRequest) -> Result<RoutingRuleResponse> {
+        let rule = self.escalation_router.add(RoutingRule {
+            id: Uuid::new_v4(),
+            severity: request.severity,
+            client_id: request.client_id,
+            relationship: request.relationship,
+            assignee: request.assignee,
+            due_hours: request.due_hours,
+        }).await?;
+        
+        Ok(routing_rule_response(rule))
+    }
+    
+    pub async fn list_routing_rules(&self) -> Vec<RoutingRuleResponse> {
+        self.escalation_router.list().await.into_iter().map(routing_rule_response).collect()
+    }
+    
+    pub async fn delete_routing_rule(&self, id: Uuid) -> bool {
+        self.escalation_router.delete(id).await
+    }
+    
+    /// Create escalation (internal), routed to an owner with a due date
+    async fn create_escalation(&self, workflow_id: Uuid, supplier_id: Uuid, reason: String, severity: String) -> Result<()> {
+        let client_id = self.workflows.read().await.get(&workflow_id).map(|w| w.client_id);
+        // An unreachable supplier database should not stop the escalation from being raised
+        let relationship = self.supplier_relationship(supplier_id).await.unwrap_or_else(|e| {
+            warn!("Failed to look up supplier {} for escalation routing: {:#}", supplier_id, e);
+            None
+        });
+        let now = Utc::now();
+        let route = self.escalation_router.route(&RoutingContext {
+            severity: &severity,
+            client_id,
+            relationship: relationship.as_ref(),
+        }, now).await;
+        
+        let escalation = StoredEscalation {
+            id: Uuid::new_v4(),
+            workflow_id,
+            supplier_id,
+            reason,
+            severity,
+            created_at: now,
+            state: EscalationState::Open,
+            assignee: route.assignee,
+            due_at: route.due_at,
+            acknowledged_at: None,
+            resolved_at: None,
+            resolution: None,
+            comments: Vec::new(),
+        };
+        
+        self.audit_escalation(&escalation, None);
+        let mut escalations = self.escalations.write().await;
+        escalations.insert(escalation.id, escalation);
+        
+        Ok(())
+    }
+    
+    /// Update workflow progress (internal)
+    async fn update_workflow_progress(&self, workflow_id: Uuid) {
+        let tasks = self.tasks.read().await;
+        let workflow_tasks: Vec<_> = tasks.values()
+            .filter(|t| t.workflow_id == workflow_id)
+            .collect();
+        
+        let total = workflow_tasks.len();
+        let completed = workflow_tasks.iter().filter(|t| t.state == TaskState::Completed).count();
+        let skipped = workflow_tasks.iter().filter(|t| t.state == TaskState::Skipped).count();
+        
+        drop(tasks);
+        let reached = self.reached_by_outreach(workflow_id).await;
         
         let mut workflows = self.workflows.write().await;
         if let Some(workflow) = workflows.get_mut(&workflow_id) {
             workflow.progress.complete = completed;
+            workflow.progress.contacted = contacted_suppliers(workflow, reached).len();
             workflow.progress.percent_complete = if total > 0 {
                 (completed as f64 / total as f64) * 100.0
             } else {
                 0.0
             };
             
-            // Check if workflow is complete
-            if completed == total && total > 0 {
+            // Check if workflow is complete; follow-ups skipped because the supplier answered do not hold it open
+            if completed + skipped == total && completed > 0 && !workflow.state.is_terminal() {
+                let previous = workflow.state;
                 workflow.state = WorkflowState::Completed;
+                self.audit_workflow(workflow, Some(previous));
             }
         }
     }
@@ -326,6 +2908,8 @@ impl WorkflowService {
             progress: w.progress.clone(),
             supplier_count: w.suppliers.len(),
             task_count,
+            cloned_from: w.cloned_from,
+            config_version: w.config_versions.len(),
         }
     }
     
@@ -342,6 +2926,16 @@ impl WorkflowService {
             started_at: t.started_at.map(|d| d.to_rfc3339()),
             completed_at: t.completed_at.map(|d| d.to_rfc3339()),
             error: t.error.clone(),
+            depends_on: t.depends_on.clone(),
+            priority: t.priority,
+            manual: t.manual.as_ref().map(|m| ManualTaskResponse {
+                assignee: m.assignee.clone(),
+                instructions: m.instructions.clone(),
+                due_at: m.due_at.map(|d| d.to_rfc3339()),
+                overdue: !t.state.is_terminal() && m.due_at.is_some_and(|due| due < Utc::now()),
+                notes: m.notes.clone(),
+                completed_by: m.completed_by.clone(),
+            }),
         }
     }
     
@@ -353,9 +2947,19 @@ impl WorkflowService {
             reason: e.reason.clone(),
             severity: e.severity.clone(),
             created_at: e.created_at.to_rfc3339(),
-            resolved: e.resolved,
+            state: e.state.to_string(),
+            resolved: e.state == EscalationState::Resolved,
+            assignee: e.assignee.clone(),
+            due_at: e.due_at.to_rfc3339(),
+            overdue: e.is_open() && e.due_at < Utc::now(),
+            acknowledged_at: e.acknowledged_at.map(|d| d.to_rfc3339()),
             resolved_at: e.resolved_at.map(|d| d.to_rfc3339()),
             resolution: e.resolution.clone(),
+            comments: e.comments.iter().map(|c| EscalationCommentResponse {
+                author: c.author.clone(),
+                body: c.body.clone(),
+                created_at: c.created_at.to_rfc3339(),
+            }).collect(),
         }
     }
 }
@@ -365,3 +2969,1326 @@ impl Default for WorkflowService {
         Self::new()
     }
 }
+
+fn campaign_template_response(template: CampaignTemplate) -> CampaignTemplateResponse {
+    CampaignTemplateResponse {
+        id: template.id,
+        name: template.name,
+        description: template.description,
+        config: template.config,
+        response_days: template.response_days,
+        built_in: template.built_in,
+        updated_at: template.updated_at.to_rfc3339(),
+        recurrence: template.recurrence.map(|r| RecurrenceResponse {
+            cron: r.schedule.expression().to_string(),
+            client_id: r.client_id,
+            supplier_ids: r.supplier_ids,
+            require_confirmation: r.require_confirmation,
+            next_run_at: r.next_run_at.to_rfc3339(),
+            last_workflow_id: r.last_workflow_id,
+        }),
+    }
+}
+
+fn config_version_response(version: usize, config: ConfigVersion, active: bool) -> ConfigVersionResponse {
+    ConfigVersionResponse {
+        version,
+        config: config.config,
+        changed_by: config.changed_by,
+        changed_at: config.changed_at.to_rfc3339(),
+        reason: config.reason,
+        changes: config.changes,
+        active,
+    }
+}
+
+fn routing_rule_response(rule: RoutingRule) -> RoutingRuleResponse {
+    RoutingRuleResponse {
+        id: rule.id,
+        severity: rule.severity,
+        client_id: rule.client_id,
+        relationship: rule.relationship,
+        assignee: rule.assignee,
+        due_hours: rule.due_hours,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign_templates::TSCA_PFAS_QUARTERLY;
+    use crate::sla::SlaKind;
+    use crate::{DocumentExtractedEvent, EmailSentEvent, ReplyClassificationPayload};
+    use elementa_database::{InMemoryComplianceStore, InMemorySupplierStore};
+    use elementa_models::SuppressionSource;
+    
+    fn reply(supplier_id: Uuid, category: ReplyClassification, document_count: usize) -> ReplyClassifiedEvent {
+        ReplyClassifiedEvent {
+            email_id: Uuid::new_v4(),
+            supplier_id,
+            thread_id: "thread_1".to_string(),
+            classification: ReplyClassificationPayload { category, confidence: 0.9, method: "rules".to_string() },
+            document_count,
+            received_at: Utc::now().to_rfc3339(),
+            return_date: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_reply_advances_supplier_tasks() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        let follow_up = StoredTask::scheduled(
+            WorkflowScheduler::default().schedule_follow_up(workflow.id, supplier_id, 0).unwrap(),
+        );
+        let follow_up_id = follow_up.id;
+        let original = follow_up.scheduled_at.unwrap();
+        service.tasks.write().await.insert(follow_up_id, follow_up);
+        
+        let handled = service.handle_reply(reply(supplier_id, ReplyClassification::OutOfOffice, 0)).await.unwrap();
+        assert_eq!(handled.tasks_rescheduled, 1);
+        assert!(service.tasks.read().await[&follow_up_id].scheduled_at.unwrap() > original);
+        
+        // Held until the day after the contact is back
+        let back = (Utc::now() + Duration::days(20)).date_naive();
+        let event = ReplyClassifiedEvent { return_date: Some(back), ..reply(supplier_id, ReplyClassification::OutOfOffice, 0) };
+        assert_eq!(service.handle_reply(event).await.unwrap().tasks_rescheduled, 1);
+        assert_eq!(service.tasks.read().await[&follow_up_id].scheduled_at.unwrap().date_naive(), back + Duration::days(1));
+        
+        let handled = service.handle_reply(reply(supplier_id, ReplyClassification::CompleteResponse, 2)).await.unwrap();
+        assert_eq!(handled.tasks_skipped, 1);
+        assert_eq!(handled.tasks_scheduled, 2);
+        assert_eq!(service.tasks.read().await[&follow_up_id].state, TaskState::Skipped);
+        
+        let workflow = service.get_workflow(workflow.id).await.unwrap().unwrap();
+        assert_eq!(workflow.progress.responded, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_only_confident_questions_are_escalated() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        // The rules' fallback for a reply with no recognizable intent
+        let mut guess = reply(supplier_id, ReplyClassification::Question, 0);
+        guess.classification.confidence = 0.3;
+        assert_eq!(service.handle_reply(guess).await.unwrap().escalations_created, 0);
+        
+        let question = reply(supplier_id, ReplyClassification::Question, 0);
+        assert_eq!(service.handle_reply(question).await.unwrap().escalations_created, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_do_not_contact_supplier_routes_to_manual_handling() {
+        let mut opted_out = SupplierRecord::new("Acme".to_string(), "qa@acme.example".to_string(), "QA".to_string());
+        opted_out.communication_preferences.do_not_contact = true;
+        let opted_out_id: Uuid = opted_out.id.into();
+        let other = Uuid::new_v4();
+        let service = WorkflowService::new().with_stores(
+            Arc::new(InMemorySupplierStore::with_suppliers([opted_out])),
+            Arc::new(InMemoryComplianceStore::new()),
+        );
+        
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![opted_out_id, other],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        assert_eq!(workflow.progress.escalated, 1);
+        
+        let tasks = service.get_workflow_tasks(workflow.id).await.unwrap();
+        assert_eq!(tasks.iter().map(|t| t.supplier_id).collect::<Vec<_>>(), vec![other]);
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert_eq!(escalations[0].reason, "Supplier asked not to be contacted by email; handle manually");
+    }
+    
+    #[tokio::test]
+    async fn test_suppression_routes_supplier_to_manual_handling() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "REACH Q3".to_string(),
+            supplier_ids: vec![supplier_id, Uuid::new_v4()],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        let event = || SupplierSuppressedEvent {
+            supplier_id,
+            email_address: Some("qa@supplier.example".to_string()),
+            reason: "Recipient unsubscribed".to_string(),
+            source: SuppressionSource::UnsubscribeLink,
+            suppressed_at: Utc::now().to_rfc3339(),
+        };
+        
+        let handled = service.handle_suppression(event()).await.unwrap();
+        assert_eq!(handled.tasks_skipped, 1);
+        assert_eq!(handled.escalations_created, 1);
+        
+        let handled = service.handle_suppression(event()).await.unwrap();
+        assert_eq!(handled.workflows_updated, 0);
+        
+        let tasks = service.get_workflow_tasks(workflow.id).await.unwrap();
+        assert_eq!(tasks.iter().filter(|t| t.status == TaskState::Skipped.to_string()).count(), 1);
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().progress.escalated, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_unresponsive_supplier_is_escalated_once() {
+        let service = WorkflowService::new();
+        let (silent, replied) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "RoHS 2026".to_string(),
+            supplier_ids: vec![silent, replied],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        service.handle_reply(reply(replied, ReplyClassification::Question, 0)).await.unwrap();
+        
+        let event = |supplier_id| SupplierUnresponsiveEvent {
+            supplier_id,
+            thread_id: "thread_1".to_string(),
+            campaign_id: None,
+            unanswered_follow_ups: 3,
+            last_sent_at: Utc::now().to_rfc3339(),
+        };
+        
+        let handled = service.handle_unresponsive(event(silent)).await.unwrap();
+        assert_eq!(handled.escalations_created, 1);
+        assert_eq!(service.handle_unresponsive(event(silent)).await.unwrap().escalations_created, 0);
+        assert_eq!(service.handle_unresponsive(event(replied)).await.unwrap().escalations_created, 0);
+        
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        let silence: Vec<_> = escalations.iter().filter(|e| e.supplier_id == silent).collect();
+        assert_eq!(silence.len(), 1);
+        assert_eq!(silence[0].reason, "No response after 3 follow-ups");
+        assert_eq!(silence[0].workflow_id, workflow.id);
+    }
+    
+    #[tokio::test]
+    async fn test_cancellation_cleans_up_tasks_and_escalations() {
+        let service = WorkflowService::new();
+        let (contacted, pending) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![contacted, pending],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        let outreach = service.claim_due_tasks(Utc::now() + Duration::seconds(30), 10).await;
+        service.complete_task(outreach[0].id, Some(serde_json::json!({ "thread_id": "thread_1" }))).await.unwrap();
+        service.handle_unresponsive(SupplierUnresponsiveEvent {
+            supplier_id: contacted,
+            thread_id: "thread_1".to_string(),
+            campaign_id: Some(workflow.id),
+            unanswered_follow_ups: 3,
+            last_sent_at: Utc::now().to_rfc3339(),
+        }).await.unwrap();
+        
+        // Only the contacted supplier is notified; without an email service that fails
+        let cancelled = service.cancel_workflow(workflow.id, CancelWorkflowRequest {
+            reason: Some("Client withdrew the request".to_string()),
+            notify_suppliers: true,
+        }).await.unwrap();
+        assert_eq!(cancelled.workflow.status, "cancelled");
+        assert_eq!((cancelled.escalations_closed, cancelled.withdrawals_sent, cancelled.withdrawals_failed), (1, 0, 1));
+        
+        let tasks = service.get_workflow_tasks(workflow.id).await.unwrap();
+        assert_eq!(cancelled.tasks_cancelled, tasks.iter().filter(|t| t.status == TaskState::Cancelled.to_string()).count());
+        assert!(tasks.iter().any(|t| t.supplier_id == pending && t.status == TaskState::Cancelled.to_string()));
+        assert!(tasks.iter().all(|t| t.status != TaskState::Scheduled.to_string()));
+        
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert_eq!(escalations[0].resolution.as_deref(), Some("Campaign cancelled: Client withdrew the request"));
+        assert!(service.claim_due_tasks(Utc::now() + Duration::days(60), 10).await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_bulk_task_updates_are_applied_once() {
+        let service = WorkflowService::new();
+        service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q1".to_string(),
+            supplier_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 0, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        let due = service.claim_due_tasks(Utc::now() + Duration::days(1), 10).await;
+        let update = |key: &str, task_id: Uuid, status: TaskUpdateStatus| TaskStatusUpdate {
+            idempotency_key: key.to_string(),
+            task_id,
+            status,
+            result: None,
+            error: Some("SMTP timeout".to_string()),
+            retry_at: Some((Utc::now() + Duration::hours(1)).to_rfc3339()),
+        };
+        
+        let handled = service.apply_task_updates(vec![
+            update("email-1", due[0].id, TaskUpdateStatus::Completed),
+            update("email-2", due[1].id, TaskUpdateStatus::Failed),
+            update("email-1", due[0].id, TaskUpdateStatus::Completed),
+            update("email-1", due[1].id, TaskUpdateStatus::Completed),
+            update("email-3", Uuid::new_v4(), TaskUpdateStatus::Completed),
+        ]).await.unwrap();
+        assert_eq!((handled.applied, handled.duplicates, handled.rejected), (2, 1, 2));
+        let outcomes: Vec<&str> = handled.results.iter().map(|r| r.outcome.as_str()).collect();
+        assert_eq!(outcomes, vec!["applied", "applied", "duplicate", "rejected", "rejected"]);
+        assert_eq!(handled.results[1].task.as_ref().unwrap().status, TaskState::Failed.to_string());
+        
+        // A rejected key is free again, and a redelivered batch changes nothing
+        let handled = service.apply_task_updates(vec![
+            update("email-3", due[1].id, TaskUpdateStatus::Completed),
+            update("email-2", due[1].id, TaskUpdateStatus::Failed),
+        ]).await.unwrap();
+        assert_eq!((handled.applied, handled.duplicates), (1, 1));
+        let task = service.get_task(due[1].id).await.unwrap().unwrap();
+        assert_eq!((task.status, task.retry_count), (TaskState::Completed.to_string(), 1));
+        
+        let late = service.apply_task_updates(vec![update("email-4", due[1].id, TaskUpdateStatus::Failed)]).await.unwrap();
+        assert_eq!(late.results[0].error.as_deref(), Some("Task is already completed"));
+    }
+    
+    #[tokio::test]
+    async fn test_stalled_campaigns_need_attention_and_are_escalated_once() {
+        let service = WorkflowService::new();
+        let request = |name: &str| CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: name.to_string(),
+            supplier_ids: vec![Uuid::new_v4()],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        };
+        // Waiting for its first follow-up, which is not due yet
+        let waiting = service.create_workflow(request("Resins")).await.unwrap();
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        // Outreach never picked up
+        let stuck = service.create_workflow(request("Coatings")).await.unwrap();
+        
+        let later = Utc::now() + Duration::days(4);
+        assert!(service.stalled_workflows(later, 5).await.is_empty());
+        let stalled = service.stalled_workflows(later, 3).await;
+        assert_eq!(stalled.len(), 1);
+        assert_eq!((stalled[0].workflow_id, stalled[0].idle_days), (stuck.id, 4));
+        assert_eq!(stalled[0].causes, vec!["1 tasks are overdue; the executor may not be running"]);
+        assert!(service.stalled_workflows(later, 3).await.iter().all(|s| s.workflow_id != waiting.id));
+        
+        assert_eq!(service.run_stall_checks(later, 3).await.unwrap(), 1);
+        assert_eq!(service.run_stall_checks(later, 3).await.unwrap(), 0);
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert!(escalations[0].reason.starts_with("Campaign stalled for 4 days"));
+        let metrics = service.metrics(stuck.id, later).await.unwrap();
+        assert_eq!(metrics.funnel.escalated, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_executor_claims_due_tasks_once_and_escalates_exhausted() {
+        let service = WorkflowService::new();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let request = |name: &str, supplier_ids: Vec<Uuid>| CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: name.to_string(),
+            supplier_ids,
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        };
+        let workflow = service.create_workflow(request("PFAS 2026", vec![first, second])).await.unwrap();
+        let paused = service.create_workflow(request("REACH Q3", vec![Uuid::new_v4()])).await.unwrap();
+        service.update_status(paused.id, "paused").await.unwrap();
+        
+        // Outreach is staggered, so only the first supplier's task is due yet
+        let now = Utc::now() + Duration::seconds(30);
+        let claimed = service.claim_due_tasks(now, 10).await;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!((claimed[0].supplier_id, claimed[0].task_type, claimed[0].attempt), (first, TaskType::InitialOutreach, 1));
+        assert!(service.claim_due_tasks(now, 10).await.is_empty());
+        
+        // Retried when due, then exhausted and escalated
+        let task_id = claimed[0].id;
+        service.fail_task(task_id, "connection refused".to_string(), Some(now + Duration::minutes(1))).await.unwrap();
+        assert!(service.claim_due_tasks(now, 10).await.is_empty());
+        let retry = service.claim_due_tasks(now + Duration::minutes(1), 1).await;
+        assert_eq!((retry[0].id, retry[0].attempt), (task_id, 2));
+        let task = service.fail_task(task_id, "Recipient suppressed".to_string(), None).await.unwrap();
+        assert_eq!(task.status, TaskState::Exhausted.to_string());
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert_eq!(escalations[0].reason, "Task initial_outreach failed: Recipient suppressed");
+        
+        // A completed outreach counts as contact and gives follow-ups their thread
+        let outreach = service.claim_due_tasks(now + Duration::minutes(3), 10).await;
+        assert_eq!(outreach[0].supplier_id, second);
+        service.complete_task(outreach[0].id, Some(serde_json::json!({ "thread_id": "thread_1" }))).await.unwrap();
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().progress.contacted, 1);
+        
+        let follow_up = StoredTask::scheduled(WorkflowScheduler::default().schedule_follow_up(workflow.id, second, 0).unwrap());
+        let due_at = follow_up.scheduled_at.unwrap();
+        service.tasks.write().await.insert(follow_up.id, follow_up);
+        assert_eq!(service.claim_due_tasks(due_at, 10).await[0].thread_id.as_deref(), Some("thread_1"));
+    }
+    
+    #[tokio::test]
+    async fn test_exhausted_tasks_are_dead_lettered_and_requeued() {
+        let service = WorkflowService::new();
+        service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![Uuid::new_v4()],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        let now = Utc::now() + Duration::seconds(30);
+        let task_id = service.claim_due_tasks(now, 10).await[0].id;
+        assert!(service.requeue_task(task_id, None, None).await.is_err());
+        service.fail_task(task_id, "Template missing".to_string(), None).await.unwrap();
+        
+        let dead = service.dead_letters().await;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].task.error.as_deref(), Some("Template missing"));
+        assert_eq!((dead[0].campaign_name.as_deref(), dead[0].attempts), (Some("PFAS 2026"), 1));
+        
+        // Requeued with a different template, the task runs again from its first attempt
+        let payload = serde_json::json!({ "template_id": "pfas_request_v2" });
+        assert!(service.requeue_task(task_id, Some(serde_json::json!("v2")), None).await.is_err());
+        let task = service.requeue_task(task_id, Some(payload.clone()), Some("ops@example.com".to_string())).await.unwrap().unwrap();
+        assert_eq!((task.status.as_str(), task.retry_count, task.error), ("scheduled", 0, None));
+        assert!(service.dead_letters().await.is_empty());
+        
+        let retry = service.claim_due_tasks(Utc::now() + Duration::seconds(1), 10).await;
+        assert_eq!((retry[0].id, retry[0].attempt), (task_id, 1));
+        assert_eq!(retry[0].payload, Some(payload));
+        assert!(service.requeue_task(Uuid::new_v4(), None, None).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_suppliers_that_must_not_be_emailed_are_handled_manually() {
+        use axum::{routing::get, Json, Router};
+        use chrono::Timelike;
+        
+        // Stand-in email service with one supplier on its suppression list
+        let (opted_out, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let app = Router::new().route("/api/v1/suppressions", get(move || async move {
+            Json(serde_json::json!([{ "supplier_id": opted_out, "email_address": null, "reason": "Unsubscribed" }]))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        
+        let service = WorkflowService::new().with_email(EmailClient::new(url));
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![opted_out, other],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        assert_eq!(workflow.progress.escalated, 1);
+        
+        let tasks = service.get_workflow_tasks(workflow.id).await.unwrap();
+        assert_eq!(tasks.iter().map(|t| t.supplier_id).collect::<Vec<_>>(), vec![other]);
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert_eq!((escalations.len(), escalations[0].supplier_id), (1, opted_out));
+        assert_eq!(escalations[0].reason, "Supplier is on the suppression list (Unsubscribed); handle manually");
+        let suppliers = service.supplier_progress(workflow.id).await.unwrap().unwrap();
+        assert!(suppliers.iter().find(|s| s.supplier_id == opted_out).unwrap().manual);
+        
+        // Outside its contact window the other supplier's outreach waits for it to open
+        let now = Utc::now();
+        let window = ContactWindow { start_hour: (now.hour() + 2) % 24, end_hour: (now.hour() + 3) % 24, utc_offset_minutes: 0, weekdays_only: false };
+        let opening = next_opening(&window, now);
+        service.workflows.write().await.get_mut(&workflow.id).unwrap().contact_windows.insert(other, window);
+        assert!(service.claim_due_tasks(now + Duration::seconds(30), 10).await.is_empty());
+        let deferred = service.get_task(tasks[0].id).await.unwrap().unwrap();
+        assert_eq!(deferred.scheduled_at, Some(opening.to_rfc3339()));
+        assert_eq!(service.claim_due_tasks(opening, 10).await.len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_state_transitions_are_audited() {
+        use axum::{extract::State, routing::post, Json, Router};
+        type Received = Arc<RwLock<Vec<serde_json::Value>>>;
+        
+        // Stand-in audit service that keeps what it is sent
+        let received = Received::default();
+        let app = Router::new()
+            .route("/api/v1/audit", post(|State(received): State<Received>, Json(entry): Json<serde_json::Value>| async move {
+                received.write().await.push(entry);
+            }))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        
+        let service = WorkflowService::new().with_audit(AuditClient::new(url));
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![Uuid::new_v4()],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        let task_id = service.claim_due_tasks(Utc::now() + Duration::seconds(30), 10).await[0].id;
+        service.fail_task(task_id, "Recipient suppressed".to_string(), None).await.unwrap();
+        service.cancel_workflow(workflow.id, CancelWorkflowRequest::default()).await.unwrap();
+        
+        let mut entries = Vec::new();
+        for _ in 0..50 {
+            entries = received.read().await.clone();
+            if entries.len() >= 5 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let transition = |entity_type: &str, to: &str| entries.iter()
+            .find(|e| e["entity_type"] == entity_type && e["details"]["to_state"] == to)
+            .map(|e| (e["action"].as_str().unwrap().to_string(), e["details"]["from_state"].clone()));
+        
+        assert_eq!(transition("workflow", "active"), Some(("create".to_string(), serde_json::Value::Null)));
+        assert_eq!(transition("task", "running"), Some(("update".to_string(), serde_json::json!("scheduled"))));
+        assert_eq!(transition("task", "exhausted"), Some(("update".to_string(), serde_json::json!("running"))));
+        assert_eq!(transition("escalation", "open"), Some(("escalate".to_string(), serde_json::Value::Null)));
+        assert_eq!(transition("workflow", "cancelled"), Some(("update".to_string(), serde_json::json!("active"))));
+    }
+    
+    #[tokio::test]
+    async fn test_overlapping_campaigns_do_not_email_a_supplier_twice() {
+        use crate::outreach_guard::OutreachPolicy;
+        
+        let supplier_id = Uuid::new_v4();
+        let request = |name: &str| CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: name.to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        };
+        let now = Utc::now() + Duration::seconds(30);
+        
+        // Staggered: the second campaign's outreach waits out the gap after the first email
+        let mut service = WorkflowService::new();
+        service.outreach_guard = OutreachGuard { policy: OutreachPolicy::Stagger, min_gap: Duration::hours(24) };
+        service.create_workflow(request("PFAS 2026")).await.unwrap();
+        service.create_workflow(request("REACH Q3")).await.unwrap();
+        let first = service.claim_due_tasks(now, 10).await;
+        assert_eq!(first.len(), 1);
+        service.complete_task(first[0].id, Some(serde_json::json!({ "thread_id": "thread_1" }))).await.unwrap();
+        assert!(service.claim_due_tasks(now + Duration::hours(23), 10).await.is_empty());
+        let second = service.claim_due_tasks(now + Duration::hours(25), 10).await;
+        assert_eq!(second.len(), 1);
+        assert_ne!(second[0].workflow_id, first[0].workflow_id);
+        
+        // Coalesced: one email covers both campaigns and both tasks complete with it
+        let mut service = WorkflowService::new();
+        service.outreach_guard = OutreachGuard { policy: OutreachPolicy::Coalesce, min_gap: Duration::hours(24) };
+        service.create_workflow(request("PFAS 2026")).await.unwrap();
+        let reach = service.create_workflow(request("REACH Q3")).await.unwrap();
+        let claimed = service.claim_due_tasks(now, 10).await;
+        assert_eq!((claimed.len(), claimed[0].coalesced.len()), (1, 1));
+        let joined = service.get_task(claimed[0].coalesced[0].id).await.unwrap().unwrap();
+        assert_ne!(joined.workflow_id, claimed[0].workflow_id);
+        assert_eq!(joined.status, "running");
+        
+        service.complete_task(joined.id, Some(serde_json::json!({ "coalesced_into": claimed[0].id }))).await.unwrap();
+        assert_eq!(service.get_workflow(reach.id).await.unwrap().unwrap().progress.contacted, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_forecast_projects_silent_suppliers_from_replies_so_far() {
+        let service = WorkflowService::new();
+        let (quick, silent) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS 2026".to_string(),
+            supplier_ids: vec![quick, silent],
+            deadline: (Utc::now() + Duration::days(3)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        for task in service.claim_due_tasks(Utc::now() + Duration::minutes(5), 10).await {
+            service.complete_task(task.id, None).await.unwrap();
+        }
+        
+        // Without any history there is nothing to go on
+        let forecast = service.forecast(workflow.id, Utc::now()).await.unwrap().unwrap();
+        assert_eq!(forecast.suppliers[0].basis, "none");
+        assert!(!forecast.likely_to_miss_deadline);
+        
+        // One supplier took four days, so the other is expected about then, after the deadline
+        let mut event = reply(quick, ReplyClassification::PartialResponse, 0);
+        event.received_at = (Utc::now() + Duration::days(4)).to_rfc3339();
+        service.handle_reply(event).await.unwrap();
+        let forecast = service.forecast(workflow.id, Utc::now() + Duration::days(1)).await.unwrap().unwrap();
+        assert_eq!((forecast.responded, forecast.suppliers.len()), (1, 1));
+        assert_eq!((forecast.suppliers[0].supplier_id, forecast.suppliers[0].basis.as_str()), (silent, "campaign_history"));
+        assert_eq!((forecast.expected_response_rate, forecast.expected_response_rate_by_deadline), (100.0, 50.0));
+        assert!(forecast.likely_to_miss_deadline);
+        
+        // Silent for longer than anyone took to answer, it is no longer expected to
+        let forecast = service.forecast(workflow.id, Utc::now() + Duration::days(5)).await.unwrap().unwrap();
+        assert_eq!(forecast.expected_response_rate, 50.0);
+        
+        service.cancel_workflow(workflow.id, CancelWorkflowRequest::default()).await.unwrap();
+        assert!(service.forecast(workflow.id, Utc::now()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_metrics_compare_campaign_with_client_history() {
+        let service = WorkflowService::new();
+        let client_id = Uuid::new_v4();
+        let (earlier, answering, silent) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let request = |name: &str, supplier_ids: Vec<Uuid>| CreateWorkflowRequest {
+            client_id,
+            campaign_name: name.to_string(),
+            supplier_ids,
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: None,
+        };
+        let last_year = service.create_workflow(request("REACH 2025", vec![earlier])).await.unwrap();
+        let due = service.claim_due_tasks(Utc::now() + Duration::seconds(30), 10).await;
+        service.complete_task(due[0].id, None).await.unwrap();
+        service.handle_reply(reply(earlier, ReplyClassification::CompleteResponse, 1)).await.unwrap();
+        
+        let workflow = service.create_workflow(request("REACH 2026", vec![answering, silent])).await.unwrap();
+        let due = service.claim_due_tasks(Utc::now() + Duration::seconds(30), 10).await;
+        let outreach = due.iter().find(|t| t.workflow_id == workflow.id).unwrap();
+        service.complete_task(outreach.id, None).await.unwrap();
+        service.handle_reply(reply(answering, ReplyClassification::PartialResponse, 1)).await.unwrap();
+        service.escalate_supplier(workflow.id, silent, "Bounced".to_string()).await.unwrap();
+        
+        let metrics = service.metrics(workflow.id, Utc::now()).await.unwrap();
+        let funnel = &metrics.funnel;
+        assert_eq!((funnel.suppliers, funnel.contacted, funnel.responded, funnel.complete, funnel.escalated), (2, 1, 1, 0, 1));
+        assert_eq!((metrics.metrics.response_rate, metrics.metrics.escalation_rate), (50.0, 50.0));
+        assert_eq!(metrics.metrics.follow_ups_per_response, Some(0.0));
+        assert!(metrics.metrics.average_response_hours.is_some());
+        
+        let history = metrics.client_history.unwrap();
+        assert_eq!((history.campaigns, history.averages.completion_rate), (1, 100.0));
+        assert_eq!((history.difference.response_rate, history.difference.completion_rate), (-50.0, -100.0));
+        assert!(service.metrics(last_year.id, Utc::now()).await.unwrap().client_history.is_none());
+        assert!(service.metrics(Uuid::new_v4(), Utc::now()).await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_recurring_templates_launch_each_cycle_once() {
+        use chrono::TimeZone;
+        
+        let service = WorkflowService::new();
+        let recurrence = |require_confirmation: bool| RecurrenceRequest {
+            cron: "0 9 1 1,4,7,10 *".to_string(),
+            client_id: Uuid::new_v4(),
+            supplier_ids: vec![Uuid::new_v4()],
+            require_confirmation,
+        };
+        let now = Utc.with_ymd_and_hms(2026, 2, 14, 12, 0, 0).unwrap();
+        let template = service.set_campaign_recurrence(TSCA_PFAS_QUARTERLY, recurrence(false), now).await.unwrap().unwrap();
+        assert_eq!(template.recurrence.unwrap().next_run_at, "2026-04-01T09:00:00+00:00");
+        assert!(service.set_campaign_recurrence("missing", recurrence(false), now).await.unwrap().is_none());
+        let mut invalid = recurrence(false);
+        invalid.cron = "0 9 1 *".to_string();
+        assert!(service.set_campaign_recurrence(TSCA_PFAS_QUARTERLY, invalid, now).await.is_err());
+        
+        assert!(service.launch_due_campaigns(now).await.is_empty());
+        let april = Utc.with_ymd_and_hms(2026, 4, 1, 9, 0, 0).unwrap();
+        let launched = service.launch_due_campaigns(april).await;
+        assert_eq!((launched.len(), launched[0].status.as_str()), (1, "active"));
+        assert!(service.launch_due_campaigns(april).await.is_empty());
+        let recurrence_state = service.get_campaign_template(TSCA_PFAS_QUARTERLY).await.unwrap().recurrence.unwrap();
+        assert_eq!((recurrence_state.next_run_at.as_str(), recurrence_state.last_workflow_id), ("2026-07-01T09:00:00+00:00", Some(launched[0].id)));
+        
+        // With confirmation on, the cycle waits as a pending campaign until someone activates it
+        service.set_campaign_recurrence(TSCA_PFAS_QUARTERLY, recurrence(true), april).await.unwrap();
+        let pending = service.launch_due_campaigns(Utc.with_ymd_and_hms(2026, 7, 1, 9, 0, 0).unwrap()).await;
+        assert_eq!(pending[0].status, "pending");
+        let due = service.claim_due_tasks(Utc::now() + Duration::minutes(5), 10).await;
+        assert!(due.iter().all(|t| t.workflow_id != pending[0].id));
+        assert_eq!(service.update_status(pending[0].id, "active").await.unwrap().status, "active");
+        
+        service.clear_campaign_recurrence(TSCA_PFAS_QUARTERLY).await.unwrap();
+        assert!(service.launch_due_campaigns(Utc.with_ymd_and_hms(2027, 1, 1, 9, 0, 0).unwrap()).await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_completed_outreach_schedules_follow_ups_from_config() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q1".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 2, follow_up_interval_days: 5, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        service.complete_task(outreach[0].id, Some(serde_json::json!({ "thread_id": "thread_1" }))).await.unwrap();
+        assert_eq!(service.schedule_follow_ups(workflow.id, supplier_id).await, 0);
+        
+        let follow_up_times = |tasks: Vec<TaskResponse>| {
+            let mut times: Vec<DateTime<Utc>> = tasks.into_iter()
+                .filter(|t| t.task_type == TaskType::FollowUp.to_string() && t.status == TaskState::Scheduled.to_string())
+                .map(|t| DateTime::parse_from_rfc3339(&t.scheduled_at.unwrap()).unwrap().with_timezone(&Utc))
+                .collect();
+            times.sort();
+            times
+        };
+        let scheduled = follow_up_times(service.get_workflow_tasks(workflow.id).await.unwrap());
+        assert_eq!(scheduled.len(), 2);
+        assert_eq!((scheduled[0] - Utc::now() + Duration::minutes(1)).num_days(), 5);
+        assert_eq!((scheduled[1] - scheduled[0]).num_days(), 5);
+        
+        // A question restarts the clock, keeping the spacing
+        let handled = service.handle_reply(reply(supplier_id, ReplyClassification::Question, 0)).await.unwrap();
+        assert_eq!(handled.tasks_rescheduled, 2);
+        let rescheduled = follow_up_times(service.get_workflow_tasks(workflow.id).await.unwrap());
+        assert!(rescheduled[0] > scheduled[0]);
+        assert_eq!((rescheduled[1] - rescheduled[0]).num_days(), 5);
+        
+        let due = service.claim_due_tasks(rescheduled[0], 10).await;
+        assert_eq!((due[0].task_type, due[0].thread_id.as_deref()), (TaskType::FollowUp, Some("thread_1")));
+        
+        // Once the data is in, the remaining chaser is skipped and the workflow completes
+        service.complete_task(due[0].id, None).await.unwrap();
+        service.handle_reply(reply(supplier_id, ReplyClassification::CompleteResponse, 0)).await.unwrap();
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().status, WorkflowState::Completed.to_string());
+    }
+    
+    #[tokio::test]
+    async fn test_config_changes_are_versioned_and_apply_from_now_on() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let config = WorkflowConfig { max_follow_ups: 2, follow_up_interval_days: 5, ..WorkflowConfig::default() };
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q1".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(config.clone()),
+        }).await.unwrap();
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        let follow_up_times = |tasks: Vec<TaskResponse>| {
+            let mut times: Vec<String> = tasks.into_iter()
+                .filter(|t| t.task_type == TaskType::FollowUp.to_string())
+                .filter_map(|t| t.scheduled_at)
+                .collect();
+            times.sort();
+            times
+        };
+        let scheduled = follow_up_times(service.get_workflow_tasks(workflow.id).await.unwrap());
+        
+        let slower = WorkflowConfig { follow_up_interval_days: 10, ..config.clone() };
+        let version = service.update_config(workflow.id, UpdateWorkflowConfigRequest {
+            config: slower.clone(),
+            changed_by: Some("compliance@acme.example".to_string()),
+            reason: Some("Supplier holidays".to_string()),
+        }).await.unwrap().unwrap();
+        assert_eq!((version.version, version.changes.clone()), (2, vec!["follow_up_interval_days: 5 -> 10".to_string()]));
+        assert_eq!(follow_up_times(service.get_workflow_tasks(workflow.id).await.unwrap()), scheduled);
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().config_version, 2);
+        
+        // Rescheduling after a question follows the new interval
+        service.handle_reply(reply(supplier_id, ReplyClassification::Question, 0)).await.unwrap();
+        let rescheduled = follow_up_times(service.get_workflow_tasks(workflow.id).await.unwrap());
+        let next = DateTime::parse_from_rfc3339(&rescheduled[0]).unwrap().with_timezone(&Utc);
+        assert_eq!((next - Utc::now() + Duration::minutes(1)).num_days(), 10);
+        
+        let versions = service.config_versions(workflow.id).await.unwrap();
+        assert_eq!(versions.iter().map(|v| v.active).collect::<Vec<_>>(), vec![false, true]);
+        assert_eq!(versions[1].changed_by.as_deref(), Some("compliance@acme.example"));
+        let unchanged = UpdateWorkflowConfigRequest { config: slower, changed_by: None, reason: None };
+        assert!(service.update_config(workflow.id, unchanged).await.is_err());
+        let phased = WorkflowConfig {
+            phases: vec![PhaseConfig {
+                phase: CampaignPhase::InitialOutreach,
+                template_id: Some("initial_outreach".to_string()),
+                after_days: 0,
+                entry: None,
+            }],
+            ..config
+        };
+        assert!(service.update_config(workflow.id, UpdateWorkflowConfigRequest { config: phased, changed_by: None, reason: None }).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_phased_campaign_skips_phases_whose_entry_criteria_fail() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let phase = |phase: CampaignPhase, template_id: Option<&str>, after_days: i32| PhaseConfig {
+            phase,
+            template_id: template_id.map(str::to_string),
+            after_days,
+            entry: None,
+        };
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q1".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(WorkflowConfig {
+                phases: vec![
+                    phase(CampaignPhase::InitialOutreach, Some("initial_outreach"), 0),
+                    phase(CampaignPhase::Reminder, Some("follow_up"), 3),
+                    phase(CampaignPhase::FinalNotice, Some("final_notice"), 5),
+                    phase(CampaignPhase::Closeout, None, 2),
+                ],
+                ..WorkflowConfig::default()
+            }),
+        }).await.unwrap();
+        
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        assert_eq!(outreach[0].email_template_id.as_deref(), Some("initial_outreach"));
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        let status = service.phase_status(workflow.id).await.unwrap();
+        assert_eq!(status.suppliers[0].current_phase.as_deref(), Some("initial_outreach"));
+        assert_eq!(status.suppliers[0].next_phase.as_deref(), Some("reminder"));
+        
+        // The supplier answered, so the reminder is skipped and the final notice follows
+        service.handle_reply(reply(supplier_id, ReplyClassification::PartialResponse, 0)).await.unwrap();
+        let later = Utc::now() + Duration::days(30);
+        assert!(service.claim_due_tasks(later, 10).await.is_empty());
+        let notice = service.claim_due_tasks(later + Duration::days(5), 10).await;
+        assert_eq!((notice[0].task_type, notice[0].email_template_id.as_deref()), (TaskType::FollowUp, Some("final_notice")));
+        service.complete_task(notice[0].id, None).await.unwrap();
+        
+        let closeout = service.claim_due_tasks(later + Duration::days(5), 10).await;
+        assert_eq!((closeout[0].task_type, closeout[0].email_template_id.as_deref()), (TaskType::Closeout, None));
+        service.complete_task(closeout[0].id, None).await.unwrap();
+        
+        let status = service.phase_status(workflow.id).await.unwrap();
+        let supplier = &status.suppliers[0];
+        assert!(status.phased && supplier.closed && supplier.next_phase.is_none());
+        assert_eq!(supplier.completed_phases, vec!["initial_outreach", "final_notice", "closeout"]);
+        assert_eq!(supplier.skipped_phases, vec!["reminder"]);
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().status, WorkflowState::Completed.to_string());
+    }
+    
+    #[tokio::test]
+    async fn test_escalation_checks_follow_threshold_and_deadline() {
+        let service = WorkflowService::new();
+        let (silent, replied) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q2".to_string(),
+            supplier_ids: vec![silent, replied],
+            deadline: (now + Duration::days(30)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        service.handle_reply(reply(replied, ReplyClassification::PartialResponse, 0)).await.unwrap();
+        
+        // Nothing is due yet: no contact, deadline well away
+        assert_eq!(service.run_escalation_checks(now).await.unwrap(), EscalationSweep::default());
+        
+        for task in service.tasks.write().await.values_mut().filter(|t| t.supplier_id == silent) {
+            task.state = TaskState::Completed;
+            task.completed_at = Some(now - Duration::days(21));
+        }
+        let sweep = service.run_escalation_checks(now).await.unwrap();
+        assert_eq!(sweep.escalations_created, 1);
+        
+        // Inside the warning window: a deadline escalation, then raised as it gets closer
+        let sweep = service.run_escalation_checks(now + Duration::days(27)).await.unwrap();
+        assert_eq!((sweep.escalations_created, sweep.escalations_raised), (1, 1));
+        assert_eq!(service.run_escalation_checks(now + Duration::days(27)).await.unwrap(), EscalationSweep::default());
+        let sweep = service.run_escalation_checks(now + Duration::days(31)).await.unwrap();
+        assert_eq!((sweep.escalations_created, sweep.escalations_raised), (0, 2));
+        
+        let escalations = service.list_escalations(&EscalationQuery::default()).await.unwrap();
+        assert_eq!(escalations.len(), 2);
+        assert!(escalations.iter().all(|e| e.supplier_id == silent && e.severity == "critical"));
+        assert!(escalations.iter().any(|e| e.reason == "Deadline passed without a response"));
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().progress.escalated, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_launch_campaign_from_template() {
+        let service = WorkflowService::new();
+        // A supplier per campaign, so the outreach guard does not hold one back
+        let launch = |campaign_name: Option<&str>| LaunchCampaignRequest {
+            client_id: Uuid::new_v4(),
+            supplier_ids: vec![Uuid::new_v4()],
+            campaign_name: campaign_name.map(str::to_string),
+            deadline: None,
+        };
+        
+        let workflow = service.launch_campaign(TSCA_PFAS_QUARTERLY, launch(None)).await.unwrap().unwrap();
+        assert!(workflow.campaign_name.starts_with("Quarterly TSCA PFAS outreach 20"));
+        let deadline = DateTime::parse_from_rfc3339(&workflow.deadline).unwrap().with_timezone(&Utc);
+        assert_eq!((deadline - Utc::now() + Duration::minutes(1)).num_days(), 45);
+        assert!(service.launch_campaign("missing", launch(None)).await.unwrap().is_none());
+        
+        let template = service.set_campaign_template("reach_svhc", CampaignTemplateRequest {
+            name: "REACH SVHC update".to_string(),
+            description: None,
+            config: Some(WorkflowConfig { follow_up_template_id: "svhc_reminder".to_string(), ..WorkflowConfig::default() }),
+            response_days: 30,
+        }).await.unwrap();
+        assert!(!template.built_in);
+        assert!(service.set_campaign_template("Bad Id", CampaignTemplateRequest {
+            name: "Bad".to_string(),
+            description: None,
+            config: None,
+            response_days: 30,
+        }).await.is_err());
+        
+        // The template's email selections reach the tasks it schedules
+        let workflow = service.launch_campaign("reach_svhc", launch(Some("SVHC 2026"))).await.unwrap().unwrap();
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        let outreach = outreach.iter().find(|t| t.workflow_id == workflow.id).unwrap();
+        assert_eq!(outreach.email_template_id.as_deref(), Some("initial_outreach"));
+        service.complete_task(outreach.id, None).await.unwrap();
+        let follow_ups = service.claim_due_tasks(Utc::now() + Duration::days(8), 10).await;
+        let follow_up = follow_ups.iter().find(|t| t.workflow_id == workflow.id).unwrap();
+        assert_eq!(follow_up.email_template_id.as_deref(), Some("svhc_reminder"));
+        assert_eq!(service.list_campaign_templates().await.len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_tasks_wait_on_their_dependencies() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q2".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 2, follow_up_interval_days: 5, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        
+        let outreach = service.claim_due_tasks(Utc::now(), 10).await;
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        
+        // Follow-ups are chained after the outreach and each other
+        let graph = service.task_graph(workflow.id).await.unwrap().unwrap();
+        assert_eq!(graph.tasks.len(), 3);
+        let (first, second) = (graph.tasks[1].id, graph.tasks[2].id);
+        assert_eq!(graph.tasks[1].depends_on, vec![outreach[0].id]);
+        assert_eq!(graph.tasks[2].depends_on, vec![first]);
+        assert_eq!(graph.blocked, vec![second]);
+        assert!(service.task_graph(Uuid::new_v4()).await.unwrap().is_none());
+        
+        assert!(service.add_task_dependencies(first, &[second]).await.is_err());
+        assert!(service.add_task_dependencies(first, &[Uuid::new_v4()]).await.is_err());
+        assert!(service.add_task_dependencies(Uuid::new_v4(), &[first]).await.unwrap().is_none());
+        
+        // Both are overdue, but the second waits for the first
+        let due = service.claim_due_tasks(Utc::now() + Duration::days(11), 10).await;
+        assert_eq!(due.iter().map(|t| t.id).collect::<Vec<_>>(), vec![first]);
+        
+        // A dependency that can never complete skips its dependents
+        service.fail_task(first, "Recipient suppressed".to_string(), None).await.unwrap();
+        assert!(service.claim_due_tasks(Utc::now() + Duration::days(11), 10).await.is_empty());
+        let skipped = service.get_task(second).await.unwrap().unwrap();
+        assert_eq!(skipped.status, TaskState::Skipped.to_string());
+    }
+    
+    #[tokio::test]
+    async fn test_manual_tasks_are_worked_by_people_within_the_workflow() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "Conflict Minerals 2026".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 0, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        let outreach = service.get_workflow_tasks(workflow.id).await.unwrap()[0].id;
+        
+        let request = |supplier_id, instructions: &str| CreateManualTaskRequest {
+            supplier_id,
+            assignee: None,
+            instructions: instructions.to_string(),
+            due_at: Some((Utc::now() - Duration::days(1)).to_rfc3339()),
+            depends_on: vec![outreach],
+        };
+        assert!(service.create_manual_task(workflow.id, request(Uuid::new_v4(), "Call supplier")).await.is_err());
+        assert!(service.create_manual_task(workflow.id, request(supplier_id, " ")).await.is_err());
+        assert!(service.create_manual_task(Uuid::new_v4(), request(supplier_id, "Call supplier")).await.unwrap().is_none());
+        let call = service.create_manual_task(workflow.id, request(supplier_id, "Call the quality manager")).await.unwrap().unwrap();
+        assert_eq!(call.task_type, "manual");
+        assert!(call.manual.as_ref().unwrap().overdue);
+        
+        // The executor only runs the outreach, and the call waits for it
+        let claimed = service.claim_due_tasks(Utc::now() + Duration::seconds(1), 10).await;
+        assert_eq!(claimed.iter().map(|t| t.id).collect::<Vec<_>>(), vec![outreach]);
+        assert!(service.complete_manual_task(call.id, None, None).await.is_err());
+        service.complete_task(outreach, None).await.unwrap();
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().status, "active");
+        
+        service.assign_manual_task(call.id, "maria@example.com").await.unwrap().unwrap();
+        let open = service.list_manual_tasks(&ManualTaskQuery { assignee: Some("maria@example.com".to_string()), open: Some(true) }).await;
+        assert_eq!(open.len(), 1);
+        assert!(service.assign_manual_task(outreach, "maria@example.com").await.unwrap().is_none());
+        
+        let done = service.complete_manual_task(call.id, Some("SDS promised by Friday".to_string()), None).await.unwrap().unwrap();
+        let manual = done.manual.unwrap();
+        assert_eq!((manual.completed_by.as_deref(), manual.notes.as_deref(), manual.overdue), (Some("maria@example.com"), Some("SDS promised by Friday"), false));
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().status, "completed");
+        assert!(service.list_manual_tasks(&ManualTaskQuery { assignee: None, open: Some(true) }).await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_email_and_document_events_update_progress() {
+        let service = WorkflowService::new();
+        let (emailed, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q3".to_string(),
+            supplier_ids: vec![emailed, other],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        let sent = |campaign_id: Option<Uuid>| EmailSentEvent {
+            email_id: Uuid::new_v4(),
+            supplier_id: emailed,
+            thread_id: "thread_1".to_string(),
+            campaign_id,
+            template_id: Some("initial_outreach".to_string()),
+            sent_at: Utc::now().to_rfc3339(),
+        };
+        
+        assert_eq!(service.handle_email_sent(sent(Some(Uuid::new_v4()))).await.unwrap().workflows_updated, 0);
+        assert_eq!(service.handle_email_sent(sent(Some(workflow.id))).await.unwrap().workflows_updated, 1);
+        service.handle_email_sent(sent(None)).await.unwrap();
+        service.handle_reply(reply(emailed, ReplyClassification::CompleteResponse, 1)).await.unwrap();
+        service.handle_document_extracted(DocumentExtractedEvent {
+            email_id: Uuid::new_v4(),
+            supplier_id: emailed,
+            campaign_id: None,
+            document_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+            cas_numbers_found: 4,
+            needs_review: false,
+            extracted_at: Utc::now().to_rfc3339(),
+        }).await.unwrap();
+        
+        let progress = service.get_workflow(workflow.id).await.unwrap().unwrap().progress;
+        assert_eq!((progress.contacted, progress.responded, progress.documents_received), (1, 1, 1));
+        
+        let suppliers = service.supplier_progress(workflow.id).await.unwrap().unwrap();
+        let supplier = suppliers.iter().find(|s| s.supplier_id == emailed).unwrap();
+        assert_eq!(supplier.status, SupplierStatus::DataComplete.to_string());
+        assert_eq!((supplier.emails_sent, supplier.replies, supplier.documents_extracted, supplier.cas_numbers_found), (2, 1, 2, 4));
+        assert_eq!(suppliers.iter().find(|s| s.supplier_id == other).unwrap().status, SupplierStatus::NotContacted.to_string());
+        assert!(service.supplier_progress(Uuid::new_v4()).await.unwrap().is_none());
+        
+        let mut invalid = sent(None);
+        invalid.sent_at = "yesterday".to_string();
+        assert!(service.handle_email_sent(invalid).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_domain_events_dispatch_to_handlers() {
+        let service = WorkflowService::new();
+        let supplier_id = Uuid::new_v4();
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "PFAS Q4".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        
+        let sent = EventEnvelope::new("email-communication", DomainEvent::EmailSent(elementa_models::EmailSentEvent {
+            email_id: Uuid::new_v4(),
+            supplier_id,
+            thread_id: "thread_1".to_string(),
+            campaign_id: Some(workflow.id),
+            template_id: None,
+            sent_at: Utc::now(),
+        }));
+        let handled = service.handle_domain_event(sent.clone()).await.unwrap();
+        assert_eq!((handled.event_id, handled.event_type.as_str(), handled.handled), (sent.id, "email_sent", true));
+        assert_eq!(handled.outcome.unwrap()["workflows_updated"], 1);
+        assert_eq!(service.get_workflow(workflow.id).await.unwrap().unwrap().progress.contacted, 1);
+        
+        let bounced = EventEnvelope::caused_by(&sent, "email-communication", DomainEvent::EmailBounced(elementa_models::EmailBouncedEvent {
+            email_id: None,
+            supplier_id: Some(supplier_id),
+            email_address: Some("quality@supplier.example".to_string()),
+            permanent: true,
+            reason: "bounce".to_string(),
+            bounced_at: Utc::now(),
+        }));
+        let ignored = service.handle_domain_event(bounced).await.unwrap();
+        assert!(!ignored.handled && ignored.outcome.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_expiring_certifications_launch_renewal_outreach_once() {
+        let service = WorkflowService::new();
+        let (client_id, supplier_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let expiring = |client_id: Option<Uuid>| CertificationExpiringEvent {
+            supplier_id,
+            component_id: Uuid::new_v4(),
+            compliance_record_id: Uuid::new_v4(),
+            client_id,
+            certification_type: elementa_models::CertificationType::RoHS,
+            certificate_number: "RH-2291".to_string(),
+            expiry_date: Utc::now() + Duration::days(20),
+            status: elementa_models::CertificationStatus::ExpiringSoon,
+        };
+        
+        // Without a campaign to go by, nobody is known to run the renewal for
+        assert!(service.handle_certification_expiring(expiring(None)).await.is_err());
+        
+        service.create_workflow(CreateWorkflowRequest {
+            client_id,
+            campaign_name: "PFAS Q4".to_string(),
+            supplier_ids: vec![supplier_id],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        let envelope = EventEnvelope::new("compliance", DomainEvent::CertificationExpiring(expiring(None)));
+        let handled = service.handle_domain_event(envelope).await.unwrap();
+        assert!(handled.handled);
+        let outreach = handled.outcome.unwrap();
+        assert_eq!(outreach["launched"], true);
+        
+        let workflow_id: Uuid = serde_json::from_value(outreach["workflow_id"].clone()).unwrap();
+        let renewal = service.get_workflow(workflow_id).await.unwrap().unwrap();
+        assert_eq!((renewal.client_id, renewal.campaign_name.as_str()), (client_id, "Certification renewal RoHS RH-2291"));
+        assert!(renewal.deadline.starts_with(&(Utc::now() + Duration::days(20)).format("%Y-%m-%d").to_string()));
+        
+        // A second warning for the same certificate finds the open campaign
+        let again = service.handle_certification_expiring(expiring(Some(Uuid::new_v4()))).await.unwrap();
+        assert_eq!((again.workflow_id, again.launched), (workflow_id, false));
+        
+        // Nothing to check without a database
+        assert_eq!(service.run_certification_checks(Utc::now(), 60).await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_supplier_status_follows_escalations() {
+        let service = WorkflowService::new();
+        let (asking, silent) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "REACH SVHC Q1".to_string(),
+            supplier_ids: vec![asking, silent],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        let status = |suppliers: &[SupplierProgress], id: Uuid| {
+            suppliers.iter().find(|s| s.supplier_id == id).unwrap().status.clone()
+        };
+        
+        let outreach = service.claim_due_tasks(Utc::now() + Duration::minutes(5), 10).await;
+        for task in &outreach {
+            service.complete_task(task.id, None).await.unwrap();
+        }
+        service.handle_reply(reply(asking, ReplyClassification::Question, 0)).await.unwrap();
+        
+        let suppliers = service.supplier_progress(workflow.id).await.unwrap().unwrap();
+        assert_eq!(status(&suppliers, asking), SupplierStatus::Escalated.to_string());
+        assert_eq!(suppliers.iter().find(|s| s.supplier_id == asking).unwrap().open_escalations.len(), 1);
+        assert_eq!(status(&suppliers, silent), SupplierStatus::Contacted.to_string());
+        
+        // Answering the question clears the escalation, leaving the supplier responded
+        let escalation = service.list_escalations(&EscalationQuery::default()).await.unwrap().into_iter().find(|e| e.supplier_id == asking).unwrap();
+        service.resolve_escalation(escalation.id, "Answered").await.unwrap();
+        let suppliers = service.supplier_progress(workflow.id).await.unwrap().unwrap();
+        assert_eq!(status(&suppliers, asking), SupplierStatus::Responded.to_string());
+    }
+    
+    #[tokio::test]
+    async fn test_clone_workflow_repeats_campaign_with_new_deadline() {
+        let service = WorkflowService::new();
+        let suppliers = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let source = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "Conflict minerals 2025".to_string(),
+            supplier_ids: suppliers.clone(),
+            deadline: (Utc::now() + Duration::days(30)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 1, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        let deadline = (Utc::now() + Duration::days(400)).to_rfc3339();
+        let clone = |campaign_name: Option<&str>, supplier_ids: Option<Vec<Uuid>>| CloneWorkflowRequest {
+            deadline: deadline.clone(),
+            campaign_name: campaign_name.map(str::to_string),
+            supplier_ids,
+            config: None,
+        };
+        
+        let copy = service.clone_workflow(source.id, clone(None, None)).await.unwrap().unwrap();
+        assert_eq!((copy.cloned_from, copy.client_id), (Some(source.id), source.client_id));
+        assert_eq!((copy.campaign_name.as_str(), copy.supplier_count, copy.task_count), ("Conflict minerals 2025 (copy)", 2, 2));
+        assert_eq!(copy.deadline, DateTime::parse_from_rfc3339(&deadline).unwrap().with_timezone(&Utc).to_rfc3339());
+        assert_eq!(service.workflows.read().await[&copy.id].config.max_follow_ups, 1);
+        
+        let trimmed = service.clone_workflow(source.id, clone(Some("Conflict minerals 2026"), Some(vec![suppliers[0]]))).await.unwrap().unwrap();
+        assert_eq!((trimmed.campaign_name.as_str(), trimmed.supplier_count), ("Conflict minerals 2026", 1));
+        assert!(service.clone_workflow(source.id, clone(None, Some(Vec::new()))).await.is_err());
+        assert!(service.clone_workflow(Uuid::new_v4(), clone(None, None)).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_sla_report_flags_late_contact_and_follow_up() {
+        let service = WorkflowService {
+            sla: SlaPolicy { first_contact: Duration::hours(24), follow_up: Duration::days(7), at_risk_percent: 75 },
+            ..WorkflowService::new()
+        };
+        let (reached, waiting) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "Prop 65 2026".to_string(),
+            supplier_ids: vec![reached, waiting],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        let outreach = service.claim_due_tasks(Utc::now(), 1).await;
+        assert_eq!(outreach[0].supplier_id, reached);
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        
+        let report = |days: i64, hours: i64| {
+            let service = service.clone();
+            async move {
+                let now = Utc::now() + Duration::days(days) + Duration::hours(hours);
+                let report = service.sla_report(workflow.id, now).await.unwrap().unwrap();
+                let items = |items: Vec<SlaItemResponse>| items.into_iter().map(|i| (i.supplier_id, i.sla)).collect::<Vec<_>>();
+                (report.met, items(report.breaches), items(report.at_risk))
+            }
+        };
+        
+        let first_contact = SlaKind::FirstContact.to_string();
+        let follow_up = SlaKind::FollowUp.to_string();
+        assert_eq!(report(0, 20).await, (1, vec![], vec![(waiting, first_contact.clone())]));
+        assert_eq!(report(6, 0).await, (1, vec![(waiting, first_contact.clone())], vec![(reached, follow_up.clone())]));
+        assert_eq!(report(8, 0).await.1.len(), 2);
+        
+        // A reply settles the outstanding chaser
+        service.handle_reply(reply(reached, ReplyClassification::PartialResponse, 0)).await.unwrap();
+        assert_eq!(report(8, 0).await.1, vec![(waiting, first_contact)]);
+        assert!(service.sla_report(Uuid::new_v4(), Utc::now()).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_escalations_are_routed_and_worked_through() {
+        let service = WorkflowService::new();
+        let client_id = Uuid::new_v4();
+        let rule = |severity: Option<&str>, client_id: Option<Uuid>, assignee: &str| RoutingRuleRequest {
+            severity: severity.map(str::to_string),
+            client_id,
+            relationship: None,
+            assignee: assignee.to_string(),
+            due_hours: None,
+        };
+        service.add_routing_rule(rule(Some("high"), None, "compliance-lead")).await.unwrap();
+        service.add_routing_rule(rule(Some("high"), Some(client_id), "account-manager")).await.unwrap();
+        assert!(service.add_routing_rule(rule(None, None, " ")).await.is_err());
+        
+        let (refusing, asking) = (Uuid::new_v4(), Uuid::new_v4());
+        service.create_workflow(CreateWorkflowRequest {
+            client_id,
+            campaign_name: "RoHS 2026".to_string(),
+            supplier_ids: vec![refusing, asking],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: None,
+        }).await.unwrap();
+        service.handle_reply(reply(refusing, ReplyClassification::Refusal, 0)).await.unwrap();
+        service.handle_reply(reply(asking, ReplyClassification::Question, 0)).await.unwrap();
+        
+        // The client-specific rule outranks the severity-only one; nothing matches medium
+        let query = |assignee: Option<&str>, state: Option<&str>| EscalationQuery {
+            assignee: assignee.map(str::to_string),
+            state: state.map(str::to_string),
+        };
+        let routed = service.list_escalations(&query(Some("account-manager"), None)).await.unwrap();
+        assert_eq!((routed.len(), routed[0].supplier_id), (1, refusing));
+        let due = DateTime::parse_from_rfc3339(&routed[0].due_at).unwrap().with_timezone(&Utc);
+        assert_eq!((due - Utc::now() + Duration::minutes(1)).num_hours(), 24);
+        let unassigned = service.list_escalations(&query(None, None)).await.unwrap().into_iter().find(|e| e.supplier_id == asking).unwrap();
+        assert_eq!((unassigned.assignee, unassigned.state.as_str()), (None, "open"));
+        
+        // open -> acknowledged -> resolved
+        let acknowledged = service.acknowledge_escalation(unassigned.id, "analyst").await.unwrap().unwrap();
+        assert_eq!((acknowledged.assignee.as_deref(), acknowledged.state.as_str()), (Some("analyst"), "acknowledged"));
+        assert!(service.acknowledge_escalation(unassigned.id, "analyst").await.is_err());
+        service.comment_on_escalation(unassigned.id, "analyst", "Sent the FAQ").await.unwrap();
+        assert_eq!(service.list_escalations(&query(Some("analyst"), Some("acknowledged"))).await.unwrap()[0].comments.len(), 1);
+        
+        let resolved = service.resolve_escalation(unassigned.id, "Answered").await.unwrap();
+        assert!(resolved.resolved);
+        assert!(service.resolve_escalation(unassigned.id, "Answered").await.is_err());
+        assert!(service.assign_escalation(unassigned.id, "someone").await.is_err());
+        assert_eq!(service.list_escalations(&query(None, Some("open"))).await.unwrap().len(), 1);
+        assert!(service.list_escalations(&query(None, Some("closed"))).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_timeline_shows_actual_and_projected_tasks() {
+        let service = WorkflowService::new();
+        let (sent, waiting) = (Uuid::new_v4(), Uuid::new_v4());
+        let workflow = service.create_workflow(CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "TSCA PFAS Q4".to_string(),
+            supplier_ids: vec![sent, waiting],
+            deadline: (Utc::now() + Duration::days(60)).to_rfc3339(),
+            config: Some(WorkflowConfig { max_follow_ups: 2, follow_up_interval_days: 5, ..WorkflowConfig::default() }),
+        }).await.unwrap();
+        let outreach = service.claim_due_tasks(Utc::now(), 1).await;
+        service.complete_task(outreach[0].id, None).await.unwrap();
+        
+        let timeline = service.timeline(workflow.id).await.unwrap().unwrap();
+        let lane = |id: Uuid| &timeline.suppliers.iter().find(|s| s.supplier_id == id).unwrap().entries;
+        
+        let sent_lane = lane(sent);
+        assert_eq!(sent_lane.len(), 3);
+        assert!(sent_lane[0].completed_at.is_some() && sent_lane.iter().all(|e| !e.projected));
+        
+        let waiting_lane = lane(waiting);
+        assert_eq!(waiting_lane.iter().map(|e| e.projected).collect::<Vec<_>>(), vec![false, true, true]);
+        let at = |e: &TimelineEntry| DateTime::parse_from_rfc3339(&e.start).unwrap();
+        assert_eq!((at(&waiting_lane[2]) - at(&waiting_lane[1])).num_days(), 5);
+        assert!(service.timeline(Uuid::new_v4()).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_due_tasks_are_claimed_by_priority() {
+        let service = WorkflowService::new();
+        let (standard, strategic, urgent) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let request = |supplier_ids: Vec<Uuid>, days_left: i64| CreateWorkflowRequest {
+            client_id: Uuid::new_v4(),
+            campaign_name: "Conflict minerals".to_string(),
+            supplier_ids,
+            deadline: (Utc::now() + Duration::days(days_left)).to_rfc3339(),
+            config: None,
+        };
+        let relaxed = service.create_workflow(request(vec![standard, strategic], 90)).await.unwrap();
+        service.workflows.write().await.get_mut(&relaxed.id).unwrap()
+            .relationships.insert(strategic, SupplierRelationship::Strategic);
+        
+        // Strategic outreach is staggered behind the standard one but goes first
+        let now = Utc::now() + Duration::minutes(5);
+        let first = service.claim_due_tasks(now, 1).await;
+        assert_eq!(first[0].supplier_id, strategic);
+        
+        // A campaign two days from its deadline goes ahead of other due outreach
+        service.create_workflow(request(vec![urgent], 2)).await.unwrap();
+        let next = service.claim_due_tasks(now, 2).await;
+        assert_eq!(next.iter().map(|t| t.supplier_id).collect::<Vec<_>>(), vec![urgent, standard]);
+        let task = service.get_task(next[0].id).await.unwrap().unwrap();
+        assert_eq!(task.priority, 100 + 25);
+    }
+}
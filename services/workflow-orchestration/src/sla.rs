@@ -0,0 +1,119 @@
+//! Service Levels
+//!
+//! Campaign SLAs measured against task records: suppliers are first
+//! contacted within a set time of the campaign starting, and a supplier
+//! that has not replied is chased again within a set time of the last
+//! email. Each check is met, pending, at risk once most of its allowance
+//! is used, or breached.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct SlaPolicy {
+    /// Allowance from campaign start to a supplier's first email
+    pub first_contact: Duration,
+    /// Allowance from an unanswered email to the next one
+    pub follow_up: Duration,
+    /// Share of an allowance after which an open check is at risk
+    pub at_risk_percent: i32,
+}
+
+impl Default for SlaPolicy {
+    fn default() -> Self {
+        let env_i64 = |key: &str, default: i64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            first_contact: Duration::hours(env_i64("SLA_FIRST_CONTACT_HOURS", 24)),
+            follow_up: Duration::days(env_i64("SLA_FOLLOW_UP_DAYS", 7)),
+            at_risk_percent: env_i64("SLA_AT_RISK_PERCENT", 75).clamp(1, 100) as i32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlaKind {
+    FirstContact,
+    FollowUp,
+}
+
+impl std::fmt::Display for SlaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FirstContact => write!(f, "first_contact"),
+            Self::FollowUp => write!(f, "follow_up"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlaStatus {
+    Met,
+    /// Open, with time to spare
+    Pending,
+    AtRisk,
+    Breached,
+}
+
+/// One supplier's emails as recorded by completed outreach and follow-up tasks
+#[derive(Debug, Clone)]
+pub struct SupplierTimeline {
+    pub supplier_id: Uuid,
+    /// When each email went out, oldest first
+    pub contacts: Vec<DateTime<Utc>>,
+    /// Latest engaged reply
+    pub replied_at: Option<DateTime<Utc>>,
+    /// No further email is owed, e.g. the supplier is handled manually
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlaCheck {
+    pub supplier_id: Uuid,
+    pub kind: SlaKind,
+    pub due_at: DateTime<Utc>,
+    /// When the email that satisfied the check went out
+    pub completed_at: Option<DateTime<Utc>>,
+    pub status: SlaStatus,
+}
+
+impl SlaPolicy {
+    /// Check a supplier's emails against the SLAs. `open` is false once the
+    /// campaign has ended, so only checks that already happened are reported.
+    pub fn evaluate(&self, started_at: DateTime<Utc>, timeline: &SupplierTimeline, open: bool, now: DateTime<Utc>) -> Vec<SlaCheck> {
+        let mut checks = Vec::new();
+        let mut check = |kind, from: DateTime<Utc>, allowance: Duration, completed_at: Option<DateTime<Utc>>| {
+            let due_at = from + allowance;
+            let status = match completed_at {
+                Some(at) if at <= due_at => SlaStatus::Met,
+                Some(_) => SlaStatus::Breached,
+                None if now > due_at => SlaStatus::Breached,
+                None if now - from >= allowance * self.at_risk_percent / 100 => SlaStatus::AtRisk,
+                None => SlaStatus::Pending,
+            };
+            checks.push(SlaCheck { supplier_id: timeline.supplier_id, kind, due_at, completed_at, status });
+        };
+
+        let first = timeline.contacts.first().copied();
+        if first.is_some() || (open && !timeline.closed) {
+            check(SlaKind::FirstContact, started_at, self.first_contact, first);
+        }
+
+        // Every email sent before the supplier replied owes a chaser, up to the reply
+        let answered_by = |sent: DateTime<Utc>| timeline.replied_at.is_some_and(|replied| replied >= sent);
+        for (index, sent) in timeline.contacts.iter().enumerate() {
+            if answered_by(*sent) {
+                continue;
+            }
+            match timeline.contacts.get(index + 1) {
+                Some(next) => check(SlaKind::FollowUp, *sent, self.follow_up, Some(*next)),
+                None if open && !timeline.closed => check(SlaKind::FollowUp, *sent, self.follow_up, None),
+                None => {}
+            }
+        }
+
+        checks
+    }
+}
@@ -0,0 +1,47 @@
+//! Stall Job
+//!
+//! Periodically looks for active campaigns that have not moved for a while
+//! and cannot move on their own, e.g. because the executor stopped picking
+//! up due tasks or every remaining task is blocked, and escalates them so
+//! a silent stall does not eat into the regulatory deadline.
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::service::WorkflowService;
+
+/// How often stalls are looked for, `STALL_CHECK_SECS` (default one hour)
+fn check_interval() -> std::time::Duration {
+    let secs = std::env::var("STALL_CHECK_SECS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+    std::time::Duration::from_secs(secs)
+}
+
+/// Days without a change before a campaign counts as stalled, `STALLED_CAMPAIGN_DAYS` (default 3)
+pub fn stall_days() -> i64 {
+    std::env::var("STALLED_CAMPAIGN_DAYS").ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(3)
+}
+
+pub fn spawn_stall_job(service: WorkflowService) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = check_interval();
+        let days = stall_days();
+        info!("Stalled campaign checks every {:?}, after {} idle days", interval, days);
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            match service.run_stall_checks(Utc::now(), days).await {
+                Ok(0) => {}
+                Ok(escalated) => info!("Escalated {} stalled campaigns", escalated),
+                Err(e) => error!("Stalled campaign check failed: {:#}", e),
+            }
+        }
+    })
+}
@@ -3,6 +3,8 @@
 //! Defines workflow and task state transitions.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
 /// Workflow states
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -129,6 +131,125 @@ impl TaskState {
     }
 }
 
+/// Where a supplier stands within a campaign
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SupplierStatus {
+    /// No email has reached the supplier yet
+    NotContacted,
+    /// Emailed, no reply yet
+    Contacted,
+    /// Replied, but the requested data is not all in
+    Responded,
+    /// Requested data received in full
+    DataComplete,
+    /// An open escalation is waiting on a person
+    Escalated,
+}
+
+impl SupplierStatus {
+    /// Status from what is known about a supplier. An open escalation
+    /// outranks everything except complete data.
+    pub fn derive(contacted: bool, responded: bool, data_complete: bool, escalated: bool) -> Self {
+        match (data_complete, escalated, responded, contacted) {
+            (true, ..) => Self::DataComplete,
+            (_, true, ..) => Self::Escalated,
+            (_, _, true, _) => Self::Responded,
+            (.., true) => Self::Contacted,
+            _ => Self::NotContacted,
+        }
+    }
+}
+
+impl std::fmt::Display for SupplierStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotContacted => write!(f, "not_contacted"),
+            Self::Contacted => write!(f, "contacted"),
+            Self::Responded => write!(f, "responded"),
+            Self::DataComplete => write!(f, "data_complete"),
+            Self::Escalated => write!(f, "escalated"),
+        }
+    }
+}
+
+/// Escalation states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscalationState {
+    /// Raised and waiting for its owner
+    Open,
+    /// Owner is working on it
+    Acknowledged,
+    Resolved,
+}
+
+impl EscalationState {
+    pub fn can_transition_to(&self, target: EscalationState) -> bool {
+        use EscalationState::*;
+        
+        matches!((self, target), (Open, Acknowledged) | (Open, Resolved) | (Acknowledged, Resolved))
+    }
+    
+    /// Parse from string
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "open" => Some(Self::Open),
+            "acknowledged" => Some(Self::Acknowledged),
+            "resolved" => Some(Self::Resolved),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EscalationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Acknowledged => write!(f, "acknowledged"),
+            Self::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+/// Where a task stands on the tasks it depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyStatus {
+    /// Every dependency completed
+    Ready,
+    /// Some dependency has not finished yet
+    Waiting,
+    /// Some dependency finished without completing, so the task can never run
+    Broken,
+}
+
+/// Combine the states of a task's dependencies; unknown tasks do not block
+pub fn dependency_status(dependencies: impl IntoIterator<Item = Option<TaskState>>) -> DependencyStatus {
+    let mut status = DependencyStatus::Ready;
+    for state in dependencies.into_iter().flatten() {
+        match state {
+            TaskState::Completed => {}
+            state if state.is_terminal() => return DependencyStatus::Broken,
+            _ => status = DependencyStatus::Waiting,
+        }
+    }
+    status
+}
+
+/// Whether making `task` depend on `dependencies` would close a cycle in
+/// `edges` (task to the tasks it depends on)
+pub fn creates_cycle(edges: &HashMap<Uuid, Vec<Uuid>>, task: Uuid, dependencies: &[Uuid]) -> bool {
+    let mut stack: Vec<Uuid> = dependencies.to_vec();
+    let mut seen: HashSet<Uuid> = HashSet::new();
+    while let Some(next) = stack.pop() {
+        if next == task {
+            return true;
+        }
+        if seen.insert(next) {
+            stack.extend(edges.get(&next).into_iter().flatten().copied());
+        }
+    }
+    false
+}
+
 impl std::fmt::Display for TaskState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -156,6 +277,10 @@ pub enum TaskType {
     Validation,
     /// Generate escalation
     Escalation,
+    /// Work done by a person, e.g. calling a silent supplier or a legal review
+    Manual,
+    /// Close a supplier's campaign, with a closing email if its phase has a template
+    Closeout,
 }
 
 impl std::fmt::Display for TaskType {
@@ -166,6 +291,8 @@ impl std::fmt::Display for TaskType {
             Self::FollowUp => write!(f, "follow_up"),
             Self::Validation => write!(f, "validation"),
             Self::Escalation => write!(f, "escalation"),
+            Self::Manual => write!(f, "manual"),
+            Self::Closeout => write!(f, "closeout"),
         }
     }
 }
@@ -187,4 +314,34 @@ mod tests {
         assert!(TaskState::Failed.can_transition_to(TaskState::Running)); // Retry
         assert!(!TaskState::Completed.can_transition_to(TaskState::Running));
     }
+    
+    #[test]
+    fn test_escalation_transitions() {
+        assert!(EscalationState::Open.can_transition_to(EscalationState::Acknowledged));
+        assert!(EscalationState::Acknowledged.can_transition_to(EscalationState::Resolved));
+        assert!(!EscalationState::Resolved.can_transition_to(EscalationState::Open));
+        assert!(!EscalationState::Acknowledged.can_transition_to(EscalationState::Open));
+    }
+    
+    #[test]
+    fn test_task_dependencies() {
+        use TaskState::*;
+        assert_eq!(dependency_status([Some(Completed), None]), DependencyStatus::Ready);
+        assert_eq!(dependency_status([Some(Completed), Some(Running)]), DependencyStatus::Waiting);
+        assert_eq!(dependency_status([Some(Scheduled), Some(Exhausted)]), DependencyStatus::Broken);
+        
+        let (outreach, follow_up, second) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let edges = HashMap::from([(follow_up, vec![outreach]), (second, vec![follow_up])]);
+        assert!(creates_cycle(&edges, outreach, &[second]));
+        assert!(!creates_cycle(&edges, second, &[outreach]));
+    }
+    
+    #[test]
+    fn test_supplier_status() {
+        assert_eq!(SupplierStatus::derive(false, false, false, false), SupplierStatus::NotContacted);
+        assert_eq!(SupplierStatus::derive(true, true, false, false), SupplierStatus::Responded);
+        assert_eq!(SupplierStatus::derive(true, true, false, true), SupplierStatus::Escalated);
+        assert_eq!(SupplierStatus::derive(true, true, true, true), SupplierStatus::DataComplete);
+        assert_eq!(SupplierStatus::Escalated.to_string(), "escalated");
+    }
 }
@@ -0,0 +1,4 @@
+// Embedded migrations are only re-read when the crate rebuilds
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
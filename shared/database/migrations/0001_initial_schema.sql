@@ -0,0 +1,212 @@
+-- Baseline schema, as created by the inline migrations that preceded
+-- versioned migrations. Statements are idempotent so databases created by
+-- the old runner are brought up to date and recorded at this version.
+
+-- Create suppliers table
+CREATE TABLE IF NOT EXISTS suppliers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR NOT NULL,
+    contact_info JSONB NOT NULL,
+    relationship VARCHAR NOT NULL,
+    compliance_history JSONB NOT NULL DEFAULT '[]',
+    communication_preferences JSONB NOT NULL,
+    risk_profile JSONB NOT NULL,
+    tier VARCHAR NOT NULL DEFAULT 'Tier1',
+    parent_id UUID REFERENCES suppliers(id) ON DELETE SET NULL,
+    lifecycle_status VARCHAR NOT NULL DEFAULT 'active',
+    deleted_at TIMESTAMPTZ,
+    schema_version INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Columns added to suppliers after its initial release
+ALTER TABLE suppliers
+    ADD COLUMN IF NOT EXISTS schema_version INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS tier VARCHAR NOT NULL DEFAULT 'Tier1',
+    ADD COLUMN IF NOT EXISTS parent_id UUID REFERENCES suppliers(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS lifecycle_status VARCHAR NOT NULL DEFAULT 'active',
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- Create components table
+CREATE TABLE IF NOT EXISTS components (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    part_number VARCHAR NOT NULL,
+    description TEXT NOT NULL,
+    cas_numbers JSONB NOT NULL DEFAULT '[]',
+    material_type VARCHAR NOT NULL,
+    supplier_id UUID NOT NULL REFERENCES suppliers(id),
+    specifications JSONB NOT NULL,
+    lifecycle_status VARCHAR NOT NULL DEFAULT 'active',
+    deleted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Columns added to components after its initial release
+ALTER TABLE components
+    ADD COLUMN IF NOT EXISTS lifecycle_status VARCHAR NOT NULL DEFAULT 'active',
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- Create component_links table: bill of materials edges from assemblies to their parts
+CREATE TABLE IF NOT EXISTS component_links (
+    parent_id UUID NOT NULL REFERENCES components(id) ON DELETE CASCADE,
+    child_id UUID NOT NULL REFERENCES components(id) ON DELETE CASCADE,
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (parent_id, child_id),
+    CHECK (parent_id <> child_id),
+    CHECK (quantity > 0)
+);
+
+-- Create compliance_records table
+CREATE TABLE IF NOT EXISTS compliance_records (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    supplier_id UUID NOT NULL REFERENCES suppliers(id),
+    component_id UUID NOT NULL REFERENCES components(id),
+    cas_records JSONB NOT NULL DEFAULT '[]',
+    test_results JSONB NOT NULL DEFAULT '[]',
+    certifications JSONB NOT NULL DEFAULT '[]',
+    exemptions JSONB NOT NULL DEFAULT '[]',
+    submission_date TIMESTAMPTZ NOT NULL,
+    validation_status VARCHAR NOT NULL,
+    audit_trail JSONB NOT NULL DEFAULT '[]',
+    schema_version INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Columns added to compliance_records after its initial release
+ALTER TABLE compliance_records
+    ADD COLUMN IF NOT EXISTS exemptions JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS schema_version INTEGER NOT NULL DEFAULT 1;
+
+-- Create chemical_substances table
+CREATE TABLE IF NOT EXISTS chemical_substances (
+    cas_number VARCHAR PRIMARY KEY,
+    chemical_name VARCHAR NOT NULL,
+    molecular_formula VARCHAR,
+    molecular_weight DECIMAL,
+    is_pfas BOOLEAN NOT NULL DEFAULT FALSE,
+    pfas_classification JSONB,
+    regulatory_status JSONB NOT NULL,
+    last_updated TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create workflows table
+CREATE TABLE IF NOT EXISTS workflows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    client_id UUID NOT NULL,
+    campaign_name VARCHAR NOT NULL,
+    suppliers JSONB NOT NULL DEFAULT '[]',
+    status VARCHAR NOT NULL,
+    start_date TIMESTAMPTZ NOT NULL,
+    deadline TIMESTAMPTZ NOT NULL,
+    progress JSONB NOT NULL,
+    escalations JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create agent_tasks table
+CREATE TABLE IF NOT EXISTS agent_tasks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workflow_id UUID NOT NULL REFERENCES workflows(id),
+    task_type VARCHAR NOT NULL,
+    supplier_id UUID NOT NULL REFERENCES suppliers(id),
+    context JSONB NOT NULL,
+    status VARCHAR NOT NULL,
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+-- Create email_communications table
+CREATE TABLE IF NOT EXISTS email_communications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    thread_id VARCHAR NOT NULL,
+    supplier_id UUID NOT NULL REFERENCES suppliers(id),
+    direction VARCHAR NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attachments JSONB NOT NULL DEFAULT '[]',
+    sent_at TIMESTAMPTZ,
+    received_at TIMESTAMPTZ,
+    delivery_status VARCHAR NOT NULL,
+    processing_status VARCHAR NOT NULL,
+    message_id VARCHAR,
+    in_reply_to VARCHAR,
+    message_references JSONB NOT NULL DEFAULT '[]',
+    recipient VARCHAR,
+    classification VARCHAR,
+    classification_confidence DOUBLE PRECISION,
+    delivery_attempts JSONB NOT NULL DEFAULT '[]',
+    content_hash VARCHAR,
+    smime_signed BOOLEAN NOT NULL DEFAULT FALSE,
+    smime_encrypted BOOLEAN NOT NULL DEFAULT FALSE,
+    smime_signature_valid BOOLEAN,
+    campaign_id UUID,
+    template_id VARCHAR,
+    template_variant VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Columns added to email_communications after its initial release
+ALTER TABLE email_communications
+    ADD COLUMN IF NOT EXISTS message_id VARCHAR,
+    ADD COLUMN IF NOT EXISTS in_reply_to VARCHAR,
+    ADD COLUMN IF NOT EXISTS message_references JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS classification VARCHAR,
+    ADD COLUMN IF NOT EXISTS recipient VARCHAR,
+    ADD COLUMN IF NOT EXISTS classification_confidence DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS delivery_attempts JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS content_hash VARCHAR,
+    ADD COLUMN IF NOT EXISTS smime_signed BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS smime_encrypted BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS smime_signature_valid BOOLEAN,
+    ADD COLUMN IF NOT EXISTS campaign_id UUID,
+    ADD COLUMN IF NOT EXISTS template_id VARCHAR,
+    ADD COLUMN IF NOT EXISTS template_variant VARCHAR;
+
+-- Create email_suppressions table
+CREATE TABLE IF NOT EXISTS email_suppressions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    supplier_id UUID REFERENCES suppliers(id),
+    email_address VARCHAR,
+    reason TEXT NOT NULL,
+    source VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (supplier_id IS NOT NULL OR email_address IS NOT NULL)
+);
+
+-- Create audit_entries table
+CREATE TABLE IF NOT EXISTS audit_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timestamp TIMESTAMPTZ NOT NULL,
+    action VARCHAR NOT NULL,
+    user_id UUID,
+    agent_id VARCHAR,
+    details JSONB NOT NULL,
+    source_document JSONB,
+    hash VARCHAR NOT NULL,
+    previous_hash VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for better performance
+CREATE INDEX IF NOT EXISTS idx_suppliers_name ON suppliers(name);
+CREATE INDEX IF NOT EXISTS idx_suppliers_parent_id ON suppliers(parent_id);
+CREATE INDEX IF NOT EXISTS idx_suppliers_lifecycle_status ON suppliers(lifecycle_status);
+CREATE INDEX IF NOT EXISTS idx_components_supplier_id ON components(supplier_id);
+CREATE INDEX IF NOT EXISTS idx_components_lifecycle_status ON components(lifecycle_status);
+CREATE INDEX IF NOT EXISTS idx_component_links_child_id ON component_links(child_id);
+CREATE INDEX IF NOT EXISTS idx_compliance_records_supplier_id ON compliance_records(supplier_id);
+CREATE INDEX IF NOT EXISTS idx_email_communications_supplier_id ON email_communications(supplier_id);
+CREATE INDEX IF NOT EXISTS idx_email_communications_message_id ON email_communications(message_id);
+CREATE INDEX IF NOT EXISTS idx_email_communications_content_hash ON email_communications(supplier_id, content_hash);
+CREATE INDEX IF NOT EXISTS idx_email_communications_campaign_id ON email_communications(campaign_id);
+CREATE INDEX IF NOT EXISTS idx_email_suppressions_email_address ON email_suppressions(email_address);
+CREATE INDEX IF NOT EXISTS idx_audit_entries_timestamp ON audit_entries(timestamp);
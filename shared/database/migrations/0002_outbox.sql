@@ -0,0 +1,15 @@
+-- Domain events written in the same transaction as the change they describe,
+-- waiting for the outbox relay to deliver them
+CREATE TABLE outbox (
+    sequence BIGSERIAL PRIMARY KEY,
+    event_id UUID NOT NULL UNIQUE,
+    event_type VARCHAR NOT NULL,
+    envelope JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_outbox_pending ON outbox(next_attempt_at, sequence) WHERE published_at IS NULL;
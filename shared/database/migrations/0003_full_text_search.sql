@@ -0,0 +1,34 @@
+-- Ranked full-text search over suppliers, components and emails.
+-- Each table gets a generated tsvector column with a GIN index for word
+-- matches, and identifiers get trigram indexes for fuzzy and partial
+-- matches (typos, part number fragments) that word search misses. The
+-- 'simple' configuration is used because names, part numbers and supplier
+-- correspondence are multilingual and should not be stemmed as English.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE suppliers
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(contact_info->>'contact_person', '')), 'B')
+    ) STORED;
+
+CREATE INDEX idx_suppliers_search ON suppliers USING GIN (search_vector);
+CREATE INDEX idx_suppliers_name_trgm ON suppliers USING GIN (name gin_trgm_ops);
+
+ALTER TABLE components
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(part_number, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'B')
+    ) STORED;
+
+CREATE INDEX idx_components_search ON components USING GIN (search_vector);
+CREATE INDEX idx_components_part_number_trgm ON components USING GIN (part_number gin_trgm_ops);
+
+-- Bodies are capped so a pasted report cannot exceed the tsvector size limit
+ALTER TABLE email_communications
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(subject, '')), 'A') ||
+        setweight(to_tsvector('simple', left(coalesce(body, ''), 100000)), 'B')
+    ) STORED;
+
+CREATE INDEX idx_email_communications_search ON email_communications USING GIN (search_vector);
@@ -0,0 +1,84 @@
+-- Partition audit_entries by month of the entry's timestamp (UTC), so
+-- queries over a date range only read the months they cover and old
+-- months can later be detached and archived whole. The primary key must
+-- include the partition key, so it becomes (id, timestamp).
+ALTER TABLE audit_entries RENAME TO audit_entries_unpartitioned;
+ALTER TABLE audit_entries_unpartitioned RENAME CONSTRAINT audit_entries_pkey TO audit_entries_unpartitioned_pkey;
+ALTER INDEX IF EXISTS idx_audit_entries_timestamp RENAME TO idx_audit_entries_unpartitioned_timestamp;
+
+CREATE TABLE audit_entries (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    timestamp TIMESTAMPTZ NOT NULL,
+    action VARCHAR NOT NULL,
+    user_id UUID,
+    agent_id VARCHAR,
+    details JSONB NOT NULL,
+    source_document JSONB,
+    hash VARCHAR NOT NULL,
+    previous_hash VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, timestamp)
+) PARTITION BY RANGE (timestamp);
+
+CREATE INDEX idx_audit_entries_timestamp ON audit_entries(timestamp);
+CREATE INDEX idx_audit_entries_entity ON audit_entries((details->>'entity_type'), (details->>'entity_id'));
+
+-- Catches entries for months whose partition does not exist yet, so an
+-- insert never fails because the maintenance job fell behind
+CREATE TABLE audit_entries_default PARTITION OF audit_entries DEFAULT;
+
+-- Create the partition for the month containing `month_start`, moving any
+-- of its entries out of the default partition. Returns the partition's
+-- name, or NULL if it already existed.
+CREATE OR REPLACE FUNCTION create_audit_entries_partition(month_start TIMESTAMPTZ) RETURNS TEXT
+LANGUAGE plpgsql AS $$
+DECLARE
+    month_utc TIMESTAMP := date_trunc('month', month_start AT TIME ZONE 'UTC');
+    start_at TIMESTAMPTZ := month_utc AT TIME ZONE 'UTC';
+    end_at TIMESTAMPTZ := (month_utc + INTERVAL '1 month') AT TIME ZONE 'UTC';
+    partition_name TEXT := 'audit_entries_' || to_char(month_utc, 'YYYY_MM');
+BEGIN
+    IF to_regclass(partition_name) IS NOT NULL THEN
+        RETURN NULL;
+    END IF;
+
+    EXECUTE format('CREATE TABLE %I (LIKE audit_entries INCLUDING DEFAULTS)', partition_name);
+    EXECUTE format(
+        'WITH moved AS (DELETE FROM audit_entries_default WHERE timestamp >= %L AND timestamp < %L RETURNING *)
+         INSERT INTO %I SELECT * FROM moved',
+        start_at, end_at, partition_name
+    );
+    EXECUTE format(
+        'ALTER TABLE audit_entries ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+        partition_name, start_at, end_at
+    );
+    RETURN partition_name;
+END;
+$$;
+
+-- Partitions for every month with existing entries, this month and the next three
+DO $$
+DECLARE
+    month TIMESTAMPTZ;
+BEGIN
+    FOR month IN
+        SELECT DISTINCT date_trunc('month', timestamp AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+        FROM audit_entries_unpartitioned
+        UNION
+        SELECT generate_series(
+            date_trunc('month', NOW() AT TIME ZONE 'UTC'),
+            date_trunc('month', NOW() AT TIME ZONE 'UTC') + INTERVAL '3 months',
+            INTERVAL '1 month'
+        ) AT TIME ZONE 'UTC'
+    LOOP
+        PERFORM create_audit_entries_partition(month);
+    END LOOP;
+END;
+$$;
+
+INSERT INTO audit_entries
+    (id, timestamp, action, user_id, agent_id, details, source_document, hash, previous_hash, created_at)
+SELECT id, timestamp, action, user_id, agent_id, details, source_document, hash, previous_hash, created_at
+FROM audit_entries_unpartitioned;
+
+DROP TABLE audit_entries_unpartitioned;
@@ -0,0 +1,42 @@
+-- Tenant isolation with row-level security.
+-- Client-owned tables get a tenant_id column, and a policy that lets a
+-- connection see and write only the rows of the tenant named by the
+-- `elementa.tenant_id` setting. The repositories set it per connection
+-- (see tenant.rs), so a query missing a tenant filter still cannot return
+-- another client's rows. Connections without a tenant, such as migrations
+-- and platform jobs, are unrestricted. tenant_id defaults to the setting,
+-- so inserts made in a tenant's scope are stamped without naming it.
+-- FORCE applies the policies to the table owner too, which the services
+-- connect as. Chemical substances are shared reference data and audit
+-- entries stay one hash chain, so neither is scoped.
+CREATE FUNCTION elementa_current_tenant() RETURNS TEXT
+    LANGUAGE sql STABLE
+    AS $$ SELECT NULLIF(current_setting('elementa.tenant_id', true), '') $$;
+
+DO $$
+DECLARE
+    scoped TEXT;
+BEGIN
+    FOREACH scoped IN ARRAY ARRAY[
+        'suppliers',
+        'components',
+        'component_links',
+        'compliance_records',
+        'workflows',
+        'agent_tasks',
+        'email_communications',
+        'email_suppressions'
+    ] LOOP
+        EXECUTE format('ALTER TABLE %I ADD COLUMN tenant_id VARCHAR DEFAULT elementa_current_tenant()', scoped);
+        EXECUTE format('CREATE INDEX %I ON %I(tenant_id)', 'idx_' || scoped || '_tenant', scoped);
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', scoped);
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', scoped);
+        EXECUTE format(
+            'CREATE POLICY tenant_isolation ON %I
+                USING (elementa_current_tenant() IS NULL OR tenant_id = elementa_current_tenant())
+                WITH CHECK (elementa_current_tenant() IS NULL OR tenant_id = elementa_current_tenant())',
+            scoped
+        );
+    END LOOP;
+END
+$$;
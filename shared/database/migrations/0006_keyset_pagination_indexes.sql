@@ -0,0 +1,10 @@
+-- Indexes for keyset pagination on (created_at, id), newest first, so each
+-- page is an index range scan however far into the listing it is.
+CREATE INDEX idx_suppliers_created_at_id ON suppliers(created_at DESC, id DESC)
+    WHERE lifecycle_status <> 'deleted';
+CREATE INDEX idx_compliance_records_supplier_created_at_id
+    ON compliance_records(supplier_id, created_at DESC, id DESC);
+CREATE INDEX idx_email_communications_supplier_created_at_id
+    ON email_communications(supplier_id, created_at DESC, id DESC);
+CREATE INDEX idx_audit_entries_entity_created_at_id
+    ON audit_entries((details->>'entity_type'), (details->>'entity_id'), created_at DESC, id DESC);
@@ -0,0 +1,14 @@
+-- Scheduling and claim columns for agent tasks, so several workflow
+-- executors can share the queue: an executor claims due tasks with
+-- FOR UPDATE SKIP LOCKED, holds them under a lease it renews with
+-- heartbeats, and a claim whose lease runs out is released for another
+-- executor to pick up.
+ALTER TABLE agent_tasks
+    ADD COLUMN scheduled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN claimed_by VARCHAR,
+    ADD COLUMN claimed_at TIMESTAMPTZ,
+    ADD COLUMN heartbeat_at TIMESTAMPTZ,
+    ADD COLUMN lease_expires_at TIMESTAMPTZ;
+
+CREATE INDEX idx_agent_tasks_due ON agent_tasks(scheduled_at) WHERE status = 'Queued';
+CREATE INDEX idx_agent_tasks_lease ON agent_tasks(lease_expires_at) WHERE status = 'InProgress';
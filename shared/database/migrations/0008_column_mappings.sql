@@ -0,0 +1,19 @@
+-- BOM column mappings a client saved for reuse, naming the header that
+-- holds each BOM field. Names are unique per tenant; mappings saved
+-- without a tenant share the '' slot.
+CREATE TABLE column_mappings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id VARCHAR DEFAULT elementa_current_tenant(),
+    name VARCHAR NOT NULL,
+    columns JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_column_mappings_name ON column_mappings((COALESCE(tenant_id, '')), name);
+
+ALTER TABLE column_mappings ENABLE ROW LEVEL SECURITY;
+ALTER TABLE column_mappings FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON column_mappings
+    USING (elementa_current_tenant() IS NULL OR tenant_id = elementa_current_tenant())
+    WITH CHECK (elementa_current_tenant() IS NULL OR tenant_id = elementa_current_tenant());
@@ -0,0 +1,13 @@
+-- Per-tenant From addresses and DKIM signing keys used by the email
+-- service. Rows are looked up by tenant explicitly, so the table has no
+-- row level security policy.
+CREATE TABLE sender_domains (
+    tenant_id VARCHAR PRIMARY KEY,
+    from_email VARCHAR NOT NULL,
+    from_name VARCHAR NOT NULL,
+    dkim_selector VARCHAR,
+    dkim_algorithm VARCHAR,
+    dkim_private_key TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
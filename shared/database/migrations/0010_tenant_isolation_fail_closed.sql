@@ -0,0 +1,37 @@
+-- Fail closed on connections without a tenant.
+-- The policies from 0005 and 0008 let a connection with no tenant set see
+-- every row, so any query outside a tenant scope reached all clients' data.
+-- Unscoped connections now see no client-owned rows, unless they opt into
+-- every tenant through the `elementa.all_tenants` setting, as migrations,
+-- platform services and jobs do (see tenant.rs). A connection with a tenant
+-- set is held to that tenant even if it also sets the bypass.
+CREATE FUNCTION elementa_all_tenants() RETURNS BOOLEAN
+    LANGUAGE sql STABLE
+    AS $$ SELECT elementa_current_tenant() IS NULL
+        AND coalesce(current_setting('elementa.all_tenants', true), '') = 'on' $$;
+
+DO $$
+DECLARE
+    scoped TEXT;
+BEGIN
+    FOREACH scoped IN ARRAY ARRAY[
+        'suppliers',
+        'components',
+        'component_links',
+        'compliance_records',
+        'workflows',
+        'agent_tasks',
+        'email_communications',
+        'email_suppressions',
+        'column_mappings'
+    ] LOOP
+        EXECUTE format('DROP POLICY tenant_isolation ON %I', scoped);
+        EXECUTE format(
+            'CREATE POLICY tenant_isolation ON %I
+                USING (elementa_all_tenants() OR tenant_id = elementa_current_tenant())
+                WITH CHECK (elementa_all_tenants() OR tenant_id = elementa_current_tenant())',
+            scoped
+        );
+    END LOOP;
+END
+$$;
@@ -0,0 +1,29 @@
+-- Emails and suppressions belong to their supplier's tenant.
+-- Inserts made in a tenant's scope are stamped with it by the column
+-- default from 0005, but the email service also records rows no request
+-- names a tenant for: inbound replies, bounces and unsubscribe clicks.
+-- Those used to be stored without a tenant, and since 0010 no tenant could
+-- see them. A row inserted without a tenant now takes its supplier's, and
+-- rows already stored that way are given it too.
+CREATE FUNCTION elementa_stamp_supplier_tenant() RETURNS TRIGGER
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    IF NEW.tenant_id IS NULL AND NEW.supplier_id IS NOT NULL THEN
+        SELECT tenant_id INTO NEW.tenant_id FROM suppliers WHERE id = NEW.supplier_id;
+    END IF;
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER stamp_supplier_tenant BEFORE INSERT ON email_communications
+    FOR EACH ROW EXECUTE FUNCTION elementa_stamp_supplier_tenant();
+CREATE TRIGGER stamp_supplier_tenant BEFORE INSERT ON email_suppressions
+    FOR EACH ROW EXECUTE FUNCTION elementa_stamp_supplier_tenant();
+
+UPDATE email_communications e SET tenant_id = s.tenant_id
+    FROM suppliers s
+    WHERE e.tenant_id IS NULL AND e.supplier_id = s.id;
+UPDATE email_suppressions e SET tenant_id = s.tenant_id
+    FROM suppliers s
+    WHERE e.tenant_id IS NULL AND e.supplier_id = s.id;
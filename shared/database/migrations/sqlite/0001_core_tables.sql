@@ -0,0 +1,63 @@
+-- Core tables for the SQLite backend. Each row keeps its record as JSON, in
+-- the same versioned shape the Postgres tables use, beside the columns
+-- queries filter and sort on. Timestamps are RFC 3339 UTC with microseconds,
+-- so they sort as text.
+
+CREATE TABLE suppliers (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    primary_email TEXT NOT NULL,
+    lifecycle_status TEXT NOT NULL,
+    record TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX idx_suppliers_name ON suppliers(name);
+CREATE INDEX idx_suppliers_primary_email ON suppliers(lower(primary_email));
+
+CREATE TABLE compliance_records (
+    id TEXT PRIMARY KEY,
+    supplier_id TEXT NOT NULL REFERENCES suppliers(id),
+    component_id TEXT NOT NULL,
+    submission_date TEXT NOT NULL,
+    has_pfas INTEGER NOT NULL,
+    has_certifications INTEGER NOT NULL,
+    record TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX idx_compliance_records_supplier ON compliance_records(supplier_id, submission_date);
+CREATE INDEX idx_compliance_records_pfas ON compliance_records(submission_date) WHERE has_pfas;
+
+CREATE TABLE workflows (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    deadline TEXT NOT NULL,
+    record TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX idx_workflows_status ON workflows(status, deadline);
+
+CREATE TABLE audit_entries (
+    id TEXT PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    previous_hash TEXT,
+    entry TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX idx_audit_entries_timestamp ON audit_entries(timestamp, created_at);
+CREATE INDEX idx_audit_entries_entity ON audit_entries(entity_type, entity_id, timestamp);
+
+-- Audit entries are never changed once written
+CREATE TRIGGER audit_entries_no_update BEFORE UPDATE ON audit_entries
+BEGIN
+    SELECT RAISE(ABORT, 'audit entries are immutable');
+END;
+CREATE TRIGGER audit_entries_no_delete BEFORE DELETE ON audit_entries
+BEGIN
+    SELECT RAISE(ABORT, 'audit entries are immutable');
+END;
@@ -0,0 +1,262 @@
+//! Audit interception at the store layer.
+//!
+//! `Audited` wraps a store and appends an audit entry for every create,
+//! update and delete that goes through it, so a service gets audit
+//! coverage by being handed wrapped stores rather than by remembering to
+//! log each write. Each entry names the entity type and ID, the action, and
+//! the top-level fields that changed, read by serializing the record before
+//! and after the write. Reads pass straight through.
+//!
+//! The entry is appended after the write commits. If the append fails the
+//! write stands and the error is returned, so the caller knows the trail is
+//! missing an entry.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use elementa_models::{
+    AuditAction, AuditEntry, ChangeType, ComplianceRecord, ComplianceRecordId, FieldChange, SupplierId,
+    SupplierRecord, WorkflowId, WorkflowInstance, WorkflowStatus,
+};
+
+use crate::{AuditStore, ComplianceStore, ExpiringCertification, SupplierStore, WorkflowStore};
+
+/// Fields every write touches, left out of diffs
+const BOOKKEEPING_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+/// A store whose writes are recorded in the audit trail
+pub struct Audited<S: ?Sized> {
+    inner: Arc<S>,
+    audit: Arc<dyn AuditStore>,
+    user_id: Option<Uuid>,
+    agent_id: Option<String>,
+}
+
+impl<S: ?Sized> Clone for Audited<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            audit: self.audit.clone(),
+            user_id: self.user_id,
+            agent_id: self.agent_id.clone(),
+        }
+    }
+}
+
+impl<S: ?Sized> Audited<S> {
+    pub fn new(inner: Arc<S>, audit: Arc<dyn AuditStore>) -> Self {
+        Self { inner, audit, user_id: None, agent_id: None }
+    }
+
+    /// Attribute the entries to a user or agent
+    pub fn with_actor(mut self, user_id: Option<Uuid>, agent_id: Option<String>) -> Self {
+        self.user_id = user_id;
+        self.agent_id = agent_id;
+        self
+    }
+
+    async fn record(
+        &self,
+        action: AuditAction,
+        entity_type: &str,
+        entity_id: Uuid,
+        operation: &str,
+        changes: Vec<FieldChange>,
+    ) -> Result<()> {
+        let metadata = HashMap::from([("operation".to_string(), operation.to_string())]);
+        let entry = AuditEntry::new(action, entity_type.to_string(), entity_id, self.user_id, self.agent_id.clone())
+            .with_details(changes, metadata);
+
+        self.audit.append(entry)
+            .await
+            .with_context(|| format!("Failed to audit {} of {} {}", operation, entity_type, entity_id))?;
+        Ok(())
+    }
+}
+
+/// The top-level fields that differ between two versions of a record;
+/// `None` on either side lists every field as created or deleted
+pub fn diff<T: Serialize>(old: Option<&T>, new: Option<&T>) -> Result<Vec<FieldChange>> {
+    let fields = |record: Option<&T>| -> Result<serde_json::Map<String, Value>> {
+        match record.map(serde_json::to_value).transpose()? {
+            Some(Value::Object(fields)) => Ok(fields),
+            Some(other) => Ok(serde_json::Map::from_iter([("value".to_string(), other)])),
+            None => Ok(serde_json::Map::new()),
+        }
+    };
+    let (old_fields, new_fields) = (fields(old)?, fields(new)?);
+
+    let mut names: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    Ok(names
+        .into_iter()
+        .filter(|name| !BOOKKEEPING_FIELDS.contains(&name.as_str()))
+        .filter_map(|name| {
+            let (old_value, new_value) = (old_fields.get(name), new_fields.get(name));
+            let change_type = match (old_value, new_value) {
+                (Some(a), Some(b)) if a == b => return None,
+                (Some(_), Some(_)) => ChangeType::Updated,
+                (None, _) => ChangeType::Created,
+                (_, None) => ChangeType::Deleted,
+            };
+            Some(FieldChange {
+                field_name: name.clone(),
+                old_value: old_value.map(render),
+                new_value: new_value.map(render),
+                change_type,
+            })
+        })
+        .collect())
+}
+
+/// A field value as written into a change: strings bare, anything else as JSON
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[async_trait]
+impl<S: SupplierStore + ?Sized> SupplierStore for Audited<S> {
+    async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>> {
+        self.inner.find_by_email(email).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
+        self.inner.find_all().await
+    }
+
+    async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>> {
+        self.inner.search_by_name(query).await
+    }
+
+    async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>> {
+        self.inner.find_similar_names(names).await
+    }
+
+    async fn create(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let created = self.inner.create(supplier).await?;
+        let changes = diff(None, Some(&created))?;
+        self.record(AuditAction::RecordCreated, "supplier", created.id.into(), "create", changes).await?;
+        Ok(created)
+    }
+
+    async fn update(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let before = self.inner.find_by_id(supplier.id).await?;
+        let updated = self.inner.update(supplier).await?;
+        let changes = diff(before.as_ref(), Some(&updated))?;
+        self.record(AuditAction::RecordUpdated, "supplier", updated.id.into(), "update", changes).await?;
+        Ok(updated)
+    }
+
+    /// Passes through: the supplier stores record a soft delete as a
+    /// lifecycle change in the same transaction
+    async fn delete(&self, id: SupplierId) -> Result<bool> {
+        self.inner.delete(id).await
+    }
+}
+
+#[async_trait]
+impl<S: ComplianceStore + ?Sized> ComplianceStore for Audited<S> {
+    async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>> {
+        self.inner.find_by_supplier(supplier_id).await
+    }
+
+    async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>> {
+        self.inner.find_with_pfas().await
+    }
+
+    async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>> {
+        self.inner.find_expiring_certifications(now, warning).await
+    }
+
+    async fn create(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let created = self.inner.create(record).await?;
+        let changes = diff(None, Some(&created))?;
+        self.record(AuditAction::ComplianceRecordCreated, "compliance_record", created.id.into(), "create", changes).await?;
+        Ok(created)
+    }
+
+    async fn update(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let before = self.inner.find_by_id(record.id).await?;
+        let updated = self.inner.update(record).await?;
+        let changes = diff(before.as_ref(), Some(&updated))?;
+        self.record(AuditAction::ComplianceRecordUpdated, "compliance_record", updated.id.into(), "update", changes).await?;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: ComplianceRecordId) -> Result<bool> {
+        let before = self.inner.find_by_id(id).await?;
+        if !self.inner.delete(id).await? {
+            return Ok(false);
+        }
+        let changes = diff(before.as_ref(), None)?;
+        self.record(AuditAction::RecordDeleted, "compliance_record", id.into(), "delete", changes).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl<S: WorkflowStore + ?Sized> WorkflowStore for Audited<S> {
+    async fn find_by_id(&self, id: WorkflowId) -> Result<Option<WorkflowInstance>> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_status(&self, status: WorkflowStatus) -> Result<Vec<WorkflowInstance>> {
+        self.inner.find_by_status(status).await
+    }
+
+    async fn find_active(&self) -> Result<Vec<WorkflowInstance>> {
+        self.inner.find_active().await
+    }
+
+    async fn create(&self, workflow: WorkflowInstance) -> Result<WorkflowInstance> {
+        let created = self.inner.create(workflow).await?;
+        let changes = diff(None, Some(&created))?;
+        self.record(AuditAction::WorkflowStarted, "workflow", created.id.into(), "create", changes).await?;
+        Ok(created)
+    }
+
+    async fn update_status(&self, id: WorkflowId, status: WorkflowStatus) -> Result<bool> {
+        let before = self.inner.find_by_id(id).await?;
+        let action = match status {
+            WorkflowStatus::Completed => AuditAction::WorkflowCompleted,
+            _ => AuditAction::RecordUpdated,
+        };
+        if !self.inner.update_status(id, status).await? {
+            return Ok(false);
+        }
+        let after = self.inner.find_by_id(id).await?;
+        let changes = diff(before.as_ref(), after.as_ref())?;
+        self.record(action, "workflow", id.into(), "update_status", changes).await?;
+        Ok(true)
+    }
+
+    async fn delete(&self, id: WorkflowId) -> Result<bool> {
+        let before = self.inner.find_by_id(id).await?;
+        if !self.inner.delete(id).await? {
+            return Ok(false);
+        }
+        let changes = diff(before.as_ref(), None)?;
+        self.record(AuditAction::RecordDeleted, "workflow", id.into(), "delete", changes).await?;
+        Ok(true)
+    }
+}
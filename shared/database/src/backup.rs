@@ -0,0 +1,402 @@
+//! Encrypted tenant backup and restore.
+//!
+//! A backup holds one tenant's data: the rows of every tenant-scoped table,
+//! the audit entries about those rows, and the documents received from the
+//! tenant's suppliers. The rows are read in a single repeatable-read
+//! transaction, so they are a consistent snapshot even while the services
+//! keep writing; documents are read from GridFS after it, and as their
+//! content never changes once uploaded, only documents uploaded during the
+//! export can be missing.
+//!
+//! The archive is a ZIP (a manifest, one JSON file per table, and each
+//! document's metadata and content) sealed with AES-256-GCM under a 32-byte
+//! key, so it can be stored off-site and any tampering fails the restore.
+//! It is built in memory. Restore goes into a fresh environment at the same
+//! schema version: the rows are inserted in one transaction, which fails if
+//! the tenant already has suppliers, and the documents are stored after it
+//! commits. Restored audit entries keep their original hashes, so they
+//! verify against each other but not as a continuation of the target's
+//! chain.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::{Cursor, Read, Write};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::io::AsyncReadExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgConnection;
+use uuid::Uuid;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::migrations::schema_version;
+use crate::tenant::{begin_for_tenant, scope_transaction};
+use crate::{DocumentMetadata, DocumentRepository, MongoDatabase, PostgresPool, SecretSource, TenantId};
+
+/// First bytes of an archive, authenticated with the content
+const MAGIC: &[u8; 8] = b"ELEMBAK1";
+const NONCE_LEN: usize = 12;
+/// Archive layout version, bumped when the ZIP contents change shape
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Tenant-scoped tables, parents before the tables referencing them
+const TENANT_TABLES: &[&str] = &[
+    "suppliers",
+    "components",
+    "component_links",
+    "compliance_records",
+    "workflows",
+    "agent_tasks",
+    "email_communications",
+    "email_suppressions",
+    "column_mappings",
+];
+const AUDIT_TABLE: &str = "audit_entries";
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// AES-256 key an archive is sealed with
+#[derive(Clone)]
+pub struct BackupKey([u8; 32]);
+
+impl BackupKey {
+    /// A new random key
+    pub fn generate() -> Self {
+        Self(Aes256Gcm::generate_key(&mut OsRng).into())
+    }
+
+    /// A key written as 64 hex digits
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let bytes = hex::decode(hex.trim()).context("Backup key is not hex")?;
+        let key = bytes.try_into().map_err(|_| anyhow!("Backup key must be 32 bytes (64 hex digits)"))?;
+        Ok(Self(key))
+    }
+
+    /// Read a hex key from a secret, e.g. `file:/run/secrets/backup-key`
+    pub async fn read(source: &SecretSource) -> Result<Self> {
+        Self::from_hex(&source.read().await?)
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+}
+
+impl fmt::Debug for BackupKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BackupKey(..)")
+    }
+}
+
+/// Encrypt an archive's contents under `key`
+pub fn seal(key: &BackupKey, contents: &[u8]) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = key.cipher()
+        .encrypt(&nonce, Payload { msg: contents, aad: MAGIC })
+        .map_err(|_| anyhow!("Failed to encrypt backup"))?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a sealed archive, failing if it was sealed under another key or altered
+pub fn open(key: &BackupKey, sealed: &[u8]) -> Result<Vec<u8>> {
+    let Some(rest) = sealed.strip_prefix(MAGIC.as_slice()) else {
+        bail!("Not an Elementa backup archive");
+    };
+    if rest.len() < NONCE_LEN {
+        bail!("Backup archive is truncated");
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    key.cipher()
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: MAGIC })
+        .map_err(|_| anyhow!("Backup archive cannot be decrypted with this key, or has been altered"))
+}
+
+/// What an archive holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub tenant: TenantId,
+    /// Latest migration applied to the source database
+    pub schema_version: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    /// Rows per table
+    pub tables: BTreeMap<String, usize>,
+    pub documents: usize,
+}
+
+/// The contents of an archive before it is sealed
+struct Snapshot {
+    manifest: BackupManifest,
+    tables: Vec<(String, Vec<Value>)>,
+    documents: Vec<(DocumentMetadata, Vec<u8>)>,
+}
+
+impl Snapshot {
+    fn to_zip(&self) -> Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut add = |name: String, contents: &[u8]| -> Result<()> {
+            zip.start_file(name, options)?;
+            zip.write_all(contents)?;
+            Ok(())
+        };
+
+        add(MANIFEST_FILE.to_string(), &serde_json::to_vec_pretty(&self.manifest)?)?;
+        for (table, rows) in &self.tables {
+            add(format!("tables/{}.json", table), &serde_json::to_vec(rows)?)?;
+        }
+        for (metadata, content) in &self.documents {
+            add(format!("documents/{}.json", metadata.id), &serde_json::to_vec(metadata)?)?;
+            add(format!("documents/{}", metadata.id), content)?;
+        }
+
+        Ok(zip.finish().context("Failed to write backup archive")?.into_inner())
+    }
+
+    fn from_zip(contents: Vec<u8>) -> Result<Self> {
+        let mut zip = ZipArchive::new(Cursor::new(contents)).context("Backup archive is not a valid ZIP")?;
+
+        let manifest: BackupManifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_FILE)?).context("Malformed backup manifest")?;
+        if manifest.format_version != FORMAT_VERSION {
+            bail!("Backup archive format {} is not supported; expected {}", manifest.format_version, FORMAT_VERSION);
+        }
+
+        let mut tables = Vec::new();
+        for table in TENANT_TABLES.iter().chain([&AUDIT_TABLE]) {
+            let rows = serde_json::from_slice(&read_entry(&mut zip, &format!("tables/{}.json", table))?)
+                .with_context(|| format!("Malformed {} in backup archive", table))?;
+            tables.push((table.to_string(), rows));
+        }
+
+        let names: Vec<String> = zip.file_names()
+            .filter(|name| name.starts_with("documents/") && name.ends_with(".json"))
+            .map(String::from)
+            .collect();
+        let mut documents = Vec::new();
+        for name in names {
+            let metadata: DocumentMetadata = serde_json::from_slice(&read_entry(&mut zip, &name)?)
+                .context("Malformed document metadata in backup")?;
+            let content = read_entry(&mut zip, &format!("documents/{}", metadata.id))?;
+            documents.push((metadata, content));
+        }
+
+        Ok(Self { manifest, tables, documents })
+    }
+}
+
+fn read_entry(zip: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Result<Vec<u8>> {
+    let mut file = zip.by_name(name).with_context(|| format!("Backup archive has no {}", name))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).with_context(|| format!("Failed to read {} from backup archive", name))?;
+    Ok(contents)
+}
+
+/// Exports a tenant's data to a sealed archive and restores it
+pub struct Backup {
+    pool: PostgresPool,
+    documents: Option<DocumentRepository>,
+}
+
+impl Backup {
+    pub fn new(pool: PostgresPool) -> Self {
+        Self { pool, documents: None }
+    }
+
+    /// Include documents, which are left out without a document store
+    pub fn with_documents(mut self, database: &MongoDatabase) -> Self {
+        self.documents = Some(DocumentRepository::new(database));
+        self
+    }
+
+    /// Export `tenant`'s data, sealed under `key`
+    pub async fn export(&self, tenant: &TenantId, key: &BackupKey) -> Result<(BackupManifest, Vec<u8>)> {
+        let schema_version = schema_version(&self.pool).await?;
+        let mut tx = self.pool.begin().await.context("Failed to begin backup transaction")?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to start backup snapshot")?;
+        scope_transaction(&mut tx, tenant).await?;
+
+        let mut tables = Vec::new();
+        let mut entity_ids = HashSet::new();
+        for table in TENANT_TABLES {
+            let rows = export_table(&mut tx, table).await?;
+            entity_ids.extend(rows.iter().filter_map(|row| row.get("id")?.as_str().map(String::from)));
+            tables.push((table.to_string(), rows));
+        }
+
+        // Audit entries are not tenant-scoped; take those about the exported rows
+        let audit: Value = sqlx::query_scalar(
+            r#"
+            SELECT coalesce(jsonb_agg(to_jsonb(a) ORDER BY a.timestamp, a.created_at), '[]'::jsonb)
+            FROM audit_entries a
+            WHERE a.details->>'entity_id' = ANY($1)
+            "#
+        )
+        .bind(entity_ids.into_iter().collect::<Vec<_>>())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to export audit entries")?;
+        tables.push((AUDIT_TABLE.to_string(), rows_of(audit)));
+
+        tx.commit().await.context("Failed to end backup snapshot")?;
+
+        let mut documents = Vec::new();
+        if let Some(store) = &self.documents {
+            let suppliers = tables.iter()
+                .find(|(table, _)| table == "suppliers")
+                .map(|(_, rows)| rows.as_slice())
+                .unwrap_or_default();
+            for supplier_id in suppliers.iter().filter_map(|row| row.get("id")?.as_str()?.parse::<Uuid>().ok()) {
+                for metadata in store.find_by_supplier(supplier_id).await? {
+                    let Some((metadata, mut stream)) = store.open_download(metadata.id).await? else {
+                        continue;
+                    };
+                    let mut content = Vec::with_capacity(metadata.size_bytes as usize);
+                    stream.read_to_end(&mut content)
+                        .await
+                        .with_context(|| format!("Failed to read document {}", metadata.id))?;
+                    documents.push((metadata, content));
+                }
+            }
+        }
+
+        let manifest = BackupManifest {
+            format_version: FORMAT_VERSION,
+            tenant: tenant.clone(),
+            schema_version,
+            created_at: Utc::now(),
+            tables: tables.iter().map(|(table, rows)| (table.clone(), rows.len())).collect(),
+            documents: documents.len(),
+        };
+        let snapshot = Snapshot { manifest, tables, documents };
+        let sealed = seal(key, &snapshot.to_zip()?)?;
+
+        tracing::info!(tenant = %tenant, documents = snapshot.manifest.documents, bytes = sealed.len(), "Exported tenant backup");
+        Ok((snapshot.manifest, sealed))
+    }
+
+    /// Restore a sealed archive into this environment, which must be at
+    /// the archive's schema version and hold no data for its tenant
+    pub async fn restore(&self, key: &BackupKey, sealed: &[u8]) -> Result<BackupManifest> {
+        let snapshot = Snapshot::from_zip(open(key, sealed)?)?;
+        let manifest = &snapshot.manifest;
+
+        let schema_version = schema_version(&self.pool).await?;
+        if schema_version != manifest.schema_version {
+            bail!(
+                "Backup was taken at schema version {:?} but this database is at {:?}; restore into an environment at the same version",
+                manifest.schema_version,
+                schema_version
+            );
+        }
+        if !snapshot.documents.is_empty() && self.documents.is_none() {
+            bail!("Backup holds {} documents but no document store is configured", snapshot.documents.len());
+        }
+
+        let mut tx = begin_for_tenant(&self.pool, &manifest.tenant).await?;
+        let has_data: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM suppliers)")
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to check for existing tenant data")?;
+        if has_data {
+            bail!("Tenant {} already has data; restore into a fresh environment", manifest.tenant);
+        }
+
+        for (table, rows) in &snapshot.tables {
+            restore_table(&mut tx, table, rows).await?;
+        }
+        tx.commit().await.context("Failed to commit restored tenant data")?;
+
+        if let Some(store) = &self.documents {
+            for (metadata, content) in &snapshot.documents {
+                let id = metadata.id;
+                store.restore(metadata.clone(), content.as_slice())
+                    .await
+                    .with_context(|| format!("Failed to restore document {}", id))?;
+            }
+        }
+
+        tracing::info!(tenant = %manifest.tenant, documents = manifest.documents, "Restored tenant backup");
+        Ok(snapshot.manifest)
+    }
+}
+
+/// Columns Postgres computes, which are neither exported nor inserted
+async fn generated_columns(conn: &mut PgConnection, table: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1 AND is_generated <> 'NEVER'
+        "#
+    )
+    .bind(table)
+    .fetch_all(conn)
+    .await
+    .with_context(|| format!("Failed to read generated columns of {}", table))
+}
+
+async fn export_table(conn: &mut PgConnection, table: &str) -> Result<Vec<Value>> {
+    let generated = generated_columns(&mut *conn, table).await?;
+    let rows: Value = sqlx::query_scalar(&format!(
+        "SELECT coalesce(jsonb_agg(to_jsonb(t) - $1::text[]), '[]'::jsonb) FROM {} t",
+        table
+    ))
+    .bind(generated)
+    .fetch_one(conn)
+    .await
+    .with_context(|| format!("Failed to export {}", table))?;
+
+    Ok(rows_of(rows))
+}
+
+async fn restore_table(conn: &mut PgConnection, table: &str, rows: &[Value]) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let columns: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1 AND is_generated = 'NEVER'
+        ORDER BY ordinal_position
+        "#
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to read columns of {}", table))?;
+    let columns = columns.iter().map(|c| format!("\"{}\"", c.replace('"', "\"\""))).collect::<Vec<_>>().join(", ");
+
+    sqlx::query(&format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM jsonb_populate_recordset(NULL::{table}, $1)"
+    ))
+    .bind(Value::Array(rows.to_vec()))
+    .execute(conn)
+    .await
+    .with_context(|| format!("Failed to restore {}", table))?;
+
+    Ok(())
+}
+
+fn rows_of(rows: Value) -> Vec<Value> {
+    match rows {
+        Value::Array(rows) => rows,
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,59 @@
+//! Export a tenant's data to an encrypted archive, or restore one.
+//!
+//! ```text
+//! elementa-backup export <tenant> <archive>
+//! elementa-backup restore <archive>
+//! elementa-backup keygen
+//! ```
+//!
+//! Reads `DATABASE_URL`, `MONGODB_URL` to include documents, and
+//! `BACKUP_KEY`, the archive key as 64 hex digits or a secret holding them,
+//! e.g. `file:/run/secrets/backup-key`.
+
+use anyhow::{bail, Context, Result};
+
+use elementa_database::backup::{Backup, BackupKey};
+use elementa_database::{create_mongo_client, create_postgres_pools, get_database, SecretSource, TenantAccess, TenantId};
+
+const USAGE: &str = "usage: elementa-backup export <tenant> <archive> | restore <archive> | keygen";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args == ["keygen"] {
+        println!("{}", BackupKey::generate().to_hex());
+        return Ok(());
+    }
+
+    let key_source: SecretSource = std::env::var("BACKUP_KEY").context("BACKUP_KEY is not set")?.parse()?;
+    let key = BackupKey::read(&key_source).await?;
+    let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?;
+    let pools = create_postgres_pools(&database_url, None, 5, TenantAccess::Scoped).await?;
+
+    let mut backup = Backup::new(pools.primary().clone());
+    if let Ok(mongodb_url) = std::env::var("MONGODB_URL") {
+        let client = create_mongo_client(&mongodb_url).await?;
+        let database = client.default_database().unwrap_or_else(|| get_database(&client, "elementa"));
+        backup = backup.with_documents(&database);
+    }
+
+    match args.as_slice() {
+        ["export", tenant, path] => {
+            let (manifest, archive) = backup.export(&TenantId::new(*tenant)?, &key).await?;
+            tokio::fs::write(path, archive).await.with_context(|| format!("Failed to write {}", path))?;
+            println!("Exported {}: {:#?}", path, manifest);
+        }
+        ["restore", path] => {
+            let archive = tokio::fs::read(path).await.with_context(|| format!("Failed to read {}", path))?;
+            let manifest = backup.restore(&key, &archive).await?;
+            println!("Restored {}: {:#?}", path, manifest);
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! Load the demo data set into a development database.
+//!
+//! Reads `DATABASE_URL`, and `MONGODB_URL` to store supplier declarations
+//! in GridFS as well; `SEED_TENANT` loads the data for that tenant.
+
+use anyhow::{Context, Result};
+
+use elementa_database::seed::Seeder;
+use elementa_database::{create_mongo_client, create_postgres_pools, get_database, migrations, tenant, TenantAccess, TenantId};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?;
+    let pools = create_postgres_pools(&database_url, None, 5, TenantAccess::AllTenants).await?;
+    migrations::run_postgres_migrations(pools.primary()).await?;
+
+    let mut seeder = Seeder::new(pools);
+    if let Ok(mongodb_url) = std::env::var("MONGODB_URL") {
+        let client = create_mongo_client(&mongodb_url).await?;
+        let database = client.default_database().unwrap_or_else(|| get_database(&client, "elementa"));
+        seeder = seeder.with_documents(&database);
+    }
+
+    let report = match std::env::var("SEED_TENANT") {
+        Ok(tenant_id) => tenant::scope(TenantId::new(tenant_id)?, seeder.run()).await?,
+        Err(_) => seeder.run().await?,
+    };
+    if report.already_seeded {
+        println!("Demo data is already loaded");
+    } else {
+        println!("Loaded demo data: {:#?}", report);
+    }
+    Ok(())
+}
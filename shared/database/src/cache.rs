@@ -0,0 +1,262 @@
+//! Cache-aside decorator for repositories.
+//!
+//! `Cached<R>` serves a repository's by-key reads from Redis, loading from
+//! the repository on a miss and caching the result for a TTL. Writes made
+//! through the decorator delete the keys they touch, so the next read loads
+//! the entity fresh; writes made on `inner()` directly must call
+//! `invalidate`. Redis being down never fails a read or write, it only means
+//! the read goes to Postgres. Entities of tenant-scoped tables are cached
+//! per tenant, so one tenant's cached rows are never served to another. A
+//! write clears the writing tenant's copy and the one cached for reads
+//! outside a tenant scope; a write outside a tenant scope clears every
+//! tenant's copy.
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use elementa_models::{ChemicalSubstance, LifecycleStatus, SupplierId, SupplierRecord};
+
+use crate::tenant::current_tenant;
+use crate::{ChemicalRepository, RedisPool, SupplierMerge, SupplierRepository};
+
+/// Prefix shared by every cached entity's key
+const KEY_PREFIX: &str = "elementa:cache";
+/// Tenant part of the keys of entities read outside a tenant scope; tenant IDs never contain it
+const UNSCOPED: &str = "*";
+
+/// A repository whose entities can be cached by key
+#[async_trait]
+pub trait CacheSource: Send + Sync {
+    type Key: Display + Sync + ?Sized;
+    type Entity: Serialize + DeserializeOwned + Send + Sync;
+
+    /// Names the repository's keys in Redis, e.g. `supplier`
+    const NAMESPACE: &'static str;
+    /// Whether the entities are tenant-owned rather than shared reference data
+    const TENANT_SCOPED: bool = true;
+
+    /// Load an entity from the repository
+    async fn load(&self, key: &Self::Key) -> Result<Option<Self::Entity>>;
+}
+
+#[async_trait]
+impl CacheSource for SupplierRepository {
+    type Key = SupplierId;
+    type Entity = SupplierRecord;
+
+    const NAMESPACE: &'static str = "supplier";
+
+    async fn load(&self, id: &SupplierId) -> Result<Option<SupplierRecord>> {
+        self.find_by_id(*id).await
+    }
+}
+
+#[async_trait]
+impl CacheSource for ChemicalRepository {
+    type Key = str;
+    type Entity = ChemicalSubstance;
+
+    const NAMESPACE: &'static str = "chemical";
+    const TENANT_SCOPED: bool = false;
+
+    async fn load(&self, cas_number: &str) -> Result<Option<ChemicalSubstance>> {
+        self.find_by_cas(cas_number).await
+    }
+}
+
+/// A repository with a Redis cache in front of its by-key reads
+pub struct Cached<R> {
+    inner: R,
+    redis: RedisPool,
+    ttl: Duration,
+}
+
+impl<R> Cached<R> {
+    pub fn new(inner: R, redis: RedisPool) -> Self {
+        Self {
+            inner,
+            redis,
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The wrapped repository, for queries the cache does not cover
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: CacheSource> Cached<R> {
+    fn cache_key(key: &R::Key) -> String {
+        match current_tenant() {
+            Some(tenant) => Self::tenant_key(tenant.as_str(), key),
+            None => Self::tenant_key(UNSCOPED, key),
+        }
+    }
+
+    fn tenant_key(tenant: &str, key: &R::Key) -> String {
+        if !R::TENANT_SCOPED {
+            return format!("{}:{}:{}", KEY_PREFIX, R::NAMESPACE, key);
+        }
+        format!("{}:{}:{}:{}", KEY_PREFIX, tenant, R::NAMESPACE, key)
+    }
+
+    /// Keys a write to the entity leaves stale
+    async fn stale_keys(&self, key: &R::Key) -> redis::RedisResult<Vec<String>> {
+        if !R::TENANT_SCOPED {
+            return Ok(vec![Self::tenant_key(UNSCOPED, key)]);
+        }
+        if let Some(tenant) = current_tenant() {
+            return Ok(vec![Self::tenant_key(tenant.as_str(), key), Self::tenant_key(UNSCOPED, key)]);
+        }
+        // The entity's tenant is not known, so every tenant's copy goes
+        let mut redis = self.redis.clone();
+        let pattern = format!("{}:*:{}:{}", KEY_PREFIX, R::NAMESPACE, key);
+        let mut keys: Vec<String> = Vec::new();
+        let mut found = redis.scan_match::<_, String>(&pattern).await?;
+        while let Some(key) = found.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+
+    /// Read an entity from the cache, or from the repository on a miss.
+    /// Missing entities are not cached, so one created later is found at once.
+    pub async fn get(&self, key: &R::Key) -> Result<Option<R::Entity>> {
+        let cache_key = Self::cache_key(key);
+        let mut redis = self.redis.clone();
+
+        match redis.get::<_, Option<String>>(&cache_key).await {
+            Ok(Some(json)) => match serde_json::from_str(&json) {
+                Ok(entity) => return Ok(Some(entity)),
+                // Cached by a build with a different entity shape; reload it
+                Err(e) => tracing::debug!("Discarding unreadable cache entry {}: {}", cache_key, e),
+            },
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Cache read of {} failed: {}", cache_key, e),
+        }
+
+        let entity = self.inner.load(key).await?;
+        if let Some(entity) = &entity {
+            let json = serde_json::to_string(entity)?;
+            let set: redis::RedisResult<()> = redis.set_ex(&cache_key, json, self.ttl.as_secs().max(1)).await;
+            if let Err(e) = set {
+                tracing::warn!("Cache write of {} failed: {}", cache_key, e);
+            }
+        }
+        Ok(entity)
+    }
+
+    /// Drop an entity from the cache after it changed
+    pub async fn invalidate(&self, key: &R::Key) {
+        let deleted = match self.stale_keys(key).await {
+            Ok(keys) if keys.is_empty() => Ok(()),
+            Ok(keys) => self.redis.clone().del(&keys).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = deleted {
+            tracing::warn!("Cache invalidation of {} {} failed: {}", R::NAMESPACE, key, e);
+        }
+    }
+}
+
+// Writes that invalidate the suppliers they change
+impl Cached<SupplierRepository> {
+    pub async fn create(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let supplier = self.inner.create(supplier).await?;
+        self.invalidate(&supplier.id).await;
+        Ok(supplier)
+    }
+
+    pub async fn update(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let id = supplier.id;
+        let result = self.inner.update(supplier).await;
+        self.invalidate(&id).await;
+        result
+    }
+
+    pub async fn delete(&self, id: SupplierId) -> Result<bool> {
+        let result = self.inner.delete(id).await;
+        self.invalidate(&id).await;
+        result
+    }
+
+    pub async fn change_lifecycle_status(
+        &self,
+        id: SupplierId,
+        status: LifecycleStatus,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<SupplierRecord>> {
+        let result = self.inner.change_lifecycle_status(id, status, user_id).await;
+        self.invalidate(&id).await;
+        result
+    }
+
+    pub async fn archive(&self, id: SupplierId, user_id: Option<Uuid>) -> Result<Option<SupplierRecord>> {
+        let result = self.inner.archive(id, user_id).await;
+        self.invalidate(&id).await;
+        result
+    }
+
+    pub async fn restore(&self, id: SupplierId, user_id: Option<Uuid>) -> Result<Option<SupplierRecord>> {
+        let result = self.inner.restore(id, user_id).await;
+        self.invalidate(&id).await;
+        result
+    }
+
+    pub async fn set_parent(&self, supplier_id: SupplierId, parent_id: Option<SupplierId>) -> Result<Option<SupplierRecord>> {
+        let result = self.inner.set_parent(supplier_id, parent_id).await;
+        self.invalidate(&supplier_id).await;
+        result
+    }
+
+    /// Merge a duplicate supplier, invalidating both suppliers and the
+    /// subsidiaries that moved to the survivor
+    pub async fn merge(&self, survivor_id: SupplierId, duplicate_id: SupplierId, user_id: Option<Uuid>) -> Result<SupplierMerge> {
+        let result = self.inner.merge(survivor_id, duplicate_id, user_id).await;
+        self.invalidate(&survivor_id).await;
+        self.invalidate(&duplicate_id).await;
+        if result.as_ref().is_ok_and(|merge| merge.subsidiaries_moved > 0) {
+            match self.inner.find_subsidiaries(survivor_id).await {
+                Ok(subsidiaries) => {
+                    for subsidiary in subsidiaries {
+                        self.invalidate(&subsidiary.id).await;
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to invalidate subsidiaries of supplier {}: {:#}", survivor_id, e),
+            }
+        }
+        result
+    }
+}
+
+// Writes that invalidate the chemicals they change
+impl Cached<ChemicalRepository> {
+    pub async fn upsert(&self, chemical: ChemicalSubstance) -> Result<ChemicalSubstance> {
+        let cas_number = chemical.cas_number.clone();
+        let result = self.inner.upsert(chemical).await;
+        self.invalidate(&cas_number).await;
+        result
+    }
+
+    pub async fn bulk_upsert(&self, chemicals: Vec<ChemicalSubstance>) -> Result<usize> {
+        let cas_numbers: Vec<String> = chemicals.iter().map(|c| c.cas_number.clone()).collect();
+        let result = self.inner.bulk_upsert(chemicals).await;
+        for cas_number in &cas_numbers {
+            self.invalidate(cas_number).await;
+        }
+        result
+    }
+}
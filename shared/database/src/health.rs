@@ -0,0 +1,262 @@
+//! Connection pool health monitoring.
+//!
+//! `HealthMonitor` pings each pool on an interval and keeps the latest
+//! result per pool, so a transient outage shows up as a named, timestamped
+//! status rather than as opaque query errors. After a failed check the pool
+//! is probed again with exponential backoff, so recovery is noticed within
+//! seconds without hammering a database that is down. The pools reconnect by
+//! themselves: sqlx replaces broken connections on acquire and the Redis
+//! connection manager reconnects after a dropped connection; the monitor
+//! tracks when that has happened. Results are exported as Prometheus gauges
+//! alongside the pool's connection counts.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use prometheus::{register_int_counter_vec, register_int_gauge_vec, register_gauge_vec, GaugeVec, IntCounterVec, IntGaugeVec};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::{MongoClient, PostgresPool, PostgresPools, RedisPool};
+
+/// Longest a single ping may take before the pool counts as unhealthy
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// First retry delay after a failed check; doubles up to the check interval
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Connection counts of a pool; `in_use` at `max` means callers are queueing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoolConnections {
+    pub open: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub max: u32,
+}
+
+impl PoolConnections {
+    pub fn of(pool: &PostgresPool) -> Self {
+        let open = pool.size();
+        let idle = pool.num_idle() as u32;
+        Self {
+            open,
+            idle,
+            in_use: open.saturating_sub(idle),
+            max: pool.options().get_max_connections(),
+        }
+    }
+
+    /// Share of the pool's capacity in use, from 0 to 1
+    pub fn saturation(&self) -> f64 {
+        if self.max == 0 {
+            return 0.0;
+        }
+        f64::from(self.in_use) / f64::from(self.max)
+    }
+}
+
+/// Latest health check result for one pool
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolHealth {
+    pub pool: String,
+    pub healthy: bool,
+    pub message: String,
+    pub latency_ms: u64,
+    pub checked_at: DateTime<Utc>,
+    /// When the pool last answered; `None` if it never has since startup
+    pub last_healthy_at: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connections: Option<PoolConnections>,
+}
+
+impl PoolHealth {
+    pub fn status(&self) -> &'static str {
+        if self.healthy { "healthy" } else { "unhealthy" }
+    }
+}
+
+/// Periodically checks the Postgres, MongoDB and Redis pools
+pub struct HealthMonitor {
+    postgres: PostgresPools,
+    mongo: MongoClient,
+    redis: RedisPool,
+    interval: Duration,
+    statuses: RwLock<BTreeMap<String, PoolHealth>>,
+}
+
+impl HealthMonitor {
+    pub fn new(postgres: PostgresPools, mongo: MongoClient, redis: RedisPool) -> Self {
+        Self {
+            postgres,
+            mongo,
+            redis,
+            interval: Duration::from_secs(15),
+            statuses: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Check every pool now, recording and returning the results
+    pub async fn check_all(&self) -> Vec<PoolHealth> {
+        let mut redis = self.redis.clone();
+        let primary = self.postgres.primary().clone();
+        let (postgres, mongo, redis) = tokio::join!(
+            timed(crate::postgres_health_check(&primary)),
+            timed(crate::mongo_health_check(&self.mongo)),
+            timed(crate::redis_health_check(&mut redis)),
+        );
+
+        let mut results = vec![
+            self.record("postgres", postgres, Some(PoolConnections::of(&primary))).await,
+            self.record("mongodb", mongo, None).await,
+            self.record("redis", redis, None).await,
+        ];
+        if let Some(replica) = self.postgres.replica() {
+            let check = timed(crate::postgres_health_check(replica)).await;
+            results.push(self.record("postgres_replica", check, Some(PoolConnections::of(replica))).await);
+        }
+        results
+    }
+
+    /// Results of the most recent checks, by pool name
+    pub async fn statuses(&self) -> Vec<PoolHealth> {
+        self.statuses.read().await.values().cloned().collect()
+    }
+
+    /// Check the pools until the task is aborted: every interval while they
+    /// are healthy, backing off from a second up to the interval while any is not
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+            loop {
+                let results = self.check_all().await;
+                let delay = if results.iter().all(|r| r.healthy) {
+                    backoff = MIN_BACKOFF;
+                    self.interval
+                } else {
+                    let delay = backoff.min(self.interval);
+                    backoff = (backoff * 2).min(self.interval);
+                    delay
+                };
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+
+    async fn record(&self, pool: &str, check: (Result<()>, Duration), connections: Option<PoolConnections>) -> PoolHealth {
+        let (result, latency) = check;
+        let now = Utc::now();
+        let mut statuses = self.statuses.write().await;
+        let previous = statuses.get(pool);
+
+        let health = PoolHealth {
+            pool: pool.to_string(),
+            healthy: result.is_ok(),
+            message: match &result {
+                Ok(()) => "Connected".to_string(),
+                Err(e) => format!("{:#}", e),
+            },
+            latency_ms: latency.as_millis() as u64,
+            checked_at: now,
+            last_healthy_at: if result.is_ok() { Some(now) } else { previous.and_then(|p| p.last_healthy_at) },
+            consecutive_failures: match &result {
+                Ok(()) => 0,
+                Err(_) => previous.map_or(0, |p| p.consecutive_failures) + 1,
+            },
+            connections,
+        };
+
+        match (previous.map(|p| p.healthy), health.healthy) {
+            (Some(true) | None, false) => tracing::warn!("{} is unavailable: {}", pool, health.message),
+            (Some(false), true) => tracing::info!(
+                "{} recovered after {} failed checks",
+                pool,
+                previous.map_or(0, |p| p.consecutive_failures),
+            ),
+            _ => {}
+        }
+
+        export_metrics(&health);
+        statuses.insert(pool.to_string(), health.clone());
+        health
+    }
+}
+
+/// Run a ping with the check timeout, returning its result and how long it took
+async fn timed(check: impl Future<Output = Result<()>>) -> (Result<()>, Duration) {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow::anyhow!("No response within {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    (result, started.elapsed())
+}
+
+struct PoolMetrics {
+    up: IntGaugeVec,
+    latency: GaugeVec,
+    failures: IntCounterVec,
+    connections: IntGaugeVec,
+}
+
+/// Pool gauges in the default Prometheus registry, which the gateway's `/metrics` serves
+fn metrics() -> &'static PoolMetrics {
+    static METRICS: OnceLock<PoolMetrics> = OnceLock::new();
+    METRICS.get_or_init(|| PoolMetrics {
+        up: register_int_gauge_vec!("elementa_db_pool_up", "Whether the pool's last health check succeeded", &["pool"])
+            .expect("pool metric registers once"),
+        latency: register_gauge_vec!("elementa_db_pool_check_seconds", "Duration of the pool's last health check", &["pool"])
+            .expect("pool metric registers once"),
+        failures: register_int_counter_vec!("elementa_db_pool_check_failures_total", "Failed pool health checks", &["pool"])
+            .expect("pool metric registers once"),
+        connections: register_int_gauge_vec!("elementa_db_pool_connections", "Pool connections by state", &["pool", "state"])
+            .expect("pool metric registers once"),
+    })
+}
+
+fn export_metrics(health: &PoolHealth) {
+    let metrics = metrics();
+    let pool = health.pool.as_str();
+    metrics.up.with_label_values(&[pool]).set(i64::from(health.healthy));
+    metrics.latency.with_label_values(&[pool]).set(health.latency_ms as f64 / 1000.0);
+    if !health.healthy {
+        metrics.failures.with_label_values(&[pool]).inc();
+    }
+    if let Some(connections) = health.connections {
+        for (state, count) in [("idle", connections.idle), ("in_use", connections.in_use), ("max", connections.max)] {
+            metrics.connections.with_label_values(&[pool, state]).set(i64::from(count));
+        }
+    }
+}
+
+/// Retry connecting with exponential backoff, so a service started while a
+/// database is still coming up waits for it rather than exiting
+pub async fn connect_with_backoff<T, F, Fut>(what: &str, attempts: u32, mut connect: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut delay = MIN_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(connected) => return Ok(connected),
+            Err(e) if attempt < attempts => {
+                tracing::warn!("Connecting to {} failed (attempt {} of {}), retrying in {:?}: {:#}", what, attempt, attempts, delay, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.context(format!("Failed to connect to {} after {} attempts", what, attempts))),
+        }
+    }
+}
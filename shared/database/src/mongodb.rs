@@ -1,11 +1,24 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use mongodb::options::ClientOptions;
 use mongodb::{Client, Database};
 
 pub type MongoClient = Client;
 pub type MongoDatabase = Database;
 
 pub async fn create_mongo_client(database_url: &str) -> Result<MongoClient> {
-    let client = Client::with_uri_str(database_url).await?;
+    create_mongo_client_with_password(database_url, None).await
+}
+
+/// Connect with `password` for the URL's user in place of one embedded in the URL
+pub async fn create_mongo_client_with_password(database_url: &str, password: Option<String>) -> Result<MongoClient> {
+    let mut options = ClientOptions::parse(database_url).await?;
+    if let Some(password) = password {
+        let Some(credential) = options.credential.as_mut().filter(|c| c.username.is_some()) else {
+            bail!("MongoDB password is set but the MongoDB URL names no user");
+        };
+        credential.password = Some(password);
+    }
+    let client = Client::with_options(options)?;
     
     // Test connection
     client
@@ -0,0 +1,222 @@
+//! Transactional outbox for domain events.
+//!
+//! A service that changes Postgres and then publishes an event loses the
+//! event if it crashes in between. `OutboxPublisher` instead writes the
+//! event envelope to the `outbox` table on the connection of the transaction
+//! making the change, so the change and its event commit together or not at
+//! all. `OutboxRelay` delivers pending events to the message bus oldest
+//! first, retrying failures with backoff. Order only holds within an
+//! attempt: a failed event waits out its backoff while the events written
+//! after it are delivered, so it can arrive after them. Delivery is at
+//! least once: a relay that stops after delivering an event but before
+//! marking it published delivers it again, so consumers dedupe on the
+//! envelope ID and must not rely on events arriving in the order written.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use elementa_models::{DomainEvent, EventEnvelope};
+
+/// Longest a failed event waits before its next attempt
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+/// Where the relay delivers events, e.g. the workflow service's event endpoint
+#[async_trait]
+pub trait MessageBus: Send + Sync {
+    async fn deliver(&self, envelope: &EventEnvelope) -> Result<()>;
+}
+
+/// Writes a service's events to the outbox
+#[derive(Debug, Clone)]
+pub struct OutboxPublisher {
+    source: String,
+}
+
+impl OutboxPublisher {
+    /// Publisher for events from the service named `source`
+    pub fn new(source: impl Into<String>) -> Self {
+        Self { source: source.into() }
+    }
+
+    /// Write an event that starts a new chain, for the tenant when known.
+    /// `conn` should be the transaction making the change the event describes.
+    pub async fn publish(&self, conn: &mut PgConnection, event: DomainEvent, tenant: Option<String>) -> Result<EventEnvelope> {
+        let envelope = EventEnvelope::new(self.source.as_str(), event).with_tenant(tenant);
+        Self::enqueue(conn, &envelope).await?;
+        Ok(envelope)
+    }
+
+    /// Write an event that follows from `cause`, in its chain
+    pub async fn publish_caused_by(&self, conn: &mut PgConnection, cause: &EventEnvelope, event: DomainEvent) -> Result<EventEnvelope> {
+        let envelope = EventEnvelope::caused_by(cause, self.source.as_str(), event);
+        Self::enqueue(conn, &envelope).await?;
+        Ok(envelope)
+    }
+
+    /// Write an envelope to the outbox on a connection; writing the same envelope twice is a no-op
+    pub async fn enqueue(conn: &mut PgConnection, envelope: &EventEnvelope) -> Result<()> {
+        let json = serde_json::to_value(envelope)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO outbox (event_id, event_type, envelope, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (event_id) DO NOTHING
+            "#
+        )
+        .bind(envelope.id)
+        .bind(envelope.event_type())
+        .bind(&json)
+        .bind(Utc::now())
+        .execute(conn)
+        .await
+        .context("Failed to write event to outbox")?;
+
+        Ok(())
+    }
+}
+
+/// Delivers outbox events to a message bus. Several relays can run against
+/// the same database; each claims its batch with row locks the others skip.
+pub struct OutboxRelay<B> {
+    pool: PgPool,
+    bus: B,
+    batch_size: i64,
+    poll_interval: Duration,
+}
+
+impl<B: MessageBus> OutboxRelay<B> {
+    pub fn new(pool: PgPool, bus: B) -> Self {
+        Self {
+            pool,
+            bus,
+            batch_size: 100,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Deliver pending events until the task is dropped
+    pub async fn run(self) {
+        loop {
+            match self.relay_batch().await {
+                // A full batch went out; more are likely waiting
+                Ok(delivered) if delivered as i64 == self.batch_size => continue,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Outbox relay failed: {:#}", e),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Deliver one batch of events that are due, oldest first, returning how many were delivered.
+    /// A failed event is rescheduled and the rest of the batch is still delivered.
+    pub async fn relay_batch(&self) -> Result<usize> {
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+
+        let rows: Vec<(i64, serde_json::Value, i32)> = sqlx::query_as(
+            r#"
+            SELECT sequence, envelope, attempts FROM outbox
+            WHERE published_at IS NULL AND next_attempt_at <= NOW()
+            ORDER BY sequence
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#
+        )
+        .bind(self.batch_size)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to claim outbox events")?;
+
+        let mut delivered = 0;
+        for (sequence, json, attempts) in rows {
+            let result = match serde_json::from_value::<EventEnvelope>(json) {
+                Ok(envelope) => self.bus.deliver(&envelope).await,
+                Err(e) => Err(e).context("Invalid event envelope in outbox"),
+            };
+
+            match result {
+                Ok(()) => {
+                    sqlx::query("UPDATE outbox SET published_at = $2, attempts = attempts + 1, last_error = NULL WHERE sequence = $1")
+                        .bind(sequence)
+                        .bind(Utc::now())
+                        .execute(&mut *tx)
+                        .await
+                        .context("Failed to mark outbox event published")?;
+                    delivered += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to deliver outbox event {} (attempt {}): {:#}", sequence, attempts + 1, e);
+                    sqlx::query("UPDATE outbox SET attempts = attempts + 1, last_error = $2, next_attempt_at = $3 WHERE sequence = $1")
+                        .bind(sequence)
+                        .bind(format!("{:#}", e))
+                        .bind(Utc::now() + retry_delay(attempts + 1))
+                        .execute(&mut *tx)
+                        .await
+                        .context("Failed to reschedule outbox event")?;
+                }
+            }
+        }
+
+        tx.commit().await.context("Failed to commit transaction")?;
+        Ok(delivered)
+    }
+
+    /// Events not yet delivered, including those waiting to be retried
+    pub async fn pending_count(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM outbox WHERE published_at IS NULL")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count pending outbox events")?;
+
+        Ok(count)
+    }
+
+    /// Remove events delivered before `before`, returning how many were removed
+    pub async fn purge_published(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM outbox WHERE published_at IS NOT NULL AND published_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .context("Failed to purge published outbox events")?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Exponential backoff from a couple of seconds up to an hour
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let secs = 2i64.saturating_pow(attempts.clamp(1, 31) as u32).min(MAX_RETRY_DELAY_SECS);
+    chrono::Duration::seconds(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_up_to_an_hour() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(2));
+        assert_eq!(retry_delay(5), chrono::Duration::seconds(32));
+        assert_eq!(retry_delay(11), chrono::Duration::seconds(2048));
+        assert_eq!(retry_delay(12), chrono::Duration::seconds(MAX_RETRY_DELAY_SECS));
+
+        // Out-of-range attempt counts are clamped rather than overflowing
+        assert_eq!(retry_delay(0), chrono::Duration::seconds(2));
+        assert_eq!(retry_delay(-3), chrono::Duration::seconds(2));
+        assert_eq!(retry_delay(i32::MAX), chrono::Duration::seconds(MAX_RETRY_DELAY_SECS));
+    }
+}
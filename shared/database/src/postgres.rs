@@ -1,23 +1,277 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
 use sqlx::{Pool, Postgres};
+use std::future::Future;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
+use crate::tenant::{tenant_scoped, TenantAccess};
+
 pub type PostgresPool = Pool<Postgres>;
 
-pub async fn create_postgres_pool(database_url: &str, max_connections: u32) -> Result<PostgresPool> {
-    let pool = sqlx::postgres::PgPoolOptions::new()
+pub async fn create_postgres_pool(database_url: &str, max_connections: u32, access: TenantAccess) -> Result<PostgresPool> {
+    create_postgres_pool_with(PgConnectOptions::from_str(database_url)?, max_connections, access).await
+}
+
+/// A pool whose connections reach the rows `access` allows outside a tenant scope
+pub async fn create_postgres_pool_with(
+    options: PgConnectOptions,
+    max_connections: u32,
+    access: TenantAccess,
+) -> Result<PostgresPool> {
+    let pool = tenant_scoped(sqlx::postgres::PgPoolOptions::new(), access)
         .max_connections(max_connections)
         .acquire_timeout(Duration::from_secs(30))
-        .connect(database_url)
+        .connect_with(options)
         .await?;
     
     tracing::info!("Connected to PostgreSQL database");
     Ok(pool)
 }
 
+/// Pools for the primary and, optionally, a read replica. The replica
+/// connects lazily, so a replica that is down at startup does not stop the
+/// service; reads fall back to the primary until it is reachable.
+pub async fn create_postgres_pools(
+    primary_url: &str,
+    replica_url: Option<&str>,
+    max_connections: u32,
+    access: TenantAccess,
+) -> Result<PostgresPools> {
+    let replica = replica_url.map(PgConnectOptions::from_str).transpose()?;
+    create_postgres_pools_with(PgConnectOptions::from_str(primary_url)?, replica, max_connections, access).await
+}
+
+/// Pools for the primary and, optionally, a read replica, from connect
+/// options carrying TLS settings and credentials
+pub async fn create_postgres_pools_with(
+    primary: PgConnectOptions,
+    replica: Option<PgConnectOptions>,
+    max_connections: u32,
+    access: TenantAccess,
+) -> Result<PostgresPools> {
+    let primary = create_postgres_pool_with(primary, max_connections, access).await?;
+    let replica = match replica {
+        Some(options) => {
+            let replica = tenant_scoped(sqlx::postgres::PgPoolOptions::new(), access)
+                .max_connections(max_connections)
+                .acquire_timeout(Duration::from_secs(5))
+                .connect_lazy_with(options);
+            tracing::info!("Routing read-only queries to PostgreSQL read replica");
+            Some(replica)
+        }
+        None => None,
+    };
+    
+    Ok(PostgresPools { primary, replica })
+}
+
+/// TLS for PostgreSQL connections. Settings left unset keep what the
+/// connection URL says, e.g. its `sslmode` and `sslrootcert` parameters.
+#[derive(Debug, Clone, Default)]
+pub struct PostgresTls {
+    /// `require` encrypts without checking the server's certificate;
+    /// `verify-ca` and `verify-full` check it against `root_cert`
+    pub mode: Option<PgSslMode>,
+    /// CA certificate the server's certificate must chain to
+    pub root_cert: Option<PathBuf>,
+    /// Client certificate and key, for servers that authenticate clients by certificate
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
+impl PostgresTls {
+    /// Apply the settings to connect options parsed from a URL
+    pub fn apply(&self, mut options: PgConnectOptions) -> Result<PgConnectOptions> {
+        if self.client_cert.is_some() != self.client_key.is_some() {
+            bail!("PostgreSQL TLS needs both a client certificate and a client key, or neither");
+        }
+        if let Some(mode) = self.mode {
+            options = options.ssl_mode(mode);
+        }
+        if let Some(root_cert) = &self.root_cert {
+            options = options.ssl_root_cert(root_cert);
+        }
+        if let (Some(cert), Some(key)) = (&self.client_cert, &self.client_key) {
+            options = options.ssl_client_cert(cert).ssl_client_key(key);
+        }
+        Ok(options)
+    }
+}
+
+/// Primary pool for writes and reads that must see them, and an optional
+/// replica for read-only queries such as dashboards and analytics
+#[derive(Debug, Clone)]
+pub struct PostgresPools {
+    primary: PostgresPool,
+    replica: Option<PostgresPool>,
+}
+
+impl From<PostgresPool> for PostgresPools {
+    fn from(primary: PostgresPool) -> Self {
+        Self { primary, replica: None }
+    }
+}
+
+impl PostgresPools {
+    pub fn primary(&self) -> &PostgresPool {
+        &self.primary
+    }
+    
+    pub fn replica(&self) -> Option<&PostgresPool> {
+        self.replica.as_ref()
+    }
+    
+    /// Run a read-only query on the replica, falling back to the primary when
+    /// the replica is unreachable or cancels the query. Replicas lag the
+    /// primary, so reads that must see a write just made go to `primary`.
+    pub async fn read<T, F, Fut>(&self, query: F) -> Result<T>
+    where
+        F: Fn(PostgresPool) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let Some(replica) = &self.replica else {
+            return query(self.primary.clone()).await;
+        };
+        
+        match query(replica.clone()).await {
+            Err(e) if is_replica_failure(&e) => {
+                tracing::warn!("Read replica unavailable, reading from primary: {:#}", e);
+                query(self.primary.clone()).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// Whether a failed read may succeed on the primary: the replica could not
+/// be reached, or cancelled the query while replaying changes from the primary
+fn is_replica_failure(error: &anyhow::Error) -> bool {
+    error.chain()
+        .filter_map(|e| e.downcast_ref::<sqlx::Error>())
+        .any(|e| match e {
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => true,
+            // Connection exceptions, serialization failures and operator intervention
+            sqlx::Error::Database(db) => db.code()
+                .is_some_and(|code| code.starts_with("08") || code == "40001" || code.starts_with("57")),
+            _ => false,
+        })
+}
+
 pub async fn health_check(pool: &PostgresPool) -> Result<()> {
     sqlx::query("SELECT 1")
         .execute(pool)
         .await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+
+    /// A database error carrying only a SQLSTATE code
+    #[derive(Debug)]
+    struct SqlState(&'static str);
+
+    impl std::fmt::Display for SqlState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for SqlState {}
+
+    impl DatabaseError for SqlState {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str) -> anyhow::Error {
+        sqlx::Error::Database(Box::new(SqlState(code))).into()
+    }
+
+    #[test]
+    fn test_replica_failure_sqlstates() {
+        // Connection exceptions
+        assert!(is_replica_failure(&database_error("08006")));
+        assert!(is_replica_failure(&database_error("08001")));
+        // Serialization failure, e.g. a standby query cancelled by a conflict
+        assert!(is_replica_failure(&database_error("40001")));
+        // Operator intervention, e.g. admin shutdown or query canceled
+        assert!(is_replica_failure(&database_error("57P01")));
+        assert!(is_replica_failure(&database_error("57014")));
+
+        // The query itself is wrong; the primary would fail it too
+        assert!(!is_replica_failure(&database_error("42P01")));
+        assert!(!is_replica_failure(&database_error("23505")));
+        assert!(!is_replica_failure(&database_error("40P01")));
+    }
+
+    #[test]
+    fn test_replica_failure_connection_errors() {
+        let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert!(is_replica_failure(&sqlx::Error::Io(io).into()));
+        assert!(is_replica_failure(&sqlx::Error::PoolTimedOut.into()));
+        assert!(is_replica_failure(&sqlx::Error::PoolClosed.into()));
+        assert!(is_replica_failure(&sqlx::Error::WorkerCrashed.into()));
+        assert!(is_replica_failure(&sqlx::Error::Protocol("unexpected message".to_string()).into()));
+
+        assert!(!is_replica_failure(&sqlx::Error::RowNotFound.into()));
+        assert!(!is_replica_failure(&anyhow::anyhow!("not a database error")));
+
+        // Found through context added on the way up
+        let wrapped = anyhow::Error::from(sqlx::Error::PoolTimedOut).context("Failed to list suppliers");
+        assert!(is_replica_failure(&wrapped));
+    }
+    #[test]
+    fn test_tls_needs_client_cert_and_key_together() {
+        let url = "postgresql://elementa@localhost/elementa?sslmode=prefer";
+        let options = || PgConnectOptions::from_str(url).unwrap();
+
+        let cert_only = PostgresTls { client_cert: Some("client.crt".into()), ..Default::default() };
+        let error = cert_only.apply(options()).unwrap_err();
+        assert!(error.to_string().contains("both a client certificate and a client key"));
+        let key_only = PostgresTls { client_key: Some("client.key".into()), ..Default::default() };
+        assert!(key_only.apply(options()).is_err());
+
+        let both = PostgresTls {
+            mode: Some(PgSslMode::VerifyFull),
+            client_cert: Some("client.crt".into()),
+            client_key: Some("client.key".into()),
+            ..Default::default()
+        };
+        assert!(matches!(both.apply(options()).unwrap().get_ssl_mode(), PgSslMode::VerifyFull));
+
+        // Unset settings keep the URL's
+        assert!(matches!(PostgresTls::default().apply(options()).unwrap().get_ssl_mode(), PgSslMode::Prefer));
+    }
+}
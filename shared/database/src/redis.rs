@@ -1,10 +1,19 @@
 use anyhow::Result;
-use redis::{aio::ConnectionManager, Client};
+use redis::{aio::ConnectionManager, Client, IntoConnectionInfo};
 
 pub type RedisPool = ConnectionManager;
 
-pub async fn create_redis_pool(redis_url: &str, _max_connections: u32) -> Result<RedisPool> {
-    let client = Client::open(redis_url)?;
+pub async fn create_redis_pool(redis_url: &str, max_connections: u32) -> Result<RedisPool> {
+    create_redis_pool_with_password(redis_url, None, max_connections).await
+}
+
+/// Connect with `password` in place of one embedded in the URL
+pub async fn create_redis_pool_with_password(redis_url: &str, password: Option<String>, _max_connections: u32) -> Result<RedisPool> {
+    let mut info = redis_url.into_connection_info()?;
+    if password.is_some() {
+        info.redis.password = password;
+    }
+    let client = Client::open(info)?;
     let connection_manager = ConnectionManager::new(client).await?;
     
     tracing::info!("Connected to Redis cache");
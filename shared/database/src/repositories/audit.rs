@@ -1,15 +1,24 @@
 //! Audit Repository
 //!
-//! Immutable audit trail with hash chain verification.
+//! Immutable audit trail with hash chain verification. Entries are stored in
+//! monthly partitions of `audit_entries` by timestamp, so queries bound their
+//! timestamps wherever they can to read only the months they need, and
+//! `AuditPartitionJob` creates partitions ahead of the months to come.
+
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
 use sha2::{Sha256, Digest};
-use sqlx::{PgPool, FromRow};
+use sqlx::{PgConnection, PgPool, FromRow};
 use uuid::Uuid;
 
 use elementa_models::AuditEntry;
 
+use super::pagination::{into_page, keyset_bounds, page_limit};
+use super::{Cursor, Page};
+use crate::QueryTiming;
+
 pub struct AuditRepository {
     pool: PgPool,
 }
@@ -21,12 +30,55 @@ impl AuditRepository {
     
     /// Create new audit entry (immutable - no update/delete)
     pub async fn create(&self, entry: AuditEntry, previous_hash: Option<String>) -> Result<AuditEntry> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection")?;
+        Self::insert(&mut conn, entry, previous_hash).await
+    }
+    
+    /// Append an entry to the chain, after the latest entry
+    pub async fn append(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        let previous_hash = Self::latest_hash(&mut tx).await?;
+        let entry = Self::insert(&mut tx, entry, previous_hash).await?;
+        tx.commit().await.context("Failed to commit audit entry")?;
+        Ok(entry)
+    }
+    
+    /// Hash of the latest entry, which the next entry chains onto. The
+    /// latest entry is nearly always in this month's or last month's
+    /// partition, so those are searched before the whole table.
+    pub(crate) async fn latest_hash(conn: &mut PgConnection) -> Result<Option<String>> {
+        let recent: Option<(String,)> = sqlx::query_as(
+            "SELECT hash FROM audit_entries WHERE timestamp >= $1 ORDER BY timestamp DESC, created_at DESC LIMIT 1"
+        )
+        .bind(month_start(Utc::now(), -1))
+        .fetch_optional(&mut *conn)
+        .timed("audit", "latest_hash")
+        .await
+        .context("Failed to fetch latest audit hash")?;
+        
+        if let Some((hash,)) = recent {
+            return Ok(Some(hash));
+        }
+        
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT hash FROM audit_entries ORDER BY timestamp DESC, created_at DESC LIMIT 1"
+        )
+        .fetch_optional(conn)
+        .timed("audit", "latest_hash")
+        .await
+        .context("Failed to fetch latest audit hash")?;
+        
+        Ok(row.map(|r| r.0))
+    }
+    
+    /// Insert an audit entry on a connection, e.g. within another repository's transaction
+    pub(crate) async fn insert(conn: &mut PgConnection, entry: AuditEntry, previous_hash: Option<String>) -> Result<AuditEntry> {
         let action = serde_json::to_string(&entry.action)?;
         let details = serde_json::to_value(&entry.details)?;
         let source_document = serde_json::to_value(&entry.source_document)?;
         
         // Calculate hash including previous hash for chain integrity
-        let hash = self.calculate_hash(&entry, previous_hash.as_deref());
+        let hash = Self::calculate_hash(&entry, previous_hash.as_deref());
         
         let row: AuditRow = sqlx::query_as(
             r#"
@@ -48,7 +100,8 @@ impl AuditRepository {
         .bind(&hash)
         .bind(&previous_hash)
         .bind(Utc::now())
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
+        .timed("audit", "insert")
         .await
         .context("Failed to create audit entry")?;
         
@@ -62,19 +115,101 @@ impl AuditRepository {
             SELECT id, timestamp, action, user_id, agent_id, details,
                    source_document, hash, previous_hash, created_at
             FROM audit_entries
-            WHERE details->>'entity_type' = $1 AND (details->>'entity_id')::uuid = $2
+            WHERE details->>'entity_type' = $1 AND details->>'entity_id' = $2
             ORDER BY timestamp ASC
             "#
         )
         .bind(entity_type)
-        .bind(entity_id)
+        .bind(entity_id.to_string())
         .fetch_all(&self.pool)
+        .timed("audit", "find_by_entity")
         .await
         .context("Failed to fetch audit entries by entity")?;
         
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
     
+    /// One page of an entity's audit entries, newest first
+    pub async fn find_page_by_entity(
+        &self,
+        entity_type: &str,
+        entity_id: Uuid,
+        after: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Page<AuditEntry>> {
+        let (after_created_at, after_id) = keyset_bounds(after);
+        let rows: Vec<AuditRow> = sqlx::query_as(
+            r#"
+            SELECT id, timestamp, action, user_id, agent_id, details,
+                   source_document, hash, previous_hash, created_at
+            FROM audit_entries
+            WHERE details->>'entity_type' = $1 AND details->>'entity_id' = $2
+              AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5
+            "#
+        )
+        .bind(entity_type)
+        .bind(entity_id.to_string())
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(page_limit(limit) + 1)
+        .fetch_all(&self.pool)
+        .timed("audit", "find_page_by_entity")
+        .await
+        .context("Failed to fetch page of audit entries by entity")?;
+        
+        let entries = rows.into_iter().map(AuditEntry::from).collect();
+        Ok(into_page(entries, limit, |e: &AuditEntry| Cursor::new(e.created_at, e.id)))
+    }
+    
+    /// Find audit entries for an entity within a date range, reading only
+    /// the partitions for the months it covers
+    pub async fn find_by_entity_between(
+        &self,
+        entity_type: &str,
+        entity_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AuditEntry>> {
+        let rows: Vec<AuditRow> = sqlx::query_as(
+            r#"
+            SELECT id, timestamp, action, user_id, agent_id, details,
+                   source_document, hash, previous_hash, created_at
+            FROM audit_entries
+            WHERE details->>'entity_type' = $1 AND details->>'entity_id' = $2
+              AND timestamp >= $3 AND timestamp <= $4
+            ORDER BY timestamp ASC
+            "#
+        )
+        .bind(entity_type)
+        .bind(entity_id.to_string())
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .timed("audit", "find_by_entity_between")
+        .await
+        .context("Failed to fetch audit entries by entity")?;
+        
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+    
+    /// Create the partitions for this month and the `months_ahead` after it
+    /// that do not exist yet, returning the names of those created
+    pub async fn ensure_partitions(&self, now: DateTime<Utc>, months_ahead: u32) -> Result<Vec<String>> {
+        let mut created = Vec::new();
+        for offset in 0..=months_ahead as i32 {
+            let (partition,): (Option<String>,) = sqlx::query_as("SELECT create_audit_entries_partition($1)")
+                .bind(month_start(now, offset))
+                .fetch_one(&self.pool)
+                .timed("audit", "ensure_partitions")
+                .await
+                .context("Failed to create audit partition")?;
+            created.extend(partition);
+        }
+        Ok(created)
+    }
+    
     /// Verify hash chain integrity for a date range
     pub async fn verify_chain(&self, from: chrono::DateTime<Utc>, to: chrono::DateTime<Utc>) -> Result<ChainVerification> {
         let rows: Vec<AuditRow> = sqlx::query_as(
@@ -89,31 +224,15 @@ impl AuditRepository {
         .bind(from)
         .bind(to)
         .fetch_all(&self.pool)
+        .timed("audit", "verify_chain")
         .await
         .context("Failed to fetch audit entries for verification")?;
         
-        let mut broken_links = Vec::new();
-        let mut previous_hash: Option<String> = None;
-        
-        for row in &rows {
-            let entry: AuditEntry = row.clone().into();
-            let expected_hash = self.calculate_hash(&entry, previous_hash.as_deref());
-            
-            if row.hash != expected_hash {
-                broken_links.push(row.id);
-            }
-            
-            previous_hash = Some(row.hash.clone());
-        }
-        
-        Ok(ChainVerification {
-            is_valid: broken_links.is_empty(),
-            entries_verified: rows.len(),
-            broken_links,
-        })
+        let entries: Vec<AuditEntry> = rows.into_iter().map(|r| r.into()).collect();
+        Ok(verify_entries(&entries))
     }
     
-    fn calculate_hash(&self, entry: &AuditEntry, previous_hash: Option<&str>) -> String {
+    pub(crate) fn calculate_hash(entry: &AuditEntry, previous_hash: Option<&str>) -> String {
         let mut hasher = Sha256::new();
         hasher.update(entry.id.to_string().as_bytes());
         hasher.update(entry.timestamp.to_rfc3339().as_bytes());
@@ -128,6 +247,71 @@ impl AuditRepository {
     }
 }
 
+/// Keeps audit partitions created ahead of the months they cover, so
+/// entries land in their month's partition rather than the default one
+pub struct AuditPartitionJob {
+    audit: AuditRepository,
+    months_ahead: u32,
+    interval: Duration,
+}
+
+impl AuditPartitionJob {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            audit: AuditRepository::new(pool),
+            months_ahead: 3,
+            interval: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+    
+    pub fn with_months_ahead(mut self, months_ahead: u32) -> Self {
+        self.months_ahead = months_ahead;
+        self
+    }
+    
+    /// Create upcoming partitions now and then once per interval until the task is dropped
+    pub async fn run(self) {
+        loop {
+            match self.audit.ensure_partitions(Utc::now(), self.months_ahead).await {
+                Ok(created) if !created.is_empty() => tracing::info!("Created audit partitions {}", created.join(", ")),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Audit partition maintenance failed: {:#}", e),
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+/// Check each entry's hash against its content and the hash of the entry before it
+pub(crate) fn verify_entries(entries: &[AuditEntry]) -> ChainVerification {
+    let mut broken_links = Vec::new();
+    let mut previous_hash: Option<&str> = None;
+    
+    for entry in entries {
+        let expected_hash = AuditRepository::calculate_hash(entry, previous_hash);
+        
+        if entry.hash != expected_hash {
+            broken_links.push(entry.id);
+        }
+        
+        previous_hash = Some(&entry.hash);
+    }
+    
+    ChainVerification {
+        is_valid: broken_links.is_empty(),
+        entries_verified: entries.len(),
+        broken_links,
+    }
+}
+
+/// Start of the month `offset` months from the one containing `at`, in UTC
+fn month_start(at: DateTime<Utc>, offset: i32) -> DateTime<Utc> {
+    let months = at.year() * 12 + at.month0() as i32 + offset;
+    Utc.with_ymd_and_hms(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1, 0, 0, 0)
+        .single()
+        .expect("first of the month at midnight UTC is unambiguous")
+}
+
 #[derive(Debug, Clone, FromRow)]
 struct AuditRow {
     id: Uuid,
@@ -0,0 +1,168 @@
+//! Bulk inserts
+//!
+//! Imports such as a BOM spreadsheet create tens of thousands of rows, so
+//! repositories insert them in batches with one `UNNEST` statement and one
+//! transaction per batch. A row that cannot be inserted is reported by its
+//! position in the input rather than failing the import: rows with an ID
+//! that already exists are skipped, and rows referencing a supplier or
+//! component that does not exist are left out of their batch. A batch that
+//! still fails is rolled back and each of its rows reported, and later
+//! batches carry on.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use elementa_models::{ComponentId, SupplierId};
+
+use crate::QueryTiming;
+
+/// Rows inserted per statement and transaction
+pub const BULK_BATCH_SIZE: usize = 1000;
+
+/// Why a row of a bulk insert was not inserted
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum BulkRowError {
+    #[error("row could not be encoded: {0}")]
+    Encoding(String),
+    #[error("ID {0} already exists")]
+    DuplicateId(Uuid),
+    #[error("supplier {0} does not exist")]
+    MissingSupplier(SupplierId),
+    #[error("parent supplier {0} does not exist")]
+    MissingParent(SupplierId),
+    #[error("component {0} does not exist")]
+    MissingComponent(ComponentId),
+    #[error("batch failed: {0}")]
+    BatchFailed(String),
+}
+
+/// A row that was not inserted, by its index in the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkRowFailure {
+    pub index: usize,
+    pub error: BulkRowError,
+}
+
+/// Outcome of a bulk insert: the rows inserted, in input order, and the rows that were not
+#[derive(Debug, Clone)]
+pub struct BulkInsertReport<T> {
+    pub inserted: Vec<T>,
+    pub failed: Vec<BulkRowFailure>,
+}
+
+impl<T> BulkInsertReport<T> {
+    /// Whether every row was inserted
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl<T> Default for BulkInsertReport<T> {
+    fn default() -> Self {
+        Self {
+            inserted: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+/// A table rows can be bulk inserted into
+#[async_trait]
+pub(crate) trait BulkInsert {
+    type Item: Send + Sync;
+
+    fn id(item: &Self::Item) -> Uuid;
+
+    /// Insert a batch of rows, each with its input index, on a transaction.
+    /// Rows that cannot be inserted are returned as failures and the rest
+    /// inserted with `ON CONFLICT (id) DO NOTHING`, returning those inserted.
+    async fn insert_batch(
+        conn: &mut PgConnection,
+        rows: Vec<(usize, &Self::Item)>,
+    ) -> Result<(Vec<Self::Item>, Vec<BulkRowFailure>)>;
+}
+
+/// Insert `items` in batches, reporting each row that was not inserted
+pub(crate) async fn bulk_insert<B: BulkInsert>(pool: &PgPool, items: &[B::Item]) -> BulkInsertReport<B::Item> {
+    let mut report = BulkInsertReport::default();
+    let mut seen = HashSet::new();
+
+    for (batch_number, batch) in items.chunks(BULK_BATCH_SIZE).enumerate() {
+        let mut rows = Vec::with_capacity(batch.len());
+        for (offset, item) in batch.iter().enumerate() {
+            let index = batch_number * BULK_BATCH_SIZE + offset;
+            let id = B::id(item);
+            if seen.insert(id) {
+                rows.push((index, item));
+            } else {
+                report.failed.push(BulkRowFailure { index, error: BulkRowError::DuplicateId(id) });
+            }
+        }
+        let submitted: Vec<(usize, Uuid)> = rows.iter().map(|(index, item)| (*index, B::id(item))).collect();
+
+        match insert_in_transaction::<B>(pool, rows).await {
+            Ok((mut inserted, failed)) => {
+                // Rows neither inserted nor failed were skipped by the conflict clause
+                let inserted_ids: HashSet<Uuid> = inserted.iter().map(B::id).collect();
+                let failed_indices: HashSet<usize> = failed.iter().map(|f| f.index).collect();
+                for (index, id) in &submitted {
+                    if !inserted_ids.contains(id) && !failed_indices.contains(index) {
+                        report.failed.push(BulkRowFailure { index: *index, error: BulkRowError::DuplicateId(*id) });
+                    }
+                }
+
+                let positions: HashMap<Uuid, usize> = submitted.iter().map(|(index, id)| (*id, *index)).collect();
+                inserted.sort_by_key(|item| positions.get(&B::id(item)).copied());
+                report.inserted.extend(inserted);
+                report.failed.extend(failed);
+            }
+            Err(e) => {
+                let message = format!("{:#}", e);
+                tracing::warn!("Bulk insert batch {} failed: {}", batch_number, message);
+                report.failed.extend(submitted.into_iter().map(|(index, _)| BulkRowFailure {
+                    index,
+                    error: BulkRowError::BatchFailed(message.clone()),
+                }));
+            }
+        }
+    }
+
+    report.failed.sort_by_key(|f| f.index);
+    report
+}
+
+async fn insert_in_transaction<B: BulkInsert>(
+    pool: &PgPool,
+    rows: Vec<(usize, &B::Item)>,
+) -> Result<(Vec<B::Item>, Vec<BulkRowFailure>)> {
+    if rows.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let result = B::insert_batch(&mut tx, rows).await?;
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(result)
+}
+
+/// Which of `ids` name live (not soft-deleted) rows of `table`
+pub(crate) async fn live_ids(conn: &mut PgConnection, table: &str, ids: Vec<Uuid>) -> Result<HashSet<Uuid>> {
+    if ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let query = format!("SELECT id FROM {} WHERE id = ANY($1) AND lifecycle_status <> 'deleted'", table);
+    let rows: Vec<(Uuid,)> = sqlx::query_as(&query)
+        .bind(ids)
+        .fetch_all(&mut *conn)
+        .timed("bulk", "live_ids")
+        .await
+        .with_context(|| format!("Failed to look up {}", table))?;
+
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
@@ -9,6 +9,8 @@ use sqlx::{PgPool, FromRow};
 
 use elementa_models::ChemicalSubstance;
 
+use crate::QueryTiming;
+
 pub struct ChemicalRepository {
     pool: PgPool,
 }
@@ -22,14 +24,15 @@ impl ChemicalRepository {
     pub async fn find_by_cas(&self, cas_number: &str) -> Result<Option<ChemicalSubstance>> {
         let row: Option<ChemicalRow> = sqlx::query_as(
             r#"
-            SELECT cas_number, chemical_name, molecular_formula, molecular_weight, is_pfas,
+            SELECT cas_number, chemical_name, molecular_formula, molecular_weight::FLOAT8 AS molecular_weight, is_pfas,
                    pfas_classification, regulatory_status, last_updated
-            FROM chemicals
+            FROM chemical_substances
             WHERE cas_number = $1
             "#
         )
         .bind(cas_number)
         .fetch_optional(&self.pool)
+        .timed("chemical", "find_by_cas")
         .await
         .context("Failed to fetch chemical by CAS")?;
         
@@ -40,14 +43,15 @@ impl ChemicalRepository {
     pub async fn find_all_pfas(&self) -> Result<Vec<ChemicalSubstance>> {
         let rows: Vec<ChemicalRow> = sqlx::query_as(
             r#"
-            SELECT cas_number, chemical_name, molecular_formula, molecular_weight, is_pfas,
+            SELECT cas_number, chemical_name, molecular_formula, molecular_weight::FLOAT8 AS molecular_weight, is_pfas,
                    pfas_classification, regulatory_status, last_updated
-            FROM chemicals
+            FROM chemical_substances
             WHERE is_pfas = true
             ORDER BY chemical_name
             "#
         )
         .fetch_all(&self.pool)
+        .timed("chemical", "find_all_pfas")
         .await
         .context("Failed to fetch PFAS chemicals")?;
         
@@ -62,7 +66,7 @@ impl ChemicalRepository {
         
         let row: ChemicalRow = sqlx::query_as(
             r#"
-            INSERT INTO chemicals 
+            INSERT INTO chemical_substances
                 (cas_number, chemical_name, molecular_formula, molecular_weight, is_pfas,
                  pfas_classification, regulatory_status, last_updated)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
@@ -74,7 +78,7 @@ impl ChemicalRepository {
                 pfas_classification = EXCLUDED.pfas_classification,
                 regulatory_status = EXCLUDED.regulatory_status,
                 last_updated = EXCLUDED.last_updated
-            RETURNING cas_number, chemical_name, molecular_formula, molecular_weight, is_pfas,
+            RETURNING cas_number, chemical_name, molecular_formula, molecular_weight::FLOAT8 AS molecular_weight, is_pfas,
                       pfas_classification, regulatory_status, last_updated
             "#
         )
@@ -87,6 +91,7 @@ impl ChemicalRepository {
         .bind(&regulatory_status)
         .bind(now)
         .fetch_one(&self.pool)
+        .timed("chemical", "upsert")
         .await
         .context("Failed to upsert chemical")?;
         
@@ -105,8 +110,9 @@ impl ChemicalRepository {
     
     /// Count PFAS substances
     pub async fn count_pfas(&self) -> Result<i64> {
-        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM chemicals WHERE is_pfas = true")
+        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM chemical_substances WHERE is_pfas = true")
             .fetch_one(&self.pool)
+            .timed("chemical", "count_pfas")
             .await
             .context("Failed to count PFAS")?;
         
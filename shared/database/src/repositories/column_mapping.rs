@@ -0,0 +1,117 @@
+//! Column Mapping Repository
+//!
+//! BOM column mappings clients saved by name for reuse. Each tenant has
+//! its own names, so one client's mapping never replaces another's.
+
+use anyhow::{Context, Result};
+use sqlx::{types::Json, FromRow, PgPool};
+use uuid::Uuid;
+
+use elementa_models::{ColumnMapping, SavedColumnMapping};
+
+use crate::{QueryTiming, TenantId};
+
+pub struct ColumnMappingRepository {
+    pool: PgPool,
+}
+
+impl ColumnMappingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// List a tenant's saved mappings by name
+    pub async fn list(&self, tenant: &TenantId) -> Result<Vec<SavedColumnMapping>> {
+        let rows: Vec<ColumnMappingRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, columns, created_at, updated_at
+            FROM column_mappings
+            WHERE tenant_id = $1
+            ORDER BY name
+            "#
+        )
+        .bind(tenant.as_str())
+        .fetch_all(&self.pool)
+        .timed("column_mapping", "list")
+        .await
+        .context("Failed to list column mappings")?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Find a tenant's saved mapping by name
+    pub async fn get(&self, tenant: &TenantId, name: &str) -> Result<Option<SavedColumnMapping>> {
+        let row: Option<ColumnMappingRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, columns, created_at, updated_at
+            FROM column_mappings
+            WHERE tenant_id = $1 AND name = $2
+            "#
+        )
+        .bind(tenant.as_str())
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .timed("column_mapping", "get")
+        .await
+        .context("Failed to get column mapping")?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    /// Save a tenant's mapping under `name`, replacing the one it saved under it before
+    pub async fn save(&self, tenant: &TenantId, name: &str, mapping: &ColumnMapping) -> Result<SavedColumnMapping> {
+        let row: ColumnMappingRow = sqlx::query_as(
+            r#"
+            INSERT INTO column_mappings (id, tenant_id, name, columns)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT ((COALESCE(tenant_id, '')), name)
+            DO UPDATE SET columns = EXCLUDED.columns, updated_at = NOW()
+            RETURNING id, name, columns, created_at, updated_at
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant.as_str())
+        .bind(name)
+        .bind(Json(mapping))
+        .fetch_one(&self.pool)
+        .timed("column_mapping", "save")
+        .await
+        .context("Failed to save column mapping")?;
+
+        Ok(row.into())
+    }
+
+    /// Delete the mapping a tenant saved under `name`
+    pub async fn delete(&self, tenant: &TenantId, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM column_mappings WHERE tenant_id = $1 AND name = $2")
+            .bind(tenant.as_str())
+            .bind(name)
+            .execute(&self.pool)
+            .timed("column_mapping", "delete")
+            .await
+            .context("Failed to delete column mapping")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct ColumnMappingRow {
+    id: Uuid,
+    name: String,
+    columns: Json<ColumnMapping>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ColumnMappingRow> for SavedColumnMapping {
+    fn from(row: ColumnMappingRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            mapping: row.columns.0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -4,49 +4,64 @@
 //! Uses runtime SQL queries to avoid requiring DATABASE_URL at compile time.
 
 use anyhow::{Context, Result};
-use chrono::Utc;
-use sqlx::{PgPool, FromRow};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgConnection, PgPool, FromRow};
 use uuid::Uuid;
 
 use elementa_models::{
-    ComplianceRecord, ValidationStatus,
+    ComplianceRecord, ValidationStatus, Versioned,
+    AuditAction, AuditEntry, ComplianceRecordDiff, MergePolicy,
+    Certification, CertificationStatus, ComplianceRecordId, ComponentId, SupplierId,
 };
 
+use super::bulk::{bulk_insert, live_ids, BulkInsert};
+use super::pagination::{into_page, keyset_bounds, page_limit};
+use super::{AuditRepository, BulkInsertReport, BulkRowError, BulkRowFailure, Cursor, Page};
+use crate::{PostgresPools, QueryTiming};
+
 pub struct ComplianceRepository {
     pool: PgPool,
+    reads: PostgresPools,
 }
 
 impl ComplianceRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { reads: pool.clone().into(), pool }
+    }
+    
+    /// Repository that sends listing and summary queries to the read replica
+    pub fn with_pools(pools: PostgresPools) -> Self {
+        Self { pool: pools.primary().clone(), reads: pools }
     }
     
     /// Find compliance record by ID
-    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<ComplianceRecord>> {
+    pub async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>> {
         let row: Option<ComplianceRow> = sqlx::query_as(
             r#"
             SELECT id, supplier_id, component_id, cas_records,
-                   test_results, certifications, submission_date,
-                   validation_status, audit_trail, created_at, updated_at
+                   test_results, certifications, exemptions, submission_date,
+                   validation_status, audit_trail, schema_version, created_at, updated_at
             FROM compliance_records
             WHERE id = $1
             "#
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .timed("compliance", "find_by_id")
         .await
         .context("Failed to fetch compliance record by ID")?;
         
-        Ok(row.map(|r| r.into()))
+        row.map(ComplianceRecord::try_from).transpose()
     }
     
     /// Find all compliance records for a supplier
-    pub async fn find_by_supplier(&self, supplier_id: Uuid) -> Result<Vec<ComplianceRecord>> {
+    pub async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>> {
         let rows: Vec<ComplianceRow> = sqlx::query_as(
             r#"
             SELECT id, supplier_id, component_id, cas_records,
-                   test_results, certifications, submission_date,
-                   validation_status, audit_trail, created_at, updated_at
+                   test_results, certifications, exemptions, submission_date,
+                   validation_status, audit_trail, schema_version, created_at, updated_at
             FROM compliance_records
             WHERE supplier_id = $1
             ORDER BY submission_date DESC
@@ -54,58 +69,129 @@ impl ComplianceRepository {
         )
         .bind(supplier_id)
         .fetch_all(&self.pool)
+        .timed("compliance", "find_by_supplier")
         .await
         .context("Failed to fetch compliance records by supplier")?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        rows.into_iter().map(ComplianceRecord::try_from).collect()
+    }
+    
+    /// One page of a supplier's compliance records, newest first
+    pub async fn find_page_by_supplier(&self, supplier_id: SupplierId, after: Option<Cursor>, limit: i64) -> Result<Page<ComplianceRecord>> {
+        let (after_created_at, after_id) = keyset_bounds(after);
+        let rows: Vec<ComplianceRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, supplier_id, component_id, cas_records,
+                       test_results, certifications, exemptions, submission_date,
+                       validation_status, audit_trail, schema_version, created_at, updated_at
+                FROM compliance_records
+                WHERE supplier_id = $1
+                  AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3))
+                ORDER BY created_at DESC, id DESC
+                LIMIT $4
+                "#
+            )
+            .bind(supplier_id)
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(page_limit(limit) + 1)
+            .fetch_all(&pool)
+            .timed("compliance", "find_page_by_supplier")
+            .await
+            .context("Failed to fetch page of compliance records by supplier")
+        }).await?;
+        
+        let records = rows.into_iter().map(ComplianceRecord::try_from).collect::<Result<Vec<_>>>()?;
+        Ok(into_page(records, limit, |r| Cursor::new(r.created_at, r.id)))
     }
     
     /// Find compliance records by validation status
     pub async fn find_by_status(&self, status: ValidationStatus) -> Result<Vec<ComplianceRecord>> {
         let status_str = serde_json::to_string(&status)?.trim_matches('"').to_string();
+        let status_str = status_str.as_str();
         
-        let rows: Vec<ComplianceRow> = sqlx::query_as(
-            r#"
-            SELECT id, supplier_id, component_id, cas_records,
-                   test_results, certifications, submission_date,
-                   validation_status, audit_trail, created_at, updated_at
-            FROM compliance_records
-            WHERE validation_status = $1
-            ORDER BY submission_date DESC
-            "#
-        )
-        .bind(&status_str)
-        .fetch_all(&self.pool)
-        .await
-        .context("Failed to fetch compliance records by status")?;
+        let rows: Vec<ComplianceRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, supplier_id, component_id, cas_records,
+                       test_results, certifications, exemptions, submission_date,
+                       validation_status, audit_trail, schema_version, created_at, updated_at
+                FROM compliance_records
+                WHERE validation_status = $1
+                ORDER BY submission_date DESC
+                "#
+            )
+            .bind(status_str)
+            .fetch_all(&pool)
+            .timed("compliance", "find_by_status")
+            .await
+            .context("Failed to fetch compliance records by status")
+        }).await?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        rows.into_iter().map(ComplianceRecord::try_from).collect()
     }
     
     /// Find compliance records containing PFAS
     pub async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>> {
+        let rows: Vec<ComplianceRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, supplier_id, component_id, cas_records,
+                       test_results, certifications, exemptions, submission_date,
+                       validation_status, audit_trail, schema_version, created_at, updated_at
+                FROM compliance_records
+                WHERE cas_records @> '[{"is_pfas": true}]'::jsonb
+                ORDER BY submission_date DESC
+                "#
+            )
+            .fetch_all(&pool)
+            .timed("compliance", "find_with_pfas")
+            .await
+            .context("Failed to fetch PFAS compliance records")
+        }).await?;
+        
+        rows.into_iter().map(ComplianceRecord::try_from).collect()
+    }
+    
+    /// Find certifications expired or expiring within `warning` of `now`,
+    /// soonest first; revoked certifications are left out
+    pub async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>> {
         let rows: Vec<ComplianceRow> = sqlx::query_as(
             r#"
             SELECT id, supplier_id, component_id, cas_records,
-                   test_results, certifications, submission_date,
-                   validation_status, audit_trail, created_at, updated_at
+                   test_results, certifications, exemptions, submission_date,
+                   validation_status, audit_trail, schema_version, created_at, updated_at
             FROM compliance_records
-            WHERE cas_records @> '[{"is_pfas": true}]'::jsonb
-            ORDER BY submission_date DESC
+            WHERE EXISTS (
+                SELECT 1 FROM jsonb_array_elements(certifications) c
+                WHERE c->>'expiry_date' IS NOT NULL
+                  AND c->>'revoked_at' IS NULL
+                  AND (c->>'expiry_date')::timestamptz <= $1
+            )
             "#
         )
+        .bind(now + warning)
         .fetch_all(&self.pool)
+        .timed("compliance", "find_expiring_certifications")
         .await
-        .context("Failed to fetch PFAS compliance records")?;
+        .context("Failed to fetch compliance records with expiring certifications")?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        let records = rows.into_iter().map(ComplianceRecord::try_from).collect::<Result<Vec<_>>>()?;
+        Ok(expiring_certifications(&records, now, warning))
     }
     
     /// Create new compliance record
     pub async fn create(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection")?;
+        Self::insert(&mut conn, &record).await
+    }
+    
+    async fn insert(conn: &mut PgConnection, record: &ComplianceRecord) -> Result<ComplianceRecord> {
         let cas_records = serde_json::to_value(&record.cas_records)?;
         let test_results = serde_json::to_value(&record.test_results)?;
         let certifications = serde_json::to_value(&record.certifications)?;
+        let exemptions = serde_json::to_value(&record.exemptions)?;
         let validation_status = serde_json::to_string(&record.validation_status)?;
         let audit_trail = serde_json::to_value(&record.audit_trail)?;
         let now = Utc::now();
@@ -114,12 +200,12 @@ impl ComplianceRepository {
             r#"
             INSERT INTO compliance_records 
                 (id, supplier_id, component_id, cas_records, test_results,
-                 certifications, submission_date, validation_status, 
-                 audit_trail, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 certifications, exemptions, submission_date, validation_status, 
+                 audit_trail, schema_version, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING id, supplier_id, component_id, cas_records,
-                      test_results, certifications, submission_date,
-                      validation_status, audit_trail, created_at, updated_at
+                      test_results, certifications, exemptions, submission_date,
+                      validation_status, audit_trail, schema_version, created_at, updated_at
             "#
         )
         .bind(record.id)
@@ -128,23 +214,39 @@ impl ComplianceRepository {
         .bind(&cas_records)
         .bind(&test_results)
         .bind(&certifications)
+        .bind(&exemptions)
         .bind(record.submission_date)
         .bind(validation_status.trim_matches('"'))
         .bind(&audit_trail)
+        .bind(ComplianceRecord::SCHEMA_VERSION as i32)
         .bind(now)
         .bind(now)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .timed("compliance", "insert")
         .await
         .context("Failed to create compliance record")?;
         
-        Ok(row.into())
+        row.try_into()
+    }
+    
+    /// Create compliance records in batches, reporting each one that could
+    /// not be created, e.g. because its supplier or component does not exist.
+    /// Records are stored as given; resubmissions are not merged.
+    pub async fn bulk_create(&self, records: Vec<ComplianceRecord>) -> BulkInsertReport<ComplianceRecord> {
+        bulk_insert::<Self>(&self.pool, &records).await
     }
     
     /// Update compliance record
     pub async fn update(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection")?;
+        Self::save(&mut conn, &record).await
+    }
+    
+    async fn save(conn: &mut PgConnection, record: &ComplianceRecord) -> Result<ComplianceRecord> {
         let cas_records = serde_json::to_value(&record.cas_records)?;
         let test_results = serde_json::to_value(&record.test_results)?;
         let certifications = serde_json::to_value(&record.certifications)?;
+        let exemptions = serde_json::to_value(&record.exemptions)?;
         let validation_status = serde_json::to_string(&record.validation_status)?;
         let audit_trail = serde_json::to_value(&record.audit_trail)?;
         
@@ -154,34 +256,99 @@ impl ComplianceRepository {
                 cas_records = $2,
                 test_results = $3,
                 certifications = $4,
-                validation_status = $5,
-                audit_trail = $6,
-                updated_at = $7
+                exemptions = $5,
+                submission_date = $6,
+                validation_status = $7,
+                audit_trail = $8,
+                schema_version = $9,
+                updated_at = $10
             WHERE id = $1
             RETURNING id, supplier_id, component_id, cas_records,
-                      test_results, certifications, submission_date,
-                      validation_status, audit_trail, created_at, updated_at
+                      test_results, certifications, exemptions, submission_date,
+                      validation_status, audit_trail, schema_version, created_at, updated_at
             "#
         )
         .bind(record.id)
         .bind(&cas_records)
         .bind(&test_results)
         .bind(&certifications)
+        .bind(&exemptions)
+        .bind(record.submission_date)
         .bind(validation_status.trim_matches('"'))
         .bind(&audit_trail)
+        .bind(ComplianceRecord::SCHEMA_VERSION as i32)
         .bind(Utc::now())
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .timed("compliance", "save")
         .await
         .context("Failed to update compliance record")?;
         
-        Ok(row.into())
+        row.try_into()
+    }
+    
+    /// Record a supplier's submission for a component. A resubmission is
+    /// merged into the latest record for the pair under `policy` rather than
+    /// stored alongside it; either way the audit log gets an entry.
+    pub async fn resubmit(
+        &self,
+        submission: ComplianceRecord,
+        policy: MergePolicy,
+        user_id: Option<Uuid>,
+    ) -> Result<ComplianceResubmission> {
+        let mut tx = self.pool.begin().await.context("Failed to start resubmission")?;
+        
+        let current: Option<ComplianceRow> = sqlx::query_as(
+            r#"
+            SELECT id, supplier_id, component_id, cas_records,
+                   test_results, certifications, exemptions, submission_date,
+                   validation_status, audit_trail, schema_version, created_at, updated_at
+            FROM compliance_records
+            WHERE supplier_id = $1 AND component_id = $2
+            ORDER BY submission_date DESC
+            LIMIT 1
+            FOR UPDATE
+            "#
+        )
+        .bind(submission.supplier_id)
+        .bind(submission.component_id)
+        .fetch_optional(&mut *tx)
+        .timed("compliance", "resubmit")
+        .await
+        .context("Failed to fetch compliance record for resubmission")?;
+        
+        let (record, diff, entry) = match current {
+            Some(row) => {
+                let mut record = ComplianceRecord::try_from(row)?;
+                let merge = record.merge_resubmission(&submission, policy, user_id);
+                (Self::save(&mut tx, &record).await?, Some(merge.diff), merge.audit_entry)
+            }
+            None => {
+                let entry = AuditEntry::new(
+                    AuditAction::ComplianceRecordCreated,
+                    "compliance_record".to_string(),
+                    submission.id.into(),
+                    user_id,
+                    None,
+                );
+                let mut record = submission;
+                record.audit_trail.push(entry.clone());
+                (Self::insert(&mut tx, &record).await?, None, entry)
+            }
+        };
+        let previous_hash = AuditRepository::latest_hash(&mut tx).await?;
+        let audit_entry = AuditRepository::insert(&mut tx, entry, previous_hash).await?;
+        
+        tx.commit().await.context("Failed to commit resubmission")?;
+        
+        Ok(ComplianceResubmission { record, diff, audit_entry })
     }
     
     /// Delete compliance record
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+    pub async fn delete(&self, id: ComplianceRecordId) -> Result<bool> {
         let result = sqlx::query("DELETE FROM compliance_records WHERE id = $1")
             .bind(id)
             .execute(&self.pool)
+            .timed("compliance", "delete")
             .await
             .context("Failed to delete compliance record")?;
         
@@ -190,72 +357,248 @@ impl ComplianceRepository {
     
     /// Get compliance summary statistics
     pub async fn get_summary_stats(&self) -> Result<ComplianceSummary> {
-        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM compliance_records")
-            .fetch_one(&self.pool)
+        self.reads.read(|pool| async move {
+            let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM compliance_records")
+                .fetch_one(&pool)
+                .timed("compliance", "get_summary_stats")
+                .await?;
+            
+            let validated: (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM compliance_records WHERE validation_status = 'Valid'"
+            )
+            .fetch_one(&pool)
+            .timed("compliance", "get_summary_stats")
             .await?;
+            
+            let pending: (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM compliance_records WHERE validation_status = 'Pending'"
+            )
+            .fetch_one(&pool)
+            .timed("compliance", "get_summary_stats")
+            .await?;
+            
+            let pfas_count: (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM compliance_records WHERE cas_records @> '[{\"is_pfas\": true}]'::jsonb"
+            )
+            .fetch_one(&pool)
+            .timed("compliance", "get_summary_stats")
+            .await?;
+            
+            Ok(ComplianceSummary {
+                total_records: total.0,
+                validated_records: validated.0,
+                pending_records: pending.0,
+                pfas_detected_count: pfas_count.0,
+            })
+        }).await
+    }
+}
+
+/// Certifications among `records` that expire within `warning` of `now`, soonest first
+pub(crate) fn expiring_certifications<'a>(
+    records: impl IntoIterator<Item = &'a ComplianceRecord>,
+    now: DateTime<Utc>,
+    warning: Duration,
+) -> Vec<ExpiringCertification> {
+    let mut expiring = Vec::new();
+    for record in records {
+        for certification in record.certifications_needing_renewal(now, warning) {
+            expiring.push(ExpiringCertification {
+                compliance_record_id: record.id,
+                supplier_id: record.supplier_id,
+                component_id: record.component_id,
+                status: certification.status_at(now, warning),
+                certification: certification.clone(),
+            });
+        }
+    }
+    expiring.sort_by_key(|e| e.certification.expiry_date);
+    expiring
+}
+
+#[async_trait]
+impl BulkInsert for ComplianceRepository {
+    type Item = ComplianceRecord;
+    
+    fn id(record: &ComplianceRecord) -> Uuid {
+        record.id.into()
+    }
+    
+    async fn insert_batch(
+        conn: &mut PgConnection,
+        rows: Vec<(usize, &ComplianceRecord)>,
+    ) -> Result<(Vec<ComplianceRecord>, Vec<BulkRowFailure>)> {
+        let supplier_ids = rows.iter().map(|(_, r)| r.supplier_id.into()).collect();
+        let component_ids = rows.iter().map(|(_, r)| r.component_id.into()).collect();
+        let known_suppliers = live_ids(conn, "suppliers", supplier_ids).await?;
+        let known_components = live_ids(conn, "components", component_ids).await?;
+        let mut columns = ComplianceColumns::default();
+        let mut failed = Vec::new();
         
-        let validated: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM compliance_records WHERE validation_status = 'Valid'"
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        for (index, record) in rows {
+            if !known_suppliers.contains(record.supplier_id.as_uuid()) {
+                failed.push(BulkRowFailure { index, error: BulkRowError::MissingSupplier(record.supplier_id) });
+                continue;
+            }
+            if !known_components.contains(record.component_id.as_uuid()) {
+                failed.push(BulkRowFailure { index, error: BulkRowError::MissingComponent(record.component_id) });
+                continue;
+            }
+            if let Err(e) = columns.push(record) {
+                failed.push(BulkRowFailure { index, error: BulkRowError::Encoding(e.to_string()) });
+            }
+        }
         
-        let pending: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM compliance_records WHERE validation_status = 'Pending'"
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        if columns.id.is_empty() {
+            return Ok((Vec::new(), failed));
+        }
         
-        let pfas_count: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM compliance_records WHERE cas_records @> '[{\"is_pfas\": true}]'::jsonb"
+        let now = Utc::now();
+        let rows: Vec<ComplianceRow> = sqlx::query_as(
+            r#"
+            INSERT INTO compliance_records 
+                (id, supplier_id, component_id, cas_records, test_results,
+                 certifications, exemptions, submission_date, validation_status, 
+                 audit_trail, schema_version, created_at, updated_at)
+            SELECT id, supplier_id, component_id, cas_records, test_results,
+                   certifications, exemptions, submission_date, validation_status,
+                   audit_trail, $11, $12, $12
+            FROM UNNEST($1::uuid[], $2::uuid[], $3::uuid[], $4::jsonb[], $5::jsonb[],
+                        $6::jsonb[], $7::jsonb[], $8::timestamptz[], $9::text[], $10::jsonb[])
+                AS t(id, supplier_id, component_id, cas_records, test_results,
+                     certifications, exemptions, submission_date, validation_status, audit_trail)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING id, supplier_id, component_id, cas_records,
+                      test_results, certifications, exemptions, submission_date,
+                      validation_status, audit_trail, schema_version, created_at, updated_at
+            "#
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .bind(columns.id)
+        .bind(columns.supplier_id)
+        .bind(columns.component_id)
+        .bind(columns.cas_records)
+        .bind(columns.test_results)
+        .bind(columns.certifications)
+        .bind(columns.exemptions)
+        .bind(columns.submission_date)
+        .bind(columns.validation_status)
+        .bind(columns.audit_trail)
+        .bind(ComplianceRecord::SCHEMA_VERSION as i32)
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .timed("compliance", "insert_batch")
+        .await
+        .context("Failed to bulk create compliance records")?;
+        
+        let inserted = rows.into_iter().map(ComplianceRecord::try_from).collect::<Result<_>>()?;
+        Ok((inserted, failed))
+    }
+}
+
+/// Compliance record fields as one array per column, for `UNNEST`
+#[derive(Default)]
+struct ComplianceColumns {
+    id: Vec<Uuid>,
+    supplier_id: Vec<Uuid>,
+    component_id: Vec<Uuid>,
+    cas_records: Vec<serde_json::Value>,
+    test_results: Vec<serde_json::Value>,
+    certifications: Vec<serde_json::Value>,
+    exemptions: Vec<serde_json::Value>,
+    submission_date: Vec<DateTime<Utc>>,
+    validation_status: Vec<String>,
+    audit_trail: Vec<serde_json::Value>,
+}
+
+impl ComplianceColumns {
+    /// Append a record, leaving the columns unchanged if it cannot be encoded
+    fn push(&mut self, record: &ComplianceRecord) -> serde_json::Result<()> {
+        let cas_records = serde_json::to_value(&record.cas_records)?;
+        let test_results = serde_json::to_value(&record.test_results)?;
+        let certifications = serde_json::to_value(&record.certifications)?;
+        let exemptions = serde_json::to_value(&record.exemptions)?;
+        let validation_status = serde_json::to_string(&record.validation_status)?;
+        let audit_trail = serde_json::to_value(&record.audit_trail)?;
         
-        Ok(ComplianceSummary {
-            total_records: total.0,
-            validated_records: validated.0,
-            pending_records: pending.0,
-            pfas_detected_count: pfas_count.0,
-        })
+        self.id.push(record.id.into());
+        self.supplier_id.push(record.supplier_id.into());
+        self.component_id.push(record.component_id.into());
+        self.cas_records.push(cas_records);
+        self.test_results.push(test_results);
+        self.certifications.push(certifications);
+        self.exemptions.push(exemptions);
+        self.submission_date.push(record.submission_date);
+        self.validation_status.push(validation_status.trim_matches('"').to_string());
+        self.audit_trail.push(audit_trail);
+        Ok(())
     }
 }
 
 /// Internal row type for SQLx mapping
 #[derive(Debug, FromRow)]
 struct ComplianceRow {
-    id: Uuid,
-    supplier_id: Uuid,
-    component_id: Uuid,
+    id: ComplianceRecordId,
+    supplier_id: SupplierId,
+    component_id: ComponentId,
     cas_records: serde_json::Value,
     test_results: serde_json::Value,
     certifications: serde_json::Value,
+    exemptions: serde_json::Value,
     submission_date: chrono::DateTime<Utc>,
     validation_status: String,
     audit_trail: serde_json::Value,
+    schema_version: i32,
     created_at: chrono::DateTime<Utc>,
     updated_at: chrono::DateTime<Utc>,
 }
 
-impl From<ComplianceRow> for ComplianceRecord {
-    fn from(row: ComplianceRow) -> Self {
-        Self {
-            id: row.id,
-            supplier_id: row.supplier_id,
-            component_id: row.component_id,
-            cas_records: serde_json::from_value(row.cas_records).unwrap_or_default(),
-            test_results: serde_json::from_value(row.test_results).unwrap_or_default(),
-            certifications: serde_json::from_value(row.certifications).unwrap_or_default(),
-            submission_date: row.submission_date,
-            validation_status: serde_json::from_str(&format!("\"{}\"", row.validation_status))
-                .unwrap_or(ValidationStatus::Pending),
-            audit_trail: serde_json::from_value(row.audit_trail).unwrap_or_default(),
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-        }
+/// Rows are read through the record's schema version so older shapes are
+/// upgraded rather than defaulted
+impl TryFrom<ComplianceRow> for ComplianceRecord {
+    type Error = anyhow::Error;
+    
+    fn try_from(row: ComplianceRow) -> Result<Self> {
+        let id = row.id;
+        let record = serde_json::json!({
+            "schema_version": row.schema_version,
+            "id": row.id,
+            "supplier_id": row.supplier_id,
+            "component_id": row.component_id,
+            "cas_records": row.cas_records,
+            "test_results": row.test_results,
+            "certifications": row.certifications,
+            "exemptions": row.exemptions,
+            "submission_date": row.submission_date,
+            "validation_status": row.validation_status,
+            "audit_trail": row.audit_trail,
+            "created_at": row.created_at,
+            "updated_at": row.updated_at,
+        });
+        
+        ComplianceRecord::from_versioned(record)
+            .with_context(|| format!("Failed to read compliance record {}", id))
     }
 }
 
+/// Outcome of recording a submission through `ComplianceRepository::resubmit`
+#[derive(Debug, Clone)]
+pub struct ComplianceResubmission {
+    pub record: ComplianceRecord,
+    /// What the resubmission changed; `None` for a first submission
+    pub diff: Option<ComplianceRecordDiff>,
+    pub audit_entry: AuditEntry,
+}
+
+/// A certification that needs renewing, with the record it belongs to
+#[derive(Debug, Clone)]
+pub struct ExpiringCertification {
+    pub compliance_record_id: ComplianceRecordId,
+    pub supplier_id: SupplierId,
+    pub component_id: ComponentId,
+    pub certification: Certification,
+    pub status: CertificationStatus,
+}
+
 /// Compliance summary statistics
 #[derive(Debug, Clone)]
 pub struct ComplianceSummary {
@@ -276,7 +619,7 @@ mod tests {
         fn prop_audit_trail_immutable(
             _cas_number in "[0-9]{2,7}-[0-9]{2}-[0-9]",
         ) {
-            let record = ComplianceRecord::new(Uuid::new_v4(), Uuid::new_v4());
+            let record = ComplianceRecord::new(SupplierId::new(), ComponentId::new());
             
             // Audit trail should be empty initially
             prop_assert!(record.audit_trail.is_empty());
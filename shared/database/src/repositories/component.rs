@@ -1,13 +1,25 @@
 //! Component Repository  
 //!
-//! CRUD operations for component records.
+//! CRUD operations for component records and the bill of materials
+//! links between assemblies and their parts.
 
-use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::{PgPool, FromRow};
+use sqlx::{PgConnection, PgPool, FromRow};
 use uuid::Uuid;
 
-use elementa_models::Component;
+use elementa_models::{
+    AssemblyLink, AuditAction, AuditEntry, Component, ComponentId, ComponentTree, Lifecycle, LifecycleStatus,
+    SupplierId,
+};
+
+use super::bulk::{bulk_insert, live_ids, BulkInsert};
+use super::search::search_limit;
+use super::{AuditRepository, BulkInsertReport, BulkRowError, BulkRowFailure, SearchHit};
+use crate::QueryTiming;
 
 pub struct ComponentRepository {
     pool: PgPool,
@@ -19,34 +31,76 @@ impl ComponentRepository {
     }
     
     /// Find component by ID
-    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Component>> {
+    pub async fn find_by_id(&self, id: ComponentId) -> Result<Option<Component>> {
         let row: Option<ComponentRow> = sqlx::query_as(
             r#"
             SELECT id, part_number, description, cas_numbers, material_type,
-                   supplier_id, specifications, created_at, updated_at
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
+            FROM components
+            WHERE id = $1 AND lifecycle_status <> 'deleted'
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .timed("component", "find_by_id")
+        .await
+        .context("Failed to fetch component by ID")?;
+        
+        Ok(row.map(|r| r.into()))
+    }
+    
+    /// Find component by ID even if it has been deleted, for audit and traceability
+    pub async fn find_by_id_including_deleted(&self, id: ComponentId) -> Result<Option<Component>> {
+        let row: Option<ComponentRow> = sqlx::query_as(
+            r#"
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
             FROM components
             WHERE id = $1
             "#
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .timed("component", "find_by_id_including_deleted")
         .await
         .context("Failed to fetch component by ID")?;
         
         Ok(row.map(|r| r.into()))
     }
     
-    /// Find all components
+    /// Find components in one lifecycle status, including deleted ones
+    pub async fn find_by_lifecycle_status(&self, status: LifecycleStatus) -> Result<Vec<Component>> {
+        let rows: Vec<ComponentRow> = sqlx::query_as(
+            r#"
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
+            FROM components
+            WHERE lifecycle_status = $1
+            ORDER BY part_number
+            "#
+        )
+        .bind(status.as_str())
+        .fetch_all(&self.pool)
+        .timed("component", "find_by_lifecycle_status")
+        .await
+        .context("Failed to fetch components by lifecycle status")?;
+        
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+    
+    /// Find all components that have not been deleted
     pub async fn find_all(&self) -> Result<Vec<Component>> {
         let rows: Vec<ComponentRow> = sqlx::query_as(
             r#"
             SELECT id, part_number, description, cas_numbers, material_type,
-                   supplier_id, specifications, created_at, updated_at
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
             FROM components
+            WHERE lifecycle_status <> 'deleted'
             ORDER BY part_number
             "#
         )
         .fetch_all(&self.pool)
+        .timed("component", "find_all")
         .await
         .context("Failed to fetch all components")?;
         
@@ -54,24 +108,55 @@ impl ComponentRepository {
     }
     
     /// Find components by supplier
-    pub async fn find_by_supplier(&self, supplier_id: Uuid) -> Result<Vec<Component>> {
+    pub async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<Component>> {
         let rows: Vec<ComponentRow> = sqlx::query_as(
             r#"
             SELECT id, part_number, description, cas_numbers, material_type,
-                   supplier_id, specifications, created_at, updated_at
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
             FROM components
-            WHERE supplier_id = $1
+            WHERE supplier_id = $1 AND lifecycle_status <> 'deleted'
             ORDER BY part_number
             "#
         )
         .bind(supplier_id)
         .fetch_all(&self.pool)
+        .timed("component", "find_by_supplier")
         .await
         .context("Failed to fetch components by supplier")?;
         
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
     
+    /// Search components by part number and description, best matches first.
+    /// Part number similarity is added to the full-text rank so fragments
+    /// like `LM317` still find `LM317T-DG`.
+    pub async fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit<Component>>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let rows: Vec<ComponentSearchRow> = sqlx::query_as(
+            r#"
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at,
+                   ts_rank(search_vector, ts_query) + word_similarity($1, part_number) AS rank
+            FROM components, websearch_to_tsquery('simple', $1) AS ts_query
+            WHERE (search_vector @@ ts_query OR part_number %> $1) AND lifecycle_status <> 'deleted'
+            ORDER BY rank DESC, part_number
+            LIMIT $2
+            "#
+        )
+        .bind(query)
+        .bind(search_limit(limit))
+        .fetch_all(&self.pool)
+        .timed("component", "search")
+        .await
+        .context("Failed to search components")?;
+        
+        Ok(rows.into_iter().map(|row| SearchHit { rank: row.rank, item: row.component.into() }).collect())
+    }
+    
     /// Create new component
     pub async fn create(&self, component: Component) -> Result<Component> {
         let cas_numbers = serde_json::to_value(&component.cas_numbers)?;
@@ -83,10 +168,10 @@ impl ComponentRepository {
             r#"
             INSERT INTO components 
                 (id, part_number, description, cas_numbers, material_type,
-                 supplier_id, specifications, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             RETURNING id, part_number, description, cas_numbers, material_type,
-                      supplier_id, specifications, created_at, updated_at
+                      supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
             "#
         )
         .bind(component.id)
@@ -96,17 +181,31 @@ impl ComponentRepository {
         .bind(material_type.trim_matches('"'))
         .bind(component.supplier_id)
         .bind(&specifications)
+        .bind(component.lifecycle_status.as_str())
+        .bind(component.deleted_at)
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
+        .timed("component", "create")
         .await
         .context("Failed to create component")?;
         
         Ok(row.into())
     }
     
+    /// Create components in batches, reporting each one that could not be
+    /// created, e.g. because its supplier does not exist
+    pub async fn bulk_create(&self, components: Vec<Component>) -> BulkInsertReport<Component> {
+        bulk_insert::<Self>(&self.pool, &components).await
+    }
+    
     /// Update component
     pub async fn update(&self, component: Component) -> Result<Component> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection")?;
+        Self::save(&mut conn, &component).await
+    }
+    
+    async fn save(conn: &mut PgConnection, component: &Component) -> Result<Component> {
         let cas_numbers = serde_json::to_value(&component.cas_numbers)?;
         let material_type = serde_json::to_string(&component.material_type)?;
         let specifications = serde_json::to_value(&component.specifications)?;
@@ -119,10 +218,12 @@ impl ComponentRepository {
                 cas_numbers = $4,
                 material_type = $5,
                 specifications = $6,
-                updated_at = $7
+                lifecycle_status = $7,
+                deleted_at = $8,
+                updated_at = $9
             WHERE id = $1
             RETURNING id, part_number, description, cas_numbers, material_type,
-                      supplier_id, specifications, created_at, updated_at
+                      supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
             "#
         )
         .bind(component.id)
@@ -131,39 +232,365 @@ impl ComponentRepository {
         .bind(&cas_numbers)
         .bind(material_type.trim_matches('"'))
         .bind(&specifications)
+        .bind(component.lifecycle_status.as_str())
+        .bind(component.deleted_at)
         .bind(Utc::now())
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
+        .timed("component", "save")
         .await
         .context("Failed to update component")?;
         
         Ok(row.into())
     }
     
-    /// Delete component
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM components WHERE id = $1")
-            .bind(id)
+    /// Soft-delete component; the row and its assembly links are kept for
+    /// traceability, but it drops out of assemblies loaded with `find_tree`
+    pub async fn delete(&self, id: ComponentId) -> Result<bool> {
+        Ok(self.change_lifecycle_status(id, LifecycleStatus::Deleted, None).await?.is_some())
+    }
+    
+    /// Archive a component that is no longer in use
+    pub async fn archive(&self, id: ComponentId, user_id: Option<Uuid>) -> Result<Option<Component>> {
+        self.change_lifecycle_status(id, LifecycleStatus::Archived, user_id).await
+    }
+    
+    /// Bring an archived component back into use
+    pub async fn restore(&self, id: ComponentId, user_id: Option<Uuid>) -> Result<Option<Component>> {
+        self.change_lifecycle_status(id, LifecycleStatus::Active, user_id).await
+    }
+    
+    /// Move a component to another lifecycle status and record the change in
+    /// the audit trail; `None` if the component does not exist or is deleted
+    pub async fn change_lifecycle_status(
+        &self,
+        id: ComponentId,
+        status: LifecycleStatus,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<Component>> {
+        let mut tx = self.pool.begin().await.context("Failed to start component lifecycle change")?;
+        
+        let row: Option<ComponentRow> = sqlx::query_as(
+            r#"
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
+            FROM components
+            WHERE id = $1 AND lifecycle_status <> 'deleted'
+            FOR UPDATE
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .timed("component", "change_lifecycle_status")
+        .await
+        .context("Failed to fetch component for lifecycle change")?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        
+        let mut component = Component::from(row);
+        let change = component.transition_to(status, Utc::now()).map_err(anyhow::Error::msg)?;
+        let component = Self::save(&mut tx, &component).await?;
+        
+        let entry = AuditEntry::new(AuditAction::LifecycleChanged, "component".to_string(), id.into(), user_id, None)
+            .with_details(vec![change], HashMap::new());
+        let previous_hash = AuditRepository::latest_hash(&mut tx).await?;
+        AuditRepository::insert(&mut tx, entry, previous_hash).await?;
+        
+        tx.commit().await.context("Failed to commit component lifecycle change")?;
+        Ok(Some(component))
+    }
+    
+    /// Find components by ID, leaving out deleted ones
+    pub async fn find_by_ids(&self, ids: &[ComponentId]) -> Result<Vec<Component>> {
+        let rows: Vec<ComponentRow> = sqlx::query_as(
+            r#"
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
+            FROM components
+            WHERE id = ANY($1) AND lifecycle_status <> 'deleted'
+            "#
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .timed("component", "find_by_ids")
+        .await
+        .context("Failed to fetch components by ID")?;
+        
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+    
+    /// Put `quantity` units of `child_id` into the assembly `parent_id`,
+    /// replacing the quantity if the part is already in it. Refuses links
+    /// that would make an assembly contain itself.
+    pub async fn add_child(&self, parent_id: ComponentId, child_id: ComponentId, quantity: f64) -> Result<AssemblyLink> {
+        if quantity.is_nan() || quantity <= 0.0 {
+            bail!("Quantity must be positive");
+        }
+        if parent_id == child_id || self.find_ancestor_ids(parent_id).await?.contains(&child_id) {
+            bail!("Component {} already contains {}", child_id, parent_id);
+        }
+        
+        let link: AssemblyLink = sqlx::query_as(
+            r#"
+            INSERT INTO component_links (parent_id, child_id, quantity, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (parent_id, child_id) DO UPDATE SET quantity = EXCLUDED.quantity
+            RETURNING parent_id, child_id, quantity, created_at
+            "#
+        )
+        .bind(parent_id)
+        .bind(child_id)
+        .bind(quantity)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .timed("component", "add_child")
+        .await
+        .context("Failed to link component")?;
+        
+        Ok(link)
+    }
+    
+    /// Take a part out of an assembly
+    pub async fn remove_child(&self, parent_id: ComponentId, child_id: ComponentId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM component_links WHERE parent_id = $1 AND child_id = $2")
+            .bind(parent_id)
+            .bind(child_id)
             .execute(&self.pool)
+            .timed("component", "remove_child")
             .await
-            .context("Failed to delete component")?;
+            .context("Failed to unlink component")?;
         
         Ok(result.rows_affected() > 0)
     }
+    
+    /// Find the direct parts of an assembly
+    pub async fn find_children(&self, parent_id: ComponentId) -> Result<Vec<AssemblyLink>> {
+        let links: Vec<AssemblyLink> = sqlx::query_as(
+            r#"
+            SELECT parent_id, child_id, quantity, created_at
+            FROM component_links
+            WHERE parent_id = $1
+            "#
+        )
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .timed("component", "find_children")
+        .await
+        .context("Failed to fetch component children")?;
+        
+        Ok(links)
+    }
+    
+    /// Find the assemblies a component is used in directly
+    pub async fn find_parents(&self, child_id: ComponentId) -> Result<Vec<AssemblyLink>> {
+        let links: Vec<AssemblyLink> = sqlx::query_as(
+            r#"
+            SELECT parent_id, child_id, quantity, created_at
+            FROM component_links
+            WHERE child_id = $1
+            "#
+        )
+        .bind(child_id)
+        .fetch_all(&self.pool)
+        .timed("component", "find_parents")
+        .await
+        .context("Failed to fetch component parents")?;
+        
+        Ok(links)
+    }
+    
+    /// Find every link below an assembly, at any depth
+    pub async fn find_descendant_links(&self, root_id: ComponentId) -> Result<Vec<AssemblyLink>> {
+        // UNION rather than UNION ALL stops at links already visited, so a cycle cannot loop forever
+        let links: Vec<AssemblyLink> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE bom AS (
+                SELECT parent_id, child_id, quantity, created_at
+                FROM component_links
+                WHERE parent_id = $1
+                UNION
+                SELECT l.parent_id, l.child_id, l.quantity, l.created_at
+                FROM component_links l
+                JOIN bom ON l.parent_id = bom.child_id
+            )
+            SELECT parent_id, child_id, quantity, created_at FROM bom
+            "#
+        )
+        .bind(root_id)
+        .fetch_all(&self.pool)
+        .timed("component", "find_descendant_links")
+        .await
+        .context("Failed to fetch bill of materials")?;
+        
+        Ok(links)
+    }
+    
+    /// Find every assembly a component ends up in, at any level ("where used")
+    pub async fn find_ancestor_ids(&self, component_id: ComponentId) -> Result<Vec<ComponentId>> {
+        let ids: Vec<(ComponentId,)> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE used_in AS (
+                SELECT parent_id FROM component_links WHERE child_id = $1
+                UNION
+                SELECT l.parent_id
+                FROM component_links l
+                JOIN used_in ON l.child_id = used_in.parent_id
+            )
+            SELECT parent_id FROM used_in
+            "#
+        )
+        .bind(component_id)
+        .fetch_all(&self.pool)
+        .timed("component", "find_ancestor_ids")
+        .await
+        .context("Failed to fetch assemblies using component")?;
+        
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+    
+    /// Load an assembly with everything below it; `None` if the component does not exist
+    pub async fn find_tree(&self, root_id: ComponentId) -> Result<Option<ComponentTree>> {
+        let links = self.find_descendant_links(root_id).await?;
+        let mut ids: Vec<ComponentId> = links.iter().map(|l| l.child_id).collect();
+        ids.push(root_id);
+        ids.sort();
+        ids.dedup();
+        
+        let components = self.find_by_ids(&ids).await?;
+        if !components.iter().any(|c| c.id == root_id) {
+            return Ok(None);
+        }
+        
+        ComponentTree::build(root_id, &components, &links)
+            .map(Some)
+            .map_err(anyhow::Error::msg)
+    }
+}
+
+#[async_trait]
+impl BulkInsert for ComponentRepository {
+    type Item = Component;
+    
+    fn id(component: &Component) -> Uuid {
+        component.id.into()
+    }
+    
+    async fn insert_batch(
+        conn: &mut PgConnection,
+        rows: Vec<(usize, &Component)>,
+    ) -> Result<(Vec<Component>, Vec<BulkRowFailure>)> {
+        let supplier_ids = rows.iter().map(|(_, c)| c.supplier_id.into()).collect();
+        let known_suppliers = live_ids(conn, "suppliers", supplier_ids).await?;
+        let mut columns = ComponentColumns::default();
+        let mut failed = Vec::new();
+        
+        for (index, component) in rows {
+            if !known_suppliers.contains(component.supplier_id.as_uuid()) {
+                failed.push(BulkRowFailure { index, error: BulkRowError::MissingSupplier(component.supplier_id) });
+                continue;
+            }
+            if let Err(e) = columns.push(component) {
+                failed.push(BulkRowFailure { index, error: BulkRowError::Encoding(e.to_string()) });
+            }
+        }
+        
+        if columns.id.is_empty() {
+            return Ok((Vec::new(), failed));
+        }
+        
+        let now = Utc::now();
+        let rows: Vec<ComponentRow> = sqlx::query_as(
+            r#"
+            INSERT INTO components 
+                (id, part_number, description, cas_numbers, material_type,
+                 supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at)
+            SELECT id, part_number, description, cas_numbers, material_type,
+                   supplier_id, specifications, lifecycle_status, deleted_at, $10, $10
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::jsonb[], $5::text[],
+                        $6::uuid[], $7::jsonb[], $8::text[], $9::timestamptz[])
+                AS t(id, part_number, description, cas_numbers, material_type,
+                     supplier_id, specifications, lifecycle_status, deleted_at)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING id, part_number, description, cas_numbers, material_type,
+                      supplier_id, specifications, lifecycle_status, deleted_at, created_at, updated_at
+            "#
+        )
+        .bind(columns.id)
+        .bind(columns.part_number)
+        .bind(columns.description)
+        .bind(columns.cas_numbers)
+        .bind(columns.material_type)
+        .bind(columns.supplier_id)
+        .bind(columns.specifications)
+        .bind(columns.lifecycle_status)
+        .bind(columns.deleted_at)
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .timed("component", "insert_batch")
+        .await
+        .context("Failed to bulk create components")?;
+        
+        Ok((rows.into_iter().map(|r| r.into()).collect(), failed))
+    }
+}
+
+/// Component fields as one array per column, for `UNNEST`
+#[derive(Default)]
+struct ComponentColumns {
+    id: Vec<Uuid>,
+    part_number: Vec<String>,
+    description: Vec<String>,
+    cas_numbers: Vec<serde_json::Value>,
+    material_type: Vec<String>,
+    supplier_id: Vec<Uuid>,
+    specifications: Vec<serde_json::Value>,
+    lifecycle_status: Vec<String>,
+    deleted_at: Vec<Option<chrono::DateTime<Utc>>>,
+}
+
+impl ComponentColumns {
+    /// Append a component, leaving the columns unchanged if it cannot be encoded
+    fn push(&mut self, component: &Component) -> serde_json::Result<()> {
+        let cas_numbers = serde_json::to_value(&component.cas_numbers)?;
+        let material_type = serde_json::to_string(&component.material_type)?;
+        let specifications = serde_json::to_value(&component.specifications)?;
+        
+        self.id.push(component.id.into());
+        self.part_number.push(component.part_number.clone());
+        self.description.push(component.description.clone());
+        self.cas_numbers.push(cas_numbers);
+        self.material_type.push(material_type.trim_matches('"').to_string());
+        self.supplier_id.push(component.supplier_id.into());
+        self.specifications.push(specifications);
+        self.lifecycle_status.push(component.lifecycle_status.as_str().to_string());
+        self.deleted_at.push(component.deleted_at);
+        Ok(())
+    }
 }
 
 #[derive(Debug, FromRow)]
 struct ComponentRow {
-    id: Uuid,
+    id: ComponentId,
     part_number: String,
     description: String,
     cas_numbers: serde_json::Value,
     material_type: String,
-    supplier_id: Uuid,
+    supplier_id: SupplierId,
     specifications: serde_json::Value,
+    lifecycle_status: String,
+    deleted_at: Option<chrono::DateTime<Utc>>,
     created_at: chrono::DateTime<Utc>,
     updated_at: chrono::DateTime<Utc>,
 }
 
+/// Component row with its search rank
+#[derive(Debug, FromRow)]
+struct ComponentSearchRow {
+    #[sqlx(flatten)]
+    component: ComponentRow,
+    rank: f32,
+}
+
 impl From<ComponentRow> for Component {
     fn from(row: ComponentRow) -> Self {
         use elementa_models::MaterialType;
@@ -178,6 +605,9 @@ impl From<ComponentRow> for Component {
             supplier_id: row.supplier_id,
             specifications: serde_json::from_value(row.specifications)
                 .unwrap_or_default(),
+            lifecycle_status: serde_json::from_str(&format!("\"{}\"", row.lifecycle_status))
+                .unwrap_or_default(),
+            deleted_at: row.deleted_at,
             created_at: row.created_at,
             updated_at: row.updated_at,
         }
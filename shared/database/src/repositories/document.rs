@@ -0,0 +1,356 @@
+//! Document Repository
+//!
+//! Stores uploaded document content in a MongoDB GridFS bucket and its
+//! metadata in a companion collection, both keyed by document ID. Content
+//! is streamed in and out chunk by chunk, so large scanned reports are never
+//! held in memory whole.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use futures_util::TryStreamExt;
+use mongodb::bson::{self, doc, Bson};
+use mongodb::gridfs::{GridFsBucket, GridFsDownloadStream, GridFsUploadStream};
+use mongodb::options::{FindOptions, GridFsBucketOptions, IndexOptions};
+use mongodb::{Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::MongoDatabase;
+
+/// GridFS bucket holding document content, as `documents.files` and `documents.chunks`
+const BUCKET_NAME: &str = "documents";
+const METADATA_COLLECTION: &str = "document_metadata";
+/// How much content is read from the source before it is written on to GridFS
+const COPY_BUFFER_BYTES: usize = 64 * 1024;
+
+/// What is known about a stored document besides its content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the content
+    pub sha256: String,
+    pub status: String,
+    /// ZIP package the document was expanded from
+    pub package_id: Option<Uuid>,
+    pub supplier_id: Option<Uuid>,
+    /// Email the document was attached to
+    pub email_id: Option<Uuid>,
+    pub thread_id: Option<String>,
+    pub campaign_id: Option<Uuid>,
+    pub uploaded_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DocumentMetadata {
+    /// Metadata for a new upload; size and hash are filled in as the content is stored
+    pub fn new(filename: &str, content_type: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+            size_bytes: 0,
+            sha256: String::new(),
+            status: "uploaded".to_string(),
+            package_id: None,
+            supplier_id: None,
+            email_id: None,
+            thread_id: None,
+            campaign_id: None,
+            uploaded_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+pub struct DocumentRepository {
+    bucket: GridFsBucket,
+    metadata: Collection<DocumentRow>,
+}
+
+impl DocumentRepository {
+    pub fn new(database: &MongoDatabase) -> Self {
+        let options = GridFsBucketOptions::builder()
+            .bucket_name(BUCKET_NAME.to_string())
+            .build();
+        Self {
+            bucket: database.gridfs_bucket(options),
+            metadata: database.collection(METADATA_COLLECTION),
+        }
+    }
+
+    /// Create the indexes the lookups below rely on
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        for field in ["package_id", "supplier_id"] {
+            let index = IndexModel::builder()
+                .keys(doc! { field: 1 })
+                .options(IndexOptions::builder().sparse(true).build())
+                .build();
+            self.metadata.create_index(index, None)
+                .await
+                .with_context(|| format!("Failed to create document {} index", field))?;
+        }
+        Ok(())
+    }
+
+    /// Stream `content` into GridFS and record its metadata, returning the
+    /// metadata with size, hash and upload time filled in
+    pub async fn upload<R>(&self, mut metadata: DocumentMetadata, mut content: R) -> Result<DocumentMetadata>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut stream = self.bucket.open_upload_stream_with_id(file_id(metadata.id), &metadata.filename, None);
+        let (size_bytes, sha256) = match copy_hashed(&mut content, &mut stream).await {
+            Ok(written) => written,
+            Err(e) => {
+                // Remove the chunks written so far
+                let _ = stream.abort().await;
+                return Err(e);
+            }
+        };
+
+        let now = Utc::now();
+        metadata.size_bytes = size_bytes;
+        metadata.sha256 = sha256;
+        metadata.uploaded_at = now;
+        metadata.updated_at = now;
+
+        if let Err(e) = self.metadata.insert_one(DocumentRow::from(&metadata), None).await {
+            // Content without metadata would never be found again
+            let _ = self.bucket.delete(file_id(metadata.id)).await;
+            return Err(e).context("Failed to store document metadata");
+        }
+
+        Ok(metadata)
+    }
+
+    /// Store a document from a backup under its original ID, status and
+    /// timestamps, failing if the content does not match its recorded hash
+    pub async fn restore<R>(&self, metadata: DocumentMetadata, content: R) -> Result<DocumentMetadata>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let stored = self.upload(metadata.clone(), content).await?;
+        if stored.sha256 != metadata.sha256 {
+            self.delete(metadata.id).await?;
+            bail!("Content of document {} does not match its recorded hash", metadata.id);
+        }
+
+        self.metadata.replace_one(doc! { "_id": metadata.id.to_string() }, DocumentRow::from(&metadata), None)
+            .await
+            .context("Failed to restore document metadata")?;
+
+        Ok(metadata)
+    }
+
+    /// Find document metadata by ID
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<DocumentMetadata>> {
+        let row = self.metadata.find_one(doc! { "_id": id.to_string() }, None)
+            .await
+            .context("Failed to fetch document metadata")?;
+
+        row.map(DocumentMetadata::try_from).transpose()
+    }
+
+    /// Documents expanded from a package, by filename
+    pub async fn find_by_package(&self, package_id: Uuid) -> Result<Vec<DocumentMetadata>> {
+        self.find_many(doc! { "package_id": package_id.to_string() }, doc! { "filename": 1 }).await
+    }
+
+    /// Documents received from a supplier, newest first
+    pub async fn find_by_supplier(&self, supplier_id: Uuid) -> Result<Vec<DocumentMetadata>> {
+        self.find_many(doc! { "supplier_id": supplier_id.to_string() }, doc! { "uploaded_at": -1 }).await
+    }
+
+    async fn find_many(&self, filter: bson::Document, sort: bson::Document) -> Result<Vec<DocumentMetadata>> {
+        let rows: Vec<DocumentRow> = self.metadata
+            .find(filter, FindOptions::builder().sort(sort).build())
+            .await
+            .context("Failed to fetch document metadata")?
+            .try_collect()
+            .await
+            .context("Failed to read document metadata")?;
+
+        rows.into_iter().map(DocumentMetadata::try_from).collect()
+    }
+
+    /// Open a stream over a document's content, with its metadata
+    pub async fn open_download(&self, id: Uuid) -> Result<Option<(DocumentMetadata, GridFsDownloadStream)>> {
+        let Some(metadata) = self.find_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        let stream = self.bucket.open_download_stream(file_id(id))
+            .await
+            .context("Failed to open document content")?;
+
+        Ok(Some((metadata, stream)))
+    }
+
+    /// Read a document's whole content, for processing that needs it in memory
+    pub async fn download(&self, id: Uuid) -> Result<Option<Vec<u8>>> {
+        let Some((metadata, mut stream)) = self.open_download(id).await? else {
+            return Ok(None);
+        };
+
+        let mut content = Vec::with_capacity(metadata.size_bytes as usize);
+        stream.read_to_end(&mut content)
+            .await
+            .context("Failed to read document content")?;
+
+        Ok(Some(content))
+    }
+
+    /// Update a document's processing status
+    pub async fn update_status(&self, id: Uuid, status: &str) -> Result<bool> {
+        let result = self.metadata
+            .update_one(
+                doc! { "_id": id.to_string() },
+                doc! { "$set": { "status": status, "updated_at": bson_datetime(Utc::now()) } },
+                None,
+            )
+            .await
+            .context("Failed to update document status")?;
+
+        Ok(result.matched_count > 0)
+    }
+
+    /// Delete a document's metadata and content
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = self.metadata.delete_one(doc! { "_id": id.to_string() }, None)
+            .await
+            .context("Failed to delete document metadata")?;
+
+        if result.deleted_count == 0 {
+            return Ok(false);
+        }
+
+        self.bucket.delete(file_id(id))
+            .await
+            .context("Failed to delete document content")?;
+
+        Ok(true)
+    }
+}
+
+/// GridFS file ID of a document's content
+fn file_id(id: Uuid) -> Bson {
+    Bson::String(id.to_string())
+}
+
+/// Copy `content` into an upload stream, returning its size and SHA-256
+async fn copy_hashed<R>(content: &mut R, stream: &mut GridFsUploadStream) -> Result<(u64, String)>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut size_bytes = 0u64;
+    let mut buffer = vec![0u8; COPY_BUFFER_BYTES];
+
+    loop {
+        let read = content.read(&mut buffer)
+            .await
+            .context("Failed to read document content")?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size_bytes += read as u64;
+        stream.write_all(&buffer[..read])
+            .await
+            .context("Failed to store document content")?;
+    }
+
+    stream.close()
+        .await
+        .context("Failed to store document content")?;
+
+    Ok((size_bytes, hex::encode(hasher.finalize())))
+}
+
+fn bson_datetime(at: DateTime<Utc>) -> bson::DateTime {
+    bson::DateTime::from_millis(at.timestamp_millis())
+}
+
+fn chrono_datetime(at: bson::DateTime) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(at.timestamp_millis())
+        .context("Document timestamp out of range")
+}
+
+fn parse_uuid(value: Option<&str>) -> Result<Option<Uuid>> {
+    value.map(Uuid::parse_str)
+        .transpose()
+        .context("Invalid UUID in document metadata")
+}
+
+/// Internal document type for the metadata collection. IDs are stored as
+/// strings and times as BSON dates so they can be queried and sorted.
+#[derive(Debug, Serialize, Deserialize)]
+struct DocumentRow {
+    #[serde(rename = "_id")]
+    id: String,
+    filename: String,
+    content_type: String,
+    size_bytes: i64,
+    sha256: String,
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    package_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    supplier_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thread_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    campaign_id: Option<String>,
+    uploaded_at: bson::DateTime,
+    updated_at: bson::DateTime,
+}
+
+impl From<&DocumentMetadata> for DocumentRow {
+    fn from(metadata: &DocumentMetadata) -> Self {
+        Self {
+            id: metadata.id.to_string(),
+            filename: metadata.filename.clone(),
+            content_type: metadata.content_type.clone(),
+            size_bytes: metadata.size_bytes as i64,
+            sha256: metadata.sha256.clone(),
+            status: metadata.status.clone(),
+            package_id: metadata.package_id.map(|id| id.to_string()),
+            supplier_id: metadata.supplier_id.map(|id| id.to_string()),
+            email_id: metadata.email_id.map(|id| id.to_string()),
+            thread_id: metadata.thread_id.clone(),
+            campaign_id: metadata.campaign_id.map(|id| id.to_string()),
+            uploaded_at: bson_datetime(metadata.uploaded_at),
+            updated_at: bson_datetime(metadata.updated_at),
+        }
+    }
+}
+
+impl TryFrom<DocumentRow> for DocumentMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DocumentRow) -> Result<Self> {
+        Ok(Self {
+            id: Uuid::parse_str(&row.id).context("Invalid document ID")?,
+            filename: row.filename,
+            content_type: row.content_type,
+            size_bytes: row.size_bytes as u64,
+            sha256: row.sha256,
+            status: row.status,
+            package_id: parse_uuid(row.package_id.as_deref())?,
+            supplier_id: parse_uuid(row.supplier_id.as_deref())?,
+            email_id: parse_uuid(row.email_id.as_deref())?,
+            thread_id: row.thread_id,
+            campaign_id: parse_uuid(row.campaign_id.as_deref())?,
+            uploaded_at: chrono_datetime(row.uploaded_at)?,
+            updated_at: chrono_datetime(row.updated_at)?,
+        })
+    }
+}
@@ -3,11 +3,19 @@
 //! CRUD operations for email communications.
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, FromRow};
 use uuid::Uuid;
 
-use elementa_models::{EmailCommunication, EmailDirection, DeliveryStatus, EmailProcessingStatus};
+use elementa_models::{
+    EmailCommunication, EmailDirection, EmailAttachment, DeliveryAttempt, DeliveryStatus, EmailProcessingStatus,
+    ReplyClassification, SmimeStatus, SupplierId, WorkflowId,
+};
+
+use super::pagination::{into_page, keyset_bounds, page_limit};
+use super::search::search_limit;
+use super::{Cursor, Page, SearchHit};
+use crate::QueryTiming;
 
 pub struct EmailRepository {
     pool: PgPool,
@@ -24,13 +32,17 @@ impl EmailRepository {
             r#"
             SELECT id, thread_id, supplier_id, direction, subject, body,
                    sent_at, received_at, attachments, delivery_status,
-                   processing_status, created_at, updated_at
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
             FROM email_communications
             WHERE id = $1
             "#
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .timed("email", "find_by_id")
         .await
         .context("Failed to fetch email by ID")?;
         
@@ -43,7 +55,10 @@ impl EmailRepository {
             r#"
             SELECT id, thread_id, supplier_id, direction, subject, body,
                    sent_at, received_at, attachments, delivery_status,
-                   processing_status, created_at, updated_at
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
             FROM email_communications
             WHERE thread_id = $1
             ORDER BY created_at ASC
@@ -51,19 +66,109 @@ impl EmailRepository {
         )
         .bind(thread_id)
         .fetch_all(&self.pool)
+        .timed("email", "find_by_thread")
         .await
         .context("Failed to fetch emails by thread")?;
         
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
     
+    /// Find the most recent email carrying any of the given Message-IDs
+    pub async fn find_by_message_ids(&self, message_ids: &[String]) -> Result<Option<EmailCommunication>> {
+        let row: Option<EmailRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
+            FROM email_communications
+            WHERE message_id = ANY($1)
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(message_ids)
+        .fetch_optional(&self.pool)
+        .timed("email", "find_by_message_ids")
+        .await
+        .context("Failed to fetch email by message ID")?;
+        
+        Ok(row.map(|r| r.into()))
+    }
+    
+    /// Find the most recent outbound email to an address
+    pub async fn find_latest_sent_to(&self, recipient: &str) -> Result<Option<EmailCommunication>> {
+        let row: Option<EmailRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
+            FROM email_communications
+            WHERE direction = 'Outbound' AND LOWER(recipient) = LOWER($1)
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(recipient)
+        .fetch_optional(&self.pool)
+        .timed("email", "find_latest_sent_to")
+        .await
+        .context("Failed to fetch email by recipient")?;
+        
+        Ok(row.map(|r| r.into()))
+    }
+    
+    /// Earliest inbound email with the given Message-ID, or from the supplier
+    /// with the same content hash since `since`
+    pub async fn find_inbound_duplicate(
+        &self,
+        supplier_id: SupplierId,
+        message_id: Option<&str>,
+        content_hash: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<EmailCommunication>> {
+        let row: Option<EmailRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
+            FROM email_communications
+            WHERE direction = 'Inbound'
+              AND (message_id = $1 OR (supplier_id = $2 AND content_hash = $3 AND created_at >= $4))
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#
+        )
+        .bind(message_id)
+        .bind(supplier_id)
+        .bind(content_hash)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .timed("email", "find_inbound_duplicate")
+        .await
+        .context("Failed to look up duplicate inbound email")?;
+        
+        Ok(row.map(|r| r.into()))
+    }
+    
     /// Find emails for a supplier
-    pub async fn find_by_supplier(&self, supplier_id: Uuid) -> Result<Vec<EmailCommunication>> {
+    pub async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<EmailCommunication>> {
         let rows: Vec<EmailRow> = sqlx::query_as(
             r#"
             SELECT id, thread_id, supplier_id, direction, subject, body,
                    sent_at, received_at, attachments, delivery_status,
-                   processing_status, created_at, updated_at
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
             FROM email_communications
             WHERE supplier_id = $1
             ORDER BY created_at DESC
@@ -71,18 +176,114 @@ impl EmailRepository {
         )
         .bind(supplier_id)
         .fetch_all(&self.pool)
+        .timed("email", "find_by_supplier")
         .await
         .context("Failed to fetch emails by supplier")?;
         
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
     
+    /// One page of a supplier's emails, newest first
+    pub async fn find_page_by_supplier(&self, supplier_id: SupplierId, after: Option<Cursor>, limit: i64) -> Result<Page<EmailCommunication>> {
+        let (after_created_at, after_id) = keyset_bounds(after);
+        let rows: Vec<EmailRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
+            FROM email_communications
+            WHERE supplier_id = $1
+              AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4
+            "#
+        )
+        .bind(supplier_id)
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(page_limit(limit) + 1)
+        .fetch_all(&self.pool)
+        .timed("email", "find_page_by_supplier")
+        .await
+        .context("Failed to fetch page of emails by supplier")?;
+        
+        let emails = rows.into_iter().map(EmailCommunication::from).collect();
+        Ok(into_page(emails, limit, |e: &EmailCommunication| Cursor::new(e.created_at, e.id)))
+    }
+    
+    /// Search email subjects and bodies, best matches first, optionally
+    /// within one supplier's correspondence. Subject matches outrank body
+    /// matches.
+    pub async fn search(&self, query: &str, supplier_id: Option<SupplierId>, limit: i64) -> Result<Vec<SearchHit<EmailCommunication>>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let rows: Vec<EmailSearchRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at,
+                   ts_rank(search_vector, ts_query) AS rank
+            FROM email_communications, websearch_to_tsquery('simple', $1) AS ts_query
+            WHERE search_vector @@ ts_query AND ($2::uuid IS NULL OR supplier_id = $2)
+            ORDER BY rank DESC, created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(query)
+        .bind(supplier_id)
+        .bind(search_limit(limit))
+        .fetch_all(&self.pool)
+        .timed("email", "search")
+        .await
+        .context("Failed to search emails")?;
+        
+        Ok(rows.into_iter().map(|row| SearchHit { rank: row.rank, item: row.email.into() }).collect())
+    }
+    
+    /// Emails in every thread a campaign sent to, replies included
+    pub async fn find_campaign_threads(&self, campaign_id: WorkflowId) -> Result<Vec<EmailCommunication>> {
+        let rows: Vec<EmailRow> = sqlx::query_as(
+            r#"
+            SELECT id, thread_id, supplier_id, direction, subject, body,
+                   sent_at, received_at, attachments, delivery_status,
+                   processing_status, message_id, in_reply_to, message_references, recipient,
+                   classification, classification_confidence, delivery_attempts, content_hash,
+                   smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                   created_at, updated_at
+            FROM email_communications
+            WHERE thread_id IN (SELECT thread_id FROM email_communications WHERE campaign_id = $1)
+            ORDER BY created_at ASC
+            "#
+        )
+        .bind(campaign_id)
+        .fetch_all(&self.pool)
+        .timed("email", "find_campaign_threads")
+        .await
+        .context("Failed to fetch campaign threads")?;
+        
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+    
     /// Create new email
     pub async fn create(&self, email: EmailCommunication) -> Result<EmailCommunication> {
         let attachments = serde_json::to_value(&email.attachments)?;
+        let references = serde_json::to_value(&email.references)?;
+        let delivery_attempts = serde_json::to_value(&email.delivery_attempts)?;
         let direction_str = serde_json::to_string(&email.direction)?.trim_matches('"').to_string();
         let delivery_str = serde_json::to_string(&email.delivery_status)?.trim_matches('"').to_string();
         let proc_str = serde_json::to_string(&email.processing_status)?.trim_matches('"').to_string();
+        let classification_str = email.classification
+            .map(|c| serde_json::to_string(&c).map(|s| s.trim_matches('"').to_string()))
+            .transpose()?;
         let now = Utc::now();
         
         let row: EmailRow = sqlx::query_as(
@@ -90,11 +291,17 @@ impl EmailRepository {
             INSERT INTO email_communications 
                 (id, thread_id, supplier_id, direction, subject, body,
                  sent_at, received_at, attachments, delivery_status,
-                 processing_status, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                 processing_status, message_id, in_reply_to, message_references, recipient,
+                 classification, classification_confidence, delivery_attempts, content_hash,
+                 smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                 created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27)
             RETURNING id, thread_id, supplier_id, direction, subject, body,
                       sent_at, received_at, attachments, delivery_status,
-                      processing_status, created_at, updated_at
+                      processing_status, message_id, in_reply_to, message_references, recipient,
+                      classification, classification_confidence, delivery_attempts, content_hash,
+                      smime_signed, smime_encrypted, smime_signature_valid, campaign_id, template_id, template_variant,
+                      created_at, updated_at
             "#
         )
         .bind(email.id)
@@ -108,9 +315,24 @@ impl EmailRepository {
         .bind(&attachments)
         .bind(&delivery_str)
         .bind(&proc_str)
+        .bind(&email.message_id)
+        .bind(&email.in_reply_to)
+        .bind(&references)
+        .bind(&email.recipient)
+        .bind(&classification_str)
+        .bind(email.classification_confidence)
+        .bind(&delivery_attempts)
+        .bind(&email.content_hash)
+        .bind(email.smime.signed)
+        .bind(email.smime.encrypted)
+        .bind(email.smime.signature_valid)
+        .bind(email.campaign_id)
+        .bind(&email.template_id)
+        .bind(&email.template_variant)
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
+        .timed("email", "create")
         .await
         .context("Failed to create email")?;
         
@@ -128,12 +350,81 @@ impl EmailRepository {
         .bind(&status_str)
         .bind(Utc::now())
         .execute(&self.pool)
+        .timed("email", "update_delivery_status")
         .await
         .context("Failed to update delivery status")?;
         
         Ok(result.rows_affected() > 0)
     }
     
+    /// Record delivery attempts along with the resulting status
+    pub async fn update_delivery_attempts(
+        &self,
+        id: Uuid,
+        attempts: &[DeliveryAttempt],
+        status: DeliveryStatus,
+        sent_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let attempts = serde_json::to_value(attempts)?;
+        let status_str = serde_json::to_string(&status)?.trim_matches('"').to_string();
+        
+        let result = sqlx::query(
+            r#"
+            UPDATE email_communications
+            SET delivery_attempts = $2, delivery_status = $3, sent_at = COALESCE($4, sent_at), updated_at = $5
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(&attempts)
+        .bind(&status_str)
+        .bind(sent_at)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .timed("email", "update_delivery_attempts")
+        .await
+        .context("Failed to update delivery attempts")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Replace attachment metadata, e.g. once document IDs are known
+    pub async fn update_attachments(&self, id: Uuid, attachments: &[EmailAttachment]) -> Result<bool> {
+        let attachments = serde_json::to_value(attachments)?;
+        
+        let result = sqlx::query(
+            "UPDATE email_communications SET attachments = $2, updated_at = $3 WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&attachments)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .timed("email", "update_attachments")
+        .await
+        .context("Failed to update attachments")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Record reply classification
+    pub async fn update_classification(&self, id: Uuid, classification: ReplyClassification, confidence: f64) -> Result<bool> {
+        let classification_str = serde_json::to_string(&classification)?.trim_matches('"').to_string();
+        
+        let result = sqlx::query(
+            "UPDATE email_communications SET classification = $2, classification_confidence = $3, updated_at = $4 WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&classification_str)
+        .bind(confidence)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .timed("email", "update_classification")
+        .await
+        .context("Failed to update classification")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
     /// Update processing status
     pub async fn update_processing_status(&self, id: Uuid, status: EmailProcessingStatus) -> Result<bool> {
         let status_str = serde_json::to_string(&status)?.trim_matches('"').to_string();
@@ -145,6 +436,7 @@ impl EmailRepository {
         .bind(&status_str)
         .bind(Utc::now())
         .execute(&self.pool)
+        .timed("email", "update_processing_status")
         .await
         .context("Failed to update processing status")?;
         
@@ -156,7 +448,7 @@ impl EmailRepository {
 struct EmailRow {
     id: Uuid,
     thread_id: String,
-    supplier_id: Uuid,
+    supplier_id: SupplierId,
     direction: String,
     subject: String,
     body: String,
@@ -165,10 +457,32 @@ struct EmailRow {
     attachments: serde_json::Value,
     delivery_status: String,
     processing_status: String,
+    message_id: Option<String>,
+    in_reply_to: Option<String>,
+    message_references: serde_json::Value,
+    recipient: Option<String>,
+    classification: Option<String>,
+    classification_confidence: Option<f64>,
+    delivery_attempts: serde_json::Value,
+    content_hash: Option<String>,
+    smime_signed: bool,
+    smime_encrypted: bool,
+    smime_signature_valid: Option<bool>,
+    campaign_id: Option<WorkflowId>,
+    template_id: Option<String>,
+    template_variant: Option<String>,
     created_at: chrono::DateTime<Utc>,
     updated_at: chrono::DateTime<Utc>,
 }
 
+/// Email row with its search rank
+#[derive(Debug, FromRow)]
+struct EmailSearchRow {
+    #[sqlx(flatten)]
+    email: EmailRow,
+    rank: f32,
+}
+
 impl From<EmailRow> for EmailCommunication {
     fn from(row: EmailRow) -> Self {
         Self {
@@ -186,6 +500,23 @@ impl From<EmailRow> for EmailCommunication {
                 .unwrap_or(DeliveryStatus::Pending),
             processing_status: serde_json::from_str(&format!("\"{}\"", row.processing_status))
                 .unwrap_or(EmailProcessingStatus::NotProcessed),
+            message_id: row.message_id,
+            in_reply_to: row.in_reply_to,
+            references: serde_json::from_value(row.message_references).unwrap_or_default(),
+            recipient: row.recipient,
+            classification: row.classification
+                .and_then(|c| serde_json::from_str(&format!("\"{}\"", c)).ok()),
+            classification_confidence: row.classification_confidence,
+            delivery_attempts: serde_json::from_value(row.delivery_attempts).unwrap_or_default(),
+            content_hash: row.content_hash,
+            smime: SmimeStatus {
+                signed: row.smime_signed,
+                encrypted: row.smime_encrypted,
+                signature_valid: row.smime_signature_valid,
+            },
+            campaign_id: row.campaign_id,
+            template_id: row.template_id,
+            template_variant: row.template_variant,
             created_at: row.created_at,
             updated_at: row.updated_at,
         }
@@ -9,11 +9,25 @@ pub mod chemical;
 pub mod workflow;
 pub mod audit;
 pub mod email;
+pub mod suppression;
+pub mod sender_domain;
+pub mod column_mapping;
+pub mod document;
+pub mod search;
+pub mod bulk;
+pub mod pagination;
 
-pub use supplier::SupplierRepository;
-pub use compliance::ComplianceRepository;
+pub use supplier::{SupplierRepository, SupplierMerge};
+pub use compliance::{ComplianceRepository, ComplianceResubmission, ExpiringCertification};
 pub use component::ComponentRepository;
 pub use chemical::ChemicalRepository;
-pub use workflow::WorkflowRepository;
-pub use audit::AuditRepository;
+pub use workflow::{ClaimedTask, ReleasedClaims, WorkflowRepository};
+pub use audit::{AuditPartitionJob, AuditRepository, ChainVerification};
 pub use email::EmailRepository;
+pub use suppression::SuppressionRepository;
+pub use sender_domain::SenderDomainRepository;
+pub use column_mapping::ColumnMappingRepository;
+pub use document::{DocumentMetadata, DocumentRepository};
+pub use search::{SearchHit, MAX_SEARCH_RESULTS};
+pub use pagination::{Cursor, Page, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+pub use bulk::{BulkInsertReport, BulkRowError, BulkRowFailure, BULK_BATCH_SIZE};
@@ -0,0 +1,182 @@
+//! Keyset pagination
+//!
+//! Listings page newest first on `(created_at, id)`: each page is fetched
+//! with `(created_at, id) < cursor`, so a page costs the same however deep
+//! into the listing it is, and rows inserted while a client pages through
+//! do not shift later pages the way `OFFSET` does. The `id` breaks ties
+//! between rows created in the same microsecond.
+
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Page size when the caller does not ask for one
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+/// Largest page a listing returns, whatever size the caller asks for
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+/// Position in a listing: the `(created_at, id)` of the last row of a page.
+/// Serialized as an opaque token for clients to send back for the next page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: impl Into<Uuid>) -> Self {
+        Self { created_at, id: id.into() }
+    }
+
+    /// Read a cursor from the token `Display` writes
+    pub fn parse(token: &str) -> Result<Self> {
+        let (micros, id) = token.split_once('_').ok_or_else(|| anyhow!("Malformed page cursor '{}'", token))?;
+        let micros: i64 = micros.parse().with_context(|| format!("Malformed page cursor '{}'", token))?;
+        let created_at = DateTime::from_timestamp_micros(micros).ok_or_else(|| anyhow!("Page cursor '{}' is out of range", token))?;
+        let id = Uuid::parse_str(id).with_context(|| format!("Malformed page cursor '{}'", token))?;
+        Ok(Self { created_at, id })
+    }
+}
+
+// Microseconds are Postgres' timestamp precision, so the token is exact
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.created_at.timestamp_micros(), self.id.simple())
+    }
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = anyhow::Error;
+
+    fn try_from(token: String) -> Result<Self> {
+        Self::parse(&token)
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(cursor: Cursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+/// One page of a listing, and the cursor for the next page if there is one
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Clamp a caller's page size to what a listing returns
+pub(crate) fn page_limit(limit: i64) -> i64 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Bounds to bind for `($n::timestamptz IS NULL OR (created_at, id) < ($n, $n+1))`
+pub(crate) fn keyset_bounds(after: Option<Cursor>) -> (Option<DateTime<Utc>>, Option<Uuid>) {
+    (after.map(|c| c.created_at), after.map(|c| c.id))
+}
+
+/// Build a page from rows fetched with `LIMIT page_limit(limit) + 1`: the
+/// extra row only shows whether another page follows, and is dropped
+pub(crate) fn into_page<T>(mut items: Vec<T>, limit: i64, cursor: impl Fn(&T) -> Cursor) -> Page<T> {
+    let limit = page_limit(limit) as usize;
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(cursor)
+    } else {
+        None
+    };
+    Page { items, next_cursor }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(micros: i64, id: u128) -> Cursor {
+        Cursor::new(DateTime::from_timestamp_micros(micros).unwrap(), Uuid::from_u128(id))
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let original = cursor(1_700_000_000_123_456, 0x1234);
+        let token = original.to_string();
+        assert_eq!(token, format!("1700000000123456_{}", Uuid::from_u128(0x1234).simple()));
+        assert_eq!(Cursor::parse(&token).unwrap(), original);
+
+        // Before the epoch, and through serde as clients send it back
+        let early = cursor(-86_400_000_000, 7);
+        assert_eq!(Cursor::parse(&early.to_string()).unwrap(), early);
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(serde_json::from_str::<Cursor>(&json).unwrap(), original);
+    }
+
+    #[test]
+    fn test_malformed_cursors_are_rejected() {
+        let id = Uuid::from_u128(1).simple();
+        for token in [
+            String::new(),
+            "no-separator".to_string(),
+            format!("soon_{}", id),
+            "1700000000123456_not-a-uuid".to_string(),
+            format!("{}_{}", i64::MAX, id),
+        ] {
+            assert!(Cursor::parse(&token).is_err(), "'{}' should be rejected", token);
+        }
+        assert!(serde_json::from_str::<Cursor>("\"garbage\"").is_err());
+    }
+
+    #[test]
+    fn test_page_limit_is_clamped() {
+        assert_eq!(page_limit(DEFAULT_PAGE_SIZE), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_limit(0), 1);
+        assert_eq!(page_limit(-5), 1);
+        assert_eq!(page_limit(MAX_PAGE_SIZE + 1), MAX_PAGE_SIZE);
+        assert_eq!(page_limit(i64::MAX), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_into_page() {
+        let rows = |n: u128| (1..=n).map(|i| cursor(1_000 - i as i64, i)).collect::<Vec<_>>();
+
+        // The extra row fetched past the limit is dropped and marks another page
+        let page = into_page(rows(4), 3, |c| *c);
+        assert_eq!(page.items, rows(3));
+        assert_eq!(page.next_cursor, Some(rows(3)[2]));
+        assert!(page.has_more());
+
+        // A short or exactly full page is the last one
+        assert_eq!(into_page(rows(3), 3, |c| *c).next_cursor, None);
+        assert_eq!(into_page(rows(2), 3, |c| *c).next_cursor, None);
+        assert!(!into_page(Vec::<Cursor>::new(), 3, |c| *c).has_more());
+
+        // Limits are clamped before truncating
+        let page = into_page(rows(3), 0, |c| *c);
+        assert_eq!(page.items, rows(1));
+        assert_eq!(page.next_cursor, Some(rows(1)[0]));
+    }
+
+    #[test]
+    fn test_keyset_bounds() {
+        assert_eq!(keyset_bounds(None), (None, None));
+        let after = cursor(42, 9);
+        assert_eq!(keyset_bounds(Some(after)), (Some(after.created_at), Some(after.id)));
+    }
+}
@@ -0,0 +1,22 @@
+//! Ranked search results
+//!
+//! Repository `search` methods match a user's query against the generated
+//! `search_vector` columns and trigram indexes from the full-text search
+//! migration, returning the best matches first.
+
+use serde::Serialize;
+
+/// Most results a search returns, whatever limit the caller asks for
+pub const MAX_SEARCH_RESULTS: i64 = 100;
+
+/// A search match and how well it matched; higher ranks are better matches
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit<T> {
+    pub item: T,
+    pub rank: f32,
+}
+
+/// Clamp a caller's result limit to what a search returns
+pub(crate) fn search_limit(limit: i64) -> i64 {
+    limit.clamp(1, MAX_SEARCH_RESULTS)
+}
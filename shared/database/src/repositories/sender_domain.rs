@@ -0,0 +1,90 @@
+//! Sender Domain Repository
+//!
+//! Tenant From addresses and DKIM signing keys for outbound email.
+
+use anyhow::{Context, Result};
+use sqlx::{FromRow, PgPool};
+
+use elementa_models::TenantSenderDomain;
+
+use crate::QueryTiming;
+
+pub struct SenderDomainRepository {
+    pool: PgPool,
+}
+
+impl SenderDomainRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Find a tenant's sender domain
+    pub async fn find(&self, tenant_id: &str) -> Result<Option<TenantSenderDomain>> {
+        let row: Option<SenderDomainRow> = sqlx::query_as(
+            r#"
+            SELECT tenant_id, from_email, from_name, dkim_selector, dkim_algorithm, dkim_private_key, updated_at
+            FROM sender_domains
+            WHERE tenant_id = $1
+            "#
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .timed("sender_domain", "find")
+        .await
+        .context("Failed to get sender domain")?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    /// Save a tenant's sender domain, replacing the one saved before
+    pub async fn save(&self, domain: &TenantSenderDomain) -> Result<TenantSenderDomain> {
+        let row: SenderDomainRow = sqlx::query_as(
+            r#"
+            INSERT INTO sender_domains (tenant_id, from_email, from_name, dkim_selector, dkim_algorithm, dkim_private_key)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id)
+            DO UPDATE SET from_email = EXCLUDED.from_email, from_name = EXCLUDED.from_name,
+                          dkim_selector = EXCLUDED.dkim_selector, dkim_algorithm = EXCLUDED.dkim_algorithm,
+                          dkim_private_key = EXCLUDED.dkim_private_key, updated_at = NOW()
+            RETURNING tenant_id, from_email, from_name, dkim_selector, dkim_algorithm, dkim_private_key, updated_at
+            "#
+        )
+        .bind(&domain.tenant_id)
+        .bind(&domain.from_email)
+        .bind(&domain.from_name)
+        .bind(&domain.dkim_selector)
+        .bind(&domain.dkim_algorithm)
+        .bind(&domain.dkim_private_key)
+        .fetch_one(&self.pool)
+        .timed("sender_domain", "save")
+        .await
+        .context("Failed to save sender domain")?;
+
+        Ok(row.into())
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct SenderDomainRow {
+    tenant_id: String,
+    from_email: String,
+    from_name: String,
+    dkim_selector: Option<String>,
+    dkim_algorithm: Option<String>,
+    dkim_private_key: Option<String>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<SenderDomainRow> for TenantSenderDomain {
+    fn from(row: SenderDomainRow) -> Self {
+        Self {
+            tenant_id: row.tenant_id,
+            from_email: row.from_email,
+            from_name: row.from_name,
+            dkim_selector: row.dkim_selector,
+            dkim_algorithm: row.dkim_algorithm,
+            dkim_private_key: row.dkim_private_key,
+            updated_at: row.updated_at,
+        }
+    }
+}
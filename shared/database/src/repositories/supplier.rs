@@ -3,106 +3,210 @@
 //! CRUD operations for supplier records.
 //! Uses runtime SQL queries (unchecked) to avoid requiring DATABASE_URL at compile time.
 
-use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::{PgPool, FromRow};
+use sqlx::{PgConnection, PgPool, FromRow};
 use uuid::Uuid;
 
 use elementa_models::{
-    SupplierRecord, SupplierRelationship,
-    ComplianceStatus, RiskLevel,
+    SupplierRecord, SupplierTier, ComplianceRollup, ComplianceStatus, RiskLevel, Versioned,
+    AuditAction, AuditEntry, Lifecycle, LifecycleStatus, SupplierId,
 };
 
+use super::bulk::{bulk_insert, live_ids, BulkInsert};
+use super::pagination::{into_page, keyset_bounds, page_limit};
+use super::search::search_limit;
+use super::{AuditRepository, BulkInsertReport, BulkRowError, BulkRowFailure, Cursor, Page, SearchHit};
+use crate::{PostgresPools, QueryTiming};
+
+/// Tables whose rows belong to a supplier and follow it into a merge
+const SUPPLIER_TABLES: [&str; 5] = [
+    "components",
+    "compliance_records",
+    "agent_tasks",
+    "email_communications",
+    "email_suppressions",
+];
+
 pub struct SupplierRepository {
     pool: PgPool,
+    reads: PostgresPools,
 }
 
 impl SupplierRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { reads: pool.clone().into(), pool }
+    }
+    
+    /// Repository that sends listing and counting queries to the read replica
+    pub fn with_pools(pools: PostgresPools) -> Self {
+        Self { pool: pools.primary().clone(), reads: pools }
     }
     
     /// Find supplier by ID
-    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<SupplierRecord>> {
+    pub async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
         let row: Option<SupplierRow> = sqlx::query_as(
             r#"
             SELECT id, name, contact_info, relationship, 
                    compliance_history, communication_preferences, 
-                   risk_profile, created_at, updated_at
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             FROM suppliers
-            WHERE id = $1
+            WHERE id = $1 AND lifecycle_status <> 'deleted'
             "#
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .timed("supplier", "find_by_id")
         .await
         .context("Failed to fetch supplier by ID")?;
         
-        Ok(row.map(|r| r.into()))
+        row.map(SupplierRecord::try_from).transpose()
     }
     
-    /// Find all suppliers
-    pub async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
-        let rows: Vec<SupplierRow> = sqlx::query_as(
+    /// Find supplier by ID even if it has been deleted, for audit and traceability
+    pub async fn find_by_id_including_deleted(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
+        let row: Option<SupplierRow> = sqlx::query_as(
             r#"
             SELECT id, name, contact_info, relationship, 
                    compliance_history, communication_preferences, 
-                   risk_profile, created_at, updated_at
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             FROM suppliers
-            ORDER BY name
+            WHERE id = $1
             "#
         )
-        .fetch_all(&self.pool)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .timed("supplier", "find_by_id_including_deleted")
         .await
-        .context("Failed to fetch all suppliers")?;
+        .context("Failed to fetch supplier by ID")?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        row.map(SupplierRecord::try_from).transpose()
     }
     
-    /// Find suppliers by compliance status
-    pub async fn find_by_compliance_status(&self, status: ComplianceStatus) -> Result<Vec<SupplierRecord>> {
-        let status_str = serde_json::to_string(&status)?;
-        let pattern = format!("[{{\"status\": {}}}]", status_str);
-        
+    /// Find suppliers in one lifecycle status, including deleted ones
+    pub async fn find_by_lifecycle_status(&self, status: LifecycleStatus) -> Result<Vec<SupplierRecord>> {
         let rows: Vec<SupplierRow> = sqlx::query_as(
             r#"
             SELECT id, name, contact_info, relationship, 
                    compliance_history, communication_preferences, 
-                   risk_profile, created_at, updated_at
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             FROM suppliers
-            WHERE compliance_history @> $1::jsonb
+            WHERE lifecycle_status = $1
             ORDER BY name
             "#
         )
-        .bind(&pattern)
+        .bind(status.as_str())
         .fetch_all(&self.pool)
+        .timed("supplier", "find_by_lifecycle_status")
         .await
-        .context("Failed to fetch suppliers by compliance status")?;
+        .context("Failed to fetch suppliers by lifecycle status")?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        rows.into_iter().map(SupplierRecord::try_from).collect()
     }
     
-    /// Find suppliers by risk level
-    pub async fn find_by_risk_level(&self, risk: RiskLevel) -> Result<Vec<SupplierRecord>> {
-        let risk_str = serde_json::to_string(&risk)?;
-        let risk_value = risk_str.trim_matches('"');
-        
+    /// Find all suppliers that have not been deleted
+    pub async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
         let rows: Vec<SupplierRow> = sqlx::query_as(
             r#"
             SELECT id, name, contact_info, relationship, 
                    compliance_history, communication_preferences, 
-                   risk_profile, created_at, updated_at
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             FROM suppliers
-            WHERE risk_profile->>'compliance_risk' = $1
+            WHERE lifecycle_status <> 'deleted'
             ORDER BY name
             "#
         )
-        .bind(risk_value)
         .fetch_all(&self.pool)
+        .timed("supplier", "find_all")
         .await
-        .context("Failed to fetch suppliers by risk level")?;
+        .context("Failed to fetch all suppliers")?;
+        
+        rows.into_iter().map(SupplierRecord::try_from).collect()
+    }
+    
+    /// One page of the suppliers that have not been deleted, newest first
+    pub async fn find_page(&self, after: Option<Cursor>, limit: i64) -> Result<Page<SupplierRecord>> {
+        let (after_created_at, after_id) = keyset_bounds(after);
+        let rows: Vec<SupplierRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, contact_info, relationship, 
+                       compliance_history, communication_preferences, 
+                       risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+                FROM suppliers
+                WHERE lifecycle_status <> 'deleted'
+                  AND ($1::timestamptz IS NULL OR (created_at, id) < ($1, $2))
+                ORDER BY created_at DESC, id DESC
+                LIMIT $3
+                "#
+            )
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(page_limit(limit) + 1)
+            .fetch_all(&pool)
+            .timed("supplier", "find_page")
+            .await
+            .context("Failed to fetch page of suppliers")
+        }).await?;
+        
+        let suppliers = rows.into_iter().map(SupplierRecord::try_from).collect::<Result<Vec<_>>>()?;
+        Ok(into_page(suppliers, limit, |s| Cursor::new(s.created_at, s.id)))
+    }
+    
+    /// Find suppliers by compliance status
+    pub async fn find_by_compliance_status(&self, status: ComplianceStatus) -> Result<Vec<SupplierRecord>> {
+        let status_str = serde_json::to_string(&status)?;
+        let pattern = format!("[{{\"status\": {}}}]", status_str);
+        let pattern = pattern.as_str();
+        
+        let rows: Vec<SupplierRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, contact_info, relationship, 
+                       compliance_history, communication_preferences, 
+                       risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+                FROM suppliers
+                WHERE compliance_history @> $1::jsonb AND lifecycle_status <> 'deleted'
+                ORDER BY name
+                "#
+            )
+            .bind(pattern)
+            .fetch_all(&pool)
+            .timed("supplier", "find_by_compliance_status")
+            .await
+            .context("Failed to fetch suppliers by compliance status")
+        }).await?;
+        
+        rows.into_iter().map(SupplierRecord::try_from).collect()
+    }
+    
+    /// Find suppliers by risk level
+    pub async fn find_by_risk_level(&self, risk: RiskLevel) -> Result<Vec<SupplierRecord>> {
+        let risk_str = serde_json::to_string(&risk)?;
+        let risk_value = risk_str.trim_matches('"');
+        
+        let rows: Vec<SupplierRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, contact_info, relationship, 
+                       compliance_history, communication_preferences, 
+                       risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+                FROM suppliers
+                WHERE risk_profile->>'compliance_risk' = $1 AND lifecycle_status <> 'deleted'
+                ORDER BY name
+                "#
+            )
+            .bind(risk_value)
+            .fetch_all(&pool)
+            .timed("supplier", "find_by_risk_level")
+            .await
+            .context("Failed to fetch suppliers by risk level")
+        }).await?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        rows.into_iter().map(SupplierRecord::try_from).collect()
     }
     
     /// Create new supplier
@@ -111,18 +215,19 @@ impl SupplierRepository {
         let relationship = serde_json::to_string(&supplier.relationship)?;
         let compliance_history = serde_json::to_value(&supplier.compliance_history)?;
         let communication_preferences = serde_json::to_value(&supplier.communication_preferences)?;
-        let risk_profile = serde_json::to_value(&supplier.risk_profile)?;
+        let risk_profile = supplier.risk_profile.to_versioned()?;
+        let tier = serde_json::to_string(&supplier.tier)?;
         let now = Utc::now();
         
         let row: SupplierRow = sqlx::query_as(
             r#"
             INSERT INTO suppliers 
                 (id, name, contact_info, relationship, compliance_history, 
-                 communication_preferences, risk_profile, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 communication_preferences, risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             RETURNING id, name, contact_info, relationship, 
                       compliance_history, communication_preferences, 
-                      risk_profile, created_at, updated_at
+                      risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             "#
         )
         .bind(supplier.id)
@@ -132,22 +237,41 @@ impl SupplierRepository {
         .bind(&compliance_history)
         .bind(&communication_preferences)
         .bind(&risk_profile)
+        .bind(tier.trim_matches('"'))
+        .bind(supplier.parent_id)
+        .bind(supplier.lifecycle_status.as_str())
+        .bind(supplier.deleted_at)
+        .bind(SupplierRecord::SCHEMA_VERSION as i32)
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
+        .timed("supplier", "create")
         .await
         .context("Failed to create supplier")?;
         
-        Ok(row.into())
+        row.try_into()
+    }
+    
+    /// Create suppliers in batches, reporting each one that could not be
+    /// created. A subsidiary's parent must already exist or come earlier in
+    /// `suppliers`.
+    pub async fn bulk_create(&self, suppliers: Vec<SupplierRecord>) -> BulkInsertReport<SupplierRecord> {
+        bulk_insert::<Self>(&self.pool, &suppliers).await
     }
     
     /// Update existing supplier
     pub async fn update(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection")?;
+        Self::save(&mut conn, &supplier).await
+    }
+    
+    async fn save(conn: &mut PgConnection, supplier: &SupplierRecord) -> Result<SupplierRecord> {
         let contact_info = serde_json::to_value(&supplier.contact_info)?;
         let relationship = serde_json::to_string(&supplier.relationship)?;
         let compliance_history = serde_json::to_value(&supplier.compliance_history)?;
         let communication_preferences = serde_json::to_value(&supplier.communication_preferences)?;
-        let risk_profile = serde_json::to_value(&supplier.risk_profile)?;
+        let risk_profile = supplier.risk_profile.to_versioned()?;
+        let tier = serde_json::to_string(&supplier.tier)?;
         
         let row: SupplierRow = sqlx::query_as(
             r#"
@@ -158,11 +282,16 @@ impl SupplierRepository {
                 compliance_history = $5,
                 communication_preferences = $6,
                 risk_profile = $7,
-                updated_at = $8
+                tier = $8,
+                parent_id = $9,
+                lifecycle_status = $10,
+                deleted_at = $11,
+                schema_version = $12,
+                updated_at = $13
             WHERE id = $1
             RETURNING id, name, contact_info, relationship, 
                       compliance_history, communication_preferences, 
-                      risk_profile, created_at, updated_at
+                      risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             "#
         )
         .bind(supplier.id)
@@ -172,94 +301,599 @@ impl SupplierRepository {
         .bind(&compliance_history)
         .bind(&communication_preferences)
         .bind(&risk_profile)
+        .bind(tier.trim_matches('"'))
+        .bind(supplier.parent_id)
+        .bind(supplier.lifecycle_status.as_str())
+        .bind(supplier.deleted_at)
+        .bind(SupplierRecord::SCHEMA_VERSION as i32)
         .bind(Utc::now())
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
+        .timed("supplier", "save")
         .await
         .context("Failed to update supplier")?;
         
-        Ok(row.into())
+        row.try_into()
+    }
+    
+    /// Soft-delete supplier by ID; the row is kept for audit and traceability
+    pub async fn delete(&self, id: SupplierId) -> Result<bool> {
+        Ok(self.change_lifecycle_status(id, LifecycleStatus::Deleted, None).await?.is_some())
+    }
+    
+    /// Archive a supplier that is no longer in use
+    pub async fn archive(&self, id: SupplierId, user_id: Option<Uuid>) -> Result<Option<SupplierRecord>> {
+        self.change_lifecycle_status(id, LifecycleStatus::Archived, user_id).await
+    }
+    
+    /// Bring an archived supplier back into use
+    pub async fn restore(&self, id: SupplierId, user_id: Option<Uuid>) -> Result<Option<SupplierRecord>> {
+        self.change_lifecycle_status(id, LifecycleStatus::Active, user_id).await
+    }
+    
+    /// Move a supplier to another lifecycle status and record the change in
+    /// the audit trail; `None` if the supplier does not exist or is deleted
+    pub async fn change_lifecycle_status(
+        &self,
+        id: SupplierId,
+        status: LifecycleStatus,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<SupplierRecord>> {
+        let mut tx = self.pool.begin().await.context("Failed to start supplier lifecycle change")?;
+        
+        let row: Option<SupplierRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, contact_info, relationship, 
+                   compliance_history, communication_preferences, 
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            FROM suppliers
+            WHERE id = $1 AND lifecycle_status <> 'deleted'
+            FOR UPDATE
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .timed("supplier", "change_lifecycle_status")
+        .await
+        .context("Failed to fetch supplier for lifecycle change")?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        
+        let mut supplier = SupplierRecord::try_from(row)?;
+        let change = supplier.transition_to(status, Utc::now()).map_err(anyhow::Error::msg)?;
+        let supplier = Self::save(&mut tx, &supplier).await?;
+        
+        let entry = AuditEntry::new(AuditAction::LifecycleChanged, "supplier".to_string(), id.into(), user_id, None)
+            .with_details(vec![change], HashMap::new());
+        let previous_hash = AuditRepository::latest_hash(&mut tx).await?;
+        AuditRepository::insert(&mut tx, entry, previous_hash).await?;
+        
+        tx.commit().await.context("Failed to commit supplier lifecycle change")?;
+        Ok(Some(supplier))
     }
     
-    /// Delete supplier by ID
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM suppliers WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
+    /// Merge a duplicate supplier into a survivor in one transaction: the
+    /// survivor takes over the duplicate's contact details and compliance
+    /// history, its components, compliance records, tasks, emails and
+    /// suppressions, and its place in workflows. The duplicate is soft-deleted
+    /// and the merge recorded in the audit trail.
+    pub async fn merge(&self, survivor_id: SupplierId, duplicate_id: SupplierId, user_id: Option<Uuid>) -> Result<SupplierMerge> {
+        if survivor_id == duplicate_id {
+            bail!("A supplier cannot be merged into itself");
+        }
+        
+        let mut tx = self.pool.begin().await.context("Failed to start supplier merge")?;
+        
+        let rows: Vec<SupplierRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, contact_info, relationship, 
+                   compliance_history, communication_preferences, 
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            FROM suppliers
+            WHERE id = ANY($1) AND lifecycle_status <> 'deleted'
+            FOR UPDATE
+            "#
+        )
+        .bind(vec![survivor_id, duplicate_id])
+        .fetch_all(&mut *tx)
+        .timed("supplier", "merge")
+        .await
+        .context("Failed to fetch suppliers to merge")?;
+        
+        let mut survivor = None;
+        let mut duplicate = None;
+        for row in rows {
+            let record = SupplierRecord::try_from(row)?;
+            if record.id == survivor_id {
+                survivor = Some(record);
+            } else {
+                duplicate = Some(record);
+            }
+        }
+        let Some(mut survivor) = survivor else {
+            bail!("Supplier {} not found", survivor_id);
+        };
+        let Some(mut duplicate) = duplicate else {
+            bail!("Supplier {} not found", duplicate_id);
+        };
+        
+        let changes = survivor.merge(&duplicate).map_err(anyhow::Error::msg)?;
+        let survivor = Self::save(&mut tx, &survivor).await?;
+        
+        let mut moved = HashMap::new();
+        for table in SUPPLIER_TABLES {
+            let result = sqlx::query(&format!("UPDATE {} SET supplier_id = $1 WHERE supplier_id = $2", table))
+                .bind(survivor_id)
+                .bind(duplicate_id)
+                .execute(&mut *tx)
+                .timed("supplier", "merge")
+                .await
+                .with_context(|| format!("Failed to move {} to the surviving supplier", table))?;
+            moved.insert(table, result.rows_affected());
+        }
+        
+        // Subsidiaries of the duplicate become subsidiaries of the survivor
+        let subsidiaries_moved = sqlx::query("UPDATE suppliers SET parent_id = $1 WHERE parent_id = $2 AND id <> $1")
+            .bind(survivor_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .timed("supplier", "merge")
             .await
-            .context("Failed to delete supplier")?;
+            .context("Failed to move subsidiaries to the surviving supplier")?
+            .rows_affected();
+        
+        // Workflows list their suppliers by ID
+        sqlx::query(
+            r#"
+            UPDATE workflows SET
+                suppliers = CASE
+                    WHEN suppliers ? $1 THEN suppliers - $2
+                    ELSE (suppliers - $2) || to_jsonb($1::text)
+                END,
+                updated_at = NOW()
+            WHERE suppliers ? $2
+            "#
+        )
+        .bind(survivor_id.to_string())
+        .bind(duplicate_id.to_string())
+        .execute(&mut *tx)
+        .timed("supplier", "merge")
+        .await
+        .context("Failed to move workflows to the surviving supplier")?;
+        
+        // The duplicate is kept, deleted, so references to it still resolve
+        duplicate.soft_delete(Utc::now()).map_err(anyhow::Error::msg)?;
+        Self::save(&mut tx, &duplicate).await?;
+        
+        let mut metadata = HashMap::from([
+            ("merged_supplier_id".to_string(), duplicate_id.to_string()),
+            ("merged_supplier_name".to_string(), duplicate.name.clone()),
+        ]);
+        for (table, count) in &moved {
+            metadata.insert(format!("{}_moved", table), count.to_string());
+        }
+        metadata.insert("subsidiaries_moved".to_string(), subsidiaries_moved.to_string());
+        let entry = AuditEntry::new(AuditAction::SupplierMerged, "supplier".to_string(), survivor_id.into(), user_id, None)
+            .with_details(changes, metadata);
+        let previous_hash = AuditRepository::latest_hash(&mut tx).await?;
+        let audit_entry = AuditRepository::insert(&mut tx, entry, previous_hash).await?;
+        
+        tx.commit().await.context("Failed to commit supplier merge")?;
+        
+        Ok(SupplierMerge {
+            survivor,
+            merged_id: duplicate_id,
+            components_moved: moved["components"],
+            compliance_records_moved: moved["compliance_records"],
+            tasks_moved: moved["agent_tasks"],
+            emails_moved: moved["email_communications"],
+            suppressions_moved: moved["email_suppressions"],
+            subsidiaries_moved,
+            audit_entry,
+        })
+    }
+    
+    /// Find suppliers at one tier of the supply chain
+    pub async fn find_by_tier(&self, tier: SupplierTier) -> Result<Vec<SupplierRecord>> {
+        let tier_str = serde_json::to_string(&tier)?;
+        
+        let rows: Vec<SupplierRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, contact_info, relationship, 
+                   compliance_history, communication_preferences, 
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            FROM suppliers
+            WHERE tier = $1 AND lifecycle_status <> 'deleted'
+            ORDER BY name
+            "#
+        )
+        .bind(tier_str.trim_matches('"'))
+        .fetch_all(&self.pool)
+        .timed("supplier", "find_by_tier")
+        .await
+        .context("Failed to fetch suppliers by tier")?;
+        
+        rows.into_iter().map(SupplierRecord::try_from).collect()
+    }
+    
+    /// Find every supplier under a parent company, at any depth
+    pub async fn find_subsidiaries(&self, parent_id: SupplierId) -> Result<Vec<SupplierRecord>> {
+        let rows: Vec<SupplierRow> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE subsidiaries AS (
+                SELECT id FROM suppliers WHERE parent_id = $1
+                UNION
+                SELECT s.id FROM suppliers s JOIN subsidiaries sub ON s.parent_id = sub.id
+            )
+            SELECT id, name, contact_info, relationship, 
+                   compliance_history, communication_preferences, 
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            FROM suppliers
+            WHERE id IN (SELECT id FROM subsidiaries) AND id <> $1 AND lifecycle_status <> 'deleted'
+            ORDER BY name
+            "#
+        )
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .timed("supplier", "find_subsidiaries")
+        .await
+        .context("Failed to fetch subsidiaries")?;
         
-        Ok(result.rows_affected() > 0)
+        rows.into_iter().map(SupplierRecord::try_from).collect()
+    }
+    
+    /// Make a supplier a subsidiary of another, or independent with `None`.
+    /// A parent cannot be one of the supplier's own subsidiaries.
+    pub async fn set_parent(&self, supplier_id: SupplierId, parent_id: Option<SupplierId>) -> Result<Option<SupplierRecord>> {
+        let Some(mut supplier) = self.find_by_id(supplier_id).await? else {
+            return Ok(None);
+        };
+        if let Some(parent_id) = parent_id {
+            if self.find_by_id(parent_id).await?.is_none() {
+                bail!("Parent supplier {} not found", parent_id);
+            }
+            if self.find_subsidiaries(supplier_id).await?.iter().any(|s| s.id == parent_id) {
+                bail!("Supplier {} is a subsidiary of {} and cannot be its parent", parent_id, supplier_id);
+            }
+        }
+        supplier.set_parent(parent_id).map_err(anyhow::Error::msg)?;
+        self.update(supplier).await.map(Some)
     }
     
-    /// Search suppliers by name
+    /// Roll up the compliance of a parent company and all its subsidiaries
+    pub async fn compliance_rollup(&self, parent_id: SupplierId) -> Result<Option<ComplianceRollup>> {
+        let Some(parent) = self.find_by_id(parent_id).await? else {
+            return Ok(None);
+        };
+        let subsidiaries = self.find_subsidiaries(parent_id).await?;
+        Ok(Some(ComplianceRollup::from_group(&parent, &subsidiaries)))
+    }
+    
+    /// Search suppliers by name. Substring matches are served by the name
+    /// trigram index; prefer `search` for ranked results.
     pub async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>> {
-        let search_pattern = format!("%{}%", query.to_lowercase());
+        let search_pattern = format!("%{}%", query);
         
         let rows: Vec<SupplierRow> = sqlx::query_as(
             r#"
             SELECT id, name, contact_info, relationship, 
                    compliance_history, communication_preferences, 
-                   risk_profile, created_at, updated_at
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
             FROM suppliers
-            WHERE LOWER(name) LIKE $1
+            WHERE name ILIKE $1 AND lifecycle_status <> 'deleted'
             ORDER BY name
             LIMIT 100
             "#
         )
         .bind(&search_pattern)
         .fetch_all(&self.pool)
+        .timed("supplier", "search_by_name")
         .await
         .context("Failed to search suppliers by name")?;
         
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        rows.into_iter().map(SupplierRecord::try_from).collect()
     }
     
-    /// Count total suppliers
-    pub async fn count(&self) -> Result<i64> {
-        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM suppliers")
-            .fetch_one(&self.pool)
+    /// Suppliers whose name resembles one of `names`, as candidates for
+    /// matching imported names against those on record: names containing
+    /// words like the given one, or alike as a whole. Both are served by the
+    /// name trigram index.
+    pub async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let rows: Vec<SupplierRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, contact_info, relationship, 
+                       compliance_history, communication_preferences, 
+                       risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+                FROM suppliers
+                WHERE id IN (
+                    SELECT s.id FROM unnest($1::text[]) AS n(name)
+                    JOIN suppliers s ON n.name <% s.name OR s.name % n.name
+                ) AND lifecycle_status <> 'deleted'
+                ORDER BY name
+                "#
+            )
+            .bind(names)
+            .fetch_all(&pool)
+            .timed("supplier", "find_similar_names")
             .await
-            .context("Failed to count suppliers")?;
+            .context("Failed to find suppliers with similar names")
+        }).await?;
+        
+        rows.into_iter().map(SupplierRecord::try_from).collect()
+    }
+    
+    /// Search suppliers by name and contact person, best matches first.
+    /// Word matches are ranked by full-text relevance, with name similarity
+    /// added so misspelt and partial names still match.
+    pub async fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit<SupplierRecord>>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let rows: Vec<SupplierSearchRow> = self.reads.read(|pool| async move {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, contact_info, relationship, 
+                       compliance_history, communication_preferences, 
+                       risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at,
+                       ts_rank(search_vector, ts_query) + similarity(name, $1) AS rank
+                FROM suppliers, websearch_to_tsquery('simple', $1) AS ts_query
+                WHERE (search_vector @@ ts_query OR name % $1) AND lifecycle_status <> 'deleted'
+                ORDER BY rank DESC, name
+                LIMIT $2
+                "#
+            )
+            .bind(query)
+            .bind(search_limit(limit))
+            .fetch_all(&pool)
+            .timed("supplier", "search")
+            .await
+            .context("Failed to search suppliers")
+        }).await?;
+        
+        rows.into_iter()
+            .map(|row| Ok(SearchHit { rank: row.rank, item: SupplierRecord::try_from(row.supplier)? }))
+            .collect()
+    }
+    
+    /// Find supplier by primary or alternate contact email
+    pub async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>> {
+        let row: Option<SupplierRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, contact_info, relationship, 
+                   compliance_history, communication_preferences, 
+                   risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            FROM suppliers
+            WHERE lifecycle_status <> 'deleted'
+              AND (LOWER(contact_info->>'primary_email') = LOWER($1)
+               OR EXISTS (
+                   SELECT 1 FROM jsonb_array_elements_text(contact_info->'alternate_emails') AS alt(email)
+                   WHERE LOWER(alt.email) = LOWER($1)
+               ))
+            LIMIT 1
+            "#
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .timed("supplier", "find_by_email")
+        .await
+        .context("Failed to fetch supplier by email")?;
+        
+        row.map(SupplierRecord::try_from).transpose()
+    }
+    
+    /// Count suppliers that have not been deleted
+    pub async fn count(&self) -> Result<i64> {
+        let row: (i64,) = self.reads.read(|pool| async move {
+            sqlx::query_as("SELECT COUNT(*) FROM suppliers WHERE lifecycle_status <> 'deleted'")
+                .fetch_one(&pool)
+                .timed("supplier", "count")
+                .await
+                .context("Failed to count suppliers")
+        }).await?;
         
         Ok(row.0)
     }
 }
 
+#[async_trait]
+impl BulkInsert for SupplierRepository {
+    type Item = SupplierRecord;
+    
+    fn id(supplier: &SupplierRecord) -> Uuid {
+        supplier.id.into()
+    }
+    
+    async fn insert_batch(
+        conn: &mut PgConnection,
+        rows: Vec<(usize, &SupplierRecord)>,
+    ) -> Result<(Vec<SupplierRecord>, Vec<BulkRowFailure>)> {
+        let parent_ids = rows.iter().filter_map(|(_, s)| s.parent_id).map(Uuid::from).collect();
+        let mut known_parents = live_ids(conn, "suppliers", parent_ids).await?;
+        let mut columns = SupplierColumns::default();
+        let mut failed = Vec::new();
+        
+        for (index, supplier) in rows {
+            if let Some(parent_id) = supplier.parent_id {
+                if !known_parents.contains(parent_id.as_uuid()) {
+                    failed.push(BulkRowFailure { index, error: BulkRowError::MissingParent(parent_id) });
+                    continue;
+                }
+            }
+            match columns.push(supplier) {
+                Ok(()) => {
+                    known_parents.insert(supplier.id.into());
+                }
+                Err(e) => failed.push(BulkRowFailure { index, error: BulkRowError::Encoding(e.to_string()) }),
+            }
+        }
+        
+        if columns.id.is_empty() {
+            return Ok((Vec::new(), failed));
+        }
+        
+        let now = Utc::now();
+        let rows: Vec<SupplierRow> = sqlx::query_as(
+            r#"
+            INSERT INTO suppliers 
+                (id, name, contact_info, relationship, compliance_history, 
+                 communication_preferences, risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at)
+            SELECT id, name, contact_info, relationship, compliance_history,
+                   communication_preferences, risk_profile, tier, parent_id, lifecycle_status, deleted_at, $12, $13, $13
+            FROM UNNEST($1::uuid[], $2::text[], $3::jsonb[], $4::text[], $5::jsonb[],
+                        $6::jsonb[], $7::jsonb[], $8::text[], $9::uuid[], $10::text[], $11::timestamptz[])
+                AS t(id, name, contact_info, relationship, compliance_history,
+                     communication_preferences, risk_profile, tier, parent_id, lifecycle_status, deleted_at)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING id, name, contact_info, relationship, 
+                      compliance_history, communication_preferences, 
+                      risk_profile, tier, parent_id, lifecycle_status, deleted_at, schema_version, created_at, updated_at
+            "#
+        )
+        .bind(columns.id)
+        .bind(columns.name)
+        .bind(columns.contact_info)
+        .bind(columns.relationship)
+        .bind(columns.compliance_history)
+        .bind(columns.communication_preferences)
+        .bind(columns.risk_profile)
+        .bind(columns.tier)
+        .bind(columns.parent_id)
+        .bind(columns.lifecycle_status)
+        .bind(columns.deleted_at)
+        .bind(SupplierRecord::SCHEMA_VERSION as i32)
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .timed("supplier", "insert_batch")
+        .await
+        .context("Failed to bulk create suppliers")?;
+        
+        let inserted = rows.into_iter().map(SupplierRecord::try_from).collect::<Result<_>>()?;
+        Ok((inserted, failed))
+    }
+}
+
+/// Supplier fields as one array per column, for `UNNEST`
+#[derive(Default)]
+struct SupplierColumns {
+    id: Vec<Uuid>,
+    name: Vec<String>,
+    contact_info: Vec<serde_json::Value>,
+    relationship: Vec<String>,
+    compliance_history: Vec<serde_json::Value>,
+    communication_preferences: Vec<serde_json::Value>,
+    risk_profile: Vec<serde_json::Value>,
+    tier: Vec<String>,
+    parent_id: Vec<Option<Uuid>>,
+    lifecycle_status: Vec<String>,
+    deleted_at: Vec<Option<chrono::DateTime<Utc>>>,
+}
+
+impl SupplierColumns {
+    /// Append a supplier, leaving the columns unchanged if it cannot be encoded
+    fn push(&mut self, supplier: &SupplierRecord) -> serde_json::Result<()> {
+        let contact_info = serde_json::to_value(&supplier.contact_info)?;
+        let relationship = serde_json::to_string(&supplier.relationship)?;
+        let compliance_history = serde_json::to_value(&supplier.compliance_history)?;
+        let communication_preferences = serde_json::to_value(&supplier.communication_preferences)?;
+        let risk_profile = supplier.risk_profile.to_versioned()?;
+        let tier = serde_json::to_string(&supplier.tier)?;
+        
+        self.id.push(supplier.id.into());
+        self.name.push(supplier.name.clone());
+        self.contact_info.push(contact_info);
+        self.relationship.push(relationship.trim_matches('"').to_string());
+        self.compliance_history.push(compliance_history);
+        self.communication_preferences.push(communication_preferences);
+        self.risk_profile.push(risk_profile);
+        self.tier.push(tier.trim_matches('"').to_string());
+        self.parent_id.push(supplier.parent_id.map(Uuid::from));
+        self.lifecycle_status.push(supplier.lifecycle_status.as_str().to_string());
+        self.deleted_at.push(supplier.deleted_at);
+        Ok(())
+    }
+}
+
 /// Internal row type for SQLx mapping
 #[derive(Debug, FromRow)]
 struct SupplierRow {
-    id: Uuid,
+    id: SupplierId,
     name: String,
     contact_info: serde_json::Value,
     relationship: String,
     compliance_history: serde_json::Value,
     communication_preferences: serde_json::Value,
     risk_profile: serde_json::Value,
+    tier: String,
+    parent_id: Option<SupplierId>,
+    lifecycle_status: String,
+    deleted_at: Option<chrono::DateTime<Utc>>,
+    schema_version: i32,
     created_at: chrono::DateTime<Utc>,
     updated_at: chrono::DateTime<Utc>,
 }
 
-impl From<SupplierRow> for SupplierRecord {
-    fn from(row: SupplierRow) -> Self {
-        Self {
-            id: row.id,
-            name: row.name,
-            contact_info: serde_json::from_value(row.contact_info).unwrap_or_default(),
-            relationship: serde_json::from_str(&format!("\"{}\"", row.relationship))
-                .unwrap_or(SupplierRelationship::Standard),
-            compliance_history: serde_json::from_value(row.compliance_history).unwrap_or_default(),
-            communication_preferences: serde_json::from_value(row.communication_preferences)
-                .unwrap_or_default(),
-            risk_profile: serde_json::from_value(row.risk_profile).unwrap_or_default(),
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-        }
+/// Supplier row with its search rank
+#[derive(Debug, FromRow)]
+struct SupplierSearchRow {
+    #[sqlx(flatten)]
+    supplier: SupplierRow,
+    rank: f32,
+}
+
+/// Rows are read through the record's schema version so older shapes are
+/// upgraded rather than defaulted
+impl TryFrom<SupplierRow> for SupplierRecord {
+    type Error = anyhow::Error;
+    
+    fn try_from(row: SupplierRow) -> Result<Self> {
+        let id = row.id;
+        let record = serde_json::json!({
+            "schema_version": row.schema_version,
+            "id": row.id,
+            "name": row.name,
+            "contact_info": row.contact_info,
+            "relationship": row.relationship,
+            "compliance_history": row.compliance_history,
+            "communication_preferences": row.communication_preferences,
+            "risk_profile": row.risk_profile,
+            "tier": row.tier,
+            "parent_id": row.parent_id,
+            "lifecycle_status": row.lifecycle_status,
+            "deleted_at": row.deleted_at,
+            "created_at": row.created_at,
+            "updated_at": row.updated_at,
+        });
+        
+        SupplierRecord::from_versioned(record)
+            .with_context(|| format!("Failed to read supplier {}", id))
     }
 }
 
+/// Outcome of merging a duplicate supplier into a survivor
+#[derive(Debug, Clone)]
+pub struct SupplierMerge {
+    pub survivor: SupplierRecord,
+    pub merged_id: SupplierId,
+    pub components_moved: u64,
+    pub compliance_records_moved: u64,
+    pub tasks_moved: u64,
+    pub emails_moved: u64,
+    pub suppressions_moved: u64,
+    pub subsidiaries_moved: u64,
+    pub audit_entry: AuditEntry,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use elementa_models::ContactInfo;
     use proptest::prelude::*;
     
     proptest! {
@@ -271,7 +905,7 @@ mod tests {
             email in "[a-z]{5,10}@[a-z]{5,10}\\.[a-z]{2,3}",
         ) {
             let supplier = SupplierRecord {
-                id: Uuid::new_v4(),
+                id: SupplierId::new(),
                 name,
                 contact_info: ContactInfo {
                     primary_email: email,
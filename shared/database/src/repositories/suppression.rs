@@ -0,0 +1,120 @@
+//! Suppression Repository
+//!
+//! Do-not-contact entries for suppliers and email addresses.
+
+use anyhow::{Context, Result};
+use sqlx::{PgPool, FromRow};
+use uuid::Uuid;
+
+use elementa_models::{EmailSuppression, SupplierId, SuppressionSource};
+
+use crate::QueryTiming;
+
+pub struct SuppressionRepository {
+    pool: PgPool,
+}
+
+impl SuppressionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// List all suppressions, newest first
+    pub async fn list(&self) -> Result<Vec<EmailSuppression>> {
+        let rows: Vec<SuppressionRow> = sqlx::query_as(
+            r#"
+            SELECT id, supplier_id, email_address, reason, source, created_at
+            FROM email_suppressions
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .timed("suppression", "list")
+        .await
+        .context("Failed to list suppressions")?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Find a suppression covering the supplier or the address
+    pub async fn find_matching(&self, supplier_id: SupplierId, email_address: &str) -> Result<Option<EmailSuppression>> {
+        let row: Option<SuppressionRow> = sqlx::query_as(
+            r#"
+            SELECT id, supplier_id, email_address, reason, source, created_at
+            FROM email_suppressions
+            WHERE (supplier_id = $1 AND email_address IS NULL) OR email_address = LOWER($2)
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#
+        )
+        .bind(supplier_id)
+        .bind(email_address)
+        .fetch_optional(&self.pool)
+        .timed("suppression", "find_matching")
+        .await
+        .context("Failed to check suppressions")?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    /// Create new suppression
+    pub async fn create(&self, suppression: EmailSuppression) -> Result<EmailSuppression> {
+        let source_str = serde_json::to_string(&suppression.source)?.trim_matches('"').to_string();
+
+        let row: SuppressionRow = sqlx::query_as(
+            r#"
+            INSERT INTO email_suppressions (id, supplier_id, email_address, reason, source, created_at)
+            VALUES ($1, $2, LOWER($3), $4, $5, $6)
+            RETURNING id, supplier_id, email_address, reason, source, created_at
+            "#
+        )
+        .bind(suppression.id)
+        .bind(suppression.supplier_id)
+        .bind(&suppression.email_address)
+        .bind(&suppression.reason)
+        .bind(&source_str)
+        .bind(suppression.created_at)
+        .fetch_one(&self.pool)
+        .timed("suppression", "create")
+        .await
+        .context("Failed to create suppression")?;
+
+        Ok(row.into())
+    }
+
+    /// Lift a suppression
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM email_suppressions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .timed("suppression", "delete")
+            .await
+            .context("Failed to delete suppression")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct SuppressionRow {
+    id: Uuid,
+    supplier_id: Option<SupplierId>,
+    email_address: Option<String>,
+    reason: String,
+    source: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<SuppressionRow> for EmailSuppression {
+    fn from(row: SuppressionRow) -> Self {
+        Self {
+            id: row.id,
+            supplier_id: row.supplier_id,
+            email_address: row.email_address,
+            reason: row.reason,
+            source: serde_json::from_str(&format!("\"{}\"", row.source))
+                .unwrap_or(SuppressionSource::Manual),
+            created_at: row.created_at,
+        }
+    }
+}
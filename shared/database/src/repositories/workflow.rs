@@ -1,13 +1,20 @@
 //! Workflow Repository
 //!
-//! CRUD operations for workflow instances.
+//! CRUD operations for workflow instances, and the agent task queue the
+//! workflow executors share. An executor claims due tasks with
+//! `FOR UPDATE SKIP LOCKED`, so executors never block on or double-claim
+//! each other's tasks, and holds each claim under a lease it renews with
+//! `heartbeat`. Claims whose lease runs out, because their executor died or
+//! hung, are put back in the queue by `release_stale_claims`.
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use sqlx::{PgPool, FromRow};
 use uuid::Uuid;
 
-use elementa_models::{WorkflowInstance, WorkflowStatus};
+use elementa_models::{AgentTask, SupplierId, TaskStatus, WorkflowId, WorkflowInstance, WorkflowStatus};
+
+use crate::QueryTiming;
 
 pub struct WorkflowRepository {
     pool: PgPool,
@@ -19,7 +26,7 @@ impl WorkflowRepository {
     }
     
     /// Find workflow by ID
-    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<WorkflowInstance>> {
+    pub async fn find_by_id(&self, id: WorkflowId) -> Result<Option<WorkflowInstance>> {
         let row: Option<WorkflowRow> = sqlx::query_as(
             r#"
             SELECT id, client_id, campaign_name, status, suppliers,
@@ -31,6 +38,7 @@ impl WorkflowRepository {
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .timed("workflow", "find_by_id")
         .await
         .context("Failed to fetch workflow by ID")?;
         
@@ -53,6 +61,7 @@ impl WorkflowRepository {
         )
         .bind(&status_str)
         .fetch_all(&self.pool)
+        .timed("workflow", "find_by_status")
         .await
         .context("Failed to fetch workflows by status")?;
         
@@ -72,6 +81,7 @@ impl WorkflowRepository {
             "#
         )
         .fetch_all(&self.pool)
+        .timed("workflow", "find_active")
         .await
         .context("Failed to fetch active workflows")?;
         
@@ -110,6 +120,7 @@ impl WorkflowRepository {
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
+        .timed("workflow", "create")
         .await
         .context("Failed to create workflow")?;
         
@@ -117,7 +128,7 @@ impl WorkflowRepository {
     }
     
     /// Update workflow status
-    pub async fn update_status(&self, id: Uuid, status: WorkflowStatus) -> Result<bool> {
+    pub async fn update_status(&self, id: WorkflowId, status: WorkflowStatus) -> Result<bool> {
         let status_str = serde_json::to_string(&status)?.trim_matches('"').to_string();
         
         let result = sqlx::query(
@@ -127,6 +138,7 @@ impl WorkflowRepository {
         .bind(&status_str)
         .bind(Utc::now())
         .execute(&self.pool)
+        .timed("workflow", "update_status")
         .await
         .context("Failed to update workflow status")?;
         
@@ -134,20 +146,290 @@ impl WorkflowRepository {
     }
     
     /// Delete workflow
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+    pub async fn delete(&self, id: WorkflowId) -> Result<bool> {
         let result = sqlx::query("DELETE FROM workflows WHERE id = $1")
             .bind(id)
             .execute(&self.pool)
+            .timed("workflow", "delete")
             .await
             .context("Failed to delete workflow")?;
         
         Ok(result.rows_affected() > 0)
     }
+    
+    /// Queue an agent task to run at `scheduled_at`. A task already in the
+    /// queue is put back for another run, unless an executor holds it, in
+    /// which case `None` is returned.
+    pub async fn schedule_task(&self, task: AgentTask, scheduled_at: DateTime<Utc>) -> Result<Option<AgentTask>> {
+        let now = Utc::now();
+        
+        let row: Option<AgentTaskRow> = sqlx::query_as(
+            r#"
+            INSERT INTO agent_tasks
+                (id, workflow_id, task_type, supplier_id, context, status,
+                 retry_count, max_retries, scheduled_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (id) DO UPDATE SET
+                context = EXCLUDED.context,
+                status = EXCLUDED.status,
+                retry_count = EXCLUDED.retry_count,
+                max_retries = EXCLUDED.max_retries,
+                scheduled_at = EXCLUDED.scheduled_at,
+                completed_at = NULL,
+                updated_at = EXCLUDED.updated_at
+            WHERE agent_tasks.status <> 'InProgress'
+            RETURNING id, workflow_id, task_type, supplier_id, context, status,
+                      retry_count, max_retries, created_at, updated_at, completed_at,
+                      scheduled_at, claimed_by, lease_expires_at
+            "#
+        )
+        .bind(task.id)
+        .bind(task.workflow_id)
+        .bind(variant_name(&task.task_type)?)
+        .bind(task.supplier_id)
+        .bind(serde_json::to_value(&task.context)?)
+        .bind(variant_name(&TaskStatus::Queued)?)
+        .bind(task.retry_count as i32)
+        .bind(task.max_retries as i32)
+        .bind(scheduled_at)
+        .bind(now)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .timed("workflow", "schedule_task")
+        .await
+        .context("Failed to schedule agent task")?;
+        
+        row.map(AgentTask::try_from).transpose()
+    }
+    
+    /// Claim up to `limit` queued tasks due by `now` for `worker_id`, oldest
+    /// schedule first, leasing them for `lease`. Tasks another executor is
+    /// claiming at the same moment are skipped rather than waited on.
+    pub async fn claim_due_tasks(
+        &self,
+        worker_id: &str,
+        now: DateTime<Utc>,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<ClaimedTask>> {
+        let rows: Vec<AgentTaskRow> = sqlx::query_as(
+            r#"
+            WITH due AS (
+                SELECT id FROM agent_tasks
+                WHERE status = 'Queued' AND scheduled_at <= $1
+                ORDER BY scheduled_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE agent_tasks t SET
+                status = 'InProgress',
+                claimed_by = $3,
+                claimed_at = $1,
+                heartbeat_at = $1,
+                lease_expires_at = $4,
+                updated_at = $1
+            FROM due
+            WHERE t.id = due.id
+            RETURNING t.id, t.workflow_id, t.task_type, t.supplier_id, t.context, t.status,
+                      t.retry_count, t.max_retries, t.created_at, t.updated_at, t.completed_at,
+                      t.scheduled_at, t.claimed_by, t.lease_expires_at
+            "#
+        )
+        .bind(now)
+        .bind(limit)
+        .bind(worker_id)
+        .bind(now + lease)
+        .fetch_all(&self.pool)
+        .timed("workflow", "claim_due_tasks")
+        .await
+        .context("Failed to claim due agent tasks")?;
+        
+        let mut claimed = rows.into_iter().map(ClaimedTask::try_from).collect::<Result<Vec<_>>>()?;
+        claimed.sort_by_key(|c| c.scheduled_at);
+        Ok(claimed)
+    }
+    
+    /// Renew `worker_id`'s lease on a task. `false` if the worker no longer
+    /// holds the claim, e.g. because it was released as stale, in which case
+    /// the worker should stop working on the task.
+    pub async fn heartbeat(&self, task_id: Uuid, worker_id: &str, now: DateTime<Utc>, lease: Duration) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE agent_tasks SET heartbeat_at = $3, lease_expires_at = $4, updated_at = $3
+            WHERE id = $1 AND claimed_by = $2 AND status = 'InProgress'
+            "#
+        )
+        .bind(task_id)
+        .bind(worker_id)
+        .bind(now)
+        .bind(now + lease)
+        .execute(&self.pool)
+        .timed("workflow", "heartbeat")
+        .await
+        .context("Failed to renew agent task lease")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Record the outcome of a claimed task and release the claim. `false`
+    /// if `worker_id` no longer holds it.
+    pub async fn finish_task(&self, task_id: Uuid, worker_id: &str, status: TaskStatus) -> Result<bool> {
+        let now = Utc::now();
+        let completed_at = matches!(status, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled).then_some(now);
+        
+        let result = sqlx::query(
+            r#"
+            UPDATE agent_tasks SET
+                status = $3,
+                completed_at = $4,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                heartbeat_at = NULL,
+                lease_expires_at = NULL,
+                updated_at = $5
+            WHERE id = $1 AND claimed_by = $2 AND status = 'InProgress'
+            "#
+        )
+        .bind(task_id)
+        .bind(worker_id)
+        .bind(variant_name(&status)?)
+        .bind(completed_at)
+        .bind(now)
+        .execute(&self.pool)
+        .timed("workflow", "finish_task")
+        .await
+        .context("Failed to finish agent task")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Put tasks whose lease ran out before `now` back in the queue, counting
+    /// the lost run as a retry; tasks out of retries fail instead, so a task
+    /// that keeps killing its executor is not claimed forever
+    pub async fn release_stale_claims(&self, now: DateTime<Utc>) -> Result<ReleasedClaims> {
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            UPDATE agent_tasks SET
+                status = CASE WHEN retry_count + 1 >= max_retries THEN 'Failed' ELSE 'Queued' END,
+                retry_count = retry_count + 1,
+                completed_at = CASE WHEN retry_count + 1 >= max_retries THEN $1 END,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                heartbeat_at = NULL,
+                lease_expires_at = NULL,
+                updated_at = $1
+            WHERE status = 'InProgress' AND lease_expires_at < $1
+            RETURNING id, status
+            "#
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .timed("workflow", "release_stale_claims")
+        .await
+        .context("Failed to release stale agent task claims")?;
+        
+        let (failed, requeued): (Vec<_>, Vec<_>) = rows.into_iter().partition(|(_, status)| status == "Failed");
+        let released = ReleasedClaims {
+            requeued: requeued.into_iter().map(|(id, _)| id).collect(),
+            failed: failed.into_iter().map(|(id, _)| id).collect(),
+        };
+        if !released.failed.is_empty() || !released.requeued.is_empty() {
+            tracing::warn!(
+                requeued = released.requeued.len(),
+                failed = released.failed.len(),
+                "Released agent task claims with expired leases"
+            );
+        }
+        Ok(released)
+    }
+}
+
+/// An agent task claimed by a workflow executor
+#[derive(Debug, Clone)]
+pub struct ClaimedTask {
+    pub task: AgentTask,
+    pub scheduled_at: DateTime<Utc>,
+    pub claimed_by: String,
+    /// When the claim lapses unless renewed with `heartbeat`
+    pub lease_expires_at: DateTime<Utc>,
+}
+
+/// Tasks `release_stale_claims` took back from executors
+#[derive(Debug, Clone, Default)]
+pub struct ReleasedClaims {
+    /// Back in the queue for another executor
+    pub requeued: Vec<Uuid>,
+    /// Out of retries
+    pub failed: Vec<Uuid>,
+}
+
+/// An enum as stored, by its serialized name
+fn variant_name<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?.trim_matches('"').to_string())
+}
+
+fn from_variant_name<T: serde::de::DeserializeOwned>(name: &str) -> Result<T> {
+    serde_json::from_str(&format!("\"{}\"", name)).with_context(|| format!("Unknown variant '{}'", name))
 }
 
 #[derive(Debug, FromRow)]
-struct WorkflowRow {
+struct AgentTaskRow {
     id: Uuid,
+    workflow_id: WorkflowId,
+    task_type: String,
+    supplier_id: SupplierId,
+    context: serde_json::Value,
+    status: String,
+    retry_count: i32,
+    max_retries: i32,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    scheduled_at: DateTime<Utc>,
+    claimed_by: Option<String>,
+    lease_expires_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<AgentTaskRow> for AgentTask {
+    type Error = anyhow::Error;
+    
+    fn try_from(row: AgentTaskRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id,
+            workflow_id: row.workflow_id,
+            task_type: from_variant_name(&row.task_type)?,
+            supplier_id: row.supplier_id,
+            context: serde_json::from_value(row.context).context("Malformed agent task context")?,
+            status: from_variant_name(&row.status)?,
+            retry_count: row.retry_count.max(0) as u32,
+            max_retries: row.max_retries.max(0) as u32,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            completed_at: row.completed_at,
+        })
+    }
+}
+
+impl TryFrom<AgentTaskRow> for ClaimedTask {
+    type Error = anyhow::Error;
+    
+    fn try_from(mut row: AgentTaskRow) -> Result<Self> {
+        let (Some(claimed_by), Some(lease_expires_at)) = (row.claimed_by.take(), row.lease_expires_at) else {
+            anyhow::bail!("Agent task {} is not claimed", row.id);
+        };
+        let scheduled_at = row.scheduled_at;
+        Ok(Self {
+            task: AgentTask::try_from(row)?,
+            scheduled_at,
+            claimed_by,
+            lease_expires_at,
+        })
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct WorkflowRow {
+    id: WorkflowId,
     client_id: Uuid,
     campaign_name: String,
     status: String,
@@ -0,0 +1,183 @@
+//! Database credentials kept out of connection URLs.
+//!
+//! A `SecretSource` names where a password is read from: a file, such as
+//! one written by a Vault Agent template, a Kubernetes secret volume or the
+//! AWS Secrets Manager CSI driver, or an environment variable injected by
+//! the platform. Files are re-read by `CredentialRotation`, so a password
+//! rotated in the secret manager reaches new PostgreSQL connections without
+//! a restart; connections already open stay authenticated and are replaced
+//! as the pool recycles them.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{PostgresPool, PostgresPools};
+
+/// Where a credential is read from. Written in configuration as
+/// `file:/run/secrets/postgres-password`, `env:POSTGRES_PASSWORD`, or the
+/// password itself.
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretSource {
+    /// The secret itself, as written in configuration; for development
+    Value(String),
+    /// A file holding the secret, re-read on every `read`
+    File(PathBuf),
+    /// An environment variable holding the secret
+    Env(String),
+}
+
+impl SecretSource {
+    /// Read the secret, without the trailing newline secret files usually end with
+    pub async fn read(&self) -> Result<String> {
+        let secret = match self {
+            Self::Value(value) => value.clone(),
+            Self::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read secret file {}", path.display()))?,
+            Self::Env(name) => std::env::var(name).with_context(|| format!("Secret environment variable {} is not set", name))?,
+        };
+        let secret = secret.trim_end_matches(['\r', '\n']);
+        if secret.is_empty() {
+            bail!("Secret from {:?} is empty", self);
+        }
+        Ok(secret.to_string())
+    }
+}
+
+impl FromStr for SecretSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix("file:") {
+            return Ok(Self::File(PathBuf::from(path)));
+        }
+        if let Some(name) = s.strip_prefix("env:") {
+            return Ok(Self::Env(name.to_string()));
+        }
+        if s.is_empty() {
+            bail!("Secret must not be empty");
+        }
+        Ok(Self::Value(s.to_string()))
+    }
+}
+
+// Never print a secret written in configuration
+impl fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(_) => f.write_str("Value(<redacted>)"),
+            Self::File(path) => write!(f, "File({})", path.display()),
+            Self::Env(name) => write!(f, "Env({})", name),
+        }
+    }
+}
+
+/// Applies a rotated PostgreSQL password to the pools' new connections
+pub struct CredentialRotation {
+    pools: PostgresPools,
+    password: SecretSource,
+    interval: Duration,
+    /// Password last applied; `None` until the first refresh
+    applied: Mutex<Option<String>>,
+}
+
+impl CredentialRotation {
+    pub fn new(pools: PostgresPools, password: SecretSource) -> Self {
+        Self {
+            pools,
+            password,
+            interval: Duration::from_secs(60),
+            applied: Mutex::new(None),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Re-read the password, updating the pools if it changed since the
+    /// last refresh. Returns whether it had; a password that cannot be read
+    /// leaves the pools as they are.
+    pub async fn refresh(&self) -> Result<bool> {
+        let password = self.password.read().await?;
+        let previous = self.applied.lock().expect("credential lock poisoned").replace(password.clone());
+        if previous.as_deref() == Some(password.as_str()) {
+            return Ok(false);
+        }
+        
+        apply_password(self.pools.primary(), &password);
+        if let Some(replica) = self.pools.replica() {
+            apply_password(replica, &password);
+        }
+        let rotated = previous.is_some();
+        if rotated {
+            tracing::info!("Applied rotated PostgreSQL password from {:?}", self.password);
+        }
+        Ok(rotated)
+    }
+
+    /// Refresh the password every interval until the task is aborted
+    pub async fn run(self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.refresh().await {
+                tracing::warn!("PostgreSQL password refresh failed, keeping the current one: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Use `password` for the pool's new connections
+fn apply_password(pool: &PostgresPool, password: &str) {
+    let options = (*pool.connect_options()).clone().password(password);
+    pool.set_connect_options(options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secret_source() {
+        assert_eq!("file:/run/secrets/pg".parse::<SecretSource>().unwrap(), SecretSource::File("/run/secrets/pg".into()));
+        assert_eq!("env:PG_PASSWORD".parse::<SecretSource>().unwrap(), SecretSource::Env("PG_PASSWORD".to_string()));
+        assert_eq!("hunter2".parse::<SecretSource>().unwrap(), SecretSource::Value("hunter2".to_string()));
+        // Only a leading prefix names a source
+        assert_eq!("my-file:x".parse::<SecretSource>().unwrap(), SecretSource::Value("my-file:x".to_string()));
+        assert!("".parse::<SecretSource>().is_err());
+
+        // Written values never show up in logs
+        assert_eq!(format!("{:?}", SecretSource::Value("hunter2".to_string())), "Value(<redacted>)");
+    }
+
+    #[tokio::test]
+    async fn test_read_secret_file() {
+        let path = std::env::temp_dir().join(format!("elementa-secret-{}", uuid::Uuid::new_v4()));
+        let source = SecretSource::File(path.clone());
+
+        tokio::fs::write(&path, "s3cret\r\n").await.unwrap();
+        assert_eq!(source.read().await.unwrap(), "s3cret");
+
+        // Blank secrets are an error rather than an empty password
+        tokio::fs::write(&path, "\n").await.unwrap();
+        assert!(source.read().await.unwrap_err().to_string().contains("is empty"));
+
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(source.read().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_secret_env() {
+        std::env::set_var("ELEMENTA_TEST_SECRET", "from-env\n");
+        assert_eq!(SecretSource::Env("ELEMENTA_TEST_SECRET".to_string()).read().await.unwrap(), "from-env");
+        assert!(SecretSource::Env("ELEMENTA_TEST_SECRET_UNSET".to_string()).read().await.is_err());
+        assert_eq!(SecretSource::Value("inline".to_string()).read().await.unwrap(), "inline");
+    }
+}
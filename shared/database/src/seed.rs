@@ -0,0 +1,695 @@
+//! Demo data for local development and demos.
+//!
+//! `Seeder` loads a small data set that hangs together: suppliers with
+//! campaign histories and a subsidiary, components with their CAS numbers
+//! and an assembly built from them, the chemicals they contain, compliance
+//! records with a certification about to expire, last year's campaign and
+//! this year's in progress with its outreach, replies and escalation,
+//! supplier declarations in GridFS, and an audit chain recording it all.
+//! IDs are fixed, so seeding a database that already holds the data does
+//! nothing; a run that fails part-way leaves what it loaded, so reset the
+//! database before seeding again. Seeding inside `tenant::scope` loads the
+//! data for that tenant.
+//!
+//! Built with the `seed` feature; `make seed` runs the `elementa-seed` binary.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use uuid::Uuid;
+
+use elementa_models::{
+    Address, AuditAction, AuditEntry, CASRecord, Certification, CertificationType,
+    ChemicalSubstance, CommunicationPreferences, ComplianceHistoryEntry, ComplianceRecord, ComplianceStatus,
+    Component, ComponentId, Concentration, ConcentrationBasis, ConcentrationUnit, DeliveryStatus,
+    DocumentReference, EmailAttachment, EmailCommunication, EmailDirection, EmailProcessingStatus, Escalation,
+    EscalationType, ExtractionMethod, MaterialType, ReplyClassification, RiskLevel, RiskProfile, SupplierId,
+    SupplierRecord, SupplierRelationship, SupplierTier, TechnicalLevel, WorkflowId, WorkflowInstance,
+    WorkflowProgress, WorkflowStatus,
+};
+
+use crate::{
+    AuditRepository, ChemicalRepository, ComplianceRepository, ComponentRepository, DocumentMetadata,
+    DocumentRepository, EmailRepository, MongoDatabase, PostgresPools, SupplierRepository, WorkflowRepository,
+};
+
+/// Agent recorded on the seeded audit entries
+const SEED_AGENT: &str = "demo-seed";
+
+/// Fixed ID of the `n`th seeded entity of a kind
+const fn seed_id(kind: u128, n: u128) -> Uuid {
+    Uuid::from_u128(0x5eed_0000_0000_0000_0000_0000_0000_0000 | kind << 32 | n)
+}
+
+const SUPPLIER: u128 = 1;
+const COMPONENT: u128 = 2;
+const CAMPAIGN: u128 = 3;
+const EMAIL: u128 = 4;
+const DOCUMENT: u128 = 5;
+const ESCALATION: u128 = 6;
+
+struct DemoSupplier {
+    name: &'static str,
+    email: &'static str,
+    contact: &'static str,
+    city: &'static str,
+    postal_code: &'static str,
+    country: &'static str,
+    language: &'static str,
+    relationship: SupplierRelationship,
+    risk: RiskLevel,
+    /// Index of the parent company among the demo suppliers
+    parent: Option<usize>,
+    do_not_contact: bool,
+    /// Outcome of last year's campaign, and days taken to respond
+    last_campaign: (ComplianceStatus, Option<i32>),
+}
+
+const SUPPLIERS: [DemoSupplier; 6] = [
+    DemoSupplier {
+        name: "Nordwerk Polymers GmbH",
+        email: "compliance@nordwerk-polymers.example",
+        contact: "Katrin Vogel",
+        city: "Hamburg",
+        postal_code: "20457",
+        country: "DE",
+        language: "de",
+        relationship: SupplierRelationship::Strategic,
+        risk: RiskLevel::Low,
+        parent: None,
+        do_not_contact: false,
+        last_campaign: (ComplianceStatus::Complete, Some(6)),
+    },
+    DemoSupplier {
+        name: "Nordwerk Coatings Sp. z o.o.",
+        email: "jakosc@nordwerk-coatings.example",
+        contact: "Piotr Nowak",
+        city: "Poznan",
+        postal_code: "61-001",
+        country: "PL",
+        language: "pl",
+        relationship: SupplierRelationship::Standard,
+        risk: RiskLevel::Medium,
+        parent: Some(0),
+        do_not_contact: false,
+        last_campaign: (ComplianceStatus::PartiallyComplete, Some(19)),
+    },
+    DemoSupplier {
+        name: "Pacific Fluorotech Co., Ltd.",
+        email: "regulatory@pacific-fluorotech.example",
+        contact: "Haruto Sato",
+        city: "Osaka",
+        postal_code: "530-0001",
+        country: "JP",
+        language: "ja",
+        relationship: SupplierRelationship::Preferred,
+        risk: RiskLevel::High,
+        parent: None,
+        do_not_contact: false,
+        last_campaign: (ComplianceStatus::Complete, Some(11)),
+    },
+    DemoSupplier {
+        name: "Midwest Fastener Supply",
+        email: "quality@midwest-fastener.example",
+        contact: "Dana Brooks",
+        city: "Columbus",
+        postal_code: "43215",
+        country: "US",
+        language: "en",
+        relationship: SupplierRelationship::Standard,
+        risk: RiskLevel::Low,
+        parent: None,
+        do_not_contact: false,
+        last_campaign: (ComplianceStatus::Complete, Some(3)),
+    },
+    DemoSupplier {
+        name: "Shenzhen Brightcircuit Electronics",
+        email: "sales@brightcircuit.example",
+        contact: "Li Wei",
+        city: "Shenzhen",
+        postal_code: "518000",
+        country: "CN",
+        language: "zh",
+        relationship: SupplierRelationship::NewVendor,
+        risk: RiskLevel::Medium,
+        parent: None,
+        do_not_contact: false,
+        last_campaign: (ComplianceStatus::NotStarted, None),
+    },
+    DemoSupplier {
+        name: "Atelier Metaux Precis SARL",
+        email: "contact@metaux-precis.example",
+        contact: "Claire Dubois",
+        city: "Lyon",
+        postal_code: "69002",
+        country: "FR",
+        language: "fr",
+        relationship: SupplierRelationship::AtRisk,
+        risk: RiskLevel::Critical,
+        parent: None,
+        do_not_contact: true,
+        last_campaign: (ComplianceStatus::NonCompliant, None),
+    },
+];
+
+/// CAS number, name and whether it is a PFAS
+const CHEMICALS: [(&str, &str, bool); 10] = [
+    ("9002-84-0", "Polytetrafluoroethylene (PTFE)", true),
+    ("13252-13-6", "HFPO dimer acid (GenX)", true),
+    ("335-67-1", "Perfluorooctanoic acid (PFOA)", true),
+    ("375-95-1", "Perfluorononanoic acid (PFNA)", true),
+    ("7439-92-1", "Lead", false),
+    ("80-05-7", "Bisphenol A", false),
+    ("7440-02-0", "Nickel", false),
+    ("7440-47-3", "Chromium", false),
+    ("7429-90-5", "Aluminium", false),
+    ("7440-50-8", "Copper", false),
+];
+
+struct DemoComponent {
+    part_number: &'static str,
+    description: &'static str,
+    cas_numbers: &'static [&'static str],
+    material: MaterialType,
+    supplier: usize,
+}
+
+const COMPONENTS: [DemoComponent; 8] = [
+    DemoComponent {
+        part_number: "NW-PA66-GF30",
+        description: "PA66 housing resin, 30% glass fibre, PTFE lubricated",
+        cas_numbers: &["9002-84-0"],
+        material: MaterialType::Polymer,
+        supplier: 0,
+    },
+    DemoComponent {
+        part_number: "NW-CT-220",
+        description: "Fluoropolymer release coating",
+        cas_numbers: &["9002-84-0", "13252-13-6"],
+        material: MaterialType::Chemical,
+        supplier: 1,
+    },
+    DemoComponent {
+        part_number: "PF-GSK-12",
+        description: "PTFE flat gasket, 12 mm",
+        cas_numbers: &["9002-84-0"],
+        material: MaterialType::Polymer,
+        supplier: 2,
+    },
+    DemoComponent {
+        part_number: "PF-SURF-07",
+        description: "Fluorosurfactant processing aid",
+        cas_numbers: &["335-67-1", "375-95-1"],
+        material: MaterialType::Chemical,
+        supplier: 2,
+    },
+    DemoComponent {
+        part_number: "MF-M4X12-A2",
+        description: "M4 x 12 stainless steel hex screw, A2",
+        cas_numbers: &["7440-02-0", "7440-47-3"],
+        material: MaterialType::Metal,
+        supplier: 3,
+    },
+    DemoComponent {
+        part_number: "BC-PCB-4L",
+        description: "4-layer controller PCB assembly",
+        cas_numbers: &["7439-92-1", "80-05-7", "7440-50-8"],
+        material: MaterialType::Electronic,
+        supplier: 4,
+    },
+    DemoComponent {
+        part_number: "AMP-BRKT-02",
+        description: "Machined aluminium mounting bracket",
+        cas_numbers: &["7429-90-5"],
+        material: MaterialType::Metal,
+        supplier: 5,
+    },
+    DemoComponent {
+        part_number: "EL-CTRL-100",
+        description: "Controller enclosure assembly",
+        cas_numbers: &[],
+        material: MaterialType::Composite,
+        supplier: 0,
+    },
+];
+
+/// Parts of the controller assembly (the last component) and how many of each it takes
+const ASSEMBLY: [(usize, f64); 5] = [(0, 1.0), (2, 2.0), (4, 4.0), (5, 1.0), (6, 1.0)];
+
+/// How each contacted supplier answered this year's request
+struct DemoReply {
+    supplier: usize,
+    days_after_request: i64,
+    classification: ReplyClassification,
+    body: &'static str,
+    /// Declaration attached to the reply
+    attachment: Option<&'static str>,
+}
+
+const REPLIES: [DemoReply; 3] = [
+    DemoReply {
+        supplier: 0,
+        days_after_request: 4,
+        classification: ReplyClassification::CompleteResponse,
+        body: "Please find attached our full material declaration for NW-PA66-GF30. PTFE is present as a lubricant below 2% w/w.",
+        attachment: Some("Nordwerk_NW-PA66-GF30_declaration.txt"),
+    },
+    DemoReply {
+        supplier: 2,
+        days_after_request: 9,
+        classification: ReplyClassification::PartialResponse,
+        body: "Attached is the declaration for PF-GSK-12. The declaration for PF-SURF-07 is with our regulatory team and will follow.",
+        attachment: Some("PacificFluorotech_PF-GSK-12_declaration.txt"),
+    },
+    DemoReply {
+        supplier: 3,
+        days_after_request: 2,
+        classification: ReplyClassification::Question,
+        body: "Do you need the declaration per screw size, or is one for the whole M4 A2 range acceptable?",
+        attachment: None,
+    },
+];
+
+/// What `Seeder::run` loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    /// The demo data was already there, so nothing was loaded
+    pub already_seeded: bool,
+    pub suppliers: usize,
+    pub components: usize,
+    pub chemicals: usize,
+    pub compliance_records: usize,
+    pub campaigns: usize,
+    pub emails: usize,
+    pub documents: usize,
+    pub audit_entries: usize,
+}
+
+/// Loads the demo data set
+pub struct Seeder {
+    pools: PostgresPools,
+    documents: Option<DocumentRepository>,
+}
+
+impl Seeder {
+    pub fn new(pools: PostgresPools) -> Self {
+        Self { pools, documents: None }
+    }
+
+    /// Also store the suppliers' declarations in GridFS
+    pub fn with_documents(mut self, database: &MongoDatabase) -> Self {
+        self.documents = Some(DocumentRepository::new(database));
+        self
+    }
+
+    pub async fn run(&self) -> Result<SeedReport> {
+        let pool = self.pools.primary().clone();
+        let suppliers = SupplierRepository::new(pool.clone());
+        if suppliers.find_by_id_including_deleted(seed_id(SUPPLIER, 0).into()).await?.is_some() {
+            tracing::info!("Demo data is already loaded");
+            return Ok(SeedReport { already_seeded: true, ..SeedReport::default() });
+        }
+
+        let now = Utc::now();
+        let campaign_start = now - Duration::days(21);
+        let last_campaign = WorkflowId::from(seed_id(CAMPAIGN, 0));
+        let campaign = WorkflowId::from(seed_id(CAMPAIGN, 1));
+        let mut report = SeedReport::default();
+        let mut audit = Vec::new();
+
+        let chemicals = CHEMICALS.iter()
+            .map(|(cas_number, name, is_pfas)| ChemicalSubstance::builder()
+                .cas_number(*cas_number)
+                .chemical_name(*name)
+                .is_pfas(*is_pfas)
+                .build())
+            .collect::<Result<Vec<_>, _>>()?;
+        report.chemicals = ChemicalRepository::new(pool.clone()).bulk_upsert(chemicals).await?;
+
+        // Parents come before their subsidiaries, so each parent exists when its subsidiaries are created
+        let mut supplier_ids = Vec::new();
+        for (n, demo) in SUPPLIERS.iter().enumerate() {
+            let supplier = suppliers.create(demo_supplier(n, demo, last_campaign, now)).await
+                .with_context(|| format!("Failed to seed supplier {}", demo.name))?;
+            audit.push(audit_entry(AuditAction::UserAction, "supplier", supplier.id.into(), now - Duration::days(400)));
+            supplier_ids.push(supplier.id);
+        }
+        report.suppliers = supplier_ids.len();
+
+        let components_repo = ComponentRepository::new(pool.clone());
+        let mut component_ids = Vec::new();
+        for (n, demo) in COMPONENTS.iter().enumerate() {
+            let mut component = Component::new(demo.part_number.to_string(), demo.description.to_string(), supplier_ids[demo.supplier]);
+            component.id = ComponentId::from(seed_id(COMPONENT, n as u128));
+            component.material_type = demo.material.clone();
+            for cas_number in demo.cas_numbers {
+                component.add_cas_number(cas_number.to_string()).map_err(anyhow::Error::msg)?;
+            }
+            let component = components_repo.create(component).await
+                .with_context(|| format!("Failed to seed component {}", demo.part_number))?;
+            component_ids.push(component.id);
+        }
+        let assembly = component_ids[COMPONENTS.len() - 1];
+        for (part, quantity) in ASSEMBLY {
+            components_repo.add_child(assembly, component_ids[part], quantity).await?;
+        }
+        report.components = component_ids.len();
+
+        let workflows = WorkflowRepository::new(pool.clone());
+        workflows.create(WorkflowInstance {
+            id: last_campaign,
+            campaign_name: format!("{} PFAS baseline survey", (now - Duration::days(400)).year()),
+            suppliers: supplier_ids.clone(),
+            status: WorkflowStatus::Completed,
+            start_date: now - Duration::days(400),
+            deadline: now - Duration::days(340),
+            progress: WorkflowProgress {
+                total_suppliers: 6,
+                contacted_suppliers: 5,
+                responded_suppliers: 4,
+                compliant_suppliers: 3,
+                non_compliant_suppliers: 1,
+                escalated_suppliers: 1,
+                completion_percentage: 100.0,
+            },
+            ..WorkflowInstance::default()
+        }).await?;
+        audit.push(audit_entry(AuditAction::WorkflowCompleted, "workflow", last_campaign.into(), now - Duration::days(338)));
+
+        let contacted: Vec<usize> = (0..SUPPLIERS.len()).filter(|n| !SUPPLIERS[*n].do_not_contact).collect();
+        workflows.create(WorkflowInstance {
+            id: campaign,
+            campaign_name: format!("{} PFAS declaration campaign", campaign_start.year()),
+            suppliers: supplier_ids.clone(),
+            status: WorkflowStatus::InProgress,
+            start_date: campaign_start,
+            deadline: campaign_start + Duration::days(60),
+            progress: WorkflowProgress {
+                total_suppliers: SUPPLIERS.len() as u32,
+                contacted_suppliers: contacted.len() as u32,
+                responded_suppliers: REPLIES.len() as u32,
+                compliant_suppliers: 1,
+                non_compliant_suppliers: 0,
+                escalated_suppliers: 1,
+                completion_percentage: 100.0 * REPLIES.len() as f64 / SUPPLIERS.len() as f64,
+            },
+            escalations: vec![Escalation {
+                id: seed_id(ESCALATION, 0),
+                supplier_id: supplier_ids[5],
+                escalation_type: EscalationType::SupplierDispute,
+                reason: "Supplier asked not to be emailed; request the declaration by phone".to_string(),
+                created_at: campaign_start,
+                resolved_at: None,
+                assigned_to: Some("compliance-team".to_string()),
+            }],
+            ..WorkflowInstance::default()
+        }).await?;
+        audit.push(audit_entry(AuditAction::WorkflowStarted, "workflow", campaign.into(), campaign_start));
+        audit.push(audit_entry(AuditAction::EscalationCreated, "workflow", campaign.into(), campaign_start + Duration::minutes(1)));
+        report.campaigns = 2;
+
+        let emails = EmailRepository::new(pool.clone());
+        let mut email_count = 0;
+        let mut next_email = || {
+            email_count += 1;
+            seed_id(EMAIL, email_count)
+        };
+        for n in &contacted {
+            let demo = &SUPPLIERS[*n];
+            let sent_at = campaign_start + Duration::hours(2 + *n as i64);
+            let request = emails.create(outbound_email(
+                next_email(),
+                supplier_ids[*n],
+                campaign,
+                demo,
+                format!("PFAS material declaration request - {}", demo.name),
+                format!(
+                    "Dear {},\n\nAs part of our {} PFAS declaration campaign, please send a material declaration for the parts you supply to us by {}.\n\nKind regards,\nElementa Compliance",
+                    demo.contact,
+                    campaign_start.year(),
+                    (campaign_start + Duration::days(60)).format("%d %B %Y"),
+                ),
+                sent_at,
+                None,
+            )).await?;
+            audit.push(audit_entry(AuditAction::EmailSent, "email", request.id, sent_at));
+
+            let Some(reply) = REPLIES.iter().find(|r| r.supplier == *n) else {
+                // No answer yet, so a reminder went out after the follow-up interval
+                let reminded_at = sent_at + Duration::days(7);
+                let reminder = emails.create(outbound_email(
+                    next_email(),
+                    supplier_ids[*n],
+                    campaign,
+                    demo,
+                    format!("Reminder: PFAS material declaration request - {}", demo.name),
+                    format!("Dear {},\n\nA reminder that we are waiting for your PFAS material declaration.\n\nKind regards,\nElementa Compliance", demo.contact),
+                    reminded_at,
+                    Some(&request),
+                )).await?;
+                audit.push(audit_entry(AuditAction::EmailSent, "email", reminder.id, reminded_at));
+                continue;
+            };
+
+            let received_at = sent_at + Duration::days(reply.days_after_request);
+            let mut attachments = Vec::new();
+            if let Some(file_name) = reply.attachment {
+                let document_id = seed_id(DOCUMENT, *n as u128);
+                let content = declaration(demo, *n);
+                let uploaded = match &self.documents {
+                    Some(documents) => {
+                        let mut metadata = DocumentMetadata::new(file_name, "text/plain");
+                        metadata.id = document_id;
+                        metadata.supplier_id = Some(supplier_ids[*n].into());
+                        metadata.campaign_id = Some(campaign.into());
+                        metadata.status = "extracted".to_string();
+                        documents.upload(metadata, futures_util::io::Cursor::new(content.clone().into_bytes())).await?;
+                        report.documents += 1;
+                        audit.push(audit_entry(AuditAction::DocumentUploaded, "document", document_id, received_at));
+                        true
+                    }
+                    None => false,
+                };
+                attachments.push(EmailAttachment {
+                    file_name: file_name.to_string(),
+                    file_type: "text/plain".to_string(),
+                    file_size: content.len() as i64,
+                    document_id: uploaded.then_some(document_id),
+                    status: if uploaded { "extracted" } else { "skipped" }.to_string(),
+                    cas_numbers_found: COMPONENTS.iter().filter(|c| c.supplier == *n).map(|c| c.cas_numbers.len()).sum(),
+                    needs_review: false,
+                    error: None,
+                });
+            }
+
+            let inbound_id = next_email();
+            let inbound = emails.create(EmailCommunication {
+                id: inbound_id,
+                thread_id: request.thread_id.clone(),
+                supplier_id: supplier_ids[*n],
+                direction: EmailDirection::Inbound,
+                subject: format!("Re: {}", request.subject),
+                body: reply.body.to_string(),
+                attachments,
+                received_at: Some(received_at),
+                delivery_status: DeliveryStatus::Delivered,
+                processing_status: EmailProcessingStatus::Processed,
+                message_id: Some(format!("{}@{}", inbound_id.simple(), email_domain(demo))),
+                in_reply_to: request.message_id.clone(),
+                references: request.message_id.iter().cloned().collect(),
+                classification: Some(reply.classification),
+                classification_confidence: Some(0.92),
+                ..EmailCommunication::default()
+            }).await?;
+            audit.push(audit_entry(AuditAction::EmailReceived, "email", inbound.id, received_at));
+        }
+        report.emails = email_count as usize;
+
+        let compliance = ComplianceRepository::new(pool.clone());
+        for n in [0, 2, 3, 4] {
+            let record = demo_compliance_record(n, &supplier_ids, &component_ids, campaign_start, now)?;
+            let record = compliance.create(record).await
+                .with_context(|| format!("Failed to seed compliance record for {}", SUPPLIERS[n].name))?;
+            audit.push(audit_entry(AuditAction::ComplianceRecordCreated, "compliance_record", record.id.into(), record.submission_date));
+            report.compliance_records += 1;
+        }
+
+        report.audit_entries = write_audit_chain(&self.pools, audit).await?;
+        tracing::info!(?report, "Loaded demo data");
+        Ok(report)
+    }
+}
+
+fn demo_supplier(n: usize, demo: &DemoSupplier, last_campaign: WorkflowId, now: DateTime<Utc>) -> SupplierRecord {
+    let (status, response_time_days) = demo.last_campaign.clone();
+    let mut supplier = SupplierRecord::new(demo.name.to_string(), demo.email.to_string(), demo.contact.to_string());
+    supplier.id = SupplierId::from(seed_id(SUPPLIER, n as u128));
+    supplier.relationship = demo.relationship.clone();
+    supplier.contact_info.address = Some(Address {
+        street: "1 Industriestrasse".to_string(),
+        city: demo.city.to_string(),
+        state: None,
+        postal_code: demo.postal_code.to_string(),
+        country: demo.country.to_string(),
+    });
+    if let Some(parent) = demo.parent {
+        supplier.parent_id = Some(seed_id(SUPPLIER, parent as u128).into());
+        supplier.tier = SupplierTier::Tier2;
+    }
+    supplier.communication_preferences = CommunicationPreferences {
+        preferred_language: demo.language.to_string(),
+        technical_level: TechnicalLevel::Intermediate,
+        do_not_contact: demo.do_not_contact,
+        ..CommunicationPreferences::default()
+    };
+    supplier.risk_profile = RiskProfile {
+        compliance_risk: demo.risk.clone(),
+        response_reliability: if response_time_days.is_some() { RiskLevel::Low } else { RiskLevel::High },
+        data_quality: demo.risk.clone(),
+        overall_score: match demo.risk {
+            RiskLevel::Low => 0.2,
+            RiskLevel::Medium => 0.45,
+            RiskLevel::High => 0.7,
+            RiskLevel::Critical => 0.9,
+        },
+        last_assessed: now - Duration::days(30),
+    };
+    supplier.compliance_history = vec![ComplianceHistoryEntry {
+        campaign_id: last_campaign,
+        completeness_score: match status {
+            ComplianceStatus::Complete => 1.0,
+            ComplianceStatus::PartiallyComplete => 0.6,
+            _ => 0.0,
+        },
+        status,
+        response_time_days,
+        last_updated: now - Duration::days(340),
+    }];
+    supplier
+}
+
+fn email_domain(demo: &DemoSupplier) -> &'static str {
+    demo.email.split_once('@').map_or("example", |(_, domain)| domain)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn outbound_email(
+    id: Uuid,
+    supplier_id: SupplierId,
+    campaign: WorkflowId,
+    demo: &DemoSupplier,
+    subject: String,
+    body: String,
+    sent_at: DateTime<Utc>,
+    replying_to: Option<&EmailCommunication>,
+) -> EmailCommunication {
+    let message_id = format!("{}@mail.elementa.example", id.simple());
+    EmailCommunication {
+        id,
+        thread_id: replying_to.map_or_else(|| format!("seed-{}", id.simple()), |e| e.thread_id.clone()),
+        supplier_id,
+        direction: EmailDirection::Outbound,
+        subject,
+        body,
+        sent_at: Some(sent_at),
+        delivery_status: DeliveryStatus::Delivered,
+        processing_status: EmailProcessingStatus::Processed,
+        message_id: Some(message_id),
+        in_reply_to: replying_to.and_then(|e| e.message_id.clone()),
+        references: replying_to.and_then(|e| e.message_id.clone()).into_iter().collect(),
+        recipient: Some(demo.email.to_string()),
+        campaign_id: Some(campaign),
+        template_id: Some("initial_outreach".to_string()),
+        ..EmailCommunication::default()
+    }
+}
+
+/// A supplier's material declaration for the parts it supplies, as plain text
+fn declaration(demo: &DemoSupplier, n: usize) -> String {
+    let mut text = format!("Material declaration\nSupplier: {}\nContact: {} <{}>\n\n", demo.name, demo.contact, demo.email);
+    for component in COMPONENTS.iter().filter(|c| c.supplier == n) {
+        text.push_str(&format!("Part {}: {}\n", component.part_number, component.description));
+        for cas_number in component.cas_numbers {
+            let name = CHEMICALS.iter().find(|c| c.0 == *cas_number).map_or("", |c| c.1);
+            text.push_str(&format!("  CAS {} {}\n", cas_number, name));
+        }
+    }
+    text
+}
+
+fn demo_compliance_record(
+    n: usize,
+    supplier_ids: &[SupplierId],
+    component_ids: &[ComponentId],
+    campaign_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<ComplianceRecord> {
+    let part = COMPONENTS.iter().position(|c| c.supplier == n).context("Demo supplier has no components")?;
+    let submitted = campaign_start + Duration::days(3 + n as i64);
+    let source = DocumentReference {
+        document_id: seed_id(DOCUMENT, n as u128),
+        page: Some(1),
+        section: None,
+        extraction_timestamp: submitted,
+    };
+
+    let mut builder = ComplianceRecord::builder()
+        .supplier_id(supplier_ids[n])
+        .component_id(component_ids[part])
+        .submission_date(submitted);
+    for cas_number in COMPONENTS[part].cas_numbers {
+        let (_, name, is_pfas) = CHEMICALS.iter().find(|c| c.0 == *cas_number).context("Demo CAS number is not a demo chemical")?;
+        let mut cas_record = CASRecord::new(
+            cas_number.to_string(),
+            name.to_string(),
+            *is_pfas,
+            0.95,
+            source.clone(),
+            ExtractionMethod::MaterialDeclaration,
+        );
+        cas_record.concentration = Some(Concentration::new(
+            if *is_pfas { 1.8 } else { 0.05 },
+            ConcentrationUnit::Percent,
+            ConcentrationBasis::WeightByWeight,
+        ));
+        builder = builder.cas_record(cas_record);
+    }
+
+    // Brightcircuit's RoHS certificate runs out during the campaign, so it shows as expiring
+    let (certification_type, issuing_body, expiry) = match n {
+        4 => (CertificationType::RoHS, "SGS Shenzhen", now + Duration::days(20)),
+        _ => (CertificationType::ISO14001, "TUV Rheinland", now + Duration::days(500)),
+    };
+    builder = builder.certification(Certification {
+        certification_type,
+        issuing_body: issuing_body.to_string(),
+        certificate_number: format!("DEMO-{}-{:04}", SUPPLIERS[n].country, 1000 + n),
+        issue_date: now - Duration::days(700),
+        expiry_date: Some(expiry),
+        scope: COMPONENTS[part].description.to_string(),
+        source_document: source,
+        revoked_at: None,
+    });
+
+    Ok(builder.build()?)
+}
+
+fn audit_entry(action: AuditAction, entity_type: &str, entity_id: Uuid, at: DateTime<Utc>) -> AuditEntry {
+    let mut entry = AuditEntry::new(action, entity_type.to_string(), entity_id, None, Some(SEED_AGENT.to_string()));
+    entry.timestamp = at;
+    entry
+}
+
+/// Append the entries to the audit chain in time order, in one transaction
+async fn write_audit_chain(pools: &PostgresPools, mut entries: Vec<AuditEntry>) -> Result<usize> {
+    entries.sort_by_key(|e| e.timestamp);
+    let count = entries.len();
+
+    let mut tx = pools.primary().begin().await.context("Failed to begin transaction")?;
+    let mut previous_hash = AuditRepository::latest_hash(&mut tx).await?;
+    for entry in entries {
+        let entry = AuditRepository::insert(&mut tx, entry, previous_hash).await?;
+        previous_hash = Some(entry.hash);
+    }
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(count)
+}
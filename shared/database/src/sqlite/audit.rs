@@ -0,0 +1,115 @@
+//! Audit trail on SQLite
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqliteConnection;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use elementa_models::AuditEntry;
+
+use super::{now, timestamp, SqlitePool};
+use crate::repositories::audit::verify_entries;
+use crate::{AuditRepository, AuditStore, ChainVerification};
+
+/// Held while an entry is chained on, so two appends in this process never
+/// chain onto the same entry
+pub(super) static CHAIN_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub struct SqliteAuditStore {
+    pool: SqlitePool,
+}
+
+impl SqliteAuditStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Chain an entry onto the latest one and insert it, within the caller's
+/// transaction; the caller holds `CHAIN_LOCK`
+pub(super) async fn insert(conn: &mut SqliteConnection, mut entry: AuditEntry) -> Result<AuditEntry> {
+    let previous_hash: Option<String> = sqlx::query_scalar(
+        "SELECT hash FROM audit_entries ORDER BY timestamp DESC, created_at DESC LIMIT 1"
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .context("Failed to fetch latest audit hash")?;
+
+    entry.hash = AuditRepository::calculate_hash(&entry, previous_hash.as_deref());
+    entry.previous_hash = previous_hash;
+    entry.created_at = now();
+
+    sqlx::query(
+        r#"
+        INSERT INTO audit_entries
+            (id, timestamp, entity_type, entity_id, hash, previous_hash, entry, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(entry.id.to_string())
+    .bind(timestamp(entry.timestamp))
+    .bind(&entry.details.entity_type)
+    .bind(entry.details.entity_id.to_string())
+    .bind(&entry.hash)
+    .bind(&entry.previous_hash)
+    .bind(serde_json::to_string(&entry)?)
+    .bind(timestamp(entry.created_at))
+    .execute(conn)
+    .await
+    .context("Failed to create audit entry")?;
+
+    Ok(entry)
+}
+
+fn parse(rows: Vec<String>) -> Result<Vec<AuditEntry>> {
+    rows.iter()
+        .map(|entry| serde_json::from_str(entry).context("Malformed audit entry"))
+        .collect()
+}
+
+#[async_trait]
+impl AuditStore for SqliteAuditStore {
+    async fn append(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        let _chain = CHAIN_LOCK.lock().await;
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        let entry = insert(&mut tx, entry).await?;
+        tx.commit().await.context("Failed to commit audit entry")?;
+        Ok(entry)
+    }
+
+    async fn find_by_entity(&self, entity_type: &str, entity_id: Uuid) -> Result<Vec<AuditEntry>> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT entry FROM audit_entries
+            WHERE entity_type = ? AND entity_id = ?
+            ORDER BY timestamp ASC
+            "#
+        )
+        .bind(entity_type)
+        .bind(entity_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch audit entries by entity")?;
+
+        parse(rows)
+    }
+
+    async fn verify_chain(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<ChainVerification> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT entry FROM audit_entries
+            WHERE timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp ASC, created_at ASC
+            "#
+        )
+        .bind(timestamp(from))
+        .bind(timestamp(to))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch audit entries for verification")?;
+
+        Ok(verify_entries(&parse(rows)?))
+    }
+}
@@ -0,0 +1,153 @@
+//! Compliance records on SQLite
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use elementa_models::{ComplianceRecord, ComplianceRecordId, SupplierId, Versioned};
+
+use super::{now, timestamp, SqlitePool};
+use crate::repositories::compliance::expiring_certifications;
+use crate::{ComplianceStore, ExpiringCertification};
+
+pub struct SqliteComplianceStore {
+    pool: SqlitePool,
+}
+
+impl SqliteComplianceStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse(record: String) -> Result<ComplianceRecord> {
+    Ok(ComplianceRecord::from_versioned(serde_json::from_str(&record).context("Malformed compliance record")?)?)
+}
+
+fn has_pfas(record: &ComplianceRecord) -> bool {
+    record.cas_records.iter().any(|c| c.is_pfas)
+}
+
+#[async_trait]
+impl ComplianceStore for SqliteComplianceStore {
+    async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>> {
+        let record: Option<String> = sqlx::query_scalar("SELECT record FROM compliance_records WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch compliance record by ID")?;
+
+        record.map(parse).transpose()
+    }
+
+    async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM compliance_records WHERE supplier_id = ? ORDER BY submission_date DESC"
+        )
+        .bind(supplier_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch compliance records by supplier")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM compliance_records WHERE has_pfas ORDER BY submission_date DESC"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch compliance records with PFAS")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM compliance_records WHERE has_certifications"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch compliance records with certifications")?;
+
+        let records = records.into_iter().map(parse).collect::<Result<Vec<_>>>()?;
+        Ok(expiring_certifications(&records, now, warning))
+    }
+
+    async fn create(&self, mut record: ComplianceRecord) -> Result<ComplianceRecord> {
+        record.created_at = now();
+        record.updated_at = record.created_at;
+
+        sqlx::query(
+            r#"
+            INSERT INTO compliance_records
+                (id, supplier_id, component_id, submission_date, has_pfas,
+                 has_certifications, record, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(record.id.to_string())
+        .bind(record.supplier_id.to_string())
+        .bind(record.component_id.to_string())
+        .bind(timestamp(record.submission_date))
+        .bind(has_pfas(&record))
+        .bind(!record.certifications.is_empty())
+        .bind(record.to_versioned()?.to_string())
+        .bind(timestamp(record.created_at))
+        .bind(timestamp(record.updated_at))
+        .execute(&self.pool)
+        .await
+        .context("Failed to create compliance record")?;
+
+        Ok(record)
+    }
+
+    async fn update(&self, mut record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        let created_at: Option<String> = sqlx::query_scalar("SELECT created_at FROM compliance_records WHERE id = ?")
+            .bind(record.id.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to fetch compliance record for update")?;
+        let Some(created_at) = created_at else {
+            bail!("Compliance record {} not found", record.id);
+        };
+        record.created_at = created_at.parse().context("Malformed compliance record timestamp")?;
+        record.updated_at = now();
+
+        sqlx::query(
+            r#"
+            UPDATE compliance_records SET
+                submission_date = ?2,
+                has_pfas = ?3,
+                has_certifications = ?4,
+                record = ?5,
+                updated_at = ?6
+            WHERE id = ?1
+            "#
+        )
+        .bind(record.id.to_string())
+        .bind(timestamp(record.submission_date))
+        .bind(has_pfas(&record))
+        .bind(!record.certifications.is_empty())
+        .bind(record.to_versioned()?.to_string())
+        .bind(timestamp(record.updated_at))
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update compliance record")?;
+
+        tx.commit().await.context("Failed to commit compliance record update")?;
+        Ok(record)
+    }
+
+    async fn delete(&self, id: ComplianceRecordId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM compliance_records WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete compliance record")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,85 @@
+//! SQLite backend for single-node deployments.
+//!
+//! Runs the core stores (suppliers, compliance records, workflows and the
+//! audit trail) on one SQLite file, for running Elementa on a laptop
+//! without PostgreSQL. Rows keep their record as JSON beside the columns
+//! queries use, so a record reads back exactly as written, versioned
+//! shapes are upgraded on read as they are from Postgres, and the schema
+//! (migrations/sqlite) stays small. Tenant scoping, read replicas and
+//! search ranking are Postgres features and have no equivalent here.
+//!
+//! The database runs in WAL mode so readers do not block the writer.
+//! Audit entries are chained under a lock held by the process, and a
+//! second process appending at the same moment fails rather than forking
+//! the chain.
+
+mod audit;
+mod compliance;
+mod supplier;
+mod workflow;
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+
+pub use audit::SqliteAuditStore;
+pub use compliance::SqliteComplianceStore;
+pub use supplier::SqliteSupplierStore;
+pub use workflow::SqliteWorkflowStore;
+
+pub type SqlitePool = sqlx::SqlitePool;
+
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Open the database at `database_url`, e.g. `sqlite://elementa.db`,
+/// creating it if it does not exist
+pub async fn create_sqlite_pool(database_url: &str, max_connections: u32) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str(database_url)
+        .with_context(|| format!("Invalid SQLite URL {}", database_url))?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .foreign_keys(true)
+        .busy_timeout(Duration::from_secs(5));
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
+        .await
+        .context("Failed to open SQLite database")?;
+
+    tracing::info!("Opened SQLite database");
+    Ok(pool)
+}
+
+/// Applies pending SQLite migrations
+pub async fn run_sqlite_migrations(pool: &SqlitePool) -> Result<()> {
+    tracing::info!("Running SQLite migrations");
+
+    SQLITE_MIGRATOR.run(pool)
+        .await
+        .context("Failed to run SQLite migrations")?;
+
+    tracing::info!("SQLite migrations completed successfully");
+    Ok(())
+}
+
+/// The current time at the precision stored, microseconds as in Postgres
+fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+/// A timestamp as stored: RFC 3339 UTC with microseconds, which sorts as text
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// An enum as stored, by its serialized name like the Postgres tables
+fn variant_name<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?.trim_matches('"').to_string())
+}
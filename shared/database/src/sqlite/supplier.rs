@@ -0,0 +1,217 @@
+//! Suppliers on SQLite
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use sqlx::SqliteConnection;
+
+use elementa_models::{AuditAction, AuditEntry, Lifecycle, LifecycleStatus, SupplierId, SupplierRecord, Versioned};
+
+use super::audit::{self, CHAIN_LOCK};
+use super::{now, timestamp, SqlitePool};
+use crate::store::name_words;
+use crate::SupplierStore;
+
+/// Most suppliers a name search returns, as in Postgres
+const NAME_SEARCH_LIMIT: i64 = 100;
+
+pub struct SqliteSupplierStore {
+    pool: SqlitePool,
+}
+
+impl SqliteSupplierStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn save(conn: &mut SqliteConnection, supplier: &SupplierRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE suppliers SET
+                name = ?2,
+                primary_email = ?3,
+                lifecycle_status = ?4,
+                record = ?5,
+                updated_at = ?6
+            WHERE id = ?1
+            "#
+        )
+        .bind(supplier.id.to_string())
+        .bind(&supplier.name)
+        .bind(&supplier.contact_info.primary_email)
+        .bind(supplier.lifecycle_status.as_str())
+        .bind(supplier.to_versioned()?.to_string())
+        .bind(timestamp(supplier.updated_at))
+        .execute(conn)
+        .await
+        .context("Failed to update supplier")?;
+
+        Ok(())
+    }
+}
+
+fn parse(record: String) -> Result<SupplierRecord> {
+    Ok(SupplierRecord::from_versioned(serde_json::from_str(&record).context("Malformed supplier record")?)?)
+}
+
+#[async_trait]
+impl SupplierStore for SqliteSupplierStore {
+    async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
+        let record: Option<String> = sqlx::query_scalar(
+            "SELECT record FROM suppliers WHERE id = ? AND lifecycle_status <> 'deleted'"
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch supplier by ID")?;
+
+        record.map(parse).transpose()
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>> {
+        let record: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT record FROM suppliers
+            WHERE lifecycle_status <> 'deleted'
+              AND (lower(primary_email) = lower(?1)
+               OR EXISTS (
+                   SELECT 1 FROM json_each(record, '$.contact_info.alternate_emails') AS alt
+                   WHERE lower(alt.value) = lower(?1)
+               ))
+            ORDER BY name
+            LIMIT 1
+            "#
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch supplier by email")?;
+
+        record.map(parse).transpose()
+    }
+
+    async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM suppliers WHERE lifecycle_status <> 'deleted' ORDER BY name"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch suppliers")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>> {
+        // LIKE is case-insensitive for ASCII in SQLite, as ILIKE is in Postgres
+        let records: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT record FROM suppliers
+            WHERE name LIKE ? AND lifecycle_status <> 'deleted'
+            ORDER BY name
+            LIMIT ?
+            "#
+        )
+        .bind(format!("%{}%", query))
+        .bind(NAME_SEARCH_LIMIT)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search suppliers by name")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>> {
+        // No trigram matching here, so names sharing a word stand in for alike ones
+        let words = name_words(names);
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let records: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT record FROM suppliers
+            WHERE lifecycle_status <> 'deleted'
+              AND EXISTS (SELECT 1 FROM json_each(?) AS word WHERE name LIKE '%' || word.value || '%')
+            ORDER BY name
+            "#
+        )
+        .bind(serde_json::to_string(&words)?)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find suppliers with similar names")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn create(&self, mut supplier: SupplierRecord) -> Result<SupplierRecord> {
+        supplier.created_at = now();
+        supplier.updated_at = supplier.created_at;
+
+        sqlx::query(
+            r#"
+            INSERT INTO suppliers
+                (id, name, primary_email, lifecycle_status, record, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(supplier.id.to_string())
+        .bind(&supplier.name)
+        .bind(&supplier.contact_info.primary_email)
+        .bind(supplier.lifecycle_status.as_str())
+        .bind(supplier.to_versioned()?.to_string())
+        .bind(timestamp(supplier.created_at))
+        .bind(timestamp(supplier.updated_at))
+        .execute(&self.pool)
+        .await
+        .context("Failed to create supplier")?;
+
+        Ok(supplier)
+    }
+
+    async fn update(&self, mut supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        let created_at: Option<String> = sqlx::query_scalar("SELECT created_at FROM suppliers WHERE id = ?")
+            .bind(supplier.id.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to fetch supplier for update")?;
+        let Some(created_at) = created_at else {
+            bail!("Supplier {} not found", supplier.id);
+        };
+
+        supplier.created_at = created_at.parse().context("Malformed supplier timestamp")?;
+        supplier.updated_at = now();
+        Self::save(&mut tx, &supplier).await?;
+        tx.commit().await.context("Failed to commit supplier update")?;
+        Ok(supplier)
+    }
+
+    /// Soft-delete a supplier, recording the change in the audit trail
+    async fn delete(&self, id: SupplierId) -> Result<bool> {
+        let _chain = CHAIN_LOCK.lock().await;
+        let mut tx = self.pool.begin().await.context("Failed to start supplier lifecycle change")?;
+
+        let record: Option<String> = sqlx::query_scalar(
+            "SELECT record FROM suppliers WHERE id = ? AND lifecycle_status <> 'deleted'"
+        )
+        .bind(id.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch supplier for lifecycle change")?;
+        let Some(record) = record else {
+            return Ok(false);
+        };
+
+        let mut supplier = parse(record)?;
+        let change = supplier.transition_to(LifecycleStatus::Deleted, now()).map_err(anyhow::Error::msg)?;
+        supplier.updated_at = now();
+        Self::save(&mut tx, &supplier).await?;
+
+        let entry = AuditEntry::new(AuditAction::LifecycleChanged, "supplier".to_string(), id.into(), None, None)
+            .with_details(vec![change], HashMap::new());
+        audit::insert(&mut tx, entry).await?;
+
+        tx.commit().await.context("Failed to commit supplier lifecycle change")?;
+        Ok(true)
+    }
+}
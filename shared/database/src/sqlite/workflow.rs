@@ -0,0 +1,119 @@
+//! Workflows on SQLite
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use elementa_models::{WorkflowId, WorkflowInstance, WorkflowStatus};
+
+use super::{now, timestamp, variant_name, SqlitePool};
+use crate::WorkflowStore;
+
+pub struct SqliteWorkflowStore {
+    pool: SqlitePool,
+}
+
+impl SqliteWorkflowStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse(record: String) -> Result<WorkflowInstance> {
+    serde_json::from_str(&record).context("Malformed workflow")
+}
+
+#[async_trait]
+impl WorkflowStore for SqliteWorkflowStore {
+    async fn find_by_id(&self, id: WorkflowId) -> Result<Option<WorkflowInstance>> {
+        let record: Option<String> = sqlx::query_scalar("SELECT record FROM workflows WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch workflow by ID")?;
+
+        record.map(parse).transpose()
+    }
+
+    async fn find_by_status(&self, status: WorkflowStatus) -> Result<Vec<WorkflowInstance>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM workflows WHERE status = ? ORDER BY created_at DESC"
+        )
+        .bind(variant_name(&status)?)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch workflows by status")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn find_active(&self) -> Result<Vec<WorkflowInstance>> {
+        let records: Vec<String> = sqlx::query_scalar(
+            "SELECT record FROM workflows WHERE status IN ('InProgress', 'Created') ORDER BY deadline ASC"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch active workflows")?;
+
+        records.into_iter().map(parse).collect()
+    }
+
+    async fn create(&self, mut workflow: WorkflowInstance) -> Result<WorkflowInstance> {
+        workflow.created_at = now();
+        workflow.updated_at = workflow.created_at;
+
+        sqlx::query(
+            r#"
+            INSERT INTO workflows (id, status, deadline, record, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(workflow.id.to_string())
+        .bind(variant_name(&workflow.status)?)
+        .bind(timestamp(workflow.deadline))
+        .bind(serde_json::to_string(&workflow)?)
+        .bind(timestamp(workflow.created_at))
+        .bind(timestamp(workflow.updated_at))
+        .execute(&self.pool)
+        .await
+        .context("Failed to create workflow")?;
+
+        Ok(workflow)
+    }
+
+    async fn update_status(&self, id: WorkflowId, status: WorkflowStatus) -> Result<bool> {
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        let record: Option<String> = sqlx::query_scalar("SELECT record FROM workflows WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to fetch workflow for status update")?;
+        let Some(record) = record else {
+            return Ok(false);
+        };
+
+        let mut workflow = parse(record)?;
+        workflow.status = status;
+        workflow.updated_at = now();
+        sqlx::query("UPDATE workflows SET status = ?2, record = ?3, updated_at = ?4 WHERE id = ?1")
+            .bind(id.to_string())
+            .bind(variant_name(&workflow.status)?)
+            .bind(serde_json::to_string(&workflow)?)
+            .bind(timestamp(workflow.updated_at))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update workflow status")?;
+
+        tx.commit().await.context("Failed to commit workflow status update")?;
+        Ok(true)
+    }
+
+    async fn delete(&self, id: WorkflowId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM workflows WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete workflow")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,447 @@
+//! Store traits for services.
+//!
+//! Services depend on `SupplierStore`, `ComplianceStore`, `WorkflowStore`
+//! and `AuditStore` rather than on the Postgres repositories, so they can
+//! run against the in-memory stores here in unit tests and local
+//! development, or against SQLite on a single node. The other stores follow
+//! the repositories' semantics: deleted suppliers are hidden from lookups,
+//! emails match case-insensitively, and results come back in the same order.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::RwLock;
+
+use uuid::Uuid;
+
+use elementa_models::{
+    AuditEntry, ComplianceRecord, ComplianceRecordId, Lifecycle, LifecycleStatus, SupplierId, SupplierRecord,
+    WorkflowId, WorkflowInstance, WorkflowStatus,
+};
+
+use crate::repositories::compliance::expiring_certifications;
+use crate::{
+    Audited, AuditRepository, ChainVerification, ComplianceRepository, ExpiringCertification, PostgresPools, SupplierRepository,
+    WorkflowRepository,
+};
+
+/// Most suppliers a name search returns
+const NAME_SEARCH_LIMIT: usize = 100;
+
+/// Lowercase words of `names`, for stores without trigram matching to find
+/// similar names by: names sharing a word with one of them
+pub(crate) fn name_words(names: &[String]) -> Vec<String> {
+    let mut words: Vec<String> = names.iter()
+        .flat_map(|name| name.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+/// Supplier records, as services use them
+#[async_trait]
+pub trait SupplierStore: Send + Sync {
+    /// Find a supplier by ID; `None` if it does not exist or is deleted
+    async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>>;
+
+    /// Find a supplier by primary or alternate contact email
+    async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>>;
+
+    /// All suppliers that are not deleted, by name
+    async fn find_all(&self) -> Result<Vec<SupplierRecord>>;
+
+    /// Suppliers whose name contains `query`, by name
+    async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>>;
+
+    /// Suppliers whose name resembles one of `names`, by name; candidates
+    /// for matching imported names, so it may return some that are not alike
+    async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>>;
+
+    async fn create(&self, supplier: SupplierRecord) -> Result<SupplierRecord>;
+
+    async fn update(&self, supplier: SupplierRecord) -> Result<SupplierRecord>;
+
+    /// Soft-delete a supplier; `false` if it does not exist or is already deleted
+    async fn delete(&self, id: SupplierId) -> Result<bool>;
+}
+
+/// Compliance records, as services use them
+#[async_trait]
+pub trait ComplianceStore: Send + Sync {
+    async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>>;
+
+    /// A supplier's records, newest submission first
+    async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>>;
+
+    /// Records declaring a PFAS substance, newest submission first
+    async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>>;
+
+    /// Certifications expiring within `warning` of `now`, soonest first
+    async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>>;
+
+    async fn create(&self, record: ComplianceRecord) -> Result<ComplianceRecord>;
+
+    async fn update(&self, record: ComplianceRecord) -> Result<ComplianceRecord>;
+
+    async fn delete(&self, id: ComplianceRecordId) -> Result<bool>;
+}
+
+/// Workflow instances, as services use them
+#[async_trait]
+pub trait WorkflowStore: Send + Sync {
+    async fn find_by_id(&self, id: WorkflowId) -> Result<Option<WorkflowInstance>>;
+
+    /// Workflows in `status`, newest first
+    async fn find_by_status(&self, status: WorkflowStatus) -> Result<Vec<WorkflowInstance>>;
+
+    /// Created and in-progress workflows, soonest deadline first
+    async fn find_active(&self) -> Result<Vec<WorkflowInstance>>;
+
+    async fn create(&self, workflow: WorkflowInstance) -> Result<WorkflowInstance>;
+
+    /// Set a workflow's status; `false` if it does not exist
+    async fn update_status(&self, id: WorkflowId, status: WorkflowStatus) -> Result<bool>;
+
+    async fn delete(&self, id: WorkflowId) -> Result<bool>;
+}
+
+/// The audit trail, as services use it
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    /// Append an entry to the hash chain, after the latest entry
+    async fn append(&self, entry: AuditEntry) -> Result<AuditEntry>;
+
+    /// An entity's entries, oldest first
+    async fn find_by_entity(&self, entity_type: &str, entity_id: Uuid) -> Result<Vec<AuditEntry>>;
+
+    /// Check the hash chain of the entries between `from` and `to`
+    async fn verify_chain(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<ChainVerification>;
+}
+
+/// The core stores on one backend, as `initialize_stores` connects them
+#[derive(Clone)]
+pub struct Stores {
+    pub suppliers: Arc<dyn SupplierStore>,
+    pub compliance: Arc<dyn ComplianceStore>,
+    pub workflows: Arc<dyn WorkflowStore>,
+    pub audit: Arc<dyn AuditStore>,
+}
+
+impl Stores {
+    /// Stores on the PostgreSQL repositories
+    pub fn postgres(pools: PostgresPools) -> Self {
+        let pool = pools.primary().clone();
+        Self {
+            suppliers: Arc::new(SupplierRepository::with_pools(pools.clone())),
+            compliance: Arc::new(ComplianceRepository::with_pools(pools)),
+            workflows: Arc::new(WorkflowRepository::new(pool.clone())),
+            audit: Arc::new(AuditRepository::new(pool)),
+        }
+    }
+
+    /// The same stores with every write recorded in the audit trail, see
+    /// `Audited`
+    pub fn audited(self, user_id: Option<Uuid>, agent_id: Option<String>) -> Self {
+        Self {
+            suppliers: Arc::new(Audited::new(self.suppliers, self.audit.clone()).with_actor(user_id, agent_id.clone())),
+            compliance: Arc::new(Audited::new(self.compliance, self.audit.clone()).with_actor(user_id, agent_id.clone())),
+            workflows: Arc::new(Audited::new(self.workflows, self.audit.clone()).with_actor(user_id, agent_id)),
+            audit: self.audit,
+        }
+    }
+
+    /// Stores on a SQLite database
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: crate::sqlite::SqlitePool) -> Self {
+        use crate::sqlite::{SqliteAuditStore, SqliteComplianceStore, SqliteSupplierStore, SqliteWorkflowStore};
+
+        Self {
+            suppliers: Arc::new(SqliteSupplierStore::new(pool.clone())),
+            compliance: Arc::new(SqliteComplianceStore::new(pool.clone())),
+            workflows: Arc::new(SqliteWorkflowStore::new(pool.clone())),
+            audit: Arc::new(SqliteAuditStore::new(pool)),
+        }
+    }
+}
+
+#[async_trait]
+impl SupplierStore for SupplierRepository {
+    async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
+        SupplierRepository::find_by_id(self, id).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>> {
+        SupplierRepository::find_by_email(self, email).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
+        SupplierRepository::find_all(self).await
+    }
+
+    async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>> {
+        SupplierRepository::search_by_name(self, query).await
+    }
+
+    async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>> {
+        SupplierRepository::find_similar_names(self, names).await
+    }
+
+    async fn create(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        SupplierRepository::create(self, supplier).await
+    }
+
+    async fn update(&self, supplier: SupplierRecord) -> Result<SupplierRecord> {
+        SupplierRepository::update(self, supplier).await
+    }
+
+    async fn delete(&self, id: SupplierId) -> Result<bool> {
+        SupplierRepository::delete(self, id).await
+    }
+}
+
+#[async_trait]
+impl ComplianceStore for ComplianceRepository {
+    async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>> {
+        ComplianceRepository::find_by_id(self, id).await
+    }
+
+    async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>> {
+        ComplianceRepository::find_by_supplier(self, supplier_id).await
+    }
+
+    async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>> {
+        ComplianceRepository::find_with_pfas(self).await
+    }
+
+    async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>> {
+        ComplianceRepository::find_expiring_certifications(self, now, warning).await
+    }
+
+    async fn create(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        ComplianceRepository::create(self, record).await
+    }
+
+    async fn update(&self, record: ComplianceRecord) -> Result<ComplianceRecord> {
+        ComplianceRepository::update(self, record).await
+    }
+
+    async fn delete(&self, id: ComplianceRecordId) -> Result<bool> {
+        ComplianceRepository::delete(self, id).await
+    }
+}
+
+#[async_trait]
+impl WorkflowStore for WorkflowRepository {
+    async fn find_by_id(&self, id: WorkflowId) -> Result<Option<WorkflowInstance>> {
+        WorkflowRepository::find_by_id(self, id).await
+    }
+
+    async fn find_by_status(&self, status: WorkflowStatus) -> Result<Vec<WorkflowInstance>> {
+        WorkflowRepository::find_by_status(self, status).await
+    }
+
+    async fn find_active(&self) -> Result<Vec<WorkflowInstance>> {
+        WorkflowRepository::find_active(self).await
+    }
+
+    async fn create(&self, workflow: WorkflowInstance) -> Result<WorkflowInstance> {
+        WorkflowRepository::create(self, workflow).await
+    }
+
+    async fn update_status(&self, id: WorkflowId, status: WorkflowStatus) -> Result<bool> {
+        WorkflowRepository::update_status(self, id, status).await
+    }
+
+    async fn delete(&self, id: WorkflowId) -> Result<bool> {
+        WorkflowRepository::delete(self, id).await
+    }
+}
+
+#[async_trait]
+impl AuditStore for AuditRepository {
+    async fn append(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        AuditRepository::append(self, entry).await
+    }
+
+    async fn find_by_entity(&self, entity_type: &str, entity_id: Uuid) -> Result<Vec<AuditEntry>> {
+        AuditRepository::find_by_entity(self, entity_type, entity_id).await
+    }
+
+    async fn verify_chain(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<ChainVerification> {
+        AuditRepository::verify_chain(self, from, to).await
+    }
+}
+
+/// Process-local supplier store
+#[derive(Default)]
+pub struct InMemorySupplierStore {
+    suppliers: RwLock<HashMap<SupplierId, SupplierRecord>>,
+}
+
+impl InMemorySupplierStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store holding `suppliers` as given
+    pub fn with_suppliers(suppliers: impl IntoIterator<Item = SupplierRecord>) -> Self {
+        Self {
+            suppliers: RwLock::new(suppliers.into_iter().map(|s| (s.id, s)).collect()),
+        }
+    }
+
+    async fn select(&self, predicate: impl Fn(&SupplierRecord) -> bool) -> Vec<SupplierRecord> {
+        let mut selected: Vec<SupplierRecord> = self.suppliers.read().await
+            .values()
+            .filter(|s| !s.is_deleted() && predicate(s))
+            .cloned()
+            .collect();
+        selected.sort_by(|a, b| a.name.cmp(&b.name));
+        selected
+    }
+}
+
+#[async_trait]
+impl SupplierStore for InMemorySupplierStore {
+    async fn find_by_id(&self, id: SupplierId) -> Result<Option<SupplierRecord>> {
+        Ok(self.suppliers.read().await.get(&id).filter(|s| !s.is_deleted()).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<SupplierRecord>> {
+        let matches = |address: &String| address.eq_ignore_ascii_case(email);
+        Ok(self.select(|s| matches(&s.contact_info.primary_email) || s.contact_info.alternate_emails.iter().any(matches))
+            .await
+            .into_iter()
+            .next())
+    }
+
+    async fn find_all(&self) -> Result<Vec<SupplierRecord>> {
+        Ok(self.select(|_| true).await)
+    }
+
+    async fn search_by_name(&self, query: &str) -> Result<Vec<SupplierRecord>> {
+        let query = query.to_lowercase();
+        let mut found = self.select(|s| s.name.to_lowercase().contains(&query)).await;
+        found.truncate(NAME_SEARCH_LIMIT);
+        Ok(found)
+    }
+
+    async fn find_similar_names(&self, names: &[String]) -> Result<Vec<SupplierRecord>> {
+        let words = name_words(names);
+        Ok(self.select(|s| {
+            let name = s.name.to_lowercase();
+            words.iter().any(|word| name.contains(word.as_str()))
+        }).await)
+    }
+
+    async fn create(&self, mut supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let mut suppliers = self.suppliers.write().await;
+        if suppliers.contains_key(&supplier.id) {
+            bail!("Supplier {} already exists", supplier.id);
+        }
+        supplier.created_at = Utc::now();
+        supplier.updated_at = supplier.created_at;
+        suppliers.insert(supplier.id, supplier.clone());
+        Ok(supplier)
+    }
+
+    async fn update(&self, mut supplier: SupplierRecord) -> Result<SupplierRecord> {
+        let mut suppliers = self.suppliers.write().await;
+        let Some(stored) = suppliers.get_mut(&supplier.id) else {
+            bail!("Supplier {} not found", supplier.id);
+        };
+        supplier.created_at = stored.created_at;
+        supplier.updated_at = Utc::now();
+        *stored = supplier.clone();
+        Ok(supplier)
+    }
+
+    async fn delete(&self, id: SupplierId) -> Result<bool> {
+        let mut suppliers = self.suppliers.write().await;
+        let Some(supplier) = suppliers.get_mut(&id).filter(|s| !s.is_deleted()) else {
+            return Ok(false);
+        };
+        supplier.transition_to(LifecycleStatus::Deleted, Utc::now()).map_err(anyhow::Error::msg)?;
+        Ok(true)
+    }
+}
+
+/// Process-local compliance record store
+#[derive(Default)]
+pub struct InMemoryComplianceStore {
+    records: RwLock<HashMap<ComplianceRecordId, ComplianceRecord>>,
+}
+
+impl InMemoryComplianceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store holding `records` as given
+    pub fn with_records(records: impl IntoIterator<Item = ComplianceRecord>) -> Self {
+        Self {
+            records: RwLock::new(records.into_iter().map(|r| (r.id, r)).collect()),
+        }
+    }
+
+    async fn select(&self, predicate: impl Fn(&ComplianceRecord) -> bool) -> Vec<ComplianceRecord> {
+        let mut selected: Vec<ComplianceRecord> = self.records.read().await
+            .values()
+            .filter(|r| predicate(r))
+            .cloned()
+            .collect();
+        selected.sort_by_key(|r| std::cmp::Reverse(r.submission_date));
+        selected
+    }
+}
+
+#[async_trait]
+impl ComplianceStore for InMemoryComplianceStore {
+    async fn find_by_id(&self, id: ComplianceRecordId) -> Result<Option<ComplianceRecord>> {
+        Ok(self.records.read().await.get(&id).cloned())
+    }
+
+    async fn find_by_supplier(&self, supplier_id: SupplierId) -> Result<Vec<ComplianceRecord>> {
+        Ok(self.select(|r| r.supplier_id == supplier_id).await)
+    }
+
+    async fn find_with_pfas(&self) -> Result<Vec<ComplianceRecord>> {
+        Ok(self.select(|r| r.cas_records.iter().any(|c| c.is_pfas)).await)
+    }
+
+    async fn find_expiring_certifications(&self, now: DateTime<Utc>, warning: Duration) -> Result<Vec<ExpiringCertification>> {
+        Ok(expiring_certifications(self.records.read().await.values(), now, warning))
+    }
+
+    async fn create(&self, mut record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let mut records = self.records.write().await;
+        if records.contains_key(&record.id) {
+            bail!("Compliance record {} already exists", record.id);
+        }
+        record.created_at = Utc::now();
+        record.updated_at = record.created_at;
+        records.insert(record.id, record.clone());
+        Ok(record)
+    }
+
+    async fn update(&self, mut record: ComplianceRecord) -> Result<ComplianceRecord> {
+        let mut records = self.records.write().await;
+        let Some(stored) = records.get_mut(&record.id) else {
+            bail!("Compliance record {} not found", record.id);
+        };
+        record.created_at = stored.created_at;
+        record.updated_at = Utc::now();
+        *stored = record.clone();
+        Ok(record)
+    }
+
+    async fn delete(&self, id: ComplianceRecordId) -> Result<bool> {
+        Ok(self.records.write().await.remove(&id).is_some())
+    }
+}
@@ -0,0 +1,190 @@
+//! Tenant scoping for Postgres connections.
+//!
+//! Client-owned tables carry a `tenant_id` and a row-level security policy
+//! that limits a connection to the tenant named by its `elementa.tenant_id`
+//! setting (migration 0005). The pools set that setting every time a
+//! connection is acquired, from the tenant of the task acquiring it, so
+//! repository queries run inside `scope` see and write only that tenant's
+//! rows without passing the tenant around, and a connection returned to the
+//! pool never carries one request's tenant into the next.
+//!
+//! Queries outside a scope see no tenant's rows (migration 0010). Access to
+//! every tenant is an explicit bypass, through the `elementa.all_tenants`
+//! setting: work wrapped in `all_tenants`, such as migrations, or any
+//! unscoped query on a pool created with `TenantAccess::AllTenants`, as the
+//! platform services and jobs use. A tenant scope always wins over the bypass.
+//!
+//! The tenant is task-local: work handed to `tokio::spawn` runs unscoped
+//! unless it is wrapped in `scope` again.
+
+use std::fmt;
+use std::future::Future;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgConnection, Postgres, Transaction};
+
+use crate::PostgresPool;
+
+/// Postgres setting the row-level security policies read
+const TENANT_SETTING: &str = "elementa.tenant_id";
+/// Postgres setting that, when `on`, lifts the policies for unscoped queries
+const ALL_TENANTS_SETTING: &str = "elementa.all_tenants";
+/// Longest tenant ID accepted
+const MAX_TENANT_ID_LEN: usize = 64;
+
+tokio::task_local! {
+    static CURRENT_TENANT: TenantId;
+    static ALL_TENANTS: ();
+}
+
+/// Which rows a pool's connections may reach outside a tenant scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TenantAccess {
+    /// None; client-owned tables are only reachable inside `scope`
+    #[default]
+    Scoped,
+    /// Every tenant's, for platform services and jobs that work across tenants
+    AllTenants,
+}
+
+/// A client's tenant identifier, e.g. `acme-industries`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// A tenant ID of ASCII letters, digits, `-` and `_`
+    pub fn new(id: impl Into<String>) -> Result<Self> {
+        let id = id.into();
+        if id.is_empty() || id.len() > MAX_TENANT_ID_LEN {
+            bail!("Tenant ID must be 1 to {} characters", MAX_TENANT_ID_LEN);
+        }
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            bail!("Tenant ID '{}' may only contain letters, digits, '-' and '_'", id);
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = anyhow::Error;
+
+    fn try_from(id: String) -> Result<Self> {
+        Self::new(id)
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(tenant: TenantId) -> Self {
+        tenant.0
+    }
+}
+
+/// Run `work` with every query it makes scoped to `tenant`
+pub async fn scope<F: Future>(tenant: TenantId, work: F) -> F::Output {
+    CURRENT_TENANT.scope(tenant, work).await
+}
+
+/// Run `work` with its unscoped queries reaching every tenant's rows,
+/// whichever pool they use; for migrations and one-off platform tasks
+pub async fn all_tenants<F: Future>(work: F) -> F::Output {
+    ALL_TENANTS.scope((), work).await
+}
+
+/// The tenant the current task is scoped to, if any
+pub fn current_tenant() -> Option<TenantId> {
+    CURRENT_TENANT.try_with(TenantId::clone).ok()
+}
+
+/// Begin a transaction scoped to `tenant`, whatever the task's own scope,
+/// for jobs that work through several tenants' data in turn
+pub async fn begin_for_tenant(pool: &PostgresPool, tenant: &TenantId) -> Result<Transaction<'static, Postgres>> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    scope_transaction(&mut tx, tenant).await?;
+    Ok(tx)
+}
+
+/// Scope the rest of an open transaction to `tenant`
+pub(crate) async fn scope_transaction(conn: &mut PgConnection, tenant: &TenantId) -> Result<()> {
+    // Local to the transaction, so the pool's settings apply again after it
+    sqlx::query("SELECT set_config($1, $2, true), set_config($3, '', true)")
+        .bind(TENANT_SETTING)
+        .bind(tenant.as_str())
+        .bind(ALL_TENANTS_SETTING)
+        .execute(conn)
+        .await
+        .with_context(|| format!("Failed to scope transaction to tenant {}", tenant))?;
+    Ok(())
+}
+
+/// Set the connection's tenant to the current task's; outside a scope, reach
+/// every tenant only if the task or the pool asked to
+async fn apply_current_tenant(conn: &mut PgConnection, access: TenantAccess) -> Result<(), sqlx::Error> {
+    let tenant = current_tenant();
+    let all_tenants = tenant.is_none() && (access == TenantAccess::AllTenants || ALL_TENANTS.try_with(|_| ()).is_ok());
+    sqlx::query("SELECT set_config($1, $2, false), set_config($3, $4, false)")
+        .bind(TENANT_SETTING)
+        .bind(tenant.map(String::from).unwrap_or_default())
+        .bind(ALL_TENANTS_SETTING)
+        .bind(if all_tenants { "on" } else { "" })
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Pool options that scope each connection to the acquiring task's tenant:
+/// new connections when they connect, idle ones when they are handed out
+pub(crate) fn tenant_scoped(options: PgPoolOptions, access: TenantAccess) -> PgPoolOptions {
+    options
+        .after_connect(move |conn, _| Box::pin(apply_current_tenant(conn, access)))
+        .before_acquire(move |conn, _| Box::pin(async move {
+            apply_current_tenant(conn, access).await?;
+            Ok(true)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_id_validation() {
+        assert_eq!(TenantId::new("acme-industries").unwrap().as_str(), "acme-industries");
+        assert!(TenantId::new("Tenant_42").is_ok());
+        assert!(TenantId::new("a".repeat(MAX_TENANT_ID_LEN)).is_ok());
+
+        assert!(TenantId::new("").is_err());
+        assert!(TenantId::new("a".repeat(MAX_TENANT_ID_LEN + 1)).is_err());
+        for invalid in ["acme industries", "acme.io", "acme/other", "'; DROP TABLE suppliers; --", "acmé"] {
+            assert!(TenantId::new(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_tenant_id_deserialization_is_validated() {
+        let tenant: TenantId = serde_json::from_str("\"acme\"").unwrap();
+        assert_eq!(tenant.to_string(), "acme");
+        assert!(serde_json::from_str::<TenantId>("\"not a tenant\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current_tenant() {
+        assert_eq!(current_tenant(), None);
+        let tenant = TenantId::new("acme").unwrap();
+        let scoped = scope(tenant.clone(), async { current_tenant() }).await;
+        assert_eq!(scoped, Some(tenant));
+        assert_eq!(current_tenant(), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! Query timing.
+//!
+//! Repository queries are awaited through `QueryTiming::timed`, which
+//! records each query's duration in the `elementa_db_query_seconds`
+//! histogram, labeled by repository and method, and logs a warning for any
+//! query slower than the configured threshold. A method whose count climbs
+//! with the rows it returns is an N+1; one whose duration climbs with table
+//! size is missing an index.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use prometheus::{register_histogram_vec, HistogramVec};
+
+/// Queries slower than this are logged unless configured otherwise
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+static SLOW_QUERY_THRESHOLD_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_THRESHOLD.as_millis() as u64);
+
+/// Set the duration above which a query is logged as slow, for the process
+pub fn set_slow_query_threshold(threshold: Duration) {
+    SLOW_QUERY_THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn slow_query_threshold() -> Duration {
+    Duration::from_millis(SLOW_QUERY_THRESHOLD_MS.load(Ordering::Relaxed))
+}
+
+/// Query duration histogram in the default Prometheus registry, which the gateway's `/metrics` serves
+fn query_seconds() -> &'static HistogramVec {
+    static METRIC: OnceLock<HistogramVec> = OnceLock::new();
+    METRIC.get_or_init(|| {
+        register_histogram_vec!(
+            "elementa_db_query_seconds",
+            "Duration of repository queries",
+            &["repository", "method"],
+            vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+        )
+        .expect("query metric registers once")
+    })
+}
+
+/// Record a query's duration, logging it if it was slow
+pub fn observe_query(repository: &'static str, method: &'static str, elapsed: Duration) {
+    query_seconds().with_label_values(&[repository, method]).observe(elapsed.as_secs_f64());
+
+    let threshold = slow_query_threshold();
+    if elapsed >= threshold {
+        tracing::warn!(
+            repository,
+            method,
+            duration_ms = elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            "Slow query"
+        );
+    }
+}
+
+/// Times a query future as it is awaited
+pub trait QueryTiming: Future + Send + Sized {
+    fn timed(self, repository: &'static str, method: &'static str) -> impl Future<Output = Self::Output> + Send {
+        async move {
+            let started = Instant::now();
+            let output = self.await;
+            observe_query(repository, method, started.elapsed());
+            output
+        }
+    }
+}
+
+impl<F: Future + Send> QueryTiming for F {}
@@ -0,0 +1,78 @@
+#![cfg(feature = "sqlite")]
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use elementa_database::sqlite::{create_sqlite_pool, run_sqlite_migrations};
+use elementa_database::Stores;
+use elementa_models::{
+    AuditAction, ChangeType, ComplianceRecord, ComponentId, SupplierRecord, WorkflowInstance, WorkflowStatus,
+};
+
+async fn audited_stores(user_id: Uuid) -> Stores {
+    let path = std::env::temp_dir().join(format!("elementa-{}.db", Uuid::new_v4()));
+    let pool = create_sqlite_pool(&format!("sqlite://{}", path.display()), 4).await.unwrap();
+    run_sqlite_migrations(&pool).await.unwrap();
+    Stores::sqlite(pool).audited(Some(user_id), None)
+}
+
+#[tokio::test]
+async fn compliance_writes_are_audited_with_a_diff() {
+    let user = Uuid::new_v4();
+    let stores = audited_stores(user).await;
+    let start = Utc::now() - Duration::seconds(1);
+    let acme = stores.suppliers
+        .create(SupplierRecord::new("Acme Polymers".to_string(), "qa@acme.example".to_string(), "Quality".to_string()))
+        .await
+        .unwrap();
+    let record = ComplianceRecord::builder()
+        .supplier_id(acme.id)
+        .component_id(ComponentId::from(Uuid::new_v4()))
+        .submission_date(Utc::now() - Duration::days(3))
+        .build()
+        .unwrap();
+    let mut record = stores.compliance.create(record).await.unwrap();
+
+    let submitted = record.submission_date;
+    record.submission_date = Utc::now();
+    let record = stores.compliance.update(record).await.unwrap();
+    assert!(stores.compliance.delete(record.id).await.unwrap());
+    assert!(!stores.compliance.delete(record.id).await.unwrap());
+
+    let history = stores.audit.find_by_entity("compliance_record", record.id.into()).await.unwrap();
+    let actions: Vec<_> = history.iter().map(|e| e.action.clone()).collect();
+    assert_eq!(
+        actions,
+        [AuditAction::ComplianceRecordCreated, AuditAction::ComplianceRecordUpdated, AuditAction::RecordDeleted]
+    );
+    assert!(history.iter().all(|e| e.user_id == Some(user)));
+
+    let update = &history[1].details.changes;
+    assert_eq!(update.len(), 1);
+    assert_eq!(update[0].field_name, "submission_date");
+    assert_eq!(update[0].change_type, ChangeType::Updated);
+    assert_eq!(update[0].old_value.as_deref(), Some(serde_json::to_value(submitted).unwrap().as_str().unwrap()));
+    assert!(history[2].details.changes.iter().all(|c| c.change_type == ChangeType::Deleted && c.new_value.is_none()));
+
+    let supplier_history = stores.audit.find_by_entity("supplier", acme.id.into()).await.unwrap();
+    assert_eq!(supplier_history[0].action, AuditAction::RecordCreated);
+    assert!(stores.audit.verify_chain(start, Utc::now()).await.unwrap().is_valid);
+}
+
+#[tokio::test]
+async fn workflow_status_changes_are_audited() {
+    let stores = audited_stores(Uuid::new_v4()).await;
+    let workflow = stores.workflows.create(WorkflowInstance::default()).await.unwrap();
+    assert!(stores.workflows.update_status(workflow.id, WorkflowStatus::Completed).await.unwrap());
+    assert!(stores.workflows.delete(workflow.id).await.unwrap());
+    assert!(!stores.workflows.update_status(workflow.id, WorkflowStatus::Paused).await.unwrap());
+
+    let history = stores.audit.find_by_entity("workflow", workflow.id.into()).await.unwrap();
+    let actions: Vec<_> = history.iter().map(|e| e.action.clone()).collect();
+    assert_eq!(actions, [AuditAction::WorkflowStarted, AuditAction::WorkflowCompleted, AuditAction::RecordDeleted]);
+
+    let status = &history[1].details.changes;
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].old_value.as_deref(), Some("Created"));
+    assert_eq!(status[0].new_value.as_deref(), Some("Completed"));
+}
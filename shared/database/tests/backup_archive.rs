@@ -0,0 +1,33 @@
+#![cfg(feature = "backup")]
+
+use elementa_database::backup::{open, seal, BackupKey};
+
+#[test]
+fn sealed_archives_open_only_with_their_key() {
+    let key = BackupKey::generate();
+    let contents = b"tenant data".to_vec();
+
+    let sealed = seal(&key, &contents).unwrap();
+    assert!(!sealed.windows(contents.len()).any(|w| w == contents.as_slice()));
+    assert_eq!(open(&key, &sealed).unwrap(), contents);
+    // A fresh nonce each time
+    assert_ne!(seal(&key, &contents).unwrap(), sealed);
+
+    assert!(open(&BackupKey::generate(), &sealed).is_err());
+    let mut tampered = sealed.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(open(&key, &tampered).is_err());
+    assert!(open(&key, b"PK\x03\x04 not sealed").is_err());
+    assert!(open(&key, &sealed[..10]).is_err());
+}
+
+#[test]
+fn keys_are_read_as_hex() {
+    let key = BackupKey::generate();
+    let parsed = BackupKey::from_hex(&format!("{}\n", key.to_hex())).unwrap();
+    assert_eq!(parsed.to_hex(), key.to_hex());
+    assert_eq!(format!("{:?}", parsed), "BackupKey(..)");
+
+    assert!(BackupKey::from_hex("not hex").is_err());
+    assert!(BackupKey::from_hex(&"ab".repeat(16)).is_err());
+}
@@ -0,0 +1,57 @@
+//! Chemical substances against PostgreSQL. Runs only when `TEST_DATABASE_URL`
+//! names a disposable database. Each test writes substances under its own
+//! made-up CAS numbers, so the tests can run side by side.
+
+use elementa_database::migrations::run_postgres_migrations;
+use elementa_database::{create_postgres_pool, ChemicalRepository, TenantAccess};
+use elementa_models::ChemicalSubstance;
+
+async fn repository() -> Option<ChemicalRepository> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return None;
+    };
+    let pool = create_postgres_pool(&url, 2, TenantAccess::Scoped).await.unwrap();
+    run_postgres_migrations(&pool).await.unwrap();
+    Some(ChemicalRepository::new(pool))
+}
+
+fn substance(cas_number: &str, name: &str, is_pfas: bool) -> ChemicalSubstance {
+    ChemicalSubstance::builder()
+        .cas_number(cas_number)
+        .chemical_name(name)
+        .is_pfas(is_pfas)
+        .molecular_weight(414.07)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn substances_are_stored_and_found_by_cas() {
+    let Some(chemicals) = repository().await else { return };
+
+    chemicals.upsert(substance("99999-01-6", "Test fluoropolymer", true)).await.unwrap();
+    let updated = chemicals.upsert(substance("99999-01-6", "Renamed fluoropolymer", true)).await.unwrap();
+    assert_eq!(updated.chemical_name, "Renamed fluoropolymer");
+
+    let found = chemicals.find_by_cas("99999-01-6").await.unwrap().unwrap();
+    assert_eq!(found.chemical_name, "Renamed fluoropolymer");
+    assert_eq!(found.molecular_weight, Some(414.07));
+    assert!(found.is_pfas);
+    assert!(chemicals.find_by_cas("99999-00-5").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn pfas_are_listed_apart_from_other_substances() {
+    let Some(chemicals) = repository().await else { return };
+
+    chemicals.bulk_upsert(vec![
+        substance("99999-02-7", "Test PFAS", true),
+        substance("99999-03-8", "Test metal", false),
+    ]).await.unwrap();
+
+    let pfas: Vec<String> = chemicals.find_all_pfas().await.unwrap().into_iter().map(|c| c.cas_number).collect();
+    assert!(pfas.contains(&"99999-02-7".to_string()));
+    assert!(!pfas.contains(&"99999-03-8".to_string()));
+    assert!(chemicals.count_pfas().await.unwrap() >= 1);
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use elementa_database::timing::slow_query_threshold;
+use elementa_database::{set_slow_query_threshold, QueryTiming};
+
+fn query_count(repository: &str, method: &str) -> u64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == "elementa_db_query_seconds")
+        .flat_map(|family| family.get_metric())
+        .filter(|metric| {
+            let labels: Vec<_> = metric.get_label().iter().map(|l| (l.get_name(), l.get_value())).collect();
+            labels.contains(&("repository", repository)) && labels.contains(&("method", method))
+        })
+        .map(|metric| metric.get_histogram().get_sample_count())
+        .sum()
+}
+
+#[tokio::test]
+async fn timed_queries_are_counted_by_repository_and_method() {
+    set_slow_query_threshold(Duration::from_millis(5));
+    assert_eq!(slow_query_threshold(), Duration::from_millis(5));
+
+    let rows = async { Ok::<_, sqlx::Error>(vec![1, 2, 3]) }.timed("supplier", "find_all").await.unwrap();
+    assert_eq!(rows.len(), 3);
+    tokio::time::sleep(Duration::from_millis(10)).timed("supplier", "find_all").await;
+    async {}.timed("supplier", "find_by_id").await;
+
+    assert_eq!(query_count("supplier", "find_all"), 2);
+    assert_eq!(query_count("supplier", "find_by_id"), 1);
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "sqlite")]
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use elementa_database::sqlite::{create_sqlite_pool, run_sqlite_migrations};
+use elementa_database::Stores;
+use elementa_models::{
+    AuditAction, AuditEntry, ComplianceRecord, ComponentId, SupplierRecord, WorkflowInstance, WorkflowStatus,
+};
+
+async fn stores() -> Stores {
+    let path = std::env::temp_dir().join(format!("elementa-{}.db", Uuid::new_v4()));
+    let pool = create_sqlite_pool(&format!("sqlite://{}", path.display()), 4).await.unwrap();
+    run_sqlite_migrations(&pool).await.unwrap();
+    Stores::sqlite(pool)
+}
+
+fn supplier(name: &str, email: &str) -> SupplierRecord {
+    SupplierRecord::new(name.to_string(), email.to_string(), "Quality".to_string())
+}
+
+#[tokio::test]
+async fn suppliers_round_trip_and_soft_delete() {
+    let stores = stores().await;
+    let mut acme = supplier("Acme Polymers", "Compliance@Acme.example");
+    acme.contact_info.alternate_emails.push("pfas@acme.example".to_string());
+    let acme = stores.suppliers.create(acme).await.unwrap();
+    stores.suppliers.create(supplier("Beta Metals", "qa@beta.example")).await.unwrap();
+
+    let found = stores.suppliers.find_by_email("compliance@acme.example").await.unwrap().unwrap();
+    assert_eq!(found.id, acme.id);
+    let found = stores.suppliers.find_by_email("PFAS@acme.example").await.unwrap().unwrap();
+    assert_eq!(found.id, acme.id);
+    let names: Vec<_> = stores.suppliers.find_all().await.unwrap().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, ["Acme Polymers", "Beta Metals"]);
+    assert_eq!(stores.suppliers.search_by_name("metal").await.unwrap().len(), 1);
+
+    let mut renamed = found.clone();
+    renamed.name = "Acme Polymers AG".to_string();
+    let renamed = stores.suppliers.update(renamed).await.unwrap();
+    assert_eq!(renamed.created_at, acme.created_at);
+
+    assert!(stores.suppliers.delete(acme.id).await.unwrap());
+    assert!(!stores.suppliers.delete(acme.id).await.unwrap());
+    assert!(stores.suppliers.find_by_id(acme.id).await.unwrap().is_none());
+
+    let history = stores.audit.find_by_entity("supplier", acme.id.into()).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].action, AuditAction::LifecycleChanged);
+}
+
+#[tokio::test]
+async fn suppliers_sharing_a_word_are_similar() {
+    let stores = stores().await;
+    stores.suppliers.create(supplier("Acme Electric GmbH", "qa@acme.example")).await.unwrap();
+    stores.suppliers.create(supplier("Beta Metals", "qa@beta.example")).await.unwrap();
+
+    let names = |found: Vec<SupplierRecord>| found.into_iter().map(|s| s.name).collect::<Vec<_>>();
+    let similar = stores.suppliers.find_similar_names(&["acme electrik".to_string()]).await.unwrap();
+    assert_eq!(names(similar), ["Acme Electric GmbH"]);
+    assert!(stores.suppliers.find_similar_names(&["globex".to_string()]).await.unwrap().is_empty());
+    assert!(stores.suppliers.find_similar_names(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn compliance_records_by_supplier_newest_first() {
+    let stores = stores().await;
+    let acme = stores.suppliers.create(supplier("Acme Polymers", "qa@acme.example")).await.unwrap();
+    let now = Utc::now();
+    for days_ago in [10, 2, 5] {
+        let record = ComplianceRecord::builder()
+            .supplier_id(acme.id)
+            .component_id(ComponentId::from(Uuid::new_v4()))
+            .submission_date(now - Duration::days(days_ago))
+            .build()
+            .unwrap();
+        stores.compliance.create(record).await.unwrap();
+    }
+
+    let records = stores.compliance.find_by_supplier(acme.id).await.unwrap();
+    let dates: Vec<_> = records.iter().map(|r| r.submission_date).collect();
+    assert_eq!(dates, [now - Duration::days(2), now - Duration::days(5), now - Duration::days(10)]);
+    assert!(stores.compliance.find_with_pfas().await.unwrap().is_empty());
+    assert!(stores.compliance.delete(records[0].id).await.unwrap());
+    assert!(stores.compliance.find_by_id(records[0].id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn workflow_status_updates() {
+    let stores = stores().await;
+    let workflow = stores.workflows.create(WorkflowInstance::default()).await.unwrap();
+    assert_eq!(stores.workflows.find_active().await.unwrap().len(), 1);
+
+    assert!(stores.workflows.update_status(workflow.id, WorkflowStatus::Completed).await.unwrap());
+    assert!(stores.workflows.find_active().await.unwrap().is_empty());
+    let completed = stores.workflows.find_by_status(WorkflowStatus::Completed).await.unwrap();
+    assert_eq!(completed[0].id, workflow.id);
+    assert!(stores.workflows.delete(workflow.id).await.unwrap());
+    assert!(!stores.workflows.update_status(workflow.id, WorkflowStatus::Paused).await.unwrap());
+}
+
+#[tokio::test]
+async fn audit_entries_form_a_verifiable_chain() {
+    let stores = stores().await;
+    let start = Utc::now() - Duration::seconds(1);
+    let entity = Uuid::new_v4();
+    let mut appended = Vec::new();
+    for action in [AuditAction::ComplianceRecordCreated, AuditAction::ComplianceRecordUpdated] {
+        let entry = AuditEntry::new(action, "compliance_record".to_string(), entity, None, None);
+        appended.push(stores.audit.append(entry).await.unwrap());
+    }
+
+    assert_eq!(appended[1].previous_hash.as_deref(), Some(appended[0].hash.as_str()));
+    let verification = stores.audit.verify_chain(start, Utc::now()).await.unwrap();
+    assert!(verification.is_valid);
+    assert_eq!(verification.entries_verified, 2);
+}
@@ -0,0 +1,45 @@
+//! Supplier name matching against PostgreSQL. Runs only when
+//! `TEST_DATABASE_URL` names a disposable database; each test names its
+//! suppliers after a fresh made-up word, so other suppliers there never match.
+
+use uuid::Uuid;
+
+use elementa_database::migrations::run_postgres_migrations;
+use elementa_database::{create_postgres_pool, SupplierRepository, TenantAccess};
+use elementa_models::SupplierRecord;
+
+async fn suppliers() -> Option<SupplierRepository> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return None;
+    };
+    let pool = create_postgres_pool(&url, 2, TenantAccess::AllTenants).await.unwrap();
+    run_postgres_migrations(&pool).await.unwrap();
+    Some(SupplierRepository::new(pool))
+}
+
+/// A word no other supplier's name contains
+fn unique_word() -> String {
+    format!("zq{}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
+#[tokio::test]
+async fn similar_names_are_candidates_for_a_match() {
+    let Some(suppliers) = suppliers().await else { return };
+    let word = unique_word();
+    for name in [format!("{} Industries Inc.", word), format!("{} Polymers", unique_word()), "Beta Metals".to_string()] {
+        suppliers
+            .create(SupplierRecord::new(name, format!("qa+{}@example.com", Uuid::new_v4()), "Quality".to_string()))
+            .await
+            .unwrap();
+    }
+
+    let names = |found: Vec<SupplierRecord>| found.into_iter().map(|s| s.name).collect::<Vec<_>>();
+    // Normalized names from a BOM: suffixes dropped, and with a typo
+    let found = suppliers.find_similar_names(&[format!("{} industry", word)]).await.unwrap();
+    assert_eq!(names(found), [format!("{} Industries Inc.", word)]);
+    let typo: String = word.chars().take(9).collect();
+    let found = suppliers.find_similar_names(&[format!("{}x industries", typo)]).await.unwrap();
+    assert!(names(found).contains(&format!("{} Industries Inc.", word)));
+    assert!(suppliers.find_similar_names(&[]).await.unwrap().is_empty());
+}
@@ -0,0 +1,86 @@
+//! Tenants of rows written against a supplier, against PostgreSQL. Runs only
+//! when `TEST_DATABASE_URL` names a disposable database whose role is not a
+//! superuser, so row-level security applies.
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use elementa_database::migrations::run_postgres_migrations;
+use elementa_database::{
+    create_postgres_pool, tenant, PostgresPool, SupplierRepository, SuppressionRepository, TenantAccess, TenantId,
+};
+use elementa_models::{EmailSuppression, SupplierId, SupplierRecord, SuppressionSource};
+
+async fn pool() -> Option<PostgresPool> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return None;
+    };
+    let pool = create_postgres_pool(&url, 2, TenantAccess::AllTenants).await.unwrap();
+    run_postgres_migrations(&pool).await.unwrap();
+    Some(pool)
+}
+
+fn suppression(supplier_id: SupplierId) -> EmailSuppression {
+    EmailSuppression {
+        id: Uuid::new_v4(),
+        supplier_id: Some(supplier_id),
+        email_address: None,
+        reason: "Recipient unsubscribed".to_string(),
+        source: SuppressionSource::UnsubscribeLink,
+        created_at: Utc::now(),
+    }
+}
+
+async fn tenant_of(pool: &PostgresPool, suppression_id: Uuid) -> Option<String> {
+    sqlx::query_scalar("SELECT tenant_id FROM email_suppressions WHERE id = $1")
+        .bind(suppression_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn rows_written_without_a_tenant_take_their_suppliers() {
+    let Some(pool) = pool().await else { return };
+    let acme = TenantId::new("acme").unwrap();
+    let supplier = tenant::scope(acme.clone(), SupplierRepository::new(pool.clone()).create(SupplierRecord::new(
+        "Acme Polymers".to_string(),
+        format!("qa+{}@acme.example", Uuid::new_v4()),
+        "Quality".to_string(),
+    )))
+    .await
+    .unwrap();
+
+    let unscoped = SuppressionRepository::new(pool.clone()).create(suppression(supplier.id)).await.unwrap();
+    assert_eq!(tenant_of(&pool, unscoped.id).await.as_deref(), Some("acme"));
+
+    // The tenant's own view includes it
+    let visible: Option<Uuid> = tenant::scope(acme, async {
+        sqlx::query_scalar("SELECT id FROM email_suppressions WHERE id = $1")
+            .bind(unscoped.id)
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+    })
+    .await;
+    assert_eq!(visible, Some(unscoped.id));
+}
+
+#[tokio::test]
+async fn rows_written_in_a_tenant_scope_keep_that_tenant() {
+    let Some(pool) = pool().await else { return };
+    let acme = TenantId::new("acme").unwrap();
+    let supplier = tenant::scope(acme.clone(), SupplierRepository::new(pool.clone()).create(SupplierRecord::new(
+        "Acme Metals".to_string(),
+        format!("qa+{}@acme.example", Uuid::new_v4()),
+        "Quality".to_string(),
+    )))
+    .await
+    .unwrap();
+
+    let scoped = tenant::scope(acme, SuppressionRepository::new(pool.clone()).create(suppression(supplier.id)))
+        .await
+        .unwrap();
+    assert_eq!(tenant_of(&pool, scoped.id).await.as_deref(), Some("acme"));
+}
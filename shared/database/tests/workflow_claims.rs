@@ -0,0 +1,223 @@
+//! Agent task claims against PostgreSQL. The queries rely on
+//! `FOR UPDATE SKIP LOCKED`, so they run only when `TEST_DATABASE_URL` names
+//! a disposable database; its agent task queue is cleared by each test.
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+use elementa_database::migrations::run_postgres_migrations;
+use elementa_database::{create_postgres_pool, PostgresPool, SupplierRepository, TenantAccess, WorkflowRepository};
+use elementa_models::{AgentTask, AgentTaskType, SupplierRecord, TaskContext, TaskPriority, TaskStatus, WorkflowInstance};
+
+/// The tests share one queue, so they take turns
+static QUEUE: Mutex<()> = Mutex::const_new(());
+
+struct Queue {
+    pool: PostgresPool,
+    workflows: WorkflowRepository,
+    workflow: WorkflowInstance,
+    supplier: SupplierRecord,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl Queue {
+    /// An empty queue, or `None` to skip the test without a database
+    async fn open() -> Option<Self> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set; skipping");
+            return None;
+        };
+        let turn = QUEUE.lock().await;
+        let pool = create_postgres_pool(&url, 4, TenantAccess::AllTenants).await.unwrap();
+        run_postgres_migrations(&pool).await.unwrap();
+        sqlx::query("DELETE FROM agent_tasks").execute(&pool).await.unwrap();
+
+        let supplier = SupplierRepository::new(pool.clone())
+            .create(SupplierRecord::new(
+                "Acme Polymers".to_string(),
+                format!("qa+{}@acme.example", Uuid::new_v4()),
+                "Quality".to_string(),
+            ))
+            .await
+            .unwrap();
+        let workflows = WorkflowRepository::new(pool.clone());
+        let workflow = workflows.create(WorkflowInstance::default()).await.unwrap();
+        Some(Self { pool, workflows, workflow, supplier, _turn: turn })
+    }
+
+    async fn schedule(&self, scheduled_at: DateTime<Utc>, retry_count: u32, max_retries: u32) -> Uuid {
+        let task = self.task(Uuid::new_v4(), retry_count, max_retries);
+        self.workflows.schedule_task(task, scheduled_at).await.unwrap().unwrap().id
+    }
+
+    fn task(&self, id: Uuid, retry_count: u32, max_retries: u32) -> AgentTask {
+        let now = Utc::now();
+        AgentTask {
+            id,
+            workflow_id: self.workflow.id,
+            task_type: AgentTaskType::FollowUp,
+            supplier_id: self.supplier.id,
+            context: TaskContext {
+                components: Vec::new(),
+                deadline: now + Duration::days(30),
+                priority: TaskPriority::Medium,
+                custom_instructions: None,
+                previous_attempts: Vec::new(),
+            },
+            status: TaskStatus::Queued,
+            retry_count,
+            max_retries,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        }
+    }
+
+    /// A task's status, retry count and whether it has completed
+    async fn state(&self, id: Uuid) -> (String, i32, bool) {
+        let (status, retry_count, completed_at): (String, i32, Option<DateTime<Utc>>) =
+            sqlx::query_as("SELECT status, retry_count, completed_at FROM agent_tasks WHERE id = $1")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await
+                .unwrap();
+        (status, retry_count, completed_at.is_some())
+    }
+}
+
+/// Now, at the microsecond precision PostgreSQL stores
+fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap()
+}
+
+const LEASE: Duration = Duration::minutes(5);
+
+#[tokio::test]
+async fn due_tasks_are_claimed_once_oldest_first() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let later = queue.schedule(now - Duration::minutes(1), 0, 3).await;
+    let earlier = queue.schedule(now - Duration::minutes(2), 0, 3).await;
+    let future = queue.schedule(now + Duration::hours(1), 0, 3).await;
+
+    let claimed = queue.workflows.claim_due_tasks("worker-a", now, 10, LEASE).await.unwrap();
+    let ids: Vec<Uuid> = claimed.iter().map(|c| c.task.id).collect();
+    assert_eq!(ids, [earlier, later]);
+    for claim in &claimed {
+        assert_eq!(claim.claimed_by, "worker-a");
+        assert_eq!(claim.lease_expires_at, now + LEASE);
+        assert!(matches!(claim.task.status, TaskStatus::InProgress));
+    }
+    assert_eq!(queue.state(future).await.0, "Queued");
+
+    // Claimed tasks are not handed to another worker
+    assert!(queue.workflows.claim_due_tasks("worker-b", now, 10, LEASE).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn concurrent_claims_take_different_tasks() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    queue.schedule(now - Duration::minutes(2), 0, 3).await;
+    queue.schedule(now - Duration::minutes(1), 0, 3).await;
+
+    let (a, b) = tokio::join!(
+        queue.workflows.claim_due_tasks("worker-a", now, 1, LEASE),
+        queue.workflows.claim_due_tasks("worker-b", now, 1, LEASE),
+    );
+    let (a, b) = (a.unwrap(), b.unwrap());
+    assert_eq!((a.len(), b.len()), (1, 1));
+    assert_ne!(a[0].task.id, b[0].task.id);
+}
+
+#[tokio::test]
+async fn heartbeats_renew_only_the_holders_lease() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let task = queue.schedule(now - Duration::minutes(1), 0, 3).await;
+    queue.workflows.claim_due_tasks("worker-a", now, 1, LEASE).await.unwrap();
+
+    assert!(!queue.workflows.heartbeat(task, "worker-b", now, LEASE).await.unwrap());
+    let renewed_at = now + Duration::minutes(4);
+    assert!(queue.workflows.heartbeat(task, "worker-a", renewed_at, LEASE).await.unwrap());
+
+    // Past the first lease, but within the renewed one
+    let released = queue.workflows.release_stale_claims(now + LEASE + Duration::minutes(1)).await.unwrap();
+    assert!(released.requeued.is_empty() && released.failed.is_empty());
+    assert_eq!(queue.state(task).await.0, "InProgress");
+}
+
+#[tokio::test]
+async fn finishing_releases_the_claim() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let task = queue.schedule(now - Duration::minutes(1), 0, 3).await;
+    queue.workflows.claim_due_tasks("worker-a", now, 1, LEASE).await.unwrap();
+
+    assert!(!queue.workflows.finish_task(task, "worker-b", TaskStatus::Completed).await.unwrap());
+    assert!(queue.workflows.finish_task(task, "worker-a", TaskStatus::Completed).await.unwrap());
+    assert_eq!(queue.state(task).await, ("Completed".to_string(), 0, true));
+
+    // The claim is gone: no more heartbeats or second outcomes
+    assert!(!queue.workflows.heartbeat(task, "worker-a", now, LEASE).await.unwrap());
+    assert!(!queue.workflows.finish_task(task, "worker-a", TaskStatus::Failed).await.unwrap());
+    assert!(queue.workflows.claim_due_tasks("worker-b", now, 1, LEASE).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn finishing_requeued_task_keeps_it_open() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let task = queue.schedule(now - Duration::minutes(1), 0, 3).await;
+    queue.workflows.claim_due_tasks("worker-a", now, 1, LEASE).await.unwrap();
+
+    assert!(queue.workflows.finish_task(task, "worker-a", TaskStatus::Queued).await.unwrap());
+    assert_eq!(queue.state(task).await, ("Queued".to_string(), 0, false));
+    assert_eq!(queue.workflows.claim_due_tasks("worker-b", now, 1, LEASE).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn stale_claims_are_requeued_until_retries_run_out() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let fresh = queue.schedule(now - Duration::minutes(2), 0, 3).await;
+    let last_try = queue.schedule(now - Duration::minutes(1), 2, 3).await;
+    queue.workflows.claim_due_tasks("worker-a", now, 2, LEASE).await.unwrap();
+
+    // Leases still running are left alone
+    let released = queue.workflows.release_stale_claims(now + LEASE - Duration::seconds(1)).await.unwrap();
+    assert!(released.requeued.is_empty() && released.failed.is_empty());
+
+    let released = queue.workflows.release_stale_claims(now + LEASE + Duration::seconds(1)).await.unwrap();
+    assert_eq!(released.requeued, [fresh]);
+    assert_eq!(released.failed, [last_try]);
+    assert_eq!(queue.state(fresh).await, ("Queued".to_string(), 1, false));
+    assert_eq!(queue.state(last_try).await, ("Failed".to_string(), 3, true));
+
+    // The dead worker lost its claim; another worker picks the task up
+    assert!(!queue.workflows.finish_task(fresh, "worker-a", TaskStatus::Completed).await.unwrap());
+    let reclaimed = queue.workflows.claim_due_tasks("worker-b", now + LEASE, 10, LEASE).await.unwrap();
+    assert_eq!(reclaimed.iter().map(|c| c.task.id).collect::<Vec<_>>(), [fresh]);
+    assert_eq!(reclaimed[0].task.retry_count, 1);
+}
+
+#[tokio::test]
+async fn rescheduling_requeues_tasks_no_executor_holds() {
+    let Some(queue) = Queue::open().await else { return };
+    let now = now();
+    let task = queue.schedule(now - Duration::minutes(1), 0, 3).await;
+    queue.workflows.claim_due_tasks("worker-a", now, 1, LEASE).await.unwrap();
+
+    // Held by worker-a, so left alone
+    assert!(queue.workflows.schedule_task(queue.task(task, 1, 3), now).await.unwrap().is_none());
+    assert_eq!(queue.state(task).await, ("InProgress".to_string(), 0, false));
+
+    queue.workflows.finish_task(task, "worker-a", TaskStatus::Failed).await.unwrap();
+    let retry_at = now + Duration::minutes(5);
+    let requeued = queue.workflows.schedule_task(queue.task(task, 1, 3), retry_at).await.unwrap().unwrap();
+    assert!(matches!(requeued.status, TaskStatus::Queued));
+    assert_eq!(queue.state(task).await, ("Queued".to_string(), 1, false));
+    assert!(queue.workflows.claim_due_tasks("worker-b", now, 1, LEASE).await.unwrap().is_empty());
+    assert_eq!(queue.workflows.claim_due_tasks("worker-b", retry_at, 1, LEASE).await.unwrap().len(), 1);
+}
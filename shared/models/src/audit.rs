@@ -31,6 +31,13 @@ pub enum AuditAction {
     WorkflowStarted,
     WorkflowCompleted,
     EscalationCreated,
+    SupplierMerged,
+    /// A supplier or component was archived, restored or soft-deleted
+    LifecycleChanged,
+    /// A record was written through a store, as `Audited` records it
+    RecordCreated,
+    RecordUpdated,
+    RecordDeleted,
     UserAction,
     SystemAction,
 }
@@ -99,6 +106,14 @@ impl AuditEntry {
         }
     }
     
+    /// Records what changed, rehashing the entry
+    pub fn with_details(mut self, changes: Vec<FieldChange>, metadata: std::collections::HashMap<String, String>) -> Self {
+        self.details.changes = changes;
+        self.details.metadata = metadata;
+        self.hash = Self::calculate_hash(&self.action, &self.details, &self.timestamp);
+        self
+    }
+    
     fn calculate_hash(action: &AuditAction, details: &AuditDetails, timestamp: &DateTime<Utc>) -> String {
         use sha2::{Digest, Sha256};
         
@@ -0,0 +1,235 @@
+//! Builders for models with invariants beyond their fields.
+//!
+//! `ComplianceRecordBuilder` and `ChemicalSubstanceBuilder` require the
+//! fields a record cannot do without, derive what must not be set by hand
+//! (a record's validation status, a substance's PFAS flag) and run the
+//! model's `validator` rules on `build()`, reporting what is wrong as a
+//! `BuildError`.
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use validator::{Validate, ValidationErrors};
+
+use crate::{
+    AuditEntry, CASRecord, Certification, ChemicalRegulatoryStatus, ChemicalSubstance, ComplianceRecord,
+    ComplianceRecordId, ComponentId, Exemption, PFASClassification, SupplierId, TestResult,
+};
+
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("{model} requires {field}")]
+    Missing { model: &'static str, field: &'static str },
+
+    #[error("{model} failed validation: {errors}")]
+    Invalid { model: &'static str, errors: ValidationErrors },
+
+    #[error("{model} is inconsistent: {message}")]
+    Inconsistent { model: &'static str, message: String },
+}
+
+const COMPLIANCE_RECORD: &str = "ComplianceRecord";
+const CHEMICAL_SUBSTANCE: &str = "ChemicalSubstance";
+
+/// Builds a `ComplianceRecord` whose validation status follows from its contents
+#[derive(Debug, Clone, Default)]
+pub struct ComplianceRecordBuilder {
+    supplier_id: Option<SupplierId>,
+    component_id: Option<ComponentId>,
+    cas_records: Vec<CASRecord>,
+    test_results: Vec<TestResult>,
+    certifications: Vec<Certification>,
+    exemptions: Vec<Exemption>,
+    audit_trail: Vec<AuditEntry>,
+    submission_date: Option<DateTime<Utc>>,
+}
+
+impl ComplianceRecordBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn supplier_id(mut self, supplier_id: SupplierId) -> Self {
+        self.supplier_id = Some(supplier_id);
+        self
+    }
+
+    pub fn component_id(mut self, component_id: ComponentId) -> Self {
+        self.component_id = Some(component_id);
+        self
+    }
+
+    pub fn cas_record(mut self, cas_record: CASRecord) -> Self {
+        self.cas_records.push(cas_record);
+        self
+    }
+
+    pub fn test_result(mut self, test_result: TestResult) -> Self {
+        self.test_results.push(test_result);
+        self
+    }
+
+    pub fn certification(mut self, certification: Certification) -> Self {
+        self.certifications.push(certification);
+        self
+    }
+
+    pub fn exemption(mut self, exemption: Exemption) -> Self {
+        self.exemptions.push(exemption);
+        self
+    }
+
+    pub fn audit_entry(mut self, entry: AuditEntry) -> Self {
+        self.audit_trail.push(entry);
+        self
+    }
+
+    /// Defaults to the time of `build()`
+    pub fn submission_date(mut self, submission_date: DateTime<Utc>) -> Self {
+        self.submission_date = Some(submission_date);
+        self
+    }
+
+    /// Requires the supplier and component, exemptions for that component
+    /// and certifications that expire after they were issued
+    pub fn build(self) -> Result<ComplianceRecord, BuildError> {
+        let supplier_id = self.supplier_id.ok_or(BuildError::Missing { model: COMPLIANCE_RECORD, field: "supplier_id" })?;
+        let component_id = self.component_id.ok_or(BuildError::Missing { model: COMPLIANCE_RECORD, field: "component_id" })?;
+
+        if let Some(exemption) = self.exemptions.iter().find(|e| e.component_id != component_id) {
+            return Err(BuildError::Inconsistent {
+                model: COMPLIANCE_RECORD,
+                message: format!("exemption {} is for component {}, not {}", exemption.id, exemption.component_id, component_id),
+            });
+        }
+        if let Some(certification) = self.certifications.iter().find(|c| c.expiry_date.is_some_and(|expiry| expiry < c.issue_date)) {
+            return Err(BuildError::Inconsistent {
+                model: COMPLIANCE_RECORD,
+                message: format!("certification {} expires before it was issued", certification.certificate_number),
+            });
+        }
+
+        let now = Utc::now();
+        let mut record = ComplianceRecord {
+            id: ComplianceRecordId::new(),
+            supplier_id,
+            component_id,
+            cas_records: self.cas_records,
+            test_results: self.test_results,
+            certifications: self.certifications,
+            exemptions: self.exemptions,
+            submission_date: self.submission_date.unwrap_or(now),
+            audit_trail: self.audit_trail,
+            created_at: now,
+            updated_at: now,
+            ..ComplianceRecord::default()
+        };
+        record.update_validation_status();
+        record.validate().map_err(|errors| BuildError::Invalid { model: COMPLIANCE_RECORD, errors })?;
+        Ok(record)
+    }
+}
+
+/// Builds a `ChemicalSubstance` with a checked CAS number whose PFAS flag
+/// follows its classification
+#[derive(Debug, Clone, Default)]
+pub struct ChemicalSubstanceBuilder {
+    cas_number: Option<String>,
+    chemical_name: Option<String>,
+    molecular_formula: Option<String>,
+    molecular_weight: Option<f64>,
+    is_pfas: Option<bool>,
+    pfas_classification: Option<PFASClassification>,
+    regulatory_status: Option<ChemicalRegulatoryStatus>,
+}
+
+impl ChemicalSubstanceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cas_number(mut self, cas_number: impl Into<String>) -> Self {
+        self.cas_number = Some(cas_number.into());
+        self
+    }
+
+    pub fn chemical_name(mut self, chemical_name: impl Into<String>) -> Self {
+        self.chemical_name = Some(chemical_name.into());
+        self
+    }
+
+    pub fn molecular_formula(mut self, molecular_formula: impl Into<String>) -> Self {
+        self.molecular_formula = Some(molecular_formula.into());
+        self
+    }
+
+    pub fn molecular_weight(mut self, molecular_weight: f64) -> Self {
+        self.molecular_weight = Some(molecular_weight);
+        self
+    }
+
+    /// Only needed without a classification, which otherwise decides it
+    pub fn is_pfas(mut self, is_pfas: bool) -> Self {
+        self.is_pfas = Some(is_pfas);
+        self
+    }
+
+    pub fn pfas_classification(mut self, classification: PFASClassification) -> Self {
+        self.pfas_classification = Some(classification);
+        self
+    }
+
+    pub fn regulatory_status(mut self, status: ChemicalRegulatoryStatus) -> Self {
+        self.regulatory_status = Some(status);
+        self
+    }
+
+    /// Requires the CAS number, with a correct check digit, and the name
+    pub fn build(self) -> Result<ChemicalSubstance, BuildError> {
+        let cas_number = self.cas_number.ok_or(BuildError::Missing { model: CHEMICAL_SUBSTANCE, field: "cas_number" })?;
+        let chemical_name = self.chemical_name.ok_or(BuildError::Missing { model: CHEMICAL_SUBSTANCE, field: "chemical_name" })?;
+
+        let classified = self.pfas_classification.as_ref().map(|c| c.is_pfas);
+        if let (Some(is_pfas), Some(classified)) = (self.is_pfas, classified) {
+            if is_pfas != classified {
+                return Err(BuildError::Inconsistent {
+                    model: CHEMICAL_SUBSTANCE,
+                    message: format!("is_pfas is {} but the classification says {}", is_pfas, classified),
+                });
+            }
+        }
+
+        let mut substance = ChemicalSubstance {
+            cas_number,
+            chemical_name,
+            molecular_formula: self.molecular_formula,
+            molecular_weight: self.molecular_weight,
+            is_pfas: classified.or(self.is_pfas).unwrap_or(false),
+            pfas_classification: self.pfas_classification,
+            last_updated: Utc::now(),
+            ..ChemicalSubstance::default()
+        };
+        if let Some(status) = self.regulatory_status {
+            substance.regulatory_status = status;
+        }
+        substance.validate().map_err(|errors| BuildError::Invalid { model: CHEMICAL_SUBSTANCE, errors })?;
+        if !substance.is_valid_cas() {
+            return Err(BuildError::Inconsistent {
+                model: CHEMICAL_SUBSTANCE,
+                message: format!("CAS number {} fails its check digit", substance.cas_number),
+            });
+        }
+        Ok(substance)
+    }
+}
+
+impl ComplianceRecord {
+    pub fn builder() -> ComplianceRecordBuilder {
+        ComplianceRecordBuilder::new()
+    }
+}
+
+impl ChemicalSubstance {
+    pub fn builder() -> ChemicalSubstanceBuilder {
+        ChemicalSubstanceBuilder::new()
+    }
+}
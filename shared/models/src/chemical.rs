@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::{Validate, ValidationError};
 
+use crate::{Concentration, ConcentrationUnit, ThresholdVerdict};
+
 /// Represents a chemical substance with CAS number, PFAS classification,
 /// and comprehensive regulatory status information.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Validate)]
@@ -62,6 +64,9 @@ pub struct ReportingRequirement {
     pub deadline: DateTime<Utc>,
     #[validate(range(min = 0.0, message = "Threshold must be positive"))]
     pub threshold: Option<f64>,
+    /// Unit of `threshold`, by weight; percent when unset
+    #[serde(default)]
+    pub threshold_unit: Option<ConcentrationUnit>,
     #[validate(length(min = 1, max = 100, message = "Reporting format is required"))]
     pub reporting_format: String,
     pub mandatory: bool,
@@ -84,6 +89,9 @@ pub struct ChemicalRestriction {
     pub restriction_type: RestrictionType,
     #[validate(range(min = 0.0, message = "Threshold must be positive"))]
     pub threshold: Option<f64>,
+    /// Unit of `threshold`, by weight; percent when unset
+    #[serde(default)]
+    pub threshold_unit: Option<ConcentrationUnit>,
     pub effective_date: DateTime<Utc>,
     #[validate(length(min = 1, max = 500, message = "Description is required"))]
     pub description: String,
@@ -219,6 +227,23 @@ impl ChemicalSubstance {
             .collect()
     }
     
+    /// Gets the active restrictions a concentration of the substance falls
+    /// under: those without a threshold and those it is at or above
+    pub fn restrictions_applying_at(&self, concentration: &Concentration) -> Vec<&ChemicalRestriction> {
+        self.active_restrictions()
+            .into_iter()
+            .filter(|r| matches!(r.evaluate(concentration), ThresholdVerdict::AtOrAbove | ThresholdVerdict::NoThreshold))
+            .collect()
+    }
+    
+    /// Gets the reporting requirements a concentration of the substance triggers
+    pub fn reporting_required_at(&self, concentration: &Concentration) -> Vec<&ReportingRequirement> {
+        self.regulatory_status.reporting_requirements
+            .iter()
+            .filter(|r| matches!(r.evaluate(concentration), ThresholdVerdict::AtOrAbove | ThresholdVerdict::NoThreshold))
+            .collect()
+    }
+    
     /// Checks if the substance is banned in any jurisdiction
     pub fn is_banned(&self) -> bool {
         self.regulatory_status.restrictions
@@ -291,6 +316,26 @@ impl PFASClassification {
     }
 }
 
+impl ReportingRequirement {
+    /// Compares a concentration with the requirement's threshold, converting units
+    pub fn evaluate(&self, concentration: &Concentration) -> ThresholdVerdict {
+        match self.threshold {
+            Some(threshold) => concentration.evaluate(threshold, self.threshold_unit.unwrap_or(ConcentrationUnit::Percent)),
+            None => ThresholdVerdict::NoThreshold,
+        }
+    }
+}
+
+impl ChemicalRestriction {
+    /// Compares a concentration with the restriction's threshold, converting units
+    pub fn evaluate(&self, concentration: &Concentration) -> ThresholdVerdict {
+        match self.threshold {
+            Some(threshold) => concentration.evaluate(threshold, self.threshold_unit.unwrap_or(ConcentrationUnit::Percent)),
+            None => ThresholdVerdict::NoThreshold,
+        }
+    }
+}
+
 impl CASValidation {
     /// Creates a new CAS validation result
     pub fn new(cas_number: &str) -> Self {
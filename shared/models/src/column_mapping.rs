@@ -0,0 +1,91 @@
+//! BOM column mappings.
+//!
+//! A `ColumnMapping` names which header of a client's BOM files holds each
+//! `BomField`, for headers the parser's aliases don't recognise, such as
+//! "Lieferant" or "Mfr Name". Clients save mappings under a name to reuse
+//! them for later uploads.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A field the BOM parser fills from a column
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BomField {
+    SupplierName,
+    SupplierEmail,
+    ContactPerson,
+    PartNumber,
+    Description,
+    MaterialType,
+    Quantity,
+    ReferenceDesignators,
+    CasNumbers,
+}
+
+impl BomField {
+    pub const ALL: [BomField; 9] = [
+        BomField::SupplierName,
+        BomField::SupplierEmail,
+        BomField::ContactPerson,
+        BomField::PartNumber,
+        BomField::Description,
+        BomField::MaterialType,
+        BomField::Quantity,
+        BomField::ReferenceDesignators,
+        BomField::CasNumbers,
+    ];
+}
+
+/// Header holding each mapped field, compared ignoring case and surrounding whitespace
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ColumnMapping {
+    pub columns: BTreeMap<BomField, String>,
+}
+
+impl ColumnMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read `field` from the `header` column
+    pub fn map(mut self, field: BomField, header: impl Into<String>) -> Self {
+        self.columns.insert(field, header.into());
+        self
+    }
+
+    /// The mapped header for `field`, lowercased and trimmed like parsed headers
+    pub fn column(&self, field: BomField) -> Option<String> {
+        self.columns.get(&field).map(|header| header.trim().to_lowercase())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+/// A column mapping a client saved for reuse
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedColumnMapping {
+    pub id: Uuid,
+    pub name: String,
+    pub mapping: ColumnMapping,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SavedColumnMapping {
+    pub fn new(name: impl Into<String>, mapping: ColumnMapping) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            mapping,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
@@ -1,27 +1,35 @@
 //! Compliance domain models for the Elementa compliance system.
 //! 
 //! This module defines compliance-related data structures including
-//! compliance records, CAS records, test results, and certifications.
+//! compliance records, CAS records with their concentrations, test results,
+//! certifications, and exemptions for uses a regulation does not cover.
 
-use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
-use crate::{AuditEntry, DocumentReference};
+use crate::schema::{self, SchemaError, Versioned};
+use crate::{AuditEntry, ComplianceRecordId, ComponentId, DocumentReference, SupplierId};
 
 /// Represents a compliance record containing all compliance data for a specific
 /// supplier-component pair, including CAS records, test results, and certifications.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Validate, PartialEq)]
 pub struct ComplianceRecord {
-    pub id: Uuid,
-    pub supplier_id: Uuid,
-    pub component_id: Uuid,
+    pub id: ComplianceRecordId,
+    pub supplier_id: SupplierId,
+    pub component_id: ComponentId,
     #[validate(custom = "validate_cas_records")]
     pub cas_records: Vec<CASRecord>,
     pub test_results: Vec<TestResult>,
     pub certifications: Vec<Certification>,
+    /// Exempt uses of the component or of substances in it
+    #[serde(default)]
+    #[validate]
+    pub exemptions: Vec<Exemption>,
     pub submission_date: DateTime<Utc>,
     pub validation_status: ValidationStatus,
     pub audit_trail: Vec<AuditEntry>,
@@ -40,6 +48,10 @@ pub struct CASRecord {
     pub is_pfas: bool,
     #[validate(range(min = 0.0, max = 1.0, message = "Confidence must be between 0.0 and 1.0"))]
     pub confidence: f64,
+    /// How much of the substance is present; `None` when only its presence is known
+    #[serde(default)]
+    #[validate]
+    pub concentration: Option<Concentration>,
     #[validate]
     pub regulatory_status: RegulatoryStatus,
     pub source_document: DocumentReference,
@@ -47,12 +59,52 @@ pub struct CASRecord {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct Concentration {
+    #[validate(range(min = 0.0, message = "Concentration must be positive"))]
+    pub value: f64,
+    pub unit: ConcentrationUnit,
+    pub basis: ConcentrationBasis,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConcentrationUnit {
+    Percent,
+    Ppm,
+    Ppb,
+    MgPerKg,
+    GPerKg,
+}
+
+/// What the concentration is measured against. Regulatory thresholds are
+/// by weight; the other bases cannot be compared with them without a density.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConcentrationBasis {
+    WeightByWeight,
+    WeightByVolume,
+    VolumeByVolume,
+}
+
+/// Outcome of comparing a CAS record's concentration with a reporting threshold
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ThresholdVerdict {
+    /// At or above the threshold, so reportable
+    AtOrAbove,
+    Below,
+    /// The requirement applies at any concentration
+    NoThreshold,
+    /// No concentration, or one on a basis the threshold cannot be compared with
+    Undetermined,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ExtractionMethod {
     VLMAutomatic,
     OCRProcessing,
     ManualEntry,
     DatabaseLookup,
+    /// Imported from a supplier's formal material declaration
+    MaterialDeclaration,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
@@ -80,6 +132,9 @@ pub struct ReportingRequirement {
     pub deadline: DateTime<Utc>,
     #[validate(range(min = 0.0, message = "Threshold must be positive"))]
     pub threshold: Option<f64>,
+    /// Unit of `threshold`, by weight; percent when unset
+    #[serde(default)]
+    pub threshold_unit: Option<ConcentrationUnit>,
     #[validate(length(min = 1, max = 100, message = "Reporting format is required"))]
     pub reporting_format: String,
 }
@@ -124,6 +179,9 @@ pub struct Certification {
     #[validate(length(min = 1, max = 500, message = "Scope is required"))]
     pub scope: String,
     pub source_document: DocumentReference,
+    /// When the issuing body withdrew the certificate
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -136,6 +194,59 @@ pub enum CertificationType {
     Other(String),
 }
 
+/// Where a certification stands on a given day. Valid, expiring-soon and
+/// expired follow from the expiry date; a revocation holds whatever the date.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CertificationStatus {
+    Valid,
+    ExpiringSoon,
+    Expired,
+    Revoked,
+}
+
+/// Days before expiry from which a certification counts as expiring soon
+pub const CERTIFICATION_EXPIRY_WARNING_DAYS: i64 = 60;
+
+/// A use of a component, or of one substance in it, that a regulation
+/// exempts, e.g. a site-limited intermediate or a fluoropolymer processing aid
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct Exemption {
+    pub id: Uuid,
+    pub component_id: ComponentId,
+    /// Substance the exemption covers; the whole component when `None`
+    #[validate(custom = "validate_cas_number")]
+    pub cas_number: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "Regulation name is required"))]
+    pub regulation: String,
+    pub category: ExemptionCategory,
+    #[validate(length(min = 1, max = 2000, message = "Justification is required"))]
+    pub justification: String,
+    pub evidence: Option<DocumentReference>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub approval_state: ExemptionApproval,
+    #[validate(length(max = 255))]
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ExemptionCategory {
+    Intermediate,
+    ProcessingAid,
+    ResearchAndDevelopment,
+    Article,
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExemptionApproval {
+    Requested,
+    Approved,
+    Rejected,
+    Revoked,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ValidationStatus {
     Pending,
@@ -148,12 +259,13 @@ pub enum ValidationStatus {
 impl Default for ComplianceRecord {
     fn default() -> Self {
         Self {
-            id: Uuid::new_v4(),
-            supplier_id: Uuid::new_v4(),
-            component_id: Uuid::new_v4(),
+            id: ComplianceRecordId::new(),
+            supplier_id: SupplierId::new(),
+            component_id: ComponentId::new(),
             cas_records: Vec::new(),
             test_results: Vec::new(),
             certifications: Vec::new(),
+            exemptions: Vec::new(),
             submission_date: Utc::now(),
             validation_status: ValidationStatus::Pending,
             audit_trail: Vec::new(),
@@ -163,6 +275,32 @@ impl Default for ComplianceRecord {
     }
 }
 
+// Schema versions of ComplianceRecord:
+// 1. Unversioned records
+// 2. Exemptions, CAS concentrations and reporting threshold units
+impl Versioned for ComplianceRecord {
+    const TYPE_NAME: &'static str = "ComplianceRecord";
+    const SCHEMA_VERSION: u32 = 2;
+    
+    fn upgrade(version: u32, value: &mut serde_json::Value) -> Result<(), SchemaError> {
+        match version {
+            1 => {
+                schema::insert_missing::<Self>(value, "exemptions", serde_json::json!([]))?;
+                schema::upgrade_each::<Self>(value, "cas_records", |cas_record| {
+                    schema::insert_missing::<Self>(cas_record, "concentration", serde_json::Value::Null)?;
+                    match cas_record.get_mut("regulatory_status") {
+                        Some(status) => schema::upgrade_each::<Self>(status, "reporting_requirements", |requirement| {
+                            schema::insert_missing::<Self>(requirement, "threshold_unit", serde_json::Value::Null)
+                        }),
+                        None => Ok(()),
+                    }
+                })
+            }
+            _ => Err(SchemaError::MissingUpgrade { type_name: Self::TYPE_NAME, version }),
+        }
+    }
+}
+
 // Custom validation functions
 fn validate_cas_records(cas_records: &[CASRecord]) -> Result<(), ValidationError> {
     for record in cas_records {
@@ -173,7 +311,7 @@ fn validate_cas_records(cas_records: &[CASRecord]) -> Result<(), ValidationError
     Ok(())
 }
 
-fn validate_cas_number(cas_number: &str) -> Result<(), ValidationError> {
+pub(crate) fn validate_cas_number(cas_number: &str) -> Result<(), ValidationError> {
     // CAS number format: XXXXXX-XX-X where X is a digit
     let parts: Vec<&str> = cas_number.split('-').collect();
     if parts.len() != 3 {
@@ -196,7 +334,7 @@ fn validate_cas_number(cas_number: &str) -> Result<(), ValidationError> {
 // Utility methods for ComplianceRecord
 impl ComplianceRecord {
     /// Creates a new compliance record for a supplier and component
-    pub fn new(supplier_id: Uuid, component_id: Uuid) -> Self {
+    pub fn new(supplier_id: SupplierId, component_id: ComponentId) -> Self {
         let mut record = Self::default();
         record.supplier_id = supplier_id;
         record.component_id = component_id;
@@ -224,11 +362,31 @@ impl ComplianceRecord {
         self.update_validation_status();
     }
     
-    /// Updates the validation status based on available data
+    /// Adds an exemption to the compliance record
+    pub fn add_exemption(&mut self, exemption: Exemption) {
+        self.exemptions.push(exemption);
+        self.updated_at = Utc::now();
+        self.update_validation_status();
+    }
+    
+    /// Records a decision on one of the record's exemptions and re-evaluates the status
+    pub fn decide_exemption(&mut self, exemption_id: Uuid, decision: ExemptionApproval, decided_by: String) -> Result<(), String> {
+        let exemption = self.exemptions.iter_mut()
+            .find(|e| e.id == exemption_id)
+            .ok_or_else(|| format!("Exemption {} not found", exemption_id))?;
+        exemption.decide(decision, decided_by)?;
+        self.updated_at = Utc::now();
+        self.update_validation_status();
+        Ok(())
+    }
+    
+    /// Updates the validation status based on available data. Substances
+    /// under an approved exemption do not need confident data, and an
+    /// exemption awaiting a decision needs review.
     pub fn update_validation_status(&mut self) {
         if self.cas_records.is_empty() && self.test_results.is_empty() && self.certifications.is_empty() {
             self.validation_status = ValidationStatus::Incomplete;
-        } else if self.has_low_confidence_data() {
+        } else if self.has_low_confidence_data() || self.has_pending_exemptions() {
             self.validation_status = ValidationStatus::RequiresReview;
         } else if self.has_complete_data() {
             self.validation_status = ValidationStatus::Valid;
@@ -239,13 +397,29 @@ impl ComplianceRecord {
     
     /// Checks if the record has low confidence data that requires review
     pub fn has_low_confidence_data(&self) -> bool {
-        self.cas_records.iter().any(|r| r.confidence < 0.7)
+        self.cas_records.iter().any(|r| r.confidence < 0.7 && !self.is_exempt(&r.cas_number))
     }
     
     /// Checks if the record has complete compliance data
     pub fn has_complete_data(&self) -> bool {
         !self.cas_records.is_empty() && 
-        self.cas_records.iter().all(|r| r.confidence >= 0.7)
+        self.cas_records.iter().all(|r| r.confidence >= 0.7 || self.is_exempt(&r.cas_number))
+    }
+    
+    /// Checks if an approved, unexpired exemption covers a substance
+    pub fn is_exempt(&self, cas_number: &str) -> bool {
+        let now = Utc::now();
+        self.exemptions.iter().any(|e| e.is_active(now) && e.covers(cas_number))
+    }
+    
+    /// Checks if any exemption is waiting for a decision
+    pub fn has_pending_exemptions(&self) -> bool {
+        self.exemptions.iter().any(|e| e.approval_state == ExemptionApproval::Requested)
+    }
+    
+    /// Gets PFAS substances no active exemption covers
+    pub fn unexempted_pfas_substances(&self) -> Vec<&CASRecord> {
+        self.cas_records.iter().filter(|r| r.is_pfas && !self.is_exempt(&r.cas_number)).collect()
     }
     
     /// Gets all PFAS substances in this record
@@ -263,6 +437,14 @@ impl ComplianceRecord {
         self.cas_records.iter().any(|r| r.is_pfas)
     }
     
+    /// Gets the certifications expired or expiring within `warning` of `now`,
+    /// leaving out revoked ones, which renewal cannot restore
+    pub fn certifications_needing_renewal(&self, now: DateTime<Utc>, warning: Duration) -> Vec<&Certification> {
+        self.certifications.iter()
+            .filter(|c| matches!(c.status_at(now, warning), CertificationStatus::ExpiringSoon | CertificationStatus::Expired))
+            .collect()
+    }
+    
     /// Gets the overall confidence score for the record
     pub fn overall_confidence(&self) -> f64 {
         if self.cas_records.is_empty() {
@@ -289,6 +471,7 @@ impl CASRecord {
             chemical_name,
             is_pfas,
             confidence,
+            concentration: None,
             regulatory_status: RegulatoryStatus {
                 regulatory_lists: Vec::new(),
                 reporting_requirements: Vec::new(),
@@ -305,6 +488,19 @@ impl CASRecord {
         !self.regulatory_status.reporting_requirements.is_empty()
     }
     
+    /// Compares the record's concentration with a requirement's threshold,
+    /// converting units; only weight-by-weight concentrations compare
+    pub fn evaluate_threshold(&self, requirement: &ReportingRequirement) -> ThresholdVerdict {
+        let Some(threshold) = requirement.threshold else {
+            return ThresholdVerdict::NoThreshold;
+        };
+        let unit = requirement.threshold_unit.unwrap_or(ConcentrationUnit::Percent);
+        match &self.concentration {
+            Some(concentration) => concentration.evaluate(threshold, unit),
+            None => ThresholdVerdict::Undetermined,
+        }
+    }
+    
     /// Gets upcoming reporting deadlines
     pub fn upcoming_deadlines(&self) -> Vec<&ReportingRequirement> {
         let now = Utc::now();
@@ -313,4 +509,245 @@ impl CASRecord {
             .filter(|req| req.deadline > now)
             .collect()
     }
-}
\ No newline at end of file
+}
+
+// Utility methods for ConcentrationUnit
+impl ConcentrationUnit {
+    /// Gets how much of the whole one unit stands for, e.g. 0.01 for percent
+    pub fn fraction(&self) -> f64 {
+        match self {
+            ConcentrationUnit::Percent => 1e-2,
+            ConcentrationUnit::Ppm | ConcentrationUnit::MgPerKg => 1e-6,
+            ConcentrationUnit::Ppb => 1e-9,
+            ConcentrationUnit::GPerKg => 1e-3,
+        }
+    }
+    
+    /// Converts a value in this unit to `unit`
+    pub fn convert(&self, value: f64, unit: ConcentrationUnit) -> f64 {
+        value * self.fraction() / unit.fraction()
+    }
+    
+    /// Gets the symbol the unit is written with
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ConcentrationUnit::Percent => "%",
+            ConcentrationUnit::Ppm => "ppm",
+            ConcentrationUnit::Ppb => "ppb",
+            ConcentrationUnit::MgPerKg => "mg/kg",
+            ConcentrationUnit::GPerKg => "g/kg",
+        }
+    }
+}
+
+impl std::fmt::Display for ConcentrationUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Parses the ways documents and lab reports write weight concentrations,
+/// e.g. `wt%`, `ppm` or `mg/kg`
+impl std::str::FromStr for ConcentrationUnit {
+    type Err = String;
+    
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        let normalized: String = unit.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        match normalized.as_str() {
+            "%" | "percent" | "wt%" | "%wt" | "%w/w" | "w/w%" | "weight%" => Ok(ConcentrationUnit::Percent),
+            "ppm" | "ppmw" => Ok(ConcentrationUnit::Ppm),
+            "ppb" | "ppbw" => Ok(ConcentrationUnit::Ppb),
+            "mg/kg" | "µg/g" | "ug/g" => Ok(ConcentrationUnit::MgPerKg),
+            "g/kg" | "mg/g" => Ok(ConcentrationUnit::GPerKg),
+            _ => Err(format!("Unknown concentration unit: {}", unit)),
+        }
+    }
+}
+
+// Utility methods for Concentration
+impl Concentration {
+    pub fn new(value: f64, unit: ConcentrationUnit, basis: ConcentrationBasis) -> Self {
+        Self { value, unit, basis }
+    }
+    
+    /// Gets the concentration in `unit`, on the same basis
+    pub fn in_unit(&self, unit: ConcentrationUnit) -> f64 {
+        self.unit.convert(self.value, unit)
+    }
+    
+    /// Compares two concentrations in whatever units they are given;
+    /// `None` when they are on different bases
+    pub fn compare(&self, other: &Concentration) -> Option<Ordering> {
+        if self.basis != other.basis {
+            return None;
+        }
+        let (ours, theirs) = (self.in_unit(ConcentrationUnit::Ppb), other.in_unit(ConcentrationUnit::Ppb));
+        if approximately_equal(ours, theirs) {
+            Some(Ordering::Equal)
+        } else {
+            ours.partial_cmp(&theirs)
+        }
+    }
+    
+    /// Compares the concentration with a threshold by weight; other bases
+    /// cannot be compared without a density
+    pub fn evaluate(&self, threshold: f64, unit: ConcentrationUnit) -> ThresholdVerdict {
+        if self.basis != ConcentrationBasis::WeightByWeight {
+            return ThresholdVerdict::Undetermined;
+        }
+        let value = self.in_unit(unit);
+        if value >= threshold || approximately_equal(value, threshold) {
+            ThresholdVerdict::AtOrAbove
+        } else {
+            ThresholdVerdict::Below
+        }
+    }
+}
+
+/// Unit conversion is not exact, so a value equal to another in a different
+/// unit must still compare equal
+fn approximately_equal(a: f64, b: f64) -> bool {
+    (a - b).abs() <= a.abs().max(b.abs()) * 1e-9
+}
+
+// Utility methods for TestResult
+impl TestResult {
+    /// Gets the result as a weight concentration, when its unit is one
+    pub fn concentration(&self) -> Option<Concentration> {
+        let unit = self.unit.parse().ok()?;
+        Some(Concentration::new(self.result_value, unit, ConcentrationBasis::WeightByWeight))
+    }
+    
+    /// Checks if the substance was not detected, i.e. the result is under the detection limit
+    pub fn is_below_detection_limit(&self) -> bool {
+        self.detection_limit.is_some_and(|limit| self.result_value < limit)
+    }
+    
+    /// Compares the result with a requirement's threshold, converting units.
+    /// A result under the detection limit is only below a threshold the
+    /// detection limit itself is below.
+    pub fn evaluate_threshold(&self, requirement: &ReportingRequirement) -> ThresholdVerdict {
+        let Some(threshold) = requirement.threshold else {
+            return ThresholdVerdict::NoThreshold;
+        };
+        let Some(concentration) = self.concentration() else {
+            return ThresholdVerdict::Undetermined;
+        };
+        let unit = requirement.threshold_unit.unwrap_or(ConcentrationUnit::Percent);
+        match self.detection_limit {
+            Some(limit) if self.is_below_detection_limit() => {
+                match Concentration::new(limit, concentration.unit, concentration.basis).evaluate(threshold, unit) {
+                    ThresholdVerdict::Below => ThresholdVerdict::Below,
+                    _ => ThresholdVerdict::Undetermined,
+                }
+            }
+            _ => concentration.evaluate(threshold, unit),
+        }
+    }
+}
+
+// Utility methods for Certification
+impl Certification {
+    /// Gets the certification's state at `now`, counting it as expiring soon
+    /// within `warning` of its expiry date
+    pub fn status_at(&self, now: DateTime<Utc>, warning: Duration) -> CertificationStatus {
+        match self.expiry_date {
+            _ if self.revoked_at.is_some_and(|revoked| revoked <= now) => CertificationStatus::Revoked,
+            Some(expiry) if expiry <= now => CertificationStatus::Expired,
+            Some(expiry) if expiry <= now + warning => CertificationStatus::ExpiringSoon,
+            _ => CertificationStatus::Valid,
+        }
+    }
+    
+    /// Gets the certification's state today with the default warning period
+    pub fn status(&self) -> CertificationStatus {
+        self.status_at(Utc::now(), Duration::days(CERTIFICATION_EXPIRY_WARNING_DAYS))
+    }
+    
+    /// Checks if the certification can be relied on at `now`
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.status_at(now, Duration::zero()), CertificationStatus::Valid)
+    }
+    
+    /// Gets the whole days left until expiry, negative once expired; `None` without an expiry date
+    pub fn days_until_expiry(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.expiry_date.map(|expiry| (expiry - now).num_days())
+    }
+    
+    /// Records that the issuing body withdrew the certificate; revocation is final
+    pub fn revoke(&mut self, at: DateTime<Utc>) -> Result<(), String> {
+        if self.revoked_at.is_some() {
+            return Err(format!("Certification {} is already revoked", self.certificate_number));
+        }
+        self.revoked_at = Some(at);
+        Ok(())
+    }
+    
+    /// Records a renewal of the certificate with a new validity period;
+    /// a revoked certificate has to be replaced rather than renewed
+    pub fn renew(&mut self, issue_date: DateTime<Utc>, expiry_date: Option<DateTime<Utc>>) -> Result<(), String> {
+        if self.revoked_at.is_some() {
+            return Err(format!("Certification {} is revoked and cannot be renewed", self.certificate_number));
+        }
+        if expiry_date.is_some_and(|expiry| expiry <= issue_date) {
+            return Err("Renewed certification must expire after it is issued".to_string());
+        }
+        self.issue_date = issue_date;
+        self.expiry_date = expiry_date;
+        Ok(())
+    }
+}
+
+// Utility methods for Exemption
+impl Exemption {
+    /// Creates a new exemption request
+    pub fn new(
+        component_id: ComponentId,
+        cas_number: Option<String>,
+        regulation: String,
+        category: ExemptionCategory,
+        justification: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            component_id,
+            cas_number,
+            regulation,
+            category,
+            justification,
+            evidence: None,
+            expires_at: None,
+            approval_state: ExemptionApproval::Requested,
+            decided_by: None,
+            decided_at: None,
+            created_at: Utc::now(),
+        }
+    }
+    
+    /// Checks if the exemption is approved and not yet expired
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.approval_state == ExemptionApproval::Approved && self.expires_at.is_none_or(|expiry| expiry > now)
+    }
+    
+    /// Checks if the exemption covers a substance
+    pub fn covers(&self, cas_number: &str) -> bool {
+        self.cas_number.as_deref().is_none_or(|c| c == cas_number)
+    }
+    
+    /// Records an approval decision; only requested exemptions can be
+    /// approved or rejected, and only approved ones revoked
+    pub fn decide(&mut self, decision: ExemptionApproval, decided_by: String) -> Result<(), String> {
+        let allowed = matches!(
+            (self.approval_state, decision),
+            (ExemptionApproval::Requested, ExemptionApproval::Approved | ExemptionApproval::Rejected)
+                | (ExemptionApproval::Approved, ExemptionApproval::Revoked)
+        );
+        if !allowed {
+            return Err(format!("Cannot move exemption from {:?} to {:?}", self.approval_state, decision));
+        }
+        self.approval_state = decision;
+        self.decided_by = Some(decided_by);
+        self.decided_at = Some(Utc::now());
+        Ok(())
+    }
+}
@@ -1,19 +1,22 @@
 //! Component domain models for the Elementa compliance system.
 //! 
 //! This module defines component-related data structures including
-//! component specifications, material types, and CAS number management.
+//! component specifications, material types, CAS number management and
+//! multi-level bills of materials (assemblies of sub-assemblies).
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use uuid::Uuid;
+use std::collections::{BTreeSet, HashMap};
 use validator::{Validate, ValidationError};
 
+use crate::{ComponentId, LifecycleStatus, SupplierId};
+
 /// Represents a component or part in the supply chain with associated CAS numbers
 /// and detailed specifications.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Validate, PartialEq)]
 pub struct Component {
-    pub id: Uuid,
+    pub id: ComponentId,
     #[validate(length(min = 1, max = 100, message = "Part number must be between 1 and 100 characters"))]
     pub part_number: String,
     #[validate(length(min = 1, max = 500, message = "Description must be between 1 and 500 characters"))]
@@ -21,9 +24,14 @@ pub struct Component {
     #[validate(custom = "validate_cas_numbers")]
     pub cas_numbers: Vec<String>,
     pub material_type: MaterialType,
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     #[validate]
     pub specifications: ComponentSpecifications,
+    /// Whether the component is in use, archived or soft-deleted
+    #[serde(default)]
+    pub lifecycle_status: LifecycleStatus,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -66,16 +74,47 @@ pub struct Dimensions {
     pub height_mm: f64,
 }
 
+/// Parent/child relationship in a bill of materials: `quantity` units of
+/// the child component go into one unit of the parent assembly.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, Validate, PartialEq)]
+pub struct AssemblyLink {
+    pub parent_id: ComponentId,
+    pub child_id: ComponentId,
+    #[validate(range(min = 0.0, message = "Quantity must be positive"))]
+    pub quantity: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A component with the sub-assemblies and parts below it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComponentTree {
+    pub component: Component,
+    /// Units per unit of the parent; 1 for the root
+    pub quantity: f64,
+    pub children: Vec<ComponentTree>,
+}
+
+/// PFAS findings rolled up from the parts of an assembly
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PfasRollUp {
+    pub contains_pfas: bool,
+    pub pfas_cas_numbers: BTreeSet<String>,
+    /// Components that list a PFAS substance themselves
+    pub pfas_components: Vec<ComponentId>,
+}
+
 impl Default for Component {
     fn default() -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: ComponentId::new(),
             part_number: String::new(),
             description: String::new(),
             cas_numbers: Vec::new(),
             material_type: MaterialType::Other("Unknown".to_string()),
-            supplier_id: Uuid::new_v4(),
+            supplier_id: SupplierId::new(),
             specifications: ComponentSpecifications::default(),
+            lifecycle_status: LifecycleStatus::default(),
+            deleted_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -125,7 +164,7 @@ fn is_valid_cas_format(cas_number: &str) -> bool {
 // Utility methods for Component
 impl Component {
     /// Creates a new component with the given part number and description
-    pub fn new(part_number: String, description: String, supplier_id: Uuid) -> Self {
+    pub fn new(part_number: String, description: String, supplier_id: SupplierId) -> Self {
         let mut component = Self::default();
         component.part_number = part_number;
         component.description = description;
@@ -184,4 +223,115 @@ impl Component {
     pub fn get_custom_property(&self, key: &str) -> Option<&String> {
         self.specifications.custom_properties.get(key)
     }
-}
\ No newline at end of file
+}
+
+impl AssemblyLink {
+    pub fn new(parent_id: ComponentId, child_id: ComponentId, quantity: f64) -> Self {
+        Self { parent_id, child_id, quantity, created_at: Utc::now() }
+    }
+}
+
+// Hierarchy traversal and roll-ups for ComponentTree
+impl ComponentTree {
+    /// Builds the tree below `root_id` from components and the links between
+    /// them. Fails if the root is missing or the links form a cycle; links to
+    /// components that are not given are left out.
+    pub fn build(root_id: ComponentId, components: &[Component], links: &[AssemblyLink]) -> Result<Self, String> {
+        let by_id: HashMap<ComponentId, &Component> = components.iter().map(|c| (c.id, c)).collect();
+        let mut children: HashMap<ComponentId, Vec<&AssemblyLink>> = HashMap::new();
+        for link in links {
+            children.entry(link.parent_id).or_default().push(link);
+        }
+        
+        fn grow(
+            id: ComponentId,
+            quantity: f64,
+            by_id: &HashMap<ComponentId, &Component>,
+            children: &HashMap<ComponentId, Vec<&AssemblyLink>>,
+            path: &mut Vec<ComponentId>,
+        ) -> Result<Option<ComponentTree>, String> {
+            if path.contains(&id) {
+                return Err(format!("Component {} contains itself", id));
+            }
+            let Some(component) = by_id.get(&id) else {
+                return Ok(None);
+            };
+            path.push(id);
+            let mut nodes = Vec::new();
+            for link in children.get(&id).into_iter().flatten() {
+                if let Some(child) = grow(link.child_id, link.quantity, by_id, children, path)? {
+                    nodes.push(child);
+                }
+            }
+            path.pop();
+            Ok(Some(ComponentTree { component: (*component).clone(), quantity, children: nodes }))
+        }
+        
+        grow(root_id, 1.0, &by_id, &children, &mut Vec::new())?
+            .ok_or_else(|| format!("Component {} not found", root_id))
+    }
+    
+    /// Checks if the component is a bought-in part rather than an assembly
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+    
+    /// Gets the number of levels in the tree, 1 for a single part
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(|c| c.depth()).max().unwrap_or(0)
+    }
+    
+    /// Gets every component in the tree, the root first
+    pub fn components(&self) -> Vec<&Component> {
+        let mut components = vec![&self.component];
+        for child in &self.children {
+            components.extend(child.components());
+        }
+        components
+    }
+    
+    /// Gets all CAS numbers listed anywhere in the tree
+    pub fn cas_numbers(&self) -> BTreeSet<String> {
+        self.components().into_iter()
+            .flat_map(|c| c.cas_numbers.iter().cloned())
+            .collect()
+    }
+    
+    /// Rolls PFAS status up the tree, with `is_pfas` telling which CAS numbers are PFAS
+    pub fn pfas_roll_up(&self, is_pfas: impl Fn(&str) -> bool) -> PfasRollUp {
+        let mut roll_up = PfasRollUp::default();
+        for component in self.components() {
+            let found: Vec<&String> = component.cas_numbers.iter().filter(|cas| is_pfas(cas)).collect();
+            if !found.is_empty() {
+                roll_up.pfas_cas_numbers.extend(found.into_iter().cloned());
+                if !roll_up.pfas_components.contains(&component.id) {
+                    roll_up.pfas_components.push(component.id);
+                }
+            }
+        }
+        roll_up.contains_pfas = !roll_up.pfas_cas_numbers.is_empty();
+        roll_up
+    }
+    
+    /// Gets the parts needed for one unit of the root, with quantities
+    /// multiplied down the levels and summed for parts used in several places
+    pub fn flatten(&self) -> Vec<(ComponentId, f64)> {
+        fn collect(tree: &ComponentTree, multiplier: f64, parts: &mut Vec<(ComponentId, f64)>) {
+            for child in &tree.children {
+                let quantity = multiplier * child.quantity;
+                if child.is_leaf() {
+                    match parts.iter_mut().find(|(id, _)| *id == child.component.id) {
+                        Some((_, total)) => *total += quantity,
+                        None => parts.push((child.component.id, quantity)),
+                    }
+                } else {
+                    collect(child, quantity, parts);
+                }
+            }
+        }
+        
+        let mut parts = Vec::new();
+        collect(self, 1.0, &mut parts);
+        parts
+    }
+}
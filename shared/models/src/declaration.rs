@@ -0,0 +1,273 @@
+//! Material declaration models for the Elementa compliance system.
+//!
+//! Formal declarations such as IPC-1752A and chemSHERPA list the substances
+//! in a part with their concentrations, optionally per homogeneous material,
+//! and are signed by the declaring supplier. They convert to and from
+//! `ComplianceRecord`s so declarations can be ingested and emitted.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::compliance::validate_cas_number;
+use crate::{
+    CASRecord, ComplianceRecord, Concentration, ConcentrationUnit, DocumentReference, Exemption,
+    ExemptionCategory, ExtractionMethod, ComponentId, SupplierId,
+};
+
+/// Confidence of substances from a signed declaration
+const SIGNED_CONFIDENCE: f64 = 1.0;
+
+/// Confidence of substances from an unsigned declaration; low enough to need review
+const UNSIGNED_CONFIDENCE: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeclarationFormat {
+    Ipc1752A,
+    ChemSherpa,
+}
+
+impl std::fmt::Display for DeclarationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ipc1752A => write!(f, "IPC-1752A"),
+            Self::ChemSherpa => write!(f, "chemSHERPA"),
+        }
+    }
+}
+
+/// IPC-1752A declaration classes; chemSHERPA declarations map onto B and D
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeclarationClass {
+    /// Query/response: whether the part meets the regulation
+    A,
+    /// Substances in the part as a whole
+    B,
+    /// Query/response per homogeneous material
+    C,
+    /// Full disclosure of substances per homogeneous material
+    D,
+}
+
+impl DeclarationClass {
+    pub fn lists_substances(&self) -> bool {
+        matches!(self, Self::B | Self::D)
+    }
+
+    pub fn per_homogeneous_material(&self) -> bool {
+        matches!(self, Self::C | Self::D)
+    }
+}
+
+/// A formal declaration of the substances in a part
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct MaterialDeclaration {
+    pub id: Uuid,
+    pub format: DeclarationFormat,
+    pub class: DeclarationClass,
+    pub supplier_id: SupplierId,
+    pub component_id: ComponentId,
+    #[validate(length(min = 1, max = 100, message = "Part number is required"))]
+    pub part_number: String,
+    /// Regulation the declaration answers, e.g. EU RoHS
+    #[validate(length(min = 1, max = 100, message = "Regulation name is required"))]
+    pub regulation: String,
+    /// Whether the part meets the regulation; required for classes A and C
+    pub complies: Option<bool>,
+    #[validate]
+    pub substances: Vec<DeclaredSubstance>,
+    #[validate]
+    pub declarer: DeclarerSignature,
+    pub issued_at: DateTime<Utc>,
+    pub source_document: Option<DocumentReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct DeclaredSubstance {
+    #[validate(custom = "validate_cas_number")]
+    pub cas_number: String,
+    #[validate(length(min = 1, max = 255, message = "Substance name is required"))]
+    pub name: String,
+    /// Of the homogeneous material when one is given, otherwise of the part
+    #[validate]
+    pub concentration: Option<Concentration>,
+    #[validate(range(min = 0.0, message = "Substance mass must be positive"))]
+    pub mass_mg: Option<f64>,
+    /// Homogeneous material the substance is in
+    #[validate]
+    pub material: Option<HomogeneousMaterial>,
+    /// Exemption the declarer claims for the substance, e.g. RoHS 6(c)
+    pub exemption: Option<String>,
+}
+
+/// A material of uniform composition that cannot be mechanically separated
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct HomogeneousMaterial {
+    #[validate(length(min = 1, max = 255, message = "Material name is required"))]
+    pub name: String,
+    #[validate(range(min = 0.0, message = "Material mass must be positive"))]
+    pub mass_mg: Option<f64>,
+}
+
+/// The declarer's authorization of the declaration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct DeclarerSignature {
+    #[validate(length(min = 1, max = 255, message = "Company name is required"))]
+    pub company_name: String,
+    #[validate(length(min = 1, max = 255, message = "Contact name is required"))]
+    pub contact_name: String,
+    pub title: Option<String>,
+    #[validate(email(message = "Declarer email must be a valid email address"))]
+    pub email: String,
+    /// The declarer accepted the format's legal declaration
+    pub accepted_legal_terms: bool,
+    pub signed_at: Option<DateTime<Utc>>,
+}
+
+// Utility methods for DeclarerSignature
+impl DeclarerSignature {
+    /// Checks if the declarer signed and accepted the legal declaration
+    pub fn is_signed(&self) -> bool {
+        self.accepted_legal_terms && self.signed_at.is_some()
+    }
+}
+
+// Utility methods for MaterialDeclaration
+impl MaterialDeclaration {
+    /// Checks what the declaration's class requires: a compliance answer for
+    /// query/response classes, substances for disclosure classes, and a
+    /// homogeneous material for every substance in classes C and D
+    pub fn check_class(&self) -> Result<(), String> {
+        if matches!(self.class, DeclarationClass::A | DeclarationClass::C) && self.complies.is_none() {
+            return Err(format!("Class {:?} declarations must state whether the part complies", self.class));
+        }
+        if self.class.lists_substances() && self.substances.is_empty() {
+            return Err(format!("Class {:?} declarations must list substances", self.class));
+        }
+        if self.class.per_homogeneous_material() {
+            if let Some(substance) = self.substances.iter().find(|s| s.material.is_none()) {
+                return Err(format!(
+                    "Class {:?} declarations need a homogeneous material for {}",
+                    self.class, substance.cas_number
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the declaration into a compliance record for its component.
+    /// A substance declared in several materials is recorded once, at its
+    /// highest concentration; claimed exemptions are added as requests.
+    pub fn to_compliance_record(&self, is_pfas: impl Fn(&str) -> bool) -> ComplianceRecord {
+        let mut record = ComplianceRecord::new(self.supplier_id, self.component_id);
+        record.submission_date = self.issued_at;
+
+        let confidence = if self.declarer.is_signed() { SIGNED_CONFIDENCE } else { UNSIGNED_CONFIDENCE };
+        let source_document = self.source_document.clone().unwrap_or(DocumentReference {
+            document_id: self.id,
+            page: None,
+            section: None,
+            extraction_timestamp: self.issued_at,
+        });
+
+        let mut highest: Vec<&DeclaredSubstance> = Vec::new();
+        for substance in &self.substances {
+            match highest.iter_mut().find(|s| s.cas_number == substance.cas_number) {
+                Some(existing) if percent(substance) > percent(existing) => *existing = substance,
+                Some(_) => {}
+                None => highest.push(substance),
+            }
+        }
+        for substance in highest {
+            let mut cas_record = CASRecord::new(
+                substance.cas_number.clone(),
+                substance.name.clone(),
+                is_pfas(&substance.cas_number),
+                confidence,
+                source_document.clone(),
+                ExtractionMethod::MaterialDeclaration,
+            );
+            cas_record.concentration = substance.concentration.clone();
+            record.add_cas_record(cas_record);
+        }
+
+        let mut claimed = HashSet::new();
+        for substance in &self.substances {
+            let Some(code) = substance.exemption.as_deref() else {
+                continue;
+            };
+            if !claimed.insert((substance.cas_number.as_str(), code)) {
+                continue;
+            }
+            let mut exemption = Exemption::new(
+                self.component_id,
+                Some(substance.cas_number.clone()),
+                self.regulation.clone(),
+                ExemptionCategory::Other(code.to_string()),
+                format!("Claimed in {} declaration {}", self.format, self.id),
+            );
+            exemption.evidence = Some(source_document.clone());
+            record.add_exemption(exemption);
+        }
+        record
+    }
+
+    /// Builds a declaration from a compliance record. Records carry no
+    /// homogeneous materials, so only classes A and B can be emitted; the
+    /// part complies when no PFAS substance lacks an approved exemption.
+    pub fn from_compliance_record(
+        record: &ComplianceRecord,
+        format: DeclarationFormat,
+        class: DeclarationClass,
+        part_number: String,
+        regulation: String,
+        declarer: DeclarerSignature,
+    ) -> Result<Self, String> {
+        if class.per_homogeneous_material() {
+            return Err(format!("Class {:?} declarations need homogeneous materials, which compliance records do not carry", class));
+        }
+        let now = Utc::now();
+        let substances = record.cas_records.iter()
+            .map(|cas_record| DeclaredSubstance {
+                cas_number: cas_record.cas_number.clone(),
+                name: cas_record.chemical_name.clone(),
+                concentration: cas_record.concentration.clone(),
+                mass_mg: None,
+                material: None,
+                exemption: record.exemptions.iter()
+                    .find(|e| e.is_active(now) && e.covers(&cas_record.cas_number))
+                    .map(|e| exemption_code(&e.category)),
+            })
+            .collect();
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            format,
+            class,
+            supplier_id: record.supplier_id,
+            component_id: record.component_id,
+            part_number,
+            regulation,
+            complies: Some(record.unexempted_pfas_substances().is_empty()),
+            substances: if class.lists_substances() { substances } else { Vec::new() },
+            declarer,
+            issued_at: now,
+            source_document: None,
+        })
+    }
+}
+
+/// Concentration of a substance in percent, for comparing declarations of it
+fn percent(substance: &DeclaredSubstance) -> f64 {
+    substance.concentration.as_ref().map_or(0.0, |c| c.in_unit(ConcentrationUnit::Percent))
+}
+
+fn exemption_code(category: &ExemptionCategory) -> String {
+    match category {
+        ExemptionCategory::Other(code) => code.clone(),
+        category => format!("{:?}", category),
+    }
+}
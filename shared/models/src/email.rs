@@ -3,13 +3,13 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-use crate::{SupplierRecord, Component, TechnicalLevel};
+use crate::{SupplierRecord, Component, TechnicalLevel, SupplierId, WorkflowId};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct EmailCommunication {
     pub id: Uuid,
     pub thread_id: String,
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     pub direction: EmailDirection,
     pub subject: String,
     pub body: String,
@@ -18,6 +18,25 @@ pub struct EmailCommunication {
     pub received_at: Option<DateTime<Utc>>,
     pub delivery_status: DeliveryStatus,
     pub processing_status: EmailProcessingStatus,
+    /// RFC 5322 Message-ID, without angle brackets
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    /// Address an outbound email was sent to
+    pub recipient: Option<String>,
+    /// Category of an inbound supplier reply
+    pub classification: Option<ReplyClassification>,
+    pub classification_confidence: Option<f64>,
+    /// Provider hand-off attempts for an outbound email, oldest first
+    pub delivery_attempts: Vec<DeliveryAttempt>,
+    /// Hash of an inbound email's content, used to drop redelivered copies
+    pub content_hash: Option<String>,
+    pub smime: SmimeStatus,
+    /// Campaign an outbound email was sent for
+    pub campaign_id: Option<WorkflowId>,
+    pub template_id: Option<String>,
+    /// A/B test variant (`A` or `B`) the supplier was assigned
+    pub template_variant: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -34,9 +53,18 @@ pub struct EmailAttachment {
     pub file_type: String,
     pub file_size: i64,
     pub document_id: Option<Uuid>,
+    /// Document-processing progress: `pending`, `skipped`, `uploaded`, `extracted` or `failed`
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub cas_numbers_found: usize,
+    #[serde(default)]
+    pub needs_review: bool,
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DeliveryStatus {
     Pending,
     Sent,
@@ -46,6 +74,32 @@ pub enum DeliveryStatus {
     SpamFiltered,
 }
 
+/// One attempt to hand an outbound email to the delivery provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub attempt: u32,
+    pub attempted_at: DateTime<Utc>,
+    /// Provider that accepted the email, or the last one tried when none did
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub succeeded: bool,
+    /// Failure may clear up on retry, e.g. an SMTP 4xx reply
+    pub transient: bool,
+    pub error: Option<String>,
+    /// When the next attempt is due; `None` once the email is sent or given up on
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// S/MIME protection of an email as sent or received
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmimeStatus {
+    pub signed: bool,
+    pub encrypted: bool,
+    /// Whether an inbound signature chains to a trusted CA and matches the
+    /// sender; `None` for unsigned and outbound email
+    pub signature_valid: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EmailProcessingStatus {
     NotProcessed,
@@ -55,6 +109,49 @@ pub enum EmailProcessingStatus {
     Failed,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplyClassification {
+    CompleteResponse,
+    PartialResponse,
+    Question,
+    Refusal,
+    OutOfOffice,
+    WrongContact,
+}
+
+/// Do-not-contact entry covering a whole supplier or a single address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSuppression {
+    pub id: Uuid,
+    pub supplier_id: Option<SupplierId>,
+    /// Lowercased email address
+    pub email_address: Option<String>,
+    pub reason: String,
+    pub source: SuppressionSource,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuppressionSource {
+    Manual,
+    UnsubscribeLink,
+}
+
+/// A tenant's From address and DKIM signing key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantSenderDomain {
+    pub tenant_id: String,
+    pub from_email: String,
+    pub from_name: String,
+    pub dkim_selector: Option<String>,
+    /// `rsa` or `ed25519`
+    pub dkim_algorithm: Option<String>,
+    /// PKCS#1 PEM for RSA, base64 for Ed25519
+    #[serde(skip_serializing)]
+    pub dkim_private_key: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceRequest {
     pub supplier: SupplierRecord,
@@ -101,7 +198,7 @@ pub struct IncomingEmail {
     pub body: String,
     pub attachments: Vec<EmailAttachment>,
     pub received_at: DateTime<Utc>,
-    pub supplier_id: Option<Uuid>,
+    pub supplier_id: Option<SupplierId>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,7 +224,7 @@ pub enum EmailClassification {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FollowUpContext {
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     pub previous_emails: Vec<Uuid>,
     pub missing_information: Vec<String>,
     pub attempt_number: u32,
@@ -138,7 +235,7 @@ pub struct FollowUpContext {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationState {
     pub thread_id: String,
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     pub messages: Vec<Uuid>,
     pub current_status: ConversationStatus,
     pub last_activity: DateTime<Utc>,
@@ -160,7 +257,7 @@ impl Default for EmailCommunication {
         Self {
             id: Uuid::new_v4(),
             thread_id: String::new(),
-            supplier_id: Uuid::new_v4(),
+            supplier_id: SupplierId::new(),
             direction: EmailDirection::Outbound,
             subject: String::new(),
             body: String::new(),
@@ -169,6 +266,18 @@ impl Default for EmailCommunication {
             received_at: None,
             delivery_status: DeliveryStatus::Pending,
             processing_status: EmailProcessingStatus::NotProcessed,
+            message_id: None,
+            in_reply_to: None,
+            references: Vec::new(),
+            recipient: None,
+            classification: None,
+            classification_confidence: None,
+            delivery_attempts: Vec::new(),
+            content_hash: None,
+            smime: SmimeStatus::default(),
+            campaign_id: None,
+            template_id: None,
+            template_variant: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
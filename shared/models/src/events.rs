@@ -0,0 +1,255 @@
+//! Domain events exchanged between the Elementa services.
+//!
+//! A service publishes what happened as a `DomainEvent` wrapped in an
+//! `EventEnvelope`, which identifies the event, the tenant it happened for
+//! and the chain of events it belongs to. Consumers check the envelope's
+//! schema version before acting on it and ignore event types they have no
+//! use for.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{CertificationStatus, CertificationType, ReplyClassification, SchemaError, SuppressionSource};
+
+/// Version of the envelope and event shapes this build publishes
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A domain event with the metadata every consumer needs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    pub schema_version: u32,
+    pub occurred_at: DateTime<Utc>,
+    /// Tenant the event happened for, when the publisher knows it
+    #[serde(default)]
+    pub tenant: Option<String>,
+    /// Shared by every event that follows from the same original event
+    pub correlation_id: Uuid,
+    /// Event this one directly follows from
+    #[serde(default)]
+    pub causation_id: Option<Uuid>,
+    /// Service that published the event
+    pub source: String,
+    pub event: DomainEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum DomainEvent {
+    SupplierCreated(SupplierCreatedEvent),
+    EmailSent(EmailSentEvent),
+    EmailBounced(EmailBouncedEvent),
+    ReplyClassified(ReplyClassifiedEvent),
+    DocumentExtracted(DocumentExtractedEvent),
+    PfasDetected(PfasDetectedEvent),
+    SupplierSuppressed(SupplierSuppressedEvent),
+    SupplierUnresponsive(SupplierUnresponsiveEvent),
+    WorkflowEscalated(WorkflowEscalatedEvent),
+    CertificationExpiring(CertificationExpiringEvent),
+}
+
+/// A supplier was added to the supplier registry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SupplierCreatedEvent {
+    pub supplier_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An outbound email was handed to the provider
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmailSentEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    /// Workflow the email was sent for
+    pub campaign_id: Option<Uuid>,
+    pub template_id: Option<String>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// An outbound email could not be delivered
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmailBouncedEvent {
+    pub email_id: Option<Uuid>,
+    pub supplier_id: Option<Uuid>,
+    pub email_address: Option<String>,
+    /// Hard bounces; soft bounces may still be delivered on a later attempt
+    pub permanent: bool,
+    pub reason: String,
+    pub bounced_at: DateTime<Utc>,
+}
+
+/// An inbound supplier reply was classified
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplyClassifiedEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    pub classification: EventClassification,
+    pub document_count: usize,
+    pub received_at: DateTime<Utc>,
+    /// First day an out-of-office contact is back, when the auto-reply says
+    #[serde(default)]
+    pub return_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventClassification {
+    pub category: ReplyClassification,
+    pub confidence: f64,
+    /// How the reply was classified, e.g. `rules` or `llm`
+    pub method: String,
+}
+
+/// Documents from a reply attachment were extracted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocumentExtractedEvent {
+    pub email_id: Uuid,
+    pub supplier_id: Uuid,
+    pub campaign_id: Option<Uuid>,
+    pub document_ids: Vec<Uuid>,
+    pub cas_numbers_found: usize,
+    pub needs_review: bool,
+    pub extracted_at: DateTime<Utc>,
+}
+
+/// PFAS substances were found in a supplier's documents or records
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PfasDetectedEvent {
+    pub supplier_id: Uuid,
+    pub component_id: Option<Uuid>,
+    pub document_id: Option<Uuid>,
+    pub cas_numbers: Vec<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A supplier or one of its addresses was put on the suppression list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SupplierSuppressedEvent {
+    pub supplier_id: Uuid,
+    pub email_address: Option<String>,
+    pub reason: String,
+    pub source: SuppressionSource,
+    pub suppressed_at: DateTime<Utc>,
+}
+
+/// A supplier ignored the configured number of follow-ups
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SupplierUnresponsiveEvent {
+    pub supplier_id: Uuid,
+    pub thread_id: String,
+    pub campaign_id: Option<Uuid>,
+    pub unanswered_follow_ups: usize,
+    pub last_sent_at: DateTime<Utc>,
+}
+
+/// A workflow escalated a supplier for manual attention
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowEscalatedEvent {
+    pub workflow_id: Uuid,
+    pub supplier_id: Uuid,
+    pub reason: String,
+    pub severity: String,
+    pub escalated_at: DateTime<Utc>,
+}
+
+/// A supplier's certification is about to lapse or has lapsed and needs renewing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificationExpiringEvent {
+    pub supplier_id: Uuid,
+    pub component_id: Uuid,
+    pub compliance_record_id: Uuid,
+    /// Client to run renewal outreach for, when the publisher knows it
+    #[serde(default)]
+    pub client_id: Option<Uuid>,
+    pub certification_type: CertificationType,
+    pub certificate_number: String,
+    pub expiry_date: DateTime<Utc>,
+    pub status: CertificationStatus,
+}
+
+// Utility methods for DomainEvent
+impl DomainEvent {
+    /// Name of the event type, as it is tagged on the wire
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::SupplierCreated(_) => "supplier_created",
+            Self::EmailSent(_) => "email_sent",
+            Self::EmailBounced(_) => "email_bounced",
+            Self::ReplyClassified(_) => "reply_classified",
+            Self::DocumentExtracted(_) => "document_extracted",
+            Self::PfasDetected(_) => "pfas_detected",
+            Self::SupplierSuppressed(_) => "supplier_suppressed",
+            Self::SupplierUnresponsive(_) => "supplier_unresponsive",
+            Self::WorkflowEscalated(_) => "workflow_escalated",
+            Self::CertificationExpiring(_) => "certification_expiring",
+        }
+    }
+
+    /// Supplier the event is about, when it names one
+    pub fn supplier_id(&self) -> Option<Uuid> {
+        match self {
+            Self::SupplierCreated(e) => Some(e.supplier_id),
+            Self::EmailSent(e) => Some(e.supplier_id),
+            Self::EmailBounced(e) => e.supplier_id,
+            Self::ReplyClassified(e) => Some(e.supplier_id),
+            Self::DocumentExtracted(e) => Some(e.supplier_id),
+            Self::PfasDetected(e) => Some(e.supplier_id),
+            Self::SupplierSuppressed(e) => Some(e.supplier_id),
+            Self::SupplierUnresponsive(e) => Some(e.supplier_id),
+            Self::WorkflowEscalated(e) => Some(e.supplier_id),
+            Self::CertificationExpiring(e) => Some(e.supplier_id),
+        }
+    }
+}
+
+// Utility methods for EventEnvelope
+impl EventEnvelope {
+    /// Wraps an event that starts a new chain of events
+    pub fn new(source: impl Into<String>, event: DomainEvent) -> Self {
+        let id = Uuid::new_v4();
+        Self {
+            id,
+            schema_version: EVENT_SCHEMA_VERSION,
+            occurred_at: Utc::now(),
+            tenant: None,
+            correlation_id: id,
+            causation_id: None,
+            source: source.into(),
+            event,
+        }
+    }
+
+    /// Wraps an event that follows from `cause`, in the same chain and for the same tenant
+    pub fn caused_by(cause: &EventEnvelope, source: impl Into<String>, event: DomainEvent) -> Self {
+        Self {
+            tenant: cause.tenant.clone(),
+            correlation_id: cause.correlation_id,
+            causation_id: Some(cause.id),
+            ..Self::new(source, event)
+        }
+    }
+
+    pub fn with_tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        self.event.event_type()
+    }
+
+    /// Checks that the envelope was published in a version this build understands
+    pub fn check_version(&self) -> Result<(), SchemaError> {
+        if self.schema_version > EVENT_SCHEMA_VERSION {
+            return Err(SchemaError::Unsupported {
+                type_name: "EventEnvelope",
+                version: self.schema_version,
+                supported: EVENT_SCHEMA_VERSION,
+            });
+        }
+        Ok(())
+    }
+}
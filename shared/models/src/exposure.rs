@@ -0,0 +1,198 @@
+//! PFAS exposure of a supplier or component, computed from its compliance records.
+//!
+//! The dashboard and the report generators both show how much PFAS a
+//! supplier or component carries, which regulatory lists those substances
+//! are on and what reporting that triggers. `PfasExposureSummary` computes
+//! it once so they agree.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{ComplianceRecord, ComponentId, SupplierId, ThresholdVerdict};
+
+/// What an exposure summary covers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum ExposureScope {
+    Supplier(SupplierId),
+    Component(ComponentId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PfasExposureSummary {
+    pub scope: ExposureScope,
+    /// Compliance records the summary was computed from
+    pub records_considered: usize,
+    /// PFAS CAS records, counting a substance once per record it appears in
+    pub pfas_record_count: usize,
+    /// Of those, the ones an active exemption covers
+    pub exempted_record_count: usize,
+    pub pfas_cas_numbers: BTreeSet<String>,
+    /// Mean extraction confidence of the PFAS CAS records
+    pub average_confidence: Option<f64>,
+    /// Lowest extraction confidence among them, the one a reviewer should check first
+    pub lowest_confidence: Option<f64>,
+    /// Regulatory lists the PFAS substances are on, highest risk first
+    pub regulatory_listings: Vec<RegulatoryListing>,
+    /// Reporting the unexempted PFAS substances trigger, earliest deadline first
+    pub reporting_obligations: Vec<ReportingObligation>,
+}
+
+/// A regulatory list and the PFAS substances on it. Lists with more of the
+/// substances rank higher, then lists that report at lower concentrations.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegulatoryListing {
+    pub source: String,
+    pub list_name: String,
+    pub cas_numbers: BTreeSet<String>,
+    /// Lowest reporting threshold the list sets for them; `None` when any
+    /// concentration is reportable
+    pub strictest_threshold: Option<f64>,
+}
+
+/// A regulation the PFAS substances have to be reported under
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportingObligation {
+    pub regulation: String,
+    pub reporting_format: String,
+    /// Earliest deadline among the substances that trigger it
+    pub deadline: DateTime<Utc>,
+    /// Substances at or above the threshold, or reportable at any concentration
+    pub triggered_by: BTreeSet<String>,
+    /// Substances whose concentration is missing or cannot be compared with
+    /// the threshold, and so may still trigger it
+    pub undetermined: BTreeSet<String>,
+}
+
+// Utility methods for ReportingObligation
+impl ReportingObligation {
+    /// Whether some substance is known to trigger the obligation
+    pub fn is_triggered(&self) -> bool {
+        !self.triggered_by.is_empty()
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.deadline <= now
+    }
+}
+
+// Utility methods for PfasExposureSummary
+impl PfasExposureSummary {
+    /// Exposure of a supplier across all its compliance records in `records`
+    pub fn for_supplier(supplier_id: SupplierId, records: &[ComplianceRecord]) -> Self {
+        let records: Vec<_> = records.iter().filter(|r| r.supplier_id == supplier_id).collect();
+        Self::compute(ExposureScope::Supplier(supplier_id), &records)
+    }
+
+    /// Exposure of a component across the compliance records for it in `records`
+    pub fn for_component(component_id: ComponentId, records: &[ComplianceRecord]) -> Self {
+        let records: Vec<_> = records.iter().filter(|r| r.component_id == component_id).collect();
+        Self::compute(ExposureScope::Component(component_id), &records)
+    }
+
+    fn compute(scope: ExposureScope, records: &[&ComplianceRecord]) -> Self {
+        let mut summary = Self {
+            scope,
+            records_considered: records.len(),
+            pfas_record_count: 0,
+            exempted_record_count: 0,
+            pfas_cas_numbers: BTreeSet::new(),
+            average_confidence: None,
+            lowest_confidence: None,
+            regulatory_listings: Vec::new(),
+            reporting_obligations: Vec::new(),
+        };
+        let mut confidences = Vec::new();
+        let mut listings: BTreeMap<(String, String), RegulatoryListing> = BTreeMap::new();
+        let mut obligations: BTreeMap<String, ReportingObligation> = BTreeMap::new();
+
+        for record in records {
+            for cas in record.pfas_substances() {
+                summary.pfas_record_count += 1;
+                summary.pfas_cas_numbers.insert(cas.cas_number.clone());
+                confidences.push(cas.confidence);
+
+                for list in &cas.regulatory_status.regulatory_lists {
+                    let listing = listings.entry((list.source.clone(), list.list_name.clone()))
+                        .or_insert_with(|| RegulatoryListing {
+                            source: list.source.clone(),
+                            list_name: list.list_name.clone(),
+                            cas_numbers: BTreeSet::new(),
+                            strictest_threshold: list.reporting_threshold,
+                        });
+                    listing.cas_numbers.insert(cas.cas_number.clone());
+                    listing.strictest_threshold = match (listing.strictest_threshold, list.reporting_threshold) {
+                        (Some(current), Some(threshold)) => Some(current.min(threshold)),
+                        _ => None,
+                    };
+                }
+
+                // Exempted substances are listed but trigger no reporting
+                if record.is_exempt(&cas.cas_number) {
+                    summary.exempted_record_count += 1;
+                    continue;
+                }
+                for requirement in &cas.regulatory_status.reporting_requirements {
+                    let verdict = cas.evaluate_threshold(requirement);
+                    if verdict == ThresholdVerdict::Below {
+                        continue;
+                    }
+                    let obligation = obligations.entry(requirement.regulation.clone())
+                        .or_insert_with(|| ReportingObligation {
+                            regulation: requirement.regulation.clone(),
+                            reporting_format: requirement.reporting_format.clone(),
+                            deadline: requirement.deadline,
+                            triggered_by: BTreeSet::new(),
+                            undetermined: BTreeSet::new(),
+                        });
+                    obligation.deadline = obligation.deadline.min(requirement.deadline);
+                    match verdict {
+                        ThresholdVerdict::AtOrAbove | ThresholdVerdict::NoThreshold => {
+                            obligation.triggered_by.insert(cas.cas_number.clone());
+                        }
+                        ThresholdVerdict::Undetermined => {
+                            obligation.undetermined.insert(cas.cas_number.clone());
+                        }
+                        ThresholdVerdict::Below => {}
+                    }
+                }
+            }
+        }
+
+        // A substance triggering an obligation in one record settles it for the others
+        for obligation in obligations.values_mut() {
+            let triggered = obligation.triggered_by.clone();
+            obligation.undetermined.retain(|cas| !triggered.contains(cas));
+        }
+
+        if !confidences.is_empty() {
+            summary.average_confidence = Some(confidences.iter().sum::<f64>() / confidences.len() as f64);
+            summary.lowest_confidence = confidences.iter().copied().reduce(f64::min);
+        }
+
+        summary.regulatory_listings = listings.into_values().collect();
+        summary.regulatory_listings.sort_by(|a, b| {
+            b.cas_numbers.len().cmp(&a.cas_numbers.len())
+                .then_with(|| match (a.strictest_threshold, b.strictest_threshold) {
+                    (None, None) => std::cmp::Ordering::Equal,
+                    (None, Some(_)) => std::cmp::Ordering::Less,
+                    (Some(_), None) => std::cmp::Ordering::Greater,
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                })
+        });
+        summary.reporting_obligations = obligations.into_values().collect();
+        summary.reporting_obligations.sort_by_key(|o| o.deadline);
+        summary
+    }
+
+    pub fn has_pfas(&self) -> bool {
+        self.pfas_record_count > 0
+    }
+
+    /// Obligations some substance is known to trigger
+    pub fn triggered_obligations(&self) -> Vec<&ReportingObligation> {
+        self.reporting_obligations.iter().filter(|o| o.is_triggered()).collect()
+    }
+}
@@ -7,9 +7,22 @@
 //! 
 //! - **SupplierRecord**: Represents a supplier with contact information, compliance history, and risk profile
 //! - **Component**: Represents a component or part with CAS numbers and specifications
+//! - **ComponentTree**: Represents an assembly with its sub-assemblies and parts, built from `AssemblyLink`s
 //! - **ComplianceRecord**: Represents compliance data for a supplier-component pair
 //! - **CASRecord**: Represents a chemical substance with CAS number and PFAS classification
 //! - **ChemicalSubstance**: Represents detailed chemical information with regulatory status
+//! - **MaterialDeclaration**: Represents a supplier's IPC-1752A or chemSHERPA material declaration
+//! - **Questionnaire**: Represents structured questions a supplier answers in a `QuestionnaireResponse`
+//! 
+//! `ComplianceRecordBuilder` and `ChemicalSubstanceBuilder` build compliance
+//! records and chemical substances with their invariants checked.
+//! 
+//! `PfasExposureSummary` sums up the PFAS in a supplier's or component's
+//! compliance records, with the regulatory lists and reporting it triggers.
+//! 
+//! `ComplianceRecord::diff` and `merge_resubmission` compare a record with a
+//! supplier's resubmission and merge it under a `MergePolicy` (see the
+//! `resubmission` module).
 //! 
 //! ## Validation
 //! 
@@ -22,11 +35,35 @@
 //! ## Utility Methods
 //! 
 //! Models include utility methods for common operations:
-//! - Risk profile calculation
+//! - Risk profile calculation through a configurable `RiskScoringPolicy`
 //! - CAS number management
 //! - Validation status updates
 //! - PFAS classification handling
+//! 
+//! Suppliers and components carry a `LifecycleStatus`: they are archived or
+//! soft-deleted through the `Lifecycle` trait rather than removed, so the
+//! records that refer to them stay traceable.
+//! 
+//! Status enums implement `Localized`, whose `display_name(locale)` gives
+//! their label in English, German, French or Chinese (see the
+//! `localization` module).
+//! 
+//! ## Schema Versions
+//! 
+//! Models persisted as JSON implement `Versioned`, which upgrades rows written
+//! in an older shape before they are deserialized (see the `schema` module).
+//! 
+//! ## Domain Events
+//! 
+//! Services publish and consume `DomainEvent`s wrapped in a versioned
+//! `EventEnvelope` (see the `events` module).
+//! 
+//! ## BOM Column Mappings
+//! 
+//! A `ColumnMapping` tells the BOM parser which header holds each
+//! `BomField`; clients keep theirs as `SavedColumnMapping`s.
 
+pub mod ids;
 pub mod supplier;
 pub mod component;
 pub mod compliance;
@@ -35,20 +72,47 @@ pub mod workflow;
 pub mod audit;
 pub mod email;
 pub mod chemical;
+pub mod declaration;
+pub mod risk;
+pub mod builders;
+pub mod schema;
+pub mod events;
+pub mod resubmission;
+pub mod lifecycle;
+pub mod localization;
+pub mod exposure;
+pub mod questionnaire;
+pub mod column_mapping;
 
 #[cfg(test)]
 pub mod property_tests;
 
+pub use ids::{SupplierId, ComponentId, ComplianceRecordId, WorkflowId};
 pub use supplier::*;
 pub use component::*;
 pub use compliance::{
     ComplianceRecord, CASRecord, ExtractionMethod, TestResult, TestType,
-    Certification, CertificationType, ValidationStatus
+    Certification, CertificationType, CertificationStatus, CERTIFICATION_EXPIRY_WARNING_DAYS, ValidationStatus,
+    Concentration, ConcentrationUnit, ConcentrationBasis, ThresholdVerdict,
+    Exemption, ExemptionCategory, ExemptionApproval
 };
 pub use document::*;
 pub use workflow::*;
 pub use audit::*;
 pub use email::*;
+pub use declaration::*;
+pub use risk::*;
+pub use builders::{BuildError, ComplianceRecordBuilder, ChemicalSubstanceBuilder};
+pub use schema::{SchemaError, Versioned};
+pub use events::*;
+pub use lifecycle::{Lifecycle, LifecycleStatus};
+pub use localization::{Locale, Localized};
+pub use exposure::{ExposureScope, PfasExposureSummary, RegulatoryListing, ReportingObligation};
+pub use questionnaire::{
+    Answer, AnswerError, Question, QuestionCondition, QuestionKind, Questionnaire, QuestionnaireResponse,
+};
+pub use column_mapping::{BomField, ColumnMapping, SavedColumnMapping};
+pub use resubmission::{ComplianceRecordDiff, ItemChange, ItemDiff, MergePolicy, ResubmissionMerge};
 pub use chemical::{
     ChemicalSubstance, PFASClassification, ChemicalRestriction, RestrictionType,
     CASValidation, DatabaseUpdateResult,
@@ -62,6 +126,7 @@ mod tests {
     use super::*;
     use uuid::Uuid;
     use chrono::Utc;
+    use validator::Validate;
 
     #[test]
     fn test_supplier_creation() {
@@ -114,7 +179,7 @@ mod tests {
         
         // Add some compliance history
         supplier.add_compliance_history(ComplianceHistoryEntry {
-            campaign_id: Uuid::new_v4(),
+            campaign_id: WorkflowId::new(),
             status: ComplianceStatus::Complete,
             response_time_days: Some(2),
             completeness_score: 0.95,
@@ -122,7 +187,7 @@ mod tests {
         });
         
         supplier.add_compliance_history(ComplianceHistoryEntry {
-            campaign_id: Uuid::new_v4(),
+            campaign_id: WorkflowId::new(),
             status: ComplianceStatus::Complete,
             response_time_days: Some(3),
             completeness_score: 0.90,
@@ -135,12 +200,102 @@ mod tests {
         assert!(!supplier.is_high_risk());
     }
 
+    #[test]
+    fn test_supplier_merge_consolidates_duplicate() {
+        let shared_campaign = WorkflowId::new();
+        let entry = |campaign_id: WorkflowId, status: ComplianceStatus, days_ago: i64| ComplianceHistoryEntry {
+            campaign_id,
+            status,
+            response_time_days: Some(2),
+            completeness_score: 1.0,
+            last_updated: Utc::now() - chrono::Duration::days(days_ago),
+        };
+        let mut survivor = SupplierRecord::new("Acme".to_string(), "sales@acme.com".to_string(), String::new());
+        survivor.add_compliance_history(entry(shared_campaign, ComplianceStatus::InProgress, 10));
+        
+        let mut duplicate = SupplierRecord::new("ACME Corp".to_string(), "Compliance@Acme.com".to_string(), "Jo Park".to_string());
+        duplicate.contact_info.alternate_emails = vec!["SALES@acme.com".to_string()];
+        duplicate.communication_preferences.do_not_contact = true;
+        duplicate.add_compliance_history(entry(shared_campaign, ComplianceStatus::Complete, 1));
+        duplicate.add_compliance_history(entry(WorkflowId::new(), ComplianceStatus::Complete, 30));
+        
+        let changes = survivor.merge(&duplicate).unwrap();
+        assert_eq!(survivor.all_emails(), vec!["sales@acme.com", "Compliance@Acme.com"]);
+        assert_eq!(survivor.contact_info.contact_person, "Jo Park");
+        assert!(survivor.communication_preferences.do_not_contact);
+        
+        // The newer entry for the shared campaign wins
+        assert_eq!(survivor.compliance_history.len(), 2);
+        assert_eq!(survivor.compliance_history[0].status, ComplianceStatus::Complete);
+        assert_eq!(survivor.risk_profile.compliance_risk, RiskLevel::Low);
+        assert_eq!(changes.iter().filter(|c| c.field_name == "compliance_history").count(), 2);
+        assert_eq!(changes.len(), 5);
+        
+        assert!(survivor.merge(&survivor.clone()).is_err());
+    }
+
+    #[test]
+    fn test_risk_scoring_policy_weights_and_tenant_overrides() {
+        let entry = |status: ComplianceStatus, days: i32| ComplianceHistoryEntry {
+            campaign_id: WorkflowId::new(),
+            status,
+            response_time_days: Some(days),
+            completeness_score: 0.6,
+            last_updated: Utc::now(),
+        };
+        let history = vec![entry(ComplianceStatus::Complete, 2), entry(ComplianceStatus::NonCompliant, 8)];
+        let current = RiskProfile::default();
+        
+        // The default policy scores as update_risk_profile always has
+        let policy = RiskScoringPolicy::default();
+        let profile = policy.assess(&history, &RiskExposure::default(), &current, Utc::now()).unwrap();
+        assert_eq!((profile.compliance_risk.clone(), profile.response_reliability.clone()), (RiskLevel::High, RiskLevel::Medium));
+        assert_eq!(profile.data_quality, current.data_quality);
+        assert!((profile.overall_score - 0.55).abs() < 1e-9);
+        assert!(policy.assess(&[], &RiskExposure::default(), &current, Utc::now()).is_none());
+        
+        // One tenant also weighs PFAS exposure and data quality
+        let mut policies = RiskPolicies::default();
+        policies.tenants.insert("acme".to_string(), RiskPolicyOverride {
+            weights: Some(RiskWeights { pfas_exposure: 2.0, data_quality: 1.0, ..policy.weights }),
+            ..RiskPolicyOverride::default()
+        });
+        assert!(policies.check().is_ok());
+        let mut record = ComplianceRecord::new(SupplierId::new(), ComponentId::new());
+        record.add_cas_record(CASRecord::new(
+            "335-67-1".to_string(),
+            "PFOA".to_string(),
+            true,
+            0.9,
+            DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: None,
+                section: None,
+                extraction_timestamp: Utc::now(),
+            },
+            ExtractionMethod::ManualEntry,
+        ));
+        let acme = policies.for_tenant("acme");
+        let exposure = acme.exposure(&[record, ComplianceRecord::new(SupplierId::new(), ComponentId::new())], Utc::now());
+        assert_eq!(exposure, RiskExposure { pfas_share: Some(0.5), expiring_certification_share: None });
+        let profile = acme.assess(&history, &exposure, &current, Utc::now()).unwrap();
+        assert_eq!(profile.data_quality, RiskLevel::High);
+        assert!((profile.overall_score - (0.4 + 0.7 + 0.4 + 2.0 * 0.5) / 5.0).abs() < 1e-9);
+        assert_eq!(policies.for_tenant("other"), policy);
+        
+        policies.tenants.insert("broken".to_string(), RiskPolicyOverride {
+            weights: Some(RiskWeights { compliance: -1.0, ..policy.weights }),
+            ..RiskPolicyOverride::default()
+        });
+        assert!(policies.check().is_err());
+    }
+
     #[test]
     fn test_component_cas_number_management() {
         let mut component = Component::new(
             "PART-001".to_string(),
             "Test Component".to_string(),
-            Uuid::new_v4(),
+            SupplierId::new(),
         );
         
         // Add valid CAS number
@@ -157,9 +312,51 @@ mod tests {
         assert!(!component.has_cas_numbers());
     }
 
+    #[test]
+    fn test_component_tree_rolls_up_parts() {
+        let part = |number: &str, cas_numbers: &[&str]| Component {
+            part_number: number.to_string(),
+            cas_numbers: cas_numbers.iter().map(|c| c.to_string()).collect(),
+            ..Component::default()
+        };
+        let pump = part("PUMP-100", &[]);
+        let seal_kit = part("SEAL-KIT", &[]);
+        let gasket = part("GASKET-PTFE", &["9002-84-0"]);
+        let o_ring = part("ORING-FKM", &["9011-17-0"]);
+        let housing = part("HOUSING-AL", &["7429-90-5"]);
+        let links = vec![
+            AssemblyLink::new(pump.id, seal_kit.id, 2.0),
+            AssemblyLink::new(pump.id, housing.id, 1.0),
+            AssemblyLink::new(pump.id, o_ring.id, 1.0),
+            AssemblyLink::new(seal_kit.id, gasket.id, 1.0),
+            AssemblyLink::new(seal_kit.id, o_ring.id, 3.0),
+        ];
+        let components = vec![pump.clone(), seal_kit.clone(), gasket.clone(), o_ring.clone(), housing.clone()];
+        
+        let tree = ComponentTree::build(pump.id, &components, &links).unwrap();
+        assert_eq!(tree.depth(), 3);
+        assert_eq!(tree.cas_numbers().len(), 3);
+        
+        // O-rings: one directly plus three in each of the two seal kits
+        let parts = tree.flatten();
+        assert_eq!(parts.iter().find(|(id, _)| *id == o_ring.id).unwrap().1, 7.0);
+        assert_eq!(parts.iter().find(|(id, _)| *id == gasket.id).unwrap().1, 2.0);
+        
+        let pfas = ["9002-84-0", "9011-17-0"];
+        let roll_up = tree.pfas_roll_up(|cas| pfas.contains(&cas));
+        assert!(roll_up.contains_pfas);
+        assert_eq!(roll_up.pfas_components.len(), 2);
+        assert!(!ComponentTree::build(housing.id, &components, &links).unwrap().pfas_roll_up(|cas| pfas.contains(&cas)).contains_pfas);
+        
+        // An assembly cannot contain itself
+        let cyclic = [links.clone(), vec![AssemblyLink::new(gasket.id, pump.id, 1.0)]].concat();
+        assert!(ComponentTree::build(pump.id, &components, &cyclic).is_err());
+        assert!(ComponentTree::build(ComponentId::new(), &components, &links).is_err());
+    }
+
     #[test]
     fn test_compliance_record_validation_status() {
-        let mut record = ComplianceRecord::new(Uuid::new_v4(), Uuid::new_v4());
+        let mut record = ComplianceRecord::new(SupplierId::new(), ComponentId::new());
         
         // Initially pending
         assert_eq!(record.validation_status, ValidationStatus::Pending);
@@ -188,6 +385,201 @@ mod tests {
         assert_eq!(record.overall_confidence(), 0.95);
     }
 
+    #[test]
+    fn test_cas_record_threshold_evaluation() {
+        let mut record = CASRecord::new(
+            "335-67-1".to_string(),
+            "PFOA".to_string(),
+            true,
+            0.9,
+            DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: Some(2),
+                section: None,
+                extraction_timestamp: Utc::now(),
+            },
+            ExtractionMethod::ManualEntry,
+        );
+        let requirement = |threshold: Option<f64>, threshold_unit: Option<ConcentrationUnit>| compliance::ReportingRequirement {
+            regulation: "TSCA 8(a)(7)".to_string(),
+            deadline: Utc::now(),
+            threshold,
+            threshold_unit,
+            reporting_format: "CDX".to_string(),
+        };
+        let de_minimis = requirement(Some(0.1), None);
+        assert_eq!(record.evaluate_threshold(&de_minimis), ThresholdVerdict::Undetermined);
+        assert_eq!(record.evaluate_threshold(&requirement(None, None)), ThresholdVerdict::NoThreshold);
+        
+        // 1000 ppm is exactly 0.1% w/w
+        record.concentration = Some(Concentration::new(1000.0, ConcentrationUnit::Ppm, ConcentrationBasis::WeightByWeight));
+        assert_eq!(record.evaluate_threshold(&de_minimis), ThresholdVerdict::AtOrAbove);
+        assert_eq!(record.evaluate_threshold(&requirement(Some(25.0), Some(ConcentrationUnit::Ppb))), ThresholdVerdict::AtOrAbove);
+        assert_eq!(record.evaluate_threshold(&requirement(Some(2.0), Some(ConcentrationUnit::GPerKg))), ThresholdVerdict::Below);
+        assert!((ConcentrationUnit::MgPerKg.convert(500.0, ConcentrationUnit::Percent) - 0.05).abs() < 1e-12);
+        
+        record.concentration = Some(Concentration::new(5.0, ConcentrationUnit::Percent, ConcentrationBasis::VolumeByVolume));
+        assert_eq!(record.evaluate_threshold(&de_minimis), ThresholdVerdict::Undetermined);
+    }
+
+    #[test]
+    fn test_compliance_record_exemptions_affect_validation() {
+        let component_id = ComponentId::new();
+        let mut record = ComplianceRecord::new(SupplierId::new(), component_id);
+        record.add_cas_record(CASRecord::new(
+            "375-95-1".to_string(),
+            "PFNA".to_string(),
+            true,
+            0.5,
+            DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: None,
+                section: None,
+                extraction_timestamp: Utc::now(),
+            },
+            ExtractionMethod::VLMAutomatic,
+        ));
+        assert_eq!(record.validation_status, ValidationStatus::RequiresReview);
+        
+        // A requested exemption still needs review until it is approved
+        let exemption = Exemption::new(
+            component_id,
+            Some("375-95-1".to_string()),
+            "TSCA 8(a)(7)".to_string(),
+            ExemptionCategory::ResearchAndDevelopment,
+            "Used only in pilot-line trials".to_string(),
+        );
+        let exemption_id = exemption.id;
+        record.add_exemption(exemption);
+        assert_eq!(record.validation_status, ValidationStatus::RequiresReview);
+        
+        record.decide_exemption(exemption_id, ExemptionApproval::Approved, "compliance@elementa.io".to_string()).unwrap();
+        assert_eq!(record.validation_status, ValidationStatus::Valid);
+        assert!(record.is_exempt("375-95-1"));
+        assert!(record.unexempted_pfas_substances().is_empty());
+        assert!(record.decide_exemption(exemption_id, ExemptionApproval::Rejected, "compliance@elementa.io".to_string()).is_err());
+        
+        // Expired exemptions no longer cover the substance
+        record.exemptions[0].expires_at = Some(Utc::now() - chrono::Duration::days(1));
+        record.update_validation_status();
+        assert_eq!(record.validation_status, ValidationStatus::RequiresReview);
+        assert_eq!(record.unexempted_pfas_substances().len(), 1);
+    }
+
+    #[test]
+    fn test_versioned_records_upgrade_older_shapes() {
+        let mut record = ComplianceRecord::new(SupplierId::new(), ComponentId::new());
+        let mut cas_record = CASRecord::new(
+            "335-67-1".to_string(),
+            "PFOA".to_string(),
+            true,
+            0.9,
+            DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: None,
+                section: None,
+                extraction_timestamp: Utc::now(),
+            },
+            ExtractionMethod::ManualEntry,
+        );
+        cas_record.regulatory_status.reporting_requirements.push(compliance::ReportingRequirement {
+            regulation: "TSCA 8(a)(7)".to_string(),
+            deadline: Utc::now(),
+            threshold: Some(0.1),
+            threshold_unit: None,
+            reporting_format: "CDX".to_string(),
+        });
+        record.add_cas_record(cas_record);
+        
+        let current = record.to_versioned().unwrap();
+        assert_eq!(current[schema::SCHEMA_VERSION_KEY], 2);
+        
+        // A row from before exemptions, concentrations and threshold units upgrades to the same record
+        let mut legacy = current.clone();
+        let legacy_object = legacy.as_object_mut().unwrap();
+        legacy_object.remove(schema::SCHEMA_VERSION_KEY);
+        legacy_object.remove("exemptions");
+        let legacy_cas = legacy["cas_records"][0].as_object_mut().unwrap();
+        legacy_cas.remove("concentration");
+        legacy_cas["regulatory_status"]["reporting_requirements"][0].as_object_mut().unwrap().remove("threshold_unit");
+        assert_eq!(ComplianceRecord::from_versioned(legacy).unwrap(), record);
+        
+        // Rows from a newer build are refused rather than read with defaults
+        let mut newer = current;
+        newer[schema::SCHEMA_VERSION_KEY] = 3.into();
+        assert!(matches!(ComplianceRecord::from_versioned(newer), Err(SchemaError::Unsupported { version: 3, .. })));
+        
+        // A supplier's risk profile carries its own version
+        let supplier = SupplierRecord::new("Acme".to_string(), "compliance@acme.com".to_string(), "Jo".to_string());
+        let mut stored = supplier.to_versioned().unwrap();
+        assert_eq!(stored["risk_profile"][schema::SCHEMA_VERSION_KEY], 1);
+        stored[schema::SCHEMA_VERSION_KEY] = 1.into();
+        let preferences = stored["communication_preferences"].as_object_mut().unwrap();
+        preferences.remove("do_not_contact");
+        preferences.remove("contact_window");
+        assert_eq!(SupplierRecord::from_versioned(stored.clone()).unwrap(), supplier);
+        
+        // An unknown variant is an error, not a silent default
+        stored["relationship"] = "Legacy".into();
+        assert!(matches!(SupplierRecord::from_versioned(stored), Err(SchemaError::Deserialize { version: 1, .. })));
+    }
+
+    #[test]
+    fn test_material_declaration_round_trip_through_compliance_record() {
+        let material = |name: &str| Some(HomogeneousMaterial { name: name.to_string(), mass_mg: Some(120.0) });
+        let substance = |value: f64, material: Option<HomogeneousMaterial>| DeclaredSubstance {
+            cas_number: "9002-84-0".to_string(),
+            name: "PTFE".to_string(),
+            concentration: Some(Concentration::new(value, ConcentrationUnit::Percent, ConcentrationBasis::WeightByWeight)),
+            mass_mg: None,
+            material,
+            exemption: Some("6(c)".to_string()),
+        };
+        let declarer = DeclarerSignature {
+            company_name: "Acme Seals".to_string(),
+            contact_name: "Jo Park".to_string(),
+            title: None,
+            email: "compliance@acme.com".to_string(),
+            accepted_legal_terms: true,
+            signed_at: Some(Utc::now()),
+        };
+        let mut declaration = MaterialDeclaration {
+            id: Uuid::new_v4(),
+            format: DeclarationFormat::Ipc1752A,
+            class: DeclarationClass::D,
+            supplier_id: SupplierId::new(),
+            component_id: ComponentId::new(),
+            part_number: "SEAL-01".to_string(),
+            regulation: "EU RoHS".to_string(),
+            complies: Some(true),
+            substances: vec![substance(2.0, material("gasket")), substance(15.0, material("coating"))],
+            declarer: declarer.clone(),
+            issued_at: Utc::now(),
+            source_document: None,
+        };
+        assert!(declaration.validate().is_ok());
+        assert!(declaration.check_class().is_ok());
+        
+        // One record per substance at its highest concentration; the claimed exemption awaits a decision
+        let mut record = declaration.to_compliance_record(|cas| cas == "9002-84-0");
+        assert_eq!(record.cas_records.len(), 1);
+        assert_eq!(record.cas_records[0].concentration.as_ref().unwrap().value, 15.0);
+        assert_eq!(record.exemptions.len(), 1);
+        assert_eq!(record.validation_status, ValidationStatus::RequiresReview);
+        
+        let exemption_id = record.exemptions[0].id;
+        record.decide_exemption(exemption_id, ExemptionApproval::Approved, "compliance@elementa.io".to_string()).unwrap();
+        assert!(MaterialDeclaration::from_compliance_record(&record, DeclarationFormat::ChemSherpa, DeclarationClass::D, "SEAL-01".to_string(), "EU RoHS".to_string(), declarer.clone()).is_err());
+        let emitted = MaterialDeclaration::from_compliance_record(&record, DeclarationFormat::ChemSherpa, DeclarationClass::B, "SEAL-01".to_string(), "EU RoHS".to_string(), declarer).unwrap();
+        assert_eq!(emitted.complies, Some(true));
+        assert_eq!(emitted.substances[0].exemption.as_deref(), Some("6(c)"));
+        assert!(emitted.check_class().is_ok());
+        
+        // Full disclosure needs every substance in a homogeneous material
+        declaration.substances[0].material = None;
+        assert!(declaration.check_class().is_err());
+    }
+
     #[test]
     fn test_chemical_substance_creation() {
         let substance = ChemicalSubstance::new(
@@ -202,6 +594,65 @@ mod tests {
         assert!(substance.is_valid_cas());
     }
 
+    #[test]
+    fn test_builders_enforce_required_fields_and_invariants() {
+        let component_id = ComponentId::new();
+        let missing = ComplianceRecord::builder().component_id(component_id).build();
+        assert!(matches!(missing, Err(BuildError::Missing { field: "supplier_id", .. })));
+        
+        let exemption = Exemption::new(
+            ComponentId::new(),
+            None,
+            "TSCA 8(a)(7)".to_string(),
+            ExemptionCategory::Article,
+            "Imported article".to_string(),
+        );
+        let other_component = ComplianceRecord::builder()
+            .supplier_id(SupplierId::new())
+            .component_id(component_id)
+            .exemption(exemption)
+            .build();
+        assert!(matches!(other_component, Err(BuildError::Inconsistent { .. })));
+        
+        let record = ComplianceRecord::builder()
+            .supplier_id(SupplierId::new())
+            .component_id(component_id)
+            .cas_record(CASRecord::new(
+                "7732-18-5".to_string(),
+                "Water".to_string(),
+                false,
+                0.95,
+                DocumentReference {
+                    document_id: Uuid::new_v4(),
+                    page: None,
+                    section: None,
+                    extraction_timestamp: Utc::now(),
+                },
+                ExtractionMethod::ManualEntry,
+            ))
+            .build()
+            .unwrap();
+        assert_eq!(record.validation_status, ValidationStatus::Valid);
+        
+        // The classification decides the PFAS flag, and the check digit must hold
+        let substance = ChemicalSubstance::builder()
+            .cas_number("335-67-1")
+            .chemical_name("PFOA")
+            .pfas_classification(PFASClassification::new(true, 0.99, "OECD".to_string()))
+            .build()
+            .unwrap();
+        assert!(substance.is_pfas);
+        let conflicting = ChemicalSubstance::builder()
+            .cas_number("335-67-1")
+            .chemical_name("PFOA")
+            .is_pfas(false)
+            .pfas_classification(PFASClassification::new(true, 0.99, "OECD".to_string()))
+            .build();
+        assert!(matches!(conflicting, Err(BuildError::Inconsistent { .. })));
+        assert!(matches!(ChemicalSubstance::builder().cas_number("335-67-2").chemical_name("PFOA").build(), Err(BuildError::Inconsistent { .. })));
+        assert!(matches!(ChemicalSubstance::builder().cas_number("not-a-cas").chemical_name("PFOA").build(), Err(BuildError::Invalid { .. })));
+    }
+
     #[test]
     fn test_chemical_substance_invalid_cas() {
         let substance = ChemicalSubstance::new(
@@ -262,4 +713,551 @@ mod tests {
         // None is valid
         assert!(contact.set_phone(None).is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_event_envelope_round_trip_and_causation() {
+        let supplier_id = Uuid::new_v4();
+        let sent = EventEnvelope::new("email-communication", DomainEvent::EmailSent(EmailSentEvent {
+            email_id: Uuid::new_v4(),
+            supplier_id,
+            thread_id: "thread-1".to_string(),
+            campaign_id: None,
+            template_id: Some("initial_outreach".to_string()),
+            sent_at: Utc::now(),
+        }))
+        .with_tenant(Some("acme".to_string()));
+        assert_eq!(sent.correlation_id, sent.id);
+        assert_eq!(sent.schema_version, EVENT_SCHEMA_VERSION);
+
+        let json = serde_json::to_value(&sent).unwrap();
+        assert_eq!(json["event"]["type"], "email_sent");
+        assert_eq!(json["event"]["type"], sent.event_type());
+        let parsed: EventEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, sent);
+        assert_eq!(parsed.event.supplier_id(), Some(supplier_id));
+
+        let detected = EventEnvelope::caused_by(&sent, "document-processing", DomainEvent::PfasDetected(PfasDetectedEvent {
+            supplier_id,
+            component_id: None,
+            document_id: Some(Uuid::new_v4()),
+            cas_numbers: vec!["335-67-1".to_string()],
+            detected_at: Utc::now(),
+        }));
+        assert_eq!(detected.correlation_id, sent.id);
+        assert_eq!(detected.causation_id, Some(sent.id));
+        assert_eq!(detected.tenant.as_deref(), Some("acme"));
+        assert!(detected.check_version().is_ok());
+
+        let mut newer = detected.clone();
+        newer.schema_version = EVENT_SCHEMA_VERSION + 1;
+        assert!(matches!(newer.check_version(), Err(SchemaError::Unsupported { .. })));
+
+        let unknown = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "schema_version": 1,
+            "occurred_at": Utc::now(),
+            "correlation_id": Uuid::new_v4(),
+            "source": "test",
+            "event": { "type": "supplier_teleported", "data": {} }
+        });
+        assert!(serde_json::from_value::<EventEnvelope>(unknown).is_err());
+    }
+
+    #[test]
+    fn test_concentration_units_compare_and_evaluate_thresholds() {
+        assert_eq!("wt %".parse::<ConcentrationUnit>().unwrap(), ConcentrationUnit::Percent);
+        assert_eq!("mg/kg".parse::<ConcentrationUnit>().unwrap(), ConcentrationUnit::MgPerKg);
+        assert_eq!(" PPB ".parse::<ConcentrationUnit>().unwrap(), ConcentrationUnit::Ppb);
+        assert!("mg/L".parse::<ConcentrationUnit>().is_err());
+        assert_eq!(ConcentrationUnit::MgPerKg.to_string(), "mg/kg");
+
+        // Values in different units compare by what they stand for
+        let w = ConcentrationBasis::WeightByWeight;
+        let ppm = Concentration::new(1000.0, ConcentrationUnit::Ppm, w);
+        assert_eq!(ppm.compare(&Concentration::new(0.1, ConcentrationUnit::Percent, w)), Some(std::cmp::Ordering::Equal));
+        assert_eq!(ppm.compare(&Concentration::new(2.0, ConcentrationUnit::GPerKg, w)), Some(std::cmp::Ordering::Less));
+        assert_eq!(ppm.compare(&Concentration::new(0.1, ConcentrationUnit::Percent, ConcentrationBasis::WeightByVolume)), None);
+
+        // Lab results carry their unit as text
+        let test_result = |result_value: f64, unit: &str, detection_limit: Option<f64>| TestResult {
+            test_type: TestType::PFASConcentration,
+            result_value,
+            unit: unit.to_string(),
+            detection_limit,
+            test_method: "EPA 1633".to_string(),
+            test_date: Utc::now(),
+            laboratory: "Eurofins Lancaster".to_string(),
+            certificate_number: None,
+            source_document: DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: None,
+                section: None,
+                extraction_timestamp: Utc::now(),
+            },
+        };
+        let requirement = compliance::ReportingRequirement {
+            regulation: "EU POPs".to_string(),
+            deadline: Utc::now(),
+            threshold: Some(25.0),
+            threshold_unit: Some(ConcentrationUnit::Ppb),
+            reporting_format: "SCIP".to_string(),
+        };
+        assert_eq!(test_result(0.03, "mg/kg", None).evaluate_threshold(&requirement), ThresholdVerdict::AtOrAbove);
+        assert_eq!(test_result(0.02, "ppm", None).evaluate_threshold(&requirement), ThresholdVerdict::Below);
+        assert_eq!(test_result(3.0, "mg/L", None).evaluate_threshold(&requirement), ThresholdVerdict::Undetermined);
+        // A non-detect only clears thresholds above its detection limit
+        assert_eq!(test_result(0.0, "ppb", Some(10.0)).evaluate_threshold(&requirement), ThresholdVerdict::Below);
+        assert_eq!(test_result(0.0, "ppb", Some(50.0)).evaluate_threshold(&requirement), ThresholdVerdict::Undetermined);
+
+        // Chemical restrictions convert the concentration to their own unit
+        let mut substance = ChemicalSubstance::default();
+        substance.regulatory_status.restrictions.push(ChemicalRestriction {
+            regulation: "EU REACH Annex XVII".to_string(),
+            jurisdiction: "EU".to_string(),
+            restriction_type: RestrictionType::Restricted,
+            threshold: Some(25.0),
+            threshold_unit: Some(ConcentrationUnit::Ppb),
+            effective_date: Utc::now() - chrono::Duration::days(1),
+            description: "PFOA and its salts".to_string(),
+        });
+        assert_eq!(substance.restrictions_applying_at(&Concentration::new(0.1, ConcentrationUnit::MgPerKg, w)).len(), 1);
+        assert!(substance.restrictions_applying_at(&Concentration::new(0.01, ConcentrationUnit::MgPerKg, w)).is_empty());
+    }
+
+    #[test]
+    fn test_supplier_tiers_parents_and_compliance_rollup() {
+        let history = |status: ComplianceStatus, days_ago: i64| ComplianceHistoryEntry {
+            campaign_id: WorkflowId::new(),
+            status,
+            response_time_days: Some(3),
+            completeness_score: 1.0,
+            last_updated: Utc::now() - chrono::Duration::days(days_ago),
+        };
+        let mut parent = SupplierRecord::new("Acme Holdings".to_string(), "compliance@acme.com".to_string(), "Jo".to_string());
+        parent.compliance_history.push(history(ComplianceStatus::Complete, 1));
+        assert_eq!(parent.tier, SupplierTier::Tier1);
+        assert!(parent.set_parent(Some(parent.id)).is_err());
+
+        let mut plating = SupplierRecord::new("Acme Plating".to_string(), "quality@acme-plating.com".to_string(), "Sam".to_string());
+        plating.tier = SupplierTier::Tier2;
+        plating.set_parent(Some(parent.id)).unwrap();
+        // The latest campaign decides, not the order of the history
+        plating.compliance_history.push(history(ComplianceStatus::InProgress, 2));
+        plating.compliance_history.push(history(ComplianceStatus::NonCompliant, 30));
+        assert_eq!(plating.latest_compliance_status(), Some(&ComplianceStatus::InProgress));
+
+        let mut resins = SupplierRecord::new("Acme Resins".to_string(), "ehs@acme-resins.com".to_string(), "Ana".to_string());
+        resins.tier = SupplierTier::from_level(3).unwrap();
+        resins.set_parent(Some(plating.id)).unwrap();
+        assert_eq!(resins.tier.level(), 3);
+        assert!(SupplierTier::from_level(4).is_none());
+
+        let rollup = ComplianceRollup::from_group(&parent, &[plating.clone(), resins.clone()]);
+        assert_eq!((rollup.suppliers, rollup.complete, rollup.in_progress, rollup.not_started), (3, 1, 1, 1));
+        assert_eq!(rollup.status, ComplianceStatus::NotStarted);
+        assert!((rollup.compliance_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        resins.compliance_history.push(history(ComplianceStatus::Escalated, 0));
+        let rollup = ComplianceRollup::from_group(&parent, &[plating.clone(), resins.clone()]);
+        assert_eq!((rollup.non_compliant, rollup.status), (1, ComplianceStatus::Escalated));
+
+        // Merging away a subsidiary's parent hands the survivor the grandparent
+        let merge_changes = resins.merge(&plating).unwrap();
+        assert_eq!(resins.parent_id, Some(parent.id));
+        assert!(merge_changes.iter().any(|c| c.field_name == "parent_id"));
+
+        // Rows written before tiers upgrade to direct, independent suppliers
+        let mut stored = plating.to_versioned().unwrap();
+        stored[schema::SCHEMA_VERSION_KEY] = 2.into();
+        let stored_object = stored.as_object_mut().unwrap();
+        stored_object.remove("tier");
+        stored_object.remove("parent_id");
+        let upgraded = SupplierRecord::from_versioned(stored).unwrap();
+        assert_eq!((upgraded.tier, upgraded.parent_id), (SupplierTier::Tier1, None));
+    }
+
+    #[test]
+    fn test_compliance_resubmission_diff_and_merge_policies() {
+        let document = || DocumentReference {
+            document_id: Uuid::new_v4(),
+            page: Some(1),
+            section: None,
+            extraction_timestamp: Utc::now(),
+        };
+        let cas = |cas_number: &str, name: &str, confidence: f64| {
+            CASRecord::new(cas_number.to_string(), name.to_string(), false, confidence, document(), ExtractionMethod::VLMAutomatic)
+        };
+        let issued = Utc::now();
+        let certification = |certificate_number: &str| Certification {
+            certification_type: CertificationType::RoHS,
+            issuing_body: "TÜV SÜD".to_string(),
+            certificate_number: certificate_number.to_string(),
+            issue_date: issued,
+            expiry_date: None,
+            scope: "Connector housings".to_string(),
+            source_document: document(),
+            revoked_at: None,
+        };
+
+        let (supplier_id, component_id) = (SupplierId::new(), ComponentId::new());
+        let mut current = ComplianceRecord::new(supplier_id, component_id);
+        current.cas_records = vec![cas("7732-18-5", "Water", 0.95), cas("64-17-5", "Ethanol", 0.9)];
+        current.certifications = vec![certification("RH-001")];
+
+        // The resubmission re-extracts water, drops ethanol and adds a substance
+        let mut resubmitted = ComplianceRecord::new(supplier_id, component_id);
+        resubmitted.cas_records = vec![cas("7732-18-5", "Water", 0.7), cas("7440-50-8", "Copper", 0.92)];
+        resubmitted.certifications = vec![certification("RH-001")];
+
+        let diff = current.diff(&resubmitted);
+        assert_eq!(diff.cas_records.added.len(), 1);
+        assert_eq!(diff.cas_records.removed[0].cas_number, "64-17-5");
+        assert_eq!(diff.cas_records.changed[0].after.confidence, 0.7);
+        // A certification that only came from another document is unchanged
+        assert!(diff.certifications.is_empty() && diff.test_results.is_empty());
+        assert!(current.diff(&current).is_empty());
+
+        let mut kept = current.clone();
+        let merge = kept.merge_resubmission(&resubmitted, MergePolicy::KeepHighestConfidence, None);
+        let cas_numbers: Vec<_> = kept.cas_records.iter().map(|c| (c.cas_number.as_str(), c.confidence)).collect();
+        assert_eq!(cas_numbers, vec![("7732-18-5", 0.95), ("64-17-5", 0.9), ("7440-50-8", 0.92)]);
+        assert_eq!(merge.kept, 2);
+
+        let mut replaced = current.clone();
+        let merge = replaced.merge_resubmission(&resubmitted, MergePolicy::PreferNewest, None);
+        let cas_numbers: Vec<_> = replaced.cas_records.iter().map(|c| (c.cas_number.as_str(), c.confidence)).collect();
+        assert_eq!(cas_numbers, vec![("7732-18-5", 0.7), ("7440-50-8", 0.92)]);
+        assert_eq!(merge.kept, 0);
+
+        // The decision is recorded in the record's audit trail
+        let entry = replaced.audit_trail.last().unwrap();
+        assert_eq!(entry, &merge.audit_entry);
+        assert_eq!(entry.action, AuditAction::ComplianceRecordUpdated);
+        assert_eq!(entry.details.metadata["merge_policy"], "PreferNewest");
+        assert_eq!(entry.details.metadata["resubmission_id"], resubmitted.id.to_string());
+        let change_types: Vec<_> = entry.details.changes.iter().map(|c| c.change_type.clone()).collect();
+        assert_eq!(change_types, vec![ChangeType::Created, ChangeType::Updated, ChangeType::Deleted]);
+        assert_eq!(entry.details.changes[2].field_name, "cas_records[64-17-5]");
+    }
+
+    #[test]
+    fn test_certification_validity_follows_expiry_and_revocation() {
+        let now = Utc::now();
+        let warning = chrono::Duration::days(CERTIFICATION_EXPIRY_WARNING_DAYS);
+        let mut certification = Certification {
+            certification_type: CertificationType::PfasFree,
+            issuing_body: "SGS".to_string(),
+            certificate_number: "PF-0042".to_string(),
+            issue_date: now - chrono::Duration::days(300),
+            expiry_date: Some(now + chrono::Duration::days(90)),
+            scope: "Gaskets and seals".to_string(),
+            source_document: DocumentReference {
+                document_id: Uuid::new_v4(),
+                page: None,
+                section: None,
+                extraction_timestamp: now,
+            },
+            revoked_at: None,
+        };
+        assert_eq!(certification.status_at(now, warning), CertificationStatus::Valid);
+        assert_eq!(certification.status_at(now + chrono::Duration::days(45), warning), CertificationStatus::ExpiringSoon);
+        assert_eq!(certification.status_at(now + chrono::Duration::days(90), warning), CertificationStatus::Expired);
+        assert_eq!(certification.days_until_expiry(now), Some(90));
+        assert!(certification.is_valid_at(now + chrono::Duration::days(89)));
+
+        let mut record = ComplianceRecord::new(SupplierId::new(), ComponentId::new());
+        record.add_certification(certification.clone());
+        assert!(record.certifications_needing_renewal(now, warning).is_empty());
+        assert_eq!(record.certifications_needing_renewal(now + chrono::Duration::days(31), warning).len(), 1);
+
+        // Renewal moves the expiry on; it cannot end before the new issue date
+        assert!(certification.renew(now, Some(now - chrono::Duration::days(1))).is_err());
+        certification.renew(now, Some(now + chrono::Duration::days(365))).unwrap();
+        assert_eq!(certification.status_at(now + chrono::Duration::days(200), warning), CertificationStatus::Valid);
+
+        // Revocation holds whatever the expiry date and is final
+        certification.revoke(now).unwrap();
+        assert_eq!(certification.status_at(now, warning), CertificationStatus::Revoked);
+        assert!(!certification.is_valid_at(now));
+        assert!(certification.revoke(now).is_err());
+        assert!(certification.renew(now, None).is_err());
+        record.certifications = vec![certification];
+        assert!(record.certifications_needing_renewal(now + chrono::Duration::days(400), warning).is_empty());
+    }
+
+    #[test]
+    fn test_entity_ids_keep_the_uuid_wire_format() {
+        let uuid = Uuid::new_v4();
+        let supplier_id = SupplierId::from(uuid);
+        assert_eq!(supplier_id, uuid);
+        assert_eq!(Uuid::from(supplier_id), uuid);
+        assert_eq!(supplier_id.to_string(), uuid.to_string());
+        assert_eq!(uuid.to_string().parse::<SupplierId>().unwrap(), supplier_id);
+        assert!("not-a-uuid".parse::<ComponentId>().is_err());
+
+        // Serialized as the bare UUID, so stored records read back unchanged
+        let json = serde_json::to_string(&supplier_id).unwrap();
+        assert_eq!(json, serde_json::to_string(&uuid).unwrap());
+        let component_id: ComponentId = serde_json::from_str(&json).unwrap();
+        assert_eq!(component_id, uuid);
+
+        let record = ComplianceRecord::new(supplier_id, component_id);
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(value["supplier_id"], serde_json::json!(uuid.to_string()));
+        assert_eq!(value["id"], serde_json::json!(record.id.to_string()));
+    }
+
+    #[test]
+    fn test_lifecycle_archives_restores_and_soft_deletes() {
+        let now = Utc::now();
+        let mut supplier = SupplierRecord::new("Acme".to_string(), "compliance@acme.com".to_string(), "Jo".to_string());
+        assert!(supplier.is_active());
+
+        let change = supplier.archive(now).unwrap();
+        assert_eq!(supplier.lifecycle_status, LifecycleStatus::Archived);
+        assert_eq!((change.old_value.as_deref(), change.new_value.as_deref()), (Some("active"), Some("archived")));
+        assert!(supplier.archive(now).is_err());
+        supplier.restore(now).unwrap();
+        assert!(supplier.is_active());
+
+        let change = supplier.soft_delete(now).unwrap();
+        assert!(matches!(change.change_type, ChangeType::Deleted));
+        assert!(supplier.is_deleted());
+        assert_eq!(supplier.deleted_at, Some(now));
+
+        // Deletion is final
+        assert!(supplier.restore(now).is_err());
+        assert!(supplier.archive(now).is_err());
+
+        let mut component = Component::default();
+        component.soft_delete(now).unwrap();
+        let value = serde_json::to_value(&component).unwrap();
+        assert_eq!(value["lifecycle_status"], "deleted");
+
+        // Rows written before lifecycle status upgrade to active suppliers
+        let supplier = SupplierRecord::new("Acme".to_string(), "compliance@acme.com".to_string(), "Jo".to_string());
+        let mut stored = supplier.to_versioned().unwrap();
+        stored[schema::SCHEMA_VERSION_KEY] = 3.into();
+        let stored_object = stored.as_object_mut().unwrap();
+        stored_object.remove("lifecycle_status");
+        stored_object.remove("deleted_at");
+        let upgraded = SupplierRecord::from_versioned(stored).unwrap();
+        assert_eq!((upgraded.lifecycle_status, upgraded.deleted_at), (LifecycleStatus::Active, None));
+    }
+
+    #[test]
+    fn test_enum_display_names_are_translated() {
+        assert_eq!(ValidationStatus::RequiresReview.display_name(Locale::En), "Requires review");
+        assert_eq!(ValidationStatus::RequiresReview.display_name(Locale::De), "Prüfung erforderlich");
+        assert_eq!(RiskLevel::High.display_name(Locale::Fr), "Élevé");
+        assert_eq!(SupplierRelationship::NewVendor.display_name(Locale::Zh), "新供应商");
+
+        // Regional codes use the base language; unsupported languages get English
+        assert_eq!(Locale::from_language("de-AT"), Locale::De);
+        assert_eq!(Locale::from_language("ZH_cn"), Locale::Zh);
+        assert_eq!(Locale::from_language("pt"), Locale::En);
+        assert_eq!(Locale::from_language(""), Locale::En);
+
+        // Every label resolves in every locale
+        let keys: Vec<&str> = [
+            ValidationStatus::Pending, ValidationStatus::Valid, ValidationStatus::Invalid,
+            ValidationStatus::RequiresReview, ValidationStatus::Incomplete,
+        ].iter().map(|s| s.label_key())
+            .chain([RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical].iter().map(|r| r.label_key()))
+            .chain([
+                SupplierRelationship::Strategic, SupplierRelationship::Preferred, SupplierRelationship::Standard,
+                SupplierRelationship::NewVendor, SupplierRelationship::AtRisk,
+            ].iter().map(|r| r.label_key()))
+            .chain([SupplierTier::Tier1, SupplierTier::Tier2, SupplierTier::Tier3].iter().map(|t| t.label_key()))
+            .chain([
+                ComplianceStatus::NotStarted, ComplianceStatus::InProgress, ComplianceStatus::PartiallyComplete,
+                ComplianceStatus::Complete, ComplianceStatus::NonCompliant, ComplianceStatus::Escalated,
+            ].iter().map(|s| s.label_key()))
+            .chain([LifecycleStatus::Active, LifecycleStatus::Archived, LifecycleStatus::Deleted].iter().map(|s| s.label_key()))
+            .collect();
+        for locale in Locale::ALL {
+            for key in &keys {
+                assert_ne!(locale.label(key), *key, "{} has no {} label", locale.code(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pfas_exposure_summary_per_supplier_and_component() {
+        let now = Utc::now();
+        let source = DocumentReference {
+            document_id: Uuid::new_v4(),
+            page: None,
+            section: None,
+            extraction_timestamp: now,
+        };
+        let list = |source: &str, list_name: &str, reporting_threshold: Option<f64>| compliance::RegulatoryList {
+            source: source.to_string(),
+            list_name: list_name.to_string(),
+            date_added: now,
+            reporting_threshold,
+        };
+        let requirement = |regulation: &str, days: i64, threshold: Option<f64>| compliance::ReportingRequirement {
+            regulation: regulation.to_string(),
+            deadline: now + chrono::Duration::days(days),
+            threshold,
+            threshold_unit: Some(ConcentrationUnit::Ppm),
+            reporting_format: "CDX".to_string(),
+        };
+        let pfas = |cas_number: &str, confidence: f64, ppm: Option<f64>| {
+            let mut cas = CASRecord::new(
+                cas_number.to_string(),
+                "PFAS".to_string(),
+                true,
+                confidence,
+                source.clone(),
+                ExtractionMethod::VLMAutomatic,
+            );
+            cas.concentration = ppm.map(|v| Concentration::new(v, ConcentrationUnit::Ppm, ConcentrationBasis::WeightByWeight));
+            cas.regulatory_status.regulatory_lists.push(list("EPA", "TSCA PFAS", None));
+            cas.regulatory_status.reporting_requirements.push(requirement("TSCA 8(a)(7)", 200, None));
+            cas.regulatory_status.reporting_requirements.push(requirement("EU POPs", 90, Some(25.0)));
+            cas
+        };
+
+        let supplier_id = SupplierId::new();
+        let (gasket, seal) = (ComponentId::new(), ComponentId::new());
+        let mut gasket_record = ComplianceRecord::new(supplier_id, gasket);
+        let mut pfoa = pfas("335-67-1", 0.9, Some(40.0));
+        pfoa.regulatory_status.regulatory_lists.push(list("OECD", "PFAS Portal", Some(0.1)));
+        gasket_record.add_cas_record(pfoa);
+        gasket_record.add_cas_record(pfas("375-95-1", 0.6, Some(1.0)));
+        gasket_record.add_cas_record(CASRecord::new(
+            "7732-18-5".to_string(),
+            "Water".to_string(),
+            false,
+            1.0,
+            source.clone(),
+            ExtractionMethod::VLMAutomatic,
+        ));
+        let mut seal_record = ComplianceRecord::new(supplier_id, seal);
+        seal_record.add_cas_record(pfas("1763-23-1", 0.75, None));
+        let mut exemption = Exemption::new(
+            seal,
+            Some("1763-23-1".to_string()),
+            "TSCA 8(a)(7)".to_string(),
+            ExemptionCategory::Article,
+            "Imported article".to_string(),
+        );
+        exemption.decide(ExemptionApproval::Approved, "reviewer".to_string()).unwrap();
+        seal_record.add_exemption(exemption);
+        let other_supplier = ComplianceRecord::new(SupplierId::new(), gasket);
+        let records = vec![gasket_record, seal_record, other_supplier];
+
+        let summary = PfasExposureSummary::for_supplier(supplier_id, &records);
+        assert_eq!(summary.scope, ExposureScope::Supplier(supplier_id));
+        assert_eq!(summary.records_considered, 2);
+        assert_eq!((summary.pfas_record_count, summary.exempted_record_count), (3, 1));
+        assert_eq!(summary.pfas_cas_numbers.len(), 3);
+        assert!((summary.average_confidence.unwrap() - 0.75).abs() < 1e-9);
+        assert_eq!(summary.lowest_confidence, Some(0.6));
+
+        // The list with the most substances ranks first
+        let listings: Vec<&str> = summary.regulatory_listings.iter().map(|l| l.list_name.as_str()).collect();
+        assert_eq!(listings, ["TSCA PFAS", "PFAS Portal"]);
+        assert_eq!(summary.regulatory_listings[0].strictest_threshold, None);
+
+        // The exempted substance triggers nothing; the low one stays under the POPs threshold
+        let obligations: Vec<&str> = summary.reporting_obligations.iter().map(|o| o.regulation.as_str()).collect();
+        assert_eq!(obligations, ["EU POPs", "TSCA 8(a)(7)"]);
+        assert_eq!(summary.reporting_obligations[0].triggered_by, std::collections::BTreeSet::from(["335-67-1".to_string()]));
+        assert_eq!(summary.reporting_obligations[1].triggered_by.len(), 2);
+        assert!(!summary.reporting_obligations[0].is_overdue(now));
+        assert_eq!(summary.triggered_obligations().len(), 2);
+
+        // A substance without a concentration leaves threshold obligations undetermined
+        let component = PfasExposureSummary::for_component(seal, &records);
+        assert_eq!(component.scope, ExposureScope::Component(seal));
+        assert!(component.has_pfas());
+        assert!(component.reporting_obligations.is_empty());
+        let mut unmeasured = ComplianceRecord::new(supplier_id, seal);
+        unmeasured.add_cas_record(pfas("1763-23-1", 0.8, None));
+        let component = PfasExposureSummary::for_component(seal, &[unmeasured]);
+        let pops = component.reporting_obligations.iter().find(|o| o.regulation == "EU POPs").unwrap();
+        assert!(!pops.is_triggered());
+        assert_eq!(pops.undetermined.len(), 1);
+        assert!(!PfasExposureSummary::for_component(ComponentId::new(), &records).has_pfas());
+    }
+
+    #[test]
+    fn test_questionnaire_responses_are_checked_on_submit() {
+        let questionnaire = Questionnaire::new("PFAS declaration".to_string(), vec![
+            Question::new("intentional_pfas", "Do you intentionally add PFAS?", QuestionKind::YesNo, true),
+            Question::new("pfas_substances", "Which PFAS substances?", QuestionKind::CasNumbers, true)
+                .asked_when("intentional_pfas", Answer::YesNo(true)),
+            Question::new("pfas_share", "Share of PFAS by weight", QuestionKind::Number {
+                unit: Some("%".to_string()),
+                min: Some(0.0),
+                max: Some(100.0),
+            }, false),
+            Question::new("markets", "Where is the part sold?", QuestionKind::MultipleChoice {
+                options: vec!["EU".to_string(), "US".to_string()],
+            }, false),
+        ]);
+        assert!(questionnaire.check().is_ok());
+
+        let campaign_id = WorkflowId::new();
+        let mut response = questionnaire.respond(SupplierId::new()).for_campaign(campaign_id);
+        assert_eq!(response.campaign_id, Some(campaign_id));
+
+        // Drafts can be saved incomplete, but not submitted
+        response.answer("intentional_pfas", Answer::YesNo(true)).unwrap();
+        response.answer("pfas_share", Answer::Number(140.0)).unwrap();
+        response.answer("markets", Answer::Choice("EU".to_string())).unwrap();
+        let errors = response.submit(&questionnaire, Utc::now()).unwrap_err();
+        assert_eq!(errors, vec![
+            AnswerError::Missing { question_id: "pfas_substances".to_string() },
+            AnswerError::Invalid { question_id: "pfas_share".to_string(), message: "140 is out of range".to_string() },
+            AnswerError::WrongKind { question_id: "markets".to_string(), expected: "multiple choice" },
+        ]);
+        assert!(!response.is_submitted());
+
+        response.answer("pfas_substances", Answer::CasNumbers(vec!["PFOA".to_string()])).unwrap();
+        response.answer("pfas_share", Answer::Number(0.5)).unwrap();
+        response.answer("markets", Answer::Choices(vec!["EU".to_string()])).unwrap();
+        assert!(matches!(response.submit(&questionnaire, Utc::now()).unwrap_err()[..], [AnswerError::Invalid { .. }]));
+        response.answer("pfas_substances", Answer::CasNumbers(vec!["335-67-1".to_string()])).unwrap();
+        response.submit(&questionnaire, Utc::now()).unwrap();
+        assert!(response.is_submitted());
+        assert_eq!(response.cas_numbers(), ["335-67-1"]);
+        assert!(response.answer("pfas_share", Answer::Number(1.0)).is_err());
+        let record_id = ComplianceRecordId::new();
+        response.link_compliance_record(record_id);
+        assert_eq!(response.compliance_record_id, Some(record_id));
+
+        // Follow-up questions are only answered when asked
+        let mut response = questionnaire.respond(SupplierId::new());
+        response.answer("intentional_pfas", Answer::YesNo(false)).unwrap();
+        response.answer("pfas_substances", Answer::CasNumbers(vec!["335-67-1".to_string()])).unwrap();
+        response.answer("colour", Answer::Text("red".to_string())).unwrap();
+        let errors = questionnaire.check_response(&response.answers).unwrap_err();
+        assert_eq!(errors, vec![
+            AnswerError::UnknownQuestion { question_id: "colour".to_string() },
+            AnswerError::NotAsked { question_id: "pfas_substances".to_string() },
+        ]);
+
+        // A response answers the version of the questionnaire it was started from
+        let mut revised = questionnaire.clone();
+        revised.version += 1;
+        let mut response = questionnaire.respond(SupplierId::new());
+        response.answer("intentional_pfas", Answer::YesNo(false)).unwrap();
+        assert!(matches!(response.submit(&revised, Utc::now()).unwrap_err()[..], [AnswerError::WrongQuestionnaire { version: 1, .. }]));
+
+        // Answers serialize with their kind
+        let json = serde_json::to_value(Answer::YesNo(true)).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "yes_no", "value": true }));
+
+        // Conditions must refer to an earlier question
+        let broken = Questionnaire::new("Broken".to_string(), vec![
+            Question::new("details", "Details", QuestionKind::Text { max_length: None }, true)
+                .asked_when("intentional_pfas", Answer::YesNo(true)),
+            Question::new("intentional_pfas", "Do you intentionally add PFAS?", QuestionKind::YesNo, true),
+        ]);
+        assert!(broken.check().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Lifecycle of suppliers and components.
+//!
+//! Suppliers and components are archived or soft-deleted rather than removed,
+//! so the compliance records, emails and audit entries that refer to them
+//! keep pointing at something. Deleted records are left out of normal
+//! queries but stay in storage; deletion cannot be undone.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{ChangeType, Component, FieldChange, SupplierRecord};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStatus {
+    #[default]
+    Active,
+    /// No longer in use but kept visible, e.g. a supplier we stopped buying from
+    Archived,
+    /// Hidden from normal queries and kept only for audit and traceability
+    Deleted,
+}
+
+// Utility methods for LifecycleStatus
+impl LifecycleStatus {
+    /// Name of the status as stored and serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Archived => "archived",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    pub fn can_transition_to(&self, to: LifecycleStatus) -> bool {
+        *self != to && *self != Self::Deleted
+    }
+}
+
+/// A record that is archived or soft-deleted instead of being removed
+pub trait Lifecycle {
+    fn lifecycle_status(&self) -> LifecycleStatus;
+
+    /// Stores a status change; callers go through `transition_to`, which checks it
+    fn set_lifecycle_status(&mut self, status: LifecycleStatus, at: DateTime<Utc>);
+
+    fn is_active(&self) -> bool {
+        self.lifecycle_status() == LifecycleStatus::Active
+    }
+
+    fn is_deleted(&self) -> bool {
+        self.lifecycle_status() == LifecycleStatus::Deleted
+    }
+
+    /// Moves the record to `status`, returning the change for the audit trail
+    fn transition_to(&mut self, status: LifecycleStatus, at: DateTime<Utc>) -> Result<FieldChange, String> {
+        let current = self.lifecycle_status();
+        if !current.can_transition_to(status) {
+            return Err(format!("Cannot change lifecycle status from {} to {}", current.as_str(), status.as_str()));
+        }
+        self.set_lifecycle_status(status, at);
+        Ok(FieldChange {
+            field_name: "lifecycle_status".to_string(),
+            old_value: Some(current.as_str().to_string()),
+            new_value: Some(status.as_str().to_string()),
+            change_type: if status == LifecycleStatus::Deleted { ChangeType::Deleted } else { ChangeType::Updated },
+        })
+    }
+
+    fn archive(&mut self, at: DateTime<Utc>) -> Result<FieldChange, String> {
+        self.transition_to(LifecycleStatus::Archived, at)
+    }
+
+    /// Brings an archived record back into use
+    fn restore(&mut self, at: DateTime<Utc>) -> Result<FieldChange, String> {
+        self.transition_to(LifecycleStatus::Active, at)
+    }
+
+    fn soft_delete(&mut self, at: DateTime<Utc>) -> Result<FieldChange, String> {
+        self.transition_to(LifecycleStatus::Deleted, at)
+    }
+}
+
+impl Lifecycle for SupplierRecord {
+    fn lifecycle_status(&self) -> LifecycleStatus {
+        self.lifecycle_status
+    }
+
+    fn set_lifecycle_status(&mut self, status: LifecycleStatus, at: DateTime<Utc>) {
+        self.lifecycle_status = status;
+        self.deleted_at = (status == LifecycleStatus::Deleted).then_some(at);
+        self.updated_at = at;
+    }
+}
+
+impl Lifecycle for Component {
+    fn lifecycle_status(&self) -> LifecycleStatus {
+        self.lifecycle_status
+    }
+
+    fn set_lifecycle_status(&mut self, status: LifecycleStatus, at: DateTime<Utc>) {
+        self.lifecycle_status = status;
+        self.deleted_at = (status == LifecycleStatus::Deleted).then_some(at);
+        self.updated_at = at;
+    }
+}
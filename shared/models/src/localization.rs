@@ -0,0 +1,258 @@
+//! Translated display names for model enums.
+//!
+//! Each supported locale has a bundle mapping label keys such as
+//! `risk_level.high` to the text shown in the dashboard, supplier portal and
+//! reports. Enums implement `Localized` to name their key; a key missing from
+//! a bundle falls back to English.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ComplianceStatus, LifecycleStatus, RiskLevel, SupplierRelationship, SupplierTier, ValidationStatus,
+};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+    Zh,
+}
+
+// Utility methods for Locale
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::De, Locale::Fr, Locale::Zh];
+
+    /// ISO 639-1 language code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Zh => "zh",
+        }
+    }
+
+    /// Locale for a language code such as a supplier's preferred language;
+    /// regional codes like `de-AT` match the base language, and unsupported
+    /// languages get English
+    pub fn from_language(language: &str) -> Self {
+        let base = language.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        Self::ALL.into_iter()
+            .find(|locale| locale.code() == base)
+            .unwrap_or_default()
+    }
+
+    fn bundle(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::En => EN,
+            Self::De => DE,
+            Self::Fr => FR,
+            Self::Zh => ZH,
+        }
+    }
+
+    /// Text for a label key, falling back to English and then to the key itself
+    pub fn label(&self, key: &'static str) -> &'static str {
+        let find = |bundle: &'static [(&'static str, &'static str)]| {
+            bundle.iter().find(|(k, _)| *k == key).map(|(_, label)| *label)
+        };
+        find(self.bundle()).or_else(|| find(EN)).unwrap_or(key)
+    }
+}
+
+/// An enum with a translated, human-readable name
+pub trait Localized {
+    /// Key of the value's label in the locale bundles
+    fn label_key(&self) -> &'static str;
+
+    fn display_name(&self, locale: Locale) -> &'static str {
+        locale.label(self.label_key())
+    }
+}
+
+impl Localized for ValidationStatus {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::Pending => "validation_status.pending",
+            Self::Valid => "validation_status.valid",
+            Self::Invalid => "validation_status.invalid",
+            Self::RequiresReview => "validation_status.requires_review",
+            Self::Incomplete => "validation_status.incomplete",
+        }
+    }
+}
+
+impl Localized for RiskLevel {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::Low => "risk_level.low",
+            Self::Medium => "risk_level.medium",
+            Self::High => "risk_level.high",
+            Self::Critical => "risk_level.critical",
+        }
+    }
+}
+
+impl Localized for SupplierRelationship {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::Strategic => "supplier_relationship.strategic",
+            Self::Preferred => "supplier_relationship.preferred",
+            Self::Standard => "supplier_relationship.standard",
+            Self::NewVendor => "supplier_relationship.new_vendor",
+            Self::AtRisk => "supplier_relationship.at_risk",
+        }
+    }
+}
+
+impl Localized for SupplierTier {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::Tier1 => "supplier_tier.tier1",
+            Self::Tier2 => "supplier_tier.tier2",
+            Self::Tier3 => "supplier_tier.tier3",
+        }
+    }
+}
+
+impl Localized for ComplianceStatus {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::NotStarted => "compliance_status.not_started",
+            Self::InProgress => "compliance_status.in_progress",
+            Self::PartiallyComplete => "compliance_status.partially_complete",
+            Self::Complete => "compliance_status.complete",
+            Self::NonCompliant => "compliance_status.non_compliant",
+            Self::Escalated => "compliance_status.escalated",
+        }
+    }
+}
+
+impl Localized for LifecycleStatus {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Self::Active => "lifecycle_status.active",
+            Self::Archived => "lifecycle_status.archived",
+            Self::Deleted => "lifecycle_status.deleted",
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("validation_status.pending", "Pending"),
+    ("validation_status.valid", "Valid"),
+    ("validation_status.invalid", "Invalid"),
+    ("validation_status.requires_review", "Requires review"),
+    ("validation_status.incomplete", "Incomplete"),
+    ("risk_level.low", "Low"),
+    ("risk_level.medium", "Medium"),
+    ("risk_level.high", "High"),
+    ("risk_level.critical", "Critical"),
+    ("supplier_relationship.strategic", "Strategic"),
+    ("supplier_relationship.preferred", "Preferred"),
+    ("supplier_relationship.standard", "Standard"),
+    ("supplier_relationship.new_vendor", "New vendor"),
+    ("supplier_relationship.at_risk", "At risk"),
+    ("supplier_tier.tier1", "Tier 1"),
+    ("supplier_tier.tier2", "Tier 2"),
+    ("supplier_tier.tier3", "Tier 3"),
+    ("compliance_status.not_started", "Not started"),
+    ("compliance_status.in_progress", "In progress"),
+    ("compliance_status.partially_complete", "Partially complete"),
+    ("compliance_status.complete", "Complete"),
+    ("compliance_status.non_compliant", "Non-compliant"),
+    ("compliance_status.escalated", "Escalated"),
+    ("lifecycle_status.active", "Active"),
+    ("lifecycle_status.archived", "Archived"),
+    ("lifecycle_status.deleted", "Deleted"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("validation_status.pending", "Ausstehend"),
+    ("validation_status.valid", "Gültig"),
+    ("validation_status.invalid", "Ungültig"),
+    ("validation_status.requires_review", "Prüfung erforderlich"),
+    ("validation_status.incomplete", "Unvollständig"),
+    ("risk_level.low", "Niedrig"),
+    ("risk_level.medium", "Mittel"),
+    ("risk_level.high", "Hoch"),
+    ("risk_level.critical", "Kritisch"),
+    ("supplier_relationship.strategic", "Strategisch"),
+    ("supplier_relationship.preferred", "Bevorzugt"),
+    ("supplier_relationship.standard", "Standard"),
+    ("supplier_relationship.new_vendor", "Neuer Lieferant"),
+    ("supplier_relationship.at_risk", "Gefährdet"),
+    ("supplier_tier.tier1", "Stufe 1"),
+    ("supplier_tier.tier2", "Stufe 2"),
+    ("supplier_tier.tier3", "Stufe 3"),
+    ("compliance_status.not_started", "Nicht begonnen"),
+    ("compliance_status.in_progress", "In Bearbeitung"),
+    ("compliance_status.partially_complete", "Teilweise abgeschlossen"),
+    ("compliance_status.complete", "Abgeschlossen"),
+    ("compliance_status.non_compliant", "Nicht konform"),
+    ("compliance_status.escalated", "Eskaliert"),
+    ("lifecycle_status.active", "Aktiv"),
+    ("lifecycle_status.archived", "Archiviert"),
+    ("lifecycle_status.deleted", "Gelöscht"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("validation_status.pending", "En attente"),
+    ("validation_status.valid", "Valide"),
+    ("validation_status.invalid", "Non valide"),
+    ("validation_status.requires_review", "Révision requise"),
+    ("validation_status.incomplete", "Incomplet"),
+    ("risk_level.low", "Faible"),
+    ("risk_level.medium", "Moyen"),
+    ("risk_level.high", "Élevé"),
+    ("risk_level.critical", "Critique"),
+    ("supplier_relationship.strategic", "Stratégique"),
+    ("supplier_relationship.preferred", "Privilégié"),
+    ("supplier_relationship.standard", "Standard"),
+    ("supplier_relationship.new_vendor", "Nouveau fournisseur"),
+    ("supplier_relationship.at_risk", "À risque"),
+    ("supplier_tier.tier1", "Rang 1"),
+    ("supplier_tier.tier2", "Rang 2"),
+    ("supplier_tier.tier3", "Rang 3"),
+    ("compliance_status.not_started", "Non commencé"),
+    ("compliance_status.in_progress", "En cours"),
+    ("compliance_status.partially_complete", "Partiellement terminé"),
+    ("compliance_status.complete", "Terminé"),
+    ("compliance_status.non_compliant", "Non conforme"),
+    ("compliance_status.escalated", "Escaladé"),
+    ("lifecycle_status.active", "Actif"),
+    ("lifecycle_status.archived", "Archivé"),
+    ("lifecycle_status.deleted", "Supprimé"),
+];
+
+const ZH: &[(&str, &str)] = &[
+    ("validation_status.pending", "待处理"),
+    ("validation_status.valid", "有效"),
+    ("validation_status.invalid", "无效"),
+    ("validation_status.requires_review", "需要审核"),
+    ("validation_status.incomplete", "不完整"),
+    ("risk_level.low", "低"),
+    ("risk_level.medium", "中"),
+    ("risk_level.high", "高"),
+    ("risk_level.critical", "严重"),
+    ("supplier_relationship.strategic", "战略供应商"),
+    ("supplier_relationship.preferred", "优选供应商"),
+    ("supplier_relationship.standard", "标准供应商"),
+    ("supplier_relationship.new_vendor", "新供应商"),
+    ("supplier_relationship.at_risk", "风险供应商"),
+    ("supplier_tier.tier1", "一级"),
+    ("supplier_tier.tier2", "二级"),
+    ("supplier_tier.tier3", "三级"),
+    ("compliance_status.not_started", "未开始"),
+    ("compliance_status.in_progress", "进行中"),
+    ("compliance_status.partially_complete", "部分完成"),
+    ("compliance_status.complete", "已完成"),
+    ("compliance_status.non_compliant", "不合规"),
+    ("compliance_status.escalated", "已升级"),
+    ("lifecycle_status.active", "启用"),
+    ("lifecycle_status.archived", "已归档"),
+    ("lifecycle_status.deleted", "已删除"),
+];
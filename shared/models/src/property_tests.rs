@@ -10,12 +10,13 @@ use chrono::{DateTime, Utc, TimeZone};
 use uuid::Uuid;
 
 use crate::{
-    SupplierRecord, ContactInfo, Address, SupplierRelationship, ComplianceHistoryEntry,
+    SupplierRecord, ContactInfo, Address, SupplierRelationship, SupplierTier, ComplianceHistoryEntry,
     ComplianceStatus, CommunicationPreferences, TechnicalLevel, ResponseFormat, RiskProfile,
     RiskLevel, Component, ComponentSpecifications, Dimensions, MaterialType,
     ComplianceRecord, CASRecord, ExtractionMethod, TestResult, TestType, Certification, CertificationType,
     ValidationStatus, DocumentReference, AuditEntry, AuditAction,
-    AuditDetails,
+    AuditDetails, Concentration, ConcentrationUnit, ConcentrationBasis,
+    Exemption, ExemptionCategory, ExemptionApproval, LifecycleStatus,
 };
 
 // Import the correct regulatory types from compliance module
@@ -119,7 +120,7 @@ prop_compose! {
         last_updated in arb_datetime()
     ) -> ComplianceHistoryEntry {
         ComplianceHistoryEntry {
-            campaign_id,
+            campaign_id: campaign_id.into(),
             status,
             response_time_days,
             completeness_score,
@@ -150,6 +151,8 @@ prop_compose! {
             technical_level,
             response_format,
             follow_up_frequency_days,
+            do_not_contact: false,
+            contact_window: None,
         }
     }
 }
@@ -187,6 +190,14 @@ prop_compose! {
     }
 }
 
+fn arb_lifecycle_status() -> impl Strategy<Value = LifecycleStatus> {
+    prop_oneof![
+        Just(LifecycleStatus::Active),
+        Just(LifecycleStatus::Archived),
+        Just(LifecycleStatus::Deleted),
+    ]
+}
+
 prop_compose! {
     fn arb_supplier_record()(
         id in arb_uuid(),
@@ -199,20 +210,32 @@ prop_compose! {
             Just(SupplierRelationship::NewVendor),
             Just(SupplierRelationship::AtRisk),
         ],
+        tier in prop_oneof![
+            Just(SupplierTier::Tier1),
+            Just(SupplierTier::Tier2),
+            Just(SupplierTier::Tier3),
+        ],
+        parent_id in option::of(arb_uuid()),
         compliance_history in prop::collection::vec(arb_compliance_history_entry(), 0..5),
         communication_preferences in arb_communication_preferences(),
         risk_profile in arb_risk_profile(),
+        lifecycle_status in arb_lifecycle_status(),
+        deleted_at in option::of(arb_datetime()),
         created_at in arb_datetime(),
         updated_at in arb_datetime()
     ) -> SupplierRecord {
         SupplierRecord {
-            id,
+            id: id.into(),
             name,
             contact_info,
             relationship,
+            tier,
+            parent_id: parent_id.map(Into::into),
             compliance_history,
             communication_preferences,
             risk_profile,
+            lifecycle_status,
+            deleted_at,
             created_at,
             updated_at,
         }
@@ -273,17 +296,21 @@ prop_compose! {
         ],
         supplier_id in arb_uuid(),
         specifications in arb_component_specifications(),
+        lifecycle_status in arb_lifecycle_status(),
+        deleted_at in option::of(arb_datetime()),
         created_at in arb_datetime(),
         updated_at in arb_datetime()
     ) -> Component {
         Component {
-            id,
+            id: id.into(),
             part_number,
             description,
             cas_numbers,
             material_type,
-            supplier_id,
+            supplier_id: supplier_id.into(),
             specifications,
+            lifecycle_status,
+            deleted_at,
             created_at,
             updated_at,
         }
@@ -327,17 +354,43 @@ prop_compose! {
         regulation in "[A-Z]{2,20}",
         deadline in arb_datetime(),
         threshold in option::of(0.001..1000.0f64),
+        threshold_unit in option::of(arb_concentration_unit()),
         reporting_format in "[A-Z]{2,20}"
     ) -> ReportingRequirement {
         ReportingRequirement {
             regulation,
             deadline,
             threshold,
+            threshold_unit,
             reporting_format,
         }
     }
 }
 
+fn arb_concentration_unit() -> impl Strategy<Value = ConcentrationUnit> {
+    prop_oneof![
+        Just(ConcentrationUnit::Percent),
+        Just(ConcentrationUnit::Ppm),
+        Just(ConcentrationUnit::Ppb),
+        Just(ConcentrationUnit::MgPerKg),
+        Just(ConcentrationUnit::GPerKg),
+    ]
+}
+
+prop_compose! {
+    fn arb_concentration()(
+        value in 0.0..1000.0f64,
+        unit in arb_concentration_unit(),
+        basis in prop_oneof![
+            Just(ConcentrationBasis::WeightByWeight),
+            Just(ConcentrationBasis::WeightByVolume),
+            Just(ConcentrationBasis::VolumeByVolume),
+        ]
+    ) -> Concentration {
+        Concentration { value, unit, basis }
+    }
+}
+
 prop_compose! {
     fn arb_regulatory_status()(
         regulatory_lists in prop::collection::vec(arb_regulatory_list(), 0..3),
@@ -358,6 +411,7 @@ prop_compose! {
         chemical_name in "[A-Za-z0-9 ]{5,50}",
         is_pfas in any::<bool>(),
         confidence in 0.0..1.0f64,
+        concentration in option::of(arb_concentration()),
         regulatory_status in arb_regulatory_status(),
         source_document in arb_document_reference(),
         extraction_method in prop_oneof![
@@ -365,6 +419,7 @@ prop_compose! {
             Just(ExtractionMethod::OCRProcessing),
             Just(ExtractionMethod::ManualEntry),
             Just(ExtractionMethod::DatabaseLookup),
+            Just(ExtractionMethod::MaterialDeclaration),
         ],
         created_at in arb_datetime()
     ) -> CASRecord {
@@ -373,6 +428,7 @@ prop_compose! {
             chemical_name,
             is_pfas,
             confidence,
+            concentration,
             regulatory_status,
             source_document,
             extraction_method,
@@ -428,7 +484,8 @@ prop_compose! {
         issue_date in arb_datetime(),
         expiry_date in option::of(arb_datetime()),
         scope in "[A-Za-z0-9 ]{20,100}",
-        source_document in arb_document_reference()
+        source_document in arb_document_reference(),
+        revoked_at in option::of(arb_datetime())
     ) -> Certification {
         Certification {
             certification_type,
@@ -438,6 +495,7 @@ prop_compose! {
             expiry_date,
             scope,
             source_document,
+            revoked_at,
         }
     }
 }
@@ -457,6 +515,11 @@ prop_compose! {
             Just(AuditAction::WorkflowStarted),
             Just(AuditAction::WorkflowCompleted),
             Just(AuditAction::EscalationCreated),
+            Just(AuditAction::SupplierMerged),
+            Just(AuditAction::LifecycleChanged),
+            Just(AuditAction::RecordCreated),
+            Just(AuditAction::RecordUpdated),
+            Just(AuditAction::RecordDeleted),
             Just(AuditAction::UserAction),
             Just(AuditAction::SystemAction),
         ],
@@ -492,6 +555,49 @@ prop_compose! {
     }
 }
 
+prop_compose! {
+    fn arb_exemption()(
+        id in arb_uuid(),
+        component_id in arb_uuid(),
+        cas_number in option::of(arb_cas_number()),
+        regulation in "[A-Z]{2,20}",
+        category in prop_oneof![
+            Just(ExemptionCategory::Intermediate),
+            Just(ExemptionCategory::ProcessingAid),
+            Just(ExemptionCategory::ResearchAndDevelopment),
+            Just(ExemptionCategory::Article),
+            "[A-Za-z ]{5,20}".prop_map(ExemptionCategory::Other),
+        ],
+        justification in "[A-Za-z0-9 ]{10,100}",
+        evidence in option::of(arb_document_reference()),
+        expires_at in option::of(arb_datetime()),
+        approval_state in prop_oneof![
+            Just(ExemptionApproval::Requested),
+            Just(ExemptionApproval::Approved),
+            Just(ExemptionApproval::Rejected),
+            Just(ExemptionApproval::Revoked),
+        ],
+        decided_by in option::of("[A-Za-z ]{3,30}"),
+        decided_at in option::of(arb_datetime()),
+        created_at in arb_datetime()
+    ) -> Exemption {
+        Exemption {
+            id,
+            component_id: component_id.into(),
+            cas_number,
+            regulation,
+            category,
+            justification,
+            evidence,
+            expires_at,
+            approval_state,
+            decided_by,
+            decided_at,
+            created_at,
+        }
+    }
+}
+
 prop_compose! {
     fn arb_compliance_record()(
         id in arb_uuid(),
@@ -500,6 +606,7 @@ prop_compose! {
         cas_records in prop::collection::vec(arb_cas_record(), 0..5),
         test_results in prop::collection::vec(arb_test_result(), 0..3),
         certifications in prop::collection::vec(arb_certification(), 0..3),
+        exemptions in prop::collection::vec(arb_exemption(), 0..2),
         submission_date in arb_datetime(),
         validation_status in prop_oneof![
             Just(ValidationStatus::Pending),
@@ -513,12 +620,13 @@ prop_compose! {
         updated_at in arb_datetime()
     ) -> ComplianceRecord {
         ComplianceRecord {
-            id,
-            supplier_id,
-            component_id,
+            id: id.into(),
+            supplier_id: supplier_id.into(),
+            component_id: component_id.into(),
             cas_records,
             test_results,
             certifications,
+            exemptions,
             submission_date,
             validation_status,
             audit_trail,
@@ -624,6 +732,7 @@ proptest! {
         prop_assert_eq!(record.supplier_id, deserialized.supplier_id);
         prop_assert_eq!(record.component_id, deserialized.component_id);
         prop_assert_eq!(record.cas_records.len(), deserialized.cas_records.len());
+        prop_assert_eq!(&record.exemptions, &deserialized.exemptions);
         prop_assert_eq!(record.validation_status, deserialized.validation_status);
         
         // Verify CAS records with floating-point tolerance
@@ -0,0 +1,340 @@
+//! Structured questionnaires suppliers answer alongside their documents.
+//!
+//! A `Questionnaire` asks typed questions, e.g. "Do you intentionally add
+//! PFAS?" as a yes/no question, and may only ask some of them depending on
+//! an earlier answer. A `QuestionnaireResponse` holds one supplier's answers
+//! for a campaign and, once reviewed, the compliance record it fed into. A
+//! response can be saved as a draft with answers missing; `submit` checks
+//! every answer against its question.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::compliance::validate_cas_number;
+use crate::{ComplianceRecordId, ComponentId, SupplierId, WorkflowId};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Questionnaire {
+    pub id: Uuid,
+    pub title: String,
+    /// Bumped whenever the questions change; responses record the version they answered
+    pub version: u32,
+    pub questions: Vec<Question>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Question {
+    /// Stable key answers refer to, e.g. `intentional_pfas`
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub help_text: Option<String>,
+    pub kind: QuestionKind,
+    pub required: bool,
+    /// Only asked when an earlier question got a given answer
+    #[serde(default)]
+    pub asked_when: Option<QuestionCondition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuestionKind {
+    YesNo,
+    Text { max_length: Option<usize> },
+    Number { unit: Option<String>, min: Option<f64>, max: Option<f64> },
+    SingleChoice { options: Vec<String> },
+    MultipleChoice { options: Vec<String> },
+    Date,
+    /// One or more CAS numbers, checked for format like those in compliance records
+    CasNumbers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuestionCondition {
+    pub question_id: String,
+    pub answer: Answer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Answer {
+    YesNo(bool),
+    Text(String),
+    Number(f64),
+    Choice(String),
+    Choices(Vec<String>),
+    Date(NaiveDate),
+    CasNumbers(Vec<String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuestionnaireResponse {
+    pub id: Uuid,
+    pub questionnaire_id: Uuid,
+    pub questionnaire_version: u32,
+    pub supplier_id: SupplierId,
+    /// Campaign the questionnaire was sent in
+    #[serde(default)]
+    pub campaign_id: Option<WorkflowId>,
+    /// Component the answers are about, when the questionnaire is per component
+    #[serde(default)]
+    pub component_id: Option<ComponentId>,
+    /// Compliance record the answers were taken into
+    #[serde(default)]
+    pub compliance_record_id: Option<ComplianceRecordId>,
+    /// Answers by question ID
+    pub answers: BTreeMap<String, Answer>,
+    #[serde(default)]
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Why an answer does not fit its question
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AnswerError {
+    #[error("question {question_id} requires an answer")]
+    Missing { question_id: String },
+
+    #[error("question {question_id} is not part of the questionnaire")]
+    UnknownQuestion { question_id: String },
+
+    #[error("question {question_id} is not asked given the other answers")]
+    NotAsked { question_id: String },
+
+    #[error("question {question_id} expects a {expected} answer")]
+    WrongKind { question_id: String, expected: &'static str },
+
+    #[error("question {question_id}: {message}")]
+    Invalid { question_id: String, message: String },
+
+    #[error("response was started from questionnaire {questionnaire_id} version {version}")]
+    WrongQuestionnaire { questionnaire_id: Uuid, version: u32 },
+}
+
+// Utility methods for QuestionKind
+impl QuestionKind {
+    fn answer_name(&self) -> &'static str {
+        match self {
+            Self::YesNo => "yes/no",
+            Self::Text { .. } => "text",
+            Self::Number { .. } => "number",
+            Self::SingleChoice { .. } => "single choice",
+            Self::MultipleChoice { .. } => "multiple choice",
+            Self::Date => "date",
+            Self::CasNumbers => "CAS number",
+        }
+    }
+}
+
+// Utility methods for Question
+impl Question {
+    pub fn new(id: &str, text: &str, kind: QuestionKind, required: bool) -> Self {
+        Self {
+            id: id.to_string(),
+            text: text.to_string(),
+            help_text: None,
+            kind,
+            required,
+            asked_when: None,
+        }
+    }
+
+    /// Asks the question only when `question_id` was answered with `answer`
+    pub fn asked_when(mut self, question_id: &str, answer: Answer) -> Self {
+        self.asked_when = Some(QuestionCondition { question_id: question_id.to_string(), answer });
+        self
+    }
+
+    /// Whether the question applies given the other answers
+    pub fn is_asked(&self, answers: &BTreeMap<String, Answer>) -> bool {
+        self.asked_when.as_ref()
+            .is_none_or(|condition| answers.get(&condition.question_id) == Some(&condition.answer))
+    }
+
+    /// Checks that an answer has the question's kind and stays within its limits
+    pub fn check_answer(&self, answer: &Answer) -> Result<(), AnswerError> {
+        let invalid = |message: String| AnswerError::Invalid { question_id: self.id.clone(), message };
+        match (&self.kind, answer) {
+            (QuestionKind::YesNo, Answer::YesNo(_)) | (QuestionKind::Date, Answer::Date(_)) => Ok(()),
+            (QuestionKind::Text { max_length }, Answer::Text(text)) => match max_length {
+                Some(max) if text.chars().count() > *max => Err(invalid(format!("answer is longer than {} characters", max))),
+                _ => Ok(()),
+            },
+            (QuestionKind::Number { min, max, .. }, Answer::Number(value)) => {
+                if !value.is_finite() {
+                    Err(invalid("answer is not a number".to_string()))
+                } else if min.is_some_and(|min| *value < min) || max.is_some_and(|max| *value > max) {
+                    Err(invalid(format!("{} is out of range", value)))
+                } else {
+                    Ok(())
+                }
+            }
+            (QuestionKind::SingleChoice { options }, Answer::Choice(choice)) => {
+                if options.contains(choice) {
+                    Ok(())
+                } else {
+                    Err(invalid(format!("{} is not one of the options", choice)))
+                }
+            }
+            (QuestionKind::MultipleChoice { options }, Answer::Choices(choices)) => {
+                match choices.iter().find(|c| !options.contains(c)) {
+                    Some(choice) => Err(invalid(format!("{} is not one of the options", choice))),
+                    None => Ok(()),
+                }
+            }
+            (QuestionKind::CasNumbers, Answer::CasNumbers(cas_numbers)) => {
+                match cas_numbers.iter().find(|c| validate_cas_number(c).is_err()) {
+                    Some(cas_number) => Err(invalid(format!("{} is not a valid CAS number", cas_number))),
+                    None => Ok(()),
+                }
+            }
+            (kind, _) => Err(AnswerError::WrongKind { question_id: self.id.clone(), expected: kind.answer_name() }),
+        }
+    }
+}
+
+// Utility methods for Questionnaire
+impl Questionnaire {
+    pub fn new(title: String, questions: Vec<Question>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            title,
+            version: 1,
+            questions,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn question(&self, question_id: &str) -> Option<&Question> {
+        self.questions.iter().find(|q| q.id == question_id)
+    }
+
+    /// Checks that question IDs are unique, choice questions have options
+    /// and conditions refer to an earlier question
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for question in &self.questions {
+            if question.id.trim().is_empty() {
+                return Err("Question IDs cannot be empty".to_string());
+            }
+            if let QuestionKind::SingleChoice { options } | QuestionKind::MultipleChoice { options } = &question.kind {
+                if options.is_empty() {
+                    return Err(format!("Question {} has no options", question.id));
+                }
+            }
+            if let Some(condition) = &question.asked_when {
+                let Some(earlier) = self.question(&condition.question_id).filter(|q| seen.contains(q.id.as_str())) else {
+                    return Err(format!("Question {} depends on {}, which is not asked before it", question.id, condition.question_id));
+                };
+                earlier.check_answer(&condition.answer).map_err(|e| e.to_string())?;
+            }
+            if !seen.insert(question.id.as_str()) {
+                return Err(format!("Question {} appears more than once", question.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every answer against its question: required questions that
+    /// are asked have an answer, and no answer is for a question that is
+    /// unknown or not asked
+    pub fn check_response(&self, answers: &BTreeMap<String, Answer>) -> Result<(), Vec<AnswerError>> {
+        let mut errors: Vec<AnswerError> = answers.keys()
+            .filter(|id| self.question(id).is_none())
+            .map(|id| AnswerError::UnknownQuestion { question_id: id.clone() })
+            .collect();
+        for question in &self.questions {
+            let asked = question.is_asked(answers);
+            match answers.get(&question.id) {
+                Some(_) if !asked => errors.push(AnswerError::NotAsked { question_id: question.id.clone() }),
+                Some(answer) => errors.extend(question.check_answer(answer).err()),
+                None if asked && question.required => errors.push(AnswerError::Missing { question_id: question.id.clone() }),
+                None => {}
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Starts a response from a supplier
+    pub fn respond(&self, supplier_id: SupplierId) -> QuestionnaireResponse {
+        let now = Utc::now();
+        QuestionnaireResponse {
+            id: Uuid::new_v4(),
+            questionnaire_id: self.id,
+            questionnaire_version: self.version,
+            supplier_id,
+            campaign_id: None,
+            component_id: None,
+            compliance_record_id: None,
+            answers: BTreeMap::new(),
+            submitted_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+// Utility methods for QuestionnaireResponse
+impl QuestionnaireResponse {
+    pub fn for_campaign(mut self, campaign_id: WorkflowId) -> Self {
+        self.campaign_id = Some(campaign_id);
+        self
+    }
+
+    pub fn for_component(mut self, component_id: ComponentId) -> Self {
+        self.component_id = Some(component_id);
+        self
+    }
+
+    /// Records or replaces an answer in a draft response
+    pub fn answer(&mut self, question_id: &str, answer: Answer) -> Result<(), String> {
+        if self.is_submitted() {
+            return Err("A submitted response cannot be changed".to_string());
+        }
+        self.answers.insert(question_id.to_string(), answer);
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub fn is_submitted(&self) -> bool {
+        self.submitted_at.is_some()
+    }
+
+    /// Submits the response once every answer fits the questionnaire it was started from
+    pub fn submit(&mut self, questionnaire: &Questionnaire, at: DateTime<Utc>) -> Result<(), Vec<AnswerError>> {
+        if questionnaire.id != self.questionnaire_id || questionnaire.version != self.questionnaire_version {
+            return Err(vec![AnswerError::WrongQuestionnaire {
+                questionnaire_id: self.questionnaire_id,
+                version: self.questionnaire_version,
+            }]);
+        }
+        questionnaire.check_response(&self.answers)?;
+        self.submitted_at = Some(at);
+        self.updated_at = at;
+        Ok(())
+    }
+
+    /// Records the compliance record the answers were taken into
+    pub fn link_compliance_record(&mut self, compliance_record_id: ComplianceRecordId) {
+        self.compliance_record_id = Some(compliance_record_id);
+        self.updated_at = Utc::now();
+    }
+
+    /// CAS numbers given in any answer, for cross-checking with the supplier's documents
+    pub fn cas_numbers(&self) -> Vec<&str> {
+        self.answers.values()
+            .filter_map(|a| match a {
+                Answer::CasNumbers(cas_numbers) => Some(cas_numbers),
+                _ => None,
+            })
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
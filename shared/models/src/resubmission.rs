@@ -0,0 +1,271 @@
+//! Supplier resubmissions of compliance data.
+//!
+//! `ComplianceRecord::diff` compares a record with a supplier's resubmission
+//! of it, matching CAS records by CAS number, test results by test type,
+//! method and laboratory, and certifications by type and certificate number.
+//! `merge_resubmission` folds the resubmission into the record under a
+//! `MergePolicy` and records what it decided in the record's audit trail.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{AuditAction, AuditEntry, CASRecord, Certification, ChangeType, ComplianceRecord, FieldChange, TestResult};
+
+/// How a resubmission is merged into the record it replaces
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Changed CAS records are only taken when extracted with at least the
+    /// confidence of the current one; items missing from the resubmission are kept
+    #[default]
+    KeepHighestConfidence,
+    /// The resubmission replaces the record's items, dropping those it leaves out
+    PreferNewest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+/// Items added, removed and changed between a record and its resubmission
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemDiff<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    pub changed: Vec<ItemChange<T>>,
+}
+
+impl<T> Default for ItemDiff<T> {
+    fn default() -> Self {
+        Self { added: Vec::new(), removed: Vec::new(), changed: Vec::new() }
+    }
+}
+
+impl<T> ItemDiff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ComplianceRecordDiff {
+    pub cas_records: ItemDiff<CASRecord>,
+    pub test_results: ItemDiff<TestResult>,
+    pub certifications: ItemDiff<Certification>,
+}
+
+impl ComplianceRecordDiff {
+    pub fn is_empty(&self) -> bool {
+        self.cas_records.is_empty() && self.test_results.is_empty() && self.certifications.is_empty()
+    }
+}
+
+/// Outcome of merging a resubmission into a record
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResubmissionMerge {
+    pub policy: MergePolicy,
+    pub diff: ComplianceRecordDiff,
+    /// Changed or removed items the policy kept as they were
+    pub kept: usize,
+    /// Entry added to the record's audit trail
+    pub audit_entry: AuditEntry,
+}
+
+/// An item of a compliance record that a resubmission can add, remove or change
+trait ResubmittedItem: Clone + PartialEq + Serialize {
+    const FIELD: &'static str;
+
+    /// Identifies the item across submissions
+    fn key(&self) -> String;
+
+    /// Same item as `other` with the same data, ignoring where and when it was recorded
+    fn same_data(&self, other: &Self) -> bool;
+
+    /// Whether the resubmitted version of a changed item replaces the current one
+    fn resubmission_wins(policy: MergePolicy, current: &Self, resubmitted: &Self) -> bool;
+}
+
+impl ResubmittedItem for CASRecord {
+    const FIELD: &'static str = "cas_records";
+
+    fn key(&self) -> String {
+        self.cas_number.clone()
+    }
+
+    fn same_data(&self, other: &Self) -> bool {
+        let mut other = other.clone();
+        other.source_document = self.source_document.clone();
+        other.created_at = self.created_at;
+        *self == other
+    }
+
+    fn resubmission_wins(policy: MergePolicy, current: &Self, resubmitted: &Self) -> bool {
+        match policy {
+            MergePolicy::PreferNewest => true,
+            MergePolicy::KeepHighestConfidence => resubmitted.confidence >= current.confidence,
+        }
+    }
+}
+
+impl ResubmittedItem for TestResult {
+    const FIELD: &'static str = "test_results";
+
+    fn key(&self) -> String {
+        format!("{:?}/{}/{}", self.test_type, self.test_method, self.laboratory)
+    }
+
+    fn same_data(&self, other: &Self) -> bool {
+        let mut other = other.clone();
+        other.source_document = self.source_document.clone();
+        *self == other
+    }
+
+    // Test results carry no confidence; the later test stands
+    fn resubmission_wins(_policy: MergePolicy, current: &Self, resubmitted: &Self) -> bool {
+        resubmitted.test_date >= current.test_date
+    }
+}
+
+impl ResubmittedItem for Certification {
+    const FIELD: &'static str = "certifications";
+
+    fn key(&self) -> String {
+        format!("{:?}/{}", self.certification_type, self.certificate_number)
+    }
+
+    fn same_data(&self, other: &Self) -> bool {
+        let mut other = other.clone();
+        other.source_document = self.source_document.clone();
+        *self == other
+    }
+
+    // Certifications carry no confidence; the later issue stands
+    fn resubmission_wins(_policy: MergePolicy, current: &Self, resubmitted: &Self) -> bool {
+        resubmitted.issue_date >= current.issue_date
+    }
+}
+
+fn diff_items<T: ResubmittedItem>(current: &[T], resubmitted: &[T]) -> ItemDiff<T> {
+    let mut diff = ItemDiff::default();
+    for item in resubmitted {
+        match current.iter().find(|c| c.key() == item.key()) {
+            None => diff.added.push(item.clone()),
+            Some(before) if !before.same_data(item) => {
+                diff.changed.push(ItemChange { before: before.clone(), after: item.clone() });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = current.iter()
+        .filter(|c| !resubmitted.iter().any(|r| r.key() == c.key()))
+        .cloned()
+        .collect();
+    diff
+}
+
+fn to_json<T: Serialize>(item: &T) -> Option<String> {
+    serde_json::to_string(item).ok()
+}
+
+/// Applies `diff` to `items`, recording each change made; returns how many
+/// changed or removed items were kept
+fn merge_items<T: ResubmittedItem>(
+    items: &mut Vec<T>,
+    diff: &ItemDiff<T>,
+    policy: MergePolicy,
+    changes: &mut Vec<FieldChange>,
+) -> usize {
+    let mut kept = 0;
+    for item in &diff.added {
+        items.push(item.clone());
+        changes.push(FieldChange {
+            field_name: format!("{}[{}]", T::FIELD, item.key()),
+            old_value: None,
+            new_value: to_json(item),
+            change_type: ChangeType::Created,
+        });
+    }
+    for change in &diff.changed {
+        if !T::resubmission_wins(policy, &change.before, &change.after) {
+            kept += 1;
+            continue;
+        }
+        if let Some(item) = items.iter_mut().find(|i| i.key() == change.before.key()) {
+            *item = change.after.clone();
+        }
+        changes.push(FieldChange {
+            field_name: format!("{}[{}]", T::FIELD, change.after.key()),
+            old_value: to_json(&change.before),
+            new_value: to_json(&change.after),
+            change_type: ChangeType::Updated,
+        });
+    }
+    for item in &diff.removed {
+        if policy != MergePolicy::PreferNewest {
+            kept += 1;
+            continue;
+        }
+        items.retain(|i| i.key() != item.key());
+        changes.push(FieldChange {
+            field_name: format!("{}[{}]", T::FIELD, item.key()),
+            old_value: to_json(item),
+            new_value: None,
+            change_type: ChangeType::Deleted,
+        });
+    }
+    kept
+}
+
+// Utility methods for ComplianceRecord resubmissions
+impl ComplianceRecord {
+    /// What `resubmitted` adds, removes and changes relative to this record
+    pub fn diff(&self, resubmitted: &ComplianceRecord) -> ComplianceRecordDiff {
+        ComplianceRecordDiff {
+            cas_records: diff_items(&self.cas_records, &resubmitted.cas_records),
+            test_results: diff_items(&self.test_results, &resubmitted.test_results),
+            certifications: diff_items(&self.certifications, &resubmitted.certifications),
+        }
+    }
+
+    /// Merges a resubmission of this record under `policy`, appending an
+    /// audit entry with every change made and the policy that decided it
+    pub fn merge_resubmission(
+        &mut self,
+        resubmitted: &ComplianceRecord,
+        policy: MergePolicy,
+        user_id: Option<Uuid>,
+    ) -> ResubmissionMerge {
+        let diff = self.diff(resubmitted);
+        let mut changes = Vec::new();
+        let kept = merge_items(&mut self.cas_records, &diff.cas_records, policy, &mut changes)
+            + merge_items(&mut self.test_results, &diff.test_results, policy, &mut changes)
+            + merge_items(&mut self.certifications, &diff.certifications, policy, &mut changes);
+
+        self.submission_date = self.submission_date.max(resubmitted.submission_date);
+        self.update_validation_status();
+        self.updated_at = Utc::now();
+
+        let metadata = HashMap::from([
+            ("merge_policy".to_string(), format!("{:?}", policy)),
+            ("resubmission_id".to_string(), resubmitted.id.to_string()),
+            ("resubmitted_at".to_string(), resubmitted.submission_date.to_rfc3339()),
+            ("changes_applied".to_string(), changes.len().to_string()),
+            ("changes_kept".to_string(), kept.to_string()),
+        ]);
+        let audit_entry = AuditEntry::new(
+            AuditAction::ComplianceRecordUpdated,
+            "compliance_record".to_string(),
+            self.id.into(),
+            user_id,
+            None,
+        )
+        .with_details(changes, metadata);
+        self.audit_trail.push(audit_entry.clone());
+
+        ResubmissionMerge { policy, diff, kept, audit_entry }
+    }
+}
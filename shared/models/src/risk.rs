@@ -0,0 +1,263 @@
+//! Supplier risk scoring.
+//!
+//! A `RiskScoringPolicy` turns a supplier's compliance history, and
+//! optionally its PFAS exposure and certification expiries, into a
+//! `RiskProfile`. The default policy scores the compliance rate and the
+//! response time equally; the other factors count once they are given a
+//! weight. Tenants can override any part of the default through
+//! `RiskPolicies`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{ComplianceHistoryEntry, ComplianceRecord, ComplianceStatus, RiskLevel, RiskProfile};
+
+/// Bounds of the low, medium and high risk levels for one measure; values
+/// beyond `high` are critical
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LevelThresholds {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl LevelThresholds {
+    /// Level of a measure where higher is better, e.g. a compliance rate
+    pub fn level_at_least(&self, value: f64) -> RiskLevel {
+        match value {
+            v if v >= self.low => RiskLevel::Low,
+            v if v >= self.medium => RiskLevel::Medium,
+            v if v >= self.high => RiskLevel::High,
+            _ => RiskLevel::Critical,
+        }
+    }
+
+    /// Level of a measure where lower is better, e.g. response days
+    pub fn level_at_most(&self, value: f64) -> RiskLevel {
+        match value {
+            v if v <= self.low => RiskLevel::Low,
+            v if v <= self.medium => RiskLevel::Medium,
+            v if v <= self.high => RiskLevel::High,
+            _ => RiskLevel::Critical,
+        }
+    }
+}
+
+/// Score of each risk level, from 0 (worst) to 1 (best)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LevelScores {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+    pub critical: f64,
+}
+
+impl LevelScores {
+    pub fn score(&self, level: &RiskLevel) -> f64 {
+        match level {
+            RiskLevel::Low => self.low,
+            RiskLevel::Medium => self.medium,
+            RiskLevel::High => self.high,
+            RiskLevel::Critical => self.critical,
+        }
+    }
+}
+
+/// Weight of each factor in the overall score; factors weighted zero are left out
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RiskWeights {
+    pub compliance: f64,
+    pub response_reliability: f64,
+    pub data_quality: f64,
+    pub pfas_exposure: f64,
+    pub certification_expiry: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RiskScoringPolicy {
+    /// Share of campaigns completed
+    pub compliance_rate: LevelThresholds,
+    /// Average days to respond
+    pub response_days: LevelThresholds,
+    /// Average completeness of the supplier's submissions
+    pub completeness: LevelThresholds,
+    pub level_scores: LevelScores,
+    pub weights: RiskWeights,
+    /// How far ahead a certification counts as expiring
+    pub certification_horizon_days: i64,
+}
+
+impl Default for RiskScoringPolicy {
+    fn default() -> Self {
+        Self {
+            compliance_rate: LevelThresholds { low: 0.9, medium: 0.7, high: 0.5 },
+            response_days: LevelThresholds { low: 3.0, medium: 7.0, high: 14.0 },
+            completeness: LevelThresholds { low: 0.9, medium: 0.75, high: 0.5 },
+            level_scores: LevelScores { low: 0.9, medium: 0.7, high: 0.4, critical: 0.1 },
+            weights: RiskWeights {
+                compliance: 1.0,
+                response_reliability: 1.0,
+                data_quality: 0.0,
+                pfas_exposure: 0.0,
+                certification_expiry: 0.0,
+            },
+            certification_horizon_days: 90,
+        }
+    }
+}
+
+/// Exposure of a supplier found in its compliance records
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskExposure {
+    /// Share of the supplier's records with PFAS no exemption covers
+    pub pfas_share: Option<f64>,
+    /// Share of the supplier's certifications expired or about to expire
+    pub expiring_certification_share: Option<f64>,
+}
+
+// Utility methods for RiskScoringPolicy
+impl RiskScoringPolicy {
+    /// Checks that weights are non-negative with at least one factor counted,
+    /// and that thresholds run from low to critical
+    pub fn check(&self) -> Result<(), String> {
+        let w = &self.weights;
+        let weights = [w.compliance, w.response_reliability, w.data_quality, w.pfas_exposure, w.certification_expiry];
+        if weights.iter().any(|w| *w < 0.0 || !w.is_finite()) {
+            return Err("Risk weights must be non-negative".to_string());
+        }
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err("At least one risk factor needs a weight".to_string());
+        }
+        for (name, t) in [("compliance_rate", &self.compliance_rate), ("completeness", &self.completeness)] {
+            if !(t.low >= t.medium && t.medium >= t.high) {
+                return Err(format!("{} thresholds must decrease from low to high risk", name));
+            }
+        }
+        let t = &self.response_days;
+        if !(t.low <= t.medium && t.medium <= t.high) {
+            return Err("response_days thresholds must increase from low to high risk".to_string());
+        }
+        if self.certification_horizon_days < 0 {
+            return Err("Certification horizon cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Exposure figures of a supplier from its compliance records
+    pub fn exposure(&self, records: &[ComplianceRecord], now: DateTime<Utc>) -> RiskExposure {
+        let share = |count: usize, total: usize| (total > 0).then(|| count as f64 / total as f64);
+        let horizon = now + Duration::days(self.certification_horizon_days);
+        let certifications: Vec<_> = records.iter().flat_map(|r| &r.certifications).collect();
+        RiskExposure {
+            pfas_share: share(
+                records.iter().filter(|r| !r.unexempted_pfas_substances().is_empty()).count(),
+                records.len(),
+            ),
+            expiring_certification_share: share(
+                certifications.iter().filter(|c| c.expiry_date.is_some_and(|expiry| expiry <= horizon)).count(),
+                certifications.len(),
+            ),
+        }
+    }
+
+    /// Risk profile from a supplier's history and exposure; `None` without
+    /// history. Data quality is only reassessed when it carries weight.
+    pub fn assess(
+        &self,
+        history: &[ComplianceHistoryEntry],
+        exposure: &RiskExposure,
+        current: &RiskProfile,
+        now: DateTime<Utc>,
+    ) -> Option<RiskProfile> {
+        if history.is_empty() {
+            return None;
+        }
+        let count = history.len() as f64;
+
+        let compliant = history.iter().filter(|h| matches!(h.status, ComplianceStatus::Complete)).count();
+        let compliance_risk = self.compliance_rate.level_at_least(compliant as f64 / count);
+
+        // Entries without a response time count as answered at once, as they always have
+        let average_response_days = history.iter().filter_map(|h| h.response_time_days).sum::<i32>() as f64 / count;
+        let response_reliability = self.response_days.level_at_most(average_response_days);
+
+        let data_quality = if self.weights.data_quality > 0.0 {
+            self.completeness.level_at_least(history.iter().map(|h| h.completeness_score).sum::<f64>() / count)
+        } else {
+            current.data_quality.clone()
+        };
+
+        let scores = [
+            (self.weights.compliance, Some(self.level_scores.score(&compliance_risk))),
+            (self.weights.response_reliability, Some(self.level_scores.score(&response_reliability))),
+            (self.weights.data_quality, Some(self.level_scores.score(&data_quality))),
+            (self.weights.pfas_exposure, exposure.pfas_share.map(|s| 1.0 - s)),
+            (self.weights.certification_expiry, exposure.expiring_certification_share.map(|s| 1.0 - s)),
+        ];
+        let (weighted, total) = scores.iter()
+            .filter(|(weight, _)| *weight > 0.0)
+            .filter_map(|(weight, score)| score.map(|s| (weight * s, *weight)))
+            .fold((0.0, 0.0), |(sum, total), (s, w)| (sum + s, total + w));
+
+        Some(RiskProfile {
+            compliance_risk,
+            response_reliability,
+            data_quality,
+            overall_score: if total > 0.0 { weighted / total } else { current.overall_score },
+            last_assessed: now,
+        })
+    }
+}
+
+/// Overrides of parts of the default policy for one tenant
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RiskPolicyOverride {
+    #[serde(default)]
+    pub compliance_rate: Option<LevelThresholds>,
+    #[serde(default)]
+    pub response_days: Option<LevelThresholds>,
+    #[serde(default)]
+    pub completeness: Option<LevelThresholds>,
+    #[serde(default)]
+    pub level_scores: Option<LevelScores>,
+    #[serde(default)]
+    pub weights: Option<RiskWeights>,
+    #[serde(default)]
+    pub certification_horizon_days: Option<i64>,
+}
+
+/// The default scoring policy and each tenant's overrides of it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RiskPolicies {
+    pub default: RiskScoringPolicy,
+    #[serde(default)]
+    pub tenants: HashMap<String, RiskPolicyOverride>,
+}
+
+// Utility methods for RiskPolicies
+impl RiskPolicies {
+    /// Policy of a tenant: the default with the tenant's overrides applied
+    pub fn for_tenant(&self, tenant: &str) -> RiskScoringPolicy {
+        let mut policy = self.default.clone();
+        if let Some(o) = self.tenants.get(tenant) {
+            policy.compliance_rate = o.compliance_rate.unwrap_or(policy.compliance_rate);
+            policy.response_days = o.response_days.unwrap_or(policy.response_days);
+            policy.completeness = o.completeness.unwrap_or(policy.completeness);
+            policy.level_scores = o.level_scores.unwrap_or(policy.level_scores);
+            policy.weights = o.weights.unwrap_or(policy.weights);
+            policy.certification_horizon_days = o.certification_horizon_days.unwrap_or(policy.certification_horizon_days);
+        }
+        policy
+    }
+
+    /// Checks the default policy and every tenant's resulting policy
+    pub fn check(&self) -> Result<(), String> {
+        self.default.check()?;
+        for tenant in self.tenants.keys() {
+            self.for_tenant(tenant).check().map_err(|e| format!("Tenant {}: {}", tenant, e))?;
+        }
+        Ok(())
+    }
+}
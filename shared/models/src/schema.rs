@@ -0,0 +1,140 @@
+//! Schema versioning for models persisted as JSON.
+//!
+//! Supplier records, compliance records and risk profiles are stored as
+//! JSON and carry the version of the shape they were written in. Reading
+//! one upgrades it step by step to the current shape before deserializing,
+//! so a renamed field or enum variant is carried over instead of the value
+//! falling back to a default. Every change to a persisted shape bumps the
+//! type's `SCHEMA_VERSION` and adds the step from the previous version.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Key holding the schema version in a persisted value
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Version of values written before versioning was introduced
+pub const UNVERSIONED: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("{type_name} schema version {version} is newer than the supported version {supported}")]
+    Unsupported { type_name: &'static str, version: u32, supported: u32 },
+
+    #[error("No upgrade for {type_name} from schema version {version}")]
+    MissingUpgrade { type_name: &'static str, version: u32 },
+
+    #[error("Malformed {type_name}: {message}")]
+    Malformed { type_name: &'static str, message: String },
+
+    #[error("Failed to deserialize {type_name} at schema version {version}: {source}")]
+    Deserialize { type_name: &'static str, version: u32, source: serde_json::Error },
+}
+
+/// A model persisted as JSON whose shape is versioned
+pub trait Versioned: Serialize + DeserializeOwned {
+    const TYPE_NAME: &'static str;
+
+    /// Version of the shape this build writes
+    const SCHEMA_VERSION: u32;
+
+    /// Reshapes a value written at `version` into `version + 1`
+    fn upgrade(version: u32, _value: &mut Value) -> Result<(), SchemaError> {
+        Err(SchemaError::MissingUpgrade { type_name: Self::TYPE_NAME, version })
+    }
+
+    /// Upgrades nested values that are versioned on their own
+    fn upgrade_nested(_value: &mut Value) -> Result<(), SchemaError> {
+        Ok(())
+    }
+
+    /// Stamps nested values that are versioned on their own
+    fn stamp_nested(_value: &mut Value) {}
+
+    /// Serializes the value stamped with the current schema version
+    fn to_versioned(&self) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        stamp::<Self>(&mut value);
+        Self::stamp_nested(&mut value);
+        Ok(value)
+    }
+
+    /// Deserializes a value written at any supported schema version
+    fn from_versioned(mut value: Value) -> Result<Self, SchemaError> {
+        let version = upgrade_value::<Self>(&mut value)?;
+        serde_json::from_value(value).map_err(|source| SchemaError::Deserialize {
+            type_name: Self::TYPE_NAME,
+            version,
+            source,
+        })
+    }
+}
+
+/// Marks a serialized value as written in the current schema version
+pub fn stamp<T: Versioned>(value: &mut Value) {
+    if let Value::Object(object) = value {
+        object.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(T::SCHEMA_VERSION));
+    }
+}
+
+/// Schema version a persisted value was written in
+pub fn schema_version_of<T: Versioned>(value: &Value) -> Result<u32, SchemaError> {
+    match object::<T>(value)?.get(SCHEMA_VERSION_KEY) {
+        None | Some(Value::Null) => Ok(UNVERSIONED),
+        Some(version) => version.as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= UNVERSIONED)
+            .ok_or_else(|| malformed::<T>(format!("invalid schema version {}", version))),
+    }
+}
+
+/// Upgrades a persisted value in place to the current schema version,
+/// returning the version it was written in
+pub fn upgrade_value<T: Versioned>(value: &mut Value) -> Result<u32, SchemaError> {
+    let written = schema_version_of::<T>(value)?;
+    if written > T::SCHEMA_VERSION {
+        return Err(SchemaError::Unsupported {
+            type_name: T::TYPE_NAME,
+            version: written,
+            supported: T::SCHEMA_VERSION,
+        });
+    }
+    for version in written..T::SCHEMA_VERSION {
+        T::upgrade(version, value)?;
+    }
+    T::upgrade_nested(value)?;
+    stamp::<T>(value);
+    Ok(written)
+}
+
+/// Adds `key` to an object that was written before the field existed
+pub fn insert_missing<T: Versioned>(value: &mut Value, key: &str, default: Value) -> Result<(), SchemaError> {
+    object_mut::<T>(value)?.entry(key.to_string()).or_insert(default);
+    Ok(())
+}
+
+/// Applies `upgrade` to every element of the array under `key`, if present
+pub fn upgrade_each<T: Versioned>(
+    value: &mut Value,
+    key: &str,
+    mut upgrade: impl FnMut(&mut Value) -> Result<(), SchemaError>,
+) -> Result<(), SchemaError> {
+    match object_mut::<T>(value)?.get_mut(key) {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::Array(items)) => items.iter_mut().try_for_each(&mut upgrade),
+        Some(_) => Err(malformed::<T>(format!("{} is not an array", key))),
+    }
+}
+
+fn object<T: Versioned>(value: &Value) -> Result<&Map<String, Value>, SchemaError> {
+    value.as_object().ok_or_else(|| malformed::<T>("expected an object".to_string()))
+}
+
+fn object_mut<T: Versioned>(value: &mut Value) -> Result<&mut Map<String, Value>, SchemaError> {
+    value.as_object_mut().ok_or_else(|| malformed::<T>("expected an object".to_string()))
+}
+
+fn malformed<T: Versioned>(message: String) -> SchemaError {
+    SchemaError::Malformed { type_name: T::TYPE_NAME, message }
+}
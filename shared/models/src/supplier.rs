@@ -6,24 +6,38 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+use crate::schema::{self, SchemaError, Versioned};
+use crate::risk::{RiskExposure, RiskScoringPolicy};
+use crate::{ChangeType, FieldChange, LifecycleStatus, SupplierId, WorkflowId};
+
 /// Represents a supplier in the compliance system with full contact information,
 /// compliance history, and risk assessment data.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Validate, PartialEq)]
 pub struct SupplierRecord {
-    pub id: Uuid,
+    pub id: SupplierId,
     #[validate(length(min = 1, max = 255, message = "Supplier name must be between 1 and 255 characters"))]
     pub name: String,
     #[validate]
     pub contact_info: ContactInfo,
     pub relationship: SupplierRelationship,
+    /// How far down the supply chain the supplier sits
+    #[serde(default)]
+    pub tier: SupplierTier,
+    /// Company the supplier is a subsidiary of, itself a supplier record
+    #[serde(default)]
+    pub parent_id: Option<SupplierId>,
     pub compliance_history: Vec<ComplianceHistoryEntry>,
     #[validate]
     pub communication_preferences: CommunicationPreferences,
     #[validate]
     pub risk_profile: RiskProfile,
+    /// Whether the supplier is in use, archived or soft-deleted
+    #[serde(default)]
+    pub lifecycle_status: LifecycleStatus,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -65,9 +79,19 @@ pub enum SupplierRelationship {
     AtRisk,
 }
 
+/// Position in the supply chain: tier 1 suppliers sell to us directly,
+/// tier 2 suppliers sell to tier 1 suppliers, and so on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SupplierTier {
+    #[default]
+    Tier1,
+    Tier2,
+    Tier3,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ComplianceHistoryEntry {
-    pub campaign_id: Uuid,
+    pub campaign_id: WorkflowId,
     pub status: ComplianceStatus,
     pub response_time_days: Option<i32>,
     pub completeness_score: f64,
@@ -92,6 +116,29 @@ pub struct CommunicationPreferences {
     pub response_format: ResponseFormat,
     #[validate(range(min = 1, max = 30, message = "Follow-up frequency must be between 1 and 30 days"))]
     pub follow_up_frequency_days: i32,
+    /// The supplier asked not to be emailed; requests go through a person instead
+    #[serde(default)]
+    pub do_not_contact: bool,
+    /// When the supplier accepts email; any time if `None`
+    #[serde(default)]
+    #[validate]
+    pub contact_window: Option<ContactWindow>,
+}
+
+/// Daily hours a supplier accepts email, in its local time. A window whose
+/// end hour is not after its start runs past midnight; equal hours mean all day.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct ContactWindow {
+    #[validate(range(min = 0, max = 23, message = "Start hour must be between 0 and 23"))]
+    pub start_hour: u32,
+    /// First hour outside the window
+    #[validate(range(min = 0, max = 23, message = "End hour must be between 0 and 23"))]
+    pub end_hour: u32,
+    /// Offset of the supplier's local time from UTC
+    #[validate(range(min = -720, max = 840, message = "UTC offset must be between -720 and 840 minutes"))]
+    pub utc_offset_minutes: i32,
+    #[serde(default)]
+    pub weekdays_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -131,13 +178,17 @@ pub enum RiskLevel {
 impl Default for SupplierRecord {
     fn default() -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: SupplierId::new(),
             name: String::new(),
             contact_info: ContactInfo::default(),
             relationship: SupplierRelationship::Standard,
+            tier: SupplierTier::default(),
+            parent_id: None,
             compliance_history: Vec::new(),
             communication_preferences: CommunicationPreferences::default(),
             risk_profile: RiskProfile::default(),
+            lifecycle_status: LifecycleStatus::default(),
+            deleted_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -187,6 +238,8 @@ impl Default for CommunicationPreferences {
             technical_level: TechnicalLevel::Intermediate,
             response_format: ResponseFormat::Email,
             follow_up_frequency_days: 7,
+            do_not_contact: false,
+            contact_window: None,
         }
     }
 }
@@ -203,6 +256,59 @@ impl Default for RiskProfile {
     }
 }
 
+// Schema versions of SupplierRecord:
+// 1. Unversioned records
+// 2. Do-not-contact flag and contact window in communication preferences
+// 3. Supply chain tier and parent company
+// 4. Lifecycle status and deletion time
+impl Versioned for SupplierRecord {
+    const TYPE_NAME: &'static str = "SupplierRecord";
+    const SCHEMA_VERSION: u32 = 4;
+    
+    fn upgrade(version: u32, value: &mut serde_json::Value) -> Result<(), SchemaError> {
+        match version {
+            1 => {
+                let preferences = value.get_mut("communication_preferences")
+                    .ok_or(SchemaError::Malformed {
+                        type_name: Self::TYPE_NAME,
+                        message: "missing communication_preferences".to_string(),
+                    })?;
+                schema::insert_missing::<Self>(preferences, "do_not_contact", false.into())?;
+                schema::insert_missing::<Self>(preferences, "contact_window", serde_json::Value::Null)
+            }
+            2 => {
+                schema::insert_missing::<Self>(value, "tier", serde_json::to_value(SupplierTier::Tier1).unwrap_or_default())?;
+                schema::insert_missing::<Self>(value, "parent_id", serde_json::Value::Null)
+            }
+            3 => {
+                schema::insert_missing::<Self>(value, "lifecycle_status", LifecycleStatus::Active.as_str().into())?;
+                schema::insert_missing::<Self>(value, "deleted_at", serde_json::Value::Null)
+            }
+            _ => Err(SchemaError::MissingUpgrade { type_name: Self::TYPE_NAME, version }),
+        }
+    }
+    
+    fn upgrade_nested(value: &mut serde_json::Value) -> Result<(), SchemaError> {
+        match value.get_mut("risk_profile") {
+            Some(risk_profile) => schema::upgrade_value::<RiskProfile>(risk_profile).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+    
+    fn stamp_nested(value: &mut serde_json::Value) {
+        if let Some(risk_profile) = value.get_mut("risk_profile") {
+            schema::stamp::<RiskProfile>(risk_profile);
+        }
+    }
+}
+
+// Schema versions of RiskProfile, which is also stored on its own:
+// 1. Unversioned profiles
+impl Versioned for RiskProfile {
+    const TYPE_NAME: &'static str = "RiskProfile";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 // Custom validation functions
 fn validate_email_list(emails: &[String]) -> Result<(), ValidationError> {
     for email in emails {
@@ -224,58 +330,20 @@ impl SupplierRecord {
         record
     }
     
-    /// Updates the supplier's risk profile based on compliance history
+    /// Updates the supplier's risk profile based on compliance history,
+    /// scored with the default policy
     pub fn update_risk_profile(&mut self) {
-        let history_count = self.compliance_history.len();
-        if history_count == 0 {
-            return;
+        self.update_risk_profile_with(&RiskScoringPolicy::default(), &RiskExposure::default());
+    }
+    
+    /// Updates the supplier's risk profile with a scoring policy and the
+    /// exposure found in its compliance records
+    pub fn update_risk_profile_with(&mut self, policy: &RiskScoringPolicy, exposure: &RiskExposure) {
+        let now = Utc::now();
+        if let Some(profile) = policy.assess(&self.compliance_history, exposure, &self.risk_profile, now) {
+            self.risk_profile = profile;
+            self.updated_at = now;
         }
-        
-        let compliant_count = self.compliance_history.iter()
-            .filter(|h| matches!(h.status, ComplianceStatus::Complete))
-            .count();
-        
-        let compliance_rate = compliant_count as f64 / history_count as f64;
-        
-        // Update compliance risk based on compliance rate
-        self.risk_profile.compliance_risk = match compliance_rate {
-            r if r >= 0.9 => RiskLevel::Low,
-            r if r >= 0.7 => RiskLevel::Medium,
-            r if r >= 0.5 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
-        
-        // Calculate average response time
-        let avg_response_time = self.compliance_history.iter()
-            .filter_map(|h| h.response_time_days)
-            .sum::<i32>() as f64 / history_count as f64;
-        
-        // Update response reliability based on response time
-        self.risk_profile.response_reliability = match avg_response_time {
-            t if t <= 3.0 => RiskLevel::Low,
-            t if t <= 7.0 => RiskLevel::Medium,
-            t if t <= 14.0 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
-        
-        // Calculate overall score
-        let compliance_score = match self.risk_profile.compliance_risk {
-            RiskLevel::Low => 0.9,
-            RiskLevel::Medium => 0.7,
-            RiskLevel::High => 0.4,
-            RiskLevel::Critical => 0.1,
-        };
-        
-        let reliability_score = match self.risk_profile.response_reliability {
-            RiskLevel::Low => 0.9,
-            RiskLevel::Medium => 0.7,
-            RiskLevel::High => 0.4,
-            RiskLevel::Critical => 0.1,
-        };
-        
-        self.risk_profile.overall_score = (compliance_score + reliability_score) / 2.0;
-        self.risk_profile.last_assessed = Utc::now();
-        self.updated_at = Utc::now();
     }
     
     /// Checks if the supplier is high risk
@@ -302,4 +370,205 @@ impl SupplierRecord {
         self.compliance_history.push(entry);
         self.update_risk_profile();
     }
+    
+    /// Checks if an address is the primary or an alternate email, ignoring case
+    pub fn has_email(&self, email: &str) -> bool {
+        self.all_emails().iter().any(|e| e.eq_ignore_ascii_case(email.trim()))
+    }
+    
+    /// Consolidates a duplicate record into this one and returns what changed.
+    /// Contact details only fill gaps, and the duplicate's addresses become
+    /// alternates; compliance history keeps the latest entry per campaign; a
+    /// do-not-contact request from either record is kept.
+    pub fn merge(&mut self, duplicate: &SupplierRecord) -> Result<Vec<FieldChange>, String> {
+        if duplicate.id == self.id {
+            return Err("A supplier cannot be merged into itself".to_string());
+        }
+        
+        let mut changes = Vec::new();
+        let mut record = |field: &str, old_value: Option<String>, new_value: Option<String>, change_type: ChangeType| {
+            changes.push(FieldChange { field_name: field.to_string(), old_value, new_value, change_type });
+        };
+        
+        // Contact details
+        let theirs = &duplicate.contact_info;
+        if self.contact_info.primary_email.trim().is_empty() && !theirs.primary_email.trim().is_empty() {
+            self.contact_info.primary_email = theirs.primary_email.clone();
+            record("contact_info.primary_email", None, Some(theirs.primary_email.clone()), ChangeType::Updated);
+        }
+        for email in std::iter::once(&theirs.primary_email).chain(&theirs.alternate_emails) {
+            if email.trim().is_empty() || self.has_email(email) {
+                continue;
+            }
+            self.contact_info.alternate_emails.push(email.clone());
+            record("contact_info.alternate_emails", None, Some(email.clone()), ChangeType::Created);
+        }
+        if self.contact_info.contact_person.trim().is_empty() && !theirs.contact_person.trim().is_empty() {
+            self.contact_info.contact_person = theirs.contact_person.clone();
+            record("contact_info.contact_person", None, Some(theirs.contact_person.clone()), ChangeType::Updated);
+        }
+        if self.contact_info.phone.is_none() && theirs.phone.is_some() {
+            self.contact_info.phone = theirs.phone.clone();
+            record("contact_info.phone", None, theirs.phone.clone(), ChangeType::Updated);
+        }
+        if self.contact_info.address.is_none() && theirs.address.is_some() {
+            self.contact_info.address = theirs.address.clone();
+            record("contact_info.address", None, serde_json::to_string(&theirs.address).ok(), ChangeType::Updated);
+        }
+        
+        // Compliance history, one entry per campaign
+        let mut history_changed = false;
+        for entry in &duplicate.compliance_history {
+            let campaign = Some(entry.campaign_id.to_string());
+            match self.compliance_history.iter_mut().find(|e| e.campaign_id == entry.campaign_id) {
+                Some(existing) if existing.last_updated >= entry.last_updated => continue,
+                Some(existing) => {
+                    *existing = entry.clone();
+                    record("compliance_history", campaign.clone(), campaign, ChangeType::Updated);
+                }
+                None => {
+                    self.compliance_history.push(entry.clone());
+                    record("compliance_history", None, campaign, ChangeType::Created);
+                }
+            }
+            history_changed = true;
+        }
+        
+        // Communication preferences
+        let preferences = &duplicate.communication_preferences;
+        if preferences.do_not_contact && !self.communication_preferences.do_not_contact {
+            self.communication_preferences.do_not_contact = true;
+            record("communication_preferences.do_not_contact", Some(false.to_string()), Some(true.to_string()), ChangeType::Updated);
+        }
+        if self.communication_preferences.contact_window.is_none() && preferences.contact_window.is_some() {
+            self.communication_preferences.contact_window = preferences.contact_window.clone();
+            record("communication_preferences.contact_window", None, serde_json::to_string(&preferences.contact_window).ok(), ChangeType::Updated);
+        }
+        
+        // Corporate structure; a survivor that was the duplicate's subsidiary takes its parent
+        let inherited_parent = duplicate.parent_id.filter(|parent| *parent != self.id);
+        if self.parent_id == Some(duplicate.id) {
+            let old = self.parent_id.map(|p| p.to_string());
+            self.parent_id = inherited_parent;
+            record("parent_id", old, inherited_parent.map(|p| p.to_string()), ChangeType::Updated);
+        } else if self.parent_id.is_none() && inherited_parent.is_some() {
+            self.parent_id = inherited_parent;
+            record("parent_id", None, inherited_parent.map(|p| p.to_string()), ChangeType::Updated);
+        }
+        
+        if history_changed {
+            self.update_risk_profile();
+        }
+        self.updated_at = Utc::now();
+        Ok(changes)
+    }
+    
+    /// Makes the supplier a subsidiary of `parent`; a supplier cannot be its own parent
+    pub fn set_parent(&mut self, parent_id: Option<SupplierId>) -> Result<(), String> {
+        if parent_id == Some(self.id) {
+            return Err("A supplier cannot be its own parent".to_string());
+        }
+        self.parent_id = parent_id;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+    
+    /// Gets the status of the supplier's most recently updated campaign
+    pub fn latest_compliance_status(&self) -> Option<&ComplianceStatus> {
+        self.compliance_history.iter()
+            .max_by_key(|entry| entry.last_updated)
+            .map(|entry| &entry.status)
+    }
+}
+
+// Utility methods for SupplierTier
+impl SupplierTier {
+    /// Gets the tier's number, 1 for direct suppliers
+    pub fn level(&self) -> u8 {
+        match self {
+            SupplierTier::Tier1 => 1,
+            SupplierTier::Tier2 => 2,
+            SupplierTier::Tier3 => 3,
+        }
+    }
+    
+    pub fn from_level(level: u8) -> Option<Self> {
+        match level {
+            1 => Some(SupplierTier::Tier1),
+            2 => Some(SupplierTier::Tier2),
+            3 => Some(SupplierTier::Tier3),
+            _ => None,
+        }
+    }
+}
+
+// Utility methods for ComplianceStatus
+impl ComplianceStatus {
+    /// Ranks statuses from least (0) to most compliant, for rolling them up
+    fn rank(&self) -> u8 {
+        match self {
+            ComplianceStatus::NonCompliant => 0,
+            ComplianceStatus::Escalated => 1,
+            ComplianceStatus::NotStarted => 2,
+            ComplianceStatus::InProgress => 3,
+            ComplianceStatus::PartiallyComplete => 4,
+            ComplianceStatus::Complete => 5,
+        }
+    }
+}
+
+/// Compliance of a parent company and all its subsidiaries, from each
+/// supplier's latest campaign
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComplianceRollup {
+    pub parent_id: SupplierId,
+    /// The parent and its subsidiaries at every level
+    pub suppliers: usize,
+    pub complete: usize,
+    pub in_progress: usize,
+    /// Non-compliant or escalated
+    pub non_compliant: usize,
+    /// Never asked in a campaign, or not started yet
+    pub not_started: usize,
+    /// Least compliant latest status in the group; the group is only as
+    /// compliant as its worst member
+    pub status: ComplianceStatus,
+}
+
+// Utility methods for ComplianceRollup
+impl ComplianceRollup {
+    /// Rolls up the compliance of a parent and its subsidiaries
+    pub fn from_group(parent: &SupplierRecord, subsidiaries: &[SupplierRecord]) -> Self {
+        let mut rollup = Self {
+            parent_id: parent.id,
+            suppliers: 0,
+            complete: 0,
+            in_progress: 0,
+            non_compliant: 0,
+            not_started: 0,
+            status: ComplianceStatus::Complete,
+        };
+        for supplier in std::iter::once(parent).chain(subsidiaries) {
+            let status = supplier.latest_compliance_status().cloned().unwrap_or(ComplianceStatus::NotStarted);
+            rollup.suppliers += 1;
+            match status {
+                ComplianceStatus::Complete => rollup.complete += 1,
+                ComplianceStatus::InProgress | ComplianceStatus::PartiallyComplete => rollup.in_progress += 1,
+                ComplianceStatus::NonCompliant | ComplianceStatus::Escalated => rollup.non_compliant += 1,
+                ComplianceStatus::NotStarted => rollup.not_started += 1,
+            }
+            if status.rank() < rollup.status.rank() {
+                rollup.status = status;
+            }
+        }
+        rollup
+    }
+    
+    /// Share of the group whose latest campaign is complete
+    pub fn compliance_rate(&self) -> f64 {
+        if self.suppliers == 0 {
+            return 0.0;
+        }
+        self.complete as f64 / self.suppliers as f64
+    }
 }
\ No newline at end of file
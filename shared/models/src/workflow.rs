@@ -3,12 +3,14 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::{ComponentId, SupplierId, WorkflowId};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WorkflowInstance {
-    pub id: Uuid,
+    pub id: WorkflowId,
     pub client_id: Uuid,
     pub campaign_name: String,
-    pub suppliers: Vec<Uuid>,
+    pub suppliers: Vec<SupplierId>,
     pub status: WorkflowStatus,
     pub start_date: DateTime<Utc>,
     pub deadline: DateTime<Utc>,
@@ -42,7 +44,7 @@ pub struct WorkflowProgress {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Escalation {
     pub id: Uuid,
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     pub escalation_type: EscalationType,
     pub reason: String,
     pub created_at: DateTime<Utc>,
@@ -63,9 +65,9 @@ pub enum EscalationType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentTask {
     pub id: Uuid,
-    pub workflow_id: Uuid,
+    pub workflow_id: WorkflowId,
     pub task_type: AgentTaskType,
-    pub supplier_id: Uuid,
+    pub supplier_id: SupplierId,
     pub context: TaskContext,
     pub status: TaskStatus,
     pub retry_count: u32,
@@ -82,11 +84,12 @@ pub enum AgentTaskType {
     FollowUp,
     Validation,
     Escalation,
+    Closeout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskContext {
-    pub components: Vec<Uuid>,
+    pub components: Vec<ComponentId>,
     pub deadline: DateTime<Utc>,
     pub priority: TaskPriority,
     pub custom_instructions: Option<String>,
@@ -133,7 +136,7 @@ pub enum TaskResult {
 impl Default for WorkflowInstance {
     fn default() -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: WorkflowId::new(),
             client_id: Uuid::new_v4(),
             campaign_name: String::new(),
             suppliers: Vec::new(),
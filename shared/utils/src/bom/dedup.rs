@@ -0,0 +1,276 @@
+//! Supplier Name Matching
+//!
+//! Decides whether two supplier names are the same company. Names are
+//! normalized first: lowercased, stripped of punctuation and legal forms
+//! such as "Corp", "GmbH" or "Ltd", so "ACME Corporation" and
+//! "Acme Corp GmbH" are both "acme". Names that still differ are compared
+//! by Jaro-Winkler similarity, as written and with their words sorted to
+//! ignore word order. Pairs above the merge threshold are the same
+//! supplier if their words also line up one to one, each close enough to
+//! differ only by a typo; common industry words such as "Electronics" and
+//! "Electric" must match exactly, as must the distinctive words they leave,
+//! so "NXP Semiconductors" and "ON Semiconductors" are not merged. Pairs
+//! that fail this, or score between the review and merge thresholds, are
+//! left apart for someone to review.
+
+use serde::Serialize;
+
+use elementa_models::SupplierId;
+
+/// Similarity from which two names are merged without review
+pub const DEFAULT_MERGE_THRESHOLD: f64 = 0.92;
+/// Similarity from which two names are flagged for review
+pub const DEFAULT_REVIEW_THRESHOLD: f64 = 0.85;
+
+/// Words naming a company's legal form, dropped before comparing names
+const LEGAL_FORMS: &[&str] = &[
+    "inc", "incorporated", "llc", "llp", "ltd", "limited", "corp", "corporation", "co", "company",
+    "plc", "gmbh", "ag", "kg", "se", "sa", "sas", "sarl", "srl", "spa", "bv", "nv", "ab", "oy",
+    "as", "kk", "pte", "pty",
+];
+
+/// Words common to many company names, which tell companies apart only
+/// when they differ
+const INDUSTRY_WORDS: &[&str] = &[
+    "automation", "chemical", "chemicals", "circuits", "components", "controls", "devices", "electric",
+    "electrical", "electronic", "electronics", "engineering", "enterprises", "global", "group", "holdings",
+    "industrial", "industries", "instruments", "international", "materials", "manufacturing", "metals",
+    "micro", "microelectronics", "optics", "plastics", "polymers", "power", "precision", "products",
+    "semiconductor", "semiconductors", "sensors", "services", "solutions", "systems", "technologies",
+    "technology", "tech", "trading",
+];
+
+/// How alike two supplier names are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameMatch {
+    Same,
+    /// Alike enough to be worth a look, not enough to merge
+    Review,
+    Distinct,
+}
+
+/// Thresholds for merging and reviewing similar supplier names
+#[derive(Debug, Clone, Copy)]
+pub struct SupplierMatcher {
+    merge_threshold: f64,
+    review_threshold: f64,
+}
+
+impl Default for SupplierMatcher {
+    fn default() -> Self {
+        Self {
+            merge_threshold: DEFAULT_MERGE_THRESHOLD,
+            review_threshold: DEFAULT_REVIEW_THRESHOLD,
+        }
+    }
+}
+
+impl SupplierMatcher {
+    /// Merge names at least `merge_threshold` alike and flag those at least
+    /// `review_threshold` alike; the review threshold is capped at the merge one
+    pub fn new(merge_threshold: f64, review_threshold: f64) -> Self {
+        Self {
+            merge_threshold,
+            review_threshold: review_threshold.min(merge_threshold),
+        }
+    }
+
+    pub fn merge_threshold(&self) -> f64 {
+        self.merge_threshold
+    }
+
+    /// Classify a similarity from `similarity`
+    pub fn classify(&self, similarity: f64) -> NameMatch {
+        if similarity >= self.merge_threshold {
+            NameMatch::Same
+        } else if similarity >= self.review_threshold {
+            NameMatch::Review
+        } else {
+            NameMatch::Distinct
+        }
+    }
+
+    /// Compare two normalized names: their similarity, classified, with
+    /// pairs whose words do not line up left for review rather than merged
+    pub fn compare(&self, a: &str, b: &str) -> (NameMatch, f64) {
+        let score = similarity(a, b);
+        match self.classify(score) {
+            NameMatch::Same if !words_line_up(a, b, self.merge_threshold) => (NameMatch::Review, score),
+            matched => (matched, score),
+        }
+    }
+
+    /// Best match for `name` among normalized `candidates`, with how alike
+    /// they are; a candidate that is the same supplier wins over closer ones to review
+    pub fn best_match<'a, T>(
+        &self,
+        name: &str,
+        candidates: impl IntoIterator<Item = (&'a str, T)>,
+    ) -> Option<(T, NameMatch, f64)> {
+        let normalized = normalize_supplier_name(name);
+        let rank = |matched: NameMatch, score: f64| (matched == NameMatch::Same, score);
+        candidates.into_iter()
+            .map(|(candidate, value)| {
+                let (matched, score) = self.compare(&normalized, candidate);
+                (value, matched, score)
+            })
+            .filter(|(_, matched, _)| *matched != NameMatch::Distinct)
+            .fold(None, |best: Option<(T, NameMatch, f64)>, (value, matched, score)| match best {
+                Some((_, best_match, best_score)) if rank(best_match, best_score) >= rank(matched, score) => best,
+                _ => Some((value, matched, score)),
+            })
+    }
+}
+
+/// A supplier name close to another, left apart for review
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SupplierMatchReview {
+    pub name: String,
+    /// Supplier the name is close to
+    pub candidate_id: SupplierId,
+    pub candidate_name: String,
+    pub similarity: f64,
+    /// Whether the candidate is a supplier already on record rather than
+    /// one extracted from the BOMs
+    pub existing: bool,
+    pub source_rows: Vec<usize>,
+}
+
+/// A supplier name lowercased, without punctuation or legal forms
+pub fn normalize_supplier_name(name: &str) -> String {
+    let words: Vec<String> = name.to_lowercase()
+        .replace('&', " and ")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect();
+
+    // Keep names that are only a legal form, like "AG", as they are
+    let kept: Vec<&str> = words.iter()
+        .map(String::as_str)
+        .filter(|w| !LEGAL_FORMS.contains(w))
+        .collect();
+    if kept.is_empty() {
+        words.join(" ")
+    } else {
+        kept.join(" ")
+    }
+}
+
+/// Similarity of two normalized names, from 0 to 1
+pub fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+
+    let sorted = |name: &str| sorted_words(name).join(" ");
+    jaro_winkler(a, b).max(jaro_winkler(&sorted(a), &sorted(b)))
+}
+
+/// A name's distinct words, sorted to ignore word order
+fn sorted_words(name: &str) -> Vec<&str> {
+    let mut words: Vec<&str> = name.split(' ').collect();
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+/// Whether each word of one normalized name pairs off with its own word of
+/// the other, alike at least `threshold`, without pairing two different
+/// industry words
+fn words_line_up(a: &str, b: &str, threshold: f64) -> bool {
+    let (a, mut b) = (sorted_words(a), sorted_words(b));
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().all(|word| {
+        let paired = b.iter()
+            .enumerate()
+            .filter(|(_, other)| word == *other || !(INDUSTRY_WORDS.contains(word) && INDUSTRY_WORDS.contains(other)))
+            .map(|(idx, other)| (idx, jaro_winkler(word, other)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        match paired {
+            Some((idx, score)) if score >= threshold => {
+                b.swap_remove(idx);
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Jaro-Winkler similarity, boosting a common prefix of up to four characters
+///
+/// `strsim::jaro_winkler` doesn't cap the prefix, which scores a name and any
+/// longer name starting with it, like "acme" and "acme tools", as equal.
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let jaro = strsim::jaro(a, b);
+    let prefix = a.chars().zip(b.chars()).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(normalize_supplier_name("ACME Corporation"), "acme");
+        assert_eq!(normalize_supplier_name("Acme Corp. GmbH"), "acme");
+        assert_eq!(normalize_supplier_name("  Smith & Sons, Ltd."), "smith and sons");
+        assert_eq!(normalize_supplier_name("AG"), "ag");
+    }
+
+    fn compare(a: &str, b: &str) -> NameMatch {
+        SupplierMatcher::default().compare(&normalize_supplier_name(a), &normalize_supplier_name(b)).0
+    }
+
+    #[test]
+    fn test_similarity_classification() {
+        let matcher = SupplierMatcher::default();
+        let score = |a: &str, b: &str| similarity(&normalize_supplier_name(a), &normalize_supplier_name(b));
+
+        assert_eq!(matcher.classify(score("ACME Corporation", "Acme Corp GmbH")), NameMatch::Same);
+        assert_eq!(matcher.classify(score("Globex Industries", "Industries Globex Inc")), NameMatch::Same);
+        assert_eq!(matcher.classify(score("Initech Systems", "Initech Sytems")), NameMatch::Same);
+        assert_eq!(matcher.classify(score("Acme", "Acne")), NameMatch::Review);
+        assert_eq!(matcher.classify(score("Acme", "Acme Tools")), NameMatch::Review);
+        assert_eq!(matcher.classify(score("Acme", "Globex")), NameMatch::Distinct);
+    }
+
+    #[test]
+    fn test_compare_lines_up_words() {
+        assert_eq!(compare("ACME Corporation", "Acme Corp GmbH"), NameMatch::Same);
+        assert_eq!(compare("Globex Industries", "Industries Globex Inc"), NameMatch::Same);
+        assert_eq!(compare("Initech Systems", "Initech Sytems"), NameMatch::Same);
+        assert_eq!(compare("Acme", "Globex"), NameMatch::Distinct);
+    }
+
+    #[test]
+    fn test_distinct_companies_sharing_words_are_not_merged() {
+        // Different distinctive words around a shared industry word
+        assert_ne!(compare("NXP Semiconductors", "ON Semiconductors"), NameMatch::Same);
+        assert_ne!(compare("nxp semiconductors", "on semiconductors"), NameMatch::Same);
+        // The same name with a different industry word
+        assert_ne!(compare("Acme Electronics", "Acme Electric"), NameMatch::Same);
+        assert_ne!(compare("acme electronics", "acme electric"), NameMatch::Same);
+        assert_ne!(compare("Globex Chemicals", "Globex Chemical Systems"), NameMatch::Same);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let matcher = SupplierMatcher::default();
+        let candidates = [("acme", 1), ("acme tools", 2), ("globex", 3)];
+
+        assert_eq!(matcher.best_match("ACME Inc.", candidates).map(|(id, m, _)| (id, m)), Some((1, NameMatch::Same)));
+        assert_eq!(matcher.best_match("Hooli", candidates), None);
+
+        // A closer name that only goes to review does not hide the same company
+        let candidates = [("acme electric", 1), ("acme electronics", 2)];
+        let (id, matched, _) = matcher.best_match("ACME Electronics Ltd", candidates).unwrap();
+        assert_eq!((id, matched), (2, NameMatch::Same));
+        let (_, matched, _) = matcher.best_match("Acme Electric", [("acme electronics", 1)]).unwrap();
+        assert_eq!(matched, NameMatch::Review);
+    }
+}
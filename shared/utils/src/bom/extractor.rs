@@ -1,22 +1,32 @@
 //! Supplier Extractor
 //! 
-//! Extracts and deduplicates suppliers from parsed BOM data.
+//! Extracts and deduplicates suppliers from parsed BOM data. Rows name the
+//! same supplier when their names match after normalizing, or are alike
+//! enough by the extractor's `SupplierMatcher`; close calls are listed for
+//! review instead of merged. Suppliers can be matched across several BOMs
+//! and against the suppliers already on record.
 
 use std::collections::HashMap;
-use uuid::Uuid;
 
+use super::dedup::{normalize_supplier_name, NameMatch, SupplierMatchReview, SupplierMatcher};
 use super::parser::{ParsedBom, BomRow};
-use elementa_models::{SupplierRecord, ContactInfo};
+use elementa_models::{SupplierRecord, SupplierId, ContactInfo};
 
 /// Extracted supplier with associated components
 #[derive(Debug, Clone)]
 pub struct ExtractedSupplier {
-    pub id: Uuid,
+    pub id: SupplierId,
     pub name: String,
     pub email: Option<String>,
     pub contact_person: Option<String>,
     pub components: Vec<ExtractedComponent>,
     pub source_rows: Vec<usize>,
+    /// BOM files the supplier appears in
+    pub source_files: Vec<String>,
+    /// Other spellings of the name merged into this supplier
+    pub aliases: Vec<String>,
+    /// Supplier on record this one matched, whose id it takes
+    pub existing_supplier: Option<SupplierId>,
     pub is_complete: bool,
     pub missing_fields: Vec<String>,
 }
@@ -38,6 +48,8 @@ pub struct ExtractionResult {
     pub complete_count: usize,
     pub incomplete_count: usize,
     pub duplicate_count: usize,
+    /// Suppliers close to another but not merged
+    pub review: Vec<SupplierMatchReview>,
     pub warnings: Vec<String>,
 }
 
@@ -47,6 +59,10 @@ pub struct SupplierExtractor {
     require_email: bool,
     /// Require contact person
     require_contact: bool,
+    /// When two supplier names are the same supplier
+    matcher: SupplierMatcher,
+    /// Suppliers on record, with their normalized names
+    known_suppliers: Vec<(SupplierId, String, String)>,
 }
 
 impl Default for SupplierExtractor {
@@ -54,6 +70,8 @@ impl Default for SupplierExtractor {
         Self {
             require_email: true,
             require_contact: false,
+            matcher: SupplierMatcher::default(),
+            known_suppliers: Vec::new(),
         }
     }
 }
@@ -75,32 +93,72 @@ impl SupplierExtractor {
         self
     }
     
+    /// Configure the similarity thresholds for merging and reviewing names
+    pub fn with_matcher(mut self, matcher: SupplierMatcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+    
+    /// Match extracted suppliers against suppliers already on record
+    pub fn with_known_suppliers(mut self, suppliers: &[SupplierRecord]) -> Self {
+        self.known_suppliers = suppliers.iter()
+            .map(|s| (s.id, s.name.clone(), normalize_supplier_name(&s.name)))
+            .collect();
+        self
+    }
+    
     /// Extract and deduplicate suppliers from parsed BOM
     pub fn extract(&self, bom: &ParsedBom) -> ExtractionResult {
-        let mut supplier_map: HashMap<String, ExtractedSupplier> = HashMap::new();
+        self.extract_all(std::slice::from_ref(bom))
+    }
+    
+    /// Extract suppliers from several BOMs, deduplicated across all of them
+    pub fn extract_all(&self, boms: &[ParsedBom]) -> ExtractionResult {
+        let mut suppliers: Vec<ExtractedSupplier> = Vec::new();
+        // Normalized names seen, with the supplier each was merged into
+        let mut names: Vec<(String, usize)> = Vec::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
         let mut warnings = Vec::new();
         let mut duplicate_count = 0;
         
-        for row in &bom.rows {
+        for (bom, row) in boms.iter().flat_map(|bom| bom.rows.iter().map(move |row| (bom, row))) {
             // Skip rows without supplier name
             let supplier_name = match &row.supplier_name {
                 Some(name) if !name.is_empty() => name.clone(),
                 _ => {
-                    warnings.push(format!("Row {}: Missing supplier name, skipped", row.row_number));
+                    let file = if boms.len() > 1 { format!("{} ", bom.filename) } else { String::new() };
+                    warnings.push(format!("{}Row {}: Missing supplier name, skipped", file, row.row_number));
                     continue;
                 }
             };
             
             // Normalize supplier name for deduplication
-            let normalized_name = self.normalize_supplier_name(&supplier_name);
+            let normalized_name = normalize_supplier_name(&supplier_name);
             
             // Extract component data
             let component = self.extract_component(row);
             
-            if let Some(existing) = supplier_map.get_mut(&normalized_name) {
+            let matched = by_name.get(&normalized_name).copied().or_else(|| {
+                self.matcher.best_match(&supplier_name, names.iter().map(|(name, idx)| (name.as_str(), *idx)))
+                    .filter(|(_, matched, _)| *matched == NameMatch::Same)
+                    .map(|(idx, _, _)| idx)
+            });
+            
+            if let Some(idx) = matched {
                 // Deduplicate - merge component into existing supplier
+                let existing = &mut suppliers[idx];
                 duplicate_count += 1;
                 existing.source_rows.push(row.row_number);
+                if !existing.source_files.contains(&bom.filename) {
+                    existing.source_files.push(bom.filename.clone());
+                }
+                if !by_name.contains_key(&normalized_name) {
+                    by_name.insert(normalized_name.clone(), idx);
+                    names.push((normalized_name, idx));
+                }
+                if existing.name != supplier_name && !existing.aliases.contains(&supplier_name) {
+                    existing.aliases.push(supplier_name);
+                }
                 
                 if let Some(comp) = component {
                     existing.components.push(comp);
@@ -127,21 +185,26 @@ impl SupplierExtractor {
                 let is_complete = missing_fields.is_empty();
                 
                 let supplier = ExtractedSupplier {
-                    id: Uuid::new_v4(),
+                    id: SupplierId::new(),
                     name: supplier_name.clone(),
                     email: row.supplier_email.clone(),
                     contact_person: row.contact_person.clone(),
                     components: component.into_iter().collect(),
                     source_rows: vec![row.row_number],
+                    source_files: vec![bom.filename.clone()],
+                    aliases: Vec::new(),
+                    existing_supplier: None,
                     is_complete,
                     missing_fields,
                 };
                 
-                supplier_map.insert(normalized_name, supplier);
+                by_name.insert(normalized_name.clone(), suppliers.len());
+                names.push((normalized_name, suppliers.len()));
+                suppliers.push(supplier);
             }
         }
         
-        let suppliers: Vec<ExtractedSupplier> = supplier_map.into_values().collect();
+        let review = self.match_suppliers(&mut suppliers);
         let complete_count = suppliers.iter().filter(|s| s.is_complete).count();
         let incomplete_count = suppliers.len() - complete_count;
         
@@ -150,10 +213,58 @@ impl SupplierExtractor {
             complete_count,
             incomplete_count,
             duplicate_count,
+            review,
             warnings,
         }
     }
     
+    /// Give suppliers matching one on record its id, and list the pairs
+    /// close enough to review
+    fn match_suppliers(&self, suppliers: &mut [ExtractedSupplier]) -> Vec<SupplierMatchReview> {
+        let mut review = Vec::new();
+        
+        for supplier in suppliers.iter_mut() {
+            let known = self.matcher.best_match(
+                &supplier.name,
+                self.known_suppliers.iter().map(|(id, name, normal)| (normal.as_str(), (*id, name))),
+            );
+            match known {
+                Some(((id, _), NameMatch::Same, _)) => {
+                    supplier.id = id;
+                    supplier.existing_supplier = Some(id);
+                }
+                Some(((id, name), _, similarity)) => review.push(SupplierMatchReview {
+                    name: supplier.name.clone(),
+                    candidate_id: id,
+                    candidate_name: name.clone(),
+                    similarity,
+                    existing: true,
+                    source_rows: supplier.source_rows.clone(),
+                }),
+                None => {}
+            }
+        }
+        
+        let normalized: Vec<String> = suppliers.iter().map(|s| normalize_supplier_name(&s.name)).collect();
+        for (idx, supplier) in suppliers.iter().enumerate() {
+            for (earlier, candidate) in suppliers[..idx].iter().enumerate() {
+                let (matched, score) = self.matcher.compare(&normalized[idx], &normalized[earlier]);
+                if matched == NameMatch::Review {
+                    review.push(SupplierMatchReview {
+                        name: supplier.name.clone(),
+                        candidate_id: candidate.id,
+                        candidate_name: candidate.name.clone(),
+                        similarity: score,
+                        existing: false,
+                        source_rows: supplier.source_rows.clone(),
+                    });
+                }
+            }
+        }
+        
+        review
+    }
+    
     /// Convert extracted suppliers to domain model records
     pub fn to_supplier_records(&self, extraction: &ExtractionResult) -> Vec<SupplierRecord> {
         extraction.suppliers.iter()
@@ -185,28 +296,13 @@ impl SupplierExtractor {
             source_row: row.row_number,
         })
     }
-    
-    /// Normalize supplier name for deduplication
-    fn normalize_supplier_name(&self, name: &str) -> String {
-        // Convert to lowercase, remove common suffixes, normalize whitespace
-        let mut normalized = name.to_lowercase();
-        
-        // Remove common company suffixes
-        for suffix in &[" inc", " inc.", " llc", " ltd", " ltd.", " corp", " corp.", " co", " co."] {
-            if normalized.ends_with(suffix) {
-                normalized = normalized[..normalized.len() - suffix.len()].to_string();
-            }
-        }
-        
-        // Normalize whitespace
-        normalized.split_whitespace().collect::<Vec<_>>().join(" ")
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bom::parser::BomFormat;
+    use uuid::Uuid;
     
     #[test]
     fn test_supplier_deduplication() {
@@ -223,6 +319,8 @@ mod tests {
                     part_number: Some("PN-001".to_string()),
                     description: Some("Widget".to_string()),
                     material_type: None,
+                    quantity: None,
+                    reference_designators: vec![],
                     cas_numbers: vec![],
                     raw_data: Default::default(),
                 },
@@ -234,11 +332,14 @@ mod tests {
                     part_number: Some("PN-002".to_string()),
                     description: Some("Gadget".to_string()),
                     material_type: None,
+                    quantity: None,
+                    reference_designators: vec![],
                     cas_numbers: vec![],
                     raw_data: Default::default(),
                 },
             ],
             column_headers: vec![],
+            column_mapping: vec![],
             total_rows: 2,
             parse_warnings: vec![],
         };
@@ -252,4 +353,86 @@ mod tests {
         assert_eq!(result.suppliers[0].components.len(), 2);
         assert_eq!(result.duplicate_count, 1);
     }
+    
+    fn bom(filename: &str, suppliers: &[&str]) -> ParsedBom {
+        let rows: Vec<BomRow> = suppliers.iter()
+            .enumerate()
+            .map(|(idx, name)| BomRow {
+                row_number: idx + 2,
+                supplier_name: Some(name.to_string()),
+                supplier_email: None,
+                contact_person: None,
+                part_number: Some(format!("PN-{:03}", idx)),
+                description: None,
+                material_type: None,
+                quantity: None,
+                reference_designators: vec![],
+                cas_numbers: vec![],
+                raw_data: Default::default(),
+            })
+            .collect();
+        ParsedBom {
+            id: Uuid::new_v4(),
+            filename: filename.to_string(),
+            format: BomFormat::Csv,
+            total_rows: rows.len(),
+            rows,
+            column_headers: vec![],
+            column_mapping: vec![],
+            parse_warnings: vec![],
+        }
+    }
+    
+    #[test]
+    fn test_fuzzy_deduplication_across_files() {
+        let boms = [
+            bom("a.csv", &["ACME Corporation", "Initech Systems"]),
+            bom("b.csv", &["Acme Corp GmbH", "Initech Sytems", "Acne"]),
+        ];
+        
+        let result = SupplierExtractor::new().extract_all(&boms);
+        
+        let names: Vec<&str> = result.suppliers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["ACME Corporation", "Initech Systems", "Acne"]);
+        assert_eq!(result.suppliers[0].aliases, ["Acme Corp GmbH"]);
+        assert_eq!(result.suppliers[0].source_files, ["a.csv", "b.csv"]);
+        assert_eq!(result.suppliers[1].components.len(), 2);
+        assert_eq!(result.duplicate_count, 2);
+        
+        // Close, but left for review
+        assert_eq!(result.review.len(), 1);
+        assert_eq!(result.review[0].name, "Acne");
+        assert_eq!(result.review[0].candidate_name, "ACME Corporation");
+        assert_eq!(result.review[0].candidate_id, result.suppliers[0].id);
+        assert!(!result.review[0].existing);
+    }
+    
+    #[test]
+    fn test_deduplication_against_known_suppliers() {
+        let known = SupplierRecord { name: "Globex Industries Inc.".to_string(), ..Default::default() };
+        let extractor = SupplierExtractor::new()
+            .with_known_suppliers(std::slice::from_ref(&known))
+            .with_matcher(SupplierMatcher::new(0.99, 0.9));
+        
+        let result = extractor.extract(&bom("a.csv", &["Globex Industries", "Globex Industry"]));
+        
+        assert_eq!(result.suppliers.len(), 2);
+        assert_eq!(result.suppliers[0].id, known.id);
+        assert_eq!(result.suppliers[0].existing_supplier, Some(known.id));
+        assert_eq!(result.suppliers[1].existing_supplier, None);
+        assert!(result.review.iter().any(|r| r.existing && r.name == "Globex Industry" && r.candidate_id == known.id));
+    }
+    
+    #[test]
+    fn test_alike_names_of_different_companies_go_to_review() {
+        let result = SupplierExtractor::new()
+            .extract(&bom("a.csv", &["NXP Semiconductors", "ON Semiconductors", "Acme Electronics", "Acme Electric"]));
+        
+        assert_eq!(result.suppliers.len(), 4);
+        assert_eq!(result.duplicate_count, 0);
+        let reviewed: Vec<(&str, &str)> = result.review.iter()
+            .map(|r| (r.name.as_str(), r.candidate_name.as_str()))
+            .collect();
+        assert!(reviewed.contains(&("Acme Electric", "Acme Electronics")));
+    }
 }
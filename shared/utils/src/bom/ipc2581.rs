@@ -0,0 +1,378 @@
+//! IPC-2581 BOM Parser
+//!
+//! Reads the bill of materials from an IPC-2581 design file. Each `BomItem`
+//! becomes a row: its `OEMDesignNumberRef` is the part number, `quantity`
+//! and the `RefDes` children give the quantity and reference designators.
+//! The manufacturer comes from the approved vendor list: the `AvlVmpn`
+//! marked chosen (else the best ranked) names the manufacturer part number
+//! and, through `AvlVendor`, the `Enterprise` that makes it. A `Person` of
+//! that enterprise in the logistic header supplies the contact and email.
+
+use anyhow::{bail, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::parser::{BomFormat, BomRow, ParsedBom};
+
+/// Root element of an IPC-2581 file
+const ROOT_ELEMENT: &[u8] = b"IPC-2581";
+
+/// Columns of the rows produced, in the order reported
+const COLUMNS: &[&str] = &[
+    "bom",
+    "part_number",
+    "internal_part_number",
+    "description",
+    "category",
+    "quantity",
+    "reference_designators",
+    "manufacturer",
+    "manufacturer_part_number",
+    "contact",
+    "email",
+];
+
+/// Whether the document's root element is `IPC-2581`
+pub fn is_ipc2581(data: &[u8]) -> bool {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => return e.local_name().as_ref() == ROOT_ELEMENT,
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+#[derive(Debug, Default)]
+struct BomItem {
+    bom: String,
+    part_number: Option<String>,
+    internal_part_number: Option<String>,
+    description: Option<String>,
+    category: Option<String>,
+    quantity: Option<String>,
+    reference_designators: Vec<String>,
+}
+
+/// One manufacturer part approved for a design part
+#[derive(Debug, Default)]
+struct ApprovedPart {
+    chosen: bool,
+    rank: Option<u32>,
+    manufacturer_part_number: Option<String>,
+    enterprise: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Contact {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// Parse the BOM sections of an IPC-2581 file
+pub fn parse(filename: &str, data: &[u8]) -> Result<ParsedBom> {
+    let mut reader = Reader::from_reader(data);
+    reader.trim_text(true);
+
+    let mut items: Vec<BomItem> = Vec::new();
+    let mut approved: HashMap<String, Vec<ApprovedPart>> = HashMap::new();
+    let mut enterprises: HashMap<String, String> = HashMap::new();
+    let mut contacts: HashMap<String, Contact> = HashMap::new();
+    let mut warnings = Vec::new();
+
+    let mut root_seen = false;
+    let mut bom_name = String::new();
+    let mut item: Option<BomItem> = None;
+    let mut avl_part: Option<String> = None;
+    let mut vmpn: Option<ApprovedPart> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            Err(e) => {
+                warnings.push(format!("XML parse error at byte {}: {}", reader.buffer_position(), e));
+                break;
+            }
+        };
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let attrs = attributes(e);
+                match e.local_name().as_ref() {
+                    ROOT_ELEMENT => root_seen = true,
+                    _ if !root_seen => bail!("Not an IPC-2581 file"),
+                    b"Bom" => bom_name = attrs.get("name").cloned().unwrap_or_default(),
+                    b"BomItem" => {
+                        let started = BomItem {
+                            bom: bom_name.clone(),
+                            part_number: attrs.get("OEMDesignNumberRef").cloned(),
+                            internal_part_number: attrs.get("internalPartNumber").cloned(),
+                            description: attrs.get("description").cloned(),
+                            category: attrs.get("category").cloned(),
+                            quantity: attrs.get("quantity").cloned(),
+                            reference_designators: Vec::new(),
+                        };
+                        if empty {
+                            items.push(started);
+                        } else {
+                            item = Some(started);
+                        }
+                    }
+                    b"RefDes" => {
+                        if let (Some(item), Some(name)) = (item.as_mut(), attrs.get("name")) {
+                            item.reference_designators.push(name.clone());
+                        }
+                    }
+                    b"AvlItem" if !empty => avl_part = attrs.get("OEMDesignNumber").cloned(),
+                    b"AvlVmpn" if !empty => vmpn = Some(ApprovedPart {
+                        chosen: attrs.get("chosen").is_some_and(|v| v == "true"),
+                        rank: None,
+                        manufacturer_part_number: None,
+                        enterprise: None,
+                    }),
+                    b"AvlMpn" => {
+                        if let Some(vmpn) = vmpn.as_mut() {
+                            vmpn.manufacturer_part_number = attrs.get("name").cloned();
+                            vmpn.rank = attrs.get("rank").and_then(|r| r.parse().ok());
+                        }
+                    }
+                    b"AvlVendor" => {
+                        if let Some(vmpn) = vmpn.as_mut() {
+                            vmpn.enterprise = attrs.get("enterpriseRef").cloned();
+                        }
+                    }
+                    b"Enterprise" => {
+                        if let Some(id) = attrs.get("id") {
+                            let name = attrs.get("name").unwrap_or(id);
+                            enterprises.insert(id.clone(), name.clone());
+                        }
+                    }
+                    b"Person" => {
+                        if let Some(enterprise) = attrs.get("enterpriseRef") {
+                            // The first person listed for an enterprise is its contact
+                            contacts.entry(enterprise.clone()).or_insert_with(|| Contact {
+                                name: attrs.get("name").cloned(),
+                                email: attrs.get("email").cloned(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(ref e) => match e.local_name().as_ref() {
+                b"BomItem" => items.extend(item.take()),
+                b"AvlVmpn" => {
+                    if let (Some(part), Some(vmpn)) = (avl_part.as_ref(), vmpn.take()) {
+                        approved.entry(part.clone()).or_default().push(vmpn);
+                    }
+                }
+                b"AvlItem" => avl_part = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if !root_seen {
+        bail!("Not an IPC-2581 file");
+    }
+    if items.is_empty() {
+        warnings.push("IPC-2581 file has no BomItem entries".to_string());
+    }
+
+    let mut rows = Vec::with_capacity(items.len());
+    for (idx, item) in items.into_iter().enumerate() {
+        let row_number = idx + 1;
+        let label = item.part_number.clone().unwrap_or_else(|| format!("#{}", row_number));
+        if item.part_number.is_none() {
+            warnings.push(format!("BomItem {}: missing OEMDesignNumberRef", row_number));
+        }
+
+        let quantity = match item.quantity.as_deref().map(str::trim) {
+            Some(q) => match q.parse::<f64>() {
+                Ok(q) if q >= 0.0 && q.fract() == 0.0 => Some(q as u32),
+                _ => {
+                    warnings.push(format!("BomItem {}: invalid quantity '{}'", label, q));
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(quantity) = quantity {
+            let placed = item.reference_designators.len();
+            if placed > 0 && placed != quantity as usize {
+                warnings.push(format!(
+                    "BomItem {}: quantity {} but {} reference designators",
+                    label, quantity, placed
+                ));
+            }
+        }
+
+        let part = item.part_number.as_ref()
+            .and_then(|pn| approved.get(pn))
+            .and_then(|parts| preferred(parts));
+        let enterprise = part.and_then(|p| p.enterprise.as_ref());
+        let manufacturer = enterprise.map(|id| enterprises.get(id).unwrap_or(id).clone());
+        let contact = enterprise.and_then(|id| contacts.get(id));
+
+        let mut raw_data = HashMap::new();
+        let mut put = |column: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+                raw_data.insert(column.to_string(), value.trim().to_string());
+            }
+        };
+        put("bom", Some(item.bom));
+        put("part_number", item.part_number.clone());
+        put("internal_part_number", item.internal_part_number);
+        put("description", item.description.clone());
+        put("category", item.category.clone());
+        put("quantity", item.quantity);
+        put("reference_designators", Some(item.reference_designators.join(", ")));
+        put("manufacturer", manufacturer.clone());
+        put("manufacturer_part_number", part.and_then(|p| p.manufacturer_part_number.clone()));
+        put("contact", contact.and_then(|c| c.name.clone()));
+        put("email", contact.and_then(|c| c.email.clone()));
+
+        rows.push(BomRow {
+            row_number,
+            supplier_name: raw_data.get("manufacturer").cloned(),
+            supplier_email: raw_data.get("email").cloned(),
+            contact_person: raw_data.get("contact").cloned(),
+            part_number: raw_data.get("part_number").cloned(),
+            description: raw_data.get("description").cloned(),
+            material_type: raw_data.get("category").cloned(),
+            quantity,
+            reference_designators: item.reference_designators,
+            cas_numbers: Vec::new(),
+            raw_data,
+        });
+    }
+
+    Ok(ParsedBom {
+        id: Uuid::new_v4(),
+        filename: filename.to_string(),
+        format: BomFormat::Ipc2581,
+        total_rows: rows.len(),
+        rows,
+        column_headers: COLUMNS.iter().map(|c| c.to_string()).collect(),
+        column_mapping: Vec::new(),
+        parse_warnings: warnings,
+    })
+}
+
+/// The part chosen for the design, else the best ranked, else the first listed
+fn preferred(parts: &[ApprovedPart]) -> Option<&ApprovedPart> {
+    parts.iter()
+        .find(|p| p.chosen)
+        .or_else(|| parts.iter().filter(|p| p.rank.is_some()).min_by_key(|p| p.rank))
+        .or_else(|| parts.first())
+}
+
+/// An element's attributes by local name
+fn attributes(e: &BytesStart) -> HashMap<String, String> {
+    e.attributes()
+        .flatten()
+        .filter_map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            attr.unescape_value().ok().map(|value| (key, value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<IPC-2581 revision="C" xmlns="http://webstds.ipc.org/2581">
+  <LogisticHeader>
+    <Role id="Owner" roleFunction="OWNER"/>
+    <Enterprise id="YAGEO" name="Yageo Corporation" code="YAG"/>
+    <Enterprise id="MURATA" name="Murata Manufacturing" code="MUR"/>
+    <Person name="Lin Chen" enterpriseRef="YAGEO" email="compliance@yageo.example" roleRef="Owner"/>
+  </LogisticHeader>
+  <Bom name="MAIN_BOARD">
+    <BomHeader assembly="MAIN_BOARD" revision="B"/>
+    <BomItem OEMDesignNumberRef="RES-10K-0603" quantity="3" category="ELECTRICAL" description="Resistor 10k 1% 0603">
+      <RefDes name="R1" packageRef="0603" populate="true" layerRef="TOP"/>
+      <RefDes name="R2" packageRef="0603" populate="true" layerRef="TOP"/>
+      <RefDes name="R7" packageRef="0603" populate="true" layerRef="BOTTOM"/>
+    </BomItem>
+    <BomItem OEMDesignNumberRef="CAP-100N-0402" quantity="2" category="ELECTRICAL" description="Capacitor 100nF X7R">
+      <RefDes name="C1" packageRef="0402" populate="true" layerRef="TOP"/>
+    </BomItem>
+    <BomItem OEMDesignNumberRef="MECH-STANDOFF" quantity="4" category="MECHANICAL"/>
+  </Bom>
+  <Avl name="AVL">
+    <AvlHeader title="Approved vendors" source="PLM" author="eng" datetime="2026-01-05T00:00:00" version="1"/>
+    <AvlItem OEMDesignNumber="RES-10K-0603">
+      <AvlVmpn qualified="true" chosen="false">
+        <AvlMpn name="ERJ-3EKF1002V" rank="2"/>
+        <AvlVendor enterpriseRef="PANASONIC"/>
+      </AvlVmpn>
+      <AvlVmpn qualified="true" chosen="true">
+        <AvlMpn name="RC0603FR-0710KL" rank="1"/>
+        <AvlVendor enterpriseRef="YAGEO"/>
+      </AvlVmpn>
+    </AvlItem>
+    <AvlItem OEMDesignNumber="CAP-100N-0402">
+      <AvlVmpn qualified="true">
+        <AvlMpn name="GRM155R71C104KA88D" rank="1"/>
+        <AvlVendor enterpriseRef="MURATA"/>
+      </AvlVmpn>
+    </AvlItem>
+  </Avl>
+</IPC-2581>"#;
+
+    #[test]
+    fn test_detects_ipc2581_root() {
+        assert!(is_ipc2581(SAMPLE.as_bytes()));
+        assert!(!is_ipc2581(b"<?xml version=\"1.0\"?><bom><item/></bom>"));
+        assert!(!is_ipc2581(b"not xml"));
+    }
+
+    #[test]
+    fn test_bom_items_with_approved_manufacturers() {
+        let bom = parse("board.xml", SAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(bom.format, BomFormat::Ipc2581);
+        assert_eq!(bom.total_rows, 3);
+
+        let resistor = &bom.rows[0];
+        assert_eq!(resistor.part_number.as_deref(), Some("RES-10K-0603"));
+        assert_eq!(resistor.quantity, Some(3));
+        assert_eq!(resistor.reference_designators, ["R1", "R2", "R7"]);
+        assert_eq!(resistor.supplier_name.as_deref(), Some("Yageo Corporation"));
+        assert_eq!(resistor.supplier_email.as_deref(), Some("compliance@yageo.example"));
+        assert_eq!(resistor.contact_person.as_deref(), Some("Lin Chen"));
+        assert_eq!(resistor.raw_data["manufacturer_part_number"], "RC0603FR-0710KL");
+        assert_eq!(resistor.raw_data["bom"], "MAIN_BOARD");
+
+        let capacitor = &bom.rows[1];
+        assert_eq!(capacitor.supplier_name.as_deref(), Some("Murata Manufacturing"));
+        assert_eq!(capacitor.raw_data["manufacturer_part_number"], "GRM155R71C104KA88D");
+        assert_eq!(capacitor.supplier_email, None);
+
+        let standoff = &bom.rows[2];
+        assert_eq!(standoff.quantity, Some(4));
+        assert!(standoff.reference_designators.is_empty());
+        assert_eq!(standoff.supplier_name, None);
+        assert_eq!(standoff.material_type.as_deref(), Some("MECHANICAL"));
+
+        assert_eq!(bom.parse_warnings, ["BomItem CAP-100N-0402: quantity 2 but 1 reference designators"]);
+    }
+
+    #[test]
+    fn test_rejects_other_xml() {
+        assert!(parse("bom.xml", b"<bom><item><part_number>X</part_number></item></bom>").is_err());
+    }
+}
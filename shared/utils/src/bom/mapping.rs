@@ -0,0 +1,328 @@
+//! BOM Column Detection
+//!
+//! Decides which column holds each BOM field. A client's `ColumnMapping`
+//! is taken as given; other fields are matched against the parser's header
+//! aliases. Headers are tried as written, then normalized so punctuation
+//! and spacing don't matter ("Supplier Name:" is `supplier_name`), then by
+//! the words they hold ("Supplier (Primary)" holds `supplier`), and last by
+//! edit distance to catch typos ("Suplier"). Each match is reported with a
+//! confidence so uploads can show what was guessed, and a header that fits
+//! several fields about equally well is reported as ambiguous.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use elementa_models::{BomField, ColumnMapping};
+
+/// Confidence of a header equal to an alias
+pub const ALIAS_CONFIDENCE: f32 = 0.95;
+/// Confidence of a header equal to an alias once normalized
+pub const NORMALIZED_CONFIDENCE: f32 = 0.9;
+/// Confidence of a header holding every word of an alias and nothing else;
+/// extra words lower it towards `PARTIAL_MIN_CONFIDENCE`
+pub const PARTIAL_MAX_CONFIDENCE: f32 = 0.85;
+pub const PARTIAL_MIN_CONFIDENCE: f32 = 0.5;
+/// Least edit-distance similarity of a header to an alias to match it
+pub const FUZZY_MIN_SIMILARITY: f64 = 0.8;
+/// Confidence of a fuzzy match, scaled by its similarity
+pub const FUZZY_MAX_CONFIDENCE: f32 = 0.8;
+/// Matches this close to the one taken are reported as ambiguous
+pub const AMBIGUITY_MARGIN: f32 = 0.05;
+
+/// How a column was matched to a field
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    /// Named by the column mapping
+    Explicit,
+    /// Header equal to one of the field's aliases
+    Alias,
+    /// Header equal to one of the field's aliases once normalized
+    Normalized,
+    /// Header holding the words of one of the field's aliases
+    Partial,
+    /// Header within a small edit distance of one of the field's aliases
+    Fuzzy,
+}
+
+/// The column a field is read from
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DetectedColumn {
+    pub field: BomField,
+    pub column: String,
+    /// From 0 to 1, 1 being a column named by the mapping
+    pub confidence: f32,
+    pub source: MatchSource,
+}
+
+/// Columns chosen for each field of a BOM file
+#[derive(Debug, Clone, Default)]
+pub struct ColumnDetection {
+    /// One entry per field found, in field order
+    pub detected: Vec<DetectedColumn>,
+    /// Columns each field is read from, the first non-empty value winning
+    pub columns: HashMap<BomField, Vec<String>>,
+    /// Headers no field is read from
+    pub unmatched: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ColumnDetection {
+    /// Columns `field` is read from
+    pub fn columns(&self, field: BomField) -> &[String] {
+        self.columns.get(&field).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The mapping table, one line per field found, e.g.
+    /// `part_number <- 'part no.' (normalized, 0.90)`
+    pub fn table(&self) -> Vec<String> {
+        self.detected.iter()
+            .map(|d| format!(
+                "{} <- '{}' ({}, {:.2})",
+                field_name(d.field),
+                d.column,
+                serde_json::to_string(&d.source).unwrap_or_default().trim_matches('"'),
+                d.confidence
+            ))
+            .collect()
+    }
+}
+
+/// Match `headers` to fields, taking `mapping`'s columns as given and
+/// otherwise the field's `aliases`
+pub fn detect<'a>(
+    headers: &[String],
+    aliases: impl Fn(BomField) -> &'a [String],
+    mapping: Option<&ColumnMapping>,
+) -> ColumnDetection {
+    let mut detection = ColumnDetection::default();
+    let mut claimed: HashSet<&str> = HashSet::new();
+    let mut found: HashMap<BomField, DetectedColumn> = HashMap::new();
+    let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+
+    if let Some(mapping) = mapping {
+        for (field, column) in BomField::ALL.iter().filter_map(|f| mapping.column(*f).map(|c| (*f, c))) {
+            let wanted = normalize_header(&column);
+            match headers.iter().zip(&normalized).find(|(h, n)| **h == column || **n == wanted) {
+                Some((header, _)) => {
+                    claimed.insert(header.as_str());
+                    detection.columns.insert(field, vec![header.clone()]);
+                    found.insert(field, DetectedColumn { field, column: header.clone(), confidence: 1.0, source: MatchSource::Explicit });
+                }
+                None => detection.warnings.push(format!(
+                    "Column mapping reads {} from '{}', which the file does not have",
+                    field_name(field),
+                    column
+                )),
+            }
+        }
+    }
+
+    // Headers equal to an alias, as written or normalized, keeping every
+    // alias column so a row missing one value falls back to the next
+    for field in BomField::ALL {
+        if found.contains_key(&field) {
+            continue;
+        }
+        let mut exact: Vec<(&String, MatchSource)> = Vec::new();
+        for alias in aliases(field) {
+            for (header, normal) in headers.iter().zip(&normalized) {
+                if claimed.contains(header.as_str()) || exact.iter().any(|(h, _)| *h == header) {
+                    continue;
+                }
+                if header == alias {
+                    exact.push((header, MatchSource::Alias));
+                } else if normal == alias {
+                    exact.push((header, MatchSource::Normalized));
+                }
+            }
+        }
+        if let Some((first, source)) = exact.first() {
+            let confidence = if *source == MatchSource::Alias { ALIAS_CONFIDENCE } else { NORMALIZED_CONFIDENCE };
+            found.insert(field, DetectedColumn { field, column: (*first).clone(), confidence, source: *source });
+            claimed.extend(exact.iter().map(|(h, _)| h.as_str()));
+            detection.columns.insert(field, exact.into_iter().map(|(h, _)| h.clone()).collect());
+        }
+    }
+
+    // Headers holding an alias's words or close to it, best matches first
+    let mut candidates: Vec<(f32, BomField, &String, MatchSource)> = Vec::new();
+    for field in BomField::ALL.into_iter().filter(|f| !found.contains_key(f)) {
+        for (header, normal) in headers.iter().zip(&normalized).filter(|(h, _)| !claimed.contains(h.as_str())) {
+            let best = aliases(field).iter()
+                .filter_map(|alias| {
+                    word_match(normal, alias).map(|score| (score, MatchSource::Partial))
+                        .or_else(|| fuzzy_match(normal, alias).map(|score| (score, MatchSource::Fuzzy)))
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((score, source)) = best {
+                candidates.push((score, field, header, source));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut resolved: HashSet<BomField> = found.keys().copied().collect();
+    for &(confidence, field, header, source) in &candidates {
+        if resolved.contains(&field) || claimed.contains(header.as_str()) {
+            continue;
+        }
+
+        let close = |other: &&(f32, BomField, &String, MatchSource)| confidence - other.0 <= AMBIGUITY_MARGIN;
+        let rival_fields: Vec<String> = candidates.iter()
+            .filter(|c| c.2 == header && c.1 != field && !resolved.contains(&c.1))
+            .filter(close)
+            .map(|c| field_name(c.1))
+            .collect();
+        if !rival_fields.is_empty() {
+            detection.warnings.push(format!(
+                "Column '{}' could hold {} or {}; reading it as {}",
+                header,
+                field_name(field),
+                rival_fields.join(" or "),
+                field_name(field)
+            ));
+        }
+        let rival_headers: Vec<String> = candidates.iter()
+            .filter(|c| c.1 == field && c.2 != header && !claimed.contains(c.2.as_str()))
+            .filter(close)
+            .map(|c| format!("'{}'", c.2))
+            .collect();
+        if !rival_headers.is_empty() {
+            detection.warnings.push(format!(
+                "{} could be read from '{}' or {}; reading '{}'",
+                field_name(field),
+                header,
+                rival_headers.join(" or "),
+                header
+            ));
+        }
+
+        claimed.insert(header.as_str());
+        resolved.insert(field);
+        detection.columns.insert(field, vec![header.clone()]);
+        found.insert(field, DetectedColumn { field, column: header.clone(), confidence, source });
+    }
+
+    detection.detected = BomField::ALL.iter().filter_map(|f| found.remove(f)).collect();
+    detection.unmatched = headers.iter()
+        .filter(|h| !h.is_empty() && !claimed.contains(h.as_str()))
+        .cloned()
+        .collect();
+    detection
+}
+
+/// A header lowercased, with every run of characters other than letters
+/// and digits turned into one `_`, e.g. `"Supplier Name:"` to `supplier_name`
+pub fn normalize_header(header: &str) -> String {
+    header.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Confidence that normalized `header` names `alias`, when it holds all of the alias's words
+fn word_match(header: &str, alias: &str) -> Option<f32> {
+    let words: Vec<&str> = header.split('_').filter(|w| !w.is_empty()).collect();
+    let alias_words: Vec<&str> = alias.split('_').filter(|w| !w.is_empty()).collect();
+    if words.is_empty() || alias_words.is_empty() || !alias_words.iter().all(|w| words.contains(w)) {
+        return None;
+    }
+
+    let coverage = alias_words.len() as f32 / words.len() as f32;
+    Some(PARTIAL_MIN_CONFIDENCE + (PARTIAL_MAX_CONFIDENCE - PARTIAL_MIN_CONFIDENCE) * coverage)
+}
+
+/// Confidence that normalized `header` is a misspelling of `alias`
+fn fuzzy_match(header: &str, alias: &str) -> Option<f32> {
+    let similarity = strsim::normalized_levenshtein(header, alias);
+    (similarity >= FUZZY_MIN_SIMILARITY).then_some(FUZZY_MAX_CONFIDENCE * similarity as f32)
+}
+
+/// A field's name as it is written in column mappings
+pub fn field_name(field: BomField) -> String {
+    serde_json::to_string(&field).unwrap_or_default().trim_matches('"').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(field: BomField) -> &'static [String] {
+        use std::sync::OnceLock;
+        static ALIASES: OnceLock<HashMap<BomField, Vec<String>>> = OnceLock::new();
+        let aliases = ALIASES.get_or_init(|| {
+            let list = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+            HashMap::from([
+                (BomField::SupplierName, list(&["supplier", "vendor"])),
+                (BomField::SupplierEmail, list(&["email", "supplier_email"])),
+                (BomField::ContactPerson, list(&["contact", "contact_person"])),
+                (BomField::PartNumber, list(&["part_number", "pn"])),
+                (BomField::CasNumbers, list(&["cas", "cas_number"])),
+            ])
+        });
+        aliases.get(&field).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn found(detection: &ColumnDetection) -> Vec<(BomField, &str, MatchSource)> {
+        detection.detected.iter().map(|d| (d.field, d.column.as_str(), d.source)).collect()
+    }
+
+    #[test]
+    fn test_alias_and_normalized_matches() {
+        let detection = detect(&headers(&["part no.", "part number", "supplier name:", "vendor", "cas #", "notes"]), aliases, None);
+
+        assert_eq!(found(&detection), [
+            (BomField::SupplierName, "vendor", MatchSource::Alias),
+            (BomField::PartNumber, "part number", MatchSource::Normalized),
+            (BomField::CasNumbers, "cas #", MatchSource::Normalized),
+        ]);
+        assert_eq!(detection.detected[1].confidence, NORMALIZED_CONFIDENCE);
+        assert_eq!(detection.table()[0], "supplier_name <- 'vendor' (alias, 0.95)");
+        assert_eq!(detection.unmatched, ["part no.", "supplier name:", "notes"]);
+        assert!(detection.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_partial_and_fuzzy_matches() {
+        let detection = detect(&headers(&["suplier", "supplier (primary) email", "part_numbr"]), aliases, None);
+
+        assert_eq!(found(&detection), [
+            (BomField::SupplierName, "suplier", MatchSource::Fuzzy),
+            (BomField::SupplierEmail, "supplier (primary) email", MatchSource::Partial),
+            (BomField::PartNumber, "part_numbr", MatchSource::Fuzzy),
+        ]);
+        assert_eq!(detection.detected[0].confidence, FUZZY_MAX_CONFIDENCE * 0.875);
+    }
+
+    #[test]
+    fn test_ambiguous_matches_are_reported() {
+        let detection = detect(&headers(&["supplier contact"]), aliases, None);
+
+        assert_eq!(found(&detection), [(BomField::SupplierName, "supplier contact", MatchSource::Partial)]);
+        assert_eq!(detection.warnings, ["Column 'supplier contact' could hold supplier_name or contact_person; reading it as supplier_name"]);
+
+        let detection = detect(&headers(&["primary supplier email", "backup supplier email"]), aliases, None);
+        assert_eq!(detection.columns(BomField::SupplierEmail), ["primary supplier email"]);
+        assert_eq!(detection.warnings[0], "supplier_email could be read from 'primary supplier email' or 'backup supplier email'; reading 'primary supplier email'");
+    }
+
+    #[test]
+    fn test_mapping_overrides_aliases() {
+        let mapping = ColumnMapping::new()
+            .map(BomField::SupplierName, " Lieferant ")
+            .map(BomField::SupplierEmail, "E-Mail Adresse");
+        let detection = detect(&headers(&["lieferant", "supplier", "pn"]), aliases, Some(&mapping));
+
+        assert_eq!(detection.columns(BomField::SupplierName), ["lieferant"]);
+        assert_eq!(detection.detected[0].confidence, 1.0);
+        assert_eq!(detection.detected[0].source, MatchSource::Explicit);
+        assert_eq!(detection.columns(BomField::PartNumber), ["pn"]);
+        assert_eq!(detection.warnings, ["Column mapping reads supplier_email from 'e-mail adresse', which the file does not have"]);
+    }
+}
@@ -1,14 +1,22 @@
 //! BOM (Bill of Materials) Processing Module
 //! 
 //! Multi-format parser for extracting suppliers and components from BOM files.
-//! Supports CSV, Excel (XLSX/XLS), and XML formats.
+//! Supports CSV, Excel (XLSX/XLS), XML and IPC-2581 formats.
 //! 
 //! Requirements: 1.1, 1.2, 1.3, 1.4, 1.5
 
 pub mod parser;
+pub mod ipc2581;
+pub mod workbook;
+pub mod mapping;
 pub mod extractor;
+pub mod dedup;
 pub mod validator;
 
 pub use parser::{BomParser, BomFormat, ParsedBom};
+pub use workbook::{SheetSelection, SheetSummary};
+pub use mapping::{ColumnDetection, DetectedColumn, MatchSource};
+pub use elementa_models::{BomField, ColumnMapping};
 pub use extractor::{SupplierExtractor, ExtractedSupplier};
+pub use dedup::{SupplierMatcher, SupplierMatchReview};
 pub use validator::{BomValidator, ValidationResult};
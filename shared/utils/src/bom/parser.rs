@@ -1,17 +1,30 @@
 //! BOM File Parser
 //! 
 //! Multi-format parser supporting CSV, Excel, and XML bill of materials files.
+//! IPC-2581 design files get their own parser (see `ipc2581`), chosen when
+//! an XML file's root element is `IPC-2581`. Excel workbooks are read
+//! sheet by sheet and joined on part number (see `workbook`). Each field is
+//! read from the column a client's `ColumnMapping` names or, failing that,
+//! the one detected from the header aliases (see `mapping`).
 
 use anyhow::{Context, Result};
 use std::path::Path;
 use uuid::Uuid;
 
+use elementa_models::{BomField, ColumnMapping};
+
+use super::ipc2581;
+use super::mapping::{self, ColumnDetection, DetectedColumn};
+use super::workbook::{self, SheetSelection, SheetSummary};
+
 /// Supported BOM file formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BomFormat {
     Csv,
     Excel,  // XLSX/XLS
     Xml,
+    /// IPC-2581 electronics design file
+    Ipc2581,
 }
 
 impl BomFormat {
@@ -22,6 +35,7 @@ impl BomFormat {
             "csv" => Some(Self::Csv),
             "xlsx" | "xls" => Some(Self::Excel),
             "xml" => Some(Self::Xml),
+            "cvg" => Some(Self::Ipc2581),
             _ => None,
         }
     }
@@ -48,6 +62,8 @@ pub struct BomRow {
     pub part_number: Option<String>,
     pub description: Option<String>,
     pub material_type: Option<String>,
+    pub quantity: Option<u32>,
+    pub reference_designators: Vec<String>,
     pub cas_numbers: Vec<String>,
     pub raw_data: std::collections::HashMap<String, String>,
 }
@@ -60,6 +76,8 @@ pub struct ParsedBom {
     pub format: BomFormat,
     pub rows: Vec<BomRow>,
     pub column_headers: Vec<String>,
+    /// Column each field was read from
+    pub column_mapping: Vec<DetectedColumn>,
     pub total_rows: usize,
     pub parse_warnings: Vec<String>,
 }
@@ -73,7 +91,11 @@ pub struct BomParser {
     part_number_columns: Vec<String>,
     description_columns: Vec<String>,
     material_columns: Vec<String>,
+    quantity_columns: Vec<String>,
+    reference_designator_columns: Vec<String>,
     cas_columns: Vec<String>,
+    /// Which sheets of an Excel workbook to read
+    sheets: SheetSelection,
 }
 
 impl Default for BomParser {
@@ -115,12 +137,23 @@ impl Default for BomParser {
                 "material_type".to_string(),
                 "material_class".to_string(),
             ],
+            quantity_columns: vec![
+                "quantity".to_string(),
+                "qty".to_string(),
+            ],
+            reference_designator_columns: vec![
+                "reference_designators".to_string(),
+                "refdes".to_string(),
+                "designator".to_string(),
+                "designators".to_string(),
+            ],
             cas_columns: vec![
                 "cas".to_string(),
                 "cas_number".to_string(),
                 "cas_numbers".to_string(),
                 "chemical_cas".to_string(),
             ],
+            sheets: SheetSelection::default(),
         }
     }
 }
@@ -130,20 +163,53 @@ impl BomParser {
         Self::default()
     }
     
-    /// Parse BOM file from bytes
-    pub fn parse_bytes(&self, filename: &str, data: &[u8], format: Option<BomFormat>) -> Result<ParsedBom> {
+    /// Read the given sheets of Excel workbooks instead of all of them
+    pub fn with_sheets(mut self, sheets: SheetSelection) -> Self {
+        self.sheets = sheets;
+        self
+    }
+    
+    /// List the sheets of an Excel workbook
+    pub fn list_sheets(&self, data: &[u8]) -> Result<Vec<SheetSummary>> {
+        Ok(workbook::read_sheets(data)?.iter().map(|sheet| sheet.summary()).collect())
+    }
+    
+    /// Columns of a BOM with `headers` that each field would be read from
+    pub fn detect_columns(&self, headers: &[String], mapping: Option<&ColumnMapping>) -> ColumnDetection {
+        mapping::detect(headers, |field| self.aliases(field), mapping)
+    }
+    
+    /// Header aliases of a field
+    fn aliases(&self, field: BomField) -> &[String] {
+        match field {
+            BomField::SupplierName => &self.supplier_name_columns,
+            BomField::SupplierEmail => &self.supplier_email_columns,
+            BomField::ContactPerson => &self.contact_columns,
+            BomField::PartNumber => &self.part_number_columns,
+            BomField::Description => &self.description_columns,
+            BomField::MaterialType => &self.material_columns,
+            BomField::Quantity => &self.quantity_columns,
+            BomField::ReferenceDesignators => &self.reference_designator_columns,
+            BomField::CasNumbers => &self.cas_columns,
+        }
+    }
+    
+    /// Parse BOM file from bytes, reading fields from the columns `mapping` names
+    pub fn parse_bytes(&self, filename: &str, data: &[u8], format: Option<BomFormat>, mapping: Option<&ColumnMapping>) -> Result<ParsedBom> {
         let format = format.or_else(|| BomFormat::from_extension(Path::new(filename)))
             .context("Could not determine file format")?;
         
         match format {
-            BomFormat::Csv => self.parse_csv(filename, data),
-            BomFormat::Excel => self.parse_excel(filename, data),
-            BomFormat::Xml => self.parse_xml(filename, data),
+            BomFormat::Csv => self.parse_csv(filename, data, mapping),
+            BomFormat::Excel => self.parse_excel(filename, data, mapping),
+            BomFormat::Xml if ipc2581::is_ipc2581(data) => ipc2581::parse(filename, data),
+            BomFormat::Xml => self.parse_xml(filename, data, mapping),
+            BomFormat::Ipc2581 => ipc2581::parse(filename, data),
         }
     }
     
     /// Parse CSV format
-    fn parse_csv(&self, filename: &str, data: &[u8]) -> Result<ParsedBom> {
+    fn parse_csv(&self, filename: &str, data: &[u8], mapping: Option<&ColumnMapping>) -> Result<ParsedBom> {
         let mut reader = csv::ReaderBuilder::new()
             .flexible(true)
             .from_reader(data);
@@ -154,8 +220,9 @@ impl BomParser {
             .map(|h| h.to_lowercase().trim().to_string())
             .collect();
         
+        let columns = self.detect_columns(&headers, mapping);
         let mut rows = Vec::new();
-        let mut warnings = Vec::new();
+        let mut warnings = columns.warnings.clone();
         
         for (idx, result) in reader.records().enumerate() {
             match result {
@@ -167,7 +234,7 @@ impl BomParser {
                         })
                         .collect();
                     
-                    let row = self.map_row(idx + 2, &headers, &raw_data);
+                    let row = self.map_row(idx + 2, &columns, &raw_data);
                     rows.push(row);
                 }
                 Err(e) => {
@@ -183,70 +250,49 @@ impl BomParser {
             total_rows: rows.len(),
             rows,
             column_headers: headers,
+            column_mapping: columns.detected,
             parse_warnings: warnings,
         })
     }
     
-    /// Parse Excel format
-    fn parse_excel(&self, filename: &str, data: &[u8]) -> Result<ParsedBom> {
-        use calamine::{Reader, open_workbook_from_rs, Xlsx, DataType};
-        
-        let cursor = std::io::Cursor::new(data);
-        let mut workbook: Xlsx<_> = open_workbook_from_rs(cursor)
-            .context("Failed to open Excel workbook")?;
-        
-        let sheet_name = workbook.sheet_names()
-            .first()
-            .cloned()
-            .context("No sheets found in workbook")?;
-        
-        let range = workbook.worksheet_range(&sheet_name)
-            .context("Failed to read worksheet")??;
-        
-        let mut rows_iter = range.rows();
+    /// Parse Excel format, joining the selected sheets on part number
+    fn parse_excel(&self, filename: &str, data: &[u8], mapping: Option<&ColumnMapping>) -> Result<ParsedBom> {
+        let sheets = workbook::read_sheets(data)?;
+        self.merge_sheets(filename, sheets, mapping)
+    }
+    
+    fn merge_sheets(&self, filename: &str, sheets: Vec<workbook::Sheet>, mapping: Option<&ColumnMapping>) -> Result<ParsedBom> {
+        let (mut primary, others) = workbook::select(sheets, &self.sheets, &self.part_number_columns)?;
+        let mut warnings = workbook::join(&mut primary, &others, &self.part_number_columns, &self.cas_columns);
         
-        // First row is headers
-        let headers: Vec<String> = rows_iter.next()
-            .context("Empty worksheet")?
-            .iter()
-            .map(|cell: &DataType| cell.to_string().to_lowercase().trim().to_string())
+        let columns = self.detect_columns(&primary.headers, mapping);
+        warnings.extend(columns.warnings.iter().cloned());
+        let rows: Vec<BomRow> = primary.rows.iter()
+            .enumerate()
+            .map(|(idx, raw_data)| self.map_row(idx + 2, &columns, raw_data))
             .collect();
         
-        let mut rows = Vec::new();
-        let warnings = Vec::new();
-        
-        for (idx, row) in rows_iter.enumerate() {
-            let raw_data: std::collections::HashMap<String, String> = headers.iter()
-                .enumerate()
-                .filter_map(|(i, h): (usize, &String)| {
-                    row.get(i).map(|v: &DataType| (h.clone(), v.to_string()))
-                })
-                .collect();
-            
-            let parsed_row = self.map_row(idx + 2, &headers, &raw_data);
-            rows.push(parsed_row);
-        }
-        
         Ok(ParsedBom {
             id: Uuid::new_v4(),
             filename: filename.to_string(),
             format: BomFormat::Excel,
             total_rows: rows.len(),
             rows,
-            column_headers: headers,
+            column_headers: primary.headers,
+            column_mapping: columns.detected,
             parse_warnings: warnings,
         })
     }
     
     /// Parse XML format
-    fn parse_xml(&self, filename: &str, data: &[u8]) -> Result<ParsedBom> {
+    fn parse_xml(&self, filename: &str, data: &[u8], mapping: Option<&ColumnMapping>) -> Result<ParsedBom> {
         use quick_xml::Reader;
         use quick_xml::events::Event;
         
         let mut reader = Reader::from_reader(data);
         reader.trim_text(true);
         
-        let mut rows = Vec::new();
+        let mut raw_rows = Vec::new();
         let mut warnings = Vec::new();
         let mut current_row: Option<std::collections::HashMap<String, String>> = None;
         let mut current_element = String::new();
@@ -278,9 +324,7 @@ impl BomParser {
                     
                     if matches!(tag_name.as_str(), "row" | "item" | "component" | "entry" | "record") {
                         if let Some(raw_data) = current_row.take() {
-                            let headers: Vec<String> = raw_data.keys().cloned().collect();
-                            let parsed_row = self.map_row(row_number, &headers, &raw_data);
-                            rows.push(parsed_row);
+                            raw_rows.push((row_number, raw_data));
                         }
                     }
                     current_element.clear();
@@ -295,11 +339,19 @@ impl BomParser {
             buf.clear();
         }
         
-        let headers = if let Some(first) = rows.first() {
-            first.raw_data.keys().cloned().collect()
-        } else {
-            Vec::new()
-        };
+        // Elements of every row, in the order they first appear
+        let mut headers: Vec<String> = Vec::new();
+        for (_, raw_data) in &raw_rows {
+            let mut keys: Vec<&String> = raw_data.keys().filter(|k| !headers.contains(k)).collect();
+            keys.sort();
+            headers.extend(keys.into_iter().cloned());
+        }
+        
+        let columns = self.detect_columns(&headers, mapping);
+        warnings.extend(columns.warnings.iter().cloned());
+        let rows: Vec<BomRow> = raw_rows.iter()
+            .map(|(row_number, raw_data)| self.map_row(*row_number, &columns, raw_data))
+            .collect();
         
         Ok(ParsedBom {
             id: Uuid::new_v4(),
@@ -308,21 +360,31 @@ impl BomParser {
             total_rows: rows.len(),
             rows,
             column_headers: headers,
+            column_mapping: columns.detected,
             parse_warnings: warnings,
         })
     }
     
     /// Map raw data to structured BomRow
-    fn map_row(&self, row_number: usize, _headers: &[String], raw_data: &std::collections::HashMap<String, String>) -> BomRow {
+    fn map_row(&self, row_number: usize, columns: &ColumnDetection, raw_data: &std::collections::HashMap<String, String>) -> BomRow {
         BomRow {
             row_number,
-            supplier_name: self.find_value(&self.supplier_name_columns, raw_data),
-            supplier_email: self.find_value(&self.supplier_email_columns, raw_data),
-            contact_person: self.find_value(&self.contact_columns, raw_data),
-            part_number: self.find_value(&self.part_number_columns, raw_data),
-            description: self.find_value(&self.description_columns, raw_data),
-            material_type: self.find_value(&self.material_columns, raw_data),
-            cas_numbers: self.extract_cas_numbers(raw_data),
+            supplier_name: self.find_value(columns.columns(BomField::SupplierName), raw_data),
+            supplier_email: self.find_value(columns.columns(BomField::SupplierEmail), raw_data),
+            contact_person: self.find_value(columns.columns(BomField::ContactPerson), raw_data),
+            part_number: self.find_value(columns.columns(BomField::PartNumber), raw_data),
+            description: self.find_value(columns.columns(BomField::Description), raw_data),
+            material_type: self.find_value(columns.columns(BomField::MaterialType), raw_data),
+            quantity: self.find_value(columns.columns(BomField::Quantity), raw_data).and_then(|q| q.parse().ok()),
+            reference_designators: self.find_value(columns.columns(BomField::ReferenceDesignators), raw_data)
+                .map(|refs| {
+                    refs.split(&[',', ';', ' '][..])
+                        .filter(|r| !r.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            cas_numbers: self.extract_cas_numbers(columns.columns(BomField::CasNumbers), raw_data),
             raw_data: raw_data.clone(),
         }
     }
@@ -341,10 +403,10 @@ impl BomParser {
     }
     
     /// Extract and normalize CAS numbers from row
-    fn extract_cas_numbers(&self, data: &std::collections::HashMap<String, String>) -> Vec<String> {
+    fn extract_cas_numbers(&self, columns: &[String], data: &std::collections::HashMap<String, String>) -> Vec<String> {
         let mut cas_numbers = Vec::new();
         
-        for candidate in &self.cas_columns {
+        for candidate in columns {
             if let Some(value) = data.get(candidate) {
                 // Split by common delimiters and normalize
                 for cas in value.split(&[',', ';', '|', '\n'][..]) {
@@ -379,6 +441,7 @@ impl BomParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bom::MatchSource;
     use proptest::prelude::*;
     
     #[test]
@@ -386,6 +449,7 @@ mod tests {
         assert_eq!(BomFormat::from_extension(Path::new("test.csv")), Some(BomFormat::Csv));
         assert_eq!(BomFormat::from_extension(Path::new("test.xlsx")), Some(BomFormat::Excel));
         assert_eq!(BomFormat::from_extension(Path::new("test.xml")), Some(BomFormat::Xml));
+        assert_eq!(BomFormat::from_extension(Path::new("board.cvg")), Some(BomFormat::Ipc2581));
         assert_eq!(BomFormat::from_extension(Path::new("test.txt")), None);
     }
     
@@ -394,7 +458,7 @@ mod tests {
         let csv_data = b"supplier,part_number,description,cas_number\nAcme Corp,PN-001,Widget,7732-18-5\nGlobex,PN-002,Gadget,7647-14-5";
         
         let parser = BomParser::new();
-        let result = parser.parse_csv("test.csv", csv_data).unwrap();
+        let result = parser.parse_csv("test.csv", csv_data, None).unwrap();
         
         assert_eq!(result.total_rows, 2);
         assert_eq!(result.rows[0].supplier_name, Some("Acme Corp".to_string()));
@@ -402,6 +466,83 @@ mod tests {
         assert_eq!(result.rows[0].cas_numbers, vec!["7732-18-5".to_string()]);
     }
     
+    #[test]
+    fn test_column_mapping() {
+        let csv_data = b"Lieferant,Part Number,Mfr Name,cas #\nAcme GmbH,PN-001,Acme,7732-18-5";
+        let mapping = ColumnMapping::new().map(BomField::SupplierName, "Lieferant");
+        
+        let parser = BomParser::new();
+        let result = parser.parse_bytes("test.csv", csv_data, None, Some(&mapping)).unwrap();
+        
+        assert_eq!(result.rows[0].supplier_name, Some("Acme GmbH".to_string()));
+        assert_eq!(result.rows[0].part_number, Some("PN-001".to_string()));
+        assert_eq!(result.rows[0].cas_numbers, ["7732-18-5"]);
+        
+        let detected: Vec<(BomField, &str, MatchSource)> = result.column_mapping.iter()
+            .map(|c| (c.field, c.column.as_str(), c.source))
+            .collect();
+        assert_eq!(detected, [
+            (BomField::SupplierName, "lieferant", MatchSource::Explicit),
+            (BomField::PartNumber, "part number", MatchSource::Normalized),
+            (BomField::CasNumbers, "cas #", MatchSource::Normalized),
+        ]);
+        
+        // Without the mapping the supplier column isn't recognised
+        let result = parser.parse_bytes("test.csv", csv_data, None, None).unwrap();
+        assert_eq!(result.rows[0].supplier_name, None);
+    }
+    
+    #[test]
+    fn test_quantities_and_designators() {
+        let csv_data = b"supplier,part_number,qty,refdes\nAcme Corp,PN-001,3,\"R1, R2,R3\"";
+
+        let parser = BomParser::new();
+        let result = parser.parse_bytes("test.csv", csv_data, None, None).unwrap();
+
+        assert_eq!(result.rows[0].quantity, Some(3));
+        assert_eq!(result.rows[0].reference_designators, ["R1", "R2", "R3"]);
+    }
+    
+    #[test]
+    fn test_ipc2581_xml_uses_its_own_parser() {
+        let xml = br#"<IPC-2581 revision="C"><Bom name="B"><BomItem OEMDesignNumberRef="PN-001" quantity="1"><RefDes name="U1"/></BomItem></Bom></IPC-2581>"#;
+        
+        let parser = BomParser::new();
+        let result = parser.parse_bytes("board.xml", xml, None, None).unwrap();
+        
+        assert_eq!(result.format, BomFormat::Ipc2581);
+        assert_eq!(result.rows[0].part_number, Some("PN-001".to_string()));
+        assert_eq!(result.rows[0].reference_designators, ["U1"]);
+    }
+    
+    #[test]
+    fn test_excel_sheets_joined_on_part_number() {
+        let sheet = |name: &str, rows: &[&[&str]]| {
+            workbook::Sheet::from_cells(name, rows.iter().map(|r| r.iter().map(|c| c.to_string()).collect()).collect())
+        };
+        let sheets = vec![
+            sheet("Suppliers", &[&["part_number", "supplier", "supplier_email"], &["PN-001", "Acme Corp", "qa@acme.example"]]),
+            sheet("BOM", &[&["part_number", "description", "qty"], &["PN-001", "Widget", "4"], &["PN-002", "Gadget", "1"]]),
+            sheet("Chemicals", &[&["part_number", "cas_number"], &["PN-001", "7732-18-5"], &["PN-001", "7647-14-5"]]),
+        ];
+        
+        let parser = BomParser::new();
+        let result = parser.merge_sheets("bom.xlsx", sheets.clone(), None).unwrap();
+        
+        assert_eq!(result.total_rows, 2);
+        assert_eq!(result.rows[0].supplier_name, Some("Acme Corp".to_string()));
+        assert_eq!(result.rows[0].supplier_email, Some("qa@acme.example".to_string()));
+        assert_eq!(result.rows[0].quantity, Some(4));
+        assert_eq!(result.rows[0].cas_numbers, ["7732-18-5", "7647-14-5"]);
+        assert_eq!(result.rows[1].supplier_name, None);
+        assert!(result.parse_warnings.is_empty());
+        
+        let parser = BomParser::new().with_sheets(SheetSelection::sheets(["bom", "suppliers"]));
+        let result = parser.merge_sheets("bom.xlsx", sheets, None).unwrap();
+        assert_eq!(result.rows[0].supplier_name, Some("Acme Corp".to_string()));
+        assert!(result.rows[0].cas_numbers.is_empty());
+    }
+    
     proptest! {
         /// Property 1: BOM Processing Completeness
         /// For any valid BOM, processed + flagged = total entries
@@ -412,7 +553,7 @@ mod tests {
         ) {
             let csv = format!("supplier,part_number\n{},{}", supplier, part_no);
             let parser = BomParser::new();
-            let result = parser.parse_csv("test.csv", csv.as_bytes()).unwrap();
+            let result = parser.parse_csv("test.csv", csv.as_bytes(), None).unwrap();
             
             // Total parsed rows should equal input rows
             prop_assert_eq!(result.total_rows, 1);
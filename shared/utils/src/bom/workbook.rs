@@ -0,0 +1,315 @@
+//! Excel Workbook Sheets
+//!
+//! Reads every sheet of a BOM workbook and joins them into one table. BOMs
+//! exported from PLM systems often split their data across sheets, such as
+//! "BOM", "Suppliers" and "Chemicals". One sheet is the primary sheet,
+//! whose rows become the BOM's rows, and each other sheet with a part
+//! number column is joined onto it by part number. A primary row keeps its
+//! own values, gaps are filled from the first matching row of another
+//! sheet, and list columns such as CAS numbers collect the values of every
+//! matching row.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+/// Which sheets of a workbook to read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SheetSelection {
+    /// Sheets to read, by name ignoring case; every sheet when empty
+    pub sheets: Vec<String>,
+    /// Sheet whose rows become the BOM's rows; otherwise the sheet named
+    /// "BOM", else the selected sheet with a part number column and the most rows
+    pub primary: Option<String>,
+}
+
+impl SheetSelection {
+    /// Read only `sheets`
+    pub fn sheets<S: Into<String>>(sheets: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            sheets: sheets.into_iter().map(Into::into).collect(),
+            primary: None,
+        }
+    }
+
+    pub fn with_primary(mut self, sheet: impl Into<String>) -> Self {
+        self.primary = Some(sheet.into());
+        self
+    }
+
+    fn includes(&self, name: &str) -> bool {
+        self.sheets.is_empty() || self.sheets.iter().any(|s| s.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A sheet as listed for selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetSummary {
+    pub name: String,
+    /// Rows below the header row
+    pub rows: usize,
+    pub headers: Vec<String>,
+}
+
+/// A sheet's rows, keyed by lowercased header
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<HashMap<String, String>>,
+}
+
+impl Sheet {
+    /// A sheet from its cells, the first row holding the headers
+    pub fn from_cells(name: &str, cells: Vec<Vec<String>>) -> Self {
+        let mut cells = cells.into_iter();
+        let headers: Vec<String> = cells.next()
+            .unwrap_or_default()
+            .iter()
+            .map(|h| h.to_lowercase().trim().to_string())
+            .collect();
+
+        let rows = cells
+            .map(|row| {
+                headers.iter()
+                    .zip(row)
+                    .filter(|(header, _)| !header.is_empty())
+                    .map(|(header, value)| (header.clone(), value))
+                    .collect()
+            })
+            .collect();
+
+        Self { name: name.to_string(), headers, rows }
+    }
+
+    pub fn summary(&self) -> SheetSummary {
+        SheetSummary {
+            name: self.name.clone(),
+            rows: self.rows.len(),
+            headers: self.headers.clone(),
+        }
+    }
+
+    /// The first of `candidates` among the headers
+    fn key_column(&self, candidates: &[String]) -> Option<String> {
+        candidates.iter().find(|c| self.headers.contains(c)).cloned()
+    }
+}
+
+/// Every sheet of an XLSX workbook, with its cells as text
+pub fn read_sheets(data: &[u8]) -> Result<Vec<Sheet>> {
+    use calamine::{open_workbook_from_rs, DataType, Reader, Xlsx};
+
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(std::io::Cursor::new(data))
+        .context("Failed to open Excel workbook")?;
+
+    let names = workbook.sheet_names().to_vec();
+    if names.is_empty() {
+        bail!("No sheets found in workbook");
+    }
+
+    let mut sheets = Vec::with_capacity(names.len());
+    for name in names {
+        let range = workbook.worksheet_range(&name)
+            .with_context(|| format!("Sheet '{}' not found", name))?
+            .with_context(|| format!("Failed to read sheet '{}'", name))?;
+        let cells = range.rows()
+            .map(|row| row.iter().map(|cell: &DataType| cell.to_string()).collect())
+            .collect();
+        sheets.push(Sheet::from_cells(&name, cells));
+    }
+    Ok(sheets)
+}
+
+/// The selected sheets and which of them is primary
+pub fn select(sheets: Vec<Sheet>, selection: &SheetSelection, part_number_columns: &[String]) -> Result<(Sheet, Vec<Sheet>)> {
+    let available = || sheets.iter().map(|s| s.name.as_str()).collect::<Vec<_>>().join(", ");
+    for wanted in selection.sheets.iter().chain(&selection.primary) {
+        if !sheets.iter().any(|s| s.name.eq_ignore_ascii_case(wanted)) {
+            bail!("Workbook has no sheet named '{}' (sheets: {})", wanted, available());
+        }
+    }
+
+    let mut selected: Vec<Sheet> = sheets.into_iter()
+        .filter(|s| selection.includes(&s.name) || selection.primary.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(&s.name)))
+        .filter(|s| !s.headers.is_empty())
+        .collect();
+    if selected.is_empty() {
+        bail!("The selected sheets are empty");
+    }
+
+    let primary = match &selection.primary {
+        Some(name) => selected.iter().position(|s| s.name.eq_ignore_ascii_case(name)),
+        None => selected.iter().position(|s| s.name.eq_ignore_ascii_case("bom"))
+            .or_else(|| {
+                selected.iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.key_column(part_number_columns).is_some())
+                    .max_by_key(|(idx, s)| (s.rows.len(), std::cmp::Reverse(*idx)))
+                    .map(|(idx, _)| idx)
+            }),
+    }
+    .unwrap_or(0);
+
+    let primary = selected.remove(primary);
+    Ok((primary, selected))
+}
+
+/// Join `others` onto the primary sheet's rows by part number, returning
+/// warnings about the rows and sheets left out
+pub fn join(
+    primary: &mut Sheet,
+    others: &[Sheet],
+    part_number_columns: &[String],
+    list_columns: &[String],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if others.is_empty() {
+        return warnings;
+    }
+    let Some(primary_key) = primary.key_column(part_number_columns) else {
+        warnings.push(format!("Sheet '{}' has no part number column; other sheets were not joined", primary.name));
+        return warnings;
+    };
+
+    let mut by_part: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, row) in primary.rows.iter().enumerate() {
+        if let Some(part) = row.get(&primary_key).map(|p| normalize_part_number(p)).filter(|p| !p.is_empty()) {
+            by_part.entry(part).or_default().push(idx);
+        }
+    }
+
+    for sheet in others {
+        let Some(key) = sheet.key_column(part_number_columns) else {
+            warnings.push(format!("Sheet '{}' has no part number column and was not joined", sheet.name));
+            continue;
+        };
+
+        let mut unmatched = Vec::new();
+        for row in &sheet.rows {
+            let part = row.get(&key).map(|p| normalize_part_number(p)).unwrap_or_default();
+            if part.is_empty() {
+                continue;
+            }
+            let Some(targets) = by_part.get(&part) else {
+                unmatched.push(part);
+                continue;
+            };
+
+            for &target in targets {
+                let joined = &mut primary.rows[target];
+                for (column, value) in row {
+                    if column == &key || value.trim().is_empty() {
+                        continue;
+                    }
+                    let current = joined.entry(column.clone()).or_default();
+                    if current.trim().is_empty() {
+                        *current = value.clone();
+                    } else if list_columns.contains(column) && !current.split(';').any(|v| v.trim() == value.trim()) {
+                        current.push_str("; ");
+                        current.push_str(value.trim());
+                    }
+                }
+            }
+        }
+
+        for header in &sheet.headers {
+            if header != &key && !header.is_empty() && !primary.headers.contains(header) {
+                primary.headers.push(header.clone());
+            }
+        }
+
+        if !unmatched.is_empty() {
+            unmatched.dedup();
+            warnings.push(format!(
+                "Sheet '{}': {} rows reference part numbers not on sheet '{}' ({})",
+                sheet.name,
+                unmatched.len(),
+                primary.name,
+                unmatched.iter().take(5).cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+
+    warnings
+}
+
+fn normalize_part_number(part: &str) -> String {
+    part.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter().map(|r| r.iter().map(|c| c.to_string()).collect()).collect()
+    }
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn workbook() -> Vec<Sheet> {
+        vec![
+            Sheet::from_cells("Notes", cells(&[&["Exported from PLM"]])),
+            Sheet::from_cells("Chemicals", cells(&[
+                &["Part Number", "CAS Number", "Substance"],
+                &["pn-001", "7732-18-5", "Water"],
+                &["PN-001", "7647-14-5", "Sodium chloride"],
+                &["PN-404", "50-00-0", "Formaldehyde"],
+            ])),
+            Sheet::from_cells("BOM", cells(&[
+                &["Part Number", "Description", "Supplier"],
+                &["PN-001", "Widget", ""],
+                &["PN-002", "Gadget", "Globex"],
+            ])),
+            Sheet::from_cells("Suppliers", cells(&[
+                &["part_number", "Supplier", "Supplier Email"],
+                &["PN-001", "Acme Corp", "qa@acme.example"],
+                &["PN-002", "Initech", "qa@initech.example"],
+            ])),
+        ]
+    }
+
+    #[test]
+    fn test_primary_sheet_detection() {
+        let keys = columns(&["part number", "part_number"]);
+        let (primary, others) = select(workbook(), &SheetSelection::default(), &keys).unwrap();
+        assert_eq!(primary.name, "BOM");
+        assert_eq!(others.len(), 3);
+
+        let selection = SheetSelection::sheets(["chemicals", "suppliers"]);
+        let (primary, others) = select(workbook(), &selection, &keys).unwrap();
+        assert_eq!(primary.name, "Chemicals");
+        assert_eq!(others.len(), 1);
+
+        let selection = SheetSelection::default().with_primary("Suppliers");
+        assert_eq!(select(workbook(), &selection, &keys).unwrap().0.name, "Suppliers");
+
+        let error = select(workbook(), &SheetSelection::sheets(["Parts"]), &keys).unwrap_err();
+        assert!(error.to_string().contains("Chemicals, BOM, Suppliers"));
+    }
+
+    #[test]
+    fn test_join_on_part_number() {
+        let keys = columns(&["part number", "part_number"]);
+        let (mut primary, others) = select(workbook(), &SheetSelection::default(), &keys).unwrap();
+        let warnings = join(&mut primary, &others, &keys, &columns(&["cas number"]));
+
+        let widget = &primary.rows[0];
+        assert_eq!(widget["supplier"], "Acme Corp");
+        assert_eq!(widget["supplier email"], "qa@acme.example");
+        assert_eq!(widget["cas number"], "7732-18-5; 7647-14-5");
+        assert_eq!(widget["substance"], "Water");
+
+        // The primary sheet's own values win
+        assert_eq!(primary.rows[1]["supplier"], "Globex");
+        assert_eq!(primary.rows[1]["supplier email"], "qa@initech.example");
+
+        assert!(primary.headers.contains(&"cas number".to_string()));
+        assert_eq!(warnings, [
+            "Sheet 'Notes' has no part number column and was not joined",
+            "Sheet 'Chemicals': 1 rows reference part numbers not on sheet 'BOM' (PN-404)",
+        ]);
+    }
+}